@@ -1,7 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Formatter;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
+use tokio::sync::mpsc;
+
+use crate::client::Shared;
 use crate::currency::Currency;
+use crate::payload::{Pnl, PositionSummary};
+use crate::quantity::Price;
 
 #[derive(Debug, Clone, PartialOrd, PartialEq)]
 /// Represents a specific account value
@@ -164,6 +173,295 @@ pub enum Attribute {
     WhatIfPMEnabled(bool),
 }
 
+impl Attribute {
+    #[inline]
+    #[must_use]
+    /// Return the [`ValueKey`] and the [`ValueUpdate`] described by this attribute, if it holds
+    /// the account-wide total of one of the watchable account values. The value is converted
+    /// from its shortest decimal representation, which is the decimal that IBKR sent.
+    pub fn value_update(&self) -> Option<(ValueKey, ValueUpdate)> {
+        let (key, value, denomination) = match *self {
+            Self::NetLiquidation(Segment::Total(value), denomination) => {
+                (ValueKey::NetLiquidation, value, denomination)
+            }
+            Self::BuyingPower(value, denomination) => (ValueKey::BuyingPower, value, denomination),
+            Self::ExcessLiquidity(Segment::Total(value), denomination) => {
+                (ValueKey::ExcessLiquidity, value, denomination)
+            }
+            Self::MaintenanceMarginReq(Segment::Total(value), denomination) => {
+                (ValueKey::MaintenanceMarginReq, value, denomination)
+            }
+            _ => return None,
+        };
+        Some((
+            key,
+            ValueUpdate {
+                value: Price::try_from(value).ok()?,
+                denomination,
+            },
+        ))
+    }
+}
+
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+/// A unique identifier for an IBKR account (ex: DU1234567).
+///
+/// Every request that takes an [`AccountId`] validates it against the client's managed accounts
+/// (see [`crate::client::Client::get_managed_accounts`]) before any message is sent.
+pub struct AccountId(pub String);
+
+impl std::fmt::Display for AccountId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for AccountId {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_owned()))
+    }
+}
+
+impl AsRef<str> for AccountId {
+    fn as_ref(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl std::borrow::Borrow<str> for AccountId {
+    fn borrow(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A single result of a request that was made with a [`FanOut`], which belongs to one of the
+/// client's managed accounts.
+pub enum AccountEvent {
+    /// An update of the account's P&L, from [`crate::client::Client::req_pnl_all_accounts`].
+    Pnl(Pnl),
+    /// An update of the P&L of the account's position, from
+    /// [`crate::client::Client::req_single_position_pnl_all_accounts`].
+    PositionPnl {
+        /// The position's P&L.
+        pnl: Pnl,
+        /// The size of the position.
+        position: f64,
+        /// The market value of the position.
+        market_value: f64,
+    },
+    /// One of the account's positions, from
+    /// [`crate::client::Client::req_positions_all_accounts`].
+    Position(PositionSummary),
+    /// A value from the account's summary, from
+    /// [`crate::client::Client::req_account_summary_all_accounts`].
+    Summary(TagValue),
+    /// Every position or summary value of the account has been delivered.
+    End,
+}
+
+#[derive(Debug)]
+/// A single request made for every one of a client's managed accounts (ex:
+/// [`crate::client::Client::req_pnl_all_accounts`]), whose results are merged into a single
+/// [`futures_core::Stream`] of [`AccountEvent`]s keyed by the [`AccountId`] to which they belong.
+///
+/// The request IDs of the individual requests are handled internally, so the results need not be
+/// matched with their accounts by hand. They are also delivered to the client's wrapper as usual.
+/// Dropping the [`FanOut`] stops the delivery of its events, but it does not cancel its requests.
+pub struct FanOut {
+    req_ids: HashMap<i64, Option<AccountId>>,
+    accounts: Vec<AccountId>,
+    tx: Option<mpsc::UnboundedSender<(AccountId, AccountEvent)>>,
+    rx: mpsc::UnboundedReceiver<(AccountId, AccountEvent)>,
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+/// The sending half of a [`FanOut`], as registered with the client for each of its requests.
+pub(crate) struct FanOutSender {
+    tx: mpsc::UnboundedSender<(AccountId, AccountEvent)>,
+    accounts: Vec<AccountId>,
+}
+
+impl FanOut {
+    #[inline]
+    pub(crate) fn new(accounts: Vec<AccountId>, shared: &Arc<Shared>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            req_ids: HashMap::new(),
+            accounts,
+            tx: Some(tx),
+            rx,
+            shared: Arc::clone(shared),
+        }
+    }
+
+    /// Register one of the requests, which must happen before the request is sent so that none
+    /// of its results is missed.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    /// * `account_number` - The account for which the request is made, or [`None`] if it is made
+    /// for every account at once.
+    pub(crate) fn register(&mut self, req_id: i64, account_number: Option<AccountId>) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        let accounts = match &account_number {
+            Some(account_number) => vec![account_number.clone()],
+            None => self.accounts.clone(),
+        };
+        lock_fan_outs(&self.shared).insert(
+            req_id,
+            FanOutSender {
+                tx: tx.clone(),
+                accounts,
+            },
+        );
+        self.req_ids.insert(req_id, account_number);
+    }
+
+    #[inline]
+    /// Stop registering requests, so that the events end once the client loop stops.
+    pub(crate) fn seal(mut self) -> Self {
+        self.tx = None;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the account associated with a given request ID.
+    ///
+    /// # Arguments
+    /// * `req_id` - The request ID received in a callback.
+    ///
+    /// # Returns
+    /// The [`AccountId`] for which the request was made, if the request belongs to this [`FanOut`]
+    /// and was made for a single account.
+    pub fn account(&self, req_id: i64) -> Option<&AccountId> {
+        self.req_ids.get(&req_id)?.as_ref()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the request ID associated with a given account.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account whose request ID is to be found.
+    ///
+    /// # Returns
+    /// The request ID for the account, if the account belongs to this [`FanOut`].
+    pub fn req_id(&self, account_number: &AccountId) -> Option<i64> {
+        self.req_ids.iter().find_map(|(id, acct)| {
+            acct.as_ref()
+                .map_or(self.accounts.contains(account_number), |acct| {
+                    acct == account_number
+                })
+                .then_some(*id)
+        })
+    }
+
+    #[inline]
+    /// Iterate over each request ID.
+    pub fn req_ids(&self) -> impl Iterator<Item = i64> + '_ {
+        self.req_ids.keys().copied()
+    }
+
+    /// Merge results that were collected by request ID into results keyed by [`AccountId`].
+    /// Results whose request ID does not belong to this [`FanOut`], or whose request was made for
+    /// every account at once, are discarded.
+    ///
+    /// # Arguments
+    /// * `results` - The (request ID, result) pairs received in the callbacks.
+    ///
+    /// # Returns
+    /// The results keyed by the account to which they belong.
+    pub fn merge<T>(&self, results: impl IntoIterator<Item = (i64, T)>) -> HashMap<AccountId, T> {
+        results
+            .into_iter()
+            .filter_map(|(id, res)| self.account(id).map(|acct| (acct.clone(), res)))
+            .collect()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the accounts for which the requests were made.
+    pub fn get_accounts(&self) -> &[AccountId] {
+        &self.accounts
+    }
+
+    #[inline]
+    /// Wait for the next event.
+    ///
+    /// # Returns
+    /// The next event along with the account to which it belongs, or [`None`] if the client loop
+    /// stopped.
+    pub async fn recv(&mut self) -> Option<(AccountId, AccountEvent)> {
+        self.rx.recv().await
+    }
+
+    /// Wait until every account has delivered its results, and merge them by account.
+    ///
+    /// An account's results are complete once its [`AccountEvent::End`] is received or, for P&L
+    /// subscriptions, which never end, once its first update is received.
+    ///
+    /// # Arguments
+    /// * `timeout` - The maximum amount of time to wait for the results.
+    ///
+    /// # Errors
+    /// Returns [`crate::error::Error::Timeout`] if an account's results are not complete before
+    /// the timeout elapses, and [`crate::error::Error::Disconnected`] if the client loop stops.
+    ///
+    /// # Returns
+    /// The events of each account, in the order in which they were received.
+    pub async fn recv_merged(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> Result<HashMap<AccountId, Vec<AccountEvent>>, crate::error::Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut merged = HashMap::<AccountId, Vec<AccountEvent>>::new();
+        let mut pending = self
+            .accounts
+            .iter()
+            .cloned()
+            .collect::<std::collections::HashSet<_>>();
+        while !pending.is_empty() {
+            let (account_number, event) = tokio::time::timeout_at(deadline, self.rx.recv())
+                .await
+                .map_err(|_| crate::error::Error::Timeout)?
+                .ok_or(crate::error::Error::Disconnected)?;
+            if matches!(
+                event,
+                AccountEvent::End | AccountEvent::Pnl(_) | AccountEvent::PositionPnl { .. }
+            ) {
+                pending.remove(&account_number);
+            }
+            merged.entry(account_number).or_default().push(event);
+        }
+        Ok(merged)
+    }
+}
+
+impl futures_core::Stream for FanOut {
+    type Item = (AccountId, AccountEvent);
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for FanOut {
+    fn drop(&mut self) {
+        let mut fan_outs = lock_fan_outs(&self.shared);
+        for req_id in self.req_ids.keys() {
+            fan_outs.remove(req_id);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
 /// The particular account groups managed by a given client.
 pub enum Group {
@@ -206,6 +504,29 @@ impl std::str::FromStr for Denomination {
     }
 }
 
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+/// The account values whose changes can be watched with
+/// [`crate::client::Client::watch_account_value`].
+pub enum ValueKey {
+    /// The account's net liquidation value ("NetLiquidation").
+    NetLiquidation,
+    /// The account's buying power ("BuyingPower").
+    BuyingPower,
+    /// The account's excess liquidity ("ExcessLiquidity").
+    ExcessLiquidity,
+    /// The account's maintenance margin requirement ("MaintMarginReq").
+    MaintenanceMarginReq,
+}
+
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
+/// A typed update of one of the account values identified by a [`ValueKey`].
+pub struct ValueUpdate {
+    /// The new value, summed across all of the account's segments.
+    pub value: Price,
+    /// The denomination of the value.
+    pub denomination: Denomination,
+}
+
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Hash)]
 /// Represents the possible numbers of day trades before a regulatory breach of pattern day-trading
 /// rules is committed.
@@ -327,8 +648,20 @@ pub enum Tag {
     Leverage,
 }
 
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+/// An error type that represents an invalid account summary tag.
+pub struct ParseTagError(String);
+
+impl std::fmt::Display for ParseTagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid tag value encountered while parsing: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseTagError {}
+
 impl std::str::FromStr for Tag {
-    type Err = anyhow::Error;
+    type Err = ParseTagError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
@@ -361,11 +694,67 @@ impl std::str::FromStr for Tag {
             "HighestSeverity" => Self::HighestSeverity,
             "DayTradesRemaining" => Self::DayTradesRemaining,
             "Leverage" => Self::Leverage,
-            s => {
-                return Err(anyhow::Error::msg(format!(
-                    "Invalid tag value encountered while parsing: {s}"
-                )))
-            }
+            s => return Err(ParseTagError(s.to_owned())),
         })
     }
 }
+
+// === Helper functions ===
+
+#[inline]
+fn lock_fan_outs(shared: &Shared) -> std::sync::MutexGuard<'_, HashMap<i64, FanOutSender>> {
+    shared
+        .fan_outs
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Deliver the result of a request to its [`FanOut`], if it has one.
+///
+/// # Arguments
+/// * `req_id` - The ID of the request.
+/// * `account_number` - The account to which the result belongs, or [`None`] if it belongs to the
+/// account for which the request was made.
+/// * `event` - The result, which is only built when there is a [`FanOut`] to receive it.
+pub(crate) fn publish_fan_out(
+    shared: &Shared,
+    req_id: i64,
+    account_number: Option<&AccountId>,
+    event: impl FnOnce() -> AccountEvent,
+) {
+    let mut fan_outs = lock_fan_outs(shared);
+    let Some(sender) = fan_outs.get(&req_id) else {
+        return;
+    };
+    let Some(account_number) = account_number.or_else(|| sender.accounts.first()) else {
+        return;
+    };
+    if sender.tx.send((account_number.clone(), event())).is_err() {
+        fan_outs.remove(&req_id);
+    }
+}
+
+/// Deliver the end of a request's results to its [`FanOut`], if it has one, once for each of the
+/// accounts for which the request was made.
+pub(crate) fn publish_fan_out_end(shared: &Shared, req_id: i64) {
+    let mut fan_outs = lock_fan_outs(shared);
+    let Some(sender) = fan_outs.get(&req_id) else {
+        return;
+    };
+    let sent = sender.accounts.iter().all(|account_number| {
+        sender
+            .tx
+            .send((account_number.clone(), AccountEvent::End))
+            .is_ok()
+    });
+    if !sent {
+        fan_outs.remove(&req_id);
+    }
+}
+
+#[inline]
+/// Close every [`FanOut`] once the client loop stops, so that waiting for its events ends instead
+/// of hanging.
+pub(crate) fn close_fan_outs(shared: &Shared) {
+    lock_fan_outs(shared).clear();
+}
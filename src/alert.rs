@@ -0,0 +1,310 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::account::AccountId;
+use crate::client::{ConnectionHealth, Shared};
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A critical event that is delivered to the hooks registered with an [`Alerts`] handle.
+pub enum Alert {
+    /// The connection with the IBKR platform, or the platform's connection with IBKR's servers,
+    /// was lost.
+    ConnectionLost(ConnectionHealth),
+    /// IBKR rejected an order.
+    OrderRejected {
+        /// The ID of the order.
+        order_id: i64,
+        /// The error code.
+        code: i64,
+        /// The error message.
+        message: String,
+    },
+    /// An account's margin utilization rose above one of the thresholds of a
+    /// [`crate::margin::MarginMonitor`].
+    MarginThreshold {
+        /// The account.
+        account_number: AccountId,
+        /// The threshold that was crossed.
+        threshold: f64,
+        /// The margin utilization that crossed the threshold.
+        utilization: f64,
+    },
+    /// A GTC or GTD order is due to expire soon, while it has not been filled (see
+    /// [`crate::client::Client::set_expiry_warning`]).
+    OrderExpiring {
+        /// The ID of the order.
+        order_id: i64,
+        /// The order's expiration.
+        expiry: crate::order::Expiry,
+    },
+    /// IBKR rejected a request because it violated a pacing limit.
+    PacingViolation {
+        /// The ID of the request.
+        req_id: i64,
+        /// The error code.
+        code: i64,
+        /// The error message.
+        message: String,
+    },
+}
+
+type Hook = Arc<dyn Fn(Alert) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+#[derive(Clone, Default)]
+/// A cheap, cloneable handle to the alert hooks of a client, which is returned by
+/// [`crate::client::Client::get_alerts`].
+///
+/// Each hook is run on its own task whenever an [`Alert`] is fired, so that a slow hook (such as
+/// a webhook) never delays the client loop.
+pub struct Alerts(Arc<RwLock<Registry>>);
+
+#[derive(Default)]
+struct Registry {
+    hooks: Vec<Hook>,
+    runtime: Option<tokio::runtime::Handle>,
+}
+
+#[cfg(feature = "webhook")]
+#[derive(Debug, Clone)]
+struct Webhook {
+    client: reqwest::Client,
+    url: reqwest::Url,
+}
+
+#[cfg(feature = "webhook")]
+#[derive(serde::Serialize)]
+struct WebhookBody {
+    event: &'static str,
+    text: String,
+}
+
+// === Type implementations ===
+
+impl Alert {
+    #[inline]
+    #[must_use]
+    /// Get a short name for the kind of alert (ex: "order_rejected").
+    pub const fn get_kind(&self) -> &'static str {
+        match self {
+            Self::ConnectionLost(_) => "connection_lost",
+            Self::OrderRejected { .. } => "order_rejected",
+            Self::MarginThreshold { .. } => "margin_threshold",
+            Self::OrderExpiring { .. } => "order_expiring",
+            Self::PacingViolation { .. } => "pacing_violation",
+        }
+    }
+}
+
+impl std::fmt::Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ConnectionLost(health) => write!(f, "Connection lost: {health:?}"),
+            Self::OrderRejected {
+                order_id,
+                code,
+                message,
+            } => write!(f, "Order {order_id} rejected with error {code}: {message}"),
+            Self::MarginThreshold {
+                account_number,
+                threshold,
+                utilization,
+            } => write!(
+                f,
+                "Margin utilization of account {account_number} rose to {utilization:.4}, above \
+                 the threshold of {threshold:.4}"
+            ),
+            Self::OrderExpiring { order_id, expiry } => write!(
+                f,
+                "Order {order_id} expires on {} while it remains unfilled",
+                expiry.get_date()
+            ),
+            Self::PacingViolation {
+                req_id,
+                code,
+                message,
+            } => {
+                write!(
+                    f,
+                    "Request {req_id} violated a pacing limit with error {code}: {message}"
+                )
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Alerts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Alerts")
+            .field("hooks", &self.read().hooks.len())
+            .finish()
+    }
+}
+
+impl Alerts {
+    /// Register a hook, which is run for every subsequent [`Alert`] on the Tokio runtime on which
+    /// the client was built.
+    ///
+    /// # Arguments
+    /// * `hook` - The async function to run.
+    pub fn add_hook<F, Fut>(&self, hook: F)
+    where
+        F: Fn(Alert) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut registry = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        if registry.runtime.is_none() {
+            registry.runtime = tokio::runtime::Handle::try_current().ok();
+        }
+        registry
+            .hooks
+            .push(Arc::new(move |alert| Box::pin(hook(alert))));
+    }
+
+    #[cfg(feature = "webhook")]
+    /// Register a hook that POSTs every [`Alert`] to a URL as a JSON object, with the alert's
+    /// kind in its "event" field and its description in its "text" field (which chat services
+    /// such as Slack display as the message). A failed POST is only logged.
+    ///
+    /// # Arguments
+    /// * `url` - The URL, which must use the "http" or "https" scheme.
+    ///
+    /// # Errors
+    /// Returns [`crate::error::Error::Config`] if the URL is invalid.
+    pub fn add_webhook(&self, url: &str) -> Result<(), crate::error::Error> {
+        let webhook = Arc::new(Webhook::parse(url)?);
+        self.add_hook(move |alert| {
+            let webhook = Arc::clone(&webhook);
+            async move {
+                if let Err(_e) = webhook.post(&alert).await {
+                    event!(warn, error = %_e, "Failed to post alert to webhook");
+                }
+            }
+        });
+        Ok(())
+    }
+
+    #[inline]
+    /// Remove every hook.
+    pub fn clear(&self) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .hooks
+            .clear();
+    }
+
+    /// Run every hook for an alert, such as to raise custom alerts through the same hooks.
+    ///
+    /// # Arguments
+    /// * `alert` - The alert.
+    pub fn fire(&self, alert: Alert) {
+        let registry = self.read();
+        if registry.hooks.is_empty() {
+            return;
+        }
+        let Some(runtime) = registry
+            .runtime
+            .clone()
+            .or_else(|| tokio::runtime::Handle::try_current().ok())
+        else {
+            event!(warn, %alert, "Dropped an alert fired outside of any Tokio runtime");
+            return;
+        };
+        for hook in &registry.hooks {
+            runtime.spawn(hook(alert.clone()));
+        }
+    }
+
+    /// Remember the runtime on which the hooks are run, which is that of the client being built.
+    pub(crate) fn set_runtime(&self, runtime: tokio::runtime::Handle) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .runtime = Some(runtime);
+    }
+
+    #[inline]
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Registry> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "webhook")]
+impl Webhook {
+    fn parse(url: &str) -> Result<Self, crate::error::Error> {
+        let invalid = || crate::error::Error::Config(format!("Invalid webhook URL {url}"));
+        let url = reqwest::Url::parse(url).map_err(|_| invalid())?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url,
+        })
+    }
+
+    async fn post(&self, alert: &Alert) -> Result<(), reqwest::Error> {
+        let body = WebhookBody {
+            event: alert.get_kind(),
+            text: alert.to_string(),
+        };
+        self.client
+            .post(self.url.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+// === Helper functions ===
+
+/// Fire an [`Alert`] for an error message from IBKR, if the error is a rejected order or a pacing
+/// violation.
+///
+/// # Arguments
+/// * `shared` - The state of the client that received the error.
+/// * `req_id` - The ID of the request (or order) to which the error belongs.
+/// * `code` - The error code.
+/// * `message` - The error message.
+pub(crate) fn publish_error(shared: &Shared, req_id: i64, code: i64, message: &str) {
+    let alert = match code {
+        // Order validation errors, "Order rejected" and "Security not available for account"
+        103..=119 | 201 | 203 if req_id > 0 => Alert::OrderRejected {
+            order_id: req_id,
+            code,
+            message: message.to_owned(),
+        },
+        _ if crate::error::Error::is_pacing(code, message) => Alert::PacingViolation {
+            req_id,
+            code,
+            message: message.to_owned(),
+        },
+        _ => return,
+    };
+    shared.alerts.fire(alert);
+}
+
+/// Set a client's [`ConnectionHealth`], and fire an [`Alert::ConnectionLost`] if the connection
+/// was not already lost.
+pub(crate) fn set_health(shared: &Shared, health: ConnectionHealth) {
+    let was_lost = is_lost(shared.health.get());
+    shared.health.set(health);
+    if is_lost(health) && !was_lost {
+        shared.alerts.fire(Alert::ConnectionLost(health));
+    }
+}
+
+#[inline]
+const fn is_lost(health: ConnectionHealth) -> bool {
+    matches!(
+        health,
+        ConnectionHealth::Unresponsive { .. }
+            | ConnectionHealth::Disconnected
+            | ConnectionHealth::ServerConnectionLost
+    )
+}
@@ -0,0 +1,852 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+use prost::Message as _;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::client::{Environment, Profile, Trading, Unverified};
+use crate::contract::{Contract, ContractId, Security};
+use crate::error::Error;
+use crate::exchange::Routing;
+use crate::handle::ClientHandle;
+use crate::payload::market_depth::{CompleteEntry, Entry, Operation};
+use crate::payload::{Bar, SymbolSample};
+use crate::tick::{Class, Price, Size};
+use crate::watchlist::{self, Router, Update, Watchlist};
+
+// === Constants ===
+
+/// The protobuf schema of the messages exchanged with a [`Bridge`], from which the clients in
+/// other languages can generate their message types.
+pub const PROTO: &str = include_str!("../proto/bridge.proto");
+
+const MAX_FRAME_LEN: usize = 1 << 20;
+
+/// The number of frames that may wait to be written to a connection, beyond which the connection
+/// is closed for falling behind the market data.
+const CONNECTION_CAPACITY: usize = 1024;
+
+const CURRENT_TIME_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+// === Type definitions ===
+
+/// Shares a single connection to IBKR with other processes on the same machine, such as scripts
+/// written in other languages, through a local socket.
+///
+/// Each process exchanges length-delimited protobuf messages with the bridge, as described by the
+/// [`PROTO`] schema: it may look up contracts, ask for the server's time, and subscribe to the
+/// market data of contracts, which the bridge multiplexes over one [`Watchlist`] so that each
+/// contract consumes its market data lines only once, however many processes subscribe to it. A
+/// process that reads its market data too slowly is disconnected, rather than holding an
+/// ever-growing backlog in the bridge.
+///
+/// The market data reaches the bridge through the [`Router`] returned by [`Bridge::new`], which
+/// must be held in the client's [`crate::wrapper::Local`] or [`crate::wrapper::Remote`]
+/// implementation and fed from its price, size, real-time bar and market depth callbacks.
+pub struct Bridge<P: Profile = Trading, E: Environment = Unverified> {
+    handle: ClientHandle<P, E>,
+    state: Arc<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    watchlist: Arc<tokio::sync::Mutex<Watchlist>>,
+    subscribers: Mutex<HashMap<ContractId, HashMap<u64, Subscriber>>>,
+    next_connection: AtomicU64,
+}
+
+#[derive(Debug, Clone)]
+struct Subscriber {
+    tx: mpsc::Sender<Vec<u8>>,
+    lagged: CancellationToken,
+}
+
+/// The message types of the [`PROTO`] schema.
+mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Request {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(oneof = "request::Kind", tags = "2, 3, 4, 5, 6, 7")]
+        pub kind: Option<request::Kind>,
+    }
+
+    pub mod request {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum Kind {
+            #[prost(message, tag = "2")]
+            Ping(super::Empty),
+            #[prost(message, tag = "3")]
+            MatchingSymbols(super::MatchingSymbols),
+            #[prost(message, tag = "4")]
+            Subscribe(super::ContractRef),
+            #[prost(message, tag = "5")]
+            Unsubscribe(super::ContractRef),
+            #[prost(message, tag = "6")]
+            CurrentTime(super::Empty),
+            #[prost(message, tag = "7")]
+            ContractDetails(super::ContractRef),
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Empty {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MatchingSymbols {
+        #[prost(string, tag = "1")]
+        pub pattern: String,
+    }
+
+    /// The `Subscribe`, `Unsubscribe` and `ContractDetails` messages, which share their layout.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ContractRef {
+        #[prost(int64, tag = "1")]
+        pub contract_id: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Response {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(oneof = "response::Kind", tags = "2, 3, 4, 5, 6, 7, 8")]
+        pub kind: Option<response::Kind>,
+    }
+
+    pub mod response {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum Kind {
+            #[prost(message, tag = "2")]
+            Pong(super::Empty),
+            #[prost(message, tag = "3")]
+            SymbolSamples(super::SymbolSamples),
+            #[prost(message, tag = "4")]
+            Ack(super::Empty),
+            #[prost(message, tag = "5")]
+            Error(super::Error),
+            #[prost(message, tag = "6")]
+            Update(super::Update),
+            #[prost(message, tag = "7")]
+            ServerTime(super::ServerTime),
+            #[prost(message, tag = "8")]
+            Contract(super::Contract),
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SymbolSamples {
+        #[prost(message, repeated, tag = "1")]
+        pub samples: Vec<SymbolSample>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SymbolSample {
+        #[prost(int64, tag = "1")]
+        pub contract_id: i64,
+        #[prost(string, tag = "2")]
+        pub symbol: String,
+        #[prost(string, tag = "3")]
+        pub security_type: String,
+        #[prost(string, tag = "4")]
+        pub primary_exchange: String,
+        #[prost(string, tag = "5")]
+        pub currency: String,
+        #[prost(string, repeated, tag = "6")]
+        pub derivative_security_types: Vec<String>,
+        #[prost(string, tag = "7")]
+        pub description: String,
+        #[prost(string, tag = "8")]
+        pub issuer_id: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Error {
+        #[prost(string, tag = "1")]
+        pub message: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ServerTime {
+        #[prost(int64, tag = "1")]
+        pub timestamp: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Contract {
+        #[prost(int64, tag = "1")]
+        pub contract_id: i64,
+        #[prost(string, tag = "2")]
+        pub symbol: String,
+        #[prost(string, tag = "3")]
+        pub security_type: String,
+        #[prost(string, tag = "4")]
+        pub exchange: String,
+        #[prost(string, tag = "5")]
+        pub primary_exchange: String,
+        #[prost(string, tag = "6")]
+        pub currency: String,
+        #[prost(string, tag = "7")]
+        pub local_symbol: String,
+        #[prost(string, tag = "8")]
+        pub expiration_date: String,
+        #[prost(double, tag = "9")]
+        pub strike: f64,
+        #[prost(string, tag = "10")]
+        pub right: String,
+        #[prost(uint32, tag = "11")]
+        pub multiplier: u32,
+        #[prost(string, tag = "12")]
+        pub trading_class: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Update {
+        #[prost(int64, tag = "1")]
+        pub contract_id: i64,
+        #[prost(oneof = "update::Kind", tags = "2, 3, 4, 5")]
+        pub kind: Option<update::Kind>,
+    }
+
+    pub mod update {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum Kind {
+            #[prost(message, tag = "2")]
+            Price(super::PriceTick),
+            #[prost(message, tag = "3")]
+            Size(super::SizeTick),
+            #[prost(message, tag = "4")]
+            Bar(super::Bar),
+            #[prost(message, tag = "5")]
+            Depth(super::DepthUpdate),
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum PriceKind {
+        Unspecified = 0,
+        Bid = 1,
+        Ask = 2,
+        Last = 3,
+        High = 4,
+        Low = 5,
+        Close = 6,
+        Open = 7,
+        LastRthTrade = 8,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PriceTick {
+        #[prost(enumeration = "PriceKind", tag = "1")]
+        pub kind: i32,
+        #[prost(double, tag = "2")]
+        pub price: f64,
+        #[prost(bool, tag = "3")]
+        pub delayed: bool,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum SizeKind {
+        Unspecified = 0,
+        Bid = 1,
+        Ask = 2,
+        Last = 3,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SizeTick {
+        #[prost(enumeration = "SizeKind", tag = "1")]
+        pub kind: i32,
+        #[prost(double, tag = "2")]
+        pub size: f64,
+        #[prost(bool, tag = "3")]
+        pub delayed: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Bar {
+        #[prost(int64, tag = "1")]
+        pub timestamp: i64,
+        #[prost(double, tag = "2")]
+        pub open: f64,
+        #[prost(double, tag = "3")]
+        pub high: f64,
+        #[prost(double, tag = "4")]
+        pub low: f64,
+        #[prost(double, tag = "5")]
+        pub close: f64,
+        #[prost(double, tag = "6")]
+        pub volume: f64,
+        #[prost(double, tag = "7")]
+        pub wap: f64,
+        #[prost(uint64, tag = "8")]
+        pub trade_count: u64,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum DepthOperation {
+        Unspecified = 0,
+        Insert = 1,
+        Update = 2,
+        Delete = 3,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum DepthSide {
+        Unspecified = 0,
+        Bid = 1,
+        Ask = 2,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DepthUpdate {
+        #[prost(enumeration = "DepthOperation", tag = "1")]
+        pub operation: i32,
+        #[prost(enumeration = "DepthSide", tag = "2")]
+        pub side: i32,
+        #[prost(uint64, tag = "3")]
+        pub position: u64,
+        #[prost(double, tag = "4")]
+        pub price: f64,
+        #[prost(double, tag = "5")]
+        pub size: f64,
+        #[prost(string, tag = "6")]
+        pub exchange: String,
+        #[prost(string, tag = "7")]
+        pub market_maker: String,
+    }
+}
+
+// === Type implementations ===
+
+impl<P: Profile, E: Environment> Bridge<P, E> {
+    #[must_use]
+    /// Create a new bridge, which must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// * `handle` - The handle to the client whose connection is shared.
+    /// * `profile` - The market data to which to subscribe for each contract.
+    /// * `line_budget` - The maximum number of market data lines that the subscriptions may
+    ///   consume.
+    ///
+    /// # Returns
+    /// The bridge, along with the [`Router`] through which the client's wrapper must forward the
+    /// market data.
+    pub fn new(
+        handle: ClientHandle<P, E>,
+        profile: watchlist::Profile,
+        line_budget: usize,
+    ) -> (Self, Router) {
+        let (watchlist, updates) = Watchlist::new(profile, line_budget);
+        let router = watchlist.get_router();
+        let state = Arc::new(State {
+            watchlist: Arc::new(tokio::sync::Mutex::new(watchlist)),
+            subscribers: Mutex::new(HashMap::new()),
+            next_connection: AtomicU64::new(1),
+        });
+        tokio::spawn(fan_out(Arc::downgrade(&state), updates));
+        (Self { handle, state }, router)
+    }
+
+    /// Accept connections on a TCP socket, which should be bound to a loopback address, and serve
+    /// each of them on its own task.
+    ///
+    /// # Arguments
+    /// * `listener` - The socket.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if a connection could not be accepted.
+    pub async fn serve_tcp(&self, listener: tokio::net::TcpListener) -> Result<(), Error> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let bridge = self.clone();
+            tokio::spawn(async move { bridge.serve(stream).await });
+        }
+    }
+
+    #[cfg(unix)]
+    /// Accept connections on a Unix domain socket and serve each of them on its own task.
+    ///
+    /// # Arguments
+    /// * `listener` - The socket.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if a connection could not be accepted.
+    pub async fn serve_unix(&self, listener: tokio::net::UnixListener) -> Result<(), Error> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let bridge = self.clone();
+            tokio::spawn(async move { bridge.serve(stream).await });
+        }
+    }
+
+    /// Serve a single connection over any transport (such as a named pipe) until the other
+    /// process closes it, sends a malformed message, or falls too far behind its market data.
+    /// The connection's subscriptions are then cancelled.
+    ///
+    /// # Arguments
+    /// * `stream` - The connection.
+    pub async fn serve<S>(&self, stream: S)
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = tokio::io::BufReader::new(reader);
+        let (tx, mut rx) = mpsc::channel::<Vec<u8>>(CONNECTION_CAPACITY);
+        let write_task = tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                if writer.write_all(&frame).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let connection = self.state.next_connection.fetch_add(1, Ordering::Relaxed);
+        let subscriber = Subscriber {
+            tx,
+            lagged: CancellationToken::new(),
+        };
+        let mut subscriptions = HashSet::new();
+        loop {
+            let frame = tokio::select! {
+                () = subscriber.lagged.cancelled() => {
+                    event!(warn, connection, "Closed bridge connection that fell behind");
+                    break;
+                }
+                frame = read_frame(&mut reader) => frame,
+            };
+            let frame = match frame {
+                Ok(Some(frame)) => frame,
+                Ok(None) => break,
+                Err(_e) => {
+                    event!(warn, error = %_e, connection, "Failed to read from bridge connection");
+                    break;
+                }
+            };
+            let request = match proto::Request::decode(frame.as_slice()) {
+                Ok(request) => request,
+                Err(e) => {
+                    let _ = subscriber.tx.send(encode_error(0, e.to_string())).await;
+                    break;
+                }
+            };
+            let id = request.id;
+            let Some(kind) = request.kind else {
+                let message = format!("Request {id} has no known kind");
+                let _ = subscriber.tx.send(encode_error(id, message)).await;
+                break;
+            };
+            let reply = match kind {
+                proto::request::Kind::Ping(_) => Ok(proto::response::Kind::Pong(proto::Empty {})),
+                proto::request::Kind::MatchingSymbols(proto::MatchingSymbols { pattern }) => self
+                    .handle
+                    .run(move |client| {
+                        Box::pin(async move { client.req_matching_symbols(&pattern).await })
+                    })
+                    .await
+                    .and_then(|samples| samples)
+                    .map(|samples| {
+                        proto::response::Kind::SymbolSamples(proto::SymbolSamples {
+                            samples: samples.iter().map(encode_sample).collect(),
+                        })
+                    })
+                    .map_err(|e| e.to_string()),
+                proto::request::Kind::Subscribe(proto::ContractRef { contract_id }) => {
+                    let contract_id = ContractId(contract_id);
+                    self.subscribe(connection, contract_id, &subscriber)
+                        .await
+                        .map(|()| {
+                            subscriptions.insert(contract_id);
+                            proto::response::Kind::Ack(proto::Empty {})
+                        })
+                }
+                proto::request::Kind::Unsubscribe(proto::ContractRef { contract_id }) => {
+                    let contract_id = ContractId(contract_id);
+                    subscriptions.remove(&contract_id);
+                    self.unsubscribe(connection, contract_id)
+                        .await
+                        .map(|()| proto::response::Kind::Ack(proto::Empty {}))
+                }
+                proto::request::Kind::CurrentTime(_) => self
+                    .handle
+                    .run(|client| {
+                        Box::pin(async move {
+                            client.req_current_time_await(CURRENT_TIME_TIMEOUT).await
+                        })
+                    })
+                    .await
+                    .and_then(|time| time)
+                    .map(|time| {
+                        proto::response::Kind::ServerTime(proto::ServerTime {
+                            timestamp: time.timestamp(),
+                        })
+                    })
+                    .map_err(|e| e.to_string()),
+                proto::request::Kind::ContractDetails(proto::ContractRef { contract_id }) => self
+                    .handle
+                    .run(move |client| {
+                        Box::pin(
+                            async move { client.resolve_contract(ContractId(contract_id)).await },
+                        )
+                    })
+                    .await
+                    .and_then(|contract| contract)
+                    .map(|contract| proto::response::Kind::Contract(encode_contract(&contract)))
+                    .map_err(|e| e.to_string()),
+            };
+            let frame = match reply {
+                Ok(kind) => encode_response(id, kind),
+                Err(message) => encode_error(id, message),
+            };
+            if subscriber.tx.send(frame).await.is_err() {
+                break;
+            }
+        }
+
+        for contract_id in subscriptions {
+            let _ = self.unsubscribe(connection, contract_id).await;
+        }
+        drop(subscriber);
+        let _ = write_task.await;
+    }
+
+    /// Add a connection to the subscribers of a contract, subscribing to the contract's data if
+    /// it is the first one. The watchlist stays locked throughout, so that a concurrent
+    /// unsubscription cannot cancel the data in between.
+    async fn subscribe(
+        &self,
+        connection: u64,
+        contract_id: ContractId,
+        subscriber: &Subscriber,
+    ) -> Result<(), String> {
+        let watchlist = Arc::clone(&self.state.watchlist).lock_owned().await;
+        let _watchlist = if watchlist.contains(contract_id) {
+            watchlist
+        } else {
+            let mut watchlist = watchlist;
+            self.handle
+                .run(move |client| {
+                    Box::pin(async move {
+                        let contract = client
+                            .resolve_contract(contract_id)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        watchlist
+                            .add(client, contract)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        Ok::<_, String>(watchlist)
+                    })
+                })
+                .await
+                .map_err(|e| e.to_string())??
+        };
+        lock(&self.state.subscribers)
+            .entry(contract_id)
+            .or_default()
+            .insert(connection, subscriber.clone());
+        Ok(())
+    }
+
+    /// Remove a connection from the subscribers of a contract, cancelling the contract's data if
+    /// it was the last one.
+    async fn unsubscribe(&self, connection: u64, contract_id: ContractId) -> Result<(), String> {
+        let mut watchlist = Arc::clone(&self.state.watchlist).lock_owned().await;
+        {
+            let mut subscribers = lock(&self.state.subscribers);
+            let Some(connections) = subscribers.get_mut(&contract_id) else {
+                return Ok(());
+            };
+            connections.remove(&connection);
+            if !connections.is_empty() {
+                return Ok(());
+            }
+            subscribers.remove(&contract_id);
+        }
+        self.handle
+            .run(move |client| {
+                Box::pin(async move {
+                    watchlist
+                        .remove(client, contract_id)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+            })
+            .await
+            .map_err(|e| e.to_string())?
+    }
+}
+
+impl<P: Profile, E: Environment> Clone for Bridge<P, E> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<P: Profile, E: Environment> std::fmt::Debug for Bridge<P, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Bridge")
+            .field("handle", &self.handle)
+            .field("contracts", &lock(&self.state.subscribers).len())
+            .finish_non_exhaustive()
+    }
+}
+
+// === Helper functions ===
+
+#[inline]
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[inline]
+fn invalid_data(message: impl Into<String>) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message.into(),
+    ))
+}
+
+/// Forward the market data from the watchlist to the connections that subscribed to it, until
+/// the bridge is dropped. A connection whose queue is full is dropped from every contract and
+/// told to close.
+async fn fan_out(state: Weak<State>, mut updates: mpsc::UnboundedReceiver<(ContractId, Update)>) {
+    while let Some((contract_id, update)) = updates.recv().await {
+        let Some(shared) = state.upgrade() else {
+            break;
+        };
+        let frame = encode_response(
+            0,
+            proto::response::Kind::Update(encode_update(contract_id, update)),
+        );
+        let mut subscribers = lock(&shared.subscribers);
+        let mut lagged = Vec::new();
+        for (&connection, subscriber) in subscribers.get(&contract_id).into_iter().flatten() {
+            if let Err(mpsc::error::TrySendError::Full(_)) = subscriber.tx.try_send(frame.clone()) {
+                subscriber.lagged.cancel();
+                lagged.push(connection);
+            }
+        }
+        if !lagged.is_empty() {
+            for connections in subscribers.values_mut() {
+                connections.retain(|connection, _| !lagged.contains(connection));
+            }
+        }
+    }
+}
+
+/// Read a length-delimited frame.
+///
+/// # Returns
+/// The frame, or [`None`] if the connection was closed between two frames.
+async fn read_frame<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Vec<u8>>, Error> {
+    if reader.fill_buf().await?.is_empty() {
+        return Ok(None);
+    }
+    let mut len = 0_usize;
+    for shift in (0..35).step_by(7) {
+        let byte = reader.read_u8().await?;
+        len |= usize::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            if len > MAX_FRAME_LEN {
+                return Err(invalid_data(format!("Frame of {len} bytes is too long")));
+            }
+            let mut frame = vec![0; len];
+            reader.read_exact(&mut frame).await?;
+            return Ok(Some(frame));
+        }
+    }
+    Err(invalid_data("Frame length is too long"))
+}
+
+#[inline]
+fn encode_response(id: u64, kind: proto::response::Kind) -> Vec<u8> {
+    proto::Response {
+        id,
+        kind: Some(kind),
+    }
+    .encode_length_delimited_to_vec()
+}
+
+#[inline]
+fn encode_error(id: u64, message: String) -> Vec<u8> {
+    encode_response(id, proto::response::Kind::Error(proto::Error { message }))
+}
+
+fn encode_sample(sample: &SymbolSample) -> proto::SymbolSample {
+    proto::SymbolSample {
+        contract_id: sample.contract_id.0,
+        symbol: sample.symbol.clone(),
+        security_type: sample.security_type.clone(),
+        primary_exchange: sample
+            .primary_exchange
+            .map(|exchange| exchange.as_str().to_owned())
+            .unwrap_or_default(),
+        currency: sample
+            .currency
+            .map(|currency| currency.to_string())
+            .unwrap_or_default(),
+        derivative_security_types: sample.derivative_security_types.clone(),
+        description: sample.description.clone(),
+        issuer_id: sample.issuer_id.clone(),
+    }
+}
+
+fn encode_contract(contract: &Contract) -> proto::Contract {
+    fn encode<S: Security>(security: &S) -> proto::Contract {
+        proto::Contract {
+            contract_id: security.get_contract_id().0,
+            symbol: security.get_symbol().to_owned(),
+            security_type: security.get_security_type().to_owned(),
+            exchange: match security.get_exchange() {
+                Routing::Smart => "SMART".to_owned(),
+                Routing::Primary(exchange) => exchange.as_str().to_owned(),
+            },
+            primary_exchange: security
+                .get_primary_exchange()
+                .map(|exchange| exchange.as_str().to_owned())
+                .unwrap_or_default(),
+            currency: security.get_currency().to_string(),
+            local_symbol: security.get_local_symbol().to_owned(),
+            expiration_date: security
+                .get_expiration_date()
+                .map(|date| date.format("%Y%m%d").to_string())
+                .unwrap_or_default(),
+            strike: security.get_strike().unwrap_or_default(),
+            right: security.get_right().unwrap_or_default().to_owned(),
+            multiplier: security.get_multiplier().unwrap_or_default(),
+            trading_class: security.get_trading_class().unwrap_or_default().to_owned(),
+        }
+    }
+
+    match contract {
+        Contract::Forex(security) => encode(security),
+        Contract::Crypto(security) => encode(security),
+        Contract::Stock(security) => encode(security),
+        Contract::Index(security) => encode(security),
+        Contract::SecFuture(security) => encode(security),
+        Contract::SecOption(security) => encode(security),
+        Contract::Commodity(security) => encode(security),
+        Contract::Combo(security) => encode(security),
+    }
+}
+
+/// Encode a piece of market data as the `Update` of a pushed `Response`.
+fn encode_update(contract_id: ContractId, update: Update) -> proto::Update {
+    let kind = match update {
+        Update::Price(class) => {
+            let (price, delayed) = match class {
+                Class::Live(price) => (price, false),
+                Class::Delayed(price) => (price, true),
+            };
+            let (kind, price) = match price {
+                Price::Bid(price) => (proto::PriceKind::Bid, price),
+                Price::Ask(price) => (proto::PriceKind::Ask, price),
+                Price::Last(price) => (proto::PriceKind::Last, price),
+                Price::High(price) => (proto::PriceKind::High, price),
+                Price::Low(price) => (proto::PriceKind::Low, price),
+                Price::Close(price) => (proto::PriceKind::Close, price),
+                Price::Open(price) => (proto::PriceKind::Open, price),
+                Price::LastRthTrade(price) => (proto::PriceKind::LastRthTrade, price),
+            };
+            proto::update::Kind::Price(proto::PriceTick {
+                kind: kind.into(),
+                price,
+                delayed,
+            })
+        }
+        Update::Size(class) => {
+            let (size, delayed) = match class {
+                Class::Live(size) => (size, false),
+                Class::Delayed(size) => (size, true),
+            };
+            let (kind, size) = match size {
+                Size::Bid(size) => (proto::SizeKind::Bid, size),
+                Size::Ask(size) => (proto::SizeKind::Ask, size),
+                Size::Last(size) => (proto::SizeKind::Last, size),
+            };
+            proto::update::Kind::Size(proto::SizeTick {
+                kind: kind.into(),
+                size,
+                delayed,
+            })
+        }
+        Update::Bar(bar) => {
+            let (core, (volume, wap, trade_count)) = match bar {
+                Bar::Ordinary(core) => (core, Default::default()),
+                Bar::Trades {
+                    bar,
+                    volume,
+                    wap,
+                    trade_count,
+                } => (bar, (volume, wap, trade_count)),
+            };
+            proto::update::Kind::Bar(proto::Bar {
+                timestamp: core.datetime.and_utc().timestamp(),
+                open: core.open,
+                high: core.high,
+                low: core.low,
+                close: core.close,
+                volume,
+                wap,
+                trade_count,
+            })
+        }
+        Update::Depth(operation) => proto::update::Kind::Depth(encode_depth(operation)),
+    };
+    proto::Update {
+        contract_id: contract_id.0,
+        kind: Some(kind),
+    }
+}
+
+fn encode_depth(operation: Operation) -> proto::DepthUpdate {
+    let (kind, entry) = match operation {
+        Operation::Insert(entry) => (proto::DepthOperation::Insert, entry),
+        Operation::Update(entry) => (proto::DepthOperation::Update, entry),
+        Operation::Delete(entry) => (proto::DepthOperation::Delete, entry),
+    };
+    let (entry, exchange, market_maker) = match entry {
+        CompleteEntry::SmartDepth { exchange, entry } => {
+            (entry, exchange.as_str().to_owned(), String::new())
+        }
+        CompleteEntry::MarketMaker {
+            market_maker,
+            entry,
+        } => (entry, String::new(), market_maker.iter().collect()),
+        CompleteEntry::Ordinary(entry) => (entry, String::new(), String::new()),
+    };
+    let (side, position, price, size) = match entry {
+        Entry::Bid {
+            position,
+            price,
+            size,
+        } => (proto::DepthSide::Bid, position, price, size),
+        Entry::Ask {
+            position,
+            price,
+            size,
+        } => (proto::DepthSide::Ask, position, price, size),
+    };
+    proto::DepthUpdate {
+        operation: kind.into(),
+        side: side.into(),
+        position,
+        price,
+        size,
+        exchange,
+        market_maker,
+    }
+}
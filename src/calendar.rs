@@ -0,0 +1,448 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+
+use crate::exchange::Primary;
+use crate::timezone::Tz;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A single trading session, from its open to its close.
+pub struct Session {
+    /// The time at which the session opens.
+    pub open: DateTime<Utc>,
+    /// The time at which the session closes.
+    pub close: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The trading sessions of a contract over the coming days, as reported by IBKR in the contract's
+/// details (see [`crate::client::Client::get_trading_hours`]).
+pub struct TradingHours {
+    time_zone: Tz,
+    sessions: Vec<Session>,
+    closed: Vec<NaiveDate>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An error type representing trading hours that could not be parsed.
+pub struct ParseTradingHoursError(String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The kind of a trading day on a [`Calendar`].
+pub enum TradingDay {
+    /// The market is closed for a weekend or holiday.
+    Closed,
+    /// The market is open for its regular session.
+    Regular,
+    /// The market closes early (ex: the day after Thanksgiving in the US).
+    HalfDay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A bundled market calendar of holidays, half days and regular session times, which covers the
+/// years 2024 through 2027. Dates outside of those years are not covered (see
+/// [`Calendar::covers`]), since their holidays are unknown.
+///
+/// Unlike [`TradingHours`], a calendar does not need a contract's details, so it can also answer
+/// for dates beyond the few days that IBKR reports.
+pub enum Calendar {
+    /// The US equity markets (NYSE, NASDAQ, and the other US stock exchanges), which trade from
+    /// 9:30 to 16:00 New York time, closing at 13:00 on half days.
+    UsEquities,
+}
+
+// === Type implementations ===
+
+impl std::fmt::Display for ParseTradingHoursError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid trading hours: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseTradingHoursError {}
+
+impl From<crate::payload::ScheduledSession> for Session {
+    #[inline]
+    fn from(session: crate::payload::ScheduledSession) -> Self {
+        Self {
+            open: session.open,
+            close: session.close,
+        }
+    }
+}
+
+impl TradingHours {
+    /// Parse trading hours in IBKR's format, which lists the sessions of each day (ex:
+    /// "20240506:0930-20240506:1600;20240507:CLOSED"), or, for older servers, the ranges of each
+    /// day (ex: "20240506:0930-1600,1700-1800").
+    ///
+    /// # Arguments
+    /// * `hours` - The trading hours.
+    /// * `time_zone` - The time zone in which the hours are given.
+    ///
+    /// # Errors
+    /// Returns an error if any of the days or ranges is malformed.
+    pub fn parse(hours: &str, time_zone: Tz) -> Result<Self, ParseTradingHoursError> {
+        let error = || ParseTradingHoursError(hours.to_owned());
+        let mut sessions = Vec::new();
+        let mut closed = Vec::new();
+        for day in hours.split(';').filter(|day| !day.is_empty()) {
+            let (date, ranges) = day.split_once(':').ok_or_else(error)?;
+            let date = NaiveDate::parse_from_str(date, "%Y%m%d").map_err(|_| error())?;
+            if ranges == "CLOSED" {
+                closed.push(date);
+                continue;
+            }
+            for range in ranges.split(',') {
+                let (open, close) = range.split_once('-').ok_or_else(error)?;
+                let open = parse_boundary(open, date, time_zone).ok_or_else(error)?;
+                let mut close = parse_boundary(close, date, time_zone).ok_or_else(error)?;
+                // A session given by its times alone may close on the following day
+                if close <= open {
+                    close += chrono::Duration::days(1);
+                }
+                sessions.push(Session { open, close });
+            }
+        }
+        sessions.sort_unstable();
+        Ok(Self {
+            time_zone,
+            sessions,
+            closed,
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the time zone in which IBKR reported the hours.
+    pub const fn get_time_zone(&self) -> Tz {
+        self.time_zone
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the sessions, in chronological order.
+    pub fn get_sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    #[must_use]
+    /// Return whether the contract trades on a given date (in the time zone of the hours).
+    ///
+    /// # Returns
+    /// Whether a session opens on the date, or [`None`] if the date is not covered by the hours.
+    pub fn is_trading_day(&self, date: NaiveDate) -> Option<bool> {
+        if self.closed.contains(&date) {
+            return Some(false);
+        }
+        let opens_on = |session: &Session| session.open.with_timezone(&self.time_zone).date_naive();
+        if self
+            .sessions
+            .iter()
+            .any(|session| opens_on(session) == date)
+        {
+            return Some(true);
+        }
+        None
+    }
+
+    #[must_use]
+    /// Return whether the contract is trading at a given time.
+    pub fn is_open(&self, time: DateTime<Utc>) -> bool {
+        self.sessions
+            .iter()
+            .any(|session| session.open <= time && time < session.close)
+    }
+
+    #[must_use]
+    /// Get the time at which the next session opens.
+    ///
+    /// # Arguments
+    /// * `after` - The time after which to look.
+    ///
+    /// # Returns
+    /// The time, or [`None`] if no session opens after `after` within the reported hours.
+    pub fn next_session_open(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.sessions
+            .iter()
+            .map(|session| session.open)
+            .find(|&open| open > after)
+    }
+
+    #[must_use]
+    /// Get the time at which the current or next session closes.
+    ///
+    /// # Arguments
+    /// * `after` - The time after which to look.
+    ///
+    /// # Returns
+    /// The time, or [`None`] if no session closes after `after` within the reported hours.
+    pub fn next_session_close(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.sessions
+            .iter()
+            .map(|session| session.close)
+            .find(|&close| close > after)
+    }
+}
+
+impl Calendar {
+    #[must_use]
+    /// Get the calendar of an exchange.
+    ///
+    /// # Returns
+    /// The calendar, or [`None`] if no calendar is bundled for the exchange.
+    pub const fn from_exchange(exchange: Primary) -> Option<Self> {
+        match exchange {
+            Primary::NewYorkStockExchange
+            | Primary::NyseNational
+            | Primary::AmericanStockExchange
+            | Primary::Archipelago
+            | Primary::NationalAssociationOfSecurityDealers
+            | Primary::Island
+            | Primary::NasdaqOmxBx
+            | Primary::NasdaqOmxPsx
+            | Primary::BatsTradingInc
+            | Primary::BatsYExchange
+            | Primary::BatsTradingEdgx
+            | Primary::DirectEdgeEcnEdgea
+            | Primary::InvestorsExchange
+            | Primary::LongTermStockExchange
+            | Primary::MembersExchange => Some(Self::UsEquities),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the time zone in which the calendar's sessions are given.
+    pub const fn get_time_zone(self) -> Tz {
+        match self {
+            Self::UsEquities => Tz::America__New_York,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return whether the calendar's bundled holidays cover a given date.
+    ///
+    /// # Arguments
+    /// * `date` - The date, in the calendar's time zone.
+    pub fn covers(self, date: NaiveDate) -> bool {
+        let (first, last) = match self {
+            Self::UsEquities => US_EQUITY_YEARS,
+        };
+        first <= date.year() && date.year() <= last
+    }
+
+    #[must_use]
+    /// Get the kind of a given date.
+    ///
+    /// # Arguments
+    /// * `date` - The date, in the calendar's time zone.
+    ///
+    /// # Returns
+    /// The kind of the date, or [`None`] if the calendar does not cover it.
+    pub fn get_trading_day(self, date: NaiveDate) -> Option<TradingDay> {
+        if !self.covers(date) {
+            return None;
+        }
+        let (holidays, half_days) = match self {
+            Self::UsEquities => (US_EQUITY_HOLIDAYS, US_EQUITY_HALF_DAYS),
+        };
+        let key = (date.year(), date.month(), date.day());
+        Some(
+            if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || holidays.contains(&key) {
+                TradingDay::Closed
+            } else if half_days.contains(&key) {
+                TradingDay::HalfDay
+            } else {
+                TradingDay::Regular
+            },
+        )
+    }
+
+    #[must_use]
+    /// Get the regular session on a given date.
+    ///
+    /// # Arguments
+    /// * `date` - The date, in the calendar's time zone.
+    ///
+    /// # Returns
+    /// The session, or [`None`] if the market is closed on the date or the calendar does not cover
+    /// it.
+    pub fn get_session(self, date: NaiveDate) -> Option<Session> {
+        let (open, close, half_day_close) = match self {
+            Self::UsEquities => ((9, 30), (16, 0), (13, 0)),
+        };
+        let (close_hour, close_minute) = match self.get_trading_day(date)? {
+            TradingDay::Closed => return None,
+            TradingDay::Regular => close,
+            TradingDay::HalfDay => half_day_close,
+        };
+        let at = |hour, minute| {
+            let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+            self.get_time_zone()
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .map(|datetime| datetime.with_timezone(&Utc))
+        };
+        Some(Session {
+            open: at(open.0, open.1)?,
+            close: at(close_hour, close_minute)?,
+        })
+    }
+
+    /// Get the sessions that begin on or after the date of a given time, in chronological order.
+    pub(crate) fn sessions_from(self, time: DateTime<Utc>) -> impl Iterator<Item = Session> {
+        time.with_timezone(&self.get_time_zone())
+            .date_naive()
+            .iter_days()
+            // No market is closed for longer than a couple of weeks
+            .take(21)
+            .filter_map(move |date| self.get_session(date))
+    }
+
+    #[must_use]
+    /// Return whether the market is in its regular session at a given time, which is never the
+    /// case on a date that the calendar does not cover.
+    pub fn is_open(self, time: DateTime<Utc>) -> bool {
+        self.get_session(time.with_timezone(&self.get_time_zone()).date_naive())
+            .is_some_and(|session| session.open <= time && time < session.close)
+    }
+
+    #[must_use]
+    /// Get the time at which the next regular session opens.
+    ///
+    /// # Arguments
+    /// * `after` - The time after which to look.
+    ///
+    /// # Returns
+    /// The time, or [`None`] if the calendar does not cover the next session.
+    pub fn next_session_open(self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.sessions_from(after)
+            .map(|session| session.open)
+            .find(|&open| open > after)
+    }
+
+    #[must_use]
+    /// Get the time at which the current or next regular session closes.
+    ///
+    /// # Arguments
+    /// * `after` - The time after which to look.
+    ///
+    /// # Returns
+    /// The time, or [`None`] if the calendar does not cover the next session.
+    pub fn next_session_close(self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.sessions_from(after)
+            .map(|session| session.close)
+            .find(|&close| close > after)
+    }
+}
+
+// === Bundled holiday tables ===
+
+/// The first and last years covered by the holidays of the US equity markets.
+const US_EQUITY_YEARS: (i32, i32) = (2024, 2027);
+
+/// The full-day closures of the US equity markets.
+const US_EQUITY_HOLIDAYS: &[(i32, u32, u32)] = &[
+    (2024, 1, 1),
+    (2024, 1, 15),
+    (2024, 2, 19),
+    (2024, 3, 29),
+    (2024, 5, 27),
+    (2024, 6, 19),
+    (2024, 7, 4),
+    (2024, 9, 2),
+    (2024, 11, 28),
+    (2024, 12, 25),
+    (2025, 1, 1),
+    (2025, 1, 9),
+    (2025, 1, 20),
+    (2025, 2, 17),
+    (2025, 4, 18),
+    (2025, 5, 26),
+    (2025, 6, 19),
+    (2025, 7, 4),
+    (2025, 9, 1),
+    (2025, 11, 27),
+    (2025, 12, 25),
+    (2026, 1, 1),
+    (2026, 1, 19),
+    (2026, 2, 16),
+    (2026, 4, 3),
+    (2026, 5, 25),
+    (2026, 6, 19),
+    (2026, 7, 3),
+    (2026, 9, 7),
+    (2026, 11, 26),
+    (2026, 12, 25),
+    (2027, 1, 1),
+    (2027, 1, 18),
+    (2027, 2, 15),
+    (2027, 3, 26),
+    (2027, 5, 31),
+    (2027, 6, 18),
+    (2027, 7, 5),
+    (2027, 9, 6),
+    (2027, 11, 25),
+    (2027, 12, 24),
+];
+
+/// The early closures of the US equity markets.
+const US_EQUITY_HALF_DAYS: &[(i32, u32, u32)] = &[
+    (2024, 7, 3),
+    (2024, 11, 29),
+    (2024, 12, 24),
+    (2025, 7, 3),
+    (2025, 11, 28),
+    (2025, 12, 24),
+    (2026, 11, 27),
+    (2026, 12, 24),
+    (2027, 11, 26),
+];
+
+// === Helper functions ===
+
+/// Parse one end of a range of trading hours, which is either a time ("0930") on the day's date
+/// or a date and time ("20240506:0930").
+fn parse_boundary(boundary: &str, date: NaiveDate, time_zone: Tz) -> Option<DateTime<Utc>> {
+    let datetime = match boundary.split_once(':') {
+        Some(_) => NaiveDateTime::parse_from_str(boundary, "%Y%m%d:%H%M").ok()?,
+        None => date.and_time(NaiveTime::parse_from_str(boundary, "%H%M").ok()?),
+    };
+    time_zone
+        .from_local_datetime(&datetime)
+        .earliest()
+        .map(|datetime| datetime.with_timezone(&Utc))
+}
+
+#[must_use]
+/// Get the time at which an exchange's next regular session opens, according to its bundled
+/// [`Calendar`].
+///
+/// # Arguments
+/// * `exchange` - The exchange.
+/// * `after` - The time after which to look.
+///
+/// # Returns
+/// The time, or [`None`] if no calendar is bundled for the exchange or it does not cover the next
+/// session.
+pub fn next_session_open(exchange: Primary, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    Calendar::from_exchange(exchange)?.next_session_open(after)
+}
+
+#[must_use]
+/// Get the time at which an exchange's current or next regular session closes, according to its
+/// bundled [`Calendar`].
+///
+/// # Arguments
+/// * `exchange` - The exchange.
+/// * `after` - The time after which to look.
+///
+/// # Returns
+/// The time, or [`None`] if no calendar is bundled for the exchange or it does not cover the next
+/// session.
+pub fn next_session_close(exchange: Primary, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    Calendar::from_exchange(exchange)?.next_session_close(after)
+}
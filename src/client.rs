@@ -2,30 +2,61 @@ use anyhow::Context;
 use crossbeam::queue::SegQueue;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::net::tcp::OwnedReadHalf;
+use tokio::io::AsyncReadExt;
 use tokio::task::JoinHandle;
-use tokio::{io::AsyncReadExt, net::TcpStream, sync::mpsc};
 use tokio_util::sync::CancellationToken;
 
-use crate::contract::{ContractId, Security};
+use crate::contract::{
+    Contract, ContractDetails, ContractId, ContractQuery, DeltaNeutralField, MarketDataLegs,
+    SecOption, Security, Stock,
+};
 use crate::decode::Decoder;
+use crate::error::Error;
 use crate::market_data::{
-    histogram, historical_bar, historical_ticks, live_bar, live_data, live_ticks,
+    fundamental, histogram, historical_bar, historical_ticks, live_bar, live_data, live_ticks,
     updating_historical_bar,
 };
-use crate::message::{In, Out, ToClient, ToWrapper};
+use crate::message::{In, Out};
 use crate::wrapper::{
     indicators::{LocalMarker, RemoteMarker},
     Initializer, Local, Remote,
 };
 use crate::{
-    account::Tag,
-    comm::Writer,
-    constants, decode,
-    execution::Filter,
-    order::{Executable, Order},
-    payload::ExchangeId,
+    account::{self, AccountId, FanOut, Tag, ValueKey, ValueUpdate},
+    alert::{self, Alerts},
+    calendar,
+    clock::{Clock, SystemClock},
+    comm::{ReadHalf, Transport, Writer},
+    constants,
+    context::Contexts,
+    currency::Currency,
+    decode,
+    drawdown::{self, DrawdownAction, DrawdownEvent, DrawdownMonitor, DrawdownScope},
+    exchange::Primary,
+    execution::{Blotter, CommissionSummary, Execution, Filter, OrderSide},
+    exposure::{CurrencyExposure, ExposureReport},
+    flatten::{self, FlattenStyle, Flattening},
+    latency::{LatencyStats, LatencyTracker},
+    order::{
+        Attributes, BracketOrder, BracketOrderIds, Executable, ExerciseAction, Expiry, Limit,
+        Market, Order, Stop,
+    },
+    order_id::OrderIdStore,
+    pacing::Pacer,
+    payload::{
+        Bar, ExchangeId, HistoricalNews, HistoricalSchedule, MissedBars, NewsArticle, NewsProvider,
+        OpenOrder, OptionChain, OrderState, Position, Quote, SmartComponent, SymbolSample, Tick,
+    },
+    quantity::{Price, Quantity},
     reader::Reader,
+    rebalance::{Rebalance, RebalanceError, RebalanceOrder, Rebalancer},
+    scanner::Subscription,
+    schedule,
+    stops::StopBook,
+    stream::{self, EventStream, Subscription},
+    tick::TradeFilter,
+    timezone::Tz,
+    tracker::{self, OrderTracker},
 };
 
 // ======================================
@@ -40,6 +71,32 @@ struct Ports {
     gateway_paper: u16,
 }
 
+/// The standard ports used by IBKR's platforms, which are used when probing in
+/// [`Builder::discover`].
+impl Default for Ports {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            tws_live: 7496,
+            tws_paper: 7497,
+            gateway_live: 4001,
+            gateway_paper: 4002,
+        }
+    }
+}
+
+impl Ports {
+    #[inline]
+    const fn get(&self, mode: Mode, host: Host) -> u16 {
+        match (mode, host) {
+            (Mode::Live, Host::Tws) => self.tws_live,
+            (Mode::Live, Host::Gateway) => self.gateway_live,
+            (Mode::Paper, Host::Tws) => self.tws_paper,
+            (Mode::Paper, Host::Gateway) => self.gateway_paper,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 struct Config {
     address: std::net::Ipv4Addr,
@@ -49,15 +106,15 @@ struct Config {
 
 impl Config {
     #[inline]
-    fn new(path: &str) -> anyhow::Result<Self> {
+    fn new(path: &str) -> Result<Self, Error> {
         toml::from_str(
             std::fs::read_to_string(path)
-                .with_context(|| format!("Invalid config file at path {path}"))?
+                .map_err(|e| Error::Config(format!("Invalid config file at path {path}: {e}")))?
                 .as_str(),
         )
-        .with_context(|| {
-            format!(
-                "Invalid TOML file at path {path}.\n
+        .map_err(|e| {
+            Error::Config(format!(
+                "Invalid TOML file at path {path}: {e}\n
         # =========================\n
         # === config.toml Usage ===\n
         # =========================\n
@@ -69,7 +126,7 @@ impl Config {
         \n
         gateway_live: u16\n
         gateway_paper: u16\n"
-            )
+            ))
         })
     }
 }
@@ -114,6 +171,276 @@ pub enum Host {
     Gateway,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Determines how a [`Builder`] reacts when the declared [`Mode`] does not match the connection
+/// that is actually established (ex: a paper [`Mode`] connected to a live port).
+pub enum ModeMismatchPolicy {
+    /// Proceed silently.
+    Ignore,
+    #[default]
+    /// Proceed, but record a [`ModeMismatch`] that can be retrieved with
+    /// [`Client::get_mode_mismatch`].
+    Warn,
+    /// Refuse to proceed and return the [`ModeMismatch`] as an error.
+    Refuse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Limits the rate at which a [`Client`] sends messages to IBKR, whose pacing rules disconnect
+/// clients that send more than 50 messages per second. The limit is a token bucket, which allows
+/// short bursts while enforcing an average rate.
+pub struct RateLimit {
+    /// The average number of messages that may be sent per second.
+    pub messages_per_second: u32,
+    /// The number of messages that may be sent at once after a period of inactivity.
+    pub burst: u32,
+    /// When [`true`], a request that would exceed the limit waits until it can be sent. Otherwise,
+    /// it fails with [`Error::RateLimited`].
+    pub queue: bool,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            messages_per_second: 50,
+            burst: 50,
+            queue: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Options that tune the TCP connection between a [`Client`] and IBKR. By default, the operating
+/// system's settings are left unchanged.
+pub struct SocketOptions {
+    /// When [`true`], disables Nagle's algorithm (`TCP_NODELAY`), so that small messages such as
+    /// orders are sent immediately instead of being held back to be combined with later ones.
+    pub no_delay: bool,
+    /// When set, enables TCP keepalive (`SO_KEEPALIVE`) and sets how long the connection must be
+    /// idle before the first keepalive probe is sent.
+    pub keepalive: Option<std::time::Duration>,
+    /// When set, sets the time between keepalive probes. This only applies when `keepalive` is
+    /// set, and it is ignored on platforms that do not support it.
+    pub keepalive_interval: Option<std::time::Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A typed warning indicating that a client declared as [`Mode::Paper`] is in fact connected to a
+/// live trading environment.
+pub enum ModeMismatch {
+    /// The connection was made on one of the standard live trading ports.
+    LivePort(u16),
+    /// One of the managed accounts has a live (non-paper) account prefix.
+    LiveAccount(AccountId),
+}
+
+impl std::fmt::Display for ModeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LivePort(port) => write!(
+                f,
+                "Client declared as Mode::Paper is connected to live trading port {port}"
+            ),
+            Self::LiveAccount(account) => write!(
+                f,
+                "Client declared as Mode::Paper manages live trading account {account}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ModeMismatch {}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The health of a client's connection, as observed by the heartbeat started with
+/// [`Client::start_heartbeat`] and by the connectivity errors that IBKR reports.
+pub enum ConnectionHealth {
+    #[default]
+    /// No heartbeat has been answered yet.
+    Unknown,
+    /// The latest heartbeat was answered.
+    Healthy {
+        /// The round-trip time of the latest heartbeat.
+        latency: std::time::Duration,
+    },
+    /// A heartbeat was not answered before the timeout elapsed, so the connection is presumed
+    /// dead.
+    Unresponsive {
+        /// The amount of time for which the heartbeat has gone unanswered.
+        elapsed: std::time::Duration,
+    },
+    /// A heartbeat could not be sent because the connection with the IBKR platform is closed.
+    Disconnected,
+    /// The IBKR platform lost its connection to IBKR's servers (error 1100).
+    ServerConnectionLost,
+    /// The IBKR platform restored its connection to IBKR's servers, but market data was lost and
+    /// its subscriptions must be made again (error 1101).
+    RestoredDataLost,
+    /// The IBKR platform restored its connection to IBKR's servers and market data was maintained
+    /// (error 1102).
+    Restored,
+}
+
+#[derive(Debug)]
+/// Broadcasts changes of a client's [`ConnectionHealth`].
+pub(crate) struct HealthChannel(tokio::sync::watch::Sender<ConnectionHealth>);
+
+impl Default for HealthChannel {
+    fn default() -> Self {
+        Self(tokio::sync::watch::channel(ConnectionHealth::default()).0)
+    }
+}
+
+impl HealthChannel {
+    #[inline]
+    pub(crate) fn get(&self) -> ConnectionHealth {
+        *self.0.borrow()
+    }
+
+    #[inline]
+    pub(crate) fn set(&self, health: ConnectionHealth) {
+        self.0.send_if_modified(|current| {
+            let modified = *current != health;
+            *current = health;
+            modified
+        });
+    }
+}
+
+#[derive(Debug)]
+/// The responses awaited by the `*_await` methods, keyed by the request to which they belong.
+pub(crate) struct Responses<K, V> {
+    values: std::sync::Mutex<std::collections::HashMap<K, Option<V>>>,
+    notify: tokio::sync::Notify,
+}
+
+impl<K, V> Default for Responses<K, V> {
+    fn default() -> Self {
+        Self {
+            values: std::sync::Mutex::default(),
+            notify: tokio::sync::Notify::new(),
+        }
+    }
+}
+
+impl<K: Eq + std::hash::Hash, V> Responses<K, V> {
+    #[inline]
+    fn lock(&self) -> std::sync::MutexGuard<'_, std::collections::HashMap<K, Option<V>>> {
+        self.values
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[inline]
+    /// Start awaiting the response to a request. This must happen before the request is sent, so
+    /// that the response is not missed.
+    pub(crate) fn register(&self, key: K) {
+        self.lock().insert(key, None);
+    }
+
+    #[inline]
+    /// Stop awaiting the response to a request.
+    pub(crate) fn cancel(&self, key: &K) {
+        self.lock().remove(key);
+    }
+
+    #[inline]
+    /// Check whether the response to a request is being awaited.
+    pub(crate) fn is_awaited(&self, key: &K) -> bool {
+        self.lock().contains_key(key)
+    }
+
+    #[inline]
+    /// Deliver a response, provided that it is being awaited and that no other response has
+    /// been delivered for the same request.
+    pub(crate) fn deliver(&self, key: &K, value: V) {
+        if let Some(pending @ None) = self.lock().get_mut(key) {
+            *pending = Some(value);
+            self.notify.notify_waiters();
+        }
+    }
+
+    /// Wait for the response to a request until a deadline, after which it is no longer awaited.
+    pub(crate) async fn wait(&self, key: &K, deadline: tokio::time::Instant) -> Option<V> {
+        loop {
+            let notified = self.notify.notified();
+            {
+                let mut values = self.lock();
+                if matches!(values.get(key), Some(Some(_))) {
+                    return values.remove(key).flatten();
+                }
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                self.cancel(key);
+                return None;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A cache of values keyed by contract, which holds at most
+/// [`constants::CONTRACT_CACHE_CAPACITY`] contracts. Once it is full, the contract that was
+/// inserted first is evicted to make room for a new one.
+pub(crate) struct ContractCache<V> {
+    values: std::collections::HashMap<ContractId, V>,
+    inserted: std::collections::VecDeque<ContractId>,
+}
+
+impl<V> Default for ContractCache<V> {
+    fn default() -> Self {
+        Self {
+            values: std::collections::HashMap::new(),
+            inserted: std::collections::VecDeque::new(),
+        }
+    }
+}
+
+impl<V> ContractCache<V> {
+    #[inline]
+    pub(crate) fn get(&self, contract_id: &ContractId) -> Option<&V> {
+        self.values.get(contract_id)
+    }
+
+    /// Insert or replace the value of a contract, evicting the oldest contract if the cache is
+    /// full.
+    pub(crate) fn insert(&mut self, contract_id: ContractId, value: V) {
+        if self.values.insert(contract_id, value).is_some() {
+            return;
+        }
+        self.inserted.push_back(contract_id);
+        while self.inserted.len() > constants::CONTRACT_CACHE_CAPACITY {
+            if let Some(evicted) = self.inserted.pop_front() {
+                self.values.remove(&evicted);
+            }
+        }
+    }
+}
+
+impl ModeMismatch {
+    #[inline]
+    fn from_port(mode: Option<Mode>, port: u16) -> Option<Self> {
+        let live = Ports::default();
+        (mode == Some(Mode::Paper) && (port == live.tws_live || port == live.gateway_live))
+            .then_some(Self::LivePort(port))
+    }
+
+    #[inline]
+    fn from_accounts<'a>(
+        mode: Option<Mode>,
+        mut accounts: impl Iterator<Item = &'a AccountId>,
+    ) -> Option<Self> {
+        // Paper trading accounts are always prefixed with a "D" (ex: DU1234567)
+        if mode == Some(Mode::Paper) {
+            accounts
+                .find(|acct| !acct.0.starts_with('D'))
+                .map(|acct| Self::LiveAccount(acct.clone()))
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Inner {
     ConfigFile {
@@ -125,16 +452,32 @@ enum Inner {
         port: u16,
         address: std::net::Ipv4Addr,
     },
+    Discover {
+        address: std::net::Ipv4Addr,
+    },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// Facilitates the creation of a new connection to IBKR's trading systems.
 ///
 /// Each connection requires a TCP port and address with which to connect to the appropriate IBKR
 /// platform. This information is communicated by either: 1) Manually specifying the parameters in
-/// [`Builder::manual`] or 2) Automatically looking them up in the config.toml file by specifying a
-///  [`Mode`] and [`Host`] in [`Builder::from_config_file`].
-pub struct Builder(Inner);
+/// [`Builder::manual`], 2) Automatically looking them up in the config.toml file by specifying a
+///  [`Mode`] and [`Host`] in [`Builder::from_config_file`] or 3) Probing the standard IBKR ports
+/// with [`Builder::discover`].
+///
+/// By default, a client declared as [`Mode::Paper`] that turns out to be connected to a live
+/// trading environment will record a [`ModeMismatch`]. This behavior can be changed with
+/// [`Builder::with_mismatch_policy`].
+///
+/// With the "tls" feature, the connection can also be encrypted with [`Builder::with_tls`].
+pub struct Builder {
+    inner: Inner,
+    mismatch_policy: ModeMismatchPolicy,
+    transport: Transport,
+    socket_options: SocketOptions,
+    rate_limit: Option<RateLimit>,
+}
 
 impl Builder {
     #[inline]
@@ -147,10 +490,16 @@ impl Builder {
     /// * `path` - An optional string slice that overrides the default location of "./config.toml".
     ///
     /// # Errors
-    /// Returns any error encountered while reading and parsing the config file.
-    pub fn from_config_file(mode: Mode, host: Host, path: Option<&str>) -> anyhow::Result<Self> {
+    /// Returns an [`Error::Config`] if the config file cannot be read or parsed.
+    pub fn from_config_file(mode: Mode, host: Host, path: Option<&str>) -> Result<Self, Error> {
         let config = Config::new(path.unwrap_or("./config.toml"))?;
-        Ok(Self(Inner::ConfigFile { mode, host, config }))
+        Ok(Self {
+            inner: Inner::ConfigFile { mode, host, config },
+            mismatch_policy: ModeMismatchPolicy::default(),
+            transport: Transport::Plain,
+            socket_options: SocketOptions::default(),
+            rate_limit: None,
+        })
     }
 
     #[must_use]
@@ -161,10 +510,90 @@ impl Builder {
     /// * `port` - The TCP port with which to connect to IBKR's trading systems.
     /// * `address` - The IP address with which to connect to IBKR's trading systems.
     pub fn manual(port: u16, address: Option<std::net::Ipv4Addr>) -> Self {
-        Self(Inner::Manual {
-            port,
-            address: address.unwrap_or(std::net::Ipv4Addr::LOCALHOST),
-        })
+        Self {
+            inner: Inner::Manual {
+                port,
+                address: address.unwrap_or(std::net::Ipv4Addr::LOCALHOST),
+            },
+            mismatch_policy: ModeMismatchPolicy::default(),
+            transport: Transport::Plain,
+            socket_options: SocketOptions::default(),
+            rate_limit: None,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Creates a new [`Builder`] that discovers the running IBKR platform by probing the standard
+    /// ports (7496, 7497, 4001 and 4002) on a given address.
+    ///
+    /// Upon calling [`Builder::connect`], each port is tried in turn and a handshake is performed
+    /// on the first one that answers. The detected [`Mode`] and [`Host`] are then available from
+    /// [`Client::get_mode`] and [`Client::get_host`].
+    ///
+    /// # Arguments
+    /// * `address` - The IP address on which to probe for IBKR's trading systems.
+    pub fn discover(address: Option<std::net::Ipv4Addr>) -> Self {
+        Self {
+            inner: Inner::Discover {
+                address: address.unwrap_or(std::net::Ipv4Addr::LOCALHOST),
+            },
+            mismatch_policy: ModeMismatchPolicy::default(),
+            transport: Transport::Plain,
+            socket_options: SocketOptions::default(),
+            rate_limit: None,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Sets how the resulting [`Client`] reacts when a [`Mode::Paper`] connection turns out to be
+    /// a live trading environment, as indicated by either the port or the prefix of a managed
+    /// account.
+    ///
+    /// # Arguments
+    /// * `policy` - The [`ModeMismatchPolicy`] to apply.
+    pub const fn with_mismatch_policy(mut self, policy: ModeMismatchPolicy) -> Self {
+        self.mismatch_policy = policy;
+        self
+    }
+
+    #[must_use]
+    #[inline]
+    /// Limits the rate at which the resulting [`Client`] sends messages, so that bursts of requests
+    /// do not violate IBKR's pacing rules. By default, messages are not limited.
+    ///
+    /// # Arguments
+    /// * `limit` - The [`RateLimit`] to apply (see [`RateLimit::default`] for IBKR's limit).
+    pub const fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    #[must_use]
+    #[inline]
+    /// Tunes the TCP connection of the resulting [`Client`], such as to disable Nagle's algorithm
+    /// so that orders are sent without delay. The options also apply when the client reconnects.
+    ///
+    /// # Arguments
+    /// * `options` - The [`SocketOptions`] to apply.
+    pub const fn with_socket_options(mut self, options: SocketOptions) -> Self {
+        self.socket_options = options;
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    #[must_use]
+    #[inline]
+    /// Encrypts the connection with TLS, as is required to connect to the SSL port of IB
+    /// Gateway.
+    ///
+    /// # Arguments
+    /// * `config` - The [`crate::tls::TlsConfig`] that determines how the platform's certificate
+    /// is verified.
+    pub fn with_tls(mut self, config: crate::tls::TlsConfig) -> Self {
+        self.transport = Transport::Tls(config);
+        self
     }
 
     /// Initiates a connection to IBKR's trading systems and returns a [`Client`].
@@ -176,65 +605,82 @@ impl Builder {
     /// This function will error if any of the following occurs:
     /// 1) An error occurs while initiating a TCP connection on the port and address specified in
     /// either [`Builder::manual`] or in the "config.toml" file specified in
-    /// [`Builder::from_config_file`].
+    /// [`Builder::from_config_file`] ([`Error::Io`]).
     /// 2) An error occurs while reading or writing the handshake message that initiates a
-    /// connection with IBKR's trading systems.
+    /// connection with IBKR's trading systems ([`Error::Io`] or [`Error::Handshake`]).
+    /// 3) None of the standard ports answers a handshake when the [`Builder`] was created with
+    /// [`Builder::discover`] ([`Error::Handshake`]).
+    /// 4) The IBKR platform's server version is too old for this client ([`Error::ServerVersion`]).
+    /// 5) The port is a known live trading port while the [`Mode`] is [`Mode::Paper`] and the
+    /// policy is [`ModeMismatchPolicy::Refuse`] ([`Error::ModeMismatch`]).
     ///
     /// # Returns
     /// An inactive [`Client`] that will become active upon calling [`Client::local`] or
     /// [`Client::remote`].
-    pub async fn connect(&self, client_id: i64) -> anyhow::Result<Client<indicators::Inactive>> {
-        let (mode, host, port, address) = match self.0 {
-            Inner::ConfigFile { mode, host, config } => (
-                Some(mode),
-                Some(host),
-                match (mode, host) {
-                    (Mode::Live, Host::Tws) => config.ports.tws_live,
-                    (Mode::Live, Host::Gateway) => config.ports.gateway_live,
-                    (Mode::Paper, Host::Tws) => config.ports.tws_paper,
-                    (Mode::Paper, Host::Gateway) => config.ports.gateway_paper,
-                },
-                config.address,
-            ),
-            Inner::Manual { port, address } => (None, None, port, address),
-        };
-
-        let (mut reader, writer) = TcpStream::connect((address, port)).await?.into_split();
-
-        let mut writer = Writer::new(writer);
-        writer.add_prefix("API\0")?;
-        writer.add_body(format!(
-            "v{}..{}",
-            constants::MIN_CLIENT_VERSION,
-            constants::MAX_CLIENT_VERSION
-        ))?;
-        writer.send().await?;
-
-        let mut buf = bytes::BytesMut::with_capacity(usize::try_from(reader.read_u32().await?)?);
-        reader.read_buf(&mut buf).await?;
-        let resp = buf.into_iter().map(char::from).collect::<String>();
-        let mut params = resp.split('\0');
+    pub async fn connect(&self, client_id: i64) -> Result<Client<indicators::Inactive>, Error> {
+        match self.inner {
+            Inner::ConfigFile { mode, host, config } => {
+                self.handshake(
+                    Some(mode),
+                    Some(host),
+                    config.ports.get(mode, host),
+                    config.address,
+                    client_id,
+                )
+                .await
+            }
+            Inner::Manual { port, address } => {
+                self.handshake(None, None, port, address, client_id).await
+            }
+            Inner::Discover { address } => {
+                let ports = Ports::default();
+                for (mode, host) in [
+                    (Mode::Live, Host::Tws),
+                    (Mode::Paper, Host::Tws),
+                    (Mode::Live, Host::Gateway),
+                    (Mode::Paper, Host::Gateway),
+                ] {
+                    if let Ok(Ok(client)) = tokio::time::timeout(
+                        constants::DISCOVERY_TIMEOUT,
+                        self.handshake(
+                            Some(mode),
+                            Some(host),
+                            ports.get(mode, host),
+                            address,
+                            client_id,
+                        ),
+                    )
+                    .await
+                    {
+                        return Ok(client);
+                    }
+                }
+                Err(Error::Handshake(format!(
+                    "No IBKR platform answered on any of the standard ports at {address}"
+                )))
+            }
+        }
+    }
 
-        let server_version = params
-            .next()
-            .ok_or_else(|| anyhow::Error::msg("Missing server version in IBKR handshake response"))?
-            .parse()
-            .with_context(|| "Failed to parse server version")?;
-        let conn_time = chrono::NaiveDateTime::parse_and_remainder(
-            params
-                .next()
-                .ok_or_else(|| {
-                    anyhow::Error::msg("Missing connection time in IBKR handshake response")
-                })?
-                .trim_end_matches(|c: char| !c.is_numeric()),
-            "%Y%m%d %X",
-        )
-        .with_context(|| "Failed to parse connection time")?
-        .0;
+    async fn handshake(
+        &self,
+        mode: Option<Mode>,
+        host: Option<Host>,
+        port: u16,
+        address: std::net::Ipv4Addr,
+        client_id: i64,
+    ) -> Result<Client<indicators::Inactive>, Error> {
+        let mode_mismatch = match ModeMismatch::from_port(mode, port) {
+            Some(mismatch) if self.mismatch_policy == ModeMismatchPolicy::Refuse => {
+                return Err(mismatch.into())
+            }
+            Some(_) if self.mismatch_policy == ModeMismatchPolicy::Ignore => None,
+            mismatch => mismatch,
+        };
 
-        let (client_tx, wrapper_rx) =
-            mpsc::channel::<ToWrapper>(constants::TO_WRAPPER_CHANNEL_SIZE);
-        let (wrapper_tx, client_rx) = mpsc::channel::<ToClient>(constants::TO_CLIENT_CHANNEL_SIZE);
+        let (reader, writer, server_version, conn_time) =
+            open_connection(&self.transport, self.socket_options, address, port).await?;
+        writer.set_rate_limit(self.rate_limit);
 
         let mut client = Client {
             mode,
@@ -244,21 +690,78 @@ impl Builder {
             client_id,
             server_version,
             conn_time,
+            mismatch_policy: self.mismatch_policy,
+            mode_mismatch,
+            contexts: Contexts::default(),
+            transport: self.transport.clone(),
+            socket_options: self.socket_options,
             writer,
-            status: indicators::Inactive {
-                reader,
-                client_tx,
-                client_rx,
-                wrapper_tx,
-                wrapper_rx,
-            },
+            status: indicators::Inactive { reader },
         };
-        client.start_api().await?;
+        start_api(&mut client.writer, client_id).await?;
 
         Ok(client)
     }
 }
 
+async fn open_connection(
+    transport: &Transport,
+    socket_options: SocketOptions,
+    address: std::net::Ipv4Addr,
+    port: u16,
+) -> Result<(ReadHalf, Writer, u32, chrono::NaiveDateTime), Error> {
+    let (mut reader, writer) = transport.connect(address, port, socket_options).await?;
+
+    let mut writer = Writer::new(writer);
+    writer.add_prefix("API\0")?;
+    writer.add_body(format!(
+        "v{}..{}",
+        constants::MIN_CLIENT_VERSION,
+        constants::MAX_CLIENT_VERSION
+    ))?;
+    writer.send().await?;
+
+    let mut buf = bytes::BytesMut::with_capacity(reader.read_u32().await? as usize);
+    reader.read_buf(&mut buf).await?;
+    let resp = buf.into_iter().map(char::from).collect::<String>();
+    let mut params = resp.split('\0');
+
+    let server_version = params
+        .next()
+        .ok_or_else(|| {
+            Error::Handshake("Missing server version in IBKR handshake response".to_owned())
+        })?
+        .parse()
+        .map_err(|e| Error::Handshake(format!("Failed to parse server version: {e}")))?;
+    if server_version < u32::from(constants::MIN_CLIENT_VERSION) {
+        return Err(Error::ServerVersion {
+            required: constants::MIN_CLIENT_VERSION.into(),
+            actual: server_version,
+        });
+    }
+    let conn_time = chrono::NaiveDateTime::parse_and_remainder(
+        params
+            .next()
+            .ok_or_else(|| {
+                Error::Handshake("Missing connection time in IBKR handshake response".to_owned())
+            })?
+            .trim_end_matches(|c: char| !c.is_numeric()),
+        "%Y%m%d %X",
+    )
+    .map_err(|e| Error::Handshake(format!("Failed to parse connection time: {e}")))?
+    .0;
+
+    Ok((reader, writer, server_version, conn_time))
+}
+
+async fn start_api(writer: &mut Writer, client_id: i64) -> Result<(), Error> {
+    const VERSION: u8 = 2;
+
+    writer.add_body((Out::StartApi, VERSION, client_id, None::<()>))?;
+    writer.send().await?;
+    Ok(())
+}
+
 // ===============================
 // === Status Trait Definition ===
 // ===============================
@@ -267,65 +770,209 @@ impl Builder {
 /// An active client, which can request information from IBKR trading systems.
 pub type ActiveClient = Client<indicators::Active>;
 
-type IntoActive = (
-    Client<indicators::Active>,
-    mpsc::Sender<ToClient>,
-    mpsc::Receiver<ToWrapper>,
+#[allow(clippy::module_name_repetitions)]
+/// An active "data-only" client, which can request information from IBKR trading systems but
+/// cannot manage orders. It is created with [`Client::local_data_only`] or
+/// [`Client::remote_data_only`].
+pub type DataClient = Client<indicators::Active<indicators::DataOnly>>;
+
+#[allow(clippy::module_name_repetitions)]
+/// An active client that has been verified to be connected to a paper trading environment. A
+/// function that takes a [`PaperClient`] cannot be called with a live client.
+pub type PaperClient = Client<indicators::Active<indicators::Trading, indicators::Paper>>;
+
+#[allow(clippy::module_name_repetitions)]
+/// An active client that has been verified to be connected to a live trading environment.
+pub type LiveClient = Client<indicators::Active<indicators::Trading, indicators::Live>>;
+
+pub use indicators::{DataOnly, Environment, Live, Paper, Profile, Trading, Unverified};
+
+type IntoActive<P> = (
+    Client<indicators::Active<P>>,
     Arc<SegQueue<Vec<String>>>,
+    std::collections::VecDeque<Vec<String>>,
 );
 
+#[derive(Debug, Default)]
+/// State that is shared between an active client and its decoding loop.
+pub(crate) struct Shared {
+    pub(crate) commissions: std::sync::Mutex<CommissionSummary>,
+    pub(crate) blotter: std::sync::Mutex<Blotter>,
+    pub(crate) contracts: std::sync::Mutex<ContractCache<Contract>>,
+    pub(crate) contract_details: std::sync::Mutex<ContractCache<ContractDetails>>,
+    pub(crate) execution_resolver:
+        std::sync::Mutex<Option<tokio::sync::mpsc::UnboundedSender<ContractId>>>,
+    pub(crate) unresolved_executions: std::sync::Mutex<Vec<(i64, Execution)>>,
+    pub(crate) trade_filters: std::sync::Mutex<std::collections::HashMap<i64, TradeFilter>>,
+    pub(crate) quotes: std::sync::Mutex<std::collections::HashMap<i64, Quote>>,
+    pub(crate) quotes_ended: tokio::sync::Notify,
+    pub(crate) time_zones: std::sync::Mutex<std::collections::HashMap<ContractId, Tz>>,
+    pub(crate) trading_hours:
+        std::sync::Mutex<std::collections::HashMap<ContractId, calendar::TradingHours>>,
+    pub(crate) decode_offload_threshold: std::sync::atomic::AtomicUsize,
+    pub(crate) pending_batches: std::sync::Mutex<std::collections::VecDeque<decode::Batch>>,
+    pub(crate) open_orders: std::sync::Mutex<Option<Vec<OpenOrder>>>,
+    pub(crate) open_orders_ended: std::sync::atomic::AtomicBool,
+    pub(crate) open_orders_notify: tokio::sync::Notify,
+    pub(crate) head_timestamps: Responses<i64, chrono::NaiveDateTime>,
+    pub(crate) current_times: Responses<(), chrono::NaiveDateTime>,
+    pub(crate) next_valid_ids: Responses<(), i64>,
+    pub(crate) user_infos: Responses<i64, String>,
+    pub(crate) contract_queries: Responses<i64, Result<Contract, Error>>,
+    pub(crate) contract_searches: Responses<i64, Result<Vec<Contract>, Error>>,
+    pub(crate) contract_search_results:
+        std::sync::Mutex<std::collections::HashMap<i64, Vec<Contract>>>,
+    pub(crate) option_chains: Responses<i64, Result<Vec<OptionChain>, Error>>,
+    pub(crate) option_chain_results:
+        std::sync::Mutex<std::collections::HashMap<i64, Vec<OptionChain>>>,
+    pub(crate) matching_symbols: Responses<i64, Result<Vec<SymbolSample>, Error>>,
+    pub(crate) news_providers: Responses<(), Vec<NewsProvider>>,
+    pub(crate) news_articles: Responses<i64, Result<NewsArticle, Error>>,
+    pub(crate) historical_news: Responses<i64, Result<HistoricalNews, Error>>,
+    pub(crate) historical_news_results:
+        std::sync::Mutex<std::collections::HashMap<i64, Vec<crate::payload::NewsHeadline>>>,
+    pub(crate) historical_bars: Responses<i64, Result<Vec<Bar>, Error>>,
+    pub(crate) historical_schedules: Responses<i64, Result<HistoricalSchedule, Error>>,
+    pub(crate) historical_ticks: Responses<i64, Result<Vec<Tick>, Error>>,
+    pub(crate) pacer: std::sync::Mutex<Pacer>,
+    pub(crate) order_latency: std::sync::Mutex<LatencyTracker>,
+    pub(crate) what_if_orders: Responses<i64, Result<OrderState, Error>>,
+    pub(crate) order_trackers: std::sync::Mutex<tracker::Registry>,
+    pub(crate) expirations: std::sync::Mutex<std::collections::HashMap<i64, Expiry>>,
+    pub(crate) expiry_warning: std::sync::Mutex<Option<CancellationToken>>,
+    pub(crate) scheduler: std::sync::Mutex<schedule::Scheduler>,
+    pub(crate) scheduler_changed: tokio::sync::Notify,
+    pub(crate) streams: std::sync::Mutex<std::collections::HashMap<i64, stream::Sender>>,
+    pub(crate) dropped_streams: std::sync::Mutex<Vec<Replay>>,
+    pub(crate) fan_outs: std::sync::Mutex<std::collections::HashMap<i64, account::FanOutSender>>,
+    pub(crate) exchange_ids: std::sync::Mutex<std::collections::HashMap<i64, ExchangeId>>,
+    pub(crate) smart_components: Responses<i64, Vec<SmartComponent>>,
+    pub(crate) smart_components_cache:
+        std::sync::Mutex<std::collections::HashMap<ExchangeId, Vec<SmartComponent>>>,
+    pub(crate) component_exchanges: std::sync::Mutex<std::collections::HashMap<char, Primary>>,
+    pub(crate) real_time_bars:
+        std::sync::Mutex<std::collections::HashMap<i64, chrono::NaiveDateTime>>,
+    pub(crate) real_time_bar_contracts:
+        std::sync::Mutex<std::collections::HashMap<i64, ContractId>>,
+    pub(crate) health: HealthChannel,
+    pub(crate) reader_lost: tokio::sync::Notify,
+    pub(crate) received: tokio::sync::Notify,
+    pub(crate) reconnected: tokio::sync::Notify,
+    pub(crate) current_time_requests:
+        std::sync::Mutex<std::collections::VecDeque<CurrentTimeRequest>>,
+    pub(crate) heartbeat: std::sync::Mutex<Option<CancellationToken>>,
+    pub(crate) earliest_data:
+        std::sync::Mutex<std::collections::HashMap<EarliestDataKey, chrono::DateTime<chrono::Utc>>>,
+    pub(crate) cash_balances: std::sync::Mutex<std::collections::HashMap<CashKey, f64>>,
+    pub(crate) portfolio: std::sync::Mutex<std::collections::HashMap<PortfolioKey, PortfolioValue>>,
+    pub(crate) positions:
+        std::sync::Mutex<std::collections::HashMap<PortfolioKey, flatten::Holding>>,
+    pub(crate) all_positions_received: std::sync::atomic::AtomicBool,
+    pub(crate) account_positions_received: std::sync::Mutex<std::collections::HashSet<AccountId>>,
+    pub(crate) account_values:
+        std::sync::Mutex<std::collections::HashMap<AccountValueKey, AccountValueSender>>,
+    pub(crate) position_values:
+        std::sync::Mutex<std::collections::HashMap<PortfolioKey, PositionSender>>,
+    pub(crate) alerts: Alerts,
+}
+
+type EarliestDataKey = (ContractId, String, bool);
+
+type AccountValueKey = (AccountId, ValueKey);
+
+type CashKey = (AccountId, Currency);
+
+type PortfolioKey = (AccountId, ContractId);
+
+type PortfolioValue = (Currency, f64);
+
+type AccountValueSender = tokio::sync::watch::Sender<Option<ValueUpdate>>;
+
+type PositionSender = tokio::sync::watch::Sender<Option<Position>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A subscription that is replayed when the client reconnects. The variants are ordered so that
+/// settings, such as the market data type, are replayed before the requests they affect.
+pub(crate) enum Replay {
+    MarketDataType,
+    AccountUpdates(Option<AccountId>),
+    Positions,
+    Request(i64, Out),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The sender of a current time request that is awaiting its answer. IBKR's answer does not carry
+/// the ID of its request, so the answers are matched with the requests in the order in which they
+/// were sent.
+pub(crate) enum CurrentTimeRequest {
+    User,
+    Heartbeat(std::time::Instant),
+}
+
 #[inline]
 #[allow(clippy::too_many_lines)]
 async fn decode_msg_remote<W>(
     fields: Vec<String>,
     local: &mut Decoder<RemoteMarker<W>>,
-    tx: &mut mpsc::Sender<ToClient>,
-    rx: &mut mpsc::Receiver<ToWrapper>,
+    shared: &Shared,
 ) where
     W: Remote,
 {
+    event!(
+        trace,
+        msg = ?fields.first().and_then(|s| s.parse::<In>().ok()),
+        fields = fields.len(),
+        "Received message"
+    );
     let status = match fields.first() {
         None => Err(anyhow::Error::msg("Empty fields received from reader")),
         Some(s) => match s.parse() {
             Ok(In::TickPrice) => Decoder::<RemoteMarker<W>>::tick_price_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick price msg"),
             Ok(In::TickSize) => Decoder::<RemoteMarker<W>>::tick_size_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick size msg"),
             Ok(In::OrderStatus) => Decoder::<RemoteMarker<W>>::order_status_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "order status msg"),
             Ok(In::ErrMsg) => Decoder::<RemoteMarker<W>>::err_msg_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "err msg msg"),
             Ok(In::OpenOrder) => Decoder::<RemoteMarker<W>>::open_order_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "open order msg"),
             Ok(In::AcctValue) => Decoder::<RemoteMarker<W>>::acct_value_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "acct value msg"),
             Ok(In::PortfolioValue) => Decoder::<RemoteMarker<W>>::portfolio_value_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "portfolio value msg"),
@@ -338,22 +985,21 @@ async fn decode_msg_remote<W>(
             Ok(In::NextValidId) => Decoder::<RemoteMarker<W>>::next_valid_id_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
-                tx,
-                rx,
+                shared,
             )
             .await
             .with_context(|| "next valid id msg"),
             Ok(In::ContractData) => Decoder::<RemoteMarker<W>>::contract_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
-                tx,
-                rx,
+                shared,
             )
             .await
             .with_context(|| "contract data msg"),
             Ok(In::ExecutionData) => Decoder::<RemoteMarker<W>>::execution_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "execution data msg"),
@@ -378,8 +1024,6 @@ async fn decode_msg_remote<W>(
             Ok(In::ManagedAccts) => Decoder::<RemoteMarker<W>>::managed_accts_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
-                tx,
-                rx,
             )
             .await
             .with_context(|| "managed accounts msg"),
@@ -392,12 +1036,14 @@ async fn decode_msg_remote<W>(
             Ok(In::HistoricalData) => Decoder::<RemoteMarker<W>>::historical_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical data msg"),
             Ok(In::BondContractData) => Decoder::<RemoteMarker<W>>::bond_contract_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "bond contract data msg"),
@@ -424,12 +1070,14 @@ async fn decode_msg_remote<W>(
             Ok(In::TickGeneric) => Decoder::<RemoteMarker<W>>::tick_generic_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick generic msg"),
             Ok(In::TickString) => Decoder::<RemoteMarker<W>>::tick_string_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick string msg"),
@@ -442,12 +1090,14 @@ async fn decode_msg_remote<W>(
             Ok(In::CurrentTime) => Decoder::<RemoteMarker<W>>::current_time_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "current time msg"),
             Ok(In::RealTimeBars) => Decoder::<RemoteMarker<W>>::real_time_bars_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "real time bars msg"),
@@ -460,18 +1110,21 @@ async fn decode_msg_remote<W>(
             Ok(In::ContractDataEnd) => Decoder::<RemoteMarker<W>>::contract_data_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "contract data end msg"),
             Ok(In::OpenOrderEnd) => Decoder::<RemoteMarker<W>>::open_order_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "open order end msg"),
             Ok(In::AcctDownloadEnd) => Decoder::<RemoteMarker<W>>::acct_download_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "acct download end msg"),
@@ -492,6 +1145,7 @@ async fn decode_msg_remote<W>(
             Ok(In::TickSnapshotEnd) => Decoder::<RemoteMarker<W>>::tick_snapshot_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick snapshot end msg"),
@@ -504,30 +1158,35 @@ async fn decode_msg_remote<W>(
             Ok(In::CommissionReport) => Decoder::<RemoteMarker<W>>::commission_report_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "commission report msg"),
             Ok(In::PositionData) => Decoder::<RemoteMarker<W>>::position_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "position data msg"),
             Ok(In::PositionEnd) => Decoder::<RemoteMarker<W>>::position_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "position end msg"),
             Ok(In::AccountSummary) => Decoder::<RemoteMarker<W>>::account_summary_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "account summary msg"),
             Ok(In::AccountSummaryEnd) => Decoder::<RemoteMarker<W>>::account_summary_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "account summary end msg"),
@@ -574,12 +1233,14 @@ async fn decode_msg_remote<W>(
             Ok(In::PositionMulti) => Decoder::<RemoteMarker<W>>::position_multi_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "position multi msg"),
             Ok(In::PositionMultiEnd) => Decoder::<RemoteMarker<W>>::position_multi_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "position multi end msg"),
@@ -601,6 +1262,7 @@ async fn decode_msg_remote<W>(
                 Decoder::<RemoteMarker<W>>::security_definition_option_parameter_msg(
                     &mut fields.into_iter(),
                     &mut local.0.wrapper,
+                    shared,
                 )
                 .await
                 .with_context(|| "security definition option parameter msg")
@@ -609,6 +1271,7 @@ async fn decode_msg_remote<W>(
                 Decoder::<RemoteMarker<W>>::security_definition_option_parameter_end_msg(
                     &mut fields.into_iter(),
                     &mut local.0.wrapper,
+                    shared,
                 )
                 .await
                 .with_context(|| "security definition option parameter end msg")
@@ -628,6 +1291,7 @@ async fn decode_msg_remote<W>(
             Ok(In::SymbolSamples) => Decoder::<RemoteMarker<W>>::symbol_samples_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "symbol samples msg"),
@@ -640,18 +1304,21 @@ async fn decode_msg_remote<W>(
             Ok(In::TickReqParams) => Decoder::<RemoteMarker<W>>::tick_req_params_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick req params msg"),
             Ok(In::SmartComponents) => Decoder::<RemoteMarker<W>>::smart_components_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "smart components msg"),
             Ok(In::NewsArticle) => Decoder::<RemoteMarker<W>>::news_article_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "news article msg"),
@@ -664,24 +1331,28 @@ async fn decode_msg_remote<W>(
             Ok(In::NewsProviders) => Decoder::<RemoteMarker<W>>::news_providers_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "news providers msg"),
             Ok(In::HistoricalNews) => Decoder::<RemoteMarker<W>>::historical_news_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical news msg"),
             Ok(In::HistoricalNewsEnd) => Decoder::<RemoteMarker<W>>::historical_news_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical news end msg"),
             Ok(In::HeadTimestamp) => Decoder::<RemoteMarker<W>>::head_timestamp_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "head timestamp msg"),
@@ -715,20 +1386,24 @@ async fn decode_msg_remote<W>(
             )
             .await
             .with_context(|| "market rule msg"),
-            Ok(In::Pnl) => {
-                Decoder::<RemoteMarker<W>>::pnl_msg(&mut fields.into_iter(), &mut local.0.wrapper)
-                    .await
-                    .with_context(|| "pnl msg")
-            }
+            Ok(In::Pnl) => Decoder::<RemoteMarker<W>>::pnl_msg(
+                &mut fields.into_iter(),
+                &mut local.0.wrapper,
+                shared,
+            )
+            .await
+            .with_context(|| "pnl msg"),
             Ok(In::PnlSingle) => Decoder::<RemoteMarker<W>>::pnl_single_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "pnl single msg"),
             Ok(In::HistoricalTicks) => Decoder::<RemoteMarker<W>>::historical_ticks_midpoint_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical ticks msg"),
@@ -736,6 +1411,7 @@ async fn decode_msg_remote<W>(
                 Decoder::<RemoteMarker<W>>::historical_ticks_bid_ask_msg(
                     &mut fields.into_iter(),
                     &mut local.0.wrapper,
+                    shared,
                 )
                 .await
                 .with_context(|| "historical ticks bid ask msg")
@@ -743,12 +1419,14 @@ async fn decode_msg_remote<W>(
             Ok(In::HistoricalTicksLast) => Decoder::<RemoteMarker<W>>::historical_ticks_last_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical ticks last msg"),
             Ok(In::TickByTick) => Decoder::<RemoteMarker<W>>::tick_by_tick_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick by tick msg"),
@@ -791,24 +1469,27 @@ async fn decode_msg_remote<W>(
             Ok(In::HistoricalSchedule) => Decoder::<RemoteMarker<W>>::historical_schedule_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical schedule msg"),
             Ok(In::UserInfo) => Decoder::<RemoteMarker<W>>::user_info_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "user info msg"),
             Err(e) => Err(e.into()),
         },
     };
-    match status {
-        Ok(()) => (),
-        Err(e) => {
-            println!("\x1B[31m{e}");
-            println!("{}\x1B[0m", e.root_cause());
-        }
+    if let Err(e) = status {
+        event!(warn, error = %format!("{e:#}"), "Failed to decode message");
+        local
+            .0
+            .wrapper
+            .decode_error(Error::Parse(format!("{e:#}")))
+            .await;
     }
 }
 
@@ -817,53 +1498,65 @@ async fn decode_msg_remote<W>(
 async fn decode_msg_local<'c, W>(
     fields: Vec<String>,
     local: &mut Decoder<LocalMarker<'c, W>>,
-    tx: &mut mpsc::Sender<ToClient>,
-    rx: &mut mpsc::Receiver<ToWrapper>,
+    shared: &Shared,
 ) where
     W: Local<'c>,
 {
+    event!(
+        trace,
+        msg = ?fields.first().and_then(|s| s.parse::<In>().ok()),
+        fields = fields.len(),
+        "Received message"
+    );
     let status = match fields.first() {
         None => Err(anyhow::Error::msg("Empty fields received from reader")),
         Some(s) => match s.parse() {
             Ok(In::TickPrice) => Decoder::<LocalMarker<'c, W>>::tick_price_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick price msg"),
             Ok(In::TickSize) => Decoder::<LocalMarker<'c, W>>::tick_size_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick size msg"),
             Ok(In::OrderStatus) => Decoder::<LocalMarker<'c, W>>::order_status_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "order status msg"),
             Ok(In::ErrMsg) => Decoder::<LocalMarker<'c, W>>::err_msg_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "err msg msg"),
             Ok(In::OpenOrder) => Decoder::<LocalMarker<'c, W>>::open_order_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "open order msg"),
             Ok(In::AcctValue) => Decoder::<LocalMarker<'c, W>>::acct_value_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "acct value msg"),
             Ok(In::PortfolioValue) => Decoder::<LocalMarker<'c, W>>::portfolio_value_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "portfolio value msg"),
@@ -876,22 +1569,21 @@ async fn decode_msg_local<'c, W>(
             Ok(In::NextValidId) => Decoder::<LocalMarker<'c, W>>::next_valid_id_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
-                tx,
-                rx,
+                shared,
             )
             .await
             .with_context(|| "next valid id msg"),
             Ok(In::ContractData) => Decoder::<LocalMarker<'c, W>>::contract_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
-                tx,
-                rx,
+                shared,
             )
             .await
             .with_context(|| "contract data msg"),
             Ok(In::ExecutionData) => Decoder::<LocalMarker<'c, W>>::execution_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "execution data msg"),
@@ -916,8 +1608,6 @@ async fn decode_msg_local<'c, W>(
             Ok(In::ManagedAccts) => Decoder::<LocalMarker<'c, W>>::managed_accts_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
-                tx,
-                rx,
             )
             .await
             .with_context(|| "managed accounts msg"),
@@ -930,12 +1620,14 @@ async fn decode_msg_local<'c, W>(
             Ok(In::HistoricalData) => Decoder::<LocalMarker<'c, W>>::historical_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical data msg"),
             Ok(In::BondContractData) => Decoder::<LocalMarker<'c, W>>::bond_contract_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "bond contract data msg"),
@@ -962,12 +1654,14 @@ async fn decode_msg_local<'c, W>(
             Ok(In::TickGeneric) => Decoder::<LocalMarker<'c, W>>::tick_generic_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick generic msg"),
             Ok(In::TickString) => Decoder::<LocalMarker<'c, W>>::tick_string_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick string msg"),
@@ -980,12 +1674,14 @@ async fn decode_msg_local<'c, W>(
             Ok(In::CurrentTime) => Decoder::<LocalMarker<'c, W>>::current_time_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "current time msg"),
             Ok(In::RealTimeBars) => Decoder::<LocalMarker<'c, W>>::real_time_bars_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "real time bars msg"),
@@ -998,18 +1694,21 @@ async fn decode_msg_local<'c, W>(
             Ok(In::ContractDataEnd) => Decoder::<LocalMarker<'c, W>>::contract_data_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "contract data end msg"),
             Ok(In::OpenOrderEnd) => Decoder::<LocalMarker<'c, W>>::open_order_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "open order end msg"),
             Ok(In::AcctDownloadEnd) => Decoder::<LocalMarker<'c, W>>::acct_download_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "acct download end msg"),
@@ -1030,6 +1729,7 @@ async fn decode_msg_local<'c, W>(
             Ok(In::TickSnapshotEnd) => Decoder::<LocalMarker<'c, W>>::tick_snapshot_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick snapshot end msg"),
@@ -1042,30 +1742,35 @@ async fn decode_msg_local<'c, W>(
             Ok(In::CommissionReport) => Decoder::<LocalMarker<'c, W>>::commission_report_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "commission report msg"),
             Ok(In::PositionData) => Decoder::<LocalMarker<'c, W>>::position_data_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "position data msg"),
             Ok(In::PositionEnd) => Decoder::<LocalMarker<'c, W>>::position_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "position end msg"),
             Ok(In::AccountSummary) => Decoder::<LocalMarker<'c, W>>::account_summary_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "account summary msg"),
             Ok(In::AccountSummaryEnd) => Decoder::<LocalMarker<'c, W>>::account_summary_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "account summary end msg"),
@@ -1114,12 +1819,14 @@ async fn decode_msg_local<'c, W>(
             Ok(In::PositionMulti) => Decoder::<LocalMarker<'c, W>>::position_multi_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "position multi msg"),
             Ok(In::PositionMultiEnd) => Decoder::<LocalMarker<'c, W>>::position_multi_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "position multi end msg"),
@@ -1141,6 +1848,7 @@ async fn decode_msg_local<'c, W>(
                 Decoder::<LocalMarker<'c, W>>::security_definition_option_parameter_msg(
                     &mut fields.into_iter(),
                     &mut local.0.wrapper,
+                    shared,
                 )
                 .await
                 .with_context(|| "security definition option parameter msg")
@@ -1149,6 +1857,7 @@ async fn decode_msg_local<'c, W>(
                 Decoder::<LocalMarker<'c, W>>::security_definition_option_parameter_end_msg(
                     &mut fields.into_iter(),
                     &mut local.0.wrapper,
+                    shared,
                 )
                 .await
                 .with_context(|| "security definition option parameter end msg")
@@ -1168,6 +1877,7 @@ async fn decode_msg_local<'c, W>(
             Ok(In::SymbolSamples) => Decoder::<LocalMarker<'c, W>>::symbol_samples_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "symbol samples msg"),
@@ -1180,18 +1890,21 @@ async fn decode_msg_local<'c, W>(
             Ok(In::TickReqParams) => Decoder::<LocalMarker<'c, W>>::tick_req_params_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick req params msg"),
             Ok(In::SmartComponents) => Decoder::<LocalMarker<'c, W>>::smart_components_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "smart components msg"),
             Ok(In::NewsArticle) => Decoder::<LocalMarker<'c, W>>::news_article_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "news article msg"),
@@ -1204,24 +1917,28 @@ async fn decode_msg_local<'c, W>(
             Ok(In::NewsProviders) => Decoder::<LocalMarker<'c, W>>::news_providers_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "news providers msg"),
             Ok(In::HistoricalNews) => Decoder::<LocalMarker<'c, W>>::historical_news_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical news msg"),
             Ok(In::HistoricalNewsEnd) => Decoder::<LocalMarker<'c, W>>::historical_news_end_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical news end msg"),
             Ok(In::HeadTimestamp) => Decoder::<LocalMarker<'c, W>>::head_timestamp_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "head timestamp msg"),
@@ -1260,12 +1977,14 @@ async fn decode_msg_local<'c, W>(
             Ok(In::Pnl) => Decoder::<LocalMarker<'c, W>>::pnl_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "pnl msg"),
             Ok(In::PnlSingle) => Decoder::<LocalMarker<'c, W>>::pnl_single_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "pnl single msg"),
@@ -1273,6 +1992,7 @@ async fn decode_msg_local<'c, W>(
                 Decoder::<LocalMarker<'c, W>>::historical_ticks_midpoint_msg(
                     &mut fields.into_iter(),
                     &mut local.0.wrapper,
+                    shared,
                 )
                 .await
                 .with_context(|| "historical ticks msg")
@@ -1281,6 +2001,7 @@ async fn decode_msg_local<'c, W>(
                 Decoder::<LocalMarker<'c, W>>::historical_ticks_bid_ask_msg(
                     &mut fields.into_iter(),
                     &mut local.0.wrapper,
+                    shared,
                 )
                 .await
                 .with_context(|| "historical ticks bid ask msg")
@@ -1289,6 +2010,7 @@ async fn decode_msg_local<'c, W>(
                 Decoder::<LocalMarker<'c, W>>::historical_ticks_last_msg(
                     &mut fields.into_iter(),
                     &mut local.0.wrapper,
+                    shared,
                 )
                 .await
                 .with_context(|| "historical ticks last msg")
@@ -1296,6 +2018,7 @@ async fn decode_msg_local<'c, W>(
             Ok(In::TickByTick) => Decoder::<LocalMarker<'c, W>>::tick_by_tick_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "tick by tick msg"),
@@ -1338,57 +2061,110 @@ async fn decode_msg_local<'c, W>(
             Ok(In::HistoricalSchedule) => Decoder::<LocalMarker<'c, W>>::historical_schedule_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "historical schedule msg"),
             Ok(In::UserInfo) => Decoder::<LocalMarker<'c, W>>::user_info_msg(
                 &mut fields.into_iter(),
                 &mut local.0.wrapper,
+                shared,
             )
             .await
             .with_context(|| "user info msg"),
             Err(e) => Err(e.into()),
         },
     };
-    match status {
-        Ok(()) => (),
-        Err(e) => {
-            println!("\x1B[31m{e}");
-            println!("{}\x1B[0m", e.root_cause());
-        }
+    if let Err(e) = status {
+        event!(warn, error = %format!("{e:#}"), "Failed to decode message");
+        local
+            .0
+            .wrapper
+            .decode_error(Error::Parse(format!("{e:#}")))
+            .await;
     }
 }
 
 pub(crate) mod indicators {
-    use super::Reader;
-    use crate::message::{ToClient, ToWrapper};
-    use std::collections::HashSet;
-    use tokio::{net::tcp::OwnedReadHalf, sync::mpsc, task::JoinHandle};
+    use super::{Clock, OrderIdStore, Reader, Replay, Shared};
+    use crate::account::AccountId;
+    use crate::comm::ReadHalf;
+    use crate::order::Expiry;
+    use crossbeam::queue::SegQueue;
+    use std::collections::{BTreeMap, HashMap, HashSet};
+    use tokio::task::JoinHandle;
 
     pub trait Status {}
 
+    /// Implemented by the marker types that determine which functionality an active
+    /// [`super::Client`] exposes.
+    pub trait Profile: std::fmt::Debug + Send + Sync + 'static {}
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// The profile of a client that can both request data and manage orders. This is the profile
+    /// of a [`super::ActiveClient`].
+    pub struct Trading;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// The profile of a "data-only" client, which is a [`super::DataClient`]. Its methods that
+    /// place, modify, or cancel orders do not exist, and it never consumes an order ID. This makes
+    /// it a safer choice for a downloader that shares an account with trading applications.
+    pub struct DataOnly;
+
+    impl Profile for Trading {}
+    impl Profile for DataOnly {}
+
+    /// Implemented by the marker types that record the kind of trading environment to which an
+    /// active [`super::Client`] is known to be connected.
+    pub trait Environment: std::fmt::Debug + Send + Sync + 'static {}
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// The environment of a client whose connection has not been verified. This is the
+    /// environment of every client when it is first activated.
+    pub struct Unverified;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// The environment of a client that has been verified to be connected to a paper trading
+    /// environment with [`super::Client::into_paper`].
+    pub struct Paper;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// The environment of a client that has been verified to be connected to a live trading
+    /// environment with [`super::Client::into_live`].
+    pub struct Live;
+
+    impl Environment for Unverified {}
+    impl Environment for Paper {}
+    impl Environment for Live {}
+
     pub struct Inactive {
-        pub(crate) reader: OwnedReadHalf,
-        pub(crate) client_tx: mpsc::Sender<ToWrapper>,
-        pub(crate) client_rx: mpsc::Receiver<ToClient>,
-        pub(crate) wrapper_tx: mpsc::Sender<ToClient>,
-        pub(crate) wrapper_rx: mpsc::Receiver<ToWrapper>,
+        pub(crate) reader: ReadHalf,
     }
 
     impl Status for Inactive {}
 
     #[derive(Debug)]
-    pub struct Active {
-        pub(crate) r_thread: JoinHandle<Reader>,
+    pub struct Active<P: Profile = Trading, E: Environment = Unverified> {
+        pub(crate) r_thread: Option<JoinHandle<Reader>>,
         pub(crate) disconnect: tokio_util::sync::CancellationToken,
-        pub(crate) tx: mpsc::Sender<ToWrapper>,
-        pub(crate) rx: mpsc::Receiver<ToClient>,
-        pub(crate) managed_accounts: HashSet<String>,
+        pub(crate) reader_disconnect: tokio_util::sync::CancellationToken,
+        pub(crate) queue: std::sync::Arc<SegQueue<Vec<String>>>,
+        pub(crate) managed_accounts: HashSet<AccountId>,
         pub(crate) order_id: core::ops::RangeFrom<i64>,
+        pub(crate) order_id_store: Option<std::sync::Arc<dyn OrderIdStore>>,
+        pub(crate) order_id_reserved: i64,
         pub(crate) req_id: core::ops::RangeFrom<i64>,
+        pub(crate) req_id_reserved: Option<i64>,
+        pub(crate) replay: BTreeMap<Replay, Vec<u8>>,
+        pub(crate) auto_reconnect: bool,
+        pub(crate) resend_subscriptions: bool,
+        pub(crate) clock: std::sync::Arc<dyn Clock>,
+        pub(crate) shared: std::sync::Arc<Shared>,
+        pub(crate) profile: std::marker::PhantomData<P>,
+        pub(crate) environment: std::marker::PhantomData<E>,
     }
 
-    impl Status for Active {}
+    impl<P: Profile, E: Environment> Status for Active<P, E> {}
 }
 
 // =============================
@@ -1417,6 +2193,11 @@ pub struct Client<C: indicators::Status> {
     client_id: i64,
     server_version: u32,
     conn_time: chrono::NaiveDateTime,
+    mismatch_policy: ModeMismatchPolicy,
+    mode_mismatch: Option<ModeMismatch>,
+    contexts: Contexts,
+    transport: Transport,
+    socket_options: SocketOptions,
     writer: Writer,
     status: C,
 }
@@ -1427,7 +2208,8 @@ impl<S: indicators::Status> Client<S> {
     // ====================================================
 
     #[inline]
-    /// Return the client's mode, if it was created with [`Builder::from_config_file`].
+    /// Return the client's mode, if it was created with [`Builder::from_config_file`] or
+    /// [`Builder::discover`].
     ///
     /// # Returns
     /// The client's [`Mode`], if it exists; otherwise, [`None`].
@@ -1436,7 +2218,8 @@ impl<S: indicators::Status> Client<S> {
     }
 
     #[inline]
-    /// Return the client's host, if it was created with [`Builder::from_config_file`].
+    /// Return the client's host, if it was created with [`Builder::from_config_file`] or
+    /// [`Builder::discover`].
     ///
     /// # Returns
     /// The client's [`Host`], if it exists; otherwise, [`None`].
@@ -1474,26 +2257,72 @@ impl<S: indicators::Status> Client<S> {
     pub const fn get_server_version(&self) -> u32 {
         self.server_version
     }
+
+    #[inline]
+    /// Return the [`ModeMismatch`] detected for this client, if any.
+    ///
+    /// # Returns
+    /// The recorded [`ModeMismatch`] if the client was declared as [`Mode::Paper`] but is
+    /// connected to a live trading environment; otherwise, [`None`].
+    pub const fn get_mode_mismatch(&self) -> Option<&ModeMismatch> {
+        self.mode_mismatch.as_ref()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the client's store of user context, which attaches arbitrary values to request IDs.
+    /// The store carries over when the client is activated, so it can be cloned into a wrapper
+    /// beforehand.
+    pub fn get_contexts(&self) -> Contexts {
+        self.contexts.clone()
+    }
 }
 
 #[inline]
 fn spawn_reader_thread(
-    rdr: OwnedReadHalf,
-) -> (
-    CancellationToken,
-    Arc<SegQueue<Vec<String>>>,
-    JoinHandle<Reader>,
-) {
-    let disconnect = CancellationToken::new();
-    let queue = Arc::new(SegQueue::new());
-
-    let r_queue = Arc::clone(&queue);
+    rdr: ReadHalf,
+    queue: &Arc<SegQueue<Vec<String>>>,
+    disconnect: &CancellationToken,
+    shared: &Arc<Shared>,
+) -> JoinHandle<Reader> {
+    let r_queue = Arc::clone(queue);
     let r_disconnect = disconnect.clone();
-    let r_thread = tokio::spawn(async move {
-        let reader = Reader::new(rdr, r_queue, r_disconnect);
+    let r_shared = Arc::clone(shared);
+    let exit = ReaderExit {
+        disconnect: disconnect.clone(),
+        shared: Arc::clone(shared),
+    };
+    tokio::spawn(async move {
+        let _exit = exit;
+        let reader = Reader::new(rdr, r_queue, r_disconnect, r_shared);
         reader.run().await
-    });
-    (disconnect, queue, r_thread)
+    })
+}
+
+/// Reports the end of a reader thread that was not asked to disconnect when it is dropped, which
+/// happens whether the thread returns or panics.
+struct ReaderExit {
+    disconnect: CancellationToken,
+    shared: Arc<Shared>,
+}
+
+impl Drop for ReaderExit {
+    fn drop(&mut self) {
+        if !self.disconnect.is_cancelled() {
+            event!(error, "Reader thread stopped unexpectedly");
+            alert::set_health(&self.shared, ConnectionHealth::Disconnected);
+            self.shared.reader_lost.notify_one();
+        }
+    }
+}
+
+#[inline]
+/// Close the channels through which the client delivers events outside of its wrapper once the
+/// client loop stops, so that their consumers see the end of the events instead of waiting forever.
+fn close_channels(shared: &Shared) {
+    tracker::close_all(shared);
+    stream::close_all(shared);
+    account::close_fan_outs(shared);
 }
 
 impl Client<indicators::Inactive> {
@@ -1501,19 +2330,20 @@ impl Client<indicators::Inactive> {
     // === Methods That Initiate the API Loop ===
     // ==========================================
 
-    async fn start_api(&mut self) -> Result<(), anyhow::Error> {
-        const VERSION: u8 = 2;
-
-        self.writer
-            .add_body((Out::StartApi, VERSION, self.client_id, None::<()>))?;
-        self.writer.send().await?;
-        Ok(())
-    }
-
     #[allow(clippy::unwrap_used, clippy::missing_panics_doc)]
-    fn into_active(self) -> IntoActive {
-        let (disconnect, queue, r_thread) = spawn_reader_thread(self.status.reader);
+    fn into_active<P: indicators::Profile>(self) -> IntoActive<P> {
+        let disconnect = CancellationToken::new();
+        let reader_disconnect = disconnect.child_token();
+        let queue = Arc::new(SegQueue::new());
+        let shared = Arc::new(Shared::default());
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            shared.alerts.set_runtime(runtime);
+        }
+        let r_thread = spawn_reader_thread(self.status.reader, &queue, &reader_disconnect, &shared);
 
+        // Messages that arrive before the client is active are deferred rather than pushed back
+        // onto the queue, which would reorder them behind later messages
+        let mut deferred = std::collections::VecDeque::new();
         let (mut managed_accounts, mut valid_id) = (None, None);
         while managed_accounts.is_none() || valid_id.is_none() {
             if let Some(fields) = queue.pop() {
@@ -1524,7 +2354,8 @@ impl Client<indicators::Inactive> {
                                 .into_iter()
                                 .skip(2)
                                 .filter(|v| v.as_str() != "")
-                                .collect::<std::collections::HashSet<String>>(),
+                                .map(AccountId)
+                                .collect::<std::collections::HashSet<AccountId>>(),
                         );
                     }
                     Some(In::NextValidId) => {
@@ -1537,12 +2368,19 @@ impl Client<indicators::Inactive> {
                                     .ok()
                             });
                     }
-                    Some(_) => queue.push(fields),
-                    None => (),
+                    // Messages of an unknown type are also deferred so that the wrapper is
+                    // notified of them at the point at which they arrived
+                    _ => deferred.push_back(fields),
                 }
             }
         }
         let (managed_accounts, valid_id) = (managed_accounts.unwrap(), valid_id.unwrap()..);
+        let mode_mismatch = match self.mismatch_policy {
+            ModeMismatchPolicy::Ignore => None,
+            _ => self
+                .mode_mismatch
+                .or_else(|| ModeMismatch::from_accounts(self.mode, managed_accounts.iter())),
+        };
 
         let client = Client {
             mode: self.mode,
@@ -1552,112 +2390,272 @@ impl Client<indicators::Inactive> {
             client_id: self.client_id,
             server_version: self.server_version,
             conn_time: self.conn_time,
+            mismatch_policy: self.mismatch_policy,
+            mode_mismatch,
+            contexts: self.contexts,
+            transport: self.transport,
+            socket_options: self.socket_options,
             writer: self.writer,
             status: indicators::Active {
-                r_thread,
+                r_thread: Some(r_thread),
                 disconnect,
-                tx: self.status.client_tx,
-                rx: self.status.client_rx,
+                reader_disconnect,
+                queue: Arc::clone(&queue),
                 managed_accounts,
                 order_id: valid_id,
+                order_id_store: None,
+                order_id_reserved: 0,
                 req_id: 0_i64..,
+                req_id_reserved: None,
+                replay: std::collections::BTreeMap::new(),
+                auto_reconnect: false,
+                resend_subscriptions: false,
+                clock: Arc::new(SystemClock),
+                shared,
+                profile: std::marker::PhantomData,
+                environment: std::marker::PhantomData,
             },
         };
-        (
-            client,
-            self.status.wrapper_tx,
-            self.status.wrapper_rx,
-            queue,
-        )
+        (client, queue, deferred)
     }
 
     /// Initiates the main message loop and spawns all helper threads to manage the application.
     ///
+    /// Messages that arrive before the client is active are delivered to the wrapper once it has
+    /// been built, in the order in which they arrived.
+    ///
+    /// # Returns
+    /// A [`Builder`] that can be used to reconnect to the IBKR TWS API.
+    ///
+    /// # Errors
+    /// Any error that occurs in the [`Client<Active>::disconnect`] process. Also returns a
+    /// [`Error::ModeMismatch`] if a managed account indicates a live
+    /// trading environment while the policy is [`ModeMismatchPolicy::Refuse`].
+    pub async fn local<I: for<'c> Initializer<'c>>(self, init: I) -> Result<Builder, Error> {
+        self.run_local(init).await
+    }
+
+    /// Initiates the main message loop for a "data-only" client and spawns all helper threads to
+    /// manage the application. See [`DataClient`] for the differences from [`Client::local`].
+    ///
     /// # Returns
     /// A [`Builder`] that can be used to reconnect to the IBKR TWS API.
     ///
     /// # Errors
-    /// Any error that occurs in the [`Client<Active>::disconnect`] process
-    pub async fn local<I: for<'c> Initializer<'c>>(
+    /// Any error that occurs in the [`Client<Active>::disconnect`] process. Also returns a
+    /// [`Error::ModeMismatch`] if a managed account indicates a live
+    /// trading environment while the policy is [`ModeMismatchPolicy::Refuse`].
+    pub async fn local_data_only<I: for<'c> Initializer<'c, indicators::DataOnly>>(
         self,
         init: I,
-    ) -> Result<Builder, std::io::Error> {
-        let (mut client, mut tx, mut rx, queue) = self.into_active();
+    ) -> Result<Builder, Error> {
+        self.run_local(init).await
+    }
+
+    /// Initiates the main message loop and spawns all helper threads to manage the application.
+    ///
+    /// Messages that arrive before the client is active are delivered to the wrapper before any
+    /// later message, in the order in which they arrived.
+    ///
+    /// # Returns
+    /// An active [`Client`] that can be used to make API requests.
+    ///
+    /// # Errors
+    /// Returns an [`Error::ModeMismatch`] if a managed account indicates
+    /// a live trading environment while the policy is [`ModeMismatchPolicy::Refuse`]. In this case,
+    /// the connection is terminated.
+    pub fn remote<W: Remote + Send + 'static>(self, wrapper: W) -> Result<ActiveClient, Error> {
+        self.start_remote(wrapper)
+    }
+
+    /// Initiates the main message loop for a "data-only" client and spawns all helper threads to
+    /// manage the application. See [`DataClient`] for the differences from [`Client::remote`].
+    ///
+    /// # Returns
+    /// An active [`DataClient`] that can be used to make API requests.
+    ///
+    /// # Errors
+    /// Returns an [`Error::ModeMismatch`] if a managed account indicates
+    /// a live trading environment while the policy is [`ModeMismatchPolicy::Refuse`]. In this case,
+    /// the connection is terminated.
+    pub fn remote_data_only<W: Remote + Send + 'static>(
+        self,
+        wrapper: W,
+    ) -> Result<DataClient, Error> {
+        self.start_remote(wrapper)
+    }
+
+    async fn run_local<P, I>(self, init: I) -> Result<Builder, Error>
+    where
+        P: indicators::Profile,
+        I: for<'c> Initializer<'c, P>,
+    {
+        let (mut client, queue, mut deferred) = self.into_active::<P>();
+        if let Some(mismatch) = client.refused_mismatch() {
+            client.disconnect().await?;
+            return Err(mismatch.into());
+        }
 
-        let temp = CancellationToken::new();
-        let temp_2 = temp.clone();
-        let con_fut = tokio::spawn(async move {
+        // While the wrapper is being built, the responses that it may await are decoded as they
+        // arrive and every message is deferred, in order, until the wrapper can receive it
+        let shared = Arc::clone(&client.status.shared);
+        let break_loop = CancellationToken::new();
+        let wrapper = {
+            let build = Initializer::build(init, &mut client, break_loop.clone());
+            tokio::pin!(build);
             loop {
+                while let Some(fields) = queue.pop() {
+                    match decode::decode_msg_init(fields, &shared).await {
+                        Ok(Some(fields)) => deferred.push_back(fields),
+                        Ok(None) => (),
+                        Err(_e) => {
+                            event!(warn, error = %format!("{_e:#}"), "Failed to decode message");
+                        }
+                    }
+                }
                 tokio::select! {
-                    () = temp.cancelled() => { break (queue, tx, rx); },
-                    () = async {
-                        let _ = if let Some(fields) = queue.pop() {
-                            match fields.first().and_then(|t| t.parse().ok()) {
-                                Some(In::ContractData) => decode::decode_contract_no_wrapper(&mut fields.into_iter(), &mut tx, &mut rx).await.with_context(|| "contract data msg"),
-                                Some(_) => { queue.push(fields); Ok(()) },
-                                None => Ok(()),
-                            }
-                        } else { Ok(()) };
-                    } => ()
+                    wrapper = &mut build => break wrapper,
+                    () = shared.received.notified() => (),
                 }
             }
-        });
-
-        let break_loop = CancellationToken::new();
+        };
         let mut decoder = Decoder(LocalMarker {
-            wrapper: Initializer::build(init, &mut client, break_loop.clone()).await,
+            wrapper,
             _init_marker: &std::marker::PhantomData,
         });
-        temp_2.cancel();
-        let (queue, mut tx, mut rx) = con_fut.await?;
+        decoder.0.wrapper.connected().await;
+        for fields in deferred {
+            decode_msg_local(fields, &mut decoder, &shared).await;
+        }
 
         loop {
+            // Biased so that a lost connection is always reported before the reconnection
             tokio::select! {
+                biased;
                 () = break_loop.cancelled() => {
-                    println!("Client loop: disconnecting");
+                    event!(debug, "Client loop: disconnecting");
+                    decoder.0.wrapper.connection_closed().await;
                     break
                 },
+                () = shared.reader_lost.notified() => {
+                    decoder.0.wrapper.connection_lost().await;
+                },
+                () = shared.reconnected.notified() => {
+                    decoder.0.wrapper.connected().await;
+                },
+                timers = schedule::wait(&shared) => {
+                    for (timer_id, time) in timers {
+                        decoder.0.wrapper.timer(timer_id, time).await;
+                    }
+                },
                 () = async {
                     if let Some(fields) = queue.pop() {
-                        decode_msg_local(fields, &mut decoder, &mut tx, &mut rx).await;
+                        decode_msg_local(fields, &mut decoder, &shared).await;
+                    }
+                    if let Err(e) = Decoder::<LocalMarker<'_, _>>::deliver_batches(
+                        &mut decoder.0.wrapper,
+                        &shared,
+                    ).await {
+                        decoder.0.wrapper.decode_error(Error::Parse(format!("{e:#}"))).await;
                     }
                 } => (),
             }
         }
         drop(decoder);
+        close_channels(&shared);
         client.disconnect().await
     }
 
-    /// Initiates the main message loop and spawns all helper threads to manage the application.
-    ///
-    /// # Returns
-    /// An active [`Client`] that can be used to make API requests.
-    pub fn remote<W: Remote + Send + 'static>(self, wrapper: W) -> Client<indicators::Active> {
-        let (client, mut tx, mut rx, queue) = self.into_active();
+    fn start_remote<P, W>(self, wrapper: W) -> Result<Client<indicators::Active<P>>, Error>
+    where
+        P: indicators::Profile,
+        W: Remote + Send + 'static,
+    {
+        let (client, queue, deferred) = self.into_active::<P>();
+        if let Some(mismatch) = client.refused_mismatch() {
+            client.status.disconnect.cancel();
+            return Err(mismatch.into());
+        }
         let c_loop_disconnect = client.status.disconnect.clone();
+        let shared = Arc::clone(&client.status.shared);
         let mut decoder = Decoder(RemoteMarker { wrapper });
 
         tokio::spawn(async move {
+            decoder.0.wrapper.connected().await;
+            for fields in deferred {
+                decode_msg_remote(fields, &mut decoder, &shared).await;
+            }
             loop {
+                // Biased so that a lost connection is always reported before the reconnection
                 tokio::select! {
-                    () = c_loop_disconnect.cancelled() => {println!("Client loop: disconnecting"); break},
+                    biased;
+                    () = c_loop_disconnect.cancelled() => {
+                        event!(debug, "Client loop: disconnecting");
+                        decoder.0.wrapper.connection_closed().await;
+                        break
+                    },
+                    () = shared.reader_lost.notified() => {
+                        decoder.0.wrapper.connection_lost().await;
+                    },
+                    () = shared.reconnected.notified() => {
+                        decoder.0.wrapper.connected().await;
+                    },
+                    timers = schedule::wait(&shared) => {
+                        for (timer_id, time) in timers {
+                            decoder.0.wrapper.timer(timer_id, time).await;
+                        }
+                    },
                     () = async {
                             if let Some(fields) = queue.pop() {
-                                decode_msg_remote(fields, &mut decoder, &mut tx, &mut rx).await;
+                                decode_msg_remote(fields, &mut decoder, &shared).await;
+                            }
+                            if let Err(e) = Decoder::<RemoteMarker<_>>::deliver_batches(
+                                &mut decoder.0.wrapper,
+                                &shared,
+                            ).await {
+                                decoder.0.wrapper.decode_error(Error::Parse(format!("{e:#}"))).await;
                             }
                     } => (),
                 }
             }
+            close_channels(&shared);
         });
 
-        client
+        Ok(client)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A parent order that was placed by [`Client::req_place_parent_order`], but has not yet been
+/// transmitted.
+///
+/// The parent and its attached orders are transmitted by the final call to
+/// [`Client::req_attach_last_order`], which consumes the [`ParentOrder`].
+pub struct ParentOrder {
+    id: i64,
+    children: Vec<i64>,
+}
+
+impl ParentOrder {
+    #[inline]
+    #[must_use]
+    /// Return the ID of the parent order.
+    pub const fn get_id(&self) -> i64 {
+        self.id
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the IDs of the orders that have been attached so far.
+    pub fn get_children(&self) -> &[i64] {
+        &self.children
     }
 }
 
-type ReqResult = Result<(), std::io::Error>;
-type IdResult = Result<i64, std::io::Error>;
+type ReqResult = Result<(), Error>;
+type IdResult = Result<i64, Error>;
 
-impl Client<indicators::Active> {
+impl<P: indicators::Profile, N: indicators::Environment> Client<indicators::Active<P, N>> {
     // ====================================================
     // === Methods That Return Attributes of the Client ===
     // ====================================================
@@ -1665,23 +2663,37 @@ impl Client<indicators::Active> {
     // Don't worry about the allow: This function will NEVER panic
     #[inline]
     #[allow(clippy::missing_panics_doc, clippy::unwrap_used)]
-    /// Get the next valid *order* ID, as determined by the client's internal counter
+    /// Get the next valid *request* ID, as determined by the client's internal counter, or the ID
+    /// reserved by `reserve_req_id` if there is one
     ///
     /// # Returns
-    /// The next valid order ID
-    fn get_next_order_id(&mut self) -> i64 {
-        self.status.order_id.next().unwrap()
+    /// The next valid request ID
+    fn get_next_req_id(&mut self) -> i64 {
+        self.status
+            .req_id_reserved
+            .take()
+            .unwrap_or_else(|| self.status.req_id.next().unwrap())
     }
 
-    // Don't worry about the allow: This function will NEVER panic
     #[inline]
-    #[allow(clippy::missing_panics_doc, clippy::unwrap_used)]
-    /// Get the next valid *request* ID, as determined by the client's internal counter
+    /// Reserve the ID of the next request, so that its answer can be registered before a method
+    /// that makes the request is called. The next request that the client makes takes the
+    /// reserved ID.
     ///
     /// # Returns
-    /// The next valid request ID
-    fn get_next_req_id(&mut self) -> i64 {
-        self.status.req_id.next().unwrap()
+    /// The reserved request ID
+    fn reserve_req_id(&mut self) -> i64 {
+        let req_id = self.get_next_req_id();
+        self.status.req_id_reserved = Some(req_id);
+        req_id
+    }
+
+    #[inline]
+    fn refused_mismatch(&self) -> Option<ModeMismatch> {
+        match self.mismatch_policy {
+            ModeMismatchPolicy::Refuse => self.mode_mismatch.clone(),
+            _ => None,
+        }
     }
 
     #[inline]
@@ -1690,10 +2702,302 @@ impl Client<indicators::Active> {
     ///
     /// # Returns
     /// A reference to the set of the client's managed accounts
-    pub const fn get_managed_accounts(&self) -> &std::collections::HashSet<String> {
+    pub const fn get_managed_accounts(&self) -> &std::collections::HashSet<AccountId> {
         &self.status.managed_accounts
     }
 
+    #[must_use]
+    /// Get a summary of the commission reports received by the client, aggregated by trading day
+    /// and by contract.
+    ///
+    /// Reports are included once both the report and its corresponding execution have been
+    /// received, whether from a call to [`Client::req_executions`] or from an order being filled.
+    ///
+    /// # Returns
+    /// A snapshot of the [`CommissionSummary`] at the time of the call.
+    pub fn get_commission_summary(&self) -> CommissionSummary {
+        self.status
+            .shared
+            .commissions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    #[must_use]
+    /// Get the blotter of all the fills received by the client during the session, joined with
+    /// their commission reports.
+    ///
+    /// # Returns
+    /// A snapshot of the [`Blotter`] at the time of the call.
+    pub fn get_blotter(&self) -> Blotter {
+        self.status
+            .shared
+            .blotter
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    #[inline]
+    /// Set the size above which batches of historical bars and ticks are decoded on Tokio's
+    /// blocking thread pool instead of inline in the client loop.
+    ///
+    /// Decoding a large download can take long enough to delay the market data that is received
+    /// behind it. An offloaded batch is decoded while the client loop continues to process other
+    /// messages, and it is delivered to its callback once decoding finishes. Consequently, it may
+    /// be delivered after messages that were received after it. The size of the pool is
+    /// configured through the Tokio runtime (see [`tokio::runtime::Builder::max_blocking_threads`]).
+    ///
+    /// # Arguments
+    /// * `threshold` - The minimum number of bars or ticks in a batch for it to be offloaded. If
+    /// [`None`], batches are always decoded inline, which is the default.
+    pub fn set_decode_offload_threshold(&self, threshold: Option<usize>) {
+        self.status.shared.decode_offload_threshold.store(
+            threshold.map_or(0, |t| t.max(1)),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+
+    /// Set whether executions are resolved against their full contract definitions before they are
+    /// delivered.
+    ///
+    /// When enabled, each execution is delivered to [`Remote::resolved_execution`] (or
+    /// [`Local::resolved_execution`]) together with the [`Contract`] to which it belongs, rather
+    /// than to [`Remote::execution`]. Contracts are looked up in the client's contract cache, which
+    /// holds the most recent contracts created with [`crate::contract::new`] or received from
+    /// IBKR. Executions whose contract is not yet cached are held while a background task requests
+    /// the contract, and are delivered as soon as it arrives. Consequently, resolved executions
+    /// may be delivered after [`Remote::execution_end`] is called for the request to which they
+    /// belong. Should a request fail, the executions remain held until
+    /// [`Client::resolve_execution_contracts`] is called.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to resolve executions.
+    pub fn set_execution_resolution(&self, enabled: bool) {
+        const VERSION: u8 = 8;
+
+        let mut resolver = self
+            .status
+            .shared
+            .execution_resolver
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if !enabled {
+            *resolver = None;
+            return;
+        }
+        if resolver.as_ref().is_some_and(|tx| !tx.is_closed()) {
+            return;
+        }
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<ContractId>();
+        *resolver = Some(tx);
+        let cancel = self.status.disconnect.child_token();
+        let mut writer = self.writer.fork();
+
+        tokio::spawn(async move {
+            // The requests use IDs of their own, so that they never collide with the client's
+            let mut req_ids = constants::RESOLVER_REQ_ID_START..;
+            loop {
+                let contract_id = tokio::select! {
+                    () = cancel.cancelled() => break,
+                    contract_id = rx.recv() => match contract_id {
+                        Some(contract_id) => contract_id,
+                        None => break,
+                    },
+                };
+                let req_id = req_ids.next().unwrap_or(constants::RESOLVER_REQ_ID_START);
+                let result = match writer.add_body((
+                    Out::ReqContractData,
+                    VERSION,
+                    req_id,
+                    contract_id,
+                    [None::<()>; 15],
+                )) {
+                    Ok(()) => writer.send().await,
+                    Err(e) => Err(e.into()),
+                };
+                if let Err(_e) = result {
+                    event!(
+                        warn,
+                        error = %_e,
+                        contract_id = contract_id.0,
+                        "Failed to request the contract of an execution"
+                    );
+                }
+            }
+        });
+    }
+
+    #[inline]
+    /// Set the rate at which the client sends messages to IBKR, replacing the limit set with
+    /// [`Builder::with_rate_limit`]. The limit also applies to the client's heartbeat.
+    ///
+    /// # Arguments
+    /// * `limit` - The [`RateLimit`] to apply, or [`None`] to send messages without limit.
+    pub fn set_rate_limit(&self, limit: Option<RateLimit>) {
+        self.writer.set_rate_limit(limit);
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the rate at which the client sends messages to IBKR.
+    pub fn get_rate_limit(&self) -> Option<RateLimit> {
+        self.writer.get_rate_limit()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the options that tune the client's TCP connection, as set by
+    /// [`Builder::with_socket_options`].
+    pub const fn get_socket_options(&self) -> SocketOptions {
+        self.socket_options
+    }
+
+    #[must_use]
+    /// Get a contract from the client's contract cache, which holds up to 10,000 of the contracts
+    /// received from IBKR and evicts the earliest of them first.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract to get.
+    ///
+    /// # Returns
+    /// The cached [`Contract`], if it has previously been created with [`crate::contract::new`].
+    pub fn get_cached_contract(&self, contract_id: ContractId) -> Option<Contract> {
+        self.status
+            .shared
+            .contracts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&contract_id)
+            .cloned()
+    }
+
+    #[must_use]
+    /// Get the additional details that IBKR reported alongside a contract's definition, such as its
+    /// minimum order size.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract whose details to get.
+    ///
+    /// # Returns
+    /// The contract's details, if the contract has previously been created with
+    /// [`crate::contract::new`] or [`Client::resolve_contracts`].
+    pub fn get_contract_details(&self, contract_id: ContractId) -> Option<ContractDetails> {
+        self.status
+            .shared
+            .contract_details
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&contract_id)
+            .cloned()
+    }
+
+    #[must_use]
+    /// Get the time zone of the exchange on which a contract trades. See [`crate::timezone`] for
+    /// utilities that use it to convert bar and tick timestamps.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract whose time zone to get.
+    ///
+    /// # Returns
+    /// The exchange's time zone, if the contract has previously been created with
+    /// [`crate::contract::new`] and IBKR reported a time zone that could be recognized.
+    pub fn get_time_zone(&self, contract_id: ContractId) -> Option<Tz> {
+        self.status
+            .shared
+            .time_zones
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&contract_id)
+            .copied()
+    }
+
+    #[must_use]
+    /// Get the trading hours of a contract, which IBKR reports for the current day and the
+    /// following few days.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract whose trading hours to get.
+    ///
+    /// # Returns
+    /// The contract's trading hours, if the contract has previously been created with
+    /// [`crate::contract::new`] and IBKR reported hours that could be parsed.
+    pub fn get_trading_hours(&self, contract_id: ContractId) -> Option<calendar::TradingHours> {
+        self.status
+            .shared
+            .trading_hours
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&contract_id)
+            .cloned()
+    }
+
+    #[must_use]
+    /// Get the time at which a contract's next trading session opens, so that requests that
+    /// cannot be answered while the market is closed can wait until then.
+    ///
+    /// The contract's trading hours (see [`Client::get_trading_hours`]) are used if they cover
+    /// the next session. Otherwise, the bundled [`calendar::Calendar`] of the contract's primary
+    /// exchange is used.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract.
+    /// * `after` - The time after which to look.
+    ///
+    /// # Returns
+    /// The time, or [`None`] if the contract has not been created with [`crate::contract::new`] or
+    /// neither source covers the next session.
+    pub fn next_session_open(
+        &self,
+        contract_id: ContractId,
+        after: chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.get_trading_hours(contract_id)
+            .and_then(|hours| hours.next_session_open(after))
+            .or_else(|| {
+                let exchange = self
+                    .status
+                    .shared
+                    .contracts
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .get(&contract_id)?
+                    .get_primary_exchange()?;
+                calendar::next_session_open(exchange, after)
+            })
+    }
+
+    /// Create the contracts of any executions that are being held for resolution, so that they can
+    /// be delivered with their full contract definitions. See
+    /// [`Client::set_execution_resolution`] for details.
+    ///
+    /// # Errors
+    /// Returns any error encountered while creating the contracts.
+    pub async fn resolve_execution_contracts(&mut self) -> ReqResult {
+        let contract_ids = self
+            .status
+            .shared
+            .unresolved_executions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .map(|(_, execution)| execution.contract_id)
+            .collect::<std::collections::HashSet<_>>();
+        // Every query is sent before any is awaited, so that the lookups run concurrently
+        let mut req_ids = Vec::with_capacity(contract_ids.len());
+        for contract_id in contract_ids {
+            req_ids.push(
+                self.send_contract_query(&ContractQuery::Id(contract_id))
+                    .await?,
+            );
+        }
+        for req_id in req_ids {
+            self.recv_contract_query(req_id).await?;
+        }
+        Ok(())
+    }
+
     // ===================================
     // === Methods That Make API Calls ===
     // ===================================
@@ -1708,7 +3012,49 @@ impl Client<indicators::Active> {
         const VERSION: u8 = 1;
 
         self.writer.add_body((Out::ReqCurrentTime, VERSION))?;
-        self.writer.send().await
+        lock_current_time_requests(&self.status.shared).push_back(CurrentTimeRequest::User);
+        let result = self.send().await;
+        if result.is_err() {
+            let mut requests = lock_current_time_requests(&self.status.shared);
+            if let Some(i) = requests
+                .iter()
+                .rposition(|request| *request == CurrentTimeRequest::User)
+            {
+                requests.remove(i);
+            }
+        }
+        result
+    }
+
+    /// Request the current time from the server and wait for the answer, which is also delivered
+    /// to the wrapper's `current_time` callback as usual.
+    ///
+    /// # Arguments
+    /// * `timeout` - The maximum amount of time to wait for the answer.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Also returns
+    /// [`Error::Timeout`] if no answer is received before the timeout elapses.
+    ///
+    /// # Returns
+    /// The server's current time.
+    pub async fn req_current_time_await(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let shared = Arc::clone(&self.status.shared);
+        shared.current_times.register(());
+        if let Err(e) = self.req_current_time().await {
+            shared.current_times.cancel(&());
+            return Err(e);
+        }
+        shared
+            .current_times
+            .wait(&(), deadline)
+            .await
+            .map(|datetime| datetime.and_utc())
+            .ok_or(Error::Timeout)
     }
 
     /// Requests the accounts to which the logged user has access to.
@@ -1719,7 +3065,7 @@ impl Client<indicators::Active> {
         const VERSION: u8 = 1;
 
         self.writer.add_body((Out::ReqManagedAccts, VERSION))?;
-        self.writer.send().await
+        self.send().await
     }
 
     /// Creates a subscription to the TWS through which account and portfolio information is
@@ -1733,15 +3079,16 @@ impl Client<indicators::Active> {
     /// # Errors
     /// Returns any error encountered while writing the outgoing message. Additionally, returns an
     /// error if a provided `account_number` is not in the client's managed accounts.
-    pub async fn req_account_updates(&mut self, account_number: Option<String>) -> ReqResult {
+    pub async fn req_account_updates(&mut self, account_number: Option<AccountId>) -> ReqResult {
         const VERSION: u8 = 2;
         if let Some(acct_num) = &account_number {
-            check_valid_account(self, acct_num)?;
+            check_valid_account(self, acct_num, "req_account_updates")?;
         }
 
         self.writer
-            .add_body((Out::ReqAcctData, VERSION, 1, account_number))?;
-        self.writer.send().await
+            .add_body((Out::ReqAcctData, VERSION, 1, &account_number))?;
+        self.send_and_replay(Replay::AccountUpdates(account_number))
+            .await
     }
 
     /// Cancels an existing subscription to receive account updates.
@@ -1753,44 +3100,47 @@ impl Client<indicators::Active> {
     /// # Errors
     /// Returns any error encountered while writing the outgoing message. Additionally, returns an
     /// error if a provided `account_number` is not in the client's managed accounts.
-    pub async fn cancel_account_updates(&mut self, account_number: Option<String>) -> ReqResult {
+    pub async fn cancel_account_updates(&mut self, account_number: Option<AccountId>) -> ReqResult {
         const VERSION: u8 = 2;
         if let Some(acct_num) = &account_number {
-            check_valid_account(self, acct_num)?;
+            check_valid_account(self, acct_num, "cancel_account_updates")?;
         }
 
+        self.status
+            .replay
+            .remove(&Replay::AccountUpdates(account_number.clone()));
+        {
+            let mut received = self
+                .status
+                .shared
+                .account_positions_received
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            match &account_number {
+                Some(acct_num) => {
+                    received.remove(acct_num);
+                }
+                None => received.clear(),
+            }
+        }
         self.writer
             .add_body((Out::ReqAcctData, VERSION, 0, account_number))?;
-        self.writer.send().await
-    }
-
-    /// Subscribes to position updates for all accessible accounts. All positions sent initially,
-    /// and then only updates as positions change.
-    ///
-    /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
-    pub async fn req_positions(&mut self) -> ReqResult {
-        const VERSION: u8 = 1;
-
-        self.writer.add_body((Out::ReqPositions, VERSION))?;
-        self.writer.send().await
+        self.send().await
     }
 
-    /// Cancels a previous position subscription request made with [`Client::req_positions`].
+    /// Creates a subscription to the account values of a single account or model portfolio, such
+    /// as to follow the model portfolios of a financial advisor. Unlike
+    /// [`Client::req_account_updates`], many of these subscriptions may be active at once.
     ///
-    /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
-    pub async fn cancel_positions(&mut self) -> ReqResult {
-        const VERSION: u8 = 1;
-
-        self.writer.add_body((Out::CancelPositions, VERSION))?;
-        self.writer.send().await
-    }
-
-    /// Creates subscription for real time daily P&L and unrealized P&L updates.
+    /// The values are delivered to the [`crate::wrapper::Local::account_update_multi`] (or
+    /// [`crate::wrapper::Remote::account_update_multi`]) callback.
     ///
     /// # Arguments
-    /// * `account_number` - The account number with which to create the subscription.
+    /// * `account_number` - The account whose values to receive, or [`None`] for every account.
+    /// * `model_code` - The model portfolio whose values to receive (for financial advisors), or
+    ///   [`None`] for no particular model.
+    /// * `ledger_and_nlv` - When [`true`], only receive the account's cash ledger and net
+    ///   liquidation value, rather than every value.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message. Additionally, returns an
@@ -1798,49 +3148,346 @@ impl Client<indicators::Active> {
     ///
     /// # Returns
     /// Returns the unique ID associated with the request.
-    pub async fn req_pnl(&mut self, account_number: String) -> IdResult {
+    pub async fn req_account_updates_multi(
+        &mut self,
+        account_number: Option<AccountId>,
+        model_code: Option<&str>,
+        ledger_and_nlv: bool,
+    ) -> IdResult {
+        const VERSION: u8 = 1;
+        if let Some(acct_num) = &account_number {
+            check_valid_account(self, acct_num, "req_account_updates_multi")?;
+        }
         let req_id = self.get_next_req_id();
-        check_valid_account(self, &account_number)?;
 
-        self.writer
-            .add_body((Out::ReqPnl, req_id, account_number, None::<()>))?;
-        self.writer.send().await?;
+        self.writer.add_body((
+            Out::ReqAccountUpdatesMulti,
+            VERSION,
+            req_id,
+            account_number,
+            model_code,
+            ledger_and_nlv,
+        ))?;
+        self.send_and_replay(Replay::Request(req_id, Out::ReqAccountUpdatesMulti))
+            .await?;
         Ok(req_id)
     }
 
-    /// Cancel subscription for real-time updates created by [`Client::req_pnl`]
+    /// Cancel a subscription created by [`Client::req_account_updates_multi`].
     ///
     /// # Arguments
-    /// * `req_id` - The ID of the [`Client::req_pnl`] subscription to cancel.
+    /// * `req_id` - The ID of the [`Client::req_account_updates_multi`] subscription to cancel.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
-    pub async fn cancel_pnl(&mut self, req_id: i64) -> ReqResult {
-        self.writer.add_body((Out::CancelPnl, req_id))?;
-        self.writer.send().await
+    pub async fn cancel_account_updates_multi(&mut self, req_id: i64) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqAccountUpdatesMulti));
+        self.writer
+            .add_body((Out::CancelAccountUpdatesMulti, VERSION, req_id))?;
+        self.send().await
     }
 
-    /// Creates subscription for real time daily P&L and unrealized P&L updates, but only for a
-    /// specific position.
+    #[inline]
+    #[must_use]
+    /// Return a receiver that is notified whenever one of an account's values changes. The values
+    /// are only updated while a [`Client::req_account_updates`] subscription for the account is
+    /// active.
     ///
     /// # Arguments
-    /// * `account_number` - The account number with which to create the subscription.
-    /// * `contract_id` - The contract ID to create a subscription to changes for a specific
-    /// security
+    /// * `account_number` - The account whose value to watch.
+    /// * `key` - The account value to watch.
     ///
-    /// # Errors
-    /// Returns any error encountered while writing the outgoing message. Additionally, returns an
+    /// # Returns
+    /// A receiver holding the most recent [`ValueUpdate`], or [`None`] if the value has not yet
+    /// been received.
+    pub fn watch_account_value(
+        &self,
+        account_number: &AccountId,
+        key: ValueKey,
+    ) -> tokio::sync::watch::Receiver<Option<ValueUpdate>> {
+        self.status
+            .shared
+            .account_values
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .entry((account_number.clone(), key))
+            .or_insert_with(|| tokio::sync::watch::channel(None).0)
+            .subscribe()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get a handle to the client's alert hooks, with which hooks are registered that run when a
+    /// critical event occurs (ex: the connection is lost or an order is rejected).
+    pub fn get_alerts(&self) -> Alerts {
+        self.status.shared.alerts.clone()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return a receiver that is notified whenever one of an account's positions changes, such as
+    /// when its market price is updated. The position is only updated while a
+    /// [`Client::req_account_updates`] subscription for the account is active.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account that holds the position.
+    /// * `contract_id` - The ID of the position's contract.
+    ///
+    /// # Returns
+    /// A receiver holding the most recent [`Position`], whose size is zero once the position is
+    /// closed, or [`None`] if the position has not yet been received.
+    pub fn watch_position(
+        &self,
+        account_number: &AccountId,
+        contract_id: ContractId,
+    ) -> tokio::sync::watch::Receiver<Option<Position>> {
+        self.status
+            .shared
+            .position_values
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .entry((account_number.clone(), contract_id))
+            .or_insert_with(|| tokio::sync::watch::channel(None).0)
+            .subscribe()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the most recently received value of one of an account's values.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account whose value to return.
+    /// * `key` - The account value to return.
+    ///
+    /// # Returns
+    /// The most recent [`ValueUpdate`], if the value has been received.
+    pub fn get_account_value(
+        &self,
+        account_number: &AccountId,
+        key: ValueKey,
+    ) -> Option<ValueUpdate> {
+        self.status
+            .shared
+            .account_values
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&(account_number.clone(), key))
+            .and_then(|tx| *tx.borrow())
+    }
+
+    #[must_use]
+    /// Aggregate an account's cash balances and the market values of its positions into its
+    /// exposure to each currency. The balances and positions are only updated while a
+    /// [`Client::req_account_updates`] subscription for the account is active.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account whose exposure to report.
+    ///
+    /// # Returns
+    /// The [`ExposureReport`] of the account, which can be converted into a single currency with
+    /// a set of [`crate::exposure::FxRates`].
+    pub fn currency_exposure(&self, account_number: &AccountId) -> ExposureReport {
+        let mut exposures = std::collections::HashMap::<Currency, CurrencyExposure>::new();
+        for ((acct, currency), cash) in self
+            .status
+            .shared
+            .cash_balances
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+        {
+            if acct == account_number {
+                exposures.entry(*currency).or_default().cash += cash;
+            }
+        }
+        for ((acct, _), (currency, market_value)) in self
+            .status
+            .shared
+            .portfolio
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+        {
+            if acct == account_number {
+                exposures.entry(*currency).or_default().positions += market_value;
+            }
+        }
+        ExposureReport::new(account_number.clone(), exposures)
+    }
+
+    #[must_use]
+    /// Get an account's position in a contract. Positions are only updated while a
+    /// [`Client::req_positions`] or [`Client::req_account_updates`] subscription is active.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account that holds the position.
+    /// * `contract_id` - The ID of the position's contract.
+    ///
+    /// # Returns
+    /// The size of the position, which is negative for a short position, or [`None`] if the
+    /// account holds no position in the contract.
+    pub fn get_position(
+        &self,
+        account_number: &AccountId,
+        contract_id: ContractId,
+    ) -> Option<Quantity> {
+        self.status
+            .shared
+            .positions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&(account_number.clone(), contract_id))
+            .map(|holding| holding.position)
+    }
+
+    /// Subscribes to position updates for all accessible accounts. All positions sent initially,
+    /// and then only updates as positions change.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn req_positions(&mut self) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.writer.add_body((Out::ReqPositions, VERSION))?;
+        self.send_and_replay(Replay::Positions).await
+    }
+
+    /// Cancels a previous position subscription request made with [`Client::req_positions`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_positions(&mut self) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.status.replay.remove(&Replay::Positions);
+        self.status
+            .shared
+            .all_positions_received
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+        self.writer.add_body((Out::CancelPositions, VERSION))?;
+        self.send().await
+    }
+
+    /// Subscribes to position updates for a single account or model portfolio, or for all of
+    /// them. All positions are sent initially, and then only updates as positions change.
+    ///
+    /// The positions are delivered to the [`crate::wrapper::Local::position_multi`] (or
+    /// [`crate::wrapper::Remote::position_multi`]) callback.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account whose positions to receive, or [`None`] for every account.
+    /// * `model_code` - The model portfolio whose positions to receive (for financial advisors), or
+    ///   [`None`] for no particular model.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Additionally, returns an
+    /// error if a provided `account_number` is not in the client's managed accounts.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_positions_multi(
+        &mut self,
+        account_number: Option<AccountId>,
+        model_code: Option<&str>,
+    ) -> IdResult {
+        const VERSION: u8 = 1;
+        if let Some(acct_num) = &account_number {
+            check_valid_account(self, acct_num, "req_positions_multi")?;
+        }
+        let req_id = self.get_next_req_id();
+
+        self.writer.add_body((
+            Out::ReqPositionsMulti,
+            VERSION,
+            req_id,
+            account_number,
+            model_code,
+        ))?;
+        self.send_and_replay(Replay::Request(req_id, Out::ReqPositionsMulti))
+            .await?;
+        Ok(req_id)
+    }
+
+    /// Cancel a subscription created by [`Client::req_positions_multi`].
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the [`Client::req_positions_multi`] subscription to cancel.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_positions_multi(&mut self, req_id: i64) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqPositionsMulti));
+        self.writer
+            .add_body((Out::CancelPositionsMulti, VERSION, req_id))?;
+        self.send().await
+    }
+
+    /// Creates subscription for real time daily P&L and unrealized P&L updates.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account number with which to create the subscription.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Additionally, returns an
+    /// error if a provided `account_number` is not in the client's managed accounts.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_pnl(&mut self, account_number: AccountId) -> IdResult {
+        check_valid_account(self, &account_number, "req_pnl")?;
+        let req_id = self.get_next_req_id();
+
+        self.writer
+            .add_body((Out::ReqPnl, req_id, account_number, None::<()>))?;
+        self.send_and_replay(Replay::Request(req_id, Out::ReqPnl))
+            .await?;
+        Ok(req_id)
+    }
+
+    /// Cancel subscription for real-time updates created by [`Client::req_pnl`]
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the [`Client::req_pnl`] subscription to cancel.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_pnl(&mut self, req_id: i64) -> ReqResult {
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqPnl));
+        self.writer.add_body((Out::CancelPnl, req_id))?;
+        self.send().await
+    }
+
+    /// Creates subscription for real time daily P&L and unrealized P&L updates, but only for a
+    /// specific position.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account number with which to create the subscription.
+    /// * `contract_id` - The contract ID to create a subscription to changes for a specific
+    /// security
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Additionally, returns an
     /// error if a provided `account_number` is not in the client's managed accounts.
     ///
     /// # Returns
     /// Returns the unique ID associated with the request.
     pub async fn req_single_position_pnl(
         &mut self,
-        account_number: String,
+        account_number: AccountId,
         contract_id: ContractId,
     ) -> IdResult {
+        check_valid_account(self, &account_number, "req_single_position_pnl")?;
         let req_id = self.get_next_req_id();
-        check_valid_account(self, &account_number)?;
 
         self.writer.add_body((
             Out::ReqPnlSingle,
@@ -1849,7 +3496,8 @@ impl Client<indicators::Active> {
             None::<()>,
             contract_id,
         ))?;
-        self.writer.send().await?;
+        self.send_and_replay(Replay::Request(req_id, Out::ReqPnlSingle))
+            .await?;
         Ok(req_id)
     }
 
@@ -1861,8 +3509,170 @@ impl Client<indicators::Active> {
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
     pub async fn cancel_pnl_single(&mut self, req_id: i64) -> ReqResult {
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqPnlSingle));
         self.writer.add_body((Out::CancelPnl, req_id))?;
-        self.writer.send().await
+        self.send().await
+    }
+
+    // === Managed Account Fan-Out ===
+
+    /// Creates a subscription for real time daily P&L and unrealized P&L updates for every one of
+    /// the client's managed accounts, as in [`Client::req_pnl`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    ///
+    /// # Returns
+    /// Returns a [`FanOut`] that delivers each update as an [`account::AccountEvent::Pnl`] along
+    /// with the account to which it belongs.
+    pub async fn req_pnl_all_accounts(&mut self) -> Result<FanOut, Error> {
+        let mut fan_out = self.fan_out();
+        for account_number in self.status.managed_accounts.clone() {
+            fan_out.register(self.reserve_req_id(), Some(account_number.clone()));
+            self.req_pnl(account_number).await?;
+        }
+        Ok(fan_out.seal())
+    }
+
+    /// Cancel all the subscriptions created by [`Client::req_pnl_all_accounts`].
+    ///
+    /// # Arguments
+    /// * `fan_out` - The [`FanOut`] returned by [`Client::req_pnl_all_accounts`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    pub async fn cancel_pnl_all_accounts(&mut self, fan_out: &FanOut) -> ReqResult {
+        for req_id in fan_out.req_ids() {
+            self.cancel_pnl(req_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Creates a subscription for real time daily P&L and unrealized P&L updates for a specific
+    /// position in every one of the client's managed accounts, as in
+    /// [`Client::req_single_position_pnl`].
+    ///
+    /// # Arguments
+    /// * `contract_id` - The contract ID to create a subscription to changes for a specific
+    /// security
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    ///
+    /// # Returns
+    /// Returns a [`FanOut`] that delivers each update as an
+    /// [`account::AccountEvent::PositionPnl`] along with the account to which it belongs.
+    pub async fn req_single_position_pnl_all_accounts(
+        &mut self,
+        contract_id: ContractId,
+    ) -> Result<FanOut, Error> {
+        let mut fan_out = self.fan_out();
+        for account_number in self.status.managed_accounts.clone() {
+            fan_out.register(self.reserve_req_id(), Some(account_number.clone()));
+            self.req_single_position_pnl(account_number, contract_id)
+                .await?;
+        }
+        Ok(fan_out.seal())
+    }
+
+    /// Cancel all the subscriptions created by [`Client::req_single_position_pnl_all_accounts`].
+    ///
+    /// # Arguments
+    /// * `fan_out` - The [`FanOut`] returned by [`Client::req_single_position_pnl_all_accounts`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    pub async fn cancel_single_position_pnl_all_accounts(&mut self, fan_out: &FanOut) -> ReqResult {
+        for req_id in fan_out.req_ids() {
+            self.cancel_pnl_single(req_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Subscribes to position updates for every one of the client's managed accounts, with a
+    /// separate request for each account, as in [`Client::req_positions_multi`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    ///
+    /// # Returns
+    /// Returns a [`FanOut`] that delivers each position as an [`account::AccountEvent::Position`]
+    /// along with the account to which it belongs, followed by an [`account::AccountEvent::End`]
+    /// for each account once its initial positions have been delivered.
+    pub async fn req_positions_all_accounts(&mut self) -> Result<FanOut, Error> {
+        let mut fan_out = self.fan_out();
+        for account_number in self.status.managed_accounts.clone() {
+            fan_out.register(self.reserve_req_id(), Some(account_number.clone()));
+            self.req_positions_multi(Some(account_number), None).await?;
+        }
+        Ok(fan_out.seal())
+    }
+
+    /// Cancel all the subscriptions created by [`Client::req_positions_all_accounts`].
+    ///
+    /// # Arguments
+    /// * `fan_out` - The [`FanOut`] returned by [`Client::req_positions_all_accounts`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    pub async fn cancel_positions_all_accounts(&mut self, fan_out: &FanOut) -> ReqResult {
+        for req_id in fan_out.req_ids() {
+            self.cancel_positions_multi(req_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Request summary information about every one of the client's managed accounts, as in
+    /// [`Client::req_account_summary`].
+    ///
+    /// A single request already covers every account, so only one request is made.
+    ///
+    /// # Arguments
+    /// * `tags` - The list of data tags to include in the subscription.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns a [`FanOut`] that delivers each value as an [`account::AccountEvent::Summary`]
+    /// along with the account to which it belongs, followed by an [`account::AccountEvent::End`]
+    /// for each account once the initial values have been delivered.
+    pub async fn req_account_summary_all_accounts(
+        &mut self,
+        tags: &Vec<Tag>,
+    ) -> Result<FanOut, Error> {
+        let mut fan_out = self.fan_out();
+        fan_out.register(self.reserve_req_id(), None);
+        self.req_account_summary(tags).await?;
+        Ok(fan_out.seal())
+    }
+
+    /// Cancel the subscription created by [`Client::req_account_summary_all_accounts`].
+    ///
+    /// # Arguments
+    /// * `fan_out` - The [`FanOut`] returned by [`Client::req_account_summary_all_accounts`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_account_summary_all_accounts(&mut self, fan_out: &FanOut) -> ReqResult {
+        for req_id in fan_out.req_ids() {
+            self.cancel_account_summary(req_id).await?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn fan_out(&self) -> FanOut {
+        let mut accounts = self
+            .status
+            .managed_accounts
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        accounts.sort();
+        FanOut::new(accounts, &self.status.shared)
     }
 
     /// Request completed orders.
@@ -1874,7 +3684,7 @@ impl Client<indicators::Active> {
     /// Returns any error encountered while writing the outgoing message.
     pub async fn req_completed_orders(&mut self, api_only: bool) -> ReqResult {
         self.writer.add_body((Out::ReqCompletedOrders, api_only))?;
-        self.writer.send().await
+        self.send().await
     }
 
     /// Request summary information about a specific account, creating a subscription to the same
@@ -1894,7 +3704,8 @@ impl Client<indicators::Active> {
 
         self.writer
             .add_body((Out::ReqAccountSummary, VERSION, req_id, "All", tags))?;
-        self.writer.send().await?;
+        self.send_and_replay(Replay::Request(req_id, Out::ReqAccountSummary))
+            .await?;
         Ok(req_id)
     }
 
@@ -1908,9 +3719,12 @@ impl Client<indicators::Active> {
     pub async fn cancel_account_summary(&mut self, req_id: i64) -> ReqResult {
         const VERSION: u8 = 1;
 
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqAccountSummary));
         self.writer
             .add_body((Out::CancelAccountSummary, VERSION, req_id))?;
-        self.writer.send().await
+        self.send().await
     }
 
     /// Request user info details for the user associated with the calling client.
@@ -1924,10 +3738,43 @@ impl Client<indicators::Active> {
         let req_id = self.get_next_req_id();
 
         self.writer.add_body((Out::ReqUserInfo, req_id))?;
-        self.writer.send().await?;
+        self.send().await?;
         Ok(req_id)
     }
 
+    /// Request user info details for the user associated with the calling client and wait for
+    /// the answer, which is also delivered to the wrapper's `user_info` callback as usual.
+    ///
+    /// # Arguments
+    /// * `timeout` - The maximum amount of time to wait for the answer.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Also returns
+    /// [`Error::Timeout`] if no answer is received before the timeout elapses.
+    ///
+    /// # Returns
+    /// The white branding ID of the user, which is empty for users of IBKR's own platforms.
+    pub async fn req_user_info_await(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> Result<String, Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let shared = Arc::clone(&self.status.shared);
+        // The answer must be registered before the request is sent so that it is not missed, so
+        // the request ID is reserved here rather than in `req_user_info`.
+        let req_id = self.reserve_req_id();
+        shared.user_infos.register(req_id);
+        if let Err(e) = self.req_user_info().await {
+            shared.user_infos.cancel(&req_id);
+            return Err(e);
+        }
+        shared
+            .user_infos
+            .wait(&req_id, deadline)
+            .await
+            .ok_or(Error::Timeout)
+    }
+
     // === Historical Market Data ===
 
     /// Request historical bar data for a given security. See [`historical_bar`] for
@@ -1960,23 +3807,258 @@ impl Client<indicators::Active> {
         S: Security,
         D: historical_bar::data_types::DataType<S>,
     {
-        let id = self.get_next_req_id();
-
-        self.writer.add_body((
-            Out::ReqHistoricalData,
-            id,
+        let id = self.add_historical_bar(
             security,
-            false,
             end_date_time,
-            bar_size,
             duration,
+            bar_size,
+            data,
             regular_trading_hours_only,
+        )?;
+        self.send().await?;
+        Ok(id)
+    }
+
+    /// Request historical bar data for a given security once IBKR's historical data pacing rules
+    /// permit, and keep the bars so that they can be received with
+    /// [`Client::recv_historical_bars`]. See [`Client::req_historical_bar`] for details.
+    ///
+    /// IBKR rejects historical data requests that are made more than 60 times in any ten-minute
+    /// period, or that repeat an identical request within fifteen seconds. Rather than sending such
+    /// a request straight away, this method queues it to be written once it would be accepted and
+    /// returns immediately, so the client remains free for other requests in the meantime. Since
+    /// every paced request waits for those that came before it, a bulk download can simply make
+    /// its requests one after another and then receive the results in the same order.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request data.
+    /// * `end_date_time` - The last datetime for which data will be returned.
+    /// * `duration` - The duration for which historical data be returned (ie. the difference
+    /// between the first bar's datetime and the last bar's datetime).
+    /// * `bar_size` - The size of each individual bar.
+    /// * `data` - The type of data that to return (price, volume, volatility, etc.).
+    /// * `regular_trading_hours_only` - When [`true`], only return bars from regular trading hours.
+    ///
+    /// # Errors
+    /// Returns any error encountered while encoding the outgoing message. An error encountered
+    /// while writing it is instead returned by [`Client::recv_historical_bars`].
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_historical_bar_paced<S, D>(
+        &mut self,
+        security: &S,
+        end_date_time: historical_bar::EndDateTime,
+        duration: historical_bar::Duration,
+        bar_size: historical_bar::Size,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: historical_bar::data_types::DataType<S>,
+    {
+        let id = self.add_historical_bar(
+            security,
+            end_date_time,
+            duration,
+            bar_size,
             data,
-            1,
+            regular_trading_hours_only,
+        )?;
+        self.status.shared.historical_bars.register(id);
+        if let Err(e) = self.send_paced(id).await {
+            self.status.shared.historical_bars.cancel(&id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    /// Request one day of historical bars for each trading day in a range of dates, using
+    /// [`Client::req_historical_bar_paced`], so that no requests are wasted on weekends and
+    /// holidays, which IBKR answers with an error after counting them against the pacing limits.
+    ///
+    /// A date is skipped if the security's trading hours (see [`Client::get_trading_hours`]) mark
+    /// it as closed. Dates that the trading hours do not cover are checked against the bundled
+    /// [`calendar::Calendar`] of the security's primary exchange or, if it does not cover them
+    /// either, skipped only if they fall on a weekend. Each request ends at the midnight that follows its date, which TWS
+    /// interprets in its own time zone.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request data.
+    /// * `from` - The first date for which to request bars.
+    /// * `to` - The last date for which to request bars.
+    /// * `bar_size` - The size of each individual bar.
+    /// * `data` - The type of data that to return (price, volume, volatility, etc.).
+    /// * `regular_trading_hours_only` - When [`true`], only return bars from regular trading hours.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    ///
+    /// # Returns
+    /// The date and request ID of each request, whose bars can be received with
+    /// [`Client::recv_historical_bars`].
+    pub async fn req_historical_bar_sessions_paced<S, D>(
+        &mut self,
+        security: &S,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+        bar_size: historical_bar::Size,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> Result<Vec<(chrono::NaiveDate, i64)>, Error>
+    where
+        S: Security,
+        D: historical_bar::data_types::DataType<S>,
+    {
+        let hours = self.get_trading_hours(security.get_contract_id());
+        let exchange_calendar = security
+            .get_primary_exchange()
+            .and_then(calendar::Calendar::from_exchange);
+        let mut req_ids = Vec::new();
+        for date in from.iter_days().take_while(|date| *date <= to) {
+            let trades = hours
+                .as_ref()
+                .and_then(|hours| hours.is_trading_day(date))
+                .or_else(|| {
+                    exchange_calendar
+                        .and_then(|exchange_calendar| exchange_calendar.get_trading_day(date))
+                        .map(|day| day != calendar::TradingDay::Closed)
+                })
+                .unwrap_or_else(|| {
+                    !matches!(
+                        chrono::Datelike::weekday(&date),
+                        chrono::Weekday::Sat | chrono::Weekday::Sun
+                    )
+                });
+            let Some(next_date) = date.succ_opt().filter(|_| trades) else {
+                continue;
+            };
+            let req_id = self
+                .req_historical_bar_paced(
+                    security,
+                    historical_bar::EndDateTime::Past(next_date.and_time(chrono::NaiveTime::MIN)),
+                    historical_bar::Duration::Day(1),
+                    bar_size,
+                    data,
+                    regular_trading_hours_only,
+                )
+                .await?;
+            req_ids.push((date, req_id));
+        }
+        Ok(req_ids)
+    }
+
+    /// Wait for the bars of a request made with [`Client::req_historical_bar_paced`].
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    /// * `timeout` - The maximum amount of time to wait for the bars.
+    ///
+    /// # Errors
+    /// Returns [`Error::Api`] if IBKR rejects the request, [`Error::Pacing`] if it rejects the
+    /// request for violating its pacing rules, and [`Error::Timeout`] if no bars are received
+    /// before the timeout elapses.
+    ///
+    /// # Returns
+    /// The bars of the request.
+    pub async fn recv_historical_bars(
+        &self,
+        req_id: i64,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<Bar>, Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        self.status
+            .shared
+            .historical_bars
+            .wait(&req_id, deadline)
+            .await
+            .ok_or(Error::Timeout)?
+    }
+
+    /// Request the trading sessions of a security over a period, such as to find the sessions
+    /// of its exchange on past dates.
+    ///
+    /// The schedule is also delivered to the [`crate::wrapper::Local::historical_schedule`] (or
+    /// [`crate::wrapper::Remote::historical_schedule`]) callback.
+    ///
+    /// # Arguments
+    /// * `security` - The security whose sessions to request.
+    /// * `duration` - The length of the period.
+    /// * `end_date_time` - The end of the period.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing or sending the request, if IBKR answers it
+    /// with an error, or if it is not answered in time.
+    ///
+    /// # Returns
+    /// The schedule of the security's sessions.
+    pub async fn req_historical_schedule<S: Security>(
+        &mut self,
+        security: &S,
+        duration: historical_bar::Duration,
+        end_date_time: historical_bar::EndDateTime,
+    ) -> Result<HistoricalSchedule, Error> {
+        let req_id = self.get_next_req_id();
+        self.writer.add_body((
+            Out::ReqHistoricalData,
+            req_id,
+            security,
+            false,
+            end_date_time,
+            historical_bar::Size::Day,
+            duration,
+            true,
+            "SCHEDULE",
+            2,
+            false,
+            None::<()>,
+        ))?;
+
+        let shared = Arc::clone(&self.status.shared);
+        shared.historical_schedules.register(req_id);
+        if let Err(e) = self.send().await {
+            shared.historical_schedules.cancel(&req_id);
+            return Err(e);
+        }
+        let deadline = tokio::time::Instant::now() + constants::SCHEDULE_TIMEOUT;
+        shared
+            .historical_schedules
+            .wait(&req_id, deadline)
+            .await
+            .ok_or(Error::Timeout)?
+    }
+
+    #[inline]
+    fn add_historical_bar<S, D>(
+        &mut self,
+        security: &S,
+        end_date_time: historical_bar::EndDateTime,
+        duration: historical_bar::Duration,
+        bar_size: historical_bar::Size,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: historical_bar::data_types::DataType<S>,
+    {
+        let id = self.get_next_req_id();
+
+        self.writer.add_body((
+            Out::ReqHistoricalData,
+            id,
+            security,
+            false,
+            end_date_time,
+            bar_size,
+            duration,
+            regular_trading_hours_only,
+            data,
+            2,
             false,
             None::<()>,
         ))?;
-        self.writer.send().await?;
         Ok(id)
     }
 
@@ -2020,11 +4102,11 @@ impl Client<indicators::Active> {
             duration,
             regular_trading_hours_only,
             data,
-            1,
+            2,
             true,
             None::<()>,
         ))?;
-        self.writer.send().await?;
+        self.send().await?;
         Ok(id)
     }
 
@@ -2040,7 +4122,7 @@ impl Client<indicators::Active> {
 
         self.writer
             .add_body((Out::CancelHistoricalData, VERSION, req_id))?;
-        self.writer.send().await
+        self.send().await
     }
 
     /// Request the earliest available data point for a given security and data type.
@@ -2075,12 +4157,127 @@ impl Client<indicators::Active> {
             None::<()>,
             regular_trading_hours_only,
             data,
-            1,
+            2,
         ))?;
-        self.writer.send().await?;
+        self.send().await?;
         Ok(id)
     }
 
+    /// Request the earliest available data point for a given security and data type, as in
+    /// [`Client::req_head_timestamp`], and wait for the result.
+    ///
+    /// Results are cached per security, data type, and trading hours, so subsequent calls with the
+    /// same arguments return immediately without making a request. The cache can be inspected with
+    /// [`Client::get_cached_head_timestamp`] and emptied with
+    /// [`Client::clear_head_timestamp_cache`]. The result is also delivered to the wrapper's
+    /// `head_timestamp` callback as usual.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to make the request.
+    /// * `data` - The data for which to make the request.
+    /// * `regular_trading_hours_only` - When [`true`], only consider data from regular trading
+    /// hours.
+    /// * `timeout` - The maximum amount of time to wait for the result.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Also returns
+    /// [`Error::Timeout`] if no result is received before the timeout elapses (for example,
+    /// because IBKR responded with an error instead), in which case the request is cancelled.
+    ///
+    /// # Returns
+    /// The date and time of the earliest available data point.
+    pub async fn req_head_timestamp_await<S, D>(
+        &mut self,
+        security: &S,
+        data: D,
+        regular_trading_hours_only: bool,
+        timeout: std::time::Duration,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error>
+    where
+        S: Security,
+        D: historical_ticks::data_types::DataType<S>,
+    {
+        let key = (
+            security.get_contract_id(),
+            data.to_string(),
+            regular_trading_hours_only,
+        );
+        if let Some(timestamp) = self.get_earliest_data().get(&key) {
+            return Ok(*timestamp);
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let shared = Arc::clone(&self.status.shared);
+        // The result must be registered before the request is sent so that it is not missed, so
+        // the request ID is reserved here rather than in `req_head_timestamp`.
+        let req_id = self.reserve_req_id();
+        shared.head_timestamps.register(req_id);
+        if let Err(e) = self
+            .req_head_timestamp(security, data, regular_trading_hours_only)
+            .await
+        {
+            shared.head_timestamps.cancel(&req_id);
+            return Err(e);
+        }
+        let timestamp = shared
+            .head_timestamps
+            .wait(&req_id, deadline)
+            .await
+            .map(|timestamp| timestamp.and_utc());
+
+        if let Some(timestamp) = timestamp {
+            self.get_earliest_data().insert(key, timestamp);
+            Ok(timestamp)
+        } else {
+            self.cancel_head_timestamp(req_id).await?;
+            Err(Error::Timeout)
+        }
+    }
+
+    #[inline]
+    fn get_earliest_data(
+        &self,
+    ) -> std::sync::MutexGuard<
+        '_,
+        std::collections::HashMap<EarliestDataKey, chrono::DateTime<chrono::Utc>>,
+    > {
+        self.status
+            .shared
+            .earliest_data
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the earliest available data point for a given security and data type, if it was
+    /// cached by a previous call to [`Client::req_head_timestamp_await`].
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the security's contract.
+    /// * `data` - The data type of the request.
+    /// * `regular_trading_hours_only` - Whether the request only considered data from regular
+    /// trading hours.
+    ///
+    /// # Returns
+    /// The cached date and time, if any.
+    pub fn get_cached_head_timestamp<D: ToString>(
+        &self,
+        contract_id: ContractId,
+        data: &D,
+        regular_trading_hours_only: bool,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.get_earliest_data()
+            .get(&(contract_id, data.to_string(), regular_trading_hours_only))
+            .copied()
+    }
+
+    #[inline]
+    /// Empty the cache of head timestamps kept by [`Client::req_head_timestamp_await`].
+    pub fn clear_head_timestamp_cache(&self) {
+        self.get_earliest_data().clear();
+    }
+
     /// Cancel an existing [`Client::req_head_timestamp`] data request.
     ///
     /// # Arguments
@@ -2090,7 +4287,7 @@ impl Client<indicators::Active> {
     /// Returns any error encountered while writing the outgoing message.
     pub async fn cancel_head_timestamp(&mut self, req_id: i64) -> ReqResult {
         self.writer.add_body((Out::CancelHeadTimestamp, req_id))?;
-        self.writer.send().await
+        self.send().await
     }
 
     /// Request a histogram of historical data.
@@ -2124,7 +4321,7 @@ impl Client<indicators::Active> {
             regular_trading_hours_only,
             duration,
         ))?;
-        self.writer.send().await?;
+        self.send().await?;
         Ok(id)
     }
 
@@ -2137,346 +4334,2639 @@ impl Client<indicators::Active> {
     /// Returns any error encountered while writing the outgoing message.
     pub async fn cancel_histogram_data(&mut self, req_id: i64) -> ReqResult {
         self.writer.add_body((Out::CancelHistogramData, req_id))?;
-        self.writer.send().await
+        self.send().await
     }
 
-    /// Request historical ticks for a given security. See [`historical_ticks`] for
-    /// types and traits that are used in this function.
+    /// Request a fundamental data report for a stock, which is delivered as an XML document to
+    /// the [`crate::wrapper::Local::fundamental_data`] (or
+    /// [`crate::wrapper::Remote::fundamental_data`]) callback. The reports are only available
+    /// with a subscription to Refinitiv's fundamental data.
     ///
     /// # Arguments
-    /// * `security` - The security for which to request data.
-    /// * `timestamp` - The first/last datetime for which data will be returned.
-    /// * `number_of_ticks` - The number of ticks to return.
-    /// * `data` - The type of data to return (Trades, `BidAsk`, etc.).
-    /// * `regular_trading_hours_only` - When [`true`], only return ticks from regular trading hours.
+    /// * `security` - The stock for which to request the report.
+    /// * `report_type` - The report to request.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
     ///
     /// # Returns
     /// Returns the unique ID associated with the request.
-    pub async fn req_historical_ticks<S, D>(
+    pub async fn req_fundamental_data(
         &mut self,
-        security: &S,
-        timestamp: historical_ticks::TimeStamp,
-        number_of_ticks: historical_ticks::NumberOfTicks,
-        data: D,
-        regular_trading_hours_only: bool,
-    ) -> IdResult
-    where
-        S: Security,
-        D: historical_ticks::data_types::DataType<S>,
-    {
-        let id = self.get_next_req_id();
+        security: &Stock,
+        report_type: fundamental::ReportType,
+    ) -> IdResult {
+        const VERSION: u8 = 2;
 
+        let id = self.get_next_req_id();
         self.writer.add_body((
-            Out::ReqHistoricalTicks,
+            Out::ReqFundamentalData,
+            VERSION,
             id,
-            security,
-            None::<()>,
-            timestamp,
-            number_of_ticks,
-            data,
-            regular_trading_hours_only,
-            None::<()>,
+            security.get_contract_id(),
+            security.get_symbol(),
+            security.get_security_type(),
+            security.get_exchange(),
+            security.get_primary_exchange(),
+            security.get_currency(),
+            security.get_local_symbol(),
+            report_type,
             None::<()>,
         ))?;
-        self.writer.send().await?;
+        self.send().await?;
         Ok(id)
     }
 
-    // === Live Market Data ===
-
-    /// Request live data for a given security.
+    /// Cancel an existing [`Client::req_fundamental_data`] request.
     ///
     /// # Arguments
-    /// * `security` - The security for which to request data.
-    /// * `data` - The type of data to return (`RealTimeVolume`, `MarkPrice`, etc.).
-    /// * `refresh_type` - How often to refresh the data (a one-time snapshot or a continuous
-    /// streaming connection)
-    /// * `use_regulatory_snapshot` - When set to [`true`], return a NBBO snapshot even if no
-    /// appropriate subscription exists for streaming data. Note that doing so will cost 1 cent per
-    /// snapshot.
+    /// * `req_id` - The ID of the [`Client::req_fundamental_data`] request to cancel.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
-    ///
-    /// # Returns
-    /// Returns the unique ID associated with the request.
-    pub async fn req_market_data<S, D>(
-        &mut self,
-        security: &S,
-        additional_data: Vec<D>,
-        refresh_type: live_data::RefreshType,
-        use_regulatory_snapshot: bool,
-    ) -> IdResult
-    where
-        S: Security,
-        D: live_data::data_types::DataType<S>,
-    {
-        const VERSION: u8 = 11;
-        let id = self.get_next_req_id();
+    pub async fn cancel_fundamental_data(&mut self, req_id: i64) -> ReqResult {
+        const VERSION: u8 = 1;
 
-        self.writer.add_body((
-            Out::ReqMktData,
-            VERSION,
-            id,
-            security,
-            false,
-            additional_data,
-            refresh_type,
-            use_regulatory_snapshot,
-            None::<()>,
-        ))?;
-        self.writer.send().await?;
-        Ok(id)
+        self.writer
+            .add_body((Out::CancelFundamentalData, VERSION, req_id))?;
+        self.send().await
     }
 
-    /// Cancel an open streaming data connection with a given `req_id`.
+    /// Request the news providers to which the user is subscribed.
     ///
-    /// # Arguments
-    /// * `req_id` - The ID associated with the market data request to cancel.
+    /// The providers are also delivered to the [`crate::wrapper::Local::news_providers`] (or
+    /// [`crate::wrapper::Remote::news_providers`]) callback.
     ///
     /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
-    pub async fn cancel_market_data(&mut self, req_id: i64) -> ReqResult {
-        const VERSION: u8 = 2;
+    /// Returns any error encountered while writing or sending the request, or if it is not
+    /// answered in time.
+    ///
+    /// # Returns
+    /// The news providers.
+    pub async fn req_news_providers(&mut self) -> Result<Vec<NewsProvider>, Error> {
+        self.writer.add_body(Out::ReqNewsProviders)?;
+
+        let shared = Arc::clone(&self.status.shared);
+        shared.news_providers.register(());
+        if let Err(e) = self.send().await {
+            shared.news_providers.cancel(&());
+            return Err(e);
+        }
+        let deadline = tokio::time::Instant::now() + constants::NEWS_TIMEOUT;
+        shared
+            .news_providers
+            .wait(&(), deadline)
+            .await
+            .ok_or(Error::Timeout)
+    }
+
+    /// Request the body of a news article.
+    ///
+    /// The article is also delivered to the [`crate::wrapper::Local::news_article`] (or
+    /// [`crate::wrapper::Remote::news_article`]) callback.
+    ///
+    /// # Arguments
+    /// * `provider_code` - The code of the article's provider (ex: "BRFG").
+    /// * `article_id` - The ID of the article, as reported in its [`crate::payload::NewsHeadline`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing or sending the request, if IBKR answers it
+    /// with an error (such as when the user is not subscribed to the provider), or if it is not
+    /// answered in time.
+    ///
+    /// # Returns
+    /// The article.
+    pub async fn req_news_article(
+        &mut self,
+        provider_code: &str,
+        article_id: &str,
+    ) -> Result<NewsArticle, Error> {
+        let req_id = self.get_next_req_id();
+        self.writer.add_body((
+            Out::ReqNewsArticle,
+            req_id,
+            provider_code,
+            article_id,
+            None::<()>,
+        ))?;
+
+        let shared = Arc::clone(&self.status.shared);
+        shared.news_articles.register(req_id);
+        if let Err(e) = self.send().await {
+            shared.news_articles.cancel(&req_id);
+            return Err(e);
+        }
+        let deadline = tokio::time::Instant::now() + constants::NEWS_TIMEOUT;
+        shared
+            .news_articles
+            .wait(&req_id, deadline)
+            .await
+            .ok_or(Error::Timeout)?
+    }
+
+    /// Request the headlines of the news articles about a contract that were published in a
+    /// period.
+    ///
+    /// Each headline is also delivered to the [`crate::wrapper::Local::historical_news`] (or
+    /// [`crate::wrapper::Remote::historical_news`]) callback.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract.
+    /// * `provider_codes` - The codes of the providers whose articles to include.
+    /// * `start` - The beginning of the period, in UTC.
+    /// * `end` - The end of the period, in UTC.
+    /// * `total_results` - The maximum number of headlines to return (at most 300).
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing or sending the request, if IBKR answers it
+    /// with an error, or if it is not answered in time.
+    ///
+    /// # Returns
+    /// The headlines, and whether more of them matched the request.
+    pub async fn req_historical_news(
+        &mut self,
+        contract_id: ContractId,
+        provider_codes: &[&str],
+        start: chrono::NaiveDateTime,
+        end: chrono::NaiveDateTime,
+        total_results: u32,
+    ) -> Result<HistoricalNews, Error> {
+        let req_id = self.get_next_req_id();
+        self.writer.add_body((
+            Out::ReqHistoricalNews,
+            req_id,
+            contract_id,
+            provider_codes.join("+"),
+            start.format("%Y-%m-%d %H:%M:%S.0").to_string(),
+            end.format("%Y-%m-%d %H:%M:%S.0").to_string(),
+            total_results,
+            None::<()>,
+        ))?;
+
+        let shared = Arc::clone(&self.status.shared);
+        shared.historical_news.register(req_id);
+        if let Err(e) = self.send().await {
+            shared.historical_news.cancel(&req_id);
+            return Err(e);
+        }
+        let deadline = tokio::time::Instant::now() + constants::NEWS_TIMEOUT;
+        let result = shared.historical_news.wait(&req_id, deadline).await;
+        shared
+            .historical_news_results
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&req_id);
+        result.ok_or(Error::Timeout)?
+    }
+
+    /// Subscribe to IB news bulletins, which are delivered to the
+    /// [`crate::wrapper::Local::news_bulletin`] (or [`crate::wrapper::Remote::news_bulletin`])
+    /// callback.
+    ///
+    /// # Arguments
+    /// * `all_messages` - When [`true`], also deliver the bulletins issued earlier in the day.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn req_news_bulletins(&mut self, all_messages: bool) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.writer
+            .add_body((Out::ReqNewsBulletins, VERSION, all_messages))?;
+        self.send().await
+    }
+
+    /// Cancel an existing [`Client::req_news_bulletins`] subscription.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_news_bulletins(&mut self) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.writer.add_body((Out::CancelNewsBulletins, VERSION))?;
+        self.send().await
+    }
+
+    /// Request the IDs of the display groups in TWS, which link the contract shown in TWS windows.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn query_display_groups(&mut self) -> IdResult {
+        const VERSION: u8 = 1;
+        let id = self.get_next_req_id();
+
+        self.writer
+            .add_body((Out::QueryDisplayGroups, VERSION, id))?;
+        self.send().await?;
+        Ok(id)
+    }
+
+    /// Subscribe to changes of the contract displayed by a TWS display group.
+    ///
+    /// # Arguments
+    /// * `group_id` - The ID of the group, from [`Client::query_display_groups`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the subscription, which is needed to update the
+    /// group with [`Client::update_display_group`].
+    pub async fn subscribe_to_group_events(&mut self, group_id: i64) -> IdResult {
+        const VERSION: u8 = 1;
+        let id = self.get_next_req_id();
+
+        self.writer
+            .add_body((Out::SubscribeToGroupEvents, VERSION, id, group_id))?;
+        self.send_and_replay(Replay::Request(id, Out::SubscribeToGroupEvents))
+            .await?;
+        Ok(id)
+    }
+
+    /// Change the contract displayed by a TWS display group.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID returned by [`Client::subscribe_to_group_events`] for the group.
+    /// * `contract_info` - The contract to display, as its contract ID and exchange (ex:
+    /// "8314@SMART"), or "none" to clear the group.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn update_display_group(&mut self, req_id: i64, contract_info: &str) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.writer
+            .add_body((Out::UpdateDisplayGroup, VERSION, req_id, contract_info))?;
+        self.send().await
+    }
+
+    /// Unsubscribe from the changes of a TWS display group.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID returned by [`Client::subscribe_to_group_events`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn unsubscribe_from_group_events(&mut self, req_id: i64) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::SubscribeToGroupEvents));
+        self.writer
+            .add_body((Out::UnsubscribeFromGroupEvents, VERSION, req_id))?;
+        self.send().await
+    }
+
+    /// Request historical ticks for a given security. See [`historical_ticks`] for
+    /// types and traits that are used in this function.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request data.
+    /// * `timestamp` - The first/last datetime for which data will be returned.
+    /// * `number_of_ticks` - The number of ticks to return.
+    /// * `data` - The type of data to return (Trades, `BidAsk`, etc.).
+    /// * `regular_trading_hours_only` - When [`true`], only return ticks from regular trading hours.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_historical_ticks<S, D>(
+        &mut self,
+        security: &S,
+        timestamp: historical_ticks::TimeStamp,
+        number_of_ticks: historical_ticks::NumberOfTicks,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: historical_ticks::data_types::DataType<S>,
+    {
+        let id = self.add_historical_ticks(
+            security,
+            timestamp,
+            number_of_ticks,
+            data,
+            regular_trading_hours_only,
+        )?;
+        self.send().await?;
+        Ok(id)
+    }
+
+    /// Request historical ticks for a given security once IBKR's historical data pacing rules
+    /// permit, and keep the ticks so that they can be received with
+    /// [`Client::recv_historical_ticks`]. See [`Client::req_historical_ticks`] for details and
+    /// [`Client::req_historical_bar_paced`] for the pacing rules.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request data.
+    /// * `timestamp` - The first/last datetime for which data will be returned.
+    /// * `number_of_ticks` - The number of ticks to return.
+    /// * `data` - The type of data to return (Trades, `BidAsk`, etc.).
+    /// * `regular_trading_hours_only` - When [`true`], only return ticks from regular trading hours.
+    ///
+    /// # Errors
+    /// Returns any error encountered while encoding the outgoing message. An error encountered
+    /// while writing it is instead returned by [`Client::recv_historical_ticks`].
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_historical_ticks_paced<S, D>(
+        &mut self,
+        security: &S,
+        timestamp: historical_ticks::TimeStamp,
+        number_of_ticks: historical_ticks::NumberOfTicks,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: historical_ticks::data_types::DataType<S>,
+    {
+        let id = self.add_historical_ticks(
+            security,
+            timestamp,
+            number_of_ticks,
+            data,
+            regular_trading_hours_only,
+        )?;
+        self.status.shared.historical_ticks.register(id);
+        if let Err(e) = self.send_paced(id).await {
+            self.status.shared.historical_ticks.cancel(&id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    /// Wait for the ticks of a request made with [`Client::req_historical_ticks_paced`].
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    /// * `timeout` - The maximum amount of time to wait for the ticks.
+    ///
+    /// # Errors
+    /// Returns [`Error::Api`] if IBKR rejects the request, [`Error::Pacing`] if it rejects the
+    /// request for violating its pacing rules, and [`Error::Timeout`] if no ticks are received
+    /// before the timeout elapses.
+    ///
+    /// # Returns
+    /// The ticks of the request.
+    pub async fn recv_historical_ticks(
+        &self,
+        req_id: i64,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<Tick>, Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        self.status
+            .shared
+            .historical_ticks
+            .wait(&req_id, deadline)
+            .await
+            .ok_or(Error::Timeout)?
+    }
+
+    #[inline]
+    fn add_historical_ticks<S, D>(
+        &mut self,
+        security: &S,
+        timestamp: historical_ticks::TimeStamp,
+        number_of_ticks: historical_ticks::NumberOfTicks,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: historical_ticks::data_types::DataType<S>,
+    {
+        let id = self.get_next_req_id();
+
+        self.writer.add_body((
+            Out::ReqHistoricalTicks,
+            id,
+            security,
+            None::<()>,
+            timestamp,
+            number_of_ticks,
+            data,
+            regular_trading_hours_only,
+            None::<()>,
+            None::<()>,
+        ))?;
+        Ok(id)
+    }
+
+    // === Live Market Data ===
+
+    /// Request live data for a given security.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request data.
+    /// * `data` - The type of data to return (`RealTimeVolume`, `MarkPrice`, etc.).
+    /// * `refresh_type` - How often to refresh the data (a one-time snapshot or a continuous
+    /// streaming connection)
+    /// * `use_regulatory_snapshot` - When set to [`true`], return a NBBO snapshot even if no
+    /// appropriate subscription exists for streaming data. Note that doing so will cost 1 cent per
+    /// snapshot.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_market_data<S, D>(
+        &mut self,
+        security: &S,
+        additional_data: Vec<D>,
+        refresh_type: live_data::RefreshType,
+        use_regulatory_snapshot: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: live_data::data_types::DataType<S>,
+    {
+        const VERSION: u8 = 11;
+        let streaming = matches!(refresh_type, live_data::RefreshType::Streaming);
+        let id = self.get_next_req_id();
+
+        self.writer.add_body((
+            Out::ReqMktData,
+            VERSION,
+            id,
+            security,
+            MarketDataLegs(security.get_combo_legs()),
+            DeltaNeutralField(security.get_delta_neutral_contract()),
+            additional_data,
+            refresh_type,
+            use_regulatory_snapshot,
+            None::<()>,
+        ))?;
+        if streaming {
+            self.send_and_replay(Replay::Request(id, Out::ReqMktData))
+                .await?;
+        } else {
+            self.send().await?;
+        }
+        Ok(id)
+    }
+
+    /// Request live data for a given security and receive its prices, sizes, and volumes as an
+    /// [`EventStream`]. See [`Client::req_market_data`] for details.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request data.
+    /// * `data` - The type of data to return (`RealTimeVolume`, `MarkPrice`, etc.).
+    /// * `refresh_type` - How often to refresh the data (a one-time snapshot or a continuous
+    /// streaming connection)
+    /// * `use_regulatory_snapshot` - When set to [`true`], return a NBBO snapshot even if no
+    /// appropriate subscription exists for streaming data. Note that doing so will cost 1 cent per
+    /// snapshot.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the stream of the request's events.
+    pub async fn req_market_data_stream<S, D>(
+        &mut self,
+        security: &S,
+        additional_data: Vec<D>,
+        refresh_type: live_data::RefreshType,
+        use_regulatory_snapshot: bool,
+    ) -> Result<EventStream, Error>
+    where
+        S: Security,
+        D: live_data::data_types::DataType<S>,
+    {
+        let stream = self.register_stream(Subscription::MarketData);
+        match self
+            .req_market_data(
+                security,
+                additional_data,
+                refresh_type,
+                use_regulatory_snapshot,
+            )
+            .await
+        {
+            Ok(_) => Ok(stream),
+            Err(e) => {
+                stream.disarm();
+                Err(e)
+            }
+        }
+    }
+
+    /// Cancel an open streaming data connection with a given `req_id`.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID associated with the market data request to cancel.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_market_data(&mut self, req_id: i64) -> ReqResult {
+        const VERSION: u8 = 2;
+
+        stream::unregister(&self.status.shared, req_id);
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqMktData));
+        self.status
+            .shared
+            .exchange_ids
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&req_id);
+        self.writer
+            .add_body((Out::CancelMktData, VERSION, req_id))?;
+        self.send().await
+    }
+
+    /// Cancel every streaming market data subscription that has not been cancelled (see
+    /// [`Client::get_active_market_data`]).
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages. The subscriptions that
+    /// were cancelled before the error remain cancelled.
+    pub async fn cancel_all_market_data(&mut self) -> ReqResult {
+        for req_id in self.get_active_requests(&[Out::ReqMktData]) {
+            self.cancel_market_data(req_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Set the market data variant for all succeeding `Client::req_market_data` requests.
+    ///
+    /// # Arguments
+    /// * `variant` - The variant to set.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn req_market_data_type(&mut self, variant: live_data::Class) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.writer
+            .add_body((Out::ReqMarketDataType, VERSION, variant))?;
+        self.send_and_replay(Replay::MarketDataType).await
+    }
+
+    /// Request snapshot quotes for many securities at once, and wait for the snapshots to end.
+    ///
+    /// At most `line_budget` snapshots are outstanding at any time, and successive requests are
+    /// paced so as not to exceed IBKR's message rate limit. The ticks that make up each snapshot
+    /// are also delivered to the wrapper as usual.
+    ///
+    /// # Arguments
+    /// * `contracts` - The securities for which to request quotes.
+    /// * `line_budget` - The maximum number of snapshots to request at once.
+    /// * `timeout` - The maximum amount of time to wait for all of the snapshots to end.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    ///
+    /// # Returns
+    /// The [`Quote`] of each security, keyed by contract ID. Securities whose snapshots did not
+    /// end before the timeout elapsed have partial quotes, with [`Quote::complete`] set to
+    /// [`false`]; securities whose snapshots were never requested are omitted.
+    pub async fn req_quotes(
+        &mut self,
+        contracts: &[Contract],
+        line_budget: usize,
+        timeout: std::time::Duration,
+    ) -> Result<std::collections::HashMap<ContractId, Quote>, Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let line_budget = line_budget.max(1);
+        let mut pending = contracts.iter();
+        let mut in_flight = std::collections::HashMap::<i64, ContractId>::new();
+        let mut quotes = std::collections::HashMap::with_capacity(contracts.len());
+
+        let shared = Arc::clone(&self.status.shared);
+        loop {
+            while in_flight.len() < line_budget && tokio::time::Instant::now() < deadline {
+                let Some(contract) = pending.next() else {
+                    break;
+                };
+                let req_id = self.req_snapshot(contract).await?;
+                in_flight.insert(req_id, contract.get_contract_id());
+                tokio::time::sleep(constants::SNAPSHOT_PACING).await;
+            }
+            // Every snapshot has been collected, and none is left to request before the deadline
+            if in_flight.is_empty() {
+                break;
+            }
+            let ended = wait_for_snapshots(&shared, deadline, |snapshots| {
+                in_flight
+                    .keys()
+                    .any(|req_id| is_snapshot_ended(snapshots, *req_id))
+            })
+            .await;
+            self.collect_quotes(&mut in_flight, &mut quotes, false);
+            if !ended {
+                break;
+            }
+        }
+
+        self.collect_quotes(&mut in_flight, &mut quotes, true);
+        Ok(quotes)
+    }
+
+    /// Get the exchange rate between two currencies from a snapshot quote of the corresponding
+    /// IDEALPRO currency pair.
+    ///
+    /// The pair is quoted in its market convention (ex: EUR.USD or USD.JPY), and the rate is
+    /// inverted if necessary. Rates between two currencies other than USD are triangulated
+    /// through USD. Each quote is valued at its midpoint (see [`Quote::midpoint`]), and its ticks
+    /// are also delivered to the wrapper as usual.
+    ///
+    /// # Arguments
+    /// * `from` - The currency to convert from.
+    /// * `to` - The currency to convert to.
+    /// * `timeout` - The maximum amount of time to wait for each snapshot.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages. Also returns
+    /// [`Error::Timeout`] if a snapshot does not end before the timeout elapses or does not
+    /// contain a price.
+    ///
+    /// # Returns
+    /// The number of units of `to` that are worth one unit of `from`.
+    pub async fn fx_rate(
+        &mut self,
+        from: Currency,
+        to: Currency,
+        timeout: std::time::Duration,
+    ) -> Result<f64, Error> {
+        if from == to {
+            Ok(1.0)
+        } else if from == Currency::USDollar || to == Currency::USDollar {
+            self.req_fx_pair_rate(from, to, timeout).await
+        } else {
+            Ok(self
+                .req_fx_pair_rate(from, Currency::USDollar, timeout)
+                .await?
+                * self
+                    .req_fx_pair_rate(Currency::USDollar, to, timeout)
+                    .await?)
+        }
+    }
+
+    async fn req_fx_pair_rate(
+        &mut self,
+        from: Currency,
+        to: Currency,
+        timeout: std::time::Duration,
+    ) -> Result<f64, Error> {
+        const VERSION: u8 = 11;
+        let (symbol, currency) = from.idealpro_pair(to);
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        let req_id = self.get_next_req_id();
+        self.status
+            .shared
+            .quotes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(req_id, Quote::default());
+        self.writer.add_body((
+            Out::ReqMktData,
+            VERSION,
+            req_id,
+            (0, symbol, "CASH", [None::<()>; 4]),
+            ("IDEALPRO", None::<()>),
+            (currency, [None::<()>; 2]),
+            false,
+            None::<()>,
+            live_data::RefreshType::Snapshot,
+            false,
+            None::<()>,
+        ))?;
+        let sent = self.send().await;
+
+        let shared = Arc::clone(&self.status.shared);
+        if sent.is_ok() {
+            wait_for_snapshots(&shared, deadline, |snapshots| {
+                is_snapshot_ended(snapshots, req_id)
+            })
+            .await;
+        }
+        let quote = shared
+            .quotes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&req_id);
+        sent?;
+
+        let mid = quote
+            .filter(|q| q.complete)
+            .and_then(|q| q.midpoint())
+            .ok_or(Error::Timeout)?;
+        Ok(if from == symbol { mid } else { mid.recip() })
+    }
+
+    async fn req_snapshot(&mut self, contract: &Contract) -> IdResult {
+        let req_id = match contract {
+            Contract::Forex(fx) => self.req_snapshot_security(fx).await,
+            Contract::Crypto(crypto) => self.req_snapshot_security(crypto).await,
+            Contract::Stock(stk) => self.req_snapshot_security(stk).await,
+            Contract::Index(ind) => self.req_snapshot_security(ind).await,
+            Contract::SecFuture(fut) => self.req_snapshot_security(fut).await,
+            Contract::SecOption(opt) => self.req_snapshot_security(opt).await,
+            Contract::Commodity(cmdty) => self.req_snapshot_security(cmdty).await,
+            Contract::Combo(combo) => self.req_snapshot_security(combo).await,
+        }?;
+        Ok(req_id)
+    }
+
+    async fn req_snapshot_security<S>(&mut self, security: &S) -> IdResult
+    where
+        S: Security,
+        live_data::data_types::Empty: live_data::data_types::DataType<S>,
+    {
+        // The quote must be registered before the request is sent so that no ticks are missed,
+        // so the request ID is reserved here rather than in `req_market_data`.
+        let req_id = self.reserve_req_id();
+        self.status
+            .shared
+            .quotes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(req_id, Quote::default());
+        self.req_market_data(
+            security,
+            vec![live_data::data_types::Empty],
+            live_data::RefreshType::Snapshot,
+            false,
+        )
+        .await
+    }
+
+    fn collect_quotes(
+        &self,
+        in_flight: &mut std::collections::HashMap<i64, ContractId>,
+        quotes: &mut std::collections::HashMap<ContractId, Quote>,
+        all: bool,
+    ) {
+        let mut shared = self
+            .status
+            .shared
+            .quotes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        in_flight.retain(|req_id, contract_id| {
+            let done = all || is_snapshot_ended(&shared, *req_id);
+            if done {
+                if let Some(quote) = shared.remove(req_id) {
+                    quotes.insert(*contract_id, quote);
+                }
+            }
+            !done
+        });
+    }
+
+    /// Request real-time, 5 second bars for a given security.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request the bars.
+    /// * `data` - The type of data to return (trades, bid, ask, midpoint).
+    /// * `regular_trading_hours_only` -  When [`true`], only return ticks from regular trading
+    /// hours.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_real_time_bars<S, D>(
+        &mut self,
+        security: &S,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: live_bar::data_types::DataType<S>,
+    {
+        const VERSION: u8 = 3;
+        let id = self.get_next_req_id();
+        self.status
+            .shared
+            .real_time_bar_contracts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(id, security.get_contract_id());
+
+        self.writer.add_body((
+            Out::ReqRealTimeBars,
+            VERSION,
+            id,
+            security,
+            5_u32,
+            data,
+            regular_trading_hours_only,
+            None::<()>,
+        ))?;
+        self.send_and_replay(Replay::Request(id, Out::ReqRealTimeBars))
+            .await?;
+        Ok(id)
+    }
+
+    /// Request real-time, 5 second bars for a given security and receive them as an
+    /// [`EventStream`]. See [`Client::req_real_time_bars`] for details.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request the bars.
+    /// * `data` - The type of data to return (trades, bid, ask, midpoint).
+    /// * `regular_trading_hours_only` -  When [`true`], only return ticks from regular trading
+    /// hours.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the stream of the request's bars.
+    pub async fn req_real_time_bars_stream<S, D>(
+        &mut self,
+        security: &S,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> Result<EventStream, Error>
+    where
+        S: Security,
+        D: live_bar::data_types::DataType<S>,
+    {
+        let stream = self.register_stream(Subscription::RealTimeBars);
+        match self
+            .req_real_time_bars(security, data, regular_trading_hours_only)
+            .await
+        {
+            Ok(_) => Ok(stream),
+            Err(e) => {
+                stream.disarm();
+                Err(e)
+            }
+        }
+    }
+
+    /// Cancel an existing real-time bars subscription.
+    ///
+    /// # Arguments
+    /// `req_id` - The ID associated with the bar subscription to cancel.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_real_time_bars(&mut self, req_id: i64) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        stream::unregister(&self.status.shared, req_id);
+        self.status
+            .shared
+            .real_time_bars
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&req_id);
+        self.status
+            .shared
+            .real_time_bar_contracts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&req_id);
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqRealTimeBars));
+        self.writer
+            .add_body((Out::CancelRealTimeBars, VERSION, req_id))?;
+        self.send().await
+    }
+
+    /// Cancel every real-time bars subscription that has not been cancelled.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages. The subscriptions that
+    /// were cancelled before the error remain cancelled.
+    pub async fn cancel_all_real_time_bars(&mut self) -> ReqResult {
+        for req_id in self.get_active_requests(&[Out::ReqRealTimeBars]) {
+            self.cancel_real_time_bars(req_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Backfill the bars that were dropped from a [`Client::req_real_time_bars`] subscription, as
+    /// reported by the wrapper's `missed_bars` callback, with a historical request for 5-second
+    /// bars that covers the gap. The request's end is given in UTC, like the bars' timestamps.
+    ///
+    /// The wrapper's `missed_bars` callback is not called for the break between two trading
+    /// sessions, as judged by the contract's trading hours (see [`Client::get_trading_hours`]) or
+    /// the bundled [`calendar::Calendar`] of its primary exchange.
+    ///
+    /// # Arguments
+    /// * `security` - The security of the real-time bar subscription.
+    /// * `missed` - The gap to backfill.
+    /// * `data` - The type of data to return, which should match the subscription's.
+    /// * `regular_trading_hours_only` - When [`true`], only return bars from regular trading hours.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the historical request, whose bars are delivered to
+    /// the wrapper's `historical_bars` callback.
+    pub async fn req_missed_bars<S, D>(
+        &mut self,
+        security: &S,
+        missed: MissedBars,
+        data: D,
+        regular_trading_hours_only: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: historical_bar::data_types::DataType<S>,
+    {
+        let interval = chrono::Duration::seconds(crate::payload::REAL_TIME_BAR_SECONDS);
+        let seconds =
+            u32::try_from((missed.to - missed.from + interval).num_seconds()).unwrap_or(u32::MAX);
+        self.req_historical_bar(
+            security,
+            historical_bar::EndDateTime::PastUtc(missed.to + interval),
+            historical_bar::Duration::Second(seconds),
+            historical_bar::Size::Seconds(historical_bar::SecondSize::Five),
+            data,
+            regular_trading_hours_only,
+        )
+        .await
+    }
+
+    // === Live Tick-by-Tick Data ===
+
+    /// Request live tick-by-tick data for a given security.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request data.
+    /// * `tick_data` - The type of data to return.
+    /// * `number_of_historical_ticks` - The number of historical ticks to return before the live
+    /// data.
+    /// * `ignore_size` - Ignore the size parameter in the returned ticks when set to [`true`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_tick_by_tick_data<S, D>(
+        &mut self,
+        security: &S,
+        tick_data: D,
+        number_of_historical_ticks: live_ticks::NumberOfTicks,
+        ignore_size: bool,
+    ) -> IdResult
+    where
+        S: Security,
+        D: live_ticks::data_types::DataType<S>,
+    {
+        let id = self.get_next_req_id();
+
+        self.writer.add_body((
+            Out::ReqTickByTickData,
+            id,
+            security,
+            tick_data,
+            number_of_historical_ticks,
+            ignore_size,
+        ))?;
+        self.send_and_replay(Replay::Request(id, Out::ReqTickByTickData))
+            .await?;
+        Ok(id)
+    }
+
+    /// Request live tick-by-tick data for a given security and receive the ticks as an
+    /// [`EventStream`]. See [`Client::req_tick_by_tick_data`] for details.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to request data.
+    /// * `tick_data` - The type of data to return.
+    /// * `number_of_historical_ticks` - The number of historical ticks to return before the live
+    /// data.
+    /// * `ignore_size` - Ignore the size parameter in the returned ticks when set to [`true`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the stream of the request's ticks.
+    pub async fn req_tick_by_tick_data_stream<S, D>(
+        &mut self,
+        security: &S,
+        tick_data: D,
+        number_of_historical_ticks: live_ticks::NumberOfTicks,
+        ignore_size: bool,
+    ) -> Result<EventStream, Error>
+    where
+        S: Security,
+        D: live_ticks::data_types::DataType<S>,
+    {
+        let stream = self.register_stream(Subscription::TickByTick);
+        match self
+            .req_tick_by_tick_data(security, tick_data, number_of_historical_ticks, ignore_size)
+            .await
+        {
+            Ok(_) => Ok(stream),
+            Err(e) => {
+                stream.disarm();
+                Err(e)
+            }
+        }
+    }
+
+    /// Set a filter that determines which trades are delivered by a tick-by-tick
+    /// ([`Client::req_tick_by_tick_data`]) or real-time volume ([`Client::req_market_data`])
+    /// stream, such as to exclude unreported or odd-lot trades.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the stream to filter.
+    /// * `filter` - The filter to apply, or [`None`] to deliver every trade.
+    pub fn set_trade_filter(&self, req_id: i64, filter: Option<TradeFilter>) {
+        let mut filters = self
+            .status
+            .shared
+            .trade_filters
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match filter {
+            Some(filter) => filters.insert(req_id, filter),
+            None => filters.remove(&req_id),
+        };
+    }
+
+    /// Cancel an existing tick-by-tick data subscription.
+    ///
+    /// # Arguments
+    /// * `req_id` - The request ID of the subscription to cancel.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_tick_by_tick_data(&mut self, req_id: i64) -> ReqResult {
+        stream::unregister(&self.status.shared, req_id);
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqTickByTickData));
+        self.writer.add_body((Out::CancelTickByTickData, req_id))?;
+        self.send().await
+    }
+
+    // === Market Depth ===
+
+    /// Request market depth data for a given security.
+    ///
+    /// # Arguments
+    /// * `security` - The security for which to return the market depth data.
+    /// * `number_of_rows` - The maximum number of rows in the returned limit order book.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_market_depth<S>(&mut self, security: &S, number_of_rows: u32) -> IdResult
+    where
+        S: Security,
+    {
+        const VERSION: u8 = 5;
+        let id = self.get_next_req_id();
+
+        self.writer.add_body((
+            Out::ReqMktDepth,
+            VERSION,
+            id,
+            security,
+            number_of_rows,
+            true,
+            None::<()>,
+        ))?;
+        self.send_and_replay(Replay::Request(id, Out::ReqMktDepth))
+            .await?;
+        Ok(id)
+    }
+
+    /// Request exchanges available for market depth.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn req_market_depth_exchanges(&mut self) -> ReqResult {
+        self.writer.add_body(Out::ReqMktDepthExchanges)?;
+        self.send().await
+    }
+
+    /// Cancel a market depth subscription for a given `req_id`.
+    ///
+    /// # Arguments
+    /// * `req_id` - The request ID for which to cancel a market depth subscription.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_market_depth(&mut self, req_id: i64) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.status
+            .replay
+            .remove(&Replay::Request(req_id, Out::ReqMktDepth));
+        self.writer
+            .add_body((Out::CancelMktDepth, VERSION, req_id))?;
+        self.send().await
+    }
+
+    /// Cancel every market depth subscription that has not been cancelled (see
+    /// [`Client::get_active_market_depth`]).
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages. The subscriptions that
+    /// were cancelled before the error remain cancelled.
+    pub async fn cancel_all_market_depth(&mut self) -> ReqResult {
+        for req_id in self.get_active_requests(&[Out::ReqMktDepth]) {
+            self.cancel_market_depth(req_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Request exchanges comprising the aggregate SMART exchange
+    ///
+    /// # Arguments
+    /// * `exchange_id` - The identifier containing information about the component exchanges, which
+    /// is attained from an initial market data callback.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_smart_components(&mut self, exchange_id: ExchangeId) -> IdResult {
+        let id = self.get_next_req_id();
+
+        self.writer
+            .add_body((Out::ReqSmartComponents, id, exchange_id))?;
+        self.send().await?;
+        Ok(id)
+    }
+
+    /// Request the exchanges comprising the aggregate SMART exchange and wait for the response.
+    ///
+    /// The components of each exchange ID are cached for the lifetime of the client, so only the
+    /// first call for a given exchange ID sends a request.
+    ///
+    /// # Arguments
+    /// * `exchange_id` - The identifier containing information about the component exchanges,
+    /// which is attained from an initial market data callback (see [`Client::get_exchange_id`]).
+    /// * `timeout` - The maximum amount of time to wait for the response.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Additionally, returns
+    /// [`Error::Timeout`] if no response is received before the timeout elapses.
+    ///
+    /// # Returns
+    /// Returns the component exchanges.
+    pub async fn req_smart_components_await(
+        &mut self,
+        exchange_id: ExchangeId,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<SmartComponent>, Error> {
+        let shared = Arc::clone(&self.status.shared);
+        if let Some(components) = shared
+            .smart_components_cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&exchange_id)
+        {
+            return Ok(components.clone());
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let req_id = self.reserve_req_id();
+        shared.smart_components.register(req_id);
+        if let Err(e) = self.req_smart_components(exchange_id.clone()).await {
+            shared.smart_components.cancel(&req_id);
+            return Err(e);
+        }
+        let components = shared
+            .smart_components
+            .wait(&req_id, deadline)
+            .await
+            .ok_or(Error::Timeout)?;
+        shared
+            .smart_components_cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(exchange_id, components.clone());
+        Ok(components)
+    }
+
+    #[must_use]
+    /// Get the exchange ID of a market data request, which is parsed from the request's
+    /// [`crate::wrapper::LocalWrapper::tick_params`] callback.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the market data request.
+    ///
+    /// # Returns
+    /// The exchange ID, or [`None`] if its tick parameters have not yet been received.
+    pub fn get_exchange_id(&self, req_id: i64) -> Option<ExchangeId> {
+        self.status
+            .shared
+            .exchange_ids
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&req_id)
+            .cloned()
+    }
+
+    #[must_use]
+    /// Resolve the letter of a [`crate::tick::QuotingExchanges`] tick to its component exchange.
+    ///
+    /// The mapping is built from every SMART components response received during the client's
+    /// session, so it is only available once [`Client::req_smart_components`] (or
+    /// [`Client::req_smart_components_await`]) has been answered.
+    ///
+    /// # Arguments
+    /// * `letter` - The letter that identifies the exchange.
+    ///
+    /// # Returns
+    /// The component exchange, or [`None`] if the letter has not been seen in a SMART components
+    /// response.
+    pub fn resolve_component_exchange(&self, letter: char) -> Option<Primary> {
+        self.status
+            .shared
+            .component_exchanges
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&letter)
+            .copied()
+    }
+
+    // === Market Scanners ===
+
+    /// Subscribe to a market scanner, which returns a ranked list of the securities that match
+    /// its criteria. The results are updated periodically until the subscription is cancelled.
+    ///
+    /// # Arguments
+    /// * `subscription` - The parameters of the scan.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_scanner_subscription(&mut self, subscription: &Subscription) -> IdResult {
+        let req_id = self.get_next_req_id();
+
+        self.writer.add_body((
+            Out::ReqScannerSubscription,
+            req_id,
+            subscription,
+            None::<()>,
+        ))?;
+        self.send().await?;
+        Ok(req_id)
+    }
+
+    /// Cancel an existing market scanner subscription.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID associated with the scanner subscription to cancel.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    pub async fn cancel_scanner_subscription(&mut self, req_id: i64) -> ReqResult {
+        const VERSION: u8 = 1;
+
+        self.writer
+            .add_body((Out::CancelScannerSubscription, VERSION, req_id))?;
+        self.send().await
+    }
+
+    // === Executions ===
+
+    /// Request execution all execution reports that fit the criteria specified in the `filter`.
+    ///
+    /// In order to view executions beyond the past 24 hours, open the Trade Log in TWS and, while
+    /// the Trade Log is displayed, request the executions again from the API.
+    ///
+    /// # Arguments
+    /// `filter` - The conditions with which to determine whether an execution will be returned.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Additionally, returns an
+    /// error if the filter's `account_number` is not in the client's managed accounts.
+    pub async fn req_executions(&mut self, filter: Filter) -> IdResult {
+        const VERSION: u8 = 3;
+        check_valid_account(self, &filter.account_number, "req_executions")?;
+        let req_id = self.get_next_req_id();
+
+        self.writer
+            .add_body((Out::ReqExecutions, VERSION, req_id, filter))?;
+        self.send().await?;
+        Ok(req_id)
+    }
+
+    // === Contract Creation ===
+
+    #[inline]
+    pub(crate) async fn send_contract_query(&mut self, query: &ContractQuery) -> IdResult {
+        const VERSION: u8 = 8;
+        let req_id = self.get_next_req_id();
+
+        match query {
+            ContractQuery::Id(contract_id) => self.writer.add_body((
+                Out::ReqContractData,
+                VERSION,
+                req_id,
+                contract_id,
+                [None::<()>; 15],
+            ))?,
+            ContractQuery::Symbol {
+                symbol,
+                contract_type,
+                exchange,
+                currency,
+            } => self.writer.add_body((
+                Out::ReqContractData,
+                VERSION,
+                req_id,
+                None::<()>,
+                symbol,
+                contract_type,
+                [None::<()>; 4],
+                exchange,
+                None::<()>,
+                currency,
+                [None::<()>; 6],
+            ))?,
+            ContractQuery::SecurityId(security_id) => self.writer.add_body((
+                Out::ReqContractData,
+                VERSION,
+                req_id,
+                [None::<()>; 13],
+                security_id.get_type(),
+                security_id.get_value(),
+                None::<()>,
+            ))?,
+        }
+        self.status.shared.contract_queries.register(req_id);
+        if let Err(e) = self.send().await {
+            self.status.shared.contract_queries.cancel(&req_id);
+            return Err(e);
+        }
+        Ok(req_id)
+    }
+
+    #[inline]
+    pub(crate) async fn resolve_contract(
+        &mut self,
+        contract_id: ContractId,
+    ) -> Result<crate::contract::Contract, Error> {
+        match self.get_cached_contract(contract_id) {
+            Some(contract) => Ok(contract),
+            None => {
+                let req_id = self
+                    .send_contract_query(&ContractQuery::Id(contract_id))
+                    .await?;
+                self.recv_contract_query(req_id).await
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) async fn recv_contract_query(
+        &self,
+        req_id: i64,
+    ) -> Result<crate::contract::Contract, Error> {
+        let deadline = tokio::time::Instant::now() + constants::CONTRACT_QUERY_TIMEOUT;
+        self.status
+            .shared
+            .contract_queries
+            .wait(&req_id, deadline)
+            .await
+            .ok_or(Error::Timeout)?
+    }
+
+    /// Request every contract that matches a description by symbol, type, exchange, and so on,
+    /// rather than by [`ContractId`]. Each matching contract is cached, so that it can later be
+    /// created with [`crate::contract::new`] without another request.
+    ///
+    /// # Arguments
+    /// * `builder` - The description of the contracts to find.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing or sending the request, if IBKR answers it
+    /// with an error (such as when no contract matches), or if it is not answered in time.
+    ///
+    /// # Returns
+    /// The matching contracts, in the order in which IBKR reported them.
+    pub async fn req_contract_details_by_symbol(
+        &mut self,
+        builder: &crate::contract::ContractBuilder,
+    ) -> Result<Vec<Contract>, Error> {
+        const VERSION: u8 = 8;
+        let req_id = self.get_next_req_id();
+        self.writer
+            .add_body((Out::ReqContractData, VERSION, req_id, builder))?;
+
+        let shared = &self.status.shared;
+        shared
+            .contract_search_results
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(req_id, Vec::new());
+        shared.contract_searches.register(req_id);
+        if let Err(e) = self.send().await {
+            let shared = &self.status.shared;
+            shared.contract_searches.cancel(&req_id);
+            shared
+                .contract_search_results
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .remove(&req_id);
+            return Err(e);
+        }
+
+        let deadline = tokio::time::Instant::now() + constants::CONTRACT_QUERY_TIMEOUT;
+        let shared = &self.status.shared;
+        let result = shared.contract_searches.wait(&req_id, deadline).await;
+        shared
+            .contract_search_results
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&req_id);
+        result.ok_or(Error::Timeout)?
+    }
+
+    /// Request the chain of options on an underlying contract: the exchanges, expirations,
+    /// strikes, and multiplier of each trading class of options on the contract.
+    ///
+    /// # Arguments
+    /// * `underlying` - The contract on which the options are written.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing or sending the request, if IBKR answers it
+    /// with an error, or if it is not answered in time.
+    ///
+    /// # Returns
+    /// The options of each trading class, in the order in which IBKR reported them.
+    pub async fn req_option_chain<S: Security>(
+        &mut self,
+        underlying: &S,
+    ) -> Result<Vec<OptionChain>, Error> {
+        let req_id = self.get_next_req_id();
+        // Only futures options are identified by the exchange of their underlying
+        let future_exchange =
+            (underlying.get_security_type() == "FUT").then(|| underlying.get_exchange());
+        self.writer.add_body((
+            Out::ReqSecDefOptParams,
+            req_id,
+            underlying.get_symbol(),
+            future_exchange,
+            underlying.get_security_type(),
+            underlying.get_contract_id(),
+        ))?;
+
+        let shared = Arc::clone(&self.status.shared);
+        shared
+            .option_chain_results
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(req_id, Vec::new());
+        shared.option_chains.register(req_id);
+        let result = match self.send().await {
+            Ok(()) => {
+                let deadline = tokio::time::Instant::now() + constants::OPTION_CHAIN_TIMEOUT;
+                shared.option_chains.wait(&req_id, deadline).await
+            }
+            Err(e) => {
+                shared.option_chains.cancel(&req_id);
+                Some(Err(e))
+            }
+        };
+        shared
+            .option_chain_results
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&req_id);
+        result.ok_or(Error::Timeout)?
+    }
+
+    /// Search for the contracts whose symbol or description matches a pattern, such as to
+    /// suggest contracts as a user types. IBKR limits these requests to one per second.
+    ///
+    /// The results are also delivered to the [`crate::wrapper::Local::symbol_samples`] (or
+    /// [`crate::wrapper::Remote::symbol_samples`]) callback.
+    ///
+    /// # Arguments
+    /// * `pattern` - The beginning of a symbol or of a company name (ex: "AAP" or "Apple").
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing or sending the request, if IBKR answers it
+    /// with an error (such as when requests are sent too frequently), or if it is not answered in
+    /// time.
+    ///
+    /// # Returns
+    /// The matching contracts, in the order in which IBKR ranked them.
+    pub async fn req_matching_symbols(
+        &mut self,
+        pattern: &str,
+    ) -> Result<Vec<SymbolSample>, Error> {
+        let req_id = self.get_next_req_id();
+        self.writer
+            .add_body((Out::ReqMatchingSymbols, req_id, pattern))?;
+
+        let shared = Arc::clone(&self.status.shared);
+        shared.matching_symbols.register(req_id);
+        if let Err(e) = self.send().await {
+            shared.matching_symbols.cancel(&req_id);
+            return Err(e);
+        }
+        let deadline = tokio::time::Instant::now() + constants::MATCHING_SYMBOLS_TIMEOUT;
+        shared
+            .matching_symbols
+            .wait(&req_id, deadline)
+            .await
+            .ok_or(Error::Timeout)?
+    }
+
+    /// Resolve many contracts at once, such as to load a large universe of securities at startup.
+    ///
+    /// Contracts that have already been created are taken from the cache (see
+    /// [`Client::get_cached_contract`]). The rest are requested with up to `max_in_flight` queries
+    /// outstanding at a time, so that the lookups run concurrently without overwhelming IBKR's
+    /// pacing limits.
+    ///
+    /// # Arguments
+    /// * `queries` - The contracts to resolve, each of which should identify a single contract.
+    /// * `max_in_flight` - The maximum number of queries awaiting an answer at any one time.
+    ///
+    /// # Returns
+    /// The result of each query in the same order as `queries`. A query fails if its request
+    /// could not be written, if IBKR answers it with an error (such as when no contract matches),
+    /// or if it is not answered in time.
+    pub async fn resolve_contracts(
+        &mut self,
+        queries: Vec<ContractQuery>,
+        max_in_flight: usize,
+    ) -> Vec<Result<Contract, Error>> {
+        let max_in_flight = max_in_flight.max(1);
+        let mut results = queries.iter().map(|_| None).collect::<Vec<_>>();
+        let mut in_flight = std::collections::VecDeque::with_capacity(max_in_flight);
+        let mut queries = queries.into_iter().enumerate();
+        loop {
+            while in_flight.len() < max_in_flight {
+                let Some((i, query)) = queries.next() else {
+                    break;
+                };
+                if let ContractQuery::Id(contract_id) = query {
+                    if let Some(contract) = self.get_cached_contract(contract_id) {
+                        results[i] = Some(Ok(contract));
+                        continue;
+                    }
+                }
+                match self.send_contract_query(&query).await {
+                    Ok(req_id) => in_flight.push_back((i, req_id)),
+                    Err(e) => results[i] = Some(Err(e)),
+                }
+            }
+            let Some((i, req_id)) = in_flight.pop_front() else {
+                break;
+            };
+            results[i] = Some(self.recv_contract_query(req_id).await);
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    // === Timers ===
+
+    /// Schedule a timer, which the client loop delivers to the wrapper's `timer` callback each
+    /// time it falls due.
+    ///
+    /// The timer runs on the same task as every other callback, so the wrapper never has to
+    /// synchronize its timer-driven logic with its event-driven logic. Timers follow the client's
+    /// [`Clock`] (see [`Client::set_clock`]), and a timer that falls due while a callback is
+    /// running is delivered once that callback returns.
+    ///
+    /// # Arguments
+    /// * `schedule` - When the timer falls due.
+    ///
+    /// # Returns
+    /// The ID of the timer, or [`None`] if the schedule never falls due.
+    pub fn schedule(&mut self, schedule: schedule::Schedule) -> Option<schedule::TimerId> {
+        let mut scheduler = schedule::lock_scheduler(&self.status.shared);
+        let now = scheduler.now();
+        let timer_id = scheduler.add(schedule, now)?;
+        drop(scheduler);
+        self.status.shared.scheduler_changed.notify_one();
+        Some(timer_id)
+    }
+
+    #[inline]
+    /// Cancel a timer that was scheduled with [`Client::schedule`].
+    ///
+    /// # Arguments
+    /// * `timer_id` - The ID of the timer to cancel.
+    ///
+    /// # Returns
+    /// Whether the timer was still scheduled. A one-shot timer that has already fallen due is no
+    /// longer scheduled.
+    pub fn cancel_timer(&mut self, timer_id: schedule::TimerId) -> bool {
+        schedule::lock_scheduler(&self.status.shared).cancel(timer_id)
+    }
+
+    // === Heartbeat ===
+
+    /// Start a heartbeat that periodically sends [`Client::req_current_time`] and measures how
+    /// long the IBKR platform takes to answer, replacing any heartbeat that is already running.
+    ///
+    /// The heartbeat runs in its own task, so the connection is monitored even while the client
+    /// is idle. Its observations, along with IBKR's connectivity errors (1100, 1101, and 1102),
+    /// are published as a [`ConnectionHealth`], which can be watched with
+    /// [`Client::watch_connection_health`]. Note that the answers to the heartbeat are also
+    /// delivered to the wrapper's `current_time` callback.
+    ///
+    /// # Arguments
+    /// * `interval` - The amount of time between heartbeats.
+    /// * `timeout` - The amount of time after which an unanswered heartbeat marks the connection
+    /// as [`ConnectionHealth::Unresponsive`]. No other heartbeat is sent until it is answered or
+    /// the client reconnects.
+    pub fn start_heartbeat(&mut self, interval: std::time::Duration, timeout: std::time::Duration) {
+        const VERSION: u8 = 1;
+
+        let cancel = self.status.disconnect.child_token();
+        if let Some(previous) = self
+            .status
+            .shared
+            .heartbeat
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .replace(cancel.clone())
+        {
+            previous.cancel();
+        }
+        let mut writer = self.writer.fork();
+        let shared = Arc::clone(&self.status.shared);
+
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    () = cancel.cancelled() => break,
+                    _ = ticks.tick() => {
+                        let now = std::time::Instant::now();
+                        let in_flight = {
+                            let mut requests = lock_current_time_requests(&shared);
+                            let sent = requests.iter().find_map(|request| match *request {
+                                CurrentTimeRequest::Heartbeat(at) => Some(at),
+                                CurrentTimeRequest::User => None,
+                            });
+                            match sent {
+                                Some(at) => {
+                                    if now - at >= timeout {
+                                        alert::set_health(
+                                            &shared,
+                                            ConnectionHealth::Unresponsive { elapsed: now - at },
+                                        );
+                                    }
+                                    true
+                                }
+                                None => {
+                                    requests.push_back(CurrentTimeRequest::Heartbeat(now));
+                                    false
+                                }
+                            }
+                        };
+                        // Wait for the previous heartbeat if it is still in flight, since the
+                        // answers arrive in the order in which the requests were sent
+                        if in_flight {
+                            continue;
+                        }
+                        let result = match writer.add_body((Out::ReqCurrentTime, VERSION)) {
+                            Ok(()) => writer.send().await,
+                            Err(e) => Err(e.into()),
+                        };
+                        if result.is_err() {
+                            // The heartbeat was never sent, so it cannot be answered
+                            lock_current_time_requests(&shared).retain(|request| {
+                                !matches!(request, CurrentTimeRequest::Heartbeat(_))
+                            });
+                        }
+                        match result {
+                            Ok(()) | Err(Error::RateLimited) => (),
+                            Err(_) => {
+                                alert::set_health(&shared, ConnectionHealth::Disconnected);
+                            }
+                        }
+                    },
+                }
+            }
+        });
+    }
+
+    #[inline]
+    /// Stop the heartbeat started by [`Client::start_heartbeat`], if any.
+    pub fn stop_heartbeat(&mut self) {
+        if let Some(heartbeat) = self
+            .status
+            .shared
+            .heartbeat
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()
+        {
+            heartbeat.cancel();
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the latest [`ConnectionHealth`] of the client.
+    pub fn get_connection_health(&self) -> ConnectionHealth {
+        self.status.shared.health.get()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return a receiver that is notified whenever the client's [`ConnectionHealth`] changes.
+    pub fn watch_connection_health(&self) -> tokio::sync::watch::Receiver<ConnectionHealth> {
+        self.status.shared.health.0.subscribe()
+    }
+
+    // === Reconnect ===
+
+    #[inline]
+    #[must_use]
+    /// Check whether the connection with the IBKR trading systems is still open. The connection is
+    /// lost when the socket is closed, for example because of a network failure or because TWS was
+    /// restarted.
+    ///
+    /// # Returns
+    /// [`false`] if the connection was lost and the client has not yet reconnected.
+    pub fn is_connected(&self) -> bool {
+        self.status
+            .r_thread
+            .as_ref()
+            .is_some_and(|r_thread| !r_thread.is_finished())
+    }
+
+    #[inline]
+    /// Set whether the client reconnects automatically. When enabled, any request that is made
+    /// after the connection was lost first calls [`Client::reconnect`] and is then sent over the
+    /// new connection.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to reconnect automatically. The default is [`false`].
+    pub fn set_auto_reconnect(&mut self, enabled: bool) {
+        self.status.auto_reconnect = enabled;
+    }
+
+    #[inline]
+    /// Set whether a subscription that could not be written because the connection was lost is
+    /// resent when the client reconnects, along with the subscriptions that are replayed by
+    /// [`Client::reconnect`]. Either way, the failed request returns [`Error::Unsent`].
+    ///
+    /// Only a failure to write is detected: the client keeps no buffer of the messages that IBKR
+    /// has not yet acknowledged. A subscription that was written before the connection was lost is
+    /// replayed regardless of this setting, but any other request (such as a historical data
+    /// request or a contract lookup) that was written and then lost in transit is not resent, and
+    /// a call that awaits its answer fails with [`Error::Timeout`]. Orders are never resent (see
+    /// [`Error::OrderNotSent`]).
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to resend failed subscriptions. The default is [`false`].
+    pub fn set_resend_subscriptions(&mut self, enabled: bool) {
+        self.status.resend_subscriptions = enabled;
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the IDs of the streaming market data subscriptions made with
+    /// [`Client::req_market_data`] that have not been cancelled.
+    pub fn get_active_market_data(&self) -> Vec<i64> {
+        self.get_active_requests(&[Out::ReqMktData])
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the IDs of the market depth subscriptions made with [`Client::req_market_depth`] that
+    /// have not been cancelled.
+    pub fn get_active_market_depth(&self) -> Vec<i64> {
+        self.get_active_requests(&[Out::ReqMktDepth])
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the IDs of the P&L subscriptions made with [`Client::req_pnl`] and
+    /// [`Client::req_single_position_pnl`] that have not been cancelled.
+    pub fn get_active_pnl(&self) -> Vec<i64> {
+        self.get_active_requests(&[Out::ReqPnl, Out::ReqPnlSingle])
+    }
+
+    #[inline]
+    fn get_active_requests(&self, requests: &[Out]) -> Vec<i64> {
+        let dropped = self
+            .status
+            .shared
+            .dropped_streams
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        self.status
+            .replay
+            .keys()
+            .filter(|replay| !dropped.contains(replay))
+            .filter_map(|replay| match replay {
+                Replay::Request(id, request) if requests.contains(request) => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[inline]
+    /// Register the stream of the next request, which cancels the request when it is dropped.
+    fn register_stream(&mut self, subscription: Subscription) -> EventStream {
+        let req_id = self.reserve_req_id();
+        EventStream::register(
+            req_id,
+            subscription,
+            self.writer.fork(),
+            &self.status.shared,
+        )
+    }
+
+    /// Reconnect to the IBKR trading systems, usually after the connection was lost.
+    ///
+    /// The client performs a new handshake with the same address, port, and client ID, restarts
+    /// the API, and then replays every subscription that has not been cancelled: streaming market
+    /// data (along with the market data type), account updates, positions, account summaries, P&L,
+    /// real-time bars, tick-by-tick data, and market depth. Each subscription is replayed with its
+    /// original request ID, so the wrapper keeps receiving its data without any changes. Orders
+    /// are not replayed, since they are kept by IBKR regardless of the connection.
+    ///
+    /// # Errors
+    /// Returns any error encountered while connecting, restarting the API, or replaying the
+    /// subscriptions.
+    pub async fn reconnect(&mut self) -> ReqResult {
+        self.status.reader_disconnect.cancel();
+        // The reader thread is only awaited once, even if an earlier attempt to reconnect failed
+        if let Some(r_thread) = self.status.r_thread.take() {
+            let _ = r_thread.await;
+        }
+        let _ = self.writer.shutdown().await;
+
+        let (reader, writer, server_version, conn_time) = open_connection(
+            &self.transport,
+            self.socket_options,
+            self.address,
+            self.port,
+        )
+        .await?;
+        self.writer.replace_connection(writer).await;
+        self.server_version = server_version;
+        self.conn_time = conn_time;
+        self.status.reader_disconnect = self.status.disconnect.child_token();
+        self.status.r_thread = Some(spawn_reader_thread(
+            reader,
+            &self.status.queue,
+            &self.status.reader_disconnect,
+            &self.status.shared,
+        ));
+        // The requests that were sent over the previous connection will never be answered
+        lock_current_time_requests(&self.status.shared).clear();
+        start_api(&mut self.writer, self.client_id).await?;
+        self.status.shared.reconnected.notify_one();
+
+        self.forget_dropped_streams();
+        for msg in self.status.replay.values() {
+            self.writer.add_raw(msg);
+        }
+        self.writer.send().await?;
+        Ok(())
+    }
+
+    async fn send(&mut self) -> ReqResult {
+        if self.status.auto_reconnect && !self.is_connected() {
+            let pending = self.writer.take_pending();
+            self.reconnect().await?;
+            self.writer.add_raw(&pending);
+        }
+        self.writer.send().await
+    }
+
+    /// Queue the pending request to be written once IBKR's historical data pacing rules permit,
+    /// without waiting for it to be written.
+    async fn send_paced(&mut self, req_id: i64) -> ReqResult {
+        if self.status.auto_reconnect && !self.is_connected() {
+            let pending = self.writer.take_pending();
+            self.reconnect().await?;
+            self.writer.add_raw(&pending);
+        }
+        let key = self.writer.last_pending_msg_without_id();
+        let msg = self.writer.take_pending();
+        let writer = &self.writer;
+        let disconnect = &self.status.disconnect;
+        self.status
+            .shared
+            .pacer
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .enqueue(key, req_id, msg, &self.status.shared, || {
+                (writer.fork(), disconnect.child_token())
+            });
+        Ok(())
+    }
+
+    #[inline]
+    /// Stop replaying the subscriptions of the streams that were dropped, which cancelled them.
+    fn forget_dropped_streams(&mut self) {
+        let dropped = std::mem::take(
+            &mut *self
+                .status
+                .shared
+                .dropped_streams
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        );
+        for replay in dropped {
+            self.status.replay.remove(&replay);
+        }
+    }
+
+    async fn send_and_replay(&mut self, replay: Replay) -> ReqResult {
+        self.forget_dropped_streams();
+        let msg = self.writer.get_pending().to_vec();
+        match self.send().await {
+            Ok(()) => {
+                self.status.replay.insert(replay, msg);
+                Ok(())
+            }
+            Err(Error::Io(source)) => {
+                let resend_on_reconnect = self.status.resend_subscriptions;
+                if resend_on_reconnect {
+                    self.status.replay.insert(replay, msg);
+                }
+                Err(Error::Unsent {
+                    resend_on_reconnect,
+                    source,
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // === Disconnect ==
+
+    #[inline]
+    /// Terminate the connection with the IBKR trading systems and return a [`Builder`] that can
+    /// be used to reconnect if necessary.
+    ///
+    /// # Errors
+    /// Returns any error encountered while flushing and shutting down the outgoing buffer.
+    ///
+    /// # Returns
+    /// Returns a [`Builder`] with the same port and address as the existing client.
+    pub async fn disconnect(mut self) -> Result<Builder, Error> {
+        self.writer.flush().await?;
+        self.writer.shutdown().await?;
+        self.status.disconnect.cancel();
+        // A reader thread that panicked must not prevent the client from disconnecting
+        if let Some(r_thread) = self.status.r_thread.take() {
+            if r_thread.await.is_err() {
+                event!(error, "Reader thread panicked");
+            }
+        }
+        Ok(Builder {
+            inner: Inner::Manual {
+                port: self.port,
+                address: self.address,
+            },
+            mismatch_policy: self.mismatch_policy,
+            rate_limit: self.writer.get_rate_limit(),
+            transport: self.transport,
+            socket_options: self.socket_options,
+        })
+    }
+}
+
+impl<N: indicators::Environment> Client<indicators::Active<indicators::Trading, N>> {
+    // ====================================================
+    // === Methods That Return Attributes of the Client ===
+    // ====================================================
+
+    // Don't worry about the allow: This function will NEVER panic
+    #[inline]
+    #[allow(clippy::missing_panics_doc, clippy::unwrap_used)]
+    /// Get the next valid *order* ID, as determined by the client's internal counter.
+    ///
+    /// When the client has an [`OrderIdStore`], the IDs are reserved in blocks of 100: the store is
+    /// only written when the counter reaches the end of the current block, at which point the end
+    /// of the next block is recorded as the high-water mark. A restarted client therefore skips
+    /// the unused remainder of a block rather than blocking on the store for every order.
+    ///
+    /// # Errors
+    /// Returns any error encountered while recording the high-water mark.
+    ///
+    /// # Returns
+    /// The next valid order ID
+    fn get_next_order_id(&mut self) -> Result<i64, Error> {
+        let id = self.status.order_id.next().unwrap();
+        if let Some(store) = &self.status.order_id_store {
+            if id >= self.status.order_id_reserved {
+                let reserved = id.saturating_add(constants::ORDER_ID_BLOCK);
+                store.save(reserved)?;
+                self.status.order_id_reserved = reserved;
+            }
+        }
+        Ok(id)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the expiration dates of the GTC and GTD orders placed by the client.
+    ///
+    /// An order is tracked from the time it is placed or modified until it is cancelled by the
+    /// client, or until IBKR reports that it is filled, cancelled, or inactive.
+    ///
+    /// # Returns
+    /// The [`Expiry`] of each order, keyed by order ID.
+    pub fn get_order_expirations(&self) -> std::collections::HashMap<i64, Expiry> {
+        lock_expirations(&self.status.shared).clone()
+    }
+
+    #[inline]
+    /// Set the clock from which the client tells the time, such as a
+    /// [`crate::clock::ReplayClock`] during a replay or backtest. The order expirations are dated
+    /// and checked, and the timers of [`Client::schedule`] fall due, according to this clock.
+    ///
+    /// # Arguments
+    /// * `clock` - The clock. The default is the [`SystemClock`].
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        schedule::lock_scheduler(&self.status.shared).set_clock(Arc::clone(&clock));
+        self.status.shared.scheduler_changed.notify_one();
+        self.status.clock = clock;
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the clock from which the client tells the time (see [`Client::set_clock`]).
+    pub fn get_clock(&self) -> Arc<dyn Clock> {
+        Arc::clone(&self.status.clock)
+    }
+
+    /// Set the store in which the client records the highest order ID it has used, so that order
+    /// IDs are never reused across restarts. The client's counter is immediately advanced past the
+    /// mark that was previously recorded in the store, and subsequent order IDs are reserved in
+    /// blocks of 100, so that the store is written once per block rather than once per order. A
+    /// restarted client therefore skips the unused remainder of the last block.
+    ///
+    /// # Arguments
+    /// * `store` - The store, such as a [`crate::order_id::FileOrderIdStore`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while loading or recording the high-water mark.
+    pub fn set_order_id_store(&mut self, store: Arc<dyn OrderIdStore>) -> Result<(), Error> {
+        let next_id = store.load()?.map_or(self.status.order_id.start, |id| {
+            id.max(self.status.order_id.start)
+        });
+        store.save(next_id)?;
+        self.status.order_id = next_id..;
+        self.status.order_id_reserved = next_id;
+        self.status.order_id_store = Some(store);
+        Ok(())
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the store in which the client records the highest order ID it has used (see
+    /// [`Client::set_order_id_store`]).
+    pub fn get_order_id_store(&self) -> Option<Arc<dyn OrderIdStore>> {
+        self.status.order_id_store.clone()
+    }
+
+    #[must_use]
+    /// Get the orders that are due to expire within a given period, so that they can be renewed
+    /// before they are automatically cancelled.
+    ///
+    /// # Arguments
+    /// * `within` - The number of days from today (UTC, according to the client's [`Clock`])
+    /// within which to look for expirations.
+    ///
+    /// # Returns
+    /// The ID and [`Expiry`] of each order that expires within the period, sorted by date.
+    pub fn get_expiring_orders(&self, within: chrono::Days) -> Vec<(i64, Expiry)> {
+        let today = self.status.clock.now().date_naive();
+        let horizon = today
+            .checked_add_days(within)
+            .unwrap_or(chrono::NaiveDate::MAX);
+        get_expiring(&lock_expirations(&self.status.shared), horizon)
+    }
+
+    /// Start a task that warns of the orders that are due to expire within a given period, or
+    /// stop the task that is running, if any.
+    ///
+    /// Every hour, the task looks for the orders that expire within the period (as with
+    /// [`Client::get_expiring_orders`]) and fires an [`crate::alert::Alert::OrderExpiring`] for
+    /// each of them, which is also delivered to the order's
+    /// [`crate::tracker::OrderTracker`] as an [`crate::tracker::OrderEvent::Expiring`]. Each
+    /// order is warned of once, unless it is modified and given a new expiration date.
+    ///
+    /// # Arguments
+    /// * `within` - The number of days from today (UTC, according to the client's [`Clock`])
+    /// within which an expiration is warned of, or [`None`] to stop warning.
+    pub fn set_expiry_warning(&mut self, within: Option<chrono::Days>) {
+        let previous = self
+            .status
+            .shared
+            .expiry_warning
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take();
+        if let Some(previous) = previous {
+            previous.cancel();
+        }
+        let Some(within) = within else {
+            return;
+        };
+        let cancel = self.status.disconnect.child_token();
+        *self
+            .status
+            .shared
+            .expiry_warning
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(cancel.clone());
+        let shared = Arc::clone(&self.status.shared);
 
-        self.writer
-            .add_body((Out::CancelMktData, VERSION, req_id))?;
-        self.writer.send().await
+        tokio::spawn(async move {
+            let mut warned = std::collections::HashMap::<i64, Expiry>::new();
+            let mut checks = tokio::time::interval(constants::EXPIRY_CHECK_INTERVAL);
+            loop {
+                tokio::select! {
+                    () = cancel.cancelled() => break,
+                    _ = checks.tick() => {
+                        let today = schedule::lock_scheduler(&shared).now().date_naive();
+                        let horizon = today
+                            .checked_add_days(within)
+                            .unwrap_or(chrono::NaiveDate::MAX);
+                        let expiring = {
+                            let expirations = lock_expirations(&shared);
+                            warned.retain(|id, expiry| expirations.get(id) == Some(expiry));
+                            get_expiring(&expirations, horizon)
+                        };
+                        for (order_id, expiry) in expiring {
+                            if warned.insert(order_id, expiry).is_none() {
+                                tracker::publish_expiring(&shared, order_id, expiry);
+                                let alert = alert::Alert::OrderExpiring { order_id, expiry };
+                                shared.alerts.fire(alert);
+                            }
+                        }
+                    },
+                }
+            }
+        });
     }
 
-    /// Set the market data variant for all succeeding `Client::req_market_data` requests.
+    #[inline]
+    fn track_expiry<S, E>(&mut self, id: i64, order: &Order<S, E>)
+    where
+        S: Security,
+        E: Executable<S>,
+    {
+        let mut expirations = lock_expirations(&self.status.shared);
+        match Expiry::new(
+            order.get_execute_method(),
+            self.status.clock.now().date_naive(),
+        ) {
+            Some(expiry) => expirations.insert(id, expiry),
+            None => expirations.remove(&id),
+        };
+    }
+
+    // ===================================
+    // === Methods That Make API Calls ===
+    // ===================================
+
+    // === Orders and order management ===
+
+    /// Re-synchronize the client's order ID counter with the server's next valid ID, which is
+    /// necessary after orders have been placed by another client or directly in TWS.
     ///
-    /// # Arguments
-    /// * `variant` - The variant to set.
+    /// The counter is only ever advanced, so that an order ID that has already been used by this
+    /// client is never reused. The new high-water mark is recorded in the client's
+    /// [`OrderIdStore`], if any.
     ///
     /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
-    pub async fn req_market_data_type(&mut self, variant: live_data::Class) -> ReqResult {
+    /// Returns any error encountered while writing the outgoing message or recording the
+    /// high-water mark. Also returns [`Error::Timeout`] if the server does not answer within ten
+    /// seconds.
+    ///
+    /// # Returns
+    /// The next order ID that the client will use.
+    pub async fn req_ids(&mut self) -> Result<i64, Error> {
         const VERSION: u8 = 1;
 
-        self.writer
-            .add_body((Out::ReqMarketDataType, VERSION, variant))?;
-        self.writer.send().await
+        let deadline = tokio::time::Instant::now() + constants::NEXT_VALID_ID_TIMEOUT;
+        let shared = Arc::clone(&self.status.shared);
+        // The number of IDs is ignored by the server, which always answers with a single ID
+        self.writer.add_body((Out::ReqIds, VERSION, 1))?;
+        shared.next_valid_ids.register(());
+        if let Err(e) = self.send().await {
+            shared.next_valid_ids.cancel(&());
+            return Err(e);
+        }
+        let valid_id = shared
+            .next_valid_ids
+            .wait(&(), deadline)
+            .await
+            .ok_or(Error::Timeout)?;
+        let next_id = valid_id.max(self.status.order_id.start);
+        if let Some(store) = &self.status.order_id_store {
+            // IDs below the reserved mark are already covered by the store
+            if next_id > self.status.order_id_reserved {
+                store.save(next_id)?;
+                self.status.order_id_reserved = next_id;
+            }
+        }
+        self.status.order_id = next_id..;
+        Ok(next_id)
     }
 
-    /// Request real-time, 5 second bars for a given security.
+    /// Place an order.
     ///
     /// # Arguments
-    /// * `security` - The security for which to request the bars.
-    /// * `data` - The type of data to return (trades, bid, ask, midpoint).
-    /// * `regular_trading_hours_only` -  When [`true`], only return ticks from regular trading
-    /// hours.
+    /// * `security` - The security on which to place the order.
+    /// * `order` - The order to execute.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
     ///
     /// # Returns
     /// Returns the unique ID associated with the request.
-    pub async fn req_real_time_bars<S, D>(
-        &mut self,
-        security: &S,
-        data: D,
-        regular_trading_hours_only: bool,
-    ) -> IdResult
+    pub async fn req_place_order<S, E>(&mut self, order: &Order<S, E>) -> IdResult
     where
         S: Security,
-        D: live_bar::data_types::DataType<S>,
+        E: Executable<S>,
     {
-        const VERSION: u8 = 3;
-        let id = self.get_next_req_id();
+        let id = self.get_next_order_id()?;
+        self.place_order(id, order).await?;
+        Ok(id)
+    }
 
+    /// Place an order with a given ID. See [`Client::req_place_order`] for details.
+    async fn place_order<S, E>(&mut self, id: i64, order: &Order<S, E>) -> ReqResult
+    where
+        S: Security,
+        E: Executable<S>,
+    {
         self.writer.add_body((
-            Out::ReqRealTimeBars,
-            VERSION,
+            Out::PlaceOrder,
             id,
-            security,
-            5_u32,
-            data,
-            regular_trading_hours_only,
+            order.get_security(),
+            None::<()>,
             None::<()>,
+            order,
         ))?;
-        self.writer.send().await?;
-        Ok(id)
+        // The order status may arrive before the write returns, so the order is tracked beforehand
+        self.lock_order_latency().placed(id);
+        if let Err(e) = self.send().await {
+            self.lock_order_latency().cancel(id);
+            return Err(e.for_order(id));
+        }
+        self.track_expiry(id, order);
+        Ok(())
     }
 
-    /// Cancel an existing real-time bars subscription.
+    /// Place an order and follow it with an [`OrderTracker`]. See [`Client::req_place_order`] for
+    /// details.
     ///
     /// # Arguments
-    /// `req_id` - The ID associated with the bar subscription to cancel.
+    /// * `order` - The order to execute.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
-    pub async fn cancel_real_time_bars(&mut self, req_id: i64) -> ReqResult {
-        const VERSION: u8 = 1;
-
-        self.writer
-            .add_body((Out::CancelRealTimeBars, VERSION, req_id))?;
-        self.writer.send().await
+    ///
+    /// # Returns
+    /// Returns the tracker of the order, which also holds its ID.
+    pub async fn req_place_order_tracked<S, E>(
+        &mut self,
+        order: &Order<S, E>,
+    ) -> Result<OrderTracker, Error>
+    where
+        S: Security,
+        E: Executable<S>,
+    {
+        // The first status may arrive before the write returns, so the tracker is registered
+        // beforehand
+        let id = self.get_next_order_id()?;
+        let tracker = OrderTracker::register(id, &self.status.shared);
+        self.place_order(id, order).await?;
+        Ok(tracker)
     }
 
-    // === Live Tick-by-Tick Data ===
-
-    /// Request live tick-by-tick data for a given security.
+    /// Preview the margin and commission impact of an order without placing it.
     ///
     /// # Arguments
-    /// * `security` - The security for which to request data.
-    /// * `tick_data` - The type of data to return.
-    /// * `number_of_historical_ticks` - The number of historical ticks to return before the live
-    /// data.
-    /// * `ignore_size` - Ignore the size parameter in the returned ticks when set to [`true`].
+    /// * `order` - The order to preview.
+    /// * `timeout` - The maximum amount of time to wait for the preview.
     ///
     /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
+    /// Returns any error encountered while writing the outgoing message, [`Error::Api`] if IBKR
+    /// rejects the order, and [`Error::Timeout`] if no preview is received before the timeout
+    /// elapses.
     ///
     /// # Returns
-    /// Returns the unique ID associated with the request.
-    pub async fn req_tick_by_tick_data<S, D>(
+    /// The order's margin and commission impact.
+    pub async fn req_what_if_order<S, E>(
         &mut self,
-        security: &S,
-        tick_data: D,
-        number_of_historical_ticks: live_ticks::NumberOfTicks,
-        ignore_size: bool,
-    ) -> IdResult
+        order: &Order<S, E>,
+        timeout: std::time::Duration,
+    ) -> Result<OrderState, Error>
     where
         S: Security,
-        D: live_ticks::data_types::DataType<S>,
+        E: Executable<S>,
     {
-        let id = self.get_next_req_id();
+        let id = self.get_next_order_id()?;
+        let mut attributes = order.get_attributes().clone();
+        attributes.what_if = Some(true);
 
         self.writer.add_body((
-            Out::ReqTickByTickData,
+            Out::PlaceOrder,
             id,
-            security,
-            tick_data,
-            number_of_historical_ticks,
-            ignore_size,
+            order.get_security(),
+            None::<()>,
+            None::<()>,
+            order.with_attributes(attributes),
         ))?;
-        self.writer.send().await?;
-        Ok(id)
+        self.status.shared.what_if_orders.register(id);
+        if let Err(e) = self.send().await {
+            self.status.shared.what_if_orders.cancel(&id);
+            return Err(e);
+        }
+        let deadline = tokio::time::Instant::now() + timeout;
+        self.status
+            .shared
+            .what_if_orders
+            .wait(&id, deadline)
+            .await
+            .ok_or(Error::Timeout)?
     }
 
-    /// Cancel an existing tick-by-tick data subscription.
+    #[inline]
+    #[must_use]
+    /// Get the time between writing an order placed with [`Client::req_place_order`] and receiving
+    /// its first order status, which measures the round trip to the IBKR platform.
     ///
     /// # Arguments
-    /// * `req_id` - The request ID of the subscription to cancel.
+    /// * `order_id` - The ID of the order.
     ///
-    /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
-    pub async fn cancel_tick_by_tick_data(&mut self, req_id: i64) -> ReqResult {
-        self.writer.add_body((Out::CancelTickByTickData, req_id))?;
-        self.writer.send().await
+    /// # Returns
+    /// The order's round trip, or [`None`] if no order status has been received for the order or
+    /// if the order is done (filled, cancelled, or inactive), after which it is forgotten.
+    pub fn get_order_latency(&self, order_id: i64) -> Option<std::time::Duration> {
+        self.lock_order_latency().get(order_id)
     }
 
-    // === Market Depth ===
+    #[inline]
+    #[must_use]
+    /// Get statistics of the round trips measured by [`Client::get_order_latency`] for the 1,000
+    /// most recent orders.
+    ///
+    /// # Returns
+    /// The statistics, or [`None`] if no round trip has been measured yet.
+    pub fn get_order_latency_stats(&self) -> Option<LatencyStats> {
+        self.lock_order_latency().stats()
+    }
 
-    /// Request market depth data for a given security.
+    #[inline]
+    fn lock_order_latency(&self) -> std::sync::MutexGuard<'_, LatencyTracker> {
+        self.status
+            .shared
+            .order_latency
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Place a parent order to which other orders can be attached with [`Client::req_attach_order`]
+    /// and [`Client::req_attach_last_order`].
+    ///
+    /// The parent order is not transmitted until the last order is attached, which ensures that no
+    /// attached order is left unprotected if the parent is filled immediately.
     ///
     /// # Arguments
-    /// * `security` - The security for which to return the market depth data.
-    /// * `number_of_rows` - The maximum number of rows in the returned limit order book.
+    /// * `order` - The parent order to execute.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
     ///
     /// # Returns
-    /// Returns the unique ID associated with the request.
-    pub async fn req_market_depth<S>(&mut self, security: &S, number_of_rows: u32) -> IdResult
+    /// Returns a [`ParentOrder`] to which the other orders can be attached.
+    pub async fn req_place_parent_order<S, E>(
+        &mut self,
+        order: &Order<S, E>,
+    ) -> Result<ParentOrder, Error>
     where
         S: Security,
+        E: Executable<S>,
     {
-        const VERSION: u8 = 5;
-        let id = self.get_next_req_id();
-
-        self.writer.add_body((
-            Out::ReqMktDepth,
-            VERSION,
+        let id = self
+            .req_place_order(&order.with_attributes(Attributes {
+                transmit: Some(false),
+                ..order.get_attributes().clone()
+            }))
+            .await?;
+        Ok(ParentOrder {
             id,
-            security,
-            number_of_rows,
-            true,
-            None::<()>,
-        ))?;
-        self.writer.send().await?;
-        Ok(id)
+            children: Vec::new(),
+        })
     }
 
-    /// Request exchanges available for market depth.
+    /// Attach an order (ex: a stop loss, profit taker or hedge) to a parent order placed with
+    /// [`Client::req_place_parent_order`]. The order is staged, but not transmitted.
+    ///
+    /// # Arguments
+    /// * `parent` - The parent order to which the order is attached.
+    /// * `order` - The order to attach.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
-    pub async fn req_market_depth_exchanges(&mut self) -> ReqResult {
-        self.writer.add_body(Out::ReqMktDepthExchanges)?;
-        self.writer.send().await
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_attach_order<S, E>(
+        &mut self,
+        parent: &mut ParentOrder,
+        order: &Order<S, E>,
+    ) -> IdResult
+    where
+        S: Security,
+        E: Executable<S>,
+    {
+        let id = self
+            .req_place_order(&order.with_attributes(Attributes {
+                parent_id: Some(parent.id),
+                transmit: Some(false),
+                ..order.get_attributes().clone()
+            }))
+            .await?;
+        parent.children.push(id);
+        Ok(id)
     }
 
-    /// Cancel a market depth subscription for a given `req_id`.
+    /// Attach the final order to a parent order placed with [`Client::req_place_parent_order`].
+    /// This order is transmitted, which in turn transmits the parent and all of the other orders
+    /// that were attached to it.
     ///
     /// # Arguments
-    /// * `req_id` - The request ID for which to cancel a market depth subscription.
+    /// * `parent` - The parent order to which the order is attached.
+    /// * `order` - The order to attach.
     ///
     /// # Errors
     /// Returns any error encountered while writing the outgoing message.
-    pub async fn cancel_market_depth(&mut self, req_id: i64) -> ReqResult {
-        const VERSION: u8 = 1;
-
-        self.writer
-            .add_body((Out::CancelMktDepth, VERSION, req_id))?;
-        self.writer.send().await
+    ///
+    /// # Returns
+    /// Returns the IDs of the parent order and all of its attached orders, in the order in which
+    /// they were placed.
+    pub async fn req_attach_last_order<S, E>(
+        &mut self,
+        mut parent: ParentOrder,
+        order: &Order<S, E>,
+    ) -> Result<Vec<i64>, Error>
+    where
+        S: Security,
+        E: Executable<S>,
+    {
+        let id = self
+            .req_place_order(&order.with_attributes(Attributes {
+                parent_id: Some(parent.id),
+                transmit: Some(true),
+                ..order.get_attributes().clone()
+            }))
+            .await?;
+        parent.children.push(id);
+        Ok(std::iter::once(parent.id).chain(parent.children).collect())
     }
 
-    /// Request exchanges comprising the aggregate SMART exchange
+    /// Place a [`BracketOrder`]: an entry order along with its take-profit and stop-loss orders.
+    ///
+    /// The entry and take-profit orders are staged, and the stop-loss order is placed last, which
+    /// transmits all three at once. If an order fails to be placed, the orders that were already
+    /// staged are cancelled, so that the entry order is never transmitted without its exits.
     ///
     /// # Arguments
-    /// * `exchange_id` - The identifier containing information about the component exchanges, which
-    /// is attained from an initial market data callback.
+    /// * `bracket` - The bracket to place.
     ///
     /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
+    /// Returns the first error encountered while writing an outgoing message.
     ///
     /// # Returns
-    /// Returns the unique ID associated with the request.
-    pub async fn req_smart_components(&mut self, exchange_id: ExchangeId) -> IdResult {
-        let id = self.get_next_req_id();
-
-        self.writer
-            .add_body((Out::ReqSmartComponents, id, exchange_id))?;
-        self.writer.send().await?;
-        Ok(id)
+    /// Returns the IDs of the bracket's orders.
+    pub async fn req_place_bracket<S, E>(
+        &mut self,
+        bracket: &BracketOrder<S, E>,
+    ) -> Result<BracketOrderIds, Error>
+    where
+        S: Security,
+        E: Executable<S>,
+        Limit: Executable<S>,
+        Stop: Executable<S>,
+    {
+        let mut parent = self.req_place_parent_order(bracket.get_parent()).await?;
+        let parent_id = parent.get_id();
+        let take_profit = match self
+            .req_attach_order(&mut parent, bracket.get_take_profit())
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                // The original error is more useful than any error encountered here
+                let _ = self.cancel_order(parent_id).await;
+                return Err(e);
+            }
+        };
+        let stop_loss = match self
+            .req_attach_last_order(parent, bracket.get_stop_loss())
+            .await
+        {
+            Ok(ids) => ids[2],
+            Err(e) => {
+                for id in [take_profit, parent_id] {
+                    let _ = self.cancel_order(id).await;
+                }
+                return Err(e);
+            }
+        };
+        Ok(BracketOrderIds {
+            parent: parent_id,
+            take_profit,
+            stop_loss,
+        })
     }
 
-    // === Orders and order management ===
-
-    /// Place an order.
+    /// Place a basket of orders as a single unit.
+    ///
+    /// Every order is first staged (placed without being transmitted). Only once the whole basket
+    /// has been staged successfully is each order transmitted. If any order fails to be staged or
+    /// transmitted, every order in the basket is cancelled, including those that were already
+    /// transmitted, so that the basket is not left partially live. An order that was transmitted
+    /// before the failure may nevertheless have been filled before its cancellation reached IBKR.
     ///
     /// # Arguments
-    /// * `security` - The security on which to place the order.
-    /// * `order` - The order to execute.
+    /// * `orders` - The orders to place.
     ///
     /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
+    /// Returns the first error encountered while writing an outgoing message, after attempting to
+    /// cancel every order in the basket.
     ///
     /// # Returns
-    /// Returns the unique ID associated with the request.
-    pub async fn req_place_order<S, E>(&mut self, order: &Order<S, E>) -> IdResult
+    /// Returns the unique IDs associated with each order, in the same order as `orders`.
+    pub async fn req_place_basket<S, E>(
+        &mut self,
+        orders: Vec<Order<S, E>>,
+    ) -> Result<Vec<i64>, Error>
     where
         S: Security,
         E: Executable<S>,
     {
-        let id = self.get_next_order_id();
-
-        self.writer.add_body((
-            Out::PlaceOrder,
-            id,
-            order.get_security(),
-            None::<()>,
-            None::<()>,
-            order,
-        ))?;
-        self.writer.send().await?;
-        Ok(id)
+        let mut ids = Vec::with_capacity(orders.len());
+        for order in &orders {
+            match self
+                .req_place_order(&order.with_attributes(Attributes {
+                    transmit: Some(false),
+                    ..order.get_attributes().clone()
+                }))
+                .await
+            {
+                Ok(id) => ids.push(id),
+                Err(e) => {
+                    for id in ids {
+                        // The original error is more useful than any error encountered here
+                        let _ = self.cancel_order(id).await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        for (order, id) in orders.iter().zip(&ids) {
+            if let Err(e) = self
+                .req_modify_order(
+                    &order.with_attributes(Attributes {
+                        transmit: Some(true),
+                        ..order.get_attributes().clone()
+                    }),
+                    *id,
+                )
+                .await
+            {
+                // Both the transmitted orders and the staged remainder are cancelled
+                for id in &ids {
+                    let _ = self.cancel_order(*id).await;
+                }
+                return Err(e);
+            }
+        }
+        Ok(ids)
     }
 
     /// Modify an order.
@@ -2504,7 +6994,8 @@ impl Client<indicators::Active> {
             None::<()>,
             order,
         ))?;
-        self.writer.send().await?;
+        self.send().await.map_err(|e| e.for_order(id))?;
+        self.track_expiry(id, order);
         Ok(id)
     }
 
@@ -2520,7 +7011,9 @@ impl Client<indicators::Active> {
 
         self.writer
             .add_body((Out::CancelOrder, VERSION, id, None::<()>))?;
-        self.writer.send().await
+        self.send().await.map_err(|e| e.for_order(id))?;
+        lock_expirations(&self.status.shared).remove(&id);
+        Ok(())
     }
 
     /// Cancel all currently open orders, including those placed in TWS.
@@ -2531,7 +7024,9 @@ impl Client<indicators::Active> {
         const VERSION: u8 = 1;
 
         self.writer.add_body((Out::ReqGlobalCancel, VERSION))?;
-        self.writer.send().await
+        self.send().await?;
+        lock_expirations(&self.status.shared).clear();
+        Ok(())
     }
 
     /// Request all the open orders placed from all API clients and from TWS.
@@ -2545,7 +7040,7 @@ impl Client<indicators::Active> {
         const VERSION: u8 = 1;
 
         self.writer.add_body((Out::ReqAllOpenOrders, VERSION))?;
-        self.writer.send().await
+        self.send().await
     }
 
     /// Request that all newly created TWS orders will be implicitly associated with the calling
@@ -2561,7 +7056,7 @@ impl Client<indicators::Active> {
 
         self.writer
             .add_body((Out::ReqAutoOpenOrders, VERSION, true))?;
-        self.writer.send().await
+        self.send().await
     }
 
     /// Request the open orders that were placed from the calling client.
@@ -2574,105 +7069,853 @@ impl Client<indicators::Active> {
         const VERSION: u8 = 1;
 
         self.writer.add_body((Out::ReqOpenOrders, VERSION))?;
-        self.writer.send().await
+        self.send().await
     }
 
-    // === Executions ===
+    /// Request the open orders and wait for all of them to be received.
+    ///
+    /// The orders are requested with [`Client::req_all_open_orders`] when `all_clients` is
+    /// [`true`] and with [`Client::req_open_orders`] otherwise, so the same caveats apply: the
+    /// former includes orders placed by other clients and in TWS, while the latter only includes
+    /// the orders placed by this client (and those placed in TWS, for a client with ID 0). Each
+    /// order is also delivered to the wrapper's [`crate::wrapper::Remote::open_order`] callback as
+    /// usual. An order that is reported more than once while waiting, for example because its
+    /// status changed, appears only once with its latest details.
+    ///
+    /// # Arguments
+    /// * `all_clients` - Whether to include the orders placed by other clients.
+    /// * `timeout` - The maximum amount of time to wait for the end of the open orders.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message. Also returns
+    /// [`Error::Timeout`] if the end of the open orders is not received before the timeout elapses.
+    ///
+    /// # Returns
+    /// The open orders, in the order in which they were received.
+    pub async fn open_orders_await(
+        &mut self,
+        all_clients: bool,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<OpenOrder>, Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let shared = Arc::clone(&self.status.shared);
+        shared
+            .open_orders_ended
+            .store(false, std::sync::atomic::Ordering::Release);
+        *shared
+            .open_orders
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Vec::new());
+
+        let sent = if all_clients {
+            self.req_all_open_orders().await
+        } else {
+            self.req_open_orders().await
+        };
+        let ended = sent.is_ok()
+            && loop {
+                let notified = shared.open_orders_notify.notified();
+                if shared
+                    .open_orders_ended
+                    .load(std::sync::atomic::Ordering::Acquire)
+                {
+                    break true;
+                }
+                if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                    break false;
+                }
+            };
+        let orders = shared
+            .open_orders
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()
+            .unwrap_or_default();
+        sent?;
+        if ended {
+            Ok(orders)
+        } else {
+            Err(Error::Timeout)
+        }
+    }
 
-    /// Request execution all execution reports that fit the criteria specified in the `filter`.
+    /// Exercise an option position, or let it lapse, ahead of its expiration.
     ///
-    /// In order to view executions beyond the past 24 hours, open the Trade Log in TWS and, while
-    /// the Trade Log is displayed, request the executions again from the API.
+    /// # Arguments
+    /// * `security` - The option to exercise.
+    /// * `action` - Whether to exercise the options or let them lapse.
+    /// * `quantity` - The number of contracts to exercise.
+    /// * `account` - The account holding the position.
+    /// * `override_default` - When [`true`], override the exchange's default action for the
+    /// options (ex: exercise an option that is out of the money at expiration).
+    ///
+    /// # Errors
+    /// Returns an error if the account is not managed by the client, or any error encountered
+    /// while writing the outgoing message.
+    ///
+    /// # Returns
+    /// Returns the unique ID associated with the request.
+    pub async fn req_exercise_options(
+        &mut self,
+        security: &SecOption,
+        action: ExerciseAction,
+        quantity: u32,
+        account: &AccountId,
+        override_default: bool,
+    ) -> IdResult {
+        const VERSION: u8 = 2;
+
+        check_valid_account(self, account, "req_exercise_options")?;
+        let id = self.get_next_req_id();
+        self.writer.add_body((
+            Out::ExerciseOptions,
+            VERSION,
+            id,
+            (
+                security.get_contract_id(),
+                security.get_symbol(),
+                security.get_security_type(),
+                security
+                    .get_expiration_date()
+                    .map(|date| date.format("%Y%m%d").to_string()),
+                security.get_strike(),
+                security.get_right(),
+                security.get_multiplier(),
+                security.get_exchange(),
+                security.get_currency(),
+                security.get_local_symbol(),
+                security.get_trading_class(),
+            ),
+            action,
+            quantity,
+            account,
+            override_default,
+            None::<()>,
+        ))?;
+        self.send().await?;
+        Ok(id)
+    }
+
+    // === Position flattening ===
+
+    /// Close a position in every account that holds it, as reported by [`Client::req_positions`]
+    /// or [`Client::req_account_updates`].
     ///
     /// # Arguments
-    /// `filter` - The conditions with which to determine whether an execution will be returned.
+    /// * `contract` - The contract whose position to close.
+    /// * `style` - How the closing orders are priced.
     ///
     /// # Errors
-    /// Returns any error encountered while writing the outgoing message.
-    pub async fn req_executions(&mut self, filter: Filter) -> IdResult {
-        const VERSION: u8 = 3;
-        let req_id = self.get_next_req_id();
+    /// Returns [`Error::PositionsUnknown`] if the positions of any of the client's managed accounts
+    /// have not been received, [`Error::MissingMarketPrice`] if an
+    /// [`FlattenStyle::AggressiveLimit`] order cannot be priced, in which case no order is placed,
+    /// and any error encountered while placing the orders. Orders that were placed before such an
+    /// error remain active.
+    ///
+    /// # Returns
+    /// The [`Flattening`] that follows the closing orders until the position is flat.
+    pub async fn flatten_position(
+        &mut self,
+        contract: &Contract,
+        style: FlattenStyle,
+    ) -> Result<Flattening, Error> {
+        for account_number in &self.status.managed_accounts {
+            check_positions_known(self, account_number)?;
+        }
+        let contract_id = contract.get_contract_id();
+        let holdings = self
+            .status
+            .shared
+            .positions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .filter(|((_, id), _)| *id == contract_id)
+            .map(|((account_number, _), holding)| (account_number.clone(), *holding))
+            .collect::<Vec<_>>();
+        let closing = holdings
+            .into_iter()
+            .map(|(account_number, holding)| {
+                Ok((
+                    account_number,
+                    contract.clone(),
+                    get_closing_order(contract, holding, style)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.req_place_closing_orders(closing).await
+    }
 
-        self.writer
-            .add_body((Out::ReqExecutions, VERSION, req_id, filter))?;
-        self.writer.send().await?;
-        Ok(req_id)
+    /// Close every position in an account, as reported by [`Client::req_positions`] or
+    /// [`Client::req_account_updates`].
+    ///
+    /// # Arguments
+    /// * `account_number` - The account whose positions to close.
+    /// * `style` - How the closing orders are priced.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidAccount`] if the account is not managed by the client,
+    /// [`Error::PositionsUnknown`] if its positions have not been received, any error encountered
+    /// while creating the positions' contracts, and [`Error::MissingMarketPrice`] if an
+    /// [`FlattenStyle::AggressiveLimit`] order cannot be priced, in which case no order is
+    /// placed. Additionally, this function will error if an order cannot be placed, in which case
+    /// the orders that were placed before the error remain active.
+    ///
+    /// # Returns
+    /// The [`Flattening`] that follows the closing orders until every position is flat.
+    pub async fn flatten_all(
+        &mut self,
+        account_number: &AccountId,
+        style: FlattenStyle,
+    ) -> Result<Flattening, Error> {
+        check_valid_account(self, account_number, "flatten_all")?;
+        check_positions_known(self, account_number)?;
+        let holdings = self
+            .status
+            .shared
+            .positions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .filter(|((acct, _), _)| acct == account_number)
+            .map(|((_, contract_id), holding)| (*contract_id, *holding))
+            .collect::<Vec<_>>();
+        let mut closing = Vec::with_capacity(holdings.len());
+        for (contract_id, holding) in holdings {
+            let contract = self.resolve_contract(contract_id).await?;
+            let order = get_closing_order(&contract, holding, style)?;
+            closing.push((account_number.clone(), contract, order));
+        }
+        self.req_place_closing_orders(closing).await
     }
 
-    // === Contract Creation ===
+    async fn req_place_closing_orders(
+        &mut self,
+        closing: Vec<(AccountId, Contract, ClosingOrder)>,
+    ) -> Result<Flattening, Error> {
+        let mut flattening = Flattening::default();
+        for (account_number, contract, (side, quantity, price)) in closing {
+            let contract_id = contract.get_contract_id();
+            let tracker = crate::contract_dispatch! {
+                contract =>
+                    async (Self::req_place_simple_order)
+                    (self)
+                    (&account_number, side, quantity, price)
+            }?;
+            flattening.push(account_number, contract_id, tracker);
+        }
+        Ok(flattening)
+    }
 
-    #[inline]
-    pub(crate) async fn send_contract_query(
+    // === Soft stops ===
+
+    /// Check the soft stops in a [`StopBook`] against a price of their contract, and submit the
+    /// orders that close the positions of those that are breached. Each triggered stop is removed
+    /// from the book once its order is placed, or once its position is found to be flat.
+    ///
+    /// The stops are only checked when this method is called, so it should be called with every
+    /// new price of the contracts in [`StopBook::get_contract_ids`], unless the prices are watched
+    /// by [`Client::watch_soft_stops`]. The positions are taken from [`Client::req_positions`] or
+    /// [`Client::req_account_updates`], one of which must be active.
+    ///
+    /// # Arguments
+    /// * `book` - The stops.
+    /// * `contract_id` - The ID of the contract whose price changed.
+    /// * `price` - The contract's new price.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidAccount`] if a triggered stop's account is not managed by the
+    /// client, [`Error::PositionsUnknown`] if its account's positions have not been received, in
+    /// which case the stop is kept, [`Error::MissingMarketPrice`] if the price is not a finite
+    /// number, and any error encountered while resolving a contract, placing an order, or writing
+    /// the book to its file. Orders that were placed before such an error
+    /// remain active, and their stops are removed from the book.
+    ///
+    /// # Returns
+    /// The [`Flattening`] that follows the closing orders, which is empty if no stop was
+    /// triggered.
+    pub async fn trigger_soft_stops(
         &mut self,
+        book: &mut StopBook,
         contract_id: ContractId,
-    ) -> anyhow::Result<()> {
-        const VERSION: u8 = 8;
-        let req_id = self.get_next_req_id();
-        self.status
-            .tx
-            .send(ToWrapper::ContractQuery((contract_id, req_id)))
-            .await?;
+        price: f64,
+    ) -> Result<Flattening, Error> {
+        let mut flattening = Flattening::default();
+        // Several stops may protect the same position, so the quantity they close is tracked to
+        // avoid closing more than the position itself
+        let mut closed = std::collections::HashMap::<AccountId, Quantity>::new();
+        for (stop_id, stop) in book.get_breached(contract_id, price) {
+            let account_number = stop.get_account_number();
+            check_valid_account(self, account_number, "trigger_soft_stops")?;
+            // An unknown position must not be mistaken for a flat one, which would discard the stop
+            check_positions_known(self, account_number)?;
+            let position = self
+                .get_position(account_number, contract_id)
+                .unwrap_or(Quantity::ZERO);
+            let open = match stop.get_side() {
+                OrderSide::Sell => position,
+                OrderSide::Buy => -position,
+            } - closed
+                .get(account_number)
+                .copied()
+                .unwrap_or(Quantity::ZERO);
+            let quantity = stop
+                .get_quantity()
+                .map_or(open, |quantity| quantity.min(open));
+            if quantity > Quantity::ZERO {
+                let contract = self.resolve_contract(contract_id).await?;
+                let limit_price =
+                    stop.get_style()
+                        .get_limit_price(stop.get_side(), price, &contract)?;
+                let tracker = crate::contract_dispatch! {
+                    contract =>
+                        async (Self::req_place_simple_order)
+                        (self)
+                        (account_number, stop.get_side(), quantity, limit_price)
+                }?;
+                flattening.push(account_number.clone(), contract_id, tracker);
+                *closed.entry(account_number.clone()).or_default() += quantity;
+            }
+            book.remove(stop_id)?;
+        }
+        Ok(flattening)
+    }
 
-        self.writer.add_body((
-            Out::ReqContractData,
-            VERSION,
-            req_id,
-            contract_id,
-            [None::<()>; 15],
-        ))?;
-        self.writer.send().await?;
-        Ok(())
+    /// Watch the live prices of the contracts in a [`StopBook`], and trigger its stops with
+    /// [`Client::trigger_soft_stops`] as they are breached.
+    ///
+    /// The client subscribes to the market data of each contract in the book when it is first
+    /// watched, and cancels the subscription once the contract no longer has a stop. The
+    /// subscriptions are kept between calls, so this method should be called in a loop for as long
+    /// as the stops must be protected.
+    ///
+    /// # Arguments
+    /// * `book` - The stops.
+    ///
+    /// # Errors
+    /// Returns any error encountered while subscribing to or cancelling market data, any error
+    /// returned by [`Client::trigger_soft_stops`], and [`Error::Disconnected`] if the client loop
+    /// stopped while stops remained.
+    ///
+    /// # Returns
+    /// The [`Flattening`] that follows the closing orders of the first breached stops, which is
+    /// empty if the book has no stops left.
+    pub async fn watch_soft_stops(&mut self, book: &mut StopBook) -> Result<Flattening, Error> {
+        loop {
+            for prices in book.take_unneeded() {
+                self.cancel_market_data(prices.get_req_id()).await?;
+            }
+            for contract_id in book.get_unwatched() {
+                let contract = self.resolve_contract(contract_id).await?;
+                let prices = crate::contract_dispatch! {
+                    &contract =>
+                        async (Self::req_market_data_stream)
+                        (self)
+                        (
+                            vec![live_data::data_types::Empty],
+                            live_data::RefreshType::Streaming,
+                            false
+                        )
+                }?;
+                book.watch(contract_id, prices);
+            }
+            if book.is_empty() {
+                return Ok(Flattening::default());
+            }
+            let (contract_id, price) = book.next_price().await.ok_or(Error::Disconnected)?;
+            let flattening = self.trigger_soft_stops(book, contract_id, price).await?;
+            if !flattening.get_order_ids().is_empty() {
+                return Ok(flattening);
+            }
+        }
     }
 
-    #[inline]
-    pub(crate) async fn recv_contract_query(
+    // === Drawdown actions ===
+
+    /// Wait for the next [`DrawdownEvent`] of a [`DrawdownMonitor`] and carry out its action with
+    /// [`Client::apply_drawdown_action`]. The monitor's rules are enforced for as long as this
+    /// method is called in a loop.
+    ///
+    /// # Arguments
+    /// * `monitor` - The monitor whose events to act upon.
+    ///
+    /// # Errors
+    /// Returns any error returned by [`Client::apply_drawdown_action`].
+    ///
+    /// # Returns
+    /// The event and the [`Flattening`] that follows its closing orders, or [`None`] if the
+    /// values that the monitor follows are no longer supplied.
+    pub async fn enforce_drawdown(
         &mut self,
-    ) -> anyhow::Result<crate::contract::Contract> {
-        match self
+        monitor: &mut DrawdownMonitor,
+    ) -> Result<Option<(DrawdownEvent, Flattening)>, Error> {
+        let Some(event) = monitor.next_event().await else {
+            return Ok(None);
+        };
+        let flattening = self.apply_drawdown_action(&event).await?;
+        Ok(Some((event, flattening)))
+    }
+
+    /// Carry out the action of a [`DrawdownEvent`] emitted by a [`DrawdownMonitor`], by closing
+    /// all or part of the positions in the scope of the breached rule. The positions are taken
+    /// from [`Client::req_positions`] or [`Client::req_account_updates`], one of which must be
+    /// active.
+    ///
+    /// # Arguments
+    /// * `event` - The event whose action to carry out.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidAccount`] if the event's account is not managed by the client,
+    /// [`Error::PositionsUnknown`] if its positions have not been received,
+    /// [`Error::MissingMarketPrice`] if an [`FlattenStyle::AggressiveLimit`] order cannot be
+    /// priced, in which case no order is placed, and any error encountered while placing the
+    /// orders. Orders that were placed before such an error remain active.
+    ///
+    /// # Returns
+    /// The [`Flattening`] that follows the closing orders, which is empty for a
+    /// [`DrawdownAction::Alert`].
+    pub async fn apply_drawdown_action(
+        &mut self,
+        event: &DrawdownEvent,
+    ) -> Result<Flattening, Error> {
+        let account_number = &event.account_number;
+        check_valid_account(self, account_number, "apply_drawdown_action")?;
+        let (fraction, style) = match event.rule.action {
+            DrawdownAction::Alert => return Ok(Flattening::default()),
+            DrawdownAction::Reduce { fraction, style } => (Some(fraction), style),
+            DrawdownAction::Flatten(style) => (None, style),
+        };
+        check_positions_known(self, account_number)?;
+        let holdings = self
             .status
-            .rx
-            .recv()
-            .await
-            .ok_or_else(|| anyhow::Error::msg("Failed to receive contract object"))?
-        {
-            ToClient::NewContract(c) => Ok(c),
+            .shared
+            .positions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .filter(|((acct, contract_id), _)| {
+                acct == account_number
+                    && match event.rule.scope {
+                        DrawdownScope::Account => true,
+                        DrawdownScope::Position(id) => id == *contract_id,
+                    }
+            })
+            .map(|((_, contract_id), holding)| (*contract_id, *holding))
+            .collect::<Vec<_>>();
+        let mut closing = Vec::with_capacity(holdings.len());
+        for (contract_id, holding) in holdings {
+            let holding = match fraction {
+                Some(fraction) => {
+                    let increment = self
+                        .get_contract_details(contract_id)
+                        .and_then(|details| details.size_increment)
+                        .unwrap_or(1.0);
+                    match drawdown::get_reduction(holding, fraction, increment) {
+                        Some(holding) => holding,
+                        None => continue,
+                    }
+                }
+                None => holding,
+            };
+            let contract = self.resolve_contract(contract_id).await?;
+            let order = get_closing_order(&contract, holding, style)?;
+            closing.push((account_number.clone(), contract, order));
         }
+        self.req_place_closing_orders(closing).await
     }
 
-    // === Disconnect ==
+    // === Rebalancing ===
 
-    #[inline]
-    /// Terminate the connection with the IBKR trading systems and return a [`Builder`] that can
-    /// be used to reconnect if necessary.
+    /// Compute the orders that move an account's positions to the targets of a [`Rebalancer`],
+    /// without placing them.
+    ///
+    /// # Arguments
+    /// * `rebalancer` - The account and its targets.
     ///
     /// # Errors
-    /// Returns any error encountered while flushing and shutting down the outgoing buffer.
+    /// Returns [`Error::InvalidAccount`] if the account is not managed by the client,
+    /// [`Error::PositionsUnknown`] if its positions have not been received from
+    /// [`Client::req_positions`] or [`Client::req_account_updates`],
+    /// [`Error::MissingMarketPrice`] if a weight or limit order requires a contract's price but
+    /// none is known, and [`Error::Rebalance`] if a weight is given but the capital is unknown or
+    /// a target is invalid.
     ///
     /// # Returns
-    /// Returns a [`Builder`] with the same port and address as the existing client.
-    pub async fn disconnect(mut self) -> Result<Builder, std::io::Error> {
-        self.writer.flush().await?;
-        self.writer.shutdown().await?;
-        self.status.disconnect.cancel();
-        self.status.r_thread.await?;
-        Ok(Builder(Inner::Manual {
+    /// The orders, with the sales before the purchases.
+    pub fn plan_rebalance(&self, rebalancer: &Rebalancer) -> Result<Vec<RebalanceOrder>, Error> {
+        let account_number = rebalancer.get_account_number();
+        check_valid_account(self, account_number, "plan_rebalance")?;
+        check_positions_known(self, account_number)?;
+        let net_liquidation = self
+            .get_account_value(account_number, ValueKey::NetLiquidation)
+            .map(|update| update.value.to_f64());
+        let positions = self
+            .status
+            .shared
+            .positions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        rebalancer.plan(
+            |contract_id| {
+                positions
+                    .get(&(account_number.clone(), contract_id))
+                    .copied()
+            },
+            |contract_id| self.get_contract_details(contract_id),
+            net_liquidation,
+        )
+    }
+
+    /// Move an account's positions to the targets of a [`Rebalancer`]: compute the orders (see
+    /// [`Client::plan_rebalance`]), preview them if the rebalancer asks for it, and place them.
+    ///
+    /// # Arguments
+    /// * `rebalancer` - The account and its targets.
+    ///
+    /// # Errors
+    /// Returns any error encountered by [`Client::plan_rebalance`] or while previewing the orders,
+    /// and [`Error::Rebalance`] if a preview shows insufficient margin, in each of which cases no
+    /// order is placed. Additionally, this function will error if an order cannot be placed, in
+    /// which case the orders that were placed before the error remain active.
+    ///
+    /// # Returns
+    /// The [`Rebalance`] that holds the placed orders.
+    pub async fn req_rebalance(&mut self, rebalancer: &Rebalancer) -> Result<Rebalance, Error> {
+        let orders = self.plan_rebalance(rebalancer)?;
+        let account_number = rebalancer.get_account_number();
+
+        let mut previews = Vec::new();
+        if let Some(timeout) = rebalancer.get_preview() {
+            previews.reserve(orders.len());
+            for order in &orders {
+                let preview = crate::contract_dispatch! {
+                    order.contract.clone() =>
+                        async (Self::req_what_if_simple_order)
+                        (self)
+                        (account_number, order.side, order.quantity, order.limit_price, timeout)
+                }?;
+                if let (Some(margin), Some(equity)) =
+                    (preview.initial_margin_after, preview.equity_with_loan_after)
+                {
+                    if margin > equity {
+                        return Err(Error::Rebalance(RebalanceError::InsufficientMargin {
+                            contract_id: order.contract.get_contract_id(),
+                            preview: Box::new(preview),
+                        }));
+                    }
+                }
+                previews.push(preview);
+            }
+        }
+
+        let mut trackers = Vec::with_capacity(orders.len());
+        for order in &orders {
+            trackers.push(crate::contract_dispatch! {
+                order.contract.clone() =>
+                    async (Self::req_place_simple_order)
+                    (self)
+                    (account_number, order.side, order.quantity, order.limit_price)
+            }?);
+        }
+        Ok(Rebalance::new(orders, previews, trackers))
+    }
+
+    /// Preview a market order, or a limit order if a price is given, on behalf of an account.
+    async fn req_what_if_simple_order<S>(
+        &mut self,
+        security: S,
+        account_number: &AccountId,
+        side: OrderSide,
+        quantity: Quantity,
+        price: Option<Price>,
+        timeout: std::time::Duration,
+    ) -> Result<OrderState, Error>
+    where
+        S: Security,
+        Market: Executable<S>,
+        Limit: Executable<S>,
+    {
+        match price {
+            Some(price) => {
+                let limit = Limit::builder().price(price).quantity(quantity).build();
+                let order = Order::new(side, security, limit).with_account(account_number.clone());
+                self.req_what_if_order(&order, timeout).await
+            }
+            None => {
+                let market = Market::builder().quantity(quantity).build();
+                let order = Order::new(side, security, market).with_account(account_number.clone());
+                self.req_what_if_order(&order, timeout).await
+            }
+        }
+    }
+
+    /// Place a market order, or a limit order if a price is given, on behalf of an account.
+    pub(crate) async fn req_place_simple_order<S>(
+        &mut self,
+        security: S,
+        account_number: &AccountId,
+        side: OrderSide,
+        quantity: Quantity,
+        price: Option<Price>,
+    ) -> Result<OrderTracker, Error>
+    where
+        S: Security,
+        Market: Executable<S>,
+        Limit: Executable<S>,
+    {
+        match price {
+            Some(price) => {
+                let limit = Limit::builder().price(price).quantity(quantity).build();
+                let order = Order::new(side, security, limit).with_account(account_number.clone());
+                self.req_place_order_tracked(&order).await
+            }
+            None => {
+                let market = Market::builder().quantity(quantity).build();
+                let order = Order::new(side, security, market).with_account(account_number.clone());
+                self.req_place_order_tracked(&order).await
+            }
+        }
+    }
+}
+
+impl<P: indicators::Profile> Client<indicators::Active<P>> {
+    // ===================================================
+    // === Methods That Verify the Trading Environment ===
+    // ===================================================
+
+    #[inline]
+    fn is_paper_environment(&self) -> bool {
+        !self.status.managed_accounts.is_empty()
+            && ModeMismatch::from_accounts(Some(Mode::Paper), self.status.managed_accounts.iter())
+                .is_none()
+            && ModeMismatch::from_port(Some(Mode::Paper), self.port).is_none()
+    }
+
+    #[inline]
+    fn is_live_environment(&self) -> bool {
+        !self.status.managed_accounts.is_empty()
+            && self
+                .status
+                .managed_accounts
+                .iter()
+                .all(|acct| !acct.0.starts_with('D'))
+    }
+
+    #[inline]
+    fn into_environment<E: indicators::Environment>(self) -> Client<indicators::Active<P, E>> {
+        let status = self.status;
+        Client {
+            mode: self.mode,
+            host: self.host,
             port: self.port,
             address: self.address,
-        }))
+            client_id: self.client_id,
+            server_version: self.server_version,
+            conn_time: self.conn_time,
+            mismatch_policy: self.mismatch_policy,
+            mode_mismatch: self.mode_mismatch,
+            contexts: self.contexts,
+            transport: self.transport,
+            socket_options: self.socket_options,
+            writer: self.writer,
+            status: indicators::Active {
+                r_thread: status.r_thread,
+                disconnect: status.disconnect,
+                reader_disconnect: status.reader_disconnect,
+                queue: status.queue,
+                managed_accounts: status.managed_accounts,
+                order_id: status.order_id,
+                order_id_store: status.order_id_store,
+                order_id_reserved: status.order_id_reserved,
+                req_id: status.req_id,
+                req_id_reserved: status.req_id_reserved,
+                replay: status.replay,
+                auto_reconnect: status.auto_reconnect,
+                resend_subscriptions: status.resend_subscriptions,
+                clock: status.clock,
+                shared: status.shared,
+                profile: std::marker::PhantomData,
+                environment: std::marker::PhantomData,
+            },
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    /// Verify that the client is connected to a paper trading environment, so that it can be
+    /// passed to functions that require a [`PaperClient`] (or its data-only equivalent).
+    ///
+    /// A client is considered to be in a paper trading environment when all of its managed
+    /// accounts are paper accounts and it is not connected to one of the standard live trading
+    /// ports.
+    ///
+    /// # Errors
+    /// Returns the unchanged client if the connection is not a paper trading environment.
+    ///
+    /// # Returns
+    /// The verified client.
+    pub fn into_paper(self) -> Result<Client<indicators::Active<P, indicators::Paper>>, Self> {
+        if self.is_paper_environment() {
+            Ok(self.into_environment())
+        } else {
+            Err(self)
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    /// Verify that the client is connected to a live trading environment, so that it can be
+    /// passed to functions that require a [`LiveClient`] (or its data-only equivalent).
+    ///
+    /// A client is considered to be in a live trading environment when none of its managed
+    /// accounts are paper accounts.
+    ///
+    /// # Errors
+    /// Returns the unchanged client if the connection is not a live trading environment.
+    ///
+    /// # Returns
+    /// The verified client.
+    pub fn into_live(self) -> Result<Client<indicators::Active<P, indicators::Live>>, Self> {
+        if self.is_live_environment() {
+            Ok(self.into_environment())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// The side, quantity, and limit price (or [`None`] for a market order) of an order that closes a
+/// position.
+type ClosingOrder = (OrderSide, Quantity, Option<Price>);
+
+fn get_closing_order(
+    contract: &Contract,
+    holding: flatten::Holding,
+    style: FlattenStyle,
+) -> Result<ClosingOrder, Error> {
+    let side = if holding.position.is_negative() {
+        OrderSide::Buy
+    } else {
+        OrderSide::Sell
+    };
+    let price = match style {
+        FlattenStyle::Market => None,
+        FlattenStyle::AggressiveLimit { .. } => {
+            let market_price = holding
+                .market_price
+                .ok_or_else(|| Error::MissingMarketPrice(contract.get_contract_id()))?;
+            style.get_limit_price(side, market_price, contract)?
+        }
+    };
+    Ok((side, holding.position.abs(), price))
+}
+
+#[inline]
+/// Check that an account's positions have been received, so that an account without positions is
+/// not mistaken for one whose positions are unknown.
+fn check_positions_known<P: indicators::Profile, E: indicators::Environment>(
+    client: &Client<indicators::Active<P, E>>,
+    account_number: &AccountId,
+) -> ReqResult {
+    let shared = &client.status.shared;
+    if shared
+        .all_positions_received
+        .load(std::sync::atomic::Ordering::Relaxed)
+        || shared
+            .account_positions_received
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .contains(account_number)
+    {
+        Ok(())
+    } else {
+        Err(Error::PositionsUnknown(account_number.clone()))
     }
 }
 
 #[inline]
-fn check_valid_account(
-    client: &Client<indicators::Active>,
-    account_number: &str,
-) -> Result<(), std::io::Error> {
+fn check_valid_account<P: indicators::Profile, E: indicators::Environment>(
+    client: &Client<indicators::Active<P, E>>,
+    account_number: &AccountId,
+    method: &'static str,
+) -> ReqResult {
     if client.status.managed_accounts.contains(account_number) {
         Ok(())
     } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            "Invalid account number provided to req_account_updates",
-        ))
+        Err(Error::InvalidAccount {
+            account_number: account_number.clone(),
+            method,
+        })
+    }
+}
+
+#[inline]
+pub(crate) fn lock_expirations(
+    shared: &Shared,
+) -> std::sync::MutexGuard<'_, std::collections::HashMap<i64, Expiry>> {
+    shared
+        .expirations
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[inline]
+fn lock_current_time_requests(
+    shared: &Shared,
+) -> std::sync::MutexGuard<'_, std::collections::VecDeque<CurrentTimeRequest>> {
+    shared
+        .current_time_requests
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Get the orders that expire on or before a date, sorted by date.
+fn get_expiring(
+    expirations: &std::collections::HashMap<i64, Expiry>,
+    horizon: chrono::NaiveDate,
+) -> Vec<(i64, Expiry)> {
+    let mut expiring = expirations
+        .iter()
+        .filter(|(_, expiry)| expiry.get_date() <= horizon)
+        .map(|(id, expiry)| (*id, *expiry))
+        .collect::<Vec<_>>();
+    expiring.sort_unstable_by_key(|(id, expiry)| (expiry.get_date(), *id));
+    expiring
+}
+
+/// Wait until the snapshot quotes satisfy a condition, which is checked again each time a
+/// snapshot ends.
+///
+/// # Arguments
+/// * `shared` - The state shared with the client loop, which holds the snapshot quotes.
+/// * `deadline` - The time after which to stop waiting.
+/// * `condition` - The condition, which is given the snapshot quotes keyed by request ID.
+///
+/// # Returns
+/// Returns [`true`] if the condition was satisfied before the deadline.
+async fn wait_for_snapshots(
+    shared: &Shared,
+    deadline: tokio::time::Instant,
+    mut condition: impl FnMut(&std::collections::HashMap<i64, Quote>) -> bool,
+) -> bool {
+    loop {
+        // The waiter is created before the condition is checked, so that no end is missed
+        let notified = shared.quotes_ended.notified();
+        if condition(
+            &shared
+                .quotes
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        ) {
+            return true;
+        }
+        if tokio::time::timeout_at(deadline, notified).await.is_err() {
+            return false;
+        }
     }
 }
+
+#[inline]
+/// Check whether the snapshot of a request has ended, or is no longer followed.
+fn is_snapshot_ended(snapshots: &std::collections::HashMap<i64, Quote>, req_id: i64) -> bool {
+    snapshots.get(&req_id).is_none_or(|quote| quote.complete)
+}
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Utc};
+
+// === Constants ===
+
+/// The longest a [`ReplayClock`] is left unchecked, since its mode may change (or, in
+/// [`ReplayMode::AsFastAsPossible`], it may be advanced) at any moment.
+const REPLAY_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+// === Type definitions ===
+
+/// A source of the current time, which is injected into the time-dependent parts of the crate
+/// (such as [`crate::client::Client::get_expiring_orders`], the timers of
+/// [`crate::client::Client::schedule`], [`crate::strategy::BacktestRunner`] and
+/// [`crate::resample::TickAggregator::flush`]) so that they follow a virtual clock during a replay
+/// or backtest.
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    /// Get the current time.
+    fn now(&self) -> DateTime<Utc>;
+
+    #[inline]
+    /// Get the amount of real time to wait before checking again whether the clock has reached a
+    /// given time, such as when a timer falls due.
+    ///
+    /// The default implementation waits until the time arrives, which suits a clock that advances
+    /// at the same speed as the system's clock.
+    ///
+    /// # Arguments
+    /// * `time` - The time to await.
+    fn get_wait(&self, time: DateTime<Utc>) -> std::time::Duration {
+        (time - self.now()).to_std().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The system's clock, which is used unless another clock is provided.
+pub struct SystemClock;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+/// The speed at which a [`ReplayClock`] advances.
+pub enum ReplayMode {
+    #[default]
+    /// Advance at the same speed as the system's clock, as if the replayed data were live.
+    WallClock,
+    /// Advance a given number of times faster than the system's clock.
+    Accelerated(f64),
+    /// Advance only when [`ReplayClock::advance_to`] is called, without ever waiting, so that a
+    /// replay runs as fast as possible and produces the same results every time.
+    AsFastAsPossible,
+}
+
+#[derive(Debug)]
+/// A virtual clock that replays a period of time at a controllable speed.
+///
+/// The replay driver calls [`ReplayClock::advance_to`] with the timestamp of each replayed event
+/// before delivering it. In the paced modes, this waits until the virtual time reaches the event;
+/// in [`ReplayMode::AsFastAsPossible`], it moves the virtual time to the event immediately.
+pub struct ReplayClock {
+    state: std::sync::Mutex<ReplayState>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ReplayState {
+    mode: ReplayMode,
+    /// The virtual time at `anchored`.
+    virtual_time: DateTime<Utc>,
+    anchored: tokio::time::Instant,
+}
+
+// === Type implementations ===
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+impl ReplayMode {
+    #[inline]
+    fn get_speed(self) -> f64 {
+        match self {
+            Self::WallClock => 1.0,
+            Self::Accelerated(speed) => speed.max(0.0),
+            Self::AsFastAsPossible => 0.0,
+        }
+    }
+}
+
+impl ReplayState {
+    fn now(&self) -> DateTime<Utc> {
+        let elapsed = self.anchored.elapsed().mul_f64(self.mode.get_speed());
+        chrono::Duration::from_std(elapsed)
+            .ok()
+            .and_then(|elapsed| self.virtual_time.checked_add_signed(elapsed))
+            .unwrap_or(self.virtual_time)
+    }
+}
+
+impl ReplayClock {
+    #[must_use]
+    /// Create a new clock.
+    ///
+    /// # Arguments
+    /// * `start` - The virtual time at which the replay starts.
+    /// * `mode` - The speed at which the clock advances.
+    pub fn new(start: DateTime<Utc>, mode: ReplayMode) -> Self {
+        Self {
+            state: std::sync::Mutex::new(ReplayState {
+                mode,
+                virtual_time: start,
+                anchored: tokio::time::Instant::now(),
+            }),
+        }
+    }
+
+    #[inline]
+    fn lock(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[must_use]
+    /// Get the speed at which the clock advances.
+    pub fn get_mode(&self) -> ReplayMode {
+        self.lock().mode
+    }
+
+    /// Change the speed at which the clock advances, from the current virtual time onward.
+    ///
+    /// # Arguments
+    /// * `mode` - The new speed.
+    pub fn set_mode(&self, mode: ReplayMode) {
+        let mut state = self.lock();
+        *state = ReplayState {
+            mode,
+            virtual_time: state.now(),
+            anchored: tokio::time::Instant::now(),
+        };
+    }
+
+    /// Advance the virtual time to the timestamp of the next replayed event.
+    ///
+    /// In [`ReplayMode::WallClock`] and [`ReplayMode::Accelerated`], this waits until the clock
+    /// reaches `time`. In [`ReplayMode::AsFastAsPossible`], the clock is set to `time` without
+    /// waiting. The clock never moves backward, so a `time` that has already passed returns
+    /// immediately.
+    ///
+    /// # Arguments
+    /// * `time` - The virtual time to which to advance.
+    pub async fn advance_to(&self, time: DateTime<Utc>) {
+        loop {
+            let wait = {
+                let mut state = self.lock();
+                let now = state.now();
+                if time <= now {
+                    return;
+                }
+                let speed = state.mode.get_speed();
+                if speed <= 0.0 {
+                    *state = ReplayState {
+                        virtual_time: time,
+                        anchored: tokio::time::Instant::now(),
+                        ..*state
+                    };
+                    return;
+                }
+                (time - now).to_std().unwrap_or_default().div_f64(speed)
+            };
+            // The mode may change while waiting, so the remaining time is computed again
+            tokio::time::sleep(wait.min(REPLAY_POLL)).await;
+        }
+    }
+}
+
+impl Clock for ReplayClock {
+    #[inline]
+    fn now(&self) -> DateTime<Utc> {
+        self.lock().now()
+    }
+
+    fn get_wait(&self, time: DateTime<Utc>) -> std::time::Duration {
+        let state = self.lock();
+        let speed = state.mode.get_speed();
+        if speed <= 0.0 {
+            return REPLAY_POLL;
+        }
+        (time - state.now())
+            .to_std()
+            .unwrap_or_default()
+            .div_f64(speed)
+            .min(REPLAY_POLL)
+    }
+}
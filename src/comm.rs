@@ -2,30 +2,195 @@ use chrono::NaiveDateTime;
 use serde::{Serialize, Serializer};
 use std::fmt::{Display, Formatter};
 use std::io::{Error, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The kind of stream over which a client communicates with IBKR's trading systems.
+pub(crate) enum Transport {
+    #[default]
+    Plain,
+    #[cfg(feature = "tls")]
+    Tls(crate::tls::TlsConfig),
+}
+
+/// The reading half of a connection, which is either a plain or an encrypted stream.
+pub(crate) struct ReadHalf(Box<dyn AsyncRead + Send + Unpin>);
+
+/// The writing half of a connection, which is either a plain or an encrypted stream.
+pub(crate) struct WriteHalf(Box<dyn AsyncWrite + Send + Unpin>);
 
 #[derive(Debug)]
 pub(crate) struct Writer {
     buf: Vec<u8>,
     offset: Option<usize>,
-    inner: tokio::net::tcp::OwnedWriteHalf,
+    inner: std::sync::Arc<tokio::sync::Mutex<WriteHalf>>,
+    limiter: std::sync::Arc<std::sync::Mutex<Option<TokenBucket>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The state of a [`crate::client::RateLimit`], which holds one token for each message that may
+/// currently be sent.
+pub(crate) struct TokenBucket {
+    limit: crate::client::RateLimit,
+    tokens: f64,
+    refilled: tokio::time::Instant,
+}
+
+impl Transport {
+    /// Open a new connection and split it into its reading and writing halves.
+    pub(crate) async fn connect(
+        &self,
+        address: std::net::Ipv4Addr,
+        port: u16,
+        options: crate::client::SocketOptions,
+    ) -> Result<(ReadHalf, WriteHalf), crate::error::Error> {
+        let stream = tokio::net::TcpStream::connect((address, port)).await?;
+        apply_socket_options(&stream, options)?;
+        Ok(match self {
+            Self::Plain => {
+                let (reader, writer) = stream.into_split();
+                (ReadHalf(Box::new(reader)), WriteHalf(Box::new(writer)))
+            }
+            #[cfg(feature = "tls")]
+            Self::Tls(config) => {
+                let (reader, writer) = tokio::io::split(config.connect(stream, address).await?);
+                (ReadHalf(Box::new(reader)), WriteHalf(Box::new(writer)))
+            }
+        })
+    }
+}
+
+impl std::fmt::Debug for ReadHalf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ReadHalf").finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for WriteHalf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WriteHalf").finish_non_exhaustive()
+    }
+}
+
+impl AsyncRead for ReadHalf {
+    #[inline]
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for WriteHalf {
+    #[inline]
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        Pin::new(&mut *self.0).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Pin::new(&mut *self.0).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Pin::new(&mut *self.0).poll_shutdown(cx)
+    }
+}
+
+/// Apply the options that tune a TCP connection.
+fn apply_socket_options(
+    stream: &tokio::net::TcpStream,
+    options: crate::client::SocketOptions,
+) -> Result<(), Error> {
+    stream.set_nodelay(options.no_delay)?;
+    if let Some(time) = options.keepalive {
+        let keepalive = socket2::TcpKeepalive::new().with_time(time);
+        #[cfg(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "windows",
+        ))]
+        let keepalive = match options.keepalive_interval {
+            Some(interval) => keepalive.with_interval(interval),
+            None => keepalive,
+        };
+        socket2::SockRef::from(stream).set_tcp_keepalive(&keepalive)?;
+    }
+    Ok(())
 }
 
 impl Writer {
     #[inline]
     /// Create a new `Message` with the default capacity specified as [`constants::OUT_MESSAGE_SIZE`]
-    pub(crate) fn new(writer: tokio::net::tcp::OwnedWriteHalf) -> Self {
+    pub(crate) fn new(writer: WriteHalf) -> Self {
         Self::with_capacity(writer, crate::constants::OUT_MESSAGE_SIZE)
     }
 
     #[inline]
     /// Create a new `Message` with the specified capacity.
-    pub(crate) fn with_capacity(writer: tokio::net::tcp::OwnedWriteHalf, cap: usize) -> Self {
+    pub(crate) fn with_capacity(writer: WriteHalf, cap: usize) -> Self {
         let buf = Vec::with_capacity(cap);
 
         Self {
             buf,
             offset: None,
-            inner: writer,
+            inner: std::sync::Arc::new(tokio::sync::Mutex::new(writer)),
+            limiter: std::sync::Arc::default(),
+        }
+    }
+
+    #[inline]
+    /// Create a new `Writer` with its own buffer that writes to the same connection. Each message
+    /// is written while holding a lock on the connection, so messages from different writers never
+    /// interleave. The writers also share a rate limit.
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            buf: Vec::with_capacity(crate::constants::OUT_MESSAGE_SIZE),
+            offset: None,
+            inner: std::sync::Arc::clone(&self.inner),
+            limiter: std::sync::Arc::clone(&self.limiter),
+        }
+    }
+
+    #[inline]
+    /// Set the rate limit shared by this writer and all of its forks, or remove it with [`None`].
+    pub(crate) fn set_rate_limit(&self, limit: Option<crate::client::RateLimit>) {
+        *self.lock_limiter() = limit.map(TokenBucket::new);
+    }
+
+    #[inline]
+    /// Get the rate limit shared by this writer and all of its forks.
+    pub(crate) fn get_rate_limit(&self) -> Option<crate::client::RateLimit> {
+        self.lock_limiter().map(|bucket| bucket.limit)
+    }
+
+    #[inline]
+    fn lock_limiter(&self) -> std::sync::MutexGuard<'_, Option<TokenBucket>> {
+        self.limiter
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[inline]
+    /// Replace the connection with that of another `Writer`, so that every fork of this writer
+    /// also writes to the new connection.
+    pub(crate) async fn replace_connection(&mut self, other: Self) {
+        match std::sync::Arc::try_unwrap(other.inner) {
+            Ok(connection) => *self.inner.lock().await = connection.into_inner(),
+            // The other writer was already forked, so its connection cannot be moved
+            Err(connection) => self.inner = connection,
         }
     }
 
@@ -40,9 +205,14 @@ impl Writer {
     #[inline]
     pub(crate) fn add_body<T: Serialize>(&mut self, body: T) -> Result<(), Error> {
         const LENGTH_PREFIX: &[u8] = b"\0\0\0\0";
+        let start = self.buf.len();
         self.buf.write_all(LENGTH_PREFIX)?;
 
-        body.serialize(&mut *self)?;
+        // A partially encoded message must never reach the wire, so discard it on failure
+        if let Err(e) = body.serialize(&mut *self) {
+            self.buf.truncate(start);
+            return Err(e.into());
+        }
         let (len, offset) = match self.offset {
             Some(o) => (self.buf.len() - o - LENGTH_PREFIX.len(), o),
             None => (self.buf.len() - LENGTH_PREFIX.len(), 0),
@@ -59,22 +229,162 @@ impl Writer {
     }
 
     #[inline]
-    pub(crate) async fn send(&mut self) -> Result<(), Error> {
-        tokio::io::AsyncWriteExt::write_all(&mut self.inner, &self.buf).await?;
+    /// Return the encoded messages that have not yet been sent.
+    pub(crate) fn get_pending(&self) -> &[u8] {
+        &self.buf
+    }
+
+    #[inline]
+    /// Remove and return the encoded messages that have not yet been sent.
+    pub(crate) fn take_pending(&mut self) -> Vec<u8> {
+        self.offset = None;
+        std::mem::take(&mut self.buf)
+    }
+
+    #[inline]
+    /// Append messages that were already encoded, such as those from [`Writer::take_pending`].
+    pub(crate) fn add_raw(&mut self, msgs: &[u8]) {
+        self.buf.extend_from_slice(msgs);
+    }
+
+    /// Return the encoded messages that have not yet been sent, without their length prefixes.
+    fn pending_msgs(&self) -> Vec<&[u8]> {
+        let mut msgs = Vec::new();
+        let mut rest = self.buf.get(self.offset.unwrap_or(0)..).unwrap_or_default();
+        while let Some((len, body)) = rest.split_first_chunk::<4>() {
+            let Ok(len) = usize::try_from(u32::from_be_bytes(*len)) else {
+                break;
+            };
+            let Some(msg) = body.get(..len) else {
+                break;
+            };
+            msgs.push(msg);
+            rest = &body[len..];
+        }
+        msgs
+    }
+
+    /// Return the last encoded message that has not yet been sent without its second field, which
+    /// holds the request ID for most requests. This identifies the request among others that are
+    /// otherwise identical.
+    pub(crate) fn last_pending_msg_without_id(&self) -> Vec<u8> {
+        self.pending_msgs()
+            .pop()
+            .map(|msg| {
+                msg.split_inclusive(|b| *b == 0)
+                    .enumerate()
+                    .filter(|(i, _)| *i != 1)
+                    .flat_map(|(_, field)| field.iter().copied())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "tracing")]
+    /// Return the message IDs of the encoded messages that have not yet been sent.
+    fn pending_msg_ids(&self) -> Vec<&str> {
+        self.pending_msgs()
+            .into_iter()
+            .map(|msg| {
+                msg.split(|b| *b == 0)
+                    .next()
+                    .and_then(|id| std::str::from_utf8(id).ok())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    #[inline]
+    pub(crate) async fn send(&mut self) -> Result<(), crate::error::Error> {
+        let count = self.pending_msgs().len();
+        let acquired = self
+            .lock_limiter()
+            .as_mut()
+            .map_or(Ok(None), |bucket| bucket.acquire(count));
+        let wait = match acquired {
+            Ok(wait) => wait,
+            // A rejected request must not be sent along with the next one
+            Err(e) => {
+                self.buf.clear();
+                self.offset = None;
+                return Err(e);
+            }
+        };
+        if let Some(wait) = wait {
+            event!(debug, ?wait, "Delaying request to respect the rate limit");
+            tokio::time::sleep(wait).await;
+        }
+
+        let write = async {
+            event!(debug, bytes = self.buf.len(), "Sending request");
+            tokio::io::AsyncWriteExt::write_all(&mut *self.inner.lock().await, &self.buf).await
+        };
+        #[cfg(feature = "tracing")]
+        let write = tracing::Instrument::instrument(
+            write,
+            tracing::debug_span!("request", msg_ids = ?self.pending_msg_ids()),
+        );
+        let written = write.await;
+        // A message that failed to be written must not be sent along with the next one
         self.buf.clear();
         self.offset = None;
 
-        Ok(())
+        Ok(written?)
     }
 
     #[inline]
     pub(crate) async fn flush(&mut self) -> Result<(), Error> {
-        tokio::io::AsyncWriteExt::flush(&mut self.inner).await
+        tokio::io::AsyncWriteExt::flush(&mut *self.inner.lock().await).await
     }
 
     #[inline]
     pub(crate) async fn shutdown(&mut self) -> Result<(), Error> {
-        tokio::io::AsyncWriteExt::shutdown(&mut self.inner).await
+        tokio::io::AsyncWriteExt::shutdown(&mut *self.inner.lock().await).await
+    }
+}
+
+impl TokenBucket {
+    #[inline]
+    fn new(limit: crate::client::RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.burst),
+            refilled: tokio::time::Instant::now(),
+        }
+    }
+
+    /// Take a token for each of `count` messages.
+    ///
+    /// # Returns
+    /// The amount of time to wait before the messages may be sent, if the bucket holds too few
+    /// tokens. Otherwise, returns [`crate::error::Error::RateLimited`] if the limit does not queue
+    /// messages.
+    fn acquire(
+        &mut self,
+        count: usize,
+    ) -> Result<Option<std::time::Duration>, crate::error::Error> {
+        let now = tokio::time::Instant::now();
+        let burst = f64::from(self.limit.burst);
+        self.tokens = (self.tokens
+            + (now - self.refilled).as_secs_f64() * f64::from(self.limit.messages_per_second))
+        .min(burst);
+        self.refilled = now;
+
+        #[allow(clippy::cast_precision_loss)]
+        let count = count as f64;
+        // A batch larger than the burst can never fit, so it is admitted once the bucket is full
+        if self.tokens >= count.min(burst) {
+            self.tokens -= count;
+            return Ok(None);
+        }
+        if !self.limit.queue {
+            return Err(crate::error::Error::RateLimited);
+        }
+        let deficit = count.min(burst) - self.tokens;
+        self.tokens -= count;
+        Ok(Some(std::time::Duration::from_secs_f64(
+            deficit / f64::from(self.limit.messages_per_second.max(1)),
+        )))
     }
 }
 
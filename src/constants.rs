@@ -1,6 +1,16 @@
 pub const MIN_CLIENT_VERSION: u8 = 177;
 pub const MAX_CLIENT_VERSION: u8 = 177;
-pub const TO_CLIENT_CHANNEL_SIZE: usize = 10;
-pub const TO_WRAPPER_CHANNEL_SIZE: usize = 10;
 pub const OUT_MESSAGE_SIZE: usize = 512;
 pub const ORDER_TUPLE_SIZE: usize = 98;
+pub const ORDER_ID_BLOCK: i64 = 100;
+pub const DISCOVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+pub const CONTRACT_QUERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+pub const OPTION_CHAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+pub const MATCHING_SYMBOLS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+pub const NEWS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+pub const SCHEDULE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+pub const NEXT_VALID_ID_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+pub const CONTRACT_CACHE_CAPACITY: usize = 10_000;
+pub const RESOLVER_REQ_ID_START: i64 = 1 << 30;
+pub const EXPIRY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+pub const SNAPSHOT_PACING: std::time::Duration = std::time::Duration::from_millis(25);
@@ -0,0 +1,138 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+// === Type definitions ===
+
+#[derive(Clone)]
+/// A piece of user context attached to a request with [`Contexts::attach`] or
+/// [`Contexts::attach_bytes`].
+pub enum Context {
+    /// An arbitrary value, which is retrieved by its type.
+    Value(Arc<dyn Any + Send + Sync>),
+    /// A serialized value.
+    Bytes(Arc<[u8]>),
+}
+
+#[derive(Debug, Clone, Default)]
+/// A store of user context keyed by request ID.
+///
+/// Every [`crate::client::Client`] owns a store, which is returned by
+/// [`crate::client::Client::get_contexts`]. The store is cheap to clone and every clone refers
+/// to the same contexts, so a clone can be moved into a [`crate::wrapper::Remote`] wrapper before
+/// the client is activated. A callback that receives a request's ID can then look up the context
+/// attached to that request with [`Contexts::get`] (or one of its typed variants).
+///
+/// The context is not passed to the callbacks themselves, and the client never detaches it: once
+/// a request is cancelled or has ended, its context stays in the store until it is removed with
+/// [`Contexts::detach`] or [`Contexts::clear`].
+pub struct Contexts(Arc<Mutex<HashMap<i64, Context>>>);
+
+// === Type implementations ===
+
+impl std::fmt::Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(_) => f.debug_tuple("Value").finish_non_exhaustive(),
+            Self::Bytes(bytes) => f.debug_tuple("Bytes").field(bytes).finish(),
+        }
+    }
+}
+
+impl Contexts {
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, HashMap<i64, Context>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline]
+    /// Attach a value to a request, replacing any context that was previously attached to it.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    /// * `value` - The value to attach.
+    ///
+    /// # Returns
+    /// The context that was previously attached to the request, if any.
+    pub fn attach<T: Any + Send + Sync>(&self, req_id: i64, value: T) -> Option<Context> {
+        self.lock().insert(req_id, Context::Value(Arc::new(value)))
+    }
+
+    #[inline]
+    /// Attach serialized bytes to a request, replacing any context that was previously attached
+    /// to it.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    /// * `bytes` - The bytes to attach.
+    ///
+    /// # Returns
+    /// The context that was previously attached to the request, if any.
+    pub fn attach_bytes(&self, req_id: i64, bytes: impl Into<Arc<[u8]>>) -> Option<Context> {
+        self.lock().insert(req_id, Context::Bytes(bytes.into()))
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the context attached to a request.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    ///
+    /// # Returns
+    /// The attached context, if any.
+    pub fn get(&self, req_id: i64) -> Option<Context> {
+        self.lock().get(&req_id).cloned()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the value attached to a request.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    ///
+    /// # Returns
+    /// The attached value, if a value of type `T` is attached to the request.
+    pub fn get_value<T: Any + Send + Sync>(&self, req_id: i64) -> Option<Arc<T>> {
+        match self.get(req_id)? {
+            Context::Value(value) => value.downcast().ok(),
+            Context::Bytes(_) => None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the serialized bytes attached to a request.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    ///
+    /// # Returns
+    /// The attached bytes, if bytes are attached to the request.
+    pub fn get_bytes(&self, req_id: i64) -> Option<Arc<[u8]>> {
+        match self.get(req_id)? {
+            Context::Bytes(bytes) => Some(bytes),
+            Context::Value(_) => None,
+        }
+    }
+
+    #[inline]
+    /// Detach the context from a request, usually once the request has been cancelled or has
+    /// ended.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    ///
+    /// # Returns
+    /// The detached context, if any.
+    pub fn detach(&self, req_id: i64) -> Option<Context> {
+        self.lock().remove(&req_id)
+    }
+
+    #[inline]
+    /// Detach the contexts from all requests.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+}
@@ -1,9 +1,10 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc, Weekday};
 use std::{num::ParseIntError, str::FromStr};
 
 use crate::{
     currency::Currency,
     exchange::{Primary, Routing},
+    execution::OrderSide,
 };
 use ibapi_macros::Security;
 use serde::{Serialize, Serializer};
@@ -35,10 +36,52 @@ pub enum Contract {
     //MutualFund(MutualFund),
     /// A [`Commodity`] contract.
     Commodity(Commodity),
+    /// A [`Combo`] contract.
+    Combo(Combo),
     //Warrant(Warrant),
     //StructuredProduct(StructuredProduct),
 }
 
+impl Contract {
+    #[inline]
+    #[must_use]
+    /// Get the contract's [`ContractId`].
+    ///
+    /// # Returns
+    /// The contract ID of the underlying security.
+    pub fn get_contract_id(&self) -> ContractId {
+        match self {
+            Self::Forex(fx) => fx.get_contract_id(),
+            Self::Crypto(crypto) => crypto.get_contract_id(),
+            Self::Stock(stk) => stk.get_contract_id(),
+            Self::Index(ind) => ind.get_contract_id(),
+            Self::SecFuture(fut) => fut.get_contract_id(),
+            Self::SecOption(opt) => opt.get_contract_id(),
+            Self::Commodity(cmdty) => cmdty.get_contract_id(),
+            Self::Combo(combo) => combo.get_contract_id(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the contract's primary exchange.
+    ///
+    /// # Returns
+    /// The primary exchange of the underlying security, if it has one.
+    pub fn get_primary_exchange(&self) -> Option<Primary> {
+        match self {
+            Self::Forex(fx) => fx.get_primary_exchange(),
+            Self::Crypto(crypto) => crypto.get_primary_exchange(),
+            Self::Stock(stk) => stk.get_primary_exchange(),
+            Self::Index(ind) => ind.get_primary_exchange(),
+            Self::SecFuture(fut) => fut.get_primary_exchange(),
+            Self::SecOption(opt) => opt.get_primary_exchange(),
+            Self::Commodity(cmdty) => cmdty.get_primary_exchange(),
+            Self::Combo(combo) => combo.get_primary_exchange(),
+        }
+    }
+}
+
 #[allow(clippy::module_name_repetitions)]
 #[macro_export]
 /// Call a given function on a [`Contract`] by unwrapping it and applying the function to the underlying [`Security`].
@@ -122,6 +165,9 @@ macro_rules! contract_dispatch {
             Contract::Commodity(cmdty) => {
                 $func($($($pre_args),+)?, cmdty, $($($post_args),+)?).await
             },
+            Contract::Combo(combo) => {
+                $func($($($pre_args),+)?, combo, $($($post_args),+)?).await
+            },
         }
     };
     {$con: expr => $func: tt ($($($pre_args: expr),+)?) $(($($post_args: expr),+))?} => {
@@ -147,6 +193,9 @@ macro_rules! contract_dispatch {
             Contract::Commodity(cmdty) => {
                 $func($($($pre_args),+)?, cmdty, $($($post_args),+)?)
             },
+            Contract::Combo(combo) => {
+                $func($($($pre_args),+)?, combo, $($($post_args),+)?)
+            },
         }
     };
 }
@@ -168,9 +217,28 @@ macro_rules! contract_dispatch {
 /// # Returns
 /// Returns a fully-defined contract that can be used for market data, placing orders, etc.
 pub async fn new<S: Security>(
-    client: &mut crate::client::Client<crate::client::indicators::Active>,
+    client: &mut crate::client::Client<
+        crate::client::indicators::Active<
+            impl crate::client::Profile,
+            impl crate::client::Environment,
+        >,
+    >,
     contract_id: ContractId,
-) -> anyhow::Result<S>
+) -> Result<S, crate::error::Error>
+where
+    <S as TryFrom<Forex>>::Error: 'static + std::error::Error + Send + Sync,
+    <S as TryFrom<Crypto>>::Error: 'static + std::error::Error + Send + Sync,
+    <S as TryFrom<Stock>>::Error: 'static + std::error::Error + Send + Sync,
+    <S as TryFrom<Index>>::Error: 'static + std::error::Error + Send + Sync,
+    <S as TryFrom<SecFuture>>::Error: 'static + std::error::Error + Send + Sync,
+    <S as TryFrom<SecOption>>::Error: 'static + std::error::Error + Send + Sync,
+    <S as TryFrom<Commodity>>::Error: 'static + std::error::Error + Send + Sync,
+{
+    into_security(client.resolve_contract(contract_id).await?)
+}
+
+#[inline]
+fn into_security<S: Security>(contract: Contract) -> Result<S, crate::error::Error>
 where
     <S as TryFrom<Forex>>::Error: 'static + std::error::Error + Send + Sync,
     <S as TryFrom<Crypto>>::Error: 'static + std::error::Error + Send + Sync,
@@ -180,22 +248,34 @@ where
     <S as TryFrom<SecOption>>::Error: 'static + std::error::Error + Send + Sync,
     <S as TryFrom<Commodity>>::Error: 'static + std::error::Error + Send + Sync,
 {
-    client.send_contract_query(contract_id).await?;
-    Ok(match client.recv_contract_query().await? {
-        Contract::Forex(fx) => fx.try_into()?,
-        Contract::Crypto(crypto) => crypto.try_into()?,
-        Contract::Stock(stk) => stk.try_into()?,
-        Contract::Index(ind) => ind.try_into()?,
-        Contract::SecFuture(fut) => fut.try_into()?,
-        Contract::SecOption(opt) => opt.try_into()?,
-        Contract::Commodity(cmdty) => cmdty.try_into()?,
+    Ok(match contract {
+        Contract::Forex(fx) => fx.try_into().map_err(unexpected_security_type)?,
+        Contract::Crypto(crypto) => crypto.try_into().map_err(unexpected_security_type)?,
+        Contract::Stock(stk) => stk.try_into().map_err(unexpected_security_type)?,
+        Contract::Index(ind) => ind.try_into().map_err(unexpected_security_type)?,
+        Contract::SecFuture(fut) => fut.try_into().map_err(unexpected_security_type)?,
+        Contract::SecOption(opt) => opt.try_into().map_err(unexpected_security_type)?,
+        Contract::Commodity(cmdty) => cmdty.try_into().map_err(unexpected_security_type)?,
+        // IBKR does not assign contract IDs to combos
+        Contract::Combo(_) => {
+            return Err(unexpected_security_type(UnexpectedSecurityType(
+                "Combo contracts cannot be created from a contract ID",
+            )))
+        }
     })
 }
 
+#[inline]
+fn unexpected_security_type(
+    e: impl std::error::Error + Send + Sync + 'static,
+) -> crate::error::Error {
+    crate::error::Error::UnexpectedSecurityType(Box::new(e))
+}
+
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
 /// An error caused when a call to [`new`] returns a contract that differs from
 /// the type defined in the initial call.
-pub struct UnexpectedSecurityType(&'static str);
+pub struct UnexpectedSecurityType(pub(crate) &'static str);
 
 impl std::fmt::Display for UnexpectedSecurityType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -231,6 +311,386 @@ impl FromStr for ContractId {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A query that identifies a single contract, as accepted by
+/// [`crate::client::Client::resolve_contracts`].
+pub enum ContractQuery {
+    /// The contract with a given [`ContractId`].
+    Id(ContractId),
+    /// The contract with a given symbol, type, exchange, and currency.
+    Symbol {
+        /// The contract's symbol (ex: "AAPL").
+        symbol: String,
+        /// The type of the contract.
+        contract_type: crate::execution::ContractType,
+        /// The exchange on which the contract trades.
+        exchange: Routing,
+        /// The currency in which the contract is denominated.
+        currency: Currency,
+    },
+    /// The contract with a given industry-wide [`SecurityId`] (ex: an ISIN, CUSIP, or FIGI).
+    SecurityId(SecurityId),
+}
+
+impl From<ContractId> for ContractQuery {
+    fn from(value: ContractId) -> Self {
+        Self::Id(value)
+    }
+}
+
+impl From<SecurityId> for ContractQuery {
+    fn from(value: SecurityId) -> Self {
+        Self::SecurityId(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+/// The right conferred by an option contract.
+pub enum OptionRight {
+    #[serde(rename(serialize = "C"))]
+    /// The right to buy the underlying.
+    Call,
+    #[serde(rename(serialize = "P"))]
+    /// The right to sell the underlying.
+    Put,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// Describes contracts by their symbol and other defining fields, rather than by their
+/// [`ContractId`], so that securities can be created without first looking up their IDs.
+///
+/// Any field that is left unset matches every contract, so a description may match several
+/// contracts (ex: every expiration of a future). Use
+/// [`crate::client::Client::req_contract_details_by_symbol`] to get all the matches, or
+/// [`ContractBuilder::build`] to create a security from a description that matches exactly one.
+pub struct ContractBuilder {
+    symbol: String,
+    contract_type: crate::execution::ContractType,
+    exchange: Routing,
+    primary_exchange: Option<Primary>,
+    currency: Option<Currency>,
+    expiration: Option<String>,
+    strike: Option<f64>,
+    right: Option<OptionRight>,
+    multiplier: Option<u32>,
+    local_symbol: Option<String>,
+    trading_class: Option<String>,
+    include_expired: bool,
+}
+
+impl ContractBuilder {
+    #[inline]
+    #[must_use]
+    /// Create a description of the contracts with a given symbol and type, routed through
+    /// [`Routing::Smart`].
+    ///
+    /// # Arguments
+    /// * `symbol` - The contract's symbol (ex: "AAPL").
+    /// * `contract_type` - The type of the contract.
+    pub fn new(symbol: impl Into<String>, contract_type: crate::execution::ContractType) -> Self {
+        Self {
+            symbol: symbol.into(),
+            contract_type,
+            exchange: Routing::Smart,
+            primary_exchange: None,
+            currency: None,
+            expiration: None,
+            strike: None,
+            right: None,
+            multiplier: None,
+            local_symbol: None,
+            trading_class: None,
+            include_expired: false,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the exchange on which the contract trades.
+    pub const fn with_exchange(mut self, exchange: Routing) -> Self {
+        self.exchange = exchange;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the contract's primary exchange, which distinguishes stocks with the same symbol that
+    /// are listed in different places.
+    pub const fn with_primary_exchange(mut self, primary_exchange: Primary) -> Self {
+        self.primary_exchange = Some(primary_exchange);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the currency in which the contract is denominated.
+    pub const fn with_currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the contract's expiration date.
+    pub fn with_expiration_date(mut self, expiration_date: NaiveDate) -> Self {
+        self.expiration = Some(expiration_date.format("%Y%m%d").to_string());
+        self
+    }
+
+    #[must_use]
+    /// Set the contract month of a future or option, which matches every expiration in that month.
+    ///
+    /// # Arguments
+    /// * `year` - The year of the contract month.
+    /// * `month` - The month, from 1 to 12.
+    ///
+    /// # Returns
+    /// The updated builder, or [`None`] if `month` is not a valid month.
+    pub fn with_contract_month(mut self, year: i32, month: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) {
+            return None;
+        }
+        self.expiration = Some(format!("{year:04}{month:02}"));
+        Some(self)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the strike price of an option.
+    pub const fn with_strike(mut self, strike: f64) -> Self {
+        self.strike = Some(strike);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether an option is a call or a put.
+    pub const fn with_right(mut self, right: OptionRight) -> Self {
+        self.right = Some(right);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the contract's multiplier (ex: 100 for a standard equity option).
+    pub const fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = Some(multiplier);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the contract's symbol on its exchange (ex: "ESZ6").
+    pub fn with_local_symbol(mut self, local_symbol: impl Into<String>) -> Self {
+        self.local_symbol = Some(local_symbol.into());
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the contract's trading class (ex: to distinguish weekly from monthly options).
+    pub fn with_trading_class(mut self, trading_class: impl Into<String>) -> Self {
+        self.trading_class = Some(trading_class.into());
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether expired futures and options also match.
+    pub const fn with_include_expired(mut self, include_expired: bool) -> Self {
+        self.include_expired = include_expired;
+        self
+    }
+
+    /// Create a security from the one contract that matches the description.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to send the request.
+    ///
+    /// # Errors
+    /// Returns any error encountered by [`crate::client::Client::req_contract_details_by_symbol`].
+    /// Additionally, this function will error with [`crate::error::Error::AmbiguousContract`] if
+    /// the description matches more than one contract, or if the matching contract does not match
+    /// the generic type specified in the function call.
+    ///
+    /// # Returns
+    /// Returns a fully-defined contract that can be used for market data, placing orders, etc.
+    pub async fn build<S: Security>(
+        &self,
+        client: &mut crate::client::Client<
+            crate::client::indicators::Active<
+                impl crate::client::Profile,
+                impl crate::client::Environment,
+            >,
+        >,
+    ) -> Result<S, crate::error::Error>
+    where
+        <S as TryFrom<Forex>>::Error: 'static + std::error::Error + Send + Sync,
+        <S as TryFrom<Crypto>>::Error: 'static + std::error::Error + Send + Sync,
+        <S as TryFrom<Stock>>::Error: 'static + std::error::Error + Send + Sync,
+        <S as TryFrom<Index>>::Error: 'static + std::error::Error + Send + Sync,
+        <S as TryFrom<SecFuture>>::Error: 'static + std::error::Error + Send + Sync,
+        <S as TryFrom<SecOption>>::Error: 'static + std::error::Error + Send + Sync,
+        <S as TryFrom<Commodity>>::Error: 'static + std::error::Error + Send + Sync,
+    {
+        let mut matches = client.req_contract_details_by_symbol(self).await?;
+        if matches.len() != 1 {
+            return Err(crate::error::Error::AmbiguousContract(matches));
+        }
+        into_security(matches.swap_remove(0))
+    }
+}
+
+impl Serialize for ContractBuilder {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (
+            None::<()>,
+            &self.symbol,
+            self.contract_type,
+            &self.expiration,
+            self.strike,
+            self.right,
+            self.multiplier,
+            self.exchange,
+            self.primary_exchange,
+            self.currency,
+            &self.local_symbol,
+            &self.trading_class,
+            self.include_expired,
+            [None::<()>; 3],
+        )
+            .serialize(serializer)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, PartialOrd)]
+/// Additional details that IBKR reports alongside a contract's definition, as returned by
+/// [`crate::client::Client::get_contract_details`]. Fields that IBKR leaves empty, or that are not
+/// reported by the connected server version, are [`None`].
+pub struct ContractDetails {
+    /// The minimum size of an order for the contract.
+    pub min_size: Option<f64>,
+    /// The increment in which the size of an order must be specified.
+    pub size_increment: Option<f64>,
+    /// The suggested increment in which to specify the size of an order.
+    pub suggested_size_increment: Option<f64>,
+    /// The details of a mutual fund, which are only reported for funds.
+    pub fund: Option<FundDetails>,
+    /// The ID of the contract's issuer, which is only reported for bonds.
+    pub issuer_id: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, PartialOrd)]
+/// The details of a mutual fund.
+pub struct FundDetails {
+    /// The name of the fund.
+    pub name: String,
+    /// The family to which the fund belongs.
+    pub family: String,
+    /// The type of the fund.
+    pub fund_type: String,
+    /// The fund's front-end load.
+    pub front_load: Option<f64>,
+    /// The fund's back-end load.
+    pub back_load: Option<f64>,
+    /// The time interval over which the back-end load applies.
+    pub back_load_time_interval: String,
+    /// The fund's management fee.
+    pub management_fee: Option<f64>,
+    /// Whether the fund is closed.
+    pub closed: bool,
+    /// Whether the fund is closed to new investors.
+    pub closed_for_new_investors: bool,
+    /// Whether the fund is closed to new money.
+    pub closed_for_new_money: bool,
+    /// The amount above which IBKR notifies the investor.
+    pub notify_amount: Option<f64>,
+    /// The minimum initial purchase.
+    pub minimum_initial_purchase: Option<f64>,
+    /// The minimum subsequent purchase.
+    pub subsequent_minimum_purchase: Option<f64>,
+    /// The US states in which the fund may be sold.
+    pub blue_sky_states: String,
+    /// The US territories in which the fund may be sold.
+    pub blue_sky_territories: String,
+    /// Whether the fund distributes or accumulates its income.
+    pub distribution_policy: Option<FundDistributionPolicy>,
+    /// The class of assets in which the fund invests.
+    pub asset_type: Option<FundAssetType>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Whether a mutual fund distributes or accumulates its income.
+pub enum FundDistributionPolicy {
+    /// The fund reinvests its income.
+    Accumulation,
+    /// The fund distributes its income.
+    Income,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The class of assets in which a mutual fund invests.
+pub enum FundAssetType {
+    /// Any other class of assets.
+    Others,
+    /// Money market instruments.
+    MoneyMarket,
+    /// Fixed income securities.
+    FixedIncome,
+    /// Multiple classes of assets.
+    MultiAsset,
+    /// Equities.
+    Equity,
+    /// A single sector.
+    Sector,
+    /// A guaranteed fund.
+    Guaranteed,
+    /// Alternative investments.
+    Alternative,
+}
+
+impl FromStr for FundDistributionPolicy {
+    type Err = UnexpectedFundCode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "N" => Ok(Self::Accumulation),
+            "Y" => Ok(Self::Income),
+            _ => Err(UnexpectedFundCode(s.to_owned())),
+        }
+    }
+}
+
+impl FromStr for FundAssetType {
+    type Err = UnexpectedFundCode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "000" => Ok(Self::Others),
+            "001" => Ok(Self::MoneyMarket),
+            "002" => Ok(Self::FixedIncome),
+            "003" => Ok(Self::MultiAsset),
+            "004" => Ok(Self::Equity),
+            "005" => Ok(Self::Sector),
+            "006" => Ok(Self::Guaranteed),
+            "007" => Ok(Self::Alternative),
+            _ => Err(UnexpectedFundCode(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An error returned when a fund's distribution policy or asset type code is not recognized.
+pub struct UnexpectedFundCode(pub String);
+
+impl std::fmt::Display for UnexpectedFundCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unexpected fund code: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnexpectedFundCode {}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// Identifiers used by the broader industry / regulators to define a specific contract / asset.
 pub enum SecurityId {
@@ -246,6 +706,35 @@ pub enum SecurityId {
     /// For details, see:
     /// [RIC Description](https://en.wikipedia.org/wiki/Refinitiv_Identification_Code).
     Ric(String),
+    /// For details, see:
+    /// [FIGI Description](https://www.openfigi.com/about/figi).
+    Figi(String),
+}
+
+impl SecurityId {
+    #[inline]
+    #[must_use]
+    /// Get the name with which IBKR identifies the kind of security ID (ex: "ISIN").
+    pub const fn get_type(&self) -> &'static str {
+        match self {
+            Self::Cusip(_) => "CUSIP",
+            Self::Sedol(_) => "SEDOL",
+            Self::Isin(_) => "ISIN",
+            Self::Ric(_) => "RIC",
+            Self::Figi(_) => "FIGI",
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the value of the security ID.
+    pub fn get_value(&self) -> &str {
+        match self {
+            Self::Cusip(id) | Self::Sedol(id) | Self::Isin(id) | Self::Ric(id) | Self::Figi(id) => {
+                id
+            }
+        }
+    }
 }
 
 // =================================
@@ -338,6 +827,22 @@ pub trait Security: indicators::Valid {
     /// # Returns
     /// The security's trading class.
     fn get_trading_class(&self) -> Option<&str>;
+    #[inline]
+    /// Get the legs of the security, if it is a [`Combo`].
+    ///
+    /// # Returns
+    /// The security's legs, which are empty unless it is a [`Combo`].
+    fn get_combo_legs(&self) -> &[ComboLeg] {
+        &[]
+    }
+    #[inline]
+    /// Get the delta-neutral contract with which the security is hedged, if it is a [`Combo`].
+    ///
+    /// # Returns
+    /// The delta-neutral contract, if any.
+    fn get_delta_neutral_contract(&self) -> Option<DeltaNeutralContract> {
+        None
+    }
 }
 
 // =======================================
@@ -432,6 +937,314 @@ pub enum SecOption {
     Put(SecOptionInner),
 }
 
+// =======================
+// === Combo Contracts ===
+// =======================
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize)]
+/// A single leg of a [`Combo`].
+pub struct ComboLeg {
+    /// The ID of the leg's contract.
+    pub contract_id: ContractId,
+    /// The number of units of the leg's contract that are traded per unit of the combo.
+    pub ratio: u32,
+    /// Whether the leg is bought or sold when the combo is bought.
+    pub action: OrderSide,
+    /// The exchange to which the leg is routed.
+    pub exchange: Routing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The underlying contract with which a [`Combo`] is hedged in a delta-neutral trade, along with
+/// the delta and price at which the hedge is computed.
+pub struct DeltaNeutralContract {
+    /// The ID of the underlying contract.
+    pub contract_id: ContractId,
+    /// The delta of the combo with respect to the underlying.
+    pub delta: f64,
+    /// The price of the underlying.
+    pub price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A [combo contract](https://interactivebrokers.github.io/tws-api/basic_contracts.html#bag)
+/// ("BAG"), such as a calendar or vertical spread, whose legs are traded together as a single
+/// security.
+pub struct Combo {
+    pub(crate) symbol: String,
+    pub(crate) currency: Currency,
+    pub(crate) exchange: Routing,
+    pub(crate) legs: Vec<ComboLeg>,
+    pub(crate) delta_neutral_contract: Option<DeltaNeutralContract>,
+}
+
+impl Combo {
+    #[must_use]
+    /// Create a new combo.
+    ///
+    /// # Arguments
+    /// * `symbol` - The symbol of the combo, which is the underlying symbol for a spread of
+    /// options or futures on the same underlying, or a comma-separated list of the legs' symbols
+    /// otherwise (ex: "IBKR,MCD").
+    /// * `currency` - The currency in which the combo trades.
+    /// * `exchange` - The exchange to which the combo is routed.
+    /// * `legs` - The combo's legs.
+    ///
+    /// # Returns
+    /// The combo, or [`None`] if it has fewer than two legs or a leg has a zero ratio.
+    pub fn new(
+        symbol: impl Into<String>,
+        currency: Currency,
+        exchange: Routing,
+        legs: Vec<ComboLeg>,
+    ) -> Option<Self> {
+        (legs.len() >= 2 && legs.iter().all(|leg| leg.ratio > 0)).then(|| Self {
+            symbol: symbol.into(),
+            currency,
+            exchange,
+            legs,
+            delta_neutral_contract: None,
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    /// Hedge the combo with its underlying in a delta-neutral trade.
+    ///
+    /// # Arguments
+    /// * `delta_neutral_contract` - The underlying contract, along with the delta and price at
+    /// which the hedge is computed.
+    pub const fn with_delta_neutral_contract(
+        mut self,
+        delta_neutral_contract: DeltaNeutralContract,
+    ) -> Self {
+        self.delta_neutral_contract = Some(delta_neutral_contract);
+        self
+    }
+}
+
+impl Security for Combo {
+    #[inline]
+    fn get_contract_id(&self) -> ContractId {
+        ContractId(0)
+    }
+
+    #[inline]
+    fn get_symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    #[inline]
+    fn get_security_type(&self) -> &'static str {
+        "BAG"
+    }
+
+    #[inline]
+    fn get_expiration_date(&self) -> Option<NaiveDate> {
+        None
+    }
+
+    #[inline]
+    fn get_strike(&self) -> Option<f64> {
+        None
+    }
+
+    #[inline]
+    fn get_right(&self) -> Option<&'static str> {
+        None
+    }
+
+    #[inline]
+    fn get_multiplier(&self) -> Option<u32> {
+        None
+    }
+
+    #[inline]
+    fn get_exchange(&self) -> Routing {
+        self.exchange
+    }
+
+    #[inline]
+    fn get_primary_exchange(&self) -> Option<Primary> {
+        None
+    }
+
+    #[inline]
+    fn get_currency(&self) -> Currency {
+        self.currency
+    }
+
+    #[inline]
+    fn get_local_symbol(&self) -> &str {
+        ""
+    }
+
+    #[inline]
+    fn get_trading_class(&self) -> Option<&str> {
+        None
+    }
+
+    #[inline]
+    fn get_combo_legs(&self) -> &[ComboLeg] {
+        &self.legs
+    }
+
+    #[inline]
+    fn get_delta_neutral_contract(&self) -> Option<DeltaNeutralContract> {
+        self.delta_neutral_contract
+    }
+}
+
+impl Serialize for Combo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // The legs are not part of the contract's fields, as their position within a message
+        // depends on the request
+        (
+            self.get_contract_id(),
+            &self.symbol,
+            self.get_security_type(),
+            None::<()>,
+            None::<()>,
+            None::<()>,
+            None::<()>,
+            self.exchange,
+            None::<()>,
+            self.currency,
+            None::<()>,
+            None::<()>,
+        )
+            .serialize(serializer)
+    }
+}
+
+impl indicators::Valid for Combo {}
+
+impl From<Combo> for Contract {
+    #[inline]
+    fn from(value: Combo) -> Self {
+        Self::Combo(value)
+    }
+}
+
+macro_rules! impl_try_from_for_combo {
+    ($($s_name: ident),*) => {
+        $(
+            impl TryFrom<$s_name> for Combo {
+                type Error = UnexpectedSecurityType;
+
+                #[inline]
+                fn try_from(_: $s_name) -> Result<Self, Self::Error> {
+                    Err(UnexpectedSecurityType(concat!(
+                        "Expected Combo; found ",
+                        stringify!($s_name)
+                    )))
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_for_combo!(Forex, Crypto, Stock, Index, SecFuture, SecOption, Commodity);
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The legs of a security as they follow the contract in a market data request: nothing, unless
+/// the security is a [`Combo`].
+pub(crate) struct MarketDataLegs<'l>(pub(crate) &'l [ComboLeg]);
+
+impl Serialize for MarketDataLegs<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.is_empty() {
+            serializer.serialize_unit()
+        } else {
+            (self.0.len(), self.0).serialize(serializer)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A delta-neutral contract as it is serialized in market data and place-order requests: a flag,
+/// followed by the contract if the flag is set.
+pub(crate) struct DeltaNeutralField(pub(crate) Option<DeltaNeutralContract>);
+
+impl Serialize for DeltaNeutralField {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Some(contract) => {
+                (true, contract.contract_id, contract.delta, contract.price).serialize(serializer)
+            }
+            None => false.serialize(serializer),
+        }
+    }
+}
+
+// =========================
+// === Overnight Trading ===
+// =========================
+
+const OVERNIGHT_ROUTING: Routing = Routing::Primary(Primary::OvernightTrading);
+
+impl Stock {
+    #[inline]
+    #[must_use]
+    /// Check whether the stock can be routed to IBKR's overnight venue, which trades US equities
+    /// between the after-hours session and the next day's pre-market session.
+    ///
+    /// # Returns
+    /// Returns [`true`] if the overnight venue is one of the stock's valid exchanges.
+    pub fn is_overnight_eligible(&self) -> bool {
+        self.valid_exchanges.contains(&OVERNIGHT_ROUTING)
+    }
+
+    #[must_use]
+    /// Create a copy of the stock that routes orders and market data requests to IBKR's overnight
+    /// venue.
+    ///
+    /// # Returns
+    /// The rerouted stock, or [`None`] if the stock cannot be traded overnight.
+    pub fn to_overnight(&self) -> Option<Self> {
+        self.is_overnight_eligible().then(|| Self {
+            exchange: OVERNIGHT_ROUTING,
+            ..self.clone()
+        })
+    }
+
+    #[must_use]
+    /// Check whether the stock is tradable overnight at a given time.
+    ///
+    /// The overnight session runs from 20:00 to 03:50 US/Eastern time, beginning on Sunday
+    /// through Thursday evenings. Exchange holidays are not taken into account.
+    ///
+    /// # Arguments
+    /// * `datetime` - The time at which to check.
+    ///
+    /// # Returns
+    /// Returns [`true`] if the stock is eligible for overnight trading and `datetime` falls within
+    /// the overnight session.
+    pub fn is_tradable_overnight_at(&self, datetime: DateTime<Utc>) -> bool {
+        self.is_overnight_eligible() && is_overnight_session(datetime)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Check whether the stock is currently tradable overnight. See
+    /// [`Stock::is_tradable_overnight_at`] for details.
+    ///
+    /// # Returns
+    /// Returns [`true`] if the stock is eligible for overnight trading and the overnight session
+    /// is currently open.
+    pub fn is_tradable_overnight(&self) -> bool {
+        self.is_tradable_overnight_at(Utc::now())
+    }
+}
+
+#[inline]
+fn is_overnight_session(datetime: DateTime<Utc>) -> bool {
+    let eastern = datetime.with_timezone(&chrono_tz::America::New_York);
+    let minutes = eastern.hour() * 60 + eastern.minute();
+    (minutes >= 20 * 60 && !matches!(eastern.weekday(), Weekday::Fri | Weekday::Sat))
+        || (minutes < 3 * 60 + 50 && !matches!(eastern.weekday(), Weekday::Sat | Weekday::Sun))
+}
+
 // ===============================
 // === Unimplemented Contracts ===
 // ===============================
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+
+use crate::payload::{Bar, BarCore};
+
+// === Constants ===
+
+/// The relative change in the adjustment of overlapping bars below which no corporate action is
+/// detected, which absorbs the rounding of IBKR's adjusted prices.
+pub const DEFAULT_TOLERANCE: f64 = 1e-4;
+
+/// The largest numerator or denominator of the ratio of a split (ex: 10 for a one-for-ten reverse
+/// split).
+const MAX_SPLIT_TERM: u8 = 10;
+
+/// The smallest change in the adjustment that is taken to be a split rather than a dividend, since
+/// a dividend rarely exceeds a tenth of the share price.
+const MIN_SPLIT_CHANGE: f64 = 0.1;
+
+/// The number of bars that two series must share before a change in their adjustment is trusted,
+/// so that a single revised bar (such as the last bar of a partial download) is not mistaken for a
+/// corporate action.
+pub const MIN_OVERLAP: usize = 5;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// How a cached series of bars is brought up to date once a corporate action is detected.
+pub enum CachePolicy {
+    /// Discard the cached bars and keep only the newly downloaded ones.
+    Invalidate,
+    /// Scale the cached prices by the new adjustment factor and merge in the newly downloaded
+    /// bars. Volumes are scaled inversely when the action is a split (see
+    /// [`CorporateActionDetected::is_split`]), and left unchanged after a dividend.
+    Readjust,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The event emitted when re-downloaded
+/// [`crate::market_data::historical_bar::data_types::AdjustedLast`] bars disagree with the cached
+/// bars for the same times, which happens once a split or dividend is applied to the history.
+pub struct CorporateActionDetected {
+    /// The factor by which the cached prices must be multiplied to match the new data (ex: 0.5
+    /// after a two-for-one split).
+    pub factor: f64,
+    /// The number of bars present in both series from which the factor was computed.
+    pub overlap: usize,
+    /// The timestamp of the earliest overlapping bar.
+    pub first: NaiveDateTime,
+    /// The timestamp of the latest overlapping bar.
+    pub last: NaiveDateTime,
+}
+
+// === Type implementations ===
+
+impl CorporateActionDetected {
+    #[must_use]
+    /// Whether the action is a split (or a reverse split) rather than a dividend, judged by whether
+    /// the factor is a ratio of two small whole numbers (ex: 1/2 or 3/2) that differs from one by
+    /// more than a dividend would.
+    ///
+    /// # Arguments
+    /// * `tolerance` - The relative difference from the ratio below which the factor matches it
+    /// (ex: [`DEFAULT_TOLERANCE`]).
+    pub fn is_split(&self, tolerance: f64) -> bool {
+        if (self.factor - 1.0).abs() <= MIN_SPLIT_CHANGE {
+            return false;
+        }
+        (1..=MAX_SPLIT_TERM).any(|numerator| {
+            (1..=MAX_SPLIT_TERM).any(|denominator| {
+                let ratio = f64::from(numerator) / f64::from(denominator);
+                (self.factor / ratio - 1.0).abs() <= tolerance
+            })
+        })
+    }
+}
+
+// === Functions ===
+
+/// Compare newly downloaded bars with cached bars and detect a change in their adjustment.
+///
+/// The factor is the median ratio of the new to the cached close prices of the bars that share a
+/// timestamp, so that a few revised bars do not register as a corporate action. No action is
+/// detected unless at least [`MIN_OVERLAP`] bars share a timestamp.
+///
+/// # Arguments
+/// * `cached` - The previously downloaded bars.
+/// * `fresh` - The newly downloaded bars.
+/// * `tolerance` - The relative change in the factor below which no action is detected (ex:
+/// [`DEFAULT_TOLERANCE`]).
+///
+/// # Returns
+/// The detected action, or [`None`] if the series overlap too little or their adjustment agrees.
+pub fn detect(cached: &[Bar], fresh: &[Bar], tolerance: f64) -> Option<CorporateActionDetected> {
+    let cached = cached
+        .iter()
+        .map(|bar| (bar.core().datetime, bar.core().close))
+        .collect::<HashMap<_, _>>();
+    let mut first = NaiveDateTime::MAX;
+    let mut last = NaiveDateTime::MIN;
+    let mut ratios = fresh
+        .iter()
+        .filter_map(|bar| {
+            let BarCore {
+                datetime, close, ..
+            } = *bar.core();
+            let cached = *cached.get(&datetime)?;
+            (cached > 0.0 && close > 0.0).then(|| {
+                first = first.min(datetime);
+                last = last.max(datetime);
+                close / cached
+            })
+        })
+        .collect::<Vec<_>>();
+    if ratios.len() < MIN_OVERLAP {
+        return None;
+    }
+    ratios.sort_unstable_by(f64::total_cmp);
+    let factor = ratios[ratios.len() / 2];
+    ((factor - 1.0).abs() > tolerance).then_some(CorporateActionDetected {
+        factor,
+        overlap: ratios.len(),
+        first,
+        last,
+    })
+}
+
+/// Bring a cached series of bars up to date with newly downloaded bars, detecting any corporate
+/// action with [`detect`] along the way.
+///
+/// The new bars replace any cached bars with the same timestamps, and the result is sorted
+/// chronologically.
+///
+/// # Arguments
+/// * `cached` - The previously downloaded bars, which are updated in place.
+/// * `fresh` - The newly downloaded bars.
+/// * `policy` - How the cached bars are treated when an action is detected.
+/// * `tolerance` - The relative change in the factor below which no action is detected (ex:
+/// [`DEFAULT_TOLERANCE`]).
+///
+/// # Returns
+/// The detected action, if any.
+pub fn reconcile(
+    cached: &mut Vec<Bar>,
+    fresh: &[Bar],
+    policy: CachePolicy,
+    tolerance: f64,
+) -> Option<CorporateActionDetected> {
+    let detected = detect(cached, fresh, tolerance);
+    if let Some(action) = detected {
+        match policy {
+            CachePolicy::Invalidate => cached.clear(),
+            CachePolicy::Readjust => {
+                let split = action.is_split(tolerance);
+                for bar in cached.iter_mut() {
+                    readjust(bar, action.factor, split);
+                }
+            }
+        }
+    }
+    let mut merged = cached
+        .drain(..)
+        .chain(fresh.iter().copied())
+        .map(|bar| (bar.core().datetime, bar))
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into_values()
+        .collect::<Vec<_>>();
+    std::mem::swap(cached, &mut merged);
+    detected
+}
+
+// === Helper functions ===
+
+fn readjust(bar: &mut Bar, factor: f64, split: bool) {
+    let core = match bar {
+        Bar::Ordinary(core) => core,
+        Bar::Trades {
+            bar, volume, wap, ..
+        } => {
+            *wap *= factor;
+            // A split changes the number of shares traded in inverse proportion to their price
+            if split {
+                *volume /= factor;
+            }
+            bar
+        }
+    };
+    core.open *= factor;
+    core.high *= factor;
+    core.low *= factor;
+    core.close *= factor;
+}
@@ -84,6 +84,39 @@ impl std::error::Error for ParseCurrencyError {
 
 // === Type implementations ===
 
+impl Currency {
+    #[inline]
+    /// Order a pair of currencies as they are quoted on IDEALPRO, which follows the market
+    /// convention that EUR, GBP, AUD, and NZD are quoted against USD, while USD is quoted against
+    /// every other currency.
+    ///
+    /// # Returns
+    /// The pair's (base, quote) currencies.
+    pub(crate) fn idealpro_pair(self, other: Self) -> (Self, Self) {
+        const PRIORITY: [Currency; 8] = [
+            Currency::Euro,
+            Currency::BritishPound,
+            Currency::AustralianDollar,
+            Currency::NewZealandDollar,
+            Currency::USDollar,
+            Currency::CanadianDollar,
+            Currency::SwissFranc,
+            Currency::JapaneseYen,
+        ];
+        let rank = |c: Self| {
+            PRIORITY
+                .iter()
+                .position(|&p| p == c)
+                .unwrap_or(PRIORITY.len())
+        };
+        if rank(other) < rank(self) {
+            (other, self)
+        } else {
+            (self, other)
+        }
+    }
+}
+
 impl ToString for Currency {
     fn to_string(&self) -> String {
         match *self {
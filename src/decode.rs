@@ -1,35 +1,50 @@
+use std::sync::atomic::Ordering;
+use std::sync::{Mutex, MutexGuard};
+
 use anyhow::Context;
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 
-use crate::account::{self, Tag, TagValue};
+use crate::account::{self, AccountEvent, AccountId, Tag, TagValue};
+use crate::alert;
+use crate::calendar::TradingHours;
 use crate::contract::{
-    Commodity, Contract, ContractId, Crypto, Forex, Index, SecFuture, SecOption, SecOptionInner,
-    SecurityId, Stock,
+    Commodity, Contract, ContractId, Crypto, DeltaNeutralContract, Forex, Index, SecFuture,
+    SecOption, SecOptionInner, SecurityId, Stock,
 };
+use crate::flatten::Holding;
 use crate::payload::{
     market_depth::{CompleteEntry, Entry, Operation},
-    Bar, BarCore, ExchangeId, HistogramEntry, MarketDataClass, Pnl, Position, PositionSummary,
-    Tick,
+    ArticleType, Bar, BarCore, BulletinKind, ExchangeId, HistogramEntry, HistoricalNews,
+    HistoricalSchedule, MarketDataClass, MissedBars, NewsArticle, NewsBulletin, NewsHeadline,
+    NewsProvider, OpenOrder, OptionChain, OrderState, OrderStatus, OrderStatusUpdate, Pnl,
+    Position, PositionSummary, ScheduledSession, SmartComponent, SymbolSample, Tick,
+    REAL_TIME_BAR_SECONDS,
 };
 use crate::tick::{
     Accessibility, AuctionData, CalculationResult, Class, Dividends, EtfNav, ExtremeValue, Ipo,
     MarkPrice, OpenInterest, Period, Price, PriceFactor, QuotingExchanges, Rate, RealTimeVolume,
     RealTimeVolumeBase, SecOptionCalculationResults, SecOptionCalculationSource,
-    SecOptionCalculations, SecOptionVolume, Size, SummaryVolume, TimeStamp, Volatility, Yield,
+    SecOptionCalculations, SecOptionVolume, Size, SummaryVolume, TickAttributes, TickType,
+    TimeStamp, TradeFilter, Volatility, Yield,
 };
 use crate::{
+    client::{ConnectionHealth, CurrentTimeRequest, Shared},
     currency::Currency,
+    error::Error,
     exchange::Routing,
-    message::{ToClient, ToWrapper},
+    execution::{CommissionReport, Execution, OrderSide, ResolvedExecution},
+    message::In,
     order::TimeInForce,
+    quantity::{Price, Quantity},
+    scanner,
+    stream::{self, TypedEvent},
+    tracker,
     wrapper::{
         indicators::{LocalMarker, RemoteMarker, Wrapper},
         Local, Remote,
     },
 };
 
-type Tx = tokio::sync::mpsc::Sender<ToClient>;
-type Rx = tokio::sync::mpsc::Receiver<ToWrapper>;
 type Fields = std::vec::IntoIter<String>;
 
 macro_rules! decode_fields {
@@ -96,7 +111,11 @@ where
     W: Local<'c>,
 {
     #[inline]
-    pub async fn tick_price_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_price_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
         fields =>
             req_id @ 2: i64,
@@ -118,6 +137,10 @@ where
         if (price + 1.0).abs() < f64::EPSILON || (price == 0.0 && size == Some(0.0)) {
             return Ok(());
         }
+        let attributes = TickAttributes::from_price_mask(attr_mask);
+        if let Some(quote) = lock(&shared.quotes)?.get_mut(&req_id) {
+            quote.apply(tick_type, price);
+        }
 
         match tick_type {
             1 | 2 | 4 | 6 | 7 | 9 | 14 => {
@@ -134,8 +157,15 @@ where
                     (14, _) => (Price::Open(price), None),
                     _ => panic!("The impossible occurred"),
                 };
-                wrapper.price_data(req_id, Class::Live(price)).await;
+                stream::publish(shared, req_id, || TypedEvent::Price {
+                    price: Class::Live(price),
+                    attributes,
+                });
+                wrapper
+                    .price_data(req_id, Class::Live(price), attributes)
+                    .await;
                 if let Some(sz) = size {
+                    stream::publish(shared, req_id, || TypedEvent::Size(Class::Live(sz)));
                     wrapper.size_data(req_id, Class::Live(sz)).await;
                 }
             }
@@ -172,8 +202,12 @@ where
                 wrapper.yield_data(req_id, yld).await;
             }
             57 => {
+                stream::publish(shared, req_id, || TypedEvent::Price {
+                    price: Class::Live(Price::LastRthTrade(price)),
+                    attributes,
+                });
                 wrapper
-                    .price_data(req_id, Class::Live(Price::LastRthTrade(price)))
+                    .price_data(req_id, Class::Live(Price::LastRthTrade(price)), attributes)
                     .await;
             }
             66..=68 | 72 | 73 | 75 | 76 => {
@@ -190,8 +224,15 @@ where
                     (76, _) => (Price::Open(price), None),
                     _ => panic!("The impossible occurred"),
                 };
-                wrapper.price_data(req_id, Class::Delayed(price)).await;
+                stream::publish(shared, req_id, || TypedEvent::Price {
+                    price: Class::Delayed(price),
+                    attributes,
+                });
+                wrapper
+                    .price_data(req_id, Class::Delayed(price), attributes)
+                    .await;
                 if let Some(sz) = size {
+                    stream::publish(shared, req_id, || TypedEvent::Size(Class::Delayed(sz)));
                     wrapper.size_data(req_id, Class::Delayed(sz)).await;
                 }
             }
@@ -210,34 +251,81 @@ where
                 wrapper.etf_nav(req_id, nav).await;
             }
             t => {
-                return Err(anyhow::Error::msg(format!(
-                    "Unexpected price market data request: {t}"
-                )))
+                wrapper
+                    .other_tick(req_id, TickType::from(u32::from(t)), price.to_string())
+                    .await;
             }
         };
         Ok(())
     }
 
     #[inline]
-    pub async fn tick_size_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_size_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
                 tick_type @ 0: u16,
                 value @ 0: f64
         );
-        Self::decode_generic_tick_msg(req_id, tick_type, value, wrapper).await
+        if let Some(quote) = lock(&shared.quotes)?.get_mut(&req_id) {
+            quote.apply(tick_type, value);
+        }
+        Self::decode_generic_tick_msg(req_id, tick_type, value, wrapper, shared).await
     }
 
     #[inline]
-    pub async fn order_status_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn order_status_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                order_id @ 1: i64,
+                status @ 0: OrderStatus,
+                filled @ 0: Quantity,
+                remaining @ 0: Quantity,
+                average_fill_price @ 0: f64,
+                perm_id @ 0: i64,
+                parent_id @ 0: i64,
+                last_fill_price @ 0: f64,
+                client_id @ 0: i64,
+                why_held @ 0: String,
+                market_cap_price @ 0: String
+        );
+        lock(&shared.order_latency)?.status(order_id, status.is_done());
+        let update = OrderStatusUpdate {
+            order_id,
+            status,
+            filled,
+            remaining,
+            average_fill_price,
+            perm_id,
+            parent_id,
+            last_fill_price,
+            client_id,
+            why_held,
+            market_cap_price: decode_order_price(&market_cap_price).filter(|p| *p > 0.0),
+        };
+        tracker::publish_status(shared, &update);
+        if update.status.is_done() {
+            crate::client::lock_expirations(shared).remove(&update.order_id);
+        }
+        wrapper.order_status(update).await;
         Ok(())
     }
 
     #[inline]
     // todo: Implement a proper Error Enum
-    pub async fn err_msg_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn err_msg_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
@@ -245,6 +333,23 @@ where
                 error_string @ 0: String,
                 advanced_order_reject_json @ 0: String
         );
+        match error_code {
+            1100 => {
+                alert::set_health(shared, ConnectionHealth::ServerConnectionLost);
+                wrapper.reconnecting().await;
+            }
+            1101 => {
+                shared.health.set(ConnectionHealth::RestoredDataLost);
+                wrapper.reconnected(true).await;
+            }
+            1102 => {
+                shared.health.set(ConnectionHealth::Restored);
+                wrapper.reconnected(false).await;
+            }
+            _ => (),
+        }
+        fail_requests(shared, req_id, error_code, &error_string);
+        alert::publish_error(shared, req_id, error_code, &error_string);
         wrapper
             .error(req_id, error_code, error_string, advanced_order_reject_json)
             .await;
@@ -252,288 +357,152 @@ where
     }
 
     #[inline]
-    pub async fn open_order_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn open_order_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 order_id @ 1: i64,
                 contract_id @ 0: ContractId,
-                action @ 10: String,
-                quantity @ 0: f64,
+                side @ 10: OrderSide,
+                quantity @ 0: Quantity,
                 order_type @ 0: String,
                 price @ 0: String,
                 aux_price @ 0: String,
-                time_in_force @ 0: TimeInForce
+                time_in_force @ 0: String,
+                good_till_date @ 16: String
         );
+        let time_in_force = decode_time_in_force(&time_in_force, &good_till_date)?;
+        if shared.what_if_orders.is_awaited(&order_id) {
+            let state = decode_order_state(fields)
+                .with_context(|| "Expected what-if order state, found none");
+            shared
+                .what_if_orders
+                .deliver(&order_id, state.map_err(|e| Error::Parse(format!("{e:#}"))));
+            return Ok(());
+        }
+        let order = OpenOrder {
+            order_id,
+            contract_id,
+            side,
+            quantity,
+            // An unset price is sent as an empty field or as the maximum double, neither of which
+            // parses as a price
+            limit_price: price.parse().ok(),
+            aux_price: aux_price.parse().ok(),
+            time_in_force,
+            delta_neutral_contract: decode_delta_neutral_contract(fields),
+        };
+        if let Some(orders) = lock(&shared.open_orders)?.as_mut() {
+            match orders
+                .iter_mut()
+                .find(|o| o.order_id == order_id && o.contract_id == contract_id)
+            {
+                Some(existing) => *existing = order,
+                None => orders.push(order),
+            }
+        }
+        tracker::publish_open_order(shared, order);
+        wrapper.open_order(order).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn acct_value_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn acct_value_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 name @ 2: String,
                 value @ 0: String,
                 currency @ 0: String,
-                account_number @ 0: String
-        );
-        let attribute = match name.as_str() {
-            "AccountCode" => account::Attribute::AccountCode(value),
-            "AccountOrGroup" => match value.as_str() {
-                "All" => account::Attribute::AccountOrGroup(account::Group::All, currency.parse()?),
-                name => account::Attribute::AccountOrGroup(
-                    account::Group::Name(name.to_owned()),
-                    currency.parse()?,
-                ),
-            },
-            "AccountReady" => account::Attribute::AccountReady(value.parse()?),
-            "AccountType" => account::Attribute::AccountType(value),
-            expand_seg_variants!("AccruedCash") => account::Attribute::AccruedCash(
-                impl_seg_variants!("AccruedCash", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("AccruedDividend") => account::Attribute::AccruedDividend(
-                impl_seg_variants!("AccruedDividend", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("AvailableFunds") => account::Attribute::AvailableFunds(
-                impl_seg_variants!("AvailableFunds", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("Billable") => account::Attribute::Billable(
-                impl_seg_variants!("Billable", name, value),
-                currency.parse()?,
-            ),
-            "BuyingPower" => account::Attribute::BuyingPower(value.parse()?, currency.parse()?),
-            "CashBalance" => account::Attribute::CashBalance(value.parse()?, currency.parse()?),
-            expand_seg_variants!("ColumnPrio") => {
-                account::Attribute::ColumnPrio(impl_seg_variants!("ColumnPrio", name, value))
-            }
-            "CorporateBondValue" => {
-                account::Attribute::CorporateBondValue(value.parse()?, currency.parse()?)
-            }
-            "Cryptocurrency" => {
-                account::Attribute::Cryptocurrency(value.parse()?, currency.parse()?)
-            }
-            "Currency" => account::Attribute::Currency(value.parse()?),
-            "Cushion" => account::Attribute::Cushion(value.parse()?),
-            "DayTradesRemaining" => account::Attribute::DayTradesRemaining(value.parse()?),
-            "DayTradesRemainingT+1" => account::Attribute::DayTradesRemainingTPlus1(value.parse()?),
-            "DayTradesRemainingT+2" => account::Attribute::DayTradesRemainingTPlus2(value.parse()?),
-            "DayTradesRemainingT+3" => account::Attribute::DayTradesRemainingTPlus3(value.parse()?),
-            "DayTradesRemainingT+4" => account::Attribute::DayTradesRemainingTPlus4(value.parse()?),
-            "DayTradingStatus-S" => account::Attribute::DayTradingStatus(value),
-            expand_seg_variants!("EquityWithLoanValue") => account::Attribute::EquityWithLoanValue(
-                impl_seg_variants!("EquityWithLoanValue", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("ExcessLiquidity") => account::Attribute::ExcessLiquidity(
-                impl_seg_variants!("ExcessLiquidity", name, value),
-                currency.parse()?,
-            ),
-            "ExchangeRate" => account::Attribute::ExchangeRate(value.parse()?, currency.parse()?),
-            expand_seg_variants!("FullAvailableFunds") => account::Attribute::FullAvailableFunds(
-                impl_seg_variants!("FullAvailableFunds", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("FullExcessLiquidity") => account::Attribute::FullExcessLiquidity(
-                impl_seg_variants!("FullExcessLiquidity", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("FullInitMarginReq") => account::Attribute::FullInitMarginReq(
-                impl_seg_variants!("FullInitMarginReq", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("FullMaintMarginReq") => {
-                account::Attribute::FullMaintenanceMarginReq(
-                    impl_seg_variants!("FullMaintMarginReq", name, value),
-                    currency.parse()?,
-                )
-            }
-            "FundValue" => account::Attribute::FundValue(value.parse()?, currency.parse()?),
-            "FutureOptionValue" => {
-                account::Attribute::FutureOptionValue(value.parse()?, currency.parse()?)
-            }
-            "FuturesPNL" => account::Attribute::FuturesPnl(value.parse()?, currency.parse()?),
-            "FxCashBalance" => account::Attribute::FxCashBalance(value.parse()?, currency.parse()?),
-            "GrossPositionValue" => {
-                account::Attribute::GrossPositionValue(value.parse()?, currency.parse()?)
-            }
-            "GrossPositionValue-S" => {
-                account::Attribute::GrossPositionValueSecurity(value.parse()?, currency.parse()?)
-            }
-            expand_seg_variants!("Guarantee") => account::Attribute::Guarantee(
-                impl_seg_variants!("Guarantee", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("IndianStockHaircut") => account::Attribute::IndianStockHaircut(
-                impl_seg_variants!("IndianStockHaircut", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("InitMarginReq") => account::Attribute::InitMarginReq(
-                impl_seg_variants!("InitMarginReq", name, value),
-                currency.parse()?,
-            ),
-            "IssuerOptionValue" => {
-                account::Attribute::IssuerOptionValue(value.parse()?, currency.parse()?)
-            }
-            "Leverage-S" => account::Attribute::LeverageSecurity(value.parse()?),
-            expand_seg_variants!("LookAheadAvailableFunds") => {
-                account::Attribute::LookAheadAvailableFunds(
-                    impl_seg_variants!("LookAheadAvailableFunds", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("LookAheadExcessLiquidity") => {
-                account::Attribute::LookAheadExcessLiquidity(
-                    impl_seg_variants!("LookAheadExcessLiquidity", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("LookAheadInitMarginReq") => {
-                account::Attribute::LookAheadInitMarginReq(
-                    impl_seg_variants!("LookAheadInitMarginReq", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("LookAheadMaintMarginReq") => {
-                account::Attribute::LookAheadMaintenanceMarginReq(
-                    impl_seg_variants!("LookAheadMaintMarginReq", name, value),
-                    currency.parse()?,
-                )
-            }
-            "LookAheadNextChange" => account::Attribute::LookAheadNextChange(value.parse()?),
-            expand_seg_variants!("MaintMarginReq") => account::Attribute::MaintenanceMarginReq(
-                impl_seg_variants!("MaintMarginReq", name, value),
-                currency.parse()?,
-            ),
-            "MoneyMarketFundValue" => {
-                account::Attribute::MoneyMarketFundValue(value.parse()?, currency.parse()?)
-            }
-            "MutualFundValue" => {
-                account::Attribute::MutualFundValue(value.parse()?, currency.parse()?)
-            }
-            "NLVAndMarginInReview" => account::Attribute::NlvAndMarginInReview(value.parse()?),
-            "NetDividend" => account::Attribute::NetDividend(value.parse()?, currency.parse()?),
-            expand_seg_variants!("NetLiquidation") => account::Attribute::NetLiquidation(
-                impl_seg_variants!("NetLiquidation", name, value),
-                currency.parse()?,
-            ),
-            "NetLiquidationByCurrency" => {
-                account::Attribute::NetLiquidationByCurrency(value.parse()?, currency.parse()?)
-            }
-            "NetLiquidationUncertainty" => {
-                account::Attribute::NetLiquidationUncertainty(value.parse()?, currency.parse()?)
-            }
-            "OptionMarketValue" => {
-                account::Attribute::OptionMarketValue(value.parse()?, currency.parse()?)
-            }
-            expand_seg_variants!("PASharesValue") => account::Attribute::PaSharesValue(
-                impl_seg_variants!("PASharesValue", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("PhysicalCertificateValue") => {
-                account::Attribute::PhysicalCertificateValue(
-                    impl_seg_variants!("PhysicalCertificateValue", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("PostExpirationExcess") => {
-                account::Attribute::PostExpirationExcess(
-                    impl_seg_variants!("PostExpirationExcess", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("PostExpirationMargin") => {
-                account::Attribute::PostExpirationMargin(
-                    impl_seg_variants!("PostExpirationMargin", name, value),
-                    currency.parse()?,
-                )
-            }
-            "PreviousDayEquityWithLoanValue" => account::Attribute::PreviousDayEquityWithLoanValue(
-                value.parse()?,
-                currency.parse()?,
-            ),
-            "PreviousDayEquityWithLoanValue-S" => {
-                account::Attribute::PreviousDayEquityWithLoanValueSecurity(
-                    value.parse()?,
-                    currency.parse()?,
-                )
-            }
-            "RealCurrency" => account::Attribute::RealCurrency(currency.parse()?),
-            "RealizedPnL" => account::Attribute::RealizedPnL(value.parse()?, currency.parse()?),
-            "RegTEquity" => account::Attribute::RegTEquity(value.parse()?, currency.parse()?),
-            "RegTEquity-S" => {
-                account::Attribute::RegTEquitySecurity(value.parse()?, currency.parse()?)
-            }
-            "RegTMargin" => account::Attribute::RegTMargin(value.parse()?, currency.parse()?),
-            "RegTMargin-S" => {
-                account::Attribute::RegTMarginSecurity(value.parse()?, currency.parse()?)
-            }
-            "SMA" => account::Attribute::Sma(value.parse()?, currency.parse()?),
-            "SMA-S" => account::Attribute::SmaSecurity(value.parse()?, currency.parse()?),
-            "StockMarketValue" => {
-                account::Attribute::StockMarketValue(value.parse()?, currency.parse()?)
-            }
-            "TBillValue" => account::Attribute::TBillValue(value.parse()?, currency.parse()?),
-            "TBondValue" => account::Attribute::TBondValue(value.parse()?, currency.parse()?),
-            "TotalCashBalance" => {
-                account::Attribute::TotalCashBalance(value.parse()?, currency.parse()?)
-            }
-            expand_seg_variants!("TotalCashValue") => account::Attribute::TotalCashValue(
-                impl_seg_variants!("TotalCashValue", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("TotalDebitCardPendingCharges") => {
-                account::Attribute::TotalDebitCardPendingCharges(
-                    impl_seg_variants!("TotalDebitCardPendingCharges", name, value),
-                    currency.parse()?,
-                )
-            }
-            "TradingType-S" => account::Attribute::TradingTypeSecurity(value),
-            "UnrealizedPnL" => account::Attribute::UnrealizedPnL(value.parse()?, currency.parse()?),
-            "WarrantValue" => account::Attribute::WarrantValue(value.parse()?, currency.parse()?),
-            "WhatIfPMEnabled" => account::Attribute::WhatIfPMEnabled(value.parse()?),
-            expand_seg_variants!("SegmentTitle") => {
-                if name.ends_with('C') || name.ends_with('P') || name.ends_with('S') {
-                    return Ok(());
-                }
-                return Err(anyhow::Error::msg("Unexpected segment title encountered.  This may mandate an API update: currently-supported values are C, P, and S as outlined in the account::Segment type."));
-            }
-            _ => {
-                return Err(anyhow::Error::msg(format!(
-                    "Invalid account attribute encountered: {name}"
-                )))
-            }
+                account_number @ 0: AccountId
+        );
+        let Some(attribute) = decode_account_attribute(name, value, currency)? else {
+            return Ok(());
         };
+        if let account::Attribute::CashBalance(value, account::Denomination::Specific(currency)) =
+            attribute
+        {
+            lock(&shared.cash_balances)?.insert((account_number.clone(), currency), value);
+        }
+        if let Some((key, update)) = attribute.value_update() {
+            lock(&shared.account_values)?
+                .entry((account_number.clone(), key))
+                .or_insert_with(|| tokio::sync::watch::channel(None).0)
+                .send_if_modified(|current| {
+                    let modified = *current != Some(update);
+                    *current = Some(update);
+                    modified
+                });
+        }
         wrapper.account_attribute(attribute, account_number).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn portfolio_value_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn portfolio_value_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 contract_id @ 2: ContractId,
-                position @ 10: f64,
+                currency @ 7: String,
+                position @ 2: Quantity,
                 market_price @ 0: f64,
                 market_value @ 0: f64,
                 average_cost @ 0: f64,
                 unrealized_pnl @ 0: f64,
                 realized_pnl @ 0: f64,
-                account_name @ 0: String
+                account_name @ 0: AccountId
         );
-        wrapper
-            .position(Position {
-                contract_id,
-                position,
-                market_price,
-                market_value,
-                average_cost,
-                unrealized_pnl,
-                realized_pnl,
-                account_number: account_name,
-            })
-            .await;
+        if let Ok(currency) = currency.parse::<Currency>() {
+            let mut portfolio = lock(&shared.portfolio)?;
+            if position.is_zero() {
+                portfolio.remove(&(account_name.clone(), contract_id));
+            } else {
+                portfolio.insert(
+                    (account_name.clone(), contract_id),
+                    (currency, market_value),
+                );
+            }
+        }
+        {
+            let mut positions = lock(&shared.positions)?;
+            if position.is_zero() {
+                positions.remove(&(account_name.clone(), contract_id));
+            } else {
+                positions.insert(
+                    (account_name.clone(), contract_id),
+                    Holding {
+                        position,
+                        market_price: Some(market_price),
+                    },
+                );
+            }
+        }
+        let position = Position {
+            contract_id,
+            position,
+            market_price,
+            market_value,
+            average_cost,
+            unrealized_pnl,
+            realized_pnl,
+            account_number: account_name,
+        };
+        if let Some(tx) =
+            lock(&shared.position_values)?.get(&(position.account_number.clone(), contract_id))
+        {
+            tx.send_replace(Some(position.clone()));
+        }
+        wrapper.position(position).await;
         Ok(())
     }
 
@@ -554,9 +523,13 @@ where
     pub(crate) async fn next_valid_id_msg(
         fields: &mut Fields,
         wrapper: &mut W,
-        tx: &mut Tx,
-        rx: &mut Rx,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                order_id @ 2: i64
+        );
+        shared.next_valid_ids.deliver(&(), order_id);
         Ok(())
     }
 
@@ -565,212 +538,96 @@ where
     pub(crate) async fn contract_data_msg(
         fields: &mut Fields,
         wrapper: &mut W,
-        tx: &mut Tx,
-        rx: &mut Rx,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        if let Some(contract) = decode_contract_no_wrapper(fields, shared).await? {
+            for (exec_req_id, execution) in take_unresolved(shared, contract.get_contract_id())? {
+                wrapper
+                    .resolved_execution(
+                        exec_req_id,
+                        ResolvedExecution {
+                            execution,
+                            contract: contract.clone(),
+                        },
+                    )
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn execution_data_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
-                symbol @ 0: String,
-                sec_type @ 0: String,
-                expiration_date @ 0: String,
-                strike @ 0: f64,
-                class @ 0: String,
-                exchange @ 0: Routing,
-                currency @ 0: Currency,
-                local_symbol @ 0: String,
-                trading_class @ 1: String,
+                order_id @ 0: i64,
                 contract_id @ 0: ContractId,
-                min_tick @ 0: f64,
-                multiplier @ 0: String,
-                order_types @ 0: String,
-                valid_exchanges @ 0: String,
-                underlying_contract_id @ 1: ContractId,
-                long_name @ 0: String,
-                primary_exchange @ 0: String,
-                sector @ 1: String,
-                security_id_count @ 7: usize
-        );
-
-        let order_types = order_types
-            .split(',')
-            .map(std::borrow::ToOwned::to_owned)
-            .collect();
-        let valid_exchanges = valid_exchanges
-            .split(',')
-            .map(str::parse)
-            .collect::<Result<Vec<Routing>, _>>()
-            .with_context(|| "Invalid exchange in valid_exchanges")?;
-        let security_ids = (0..security_id_count)
-            .map(|_| {
-                match nth(fields, 0)
-                    .with_context(|| "Expected number of security_ids but none found")?
-                    .to_uppercase()
-                    .as_str()
-                {
-                    "CUSIP" => Ok(SecurityId::Cusip(
-                        nth(fields, 0).with_context(|| "Expected CUSIP but none found")?,
-                    )),
-                    "SEDOL" => Ok(SecurityId::Sedol(
-                        nth(fields, 0).with_context(|| "Expected SEDOL but none found")?,
-                    )),
-                    "ISIN" => Ok(SecurityId::Isin(
-                        nth(fields, 0).with_context(|| "Expected ISIN but none found")?,
-                    )),
-                    "RIC" => Ok(SecurityId::Ric(
-                        nth(fields, 0).with_context(|| "Expected RIC but none found")?,
-                    )),
-                    _ => Err(anyhow::Error::msg(
-                        "Invalid security_id type found in STK contract_data_msg",
-                    )),
-                }
-            })
-            .collect::<Result<Vec<SecurityId>, _>>()?;
-
-        if let Ok(ToWrapper::ContractQuery((con_id_client, req_id_client))) = rx.try_recv() {
-            if con_id_client != contract_id {
-                return Err(anyhow::Error::msg("Unexpected contract ID"));
-            }
-            if req_id_client != req_id {
-                return Err(anyhow::Error::msg("Unexpected request ID"));
-            }
-            let contract = match sec_type.as_str() {
-                "STK" => Some(Contract::Stock(Stock {
-                    symbol,
-                    exchange,
-                    currency,
-                    local_symbol,
-                    trading_class,
-                    contract_id,
-                    min_tick,
-                    primary_exchange: primary_exchange
-                        .parse()
-                        .with_context(|| "Invalid exchange in STK primary_exchange")?,
-                    long_name,
-                    sector,
-                    order_types,
-                    valid_exchanges,
-                    security_ids,
-                    stock_type: nth(fields, 5)
-                        .with_context(|| "Expected stock_type but none found")?,
-                })),
-                "OPT" => {
-                    let inner = SecOptionInner {
-                        contract_id,
-                        min_tick,
-                        symbol,
-                        exchange,
-                        strike,
-                        multiplier: multiplier
-                            .parse()
-                            .with_context(|| "Invalid multiplier in OPT multiplier")?,
-                        expiration_date: NaiveDate::parse_and_remainder(
-                            expiration_date.as_str(),
-                            "%Y%m%d",
+                symbol @ 0: String,
+                execution_id @ 9: String,
+                datetime @ 0: String,
+                account_number @ 0: AccountId,
+                exchange @ 0: String,
+                side @ 0: OrderSide,
+                quantity @ 0: Quantity,
+                price @ 0: Price,
+                perm_id @ 0: i64,
+                client_id @ 0: i64,
+                liquidation @ 0: i64,
+                cumulative_quantity @ 0: Quantity,
+                average_price @ 0: Price,
+                order_reference @ 0: String
+        );
+        let execution = Execution {
+            execution_id,
+            order_id,
+            contract_id,
+            symbol,
+            datetime: decode_execution_datetime(&datetime)?,
+            account_number,
+            exchange,
+            side,
+            quantity,
+            price,
+            perm_id,
+            client_id,
+            liquidation: liquidation != 0,
+            cumulative_quantity,
+            average_price,
+            order_reference,
+        };
+        lock(&shared.commissions)?.add_execution(&execution);
+        lock(&shared.blotter)?.add_execution(&execution);
+        tracker::publish_execution(shared, &execution);
+        let resolver = lock(&shared.execution_resolver)?.clone();
+        if let Some(resolver) = resolver {
+            let contract = lock(&shared.contracts)?
+                .get(&execution.contract_id)
+                .cloned();
+            match contract {
+                Some(contract) => {
+                    wrapper
+                        .resolved_execution(
+                            req_id,
+                            ResolvedExecution {
+                                execution,
+                                contract,
+                            },
                         )
-                        .with_context(|| "Invalid date string in OPT expiration_date")?
-                        .0,
-                        underlying_contract_id,
-                        sector,
-                        trading_class,
-                        currency,
-                        local_symbol,
-                        long_name,
-                        order_types,
-                        valid_exchanges,
-                    };
-                    match class.as_str() {
-                        "C" => Some(Contract::SecOption(SecOption::Call(inner))),
-                        "P" => Some(Contract::SecOption(SecOption::Put(inner))),
-                        _ => return Err(anyhow::Error::msg("Unexpected option class")),
-                    }
+                        .await;
                 }
-                "CRYPTO" => Some(Contract::Crypto(Crypto {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    trading_class,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                "CASH" => Some(Contract::Forex(Forex {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    exchange,
-                    trading_class,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                "IND" => Some(Contract::Index(Index {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    exchange,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                "FUT" => Some(Contract::SecFuture(SecFuture {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    exchange,
-                    multiplier: multiplier
-                        .parse()
-                        .with_context(|| "Invalid multiplier in FUT multiplier")?,
-                    expiration_date: NaiveDate::parse_and_remainder(
-                        expiration_date.as_str(),
-                        "%Y%m%d",
-                    )
-                    .with_context(|| "Invalid date string in OPT expiration_date")?
-                    .0,
-                    trading_class,
-                    underlying_contract_id,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                "CMDTY" => Some(Contract::Commodity(Commodity {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    exchange,
-                    trading_class,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                _ => todo!(),
-            };
-
-            tx.send(ToClient::NewContract(
-                contract.ok_or_else(|| anyhow::Error::msg("No contract was created"))?,
-            ))
-            .await
-            .with_context(|| "Failure when sending contract")?;
+                None => hold_unresolved(shared, &resolver, req_id, execution)?,
+            }
+        } else {
+            wrapper.execution(req_id, execution).await;
         }
         Ok(())
     }
 
-    #[inline]
-    pub async fn execution_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
-        Ok(())
-    }
-
     #[inline]
     pub async fn market_depth_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
         decode_fields!(
@@ -827,7 +684,27 @@ where
 
     #[inline]
     pub async fn news_bulletins_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                id @ 2: i64,
+                kind @ 0: i64,
+                message @ 0: String,
+                exchange @ 0: String
+        );
+        let kind = match kind {
+            1 => BulletinKind::Regular,
+            2 => BulletinKind::ExchangeUnavailable,
+            3 => BulletinKind::ExchangeAvailable,
+            _ => return Err(anyhow::Error::msg("Unexpected news bulletin type")),
+        };
+        wrapper
+            .news_bulletin(NewsBulletin {
+                id,
+                kind,
+                message,
+                exchange,
+            })
+            .await;
         Ok(())
     }
 
@@ -836,20 +713,22 @@ where
     pub(crate) async fn managed_accts_msg(
         fields: &mut Fields,
         wrapper: &mut W,
-        tx: &mut Tx,
-        rx: &mut Rx,
     ) -> anyhow::Result<()> {
         Ok(())
     }
 
     #[inline]
     pub async fn receive_fa_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn historical_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn historical_data_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -857,32 +736,40 @@ where
                 end_date_str @ 0: String,
                 count @ 0: usize
         );
-        let mut bars = Vec::with_capacity(count);
-        for chunk in fields.collect::<Vec<String>>().chunks(8) {
-            if let [date, open, high, low, close, volume, wap, trade_count] = chunk {
-                let core = BarCore {
-                    datetime: NaiveDateTime::parse_and_remainder(date, "%Y%m%d %T")?.0,
-                    open: open.parse()?,
-                    high: high.parse()?,
-                    low: low.parse()?,
-                    close: close.parse()?,
-                };
-                let (volume, wap, trade_count) =
-                    (volume.parse()?, wap.parse()?, trade_count.parse::<i64>()?);
-                let bar = if volume > 0. && wap > 0. && trade_count > 0 {
-                    Bar::Trades {
-                        bar: core,
-                        volume,
-                        wap,
-                        trade_count: trade_count.try_into()?,
-                    }
-                } else {
-                    Bar::Ordinary(core)
-                };
-                bars.push(bar);
+        let fields = fields.collect();
+        if offload(shared, count) {
+            lock(&shared.pending_batches)?.push_back(Batch::Bars(
+                req_id,
+                tokio::task::spawn_blocking(move || decode_bars(fields)),
+            ));
+        } else {
+            let bars = decode_bars(fields)?;
+            deliver_historical(&shared.historical_bars, req_id, &bars);
+            wrapper.historical_bars(req_id, bars).await;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn deliver_batches(wrapper: &mut W, shared: &Shared) -> anyhow::Result<()> {
+        while let Some(batch) = next_finished_batch(shared)? {
+            match batch {
+                Batch::Bars(req_id, handle) => {
+                    let bars = handle
+                        .await
+                        .with_context(|| "Failure in blocking decode task")??;
+                    deliver_historical(&shared.historical_bars, req_id, &bars);
+                    wrapper.historical_bars(req_id, bars).await;
+                }
+                Batch::Ticks(req_id, handle) => {
+                    let ticks = handle
+                        .await
+                        .with_context(|| "Failure in blocking decode task")??;
+                    deliver_historical(&shared.historical_ticks, req_id, &ticks);
+                    wrapper.historical_ticks(req_id, ticks).await;
+                }
             }
         }
-        wrapper.historical_bars(req_id, bars).await;
         Ok(())
     }
 
@@ -890,9 +777,9 @@ where
     pub async fn bond_contract_data_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
-        Ok(())
+        decode_bond_contract(fields, shared)
     }
 
     #[inline]
@@ -900,13 +787,53 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn scanner_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                number_of_rows @ 0: usize
+        );
+        let rows = (0..number_of_rows)
+            .map(|_| {
+                decode_fields!(
+                    fields =>
+                        rank @ 0: u32,
+                        contract_id @ 0: ContractId,
+                        symbol @ 0: String,
+                        security_type @ 0: String,
+                        exchange @ 3: Routing,
+                        currency @ 0: Currency,
+                        local_symbol @ 0: String,
+                        market_name @ 0: String,
+                        trading_class @ 0: String,
+                        distance @ 0: String,
+                        benchmark @ 0: String,
+                        projection @ 0: String,
+                        legs @ 0: String
+                );
+                Ok(scanner::Row {
+                    rank,
+                    contract_id,
+                    symbol,
+                    security_type,
+                    exchange,
+                    currency,
+                    local_symbol,
+                    market_name,
+                    trading_class,
+                    distance,
+                    benchmark,
+                    projection,
+                    legs,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        wrapper.scanner_data(req_id, rows).await;
         Ok(())
     }
 
@@ -972,18 +899,26 @@ where
     }
 
     #[inline]
-    pub async fn tick_generic_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_generic_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
                 tick_type @ 0: u16,
                 value @ 0: f64
         );
-        Self::decode_generic_tick_msg(req_id, tick_type, value, wrapper).await
+        Self::decode_generic_tick_msg(req_id, tick_type, value, wrapper, shared).await
     }
 
     #[inline]
-    pub async fn tick_string_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_string_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
@@ -1077,7 +1012,9 @@ where
                     77 => RealTimeVolume::Trades(base),
                     _ => panic!("The impossible occurred"),
                 };
-                wrapper.real_time_volume(req_id, volume).await;
+                if trade_filter(shared, req_id)?.is_none_or(|f| f.accepts_volume(&volume)) {
+                    wrapper.real_time_volume(req_id, volume).await;
+                }
             }
             59 => {
                 let mut divs = value.split(',');
@@ -1120,9 +1057,9 @@ where
                 wrapper.news(req_id, value).await;
             }
             t => {
-                return Err(anyhow::Error::msg(format!(
-                    "Unexpected price market data request: {t}"
-                )))
+                wrapper
+                    .other_tick(req_id, TickType::from(u32::from(t)), value)
+                    .await;
             }
         };
         Ok(())
@@ -1134,27 +1071,47 @@ where
     }
 
     #[inline]
-    pub async fn current_time_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn current_time_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 datetime @ 0: i64
         );
+        // The answers to the heartbeat are only used to measure the latency, and any answer that
+        // cannot be matched with a request is delivered to the user
+        let request = lock(&shared.current_time_requests)?.pop_front();
+        if let Some(CurrentTimeRequest::Heartbeat(sent)) = request {
+            // The platform still answers while its own connection to IBKR's servers is down, so
+            // that state is only cleared by the corresponding error message
+            if shared.health.get() != ConnectionHealth::ServerConnectionLost {
+                shared.health.set(ConnectionHealth::Healthy {
+                    latency: sent.elapsed(),
+                });
+            }
+        }
 
-        wrapper
-            .current_time(
-                NaiveDateTime::from_timestamp_opt(datetime, 0).ok_or_else(|| {
-                    anyhow::Error::msg(
-                        "Invalid datetime value encountered while parsing the UNIX timestamp!",
-                    )
-                })?,
+        let datetime = NaiveDateTime::from_timestamp_opt(datetime, 0).ok_or_else(|| {
+            anyhow::Error::msg(
+                "Invalid datetime value encountered while parsing the UNIX timestamp!",
             )
-            .await;
+        })?;
+        if !matches!(request, Some(CurrentTimeRequest::Heartbeat(_))) {
+            shared.current_times.deliver(&(), datetime);
+        }
+        wrapper.current_time(datetime).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn real_time_bars_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn real_time_bars_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
@@ -1185,34 +1142,75 @@ where
         } else {
             Bar::Ordinary(core)
         };
+        let previous = lock(&shared.real_time_bars)?.insert(req_id, core.datetime);
+        if let Some(previous) = previous {
+            let interval = chrono::Duration::seconds(REAL_TIME_BAR_SECONDS);
+            if core.datetime - previous > interval
+                && is_within_session(shared, req_id, previous, core.datetime)?
+            {
+                wrapper
+                    .missed_bars(
+                        req_id,
+                        MissedBars {
+                            from: previous + interval,
+                            to: core.datetime - interval,
+                        },
+                    )
+                    .await;
+            }
+        }
+        stream::publish(shared, req_id, || TypedEvent::Bar(bar));
         wrapper.real_time_bar(req_id, bar).await;
         Ok(())
     }
 
     #[inline]
     pub async fn fundamental_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                data @ 0: String
+        );
+        wrapper.fundamental_data(req_id, data).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn contract_data_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn contract_data_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(fields => req_id @ 2: i64);
+        end_contract_search(shared, req_id)?;
         wrapper.contract_data_end(req_id).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn open_order_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn open_order_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        if lock(&shared.open_orders)?.is_some() {
+            shared.open_orders_ended.store(true, Ordering::Release);
+            shared.open_orders_notify.notify_waiters();
+        }
         wrapper.open_order_end().await;
         Ok(())
     }
 
     #[inline]
-    pub async fn acct_download_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn acct_download_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
-            fields => account_number @ 2: String
+            fields => account_number @ 2: AccountId
         );
+        lock(&shared.account_positions_received)?.insert(account_number.clone());
         wrapper.account_download_end(account_number).await;
         Ok(())
     }
@@ -1222,8 +1220,11 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
-
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64
+        );
+        wrapper.execution_end(req_id).await;
         Ok(())
     }
 
@@ -1232,13 +1233,40 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                order_id @ 2: i64,
+                contract_id @ 0: ContractId,
+                delta @ 0: f64,
+                price @ 0: f64
+        );
+        wrapper
+            .delta_neutral_validation(
+                order_id,
+                DeltaNeutralContract {
+                    contract_id,
+                    delta,
+                    price,
+                },
+            )
+            .await;
         Ok(())
     }
 
     #[inline]
-    pub async fn tick_snapshot_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn tick_snapshot_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64
+        );
+        if let Some(quote) = lock(&shared.quotes)?.get_mut(&req_id) {
+            quote.complete = true;
+            shared.quotes_ended.notify_one();
+        }
         Ok(())
     }
 
@@ -1254,20 +1282,69 @@ where
     }
 
     #[inline]
-    pub async fn commission_report_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn commission_report_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                execution_id @ 2: String,
+                commission @ 0: f64,
+                currency @ 0: Currency,
+                realized_pnl @ 0: f64,
+                yld @ 0: f64,
+                yield_redemption_date @ 0: u32
+        );
+        let report = CommissionReport {
+            execution_id,
+            commission,
+            currency,
+            realized_pnl: (realized_pnl != f64::MAX).then_some(realized_pnl),
+            yld: (yld != f64::MAX).then_some(yld),
+            yield_redemption_date: match yield_redemption_date {
+                0 => None,
+                d => Some(
+                    NaiveDate::parse_from_str(&d.to_string(), "%Y%m%d")
+                        .with_context(|| "Invalid yield redemption date")?,
+                ),
+            },
+        };
+        lock(&shared.blotter)?.add_report(&report);
+        lock(&shared.commissions)?.add_report(report.clone());
+        tracker::publish_commission(shared, &report);
+        wrapper.commission_report(report).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn position_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn position_data_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
-                account_number @ 2: String,
+                account_number @ 2: AccountId,
                 contract_id @ 0: ContractId,
-                position @ 10: f64,
+                position @ 10: Quantity,
                 average_cost @ 0: f64
         );
+        {
+            let mut positions = lock(&shared.positions)?;
+            let key = (account_number.clone(), contract_id);
+            if position.is_zero() {
+                positions.remove(&key);
+            } else {
+                positions
+                    .entry(key)
+                    .and_modify(|holding| holding.position = position)
+                    .or_insert(Holding {
+                        position,
+                        market_price: None,
+                    });
+            }
+        }
         wrapper
             .position_summary(PositionSummary {
                 contract_id,
@@ -1280,17 +1357,26 @@ where
     }
 
     #[inline]
-    pub async fn position_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn position_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        shared.all_positions_received.store(true, Ordering::Relaxed);
         wrapper.position_end().await;
         Ok(())
     }
 
     #[inline]
-    pub async fn account_summary_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn account_summary_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
-                account_number @ 0: String,
+                account_number @ 0: AccountId,
                 tag @ 0: Tag,
                 value @ 0: String,
                 currency @ 0: String
@@ -1304,6 +1390,9 @@ where
             Tag::Leverage => TagValue::Float(Tag::Leverage, value.parse()?),
             t => TagValue::Currency(t, value.parse()?, currency.parse()?),
         };
+        account::publish_fan_out(shared, req_id, Some(&account_number), || {
+            AccountEvent::Summary(summary.clone())
+        });
         wrapper
             .account_summary(req_id, account_number, summary)
             .await;
@@ -1314,10 +1403,12 @@ where
     pub async fn account_summary_end_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields => req_id @ 2: i64
         );
+        account::publish_fan_out_end(shared, req_id);
         wrapper.account_summary_end(req_id).await;
         Ok(())
     }
@@ -1327,13 +1418,13 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn verify_completed_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -1342,7 +1433,17 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                groups @ 0: String
+        );
+        let groups = groups
+            .split('|')
+            .filter(|group| !group.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<i64>, _>>()?;
+        wrapper.display_group_list(req_id, groups).await;
         Ok(())
     }
 
@@ -1351,7 +1452,12 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                contract_info @ 0: String
+        );
+        wrapper.display_group_updated(req_id, contract_info).await;
         Ok(())
     }
 
@@ -1360,7 +1466,7 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -1369,13 +1475,35 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn position_multi_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn position_multi_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                account_number @ 0: AccountId,
+                contract_id @ 0: ContractId,
+                position @ 10: Quantity,
+                average_cost @ 0: f64,
+                model_code @ 0: String
+        );
+        let summary = PositionSummary {
+            contract_id,
+            position,
+            average_cost,
+            account_number,
+        };
+        account::publish_fan_out(shared, req_id, Some(&summary.account_number), || {
+            AccountEvent::Position(summary.clone())
+        });
+        wrapper.position_multi(req_id, summary, model_code).await;
         Ok(())
     }
 
@@ -1383,8 +1511,14 @@ where
     pub async fn position_multi_end_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64
+        );
+        account::publish_fan_out_end(shared, req_id);
+        wrapper.position_multi_end(req_id).await;
         Ok(())
     }
 
@@ -1393,7 +1527,21 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                account_number @ 0: AccountId,
+                model_code @ 0: String,
+                name @ 0: String,
+                value @ 0: String,
+                currency @ 0: String
+        );
+        let Some(attribute) = decode_account_attribute(name, value, currency)? else {
+            return Ok(());
+        };
+        wrapper
+            .account_update_multi(req_id, attribute, account_number, model_code)
+            .await;
         Ok(())
     }
 
@@ -1402,7 +1550,11 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64
+        );
+        wrapper.account_update_multi_end(req_id).await;
         Ok(())
     }
 
@@ -1410,8 +1562,52 @@ where
     pub async fn security_definition_option_parameter_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                exchange @ 0: String,
+                underlying_contract_id @ 0: ContractId,
+                trading_class @ 0: String,
+                multiplier @ 0: String,
+                expiration_count @ 0: usize
+        );
+        let expirations = (0..expiration_count)
+            .map(|_| {
+                decode_fields!(fields => expiration @ 0: String);
+                NaiveDate::parse_from_str(&expiration, "%Y%m%d")
+                    .with_context(|| "Invalid date string in option chain expiration")
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        decode_fields!(fields => strike_count @ 0: usize);
+        let strikes = (0..strike_count)
+            .map(|_| {
+                decode_fields!(fields => strike @ 0: f64);
+                Ok(strike)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // IBKR reports each trading class once per exchange, which are merged into one chain
+        if let Some(chains) = lock(&shared.option_chain_results)?.get_mut(&req_id) {
+            match chains.iter_mut().find(|chain| {
+                chain.trading_class == trading_class && chain.multiplier == multiplier
+            }) {
+                Some(chain) => chain.merge(exchange, expirations, strikes),
+                None => {
+                    let mut chain = OptionChain {
+                        underlying_contract_id,
+                        trading_class,
+                        multiplier,
+                        exchanges: Vec::new(),
+                        expirations: Vec::new(),
+                        strikes: Vec::new(),
+                    };
+                    chain.merge(exchange, expirations, strikes);
+                    chains.push(chain);
+                }
+            }
+        }
         Ok(())
     }
 
@@ -1419,26 +1615,74 @@ where
     pub async fn security_definition_option_parameter_end_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(fields => req_id @ 1: i64);
+        if let Some(chains) = lock(&shared.option_chain_results)?.remove(&req_id) {
+            shared.option_chains.deliver(&req_id, Ok(chains));
+        }
         Ok(())
     }
 
     #[inline]
     pub async fn soft_dollar_tiers_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn family_codes_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn symbol_samples_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn symbol_samples_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                count @ 0: usize
+        );
+        let samples = (0..count)
+            .map(|_| {
+                decode_fields!(
+                    fields =>
+                        contract_id @ 0: ContractId,
+                        symbol @ 0: String,
+                        security_type @ 0: String,
+                        primary_exchange @ 0: String,
+                        currency @ 0: String,
+                        derivative_count @ 0: usize
+                );
+                let derivative_security_types = (0..derivative_count)
+                    .map(|_| nth(fields, 0))
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| "Expected derivative security type but none found")?;
+                decode_fields!(
+                    fields =>
+                        description @ 0: String,
+                        issuer_id @ 0: String
+                );
+                Ok(SymbolSample {
+                    contract_id,
+                    symbol,
+                    security_type,
+                    primary_exchange: primary_exchange.parse().ok(),
+                    currency: currency.parse().ok(),
+                    derivative_security_types,
+                    description,
+                    issuer_id,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        shared
+            .matching_symbols
+            .deliver(&req_id, Ok(samples.clone()));
+        wrapper.symbol_samples(req_id, samples).await;
         Ok(())
     }
 
@@ -1447,12 +1691,16 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn tick_req_params_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_req_params_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -1460,6 +1708,7 @@ where
                 exchange_id @ 0: ExchangeId,
                 snapshot_permissions @ 0: u32
         );
+        lock(&shared.exchange_ids)?.insert(req_id, exchange_id.clone());
         wrapper
             .tick_params(req_id, min_tick, exchange_id, snapshot_permissions)
             .await;
@@ -1467,32 +1716,146 @@ where
     }
 
     #[inline]
-    pub async fn smart_components_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn smart_components_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                count @ 0: usize
+        );
+        let components = (0..count)
+            .map(|_| {
+                decode_fields!(
+                    fields =>
+                        bit_number @ 0: u32,
+                        exchange @ 0: crate::exchange::Primary,
+                        letter @ 0: char
+                );
+                Ok(SmartComponent {
+                    bit_number,
+                    exchange,
+                    letter,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        lock(&shared.component_exchanges)?.extend(
+            components
+                .iter()
+                .map(|component| (component.letter, component.exchange)),
+        );
+        shared.smart_components.deliver(&req_id, components.clone());
+        wrapper.smart_components(req_id, components).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn news_article_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn news_article_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                article_type @ 0: i64,
+                text @ 0: String
+        );
+        let article_type = match article_type {
+            0 => ArticleType::Text,
+            1 => ArticleType::Binary,
+            _ => return Err(anyhow::Error::msg("Unexpected news article type")),
+        };
+        let article = NewsArticle { article_type, text };
+        shared.news_articles.deliver(&req_id, Ok(article.clone()));
+        wrapper.news_article(req_id, article).await;
         Ok(())
     }
 
     #[inline]
     pub async fn tick_news_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                timestamp @ 0: i64,
+                provider_code @ 0: String,
+                article_id @ 0: String,
+                headline @ 0: String,
+                extra_data @ 0: String
+        );
+        let datetime = chrono::DateTime::from_timestamp_millis(timestamp)
+            .ok_or_else(|| anyhow::Error::msg("Invalid timestamp"))?
+            .naive_utc();
+        wrapper
+            .tick_news(
+                req_id,
+                NewsHeadline {
+                    datetime,
+                    provider_code,
+                    article_id,
+                    headline,
+                    extra_data,
+                },
+            )
+            .await;
         Ok(())
     }
 
     #[inline]
-    pub async fn news_providers_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn news_providers_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                count @ 1: usize
+        );
+        let providers = (0..count)
+            .map(|_| {
+                decode_fields!(
+                    fields =>
+                        code @ 0: String,
+                        name @ 0: String
+                );
+                Ok(NewsProvider { code, name })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        shared.news_providers.deliver(&(), providers.clone());
+        wrapper.news_providers(providers).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn historical_news_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn historical_news_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                datetime @ 0: String,
+                provider_code @ 0: String,
+                article_id @ 0: String,
+                headline @ 0: String
+        );
+        let headline = NewsHeadline {
+            datetime: NaiveDateTime::parse_from_str(datetime.as_str(), "%Y-%m-%d %H:%M:%S%.f")?,
+            provider_code,
+            article_id,
+            headline,
+            extra_data: String::new(),
+        };
+        if shared.historical_news.is_awaited(&req_id) {
+            lock(&shared.historical_news_results)?
+                .entry(req_id)
+                .or_default()
+                .push(headline.clone());
+        }
+        wrapper.historical_news(req_id, headline).await;
         Ok(())
     }
 
@@ -1500,24 +1863,46 @@ where
     pub async fn historical_news_end_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                has_more @ 0: i64
+        );
+        let has_more = has_more != 0;
+        let headlines = lock(&shared.historical_news_results)?
+            .remove(&req_id)
+            .unwrap_or_default();
+        shared.historical_news.deliver(
+            &req_id,
+            Ok(HistoricalNews {
+                headlines,
+                has_more,
+            }),
+        );
+        wrapper.historical_news_end(req_id, has_more).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn head_timestamp_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn head_timestamp_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 timestamp @ 0: String
         );
-        wrapper
-            .head_timestamp(
-                req_id,
-                NaiveDateTime::parse_from_str(timestamp.as_str(), "%Y%m%d-%T")?,
-            )
-            .await;
+        let timestamp = match timestamp.parse() {
+            Ok(timestamp) => NaiveDateTime::from_timestamp_opt(timestamp, 0)
+                .ok_or_else(|| anyhow::Error::msg("Invalid timestamp"))?,
+            Err(_) => NaiveDateTime::parse_from_str(timestamp.as_str(), "%Y%m%d-%T")?,
+        };
+        shared.head_timestamps.deliver(&req_id, timestamp);
+        wrapper.head_timestamp(req_id, timestamp).await;
         Ok(())
     }
 
@@ -1562,7 +1947,7 @@ where
                 volume @ 0: f64
         );
         let core = BarCore {
-            datetime: NaiveDateTime::parse_and_remainder(datetime_str.as_str(), "%Y%m%d %T")?.0,
+            datetime: decode_bar_datetime(datetime_str.as_str())?,
             open,
             high,
             low,
@@ -1587,7 +1972,7 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -1596,18 +1981,22 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn market_rule_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn pnl_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn pnl_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -1620,12 +2009,17 @@ where
             unrealized: unrealized_pnl,
             realized: realized_pnl,
         };
+        account::publish_fan_out(shared, req_id, None, || AccountEvent::Pnl(pnl));
         wrapper.pnl(req_id, pnl).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn pnl_single_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn pnl_single_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -1640,7 +2034,14 @@ where
             unrealized: unrealized_pnl,
             realized: realized_pnl,
         };
-
+        account::publish_fan_out(shared, req_id, None, || AccountEvent::PositionPnl {
+            pnl,
+            position,
+            market_value,
+        });
+        wrapper
+            .single_position_pnl(req_id, pnl, position, market_value)
+            .await;
         Ok(())
     }
 
@@ -1648,27 +2049,24 @@ where
     pub async fn historical_ticks_midpoint_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 tick_count @ 0: usize
         );
-        let mut ticks = Vec::with_capacity(tick_count);
-        for chunk in fields
-            .take(tick_count * 4)
-            .collect::<Vec<String>>()
-            .chunks_exact(4)
-        {
-            if let [time, _, price, size] = chunk {
-                ticks.push(Tick::Midpoint {
-                    datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
-                        .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
-                    price: price.parse()?,
-                });
-            }
+        let fields = fields.take(tick_count * 4).collect();
+        if offload(shared, tick_count) {
+            lock(&shared.pending_batches)?.push_back(Batch::Ticks(
+                req_id,
+                tokio::task::spawn_blocking(move || decode_midpoint_ticks(fields)),
+            ));
+        } else {
+            let ticks = decode_midpoint_ticks(fields)?;
+            deliver_historical(&shared.historical_ticks, req_id, &ticks);
+            wrapper.historical_ticks(req_id, ticks).await;
         }
-        wrapper.historical_ticks(req_id, ticks).await;
         Ok(())
     }
 
@@ -1676,30 +2074,24 @@ where
     pub async fn historical_ticks_bid_ask_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 tick_count @ 0: usize
         );
-        let mut ticks = Vec::with_capacity(tick_count);
-        for chunk in fields
-            .take(tick_count * 6)
-            .collect::<Vec<String>>()
-            .chunks_exact(6)
-        {
-            if let [time, _, bid_price, ask_price, bid_size, ask_size] = chunk {
-                ticks.push(Tick::BidAsk {
-                    datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
-                        .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
-                    bid_price: bid_price.parse()?,
-                    ask_price: ask_price.parse()?,
-                    bid_size: bid_size.parse()?,
-                    ask_size: ask_size.parse()?,
-                });
-            }
+        let fields = fields.take(tick_count * 6).collect();
+        if offload(shared, tick_count) {
+            lock(&shared.pending_batches)?.push_back(Batch::Ticks(
+                req_id,
+                tokio::task::spawn_blocking(move || decode_bid_ask_ticks(fields)),
+            ));
+        } else {
+            let ticks = decode_bid_ask_ticks(fields)?;
+            deliver_historical(&shared.historical_ticks, req_id, &ticks);
+            wrapper.historical_ticks(req_id, ticks).await;
         }
-        wrapper.historical_ticks(req_id, ticks).await;
         Ok(())
     }
 
@@ -1707,34 +2099,33 @@ where
     pub async fn historical_ticks_last_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 tick_count @ 0: usize
         );
-        let mut ticks = Vec::with_capacity(tick_count);
-        for chunk in fields
-            .take(tick_count * 6)
-            .collect::<Vec<String>>()
-            .chunks_exact(6)
-        {
-            if let [time, _, price, size, exchange, _] = chunk {
-                ticks.push(Tick::Last {
-                    datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
-                        .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
-                    price: price.parse()?,
-                    size: size.parse()?,
-                    exchange: exchange.parse()?,
-                });
-            }
+        let fields = fields.take(tick_count * 6).collect();
+        if offload(shared, tick_count) {
+            lock(&shared.pending_batches)?.push_back(Batch::Ticks(
+                req_id,
+                tokio::task::spawn_blocking(move || decode_last_ticks(fields)),
+            ));
+        } else {
+            let ticks = decode_last_ticks(fields)?;
+            deliver_historical(&shared.historical_ticks, req_id, &ticks);
+            wrapper.historical_ticks(req_id, ticks).await;
         }
-        wrapper.historical_ticks(req_id, ticks).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn tick_by_tick_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_by_tick_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -1744,19 +2135,30 @@ where
         let datetime = NaiveDateTime::from_timestamp_opt(timestamp, 0)
             .ok_or_else(|| anyhow::Error::msg("Invalid timestamp"))?;
         let tick = match tick_type {
-            1 | 2 => Tick::Last {
-                datetime,
-                price: nth(fields, 0)?.parse()?,
-                size: nth(fields, 0)?.parse()?,
-                exchange: nth(fields, 1)?.parse()?,
-            },
+            1 | 2 => {
+                decode_fields!(
+                    fields =>
+                        price @ 0: f64,
+                        size @ 0: f64,
+                        mask @ 0: u8,
+                        exchange @ 0: crate::exchange::Primary
+                );
+                Tick::Last {
+                    datetime,
+                    price,
+                    size,
+                    exchange,
+                    attributes: TickAttributes::from_last_mask(mask),
+                }
+            }
             3 => {
                 decode_fields!(
                     fields =>
                         bid_price @ 0: f64,
                         ask_price @ 0: f64,
                         bid_size @ 0: f64,
-                        ask_size @ 0: f64
+                        ask_size @ 0: f64,
+                        mask @ 0: u8
                 );
                 Tick::BidAsk {
                     datetime,
@@ -1764,6 +2166,7 @@ where
                     ask_price,
                     bid_size,
                     ask_size,
+                    attributes: TickAttributes::from_bid_ask_mask(mask),
                 }
             }
             4 => Tick::Midpoint {
@@ -1772,19 +2175,22 @@ where
             },
             _ => Err(anyhow::Error::msg("Unexpected tick type"))?,
         };
-        wrapper.live_tick(req_id, tick).await;
+        if trade_filter(shared, req_id)?.is_none_or(|f| f.accepts_tick(&tick)) {
+            stream::publish(shared, req_id, || TypedEvent::Tick(tick));
+            wrapper.live_tick(req_id, tick).await;
+        }
         Ok(())
     }
 
     #[inline]
     pub async fn order_bound_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn completed_order_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -1793,25 +2199,25 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn replace_fa_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn wsh_meta_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn wsh_event_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -1819,14 +2225,61 @@ where
     pub async fn historical_schedule_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                start @ 0: String,
+                end @ 0: String,
+                time_zone @ 0: String,
+                count @ 0: usize
+        );
+        let time_zone = time_zone
+            .parse::<crate::timezone::Tz>()
+            .map_err(|_| anyhow::Error::msg(format!("Unrecognized time zone {time_zone}")))?;
+        let mut sessions = Vec::with_capacity(count);
+        for _ in 0..count {
+            decode_fields!(
+                fields =>
+                    open @ 0: String,
+                    close @ 0: String,
+                    reference_date @ 0: String
+            );
+            sessions.push(ScheduledSession {
+                reference_date: NaiveDate::parse_from_str(&reference_date, "%Y%m%d")?,
+                open: parse_schedule_time(&open, time_zone)?,
+                close: parse_schedule_time(&close, time_zone)?,
+            });
+        }
+        let schedule = HistoricalSchedule {
+            time_zone,
+            start: parse_schedule_time(&start, time_zone)?,
+            end: parse_schedule_time(&end, time_zone)?,
+            sessions,
+        };
+        shared
+            .historical_schedules
+            .deliver(&req_id, Ok(schedule.clone()));
+        wrapper.historical_schedule(req_id, schedule).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn user_info_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn user_info_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                white_branding_id @ 0: String
+        );
+        shared
+            .user_infos
+            .deliver(&req_id, white_branding_id.clone());
+        wrapper.user_info(req_id, white_branding_id).await;
         Ok(())
     }
 
@@ -1836,6 +2289,7 @@ where
         tick_type: u16,
         value: f64,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         match tick_type {
             0 | 3 | 5 => {
@@ -1845,6 +2299,7 @@ where
                     5 => Size::Last(value),
                     _ => panic!("The impossible occurred"),
                 });
+                stream::publish(shared, req_id, || TypedEvent::Size(size));
                 wrapper.size_data(req_id, size).await;
             }
             8 | 74 => {
@@ -1853,6 +2308,7 @@ where
                     74 => Class::Delayed(value),
                     _ => panic!("The impossible occurred"),
                 };
+                stream::publish(shared, req_id, || TypedEvent::Volume(volume));
                 wrapper.volume(req_id, volume).await;
             }
             21 | 63 | 64 | 65 => {
@@ -1936,6 +2392,7 @@ where
                     71 => Size::Last(value),
                     _ => panic!("The impossible occurred"),
                 });
+                stream::publish(shared, req_id, || TypedEvent::Size(size));
                 wrapper.size_data(req_id, size).await;
             }
             101 | 102 => {
@@ -1947,9 +2404,9 @@ where
                 wrapper.ipo(req_id, ipo).await;
             }
             t => {
-                return Err(anyhow::Error::msg(format!(
-                    "Unexpected generic market data request: {t}"
-                )))
+                wrapper
+                    .other_tick(req_id, TickType::from(u32::from(t)), value.to_string())
+                    .await;
             }
         };
 
@@ -1962,7 +2419,11 @@ where
     W: Remote,
 {
     #[inline]
-    pub async fn tick_price_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_price_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
@@ -1984,6 +2445,10 @@ where
         if (price + 1.0).abs() < f64::EPSILON || (price == 0.0 && size == Some(0.0)) {
             return Ok(());
         }
+        let attributes = TickAttributes::from_price_mask(attr_mask);
+        if let Some(quote) = lock(&shared.quotes)?.get_mut(&req_id) {
+            quote.apply(tick_type, price);
+        }
 
         match tick_type {
             1 | 2 | 4 | 6 | 7 | 9 | 14 => {
@@ -2000,8 +2465,15 @@ where
                     (14, _) => (Price::Open(price), None),
                     _ => panic!("The impossible occurred"),
                 };
-                wrapper.price_data(req_id, Class::Live(price)).await;
+                stream::publish(shared, req_id, || TypedEvent::Price {
+                    price: Class::Live(price),
+                    attributes,
+                });
+                wrapper
+                    .price_data(req_id, Class::Live(price), attributes)
+                    .await;
                 if let Some(sz) = size {
+                    stream::publish(shared, req_id, || TypedEvent::Size(Class::Live(sz)));
                     wrapper.size_data(req_id, Class::Live(sz)).await;
                 }
             }
@@ -2038,8 +2510,12 @@ where
                 wrapper.yield_data(req_id, yld).await;
             }
             57 => {
+                stream::publish(shared, req_id, || TypedEvent::Price {
+                    price: Class::Live(Price::LastRthTrade(price)),
+                    attributes,
+                });
                 wrapper
-                    .price_data(req_id, Class::Live(Price::LastRthTrade(price)))
+                    .price_data(req_id, Class::Live(Price::LastRthTrade(price)), attributes)
                     .await;
             }
             66..=68 | 72 | 73 | 75 | 76 => {
@@ -2056,8 +2532,15 @@ where
                     (76, _) => (Price::Open(price), None),
                     _ => panic!("The impossible occurred"),
                 };
-                wrapper.price_data(req_id, Class::Delayed(price)).await;
+                stream::publish(shared, req_id, || TypedEvent::Price {
+                    price: Class::Delayed(price),
+                    attributes,
+                });
+                wrapper
+                    .price_data(req_id, Class::Delayed(price), attributes)
+                    .await;
                 if let Some(sz) = size {
+                    stream::publish(shared, req_id, || TypedEvent::Size(Class::Delayed(sz)));
                     wrapper.size_data(req_id, Class::Delayed(sz)).await;
                 }
             }
@@ -2076,34 +2559,81 @@ where
                 wrapper.etf_nav(req_id, nav).await;
             }
             t => {
-                return Err(anyhow::Error::msg(format!(
-                    "Unexpected price market data request: {t}"
-                )))
+                wrapper
+                    .other_tick(req_id, TickType::from(u32::from(t)), price.to_string())
+                    .await;
             }
         };
         Ok(())
     }
 
     #[inline]
-    pub async fn tick_size_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_size_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
                 tick_type @ 0: u16,
                 value @ 0: f64
         );
-        Self::decode_generic_tick_msg(req_id, tick_type, value, wrapper).await
+        if let Some(quote) = lock(&shared.quotes)?.get_mut(&req_id) {
+            quote.apply(tick_type, value);
+        }
+        Self::decode_generic_tick_msg(req_id, tick_type, value, wrapper, shared).await
     }
 
     #[inline]
-    pub async fn order_status_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn order_status_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                order_id @ 1: i64,
+                status @ 0: OrderStatus,
+                filled @ 0: Quantity,
+                remaining @ 0: Quantity,
+                average_fill_price @ 0: f64,
+                perm_id @ 0: i64,
+                parent_id @ 0: i64,
+                last_fill_price @ 0: f64,
+                client_id @ 0: i64,
+                why_held @ 0: String,
+                market_cap_price @ 0: String
+        );
+        lock(&shared.order_latency)?.status(order_id, status.is_done());
+        let update = OrderStatusUpdate {
+            order_id,
+            status,
+            filled,
+            remaining,
+            average_fill_price,
+            perm_id,
+            parent_id,
+            last_fill_price,
+            client_id,
+            why_held,
+            market_cap_price: decode_order_price(&market_cap_price).filter(|p| *p > 0.0),
+        };
+        tracker::publish_status(shared, &update);
+        if update.status.is_done() {
+            crate::client::lock_expirations(shared).remove(&update.order_id);
+        }
+        wrapper.order_status(update).await;
         Ok(())
     }
 
     #[inline]
     // todo: Implement a proper Error Enum
-    pub async fn err_msg_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn err_msg_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
@@ -2111,6 +2641,23 @@ where
                 error_string @ 0: String,
                 advanced_order_reject_json @ 0: String
         );
+        match error_code {
+            1100 => {
+                alert::set_health(shared, ConnectionHealth::ServerConnectionLost);
+                wrapper.reconnecting().await;
+            }
+            1101 => {
+                shared.health.set(ConnectionHealth::RestoredDataLost);
+                wrapper.reconnected(true).await;
+            }
+            1102 => {
+                shared.health.set(ConnectionHealth::Restored);
+                wrapper.reconnected(false).await;
+            }
+            _ => (),
+        }
+        fail_requests(shared, req_id, error_code, &error_string);
+        alert::publish_error(shared, req_id, error_code, &error_string);
         wrapper
             .error(req_id, error_code, error_string, advanced_order_reject_json)
             .await;
@@ -2118,288 +2665,152 @@ where
     }
 
     #[inline]
-    pub async fn open_order_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn open_order_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 order_id @ 1: i64,
                 contract_id @ 0: ContractId,
-                action @ 10: String,
-                quantity @ 0: f64,
+                side @ 10: OrderSide,
+                quantity @ 0: Quantity,
                 order_type @ 0: String,
                 price @ 0: String,
                 aux_price @ 0: String,
-                time_in_force @ 0: TimeInForce
+                time_in_force @ 0: String,
+                good_till_date @ 16: String
         );
+        let time_in_force = decode_time_in_force(&time_in_force, &good_till_date)?;
+        if shared.what_if_orders.is_awaited(&order_id) {
+            let state = decode_order_state(fields)
+                .with_context(|| "Expected what-if order state, found none");
+            shared
+                .what_if_orders
+                .deliver(&order_id, state.map_err(|e| Error::Parse(format!("{e:#}"))));
+            return Ok(());
+        }
+        let order = OpenOrder {
+            order_id,
+            contract_id,
+            side,
+            quantity,
+            // An unset price is sent as an empty field or as the maximum double, neither of which
+            // parses as a price
+            limit_price: price.parse().ok(),
+            aux_price: aux_price.parse().ok(),
+            time_in_force,
+            delta_neutral_contract: decode_delta_neutral_contract(fields),
+        };
+        if let Some(orders) = lock(&shared.open_orders)?.as_mut() {
+            match orders
+                .iter_mut()
+                .find(|o| o.order_id == order_id && o.contract_id == contract_id)
+            {
+                Some(existing) => *existing = order,
+                None => orders.push(order),
+            }
+        }
+        tracker::publish_open_order(shared, order);
+        wrapper.open_order(order).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn acct_value_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn acct_value_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 name @ 2: String,
                 value @ 0: String,
                 currency @ 0: String,
-                account_number @ 0: String
-        );
-        let attribute = match name.as_str() {
-            "AccountCode" => account::Attribute::AccountCode(value),
-            "AccountOrGroup" => match value.as_str() {
-                "All" => account::Attribute::AccountOrGroup(account::Group::All, currency.parse()?),
-                name => account::Attribute::AccountOrGroup(
-                    account::Group::Name(name.to_owned()),
-                    currency.parse()?,
-                ),
-            },
-            "AccountReady" => account::Attribute::AccountReady(value.parse()?),
-            "AccountType" => account::Attribute::AccountType(value),
-            expand_seg_variants!("AccruedCash") => account::Attribute::AccruedCash(
-                impl_seg_variants!("AccruedCash", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("AccruedDividend") => account::Attribute::AccruedDividend(
-                impl_seg_variants!("AccruedDividend", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("AvailableFunds") => account::Attribute::AvailableFunds(
-                impl_seg_variants!("AvailableFunds", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("Billable") => account::Attribute::Billable(
-                impl_seg_variants!("Billable", name, value),
-                currency.parse()?,
-            ),
-            "BuyingPower" => account::Attribute::BuyingPower(value.parse()?, currency.parse()?),
-            "CashBalance" => account::Attribute::CashBalance(value.parse()?, currency.parse()?),
-            expand_seg_variants!("ColumnPrio") => {
-                account::Attribute::ColumnPrio(impl_seg_variants!("ColumnPrio", name, value))
-            }
-            "CorporateBondValue" => {
-                account::Attribute::CorporateBondValue(value.parse()?, currency.parse()?)
-            }
-            "Cryptocurrency" => {
-                account::Attribute::Cryptocurrency(value.parse()?, currency.parse()?)
-            }
-            "Currency" => account::Attribute::Currency(value.parse()?),
-            "Cushion" => account::Attribute::Cushion(value.parse()?),
-            "DayTradesRemaining" => account::Attribute::DayTradesRemaining(value.parse()?),
-            "DayTradesRemainingT+1" => account::Attribute::DayTradesRemainingTPlus1(value.parse()?),
-            "DayTradesRemainingT+2" => account::Attribute::DayTradesRemainingTPlus2(value.parse()?),
-            "DayTradesRemainingT+3" => account::Attribute::DayTradesRemainingTPlus3(value.parse()?),
-            "DayTradesRemainingT+4" => account::Attribute::DayTradesRemainingTPlus4(value.parse()?),
-            "DayTradingStatus-S" => account::Attribute::DayTradingStatus(value),
-            expand_seg_variants!("EquityWithLoanValue") => account::Attribute::EquityWithLoanValue(
-                impl_seg_variants!("EquityWithLoanValue", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("ExcessLiquidity") => account::Attribute::ExcessLiquidity(
-                impl_seg_variants!("ExcessLiquidity", name, value),
-                currency.parse()?,
-            ),
-            "ExchangeRate" => account::Attribute::ExchangeRate(value.parse()?, currency.parse()?),
-            expand_seg_variants!("FullAvailableFunds") => account::Attribute::FullAvailableFunds(
-                impl_seg_variants!("FullAvailableFunds", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("FullExcessLiquidity") => account::Attribute::FullExcessLiquidity(
-                impl_seg_variants!("FullExcessLiquidity", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("FullInitMarginReq") => account::Attribute::FullInitMarginReq(
-                impl_seg_variants!("FullInitMarginReq", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("FullMaintMarginReq") => {
-                account::Attribute::FullMaintenanceMarginReq(
-                    impl_seg_variants!("FullMaintMarginReq", name, value),
-                    currency.parse()?,
-                )
-            }
-            "FundValue" => account::Attribute::FundValue(value.parse()?, currency.parse()?),
-            "FutureOptionValue" => {
-                account::Attribute::FutureOptionValue(value.parse()?, currency.parse()?)
-            }
-            "FuturesPNL" => account::Attribute::FuturesPnl(value.parse()?, currency.parse()?),
-            "FxCashBalance" => account::Attribute::FxCashBalance(value.parse()?, currency.parse()?),
-            "GrossPositionValue" => {
-                account::Attribute::GrossPositionValue(value.parse()?, currency.parse()?)
-            }
-            "GrossPositionValue-S" => {
-                account::Attribute::GrossPositionValueSecurity(value.parse()?, currency.parse()?)
-            }
-            expand_seg_variants!("Guarantee") => account::Attribute::Guarantee(
-                impl_seg_variants!("Guarantee", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("IndianStockHaircut") => account::Attribute::IndianStockHaircut(
-                impl_seg_variants!("IndianStockHaircut", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("InitMarginReq") => account::Attribute::InitMarginReq(
-                impl_seg_variants!("InitMarginReq", name, value),
-                currency.parse()?,
-            ),
-            "IssuerOptionValue" => {
-                account::Attribute::IssuerOptionValue(value.parse()?, currency.parse()?)
-            }
-            "Leverage-S" => account::Attribute::LeverageSecurity(value.parse()?),
-            expand_seg_variants!("LookAheadAvailableFunds") => {
-                account::Attribute::LookAheadAvailableFunds(
-                    impl_seg_variants!("LookAheadAvailableFunds", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("LookAheadExcessLiquidity") => {
-                account::Attribute::LookAheadExcessLiquidity(
-                    impl_seg_variants!("LookAheadExcessLiquidity", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("LookAheadInitMarginReq") => {
-                account::Attribute::LookAheadInitMarginReq(
-                    impl_seg_variants!("LookAheadInitMarginReq", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("LookAheadMaintMarginReq") => {
-                account::Attribute::LookAheadMaintenanceMarginReq(
-                    impl_seg_variants!("LookAheadMaintMarginReq", name, value),
-                    currency.parse()?,
-                )
-            }
-            "LookAheadNextChange" => account::Attribute::LookAheadNextChange(value.parse()?),
-            expand_seg_variants!("MaintMarginReq") => account::Attribute::MaintenanceMarginReq(
-                impl_seg_variants!("MaintMarginReq", name, value),
-                currency.parse()?,
-            ),
-            "MoneyMarketFundValue" => {
-                account::Attribute::MoneyMarketFundValue(value.parse()?, currency.parse()?)
-            }
-            "MutualFundValue" => {
-                account::Attribute::MutualFundValue(value.parse()?, currency.parse()?)
-            }
-            "NLVAndMarginInReview" => account::Attribute::NlvAndMarginInReview(value.parse()?),
-            "NetDividend" => account::Attribute::NetDividend(value.parse()?, currency.parse()?),
-            expand_seg_variants!("NetLiquidation") => account::Attribute::NetLiquidation(
-                impl_seg_variants!("NetLiquidation", name, value),
-                currency.parse()?,
-            ),
-            "NetLiquidationByCurrency" => {
-                account::Attribute::NetLiquidationByCurrency(value.parse()?, currency.parse()?)
-            }
-            "NetLiquidationUncertainty" => {
-                account::Attribute::NetLiquidationUncertainty(value.parse()?, currency.parse()?)
-            }
-            "OptionMarketValue" => {
-                account::Attribute::OptionMarketValue(value.parse()?, currency.parse()?)
-            }
-            expand_seg_variants!("PASharesValue") => account::Attribute::PaSharesValue(
-                impl_seg_variants!("PASharesValue", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("PhysicalCertificateValue") => {
-                account::Attribute::PhysicalCertificateValue(
-                    impl_seg_variants!("PhysicalCertificateValue", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("PostExpirationExcess") => {
-                account::Attribute::PostExpirationExcess(
-                    impl_seg_variants!("PostExpirationExcess", name, value),
-                    currency.parse()?,
-                )
-            }
-            expand_seg_variants!("PostExpirationMargin") => {
-                account::Attribute::PostExpirationMargin(
-                    impl_seg_variants!("PostExpirationMargin", name, value),
-                    currency.parse()?,
-                )
-            }
-            "PreviousDayEquityWithLoanValue" => account::Attribute::PreviousDayEquityWithLoanValue(
-                value.parse()?,
-                currency.parse()?,
-            ),
-            "PreviousDayEquityWithLoanValue-S" => {
-                account::Attribute::PreviousDayEquityWithLoanValueSecurity(
-                    value.parse()?,
-                    currency.parse()?,
-                )
-            }
-            "RealCurrency" => account::Attribute::RealCurrency(currency.parse()?),
-            "RealizedPnL" => account::Attribute::RealizedPnL(value.parse()?, currency.parse()?),
-            "RegTEquity" => account::Attribute::RegTEquity(value.parse()?, currency.parse()?),
-            "RegTEquity-S" => {
-                account::Attribute::RegTEquitySecurity(value.parse()?, currency.parse()?)
-            }
-            "RegTMargin" => account::Attribute::RegTMargin(value.parse()?, currency.parse()?),
-            "RegTMargin-S" => {
-                account::Attribute::RegTMarginSecurity(value.parse()?, currency.parse()?)
-            }
-            "SMA" => account::Attribute::Sma(value.parse()?, currency.parse()?),
-            "SMA-S" => account::Attribute::SmaSecurity(value.parse()?, currency.parse()?),
-            "StockMarketValue" => {
-                account::Attribute::StockMarketValue(value.parse()?, currency.parse()?)
-            }
-            "TBillValue" => account::Attribute::TBillValue(value.parse()?, currency.parse()?),
-            "TBondValue" => account::Attribute::TBondValue(value.parse()?, currency.parse()?),
-            "TotalCashBalance" => {
-                account::Attribute::TotalCashBalance(value.parse()?, currency.parse()?)
-            }
-            expand_seg_variants!("TotalCashValue") => account::Attribute::TotalCashValue(
-                impl_seg_variants!("TotalCashValue", name, value),
-                currency.parse()?,
-            ),
-            expand_seg_variants!("TotalDebitCardPendingCharges") => {
-                account::Attribute::TotalDebitCardPendingCharges(
-                    impl_seg_variants!("TotalDebitCardPendingCharges", name, value),
-                    currency.parse()?,
-                )
-            }
-            "TradingType-S" => account::Attribute::TradingTypeSecurity(value),
-            "UnrealizedPnL" => account::Attribute::UnrealizedPnL(value.parse()?, currency.parse()?),
-            "WarrantValue" => account::Attribute::WarrantValue(value.parse()?, currency.parse()?),
-            "WhatIfPMEnabled" => account::Attribute::WhatIfPMEnabled(value.parse()?),
-            expand_seg_variants!("SegmentTitle") => {
-                if name.ends_with('C') || name.ends_with('P') || name.ends_with('S') {
-                    return Ok(());
-                }
-                return Err(anyhow::Error::msg("Unexpected segment title encountered.  This may mandate an API update: currently-supported values are C, P, and S as outlined in the account::Segment type."));
-            }
-            _ => {
-                return Err(anyhow::Error::msg(format!(
-                    "Invalid account attribute encountered: {name}"
-                )))
-            }
+                account_number @ 0: AccountId
+        );
+        let Some(attribute) = decode_account_attribute(name, value, currency)? else {
+            return Ok(());
         };
+        if let account::Attribute::CashBalance(value, account::Denomination::Specific(currency)) =
+            attribute
+        {
+            lock(&shared.cash_balances)?.insert((account_number.clone(), currency), value);
+        }
+        if let Some((key, update)) = attribute.value_update() {
+            lock(&shared.account_values)?
+                .entry((account_number.clone(), key))
+                .or_insert_with(|| tokio::sync::watch::channel(None).0)
+                .send_if_modified(|current| {
+                    let modified = *current != Some(update);
+                    *current = Some(update);
+                    modified
+                });
+        }
         wrapper.account_attribute(attribute, account_number).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn portfolio_value_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn portfolio_value_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 contract_id @ 2: ContractId,
-                position @ 10: f64,
+                currency @ 7: String,
+                position @ 2: Quantity,
                 market_price @ 0: f64,
                 market_value @ 0: f64,
                 average_cost @ 0: f64,
                 unrealized_pnl @ 0: f64,
                 realized_pnl @ 0: f64,
-                account_name @ 0: String
+                account_name @ 0: AccountId
         );
-        wrapper
-            .position(Position {
-                contract_id,
-                position,
-                market_price,
-                market_value,
-                average_cost,
-                unrealized_pnl,
-                realized_pnl,
-                account_number: account_name,
-            })
-            .await;
+        if let Ok(currency) = currency.parse::<Currency>() {
+            let mut portfolio = lock(&shared.portfolio)?;
+            if position.is_zero() {
+                portfolio.remove(&(account_name.clone(), contract_id));
+            } else {
+                portfolio.insert(
+                    (account_name.clone(), contract_id),
+                    (currency, market_value),
+                );
+            }
+        }
+        {
+            let mut positions = lock(&shared.positions)?;
+            if position.is_zero() {
+                positions.remove(&(account_name.clone(), contract_id));
+            } else {
+                positions.insert(
+                    (account_name.clone(), contract_id),
+                    Holding {
+                        position,
+                        market_price: Some(market_price),
+                    },
+                );
+            }
+        }
+        let position = Position {
+            contract_id,
+            position,
+            market_price,
+            market_value,
+            average_cost,
+            unrealized_pnl,
+            realized_pnl,
+            account_number: account_name,
+        };
+        if let Some(tx) =
+            lock(&shared.position_values)?.get(&(position.account_number.clone(), contract_id))
+        {
+            tx.send_replace(Some(position.clone()));
+        }
+        wrapper.position(position).await;
         Ok(())
     }
 
@@ -2420,9 +2831,13 @@ where
     pub(crate) async fn next_valid_id_msg(
         fields: &mut Fields,
         wrapper: &mut W,
-        tx: &mut Tx,
-        rx: &mut Rx,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                order_id @ 2: i64
+        );
+        shared.next_valid_ids.deliver(&(), order_id);
         Ok(())
     }
 
@@ -2431,212 +2846,96 @@ where
     pub(crate) async fn contract_data_msg(
         fields: &mut Fields,
         wrapper: &mut W,
-        tx: &mut Tx,
-        rx: &mut Rx,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        decode_fields!(
-            fields =>
-                req_id @ 1: i64,
-                symbol @ 0: String,
-                sec_type @ 0: String,
-                expiration_date @ 0: String,
-                strike @ 0: f64,
-                class @ 0: String,
-                exchange @ 0: Routing,
-                currency @ 0: Currency,
-                local_symbol @ 0: String,
-                trading_class @ 1: String,
-                contract_id @ 0: ContractId,
-                min_tick @ 0: f64,
-                multiplier @ 0: String,
-                order_types @ 0: String,
-                valid_exchanges @ 0: String,
-                underlying_contract_id @ 1: ContractId,
-                long_name @ 0: String,
-                primary_exchange @ 0: String,
-                sector @ 1: String,
-                security_id_count @ 7: usize
-        );
-
-        let order_types = order_types
-            .split(',')
-            .map(std::borrow::ToOwned::to_owned)
-            .collect();
-        let valid_exchanges = valid_exchanges
-            .split(',')
-            .map(str::parse)
-            .collect::<Result<Vec<Routing>, _>>()
-            .with_context(|| "Invalid exchange in valid_exchanges")?;
-        let security_ids = (0..security_id_count)
-            .map(|_| {
-                match nth(fields, 0)
-                    .with_context(|| "Expected number of security_ids but none found")?
-                    .to_uppercase()
-                    .as_str()
-                {
-                    "CUSIP" => Ok(SecurityId::Cusip(
-                        nth(fields, 0).with_context(|| "Expected CUSIP but none found")?,
-                    )),
-                    "SEDOL" => Ok(SecurityId::Sedol(
-                        nth(fields, 0).with_context(|| "Expected SEDOL but none found")?,
-                    )),
-                    "ISIN" => Ok(SecurityId::Isin(
-                        nth(fields, 0).with_context(|| "Expected ISIN but none found")?,
-                    )),
-                    "RIC" => Ok(SecurityId::Ric(
-                        nth(fields, 0).with_context(|| "Expected RIC but none found")?,
-                    )),
-                    _ => Err(anyhow::Error::msg(
-                        "Invalid security_id type found in STK contract_data_msg",
-                    )),
-                }
-            })
-            .collect::<Result<Vec<SecurityId>, _>>()?;
-
-        if let Ok(ToWrapper::ContractQuery((con_id_client, req_id_client))) = rx.try_recv() {
-            if con_id_client != contract_id {
-                return Err(anyhow::Error::msg("Unexpected contract ID"));
-            }
-            if req_id_client != req_id {
-                return Err(anyhow::Error::msg("Unexpected request ID"));
-            }
-            let contract = match sec_type.as_str() {
-                "STK" => Some(Contract::Stock(Stock {
-                    symbol,
-                    exchange,
-                    currency,
-                    local_symbol,
-                    trading_class,
-                    contract_id,
-                    min_tick,
-                    primary_exchange: primary_exchange
-                        .parse()
-                        .with_context(|| "Invalid exchange in STK primary_exchange")?,
-                    long_name,
-                    sector,
-                    order_types,
-                    valid_exchanges,
-                    security_ids,
-                    stock_type: nth(fields, 5)
-                        .with_context(|| "Expected stock_type but none found")?,
-                })),
-                "OPT" => {
-                    let inner = SecOptionInner {
-                        contract_id,
-                        min_tick,
-                        symbol,
-                        exchange,
-                        strike,
-                        multiplier: multiplier
-                            .parse()
-                            .with_context(|| "Invalid multiplier in OPT multiplier")?,
-                        expiration_date: NaiveDate::parse_and_remainder(
-                            expiration_date.as_str(),
-                            "%Y%m%d",
-                        )
-                        .with_context(|| "Invalid date string in OPT expiration_date")?
-                        .0,
-                        underlying_contract_id,
-                        sector,
-                        trading_class,
-                        currency,
-                        local_symbol,
-                        long_name,
-                        order_types,
-                        valid_exchanges,
-                    };
-                    match class.as_str() {
-                        "C" => Some(Contract::SecOption(SecOption::Call(inner))),
-                        "P" => Some(Contract::SecOption(SecOption::Put(inner))),
-                        _ => return Err(anyhow::Error::msg("Unexpected option class")),
-                    }
-                }
-                "CRYPTO" => Some(Contract::Crypto(Crypto {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    trading_class,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                "CASH" => Some(Contract::Forex(Forex {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    exchange,
-                    trading_class,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                "IND" => Some(Contract::Index(Index {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    exchange,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                "FUT" => Some(Contract::SecFuture(SecFuture {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    exchange,
-                    multiplier: multiplier
-                        .parse()
-                        .with_context(|| "Invalid multiplier in FUT multiplier")?,
-                    expiration_date: NaiveDate::parse_and_remainder(
-                        expiration_date.as_str(),
-                        "%Y%m%d",
+        if let Some(contract) = decode_contract_no_wrapper(fields, shared).await? {
+            for (exec_req_id, execution) in take_unresolved(shared, contract.get_contract_id())? {
+                wrapper
+                    .resolved_execution(
+                        exec_req_id,
+                        ResolvedExecution {
+                            execution,
+                            contract: contract.clone(),
+                        },
                     )
-                    .with_context(|| "Invalid date string in OPT expiration_date")?
-                    .0,
-                    trading_class,
-                    underlying_contract_id,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                "CMDTY" => Some(Contract::Commodity(Commodity {
-                    contract_id,
-                    min_tick,
-                    symbol,
-                    exchange,
-                    trading_class,
-                    currency,
-                    local_symbol,
-                    long_name,
-                    order_types,
-                    valid_exchanges,
-                })),
-                _ => todo!(),
-            };
+                    .await;
+            }
+        }
+        Ok(())
+    }
 
-            tx.send(ToClient::NewContract(
-                contract.ok_or_else(|| anyhow::Error::msg("No contract was created"))?,
-            ))
-            .await
-            .with_context(|| "Failure when sending contract")?;
+    #[inline]
+    pub async fn execution_data_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                order_id @ 0: i64,
+                contract_id @ 0: ContractId,
+                symbol @ 0: String,
+                execution_id @ 9: String,
+                datetime @ 0: String,
+                account_number @ 0: AccountId,
+                exchange @ 0: String,
+                side @ 0: OrderSide,
+                quantity @ 0: Quantity,
+                price @ 0: Price,
+                perm_id @ 0: i64,
+                client_id @ 0: i64,
+                liquidation @ 0: i64,
+                cumulative_quantity @ 0: Quantity,
+                average_price @ 0: Price,
+                order_reference @ 0: String
+        );
+        let execution = Execution {
+            execution_id,
+            order_id,
+            contract_id,
+            symbol,
+            datetime: decode_execution_datetime(&datetime)?,
+            account_number,
+            exchange,
+            side,
+            quantity,
+            price,
+            perm_id,
+            client_id,
+            liquidation: liquidation != 0,
+            cumulative_quantity,
+            average_price,
+            order_reference,
+        };
+        lock(&shared.commissions)?.add_execution(&execution);
+        lock(&shared.blotter)?.add_execution(&execution);
+        tracker::publish_execution(shared, &execution);
+        let resolver = lock(&shared.execution_resolver)?.clone();
+        if let Some(resolver) = resolver {
+            let contract = lock(&shared.contracts)?
+                .get(&execution.contract_id)
+                .cloned();
+            match contract {
+                Some(contract) => {
+                    wrapper
+                        .resolved_execution(
+                            req_id,
+                            ResolvedExecution {
+                                execution,
+                                contract,
+                            },
+                        )
+                        .await;
+                }
+                None => hold_unresolved(shared, &resolver, req_id, execution)?,
+            }
+        } else {
+            wrapper.execution(req_id, execution).await;
         }
         Ok(())
     }
 
-    #[inline]
-    pub async fn execution_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
-        Ok(())
-    }
-
     #[inline]
     pub async fn market_depth_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
         decode_fields!(
@@ -2693,7 +2992,27 @@ where
 
     #[inline]
     pub async fn news_bulletins_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                id @ 2: i64,
+                kind @ 0: i64,
+                message @ 0: String,
+                exchange @ 0: String
+        );
+        let kind = match kind {
+            1 => BulletinKind::Regular,
+            2 => BulletinKind::ExchangeUnavailable,
+            3 => BulletinKind::ExchangeAvailable,
+            _ => return Err(anyhow::Error::msg("Unexpected news bulletin type")),
+        };
+        wrapper
+            .news_bulletin(NewsBulletin {
+                id,
+                kind,
+                message,
+                exchange,
+            })
+            .await;
         Ok(())
     }
 
@@ -2702,20 +3021,22 @@ where
     pub(crate) async fn managed_accts_msg(
         fields: &mut Fields,
         wrapper: &mut W,
-        tx: &mut Tx,
-        rx: &mut Rx,
     ) -> anyhow::Result<()> {
         Ok(())
     }
 
     #[inline]
     pub async fn receive_fa_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn historical_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn historical_data_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -2723,32 +3044,40 @@ where
                 end_date_str @ 0: String,
                 count @ 0: usize
         );
-        let mut bars = Vec::with_capacity(count);
-        for chunk in fields.collect::<Vec<String>>().chunks(8) {
-            if let [date, open, high, low, close, volume, wap, trade_count] = chunk {
-                let core = BarCore {
-                    datetime: NaiveDateTime::parse_and_remainder(date, "%Y%m%d %T")?.0,
-                    open: open.parse()?,
-                    high: high.parse()?,
-                    low: low.parse()?,
-                    close: close.parse()?,
-                };
-                let (volume, wap, trade_count) =
-                    (volume.parse()?, wap.parse()?, trade_count.parse::<i64>()?);
-                let bar = if volume > 0. && wap > 0. && trade_count > 0 {
-                    Bar::Trades {
-                        bar: core,
-                        volume,
-                        wap,
-                        trade_count: trade_count.try_into()?,
-                    }
-                } else {
-                    Bar::Ordinary(core)
-                };
-                bars.push(bar);
+        let fields = fields.collect();
+        if offload(shared, count) {
+            lock(&shared.pending_batches)?.push_back(Batch::Bars(
+                req_id,
+                tokio::task::spawn_blocking(move || decode_bars(fields)),
+            ));
+        } else {
+            let bars = decode_bars(fields)?;
+            deliver_historical(&shared.historical_bars, req_id, &bars);
+            wrapper.historical_bars(req_id, bars).await;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn deliver_batches(wrapper: &mut W, shared: &Shared) -> anyhow::Result<()> {
+        while let Some(batch) = next_finished_batch(shared)? {
+            match batch {
+                Batch::Bars(req_id, handle) => {
+                    let bars = handle
+                        .await
+                        .with_context(|| "Failure in blocking decode task")??;
+                    deliver_historical(&shared.historical_bars, req_id, &bars);
+                    wrapper.historical_bars(req_id, bars).await;
+                }
+                Batch::Ticks(req_id, handle) => {
+                    let ticks = handle
+                        .await
+                        .with_context(|| "Failure in blocking decode task")??;
+                    deliver_historical(&shared.historical_ticks, req_id, &ticks);
+                    wrapper.historical_ticks(req_id, ticks).await;
+                }
             }
         }
-        wrapper.historical_bars(req_id, bars).await;
         Ok(())
     }
 
@@ -2756,9 +3085,9 @@ where
     pub async fn bond_contract_data_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
-        Ok(())
+        decode_bond_contract(fields, shared)
     }
 
     #[inline]
@@ -2766,13 +3095,53 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn scanner_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                number_of_rows @ 0: usize
+        );
+        let rows = (0..number_of_rows)
+            .map(|_| {
+                decode_fields!(
+                    fields =>
+                        rank @ 0: u32,
+                        contract_id @ 0: ContractId,
+                        symbol @ 0: String,
+                        security_type @ 0: String,
+                        exchange @ 3: Routing,
+                        currency @ 0: Currency,
+                        local_symbol @ 0: String,
+                        market_name @ 0: String,
+                        trading_class @ 0: String,
+                        distance @ 0: String,
+                        benchmark @ 0: String,
+                        projection @ 0: String,
+                        legs @ 0: String
+                );
+                Ok(scanner::Row {
+                    rank,
+                    contract_id,
+                    symbol,
+                    security_type,
+                    exchange,
+                    currency,
+                    local_symbol,
+                    market_name,
+                    trading_class,
+                    distance,
+                    benchmark,
+                    projection,
+                    legs,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        wrapper.scanner_data(req_id, rows).await;
         Ok(())
     }
 
@@ -2838,18 +3207,26 @@ where
     }
 
     #[inline]
-    pub async fn tick_generic_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_generic_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
                 tick_type @ 0: u16,
                 value @ 0: f64
         );
-        Self::decode_generic_tick_msg(req_id, tick_type, value, wrapper).await
+        Self::decode_generic_tick_msg(req_id, tick_type, value, wrapper, shared).await
     }
 
     #[inline]
-    pub async fn tick_string_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_string_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
@@ -2943,7 +3320,9 @@ where
                     77 => RealTimeVolume::Trades(base),
                     _ => panic!("The impossible occurred"),
                 };
-                wrapper.real_time_volume(req_id, volume).await;
+                if trade_filter(shared, req_id)?.is_none_or(|f| f.accepts_volume(&volume)) {
+                    wrapper.real_time_volume(req_id, volume).await;
+                }
             }
             59 => {
                 let mut divs = value.split(',');
@@ -2986,9 +3365,9 @@ where
                 wrapper.news(req_id, value).await;
             }
             t => {
-                return Err(anyhow::Error::msg(format!(
-                    "Unexpected price market data request: {t}"
-                )))
+                wrapper
+                    .other_tick(req_id, TickType::from(u32::from(t)), value)
+                    .await;
             }
         };
         Ok(())
@@ -3000,27 +3379,47 @@ where
     }
 
     #[inline]
-    pub async fn current_time_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn current_time_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 datetime @ 0: i64
         );
+        // The answers to the heartbeat are only used to measure the latency, and any answer that
+        // cannot be matched with a request is delivered to the user
+        let request = lock(&shared.current_time_requests)?.pop_front();
+        if let Some(CurrentTimeRequest::Heartbeat(sent)) = request {
+            // The platform still answers while its own connection to IBKR's servers is down, so
+            // that state is only cleared by the corresponding error message
+            if shared.health.get() != ConnectionHealth::ServerConnectionLost {
+                shared.health.set(ConnectionHealth::Healthy {
+                    latency: sent.elapsed(),
+                });
+            }
+        }
 
-        wrapper
-            .current_time(
-                NaiveDateTime::from_timestamp_opt(datetime, 0).ok_or_else(|| {
-                    anyhow::Error::msg(
-                        "Invalid datetime value encountered while parsing the UNIX timestamp!",
-                    )
-                })?,
+        let datetime = NaiveDateTime::from_timestamp_opt(datetime, 0).ok_or_else(|| {
+            anyhow::Error::msg(
+                "Invalid datetime value encountered while parsing the UNIX timestamp!",
             )
-            .await;
+        })?;
+        if !matches!(request, Some(CurrentTimeRequest::Heartbeat(_))) {
+            shared.current_times.deliver(&(), datetime);
+        }
+        wrapper.current_time(datetime).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn real_time_bars_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn real_time_bars_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
@@ -3051,34 +3450,75 @@ where
         } else {
             Bar::Ordinary(core)
         };
+        let previous = lock(&shared.real_time_bars)?.insert(req_id, core.datetime);
+        if let Some(previous) = previous {
+            let interval = chrono::Duration::seconds(REAL_TIME_BAR_SECONDS);
+            if core.datetime - previous > interval
+                && is_within_session(shared, req_id, previous, core.datetime)?
+            {
+                wrapper
+                    .missed_bars(
+                        req_id,
+                        MissedBars {
+                            from: previous + interval,
+                            to: core.datetime - interval,
+                        },
+                    )
+                    .await;
+            }
+        }
+        stream::publish(shared, req_id, || TypedEvent::Bar(bar));
         wrapper.real_time_bar(req_id, bar).await;
         Ok(())
     }
 
     #[inline]
     pub async fn fundamental_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                data @ 0: String
+        );
+        wrapper.fundamental_data(req_id, data).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn contract_data_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn contract_data_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(fields => req_id @ 2: i64);
+        end_contract_search(shared, req_id)?;
         wrapper.contract_data_end(req_id).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn open_order_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn open_order_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        if lock(&shared.open_orders)?.is_some() {
+            shared.open_orders_ended.store(true, Ordering::Release);
+            shared.open_orders_notify.notify_waiters();
+        }
         wrapper.open_order_end().await;
         Ok(())
     }
 
     #[inline]
-    pub async fn acct_download_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn acct_download_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
-            fields => account_number @ 2: String
+            fields => account_number @ 2: AccountId
         );
+        lock(&shared.account_positions_received)?.insert(account_number.clone());
         wrapper.account_download_end(account_number).await;
         Ok(())
     }
@@ -3088,8 +3528,11 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
-
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64
+        );
+        wrapper.execution_end(req_id).await;
         Ok(())
     }
 
@@ -3098,13 +3541,40 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                order_id @ 2: i64,
+                contract_id @ 0: ContractId,
+                delta @ 0: f64,
+                price @ 0: f64
+        );
+        wrapper
+            .delta_neutral_validation(
+                order_id,
+                DeltaNeutralContract {
+                    contract_id,
+                    delta,
+                    price,
+                },
+            )
+            .await;
         Ok(())
     }
 
     #[inline]
-    pub async fn tick_snapshot_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn tick_snapshot_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64
+        );
+        if let Some(quote) = lock(&shared.quotes)?.get_mut(&req_id) {
+            quote.complete = true;
+            shared.quotes_ended.notify_one();
+        }
         Ok(())
     }
 
@@ -3120,20 +3590,69 @@ where
     }
 
     #[inline]
-    pub async fn commission_report_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn commission_report_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                execution_id @ 2: String,
+                commission @ 0: f64,
+                currency @ 0: Currency,
+                realized_pnl @ 0: f64,
+                yld @ 0: f64,
+                yield_redemption_date @ 0: u32
+        );
+        let report = CommissionReport {
+            execution_id,
+            commission,
+            currency,
+            realized_pnl: (realized_pnl != f64::MAX).then_some(realized_pnl),
+            yld: (yld != f64::MAX).then_some(yld),
+            yield_redemption_date: match yield_redemption_date {
+                0 => None,
+                d => Some(
+                    NaiveDate::parse_from_str(&d.to_string(), "%Y%m%d")
+                        .with_context(|| "Invalid yield redemption date")?,
+                ),
+            },
+        };
+        lock(&shared.blotter)?.add_report(&report);
+        lock(&shared.commissions)?.add_report(report.clone());
+        tracker::publish_commission(shared, &report);
+        wrapper.commission_report(report).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn position_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn position_data_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
-                account_number @ 2: String,
+                account_number @ 2: AccountId,
                 contract_id @ 0: ContractId,
-                position @ 10: f64,
+                position @ 10: Quantity,
                 average_cost @ 0: f64
         );
+        {
+            let mut positions = lock(&shared.positions)?;
+            let key = (account_number.clone(), contract_id);
+            if position.is_zero() {
+                positions.remove(&key);
+            } else {
+                positions
+                    .entry(key)
+                    .and_modify(|holding| holding.position = position)
+                    .or_insert(Holding {
+                        position,
+                        market_price: None,
+                    });
+            }
+        }
         wrapper
             .position_summary(PositionSummary {
                 contract_id,
@@ -3146,17 +3665,26 @@ where
     }
 
     #[inline]
-    pub async fn position_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn position_end_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        shared.all_positions_received.store(true, Ordering::Relaxed);
         wrapper.position_end().await;
         Ok(())
     }
 
     #[inline]
-    pub async fn account_summary_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn account_summary_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 2: i64,
-                account_number @ 0: String,
+                account_number @ 0: AccountId,
                 tag @ 0: Tag,
                 value @ 0: String,
                 currency @ 0: String
@@ -3170,6 +3698,9 @@ where
             Tag::Leverage => TagValue::Float(Tag::Leverage, value.parse()?),
             t => TagValue::Currency(t, value.parse()?, currency.parse()?),
         };
+        account::publish_fan_out(shared, req_id, Some(&account_number), || {
+            AccountEvent::Summary(summary.clone())
+        });
         wrapper
             .account_summary(req_id, account_number, summary)
             .await;
@@ -3180,10 +3711,12 @@ where
     pub async fn account_summary_end_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields => req_id @ 2: i64
         );
+        account::publish_fan_out_end(shared, req_id);
         wrapper.account_summary_end(req_id).await;
         Ok(())
     }
@@ -3193,13 +3726,13 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn verify_completed_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -3208,7 +3741,17 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                groups @ 0: String
+        );
+        let groups = groups
+            .split('|')
+            .filter(|group| !group.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<i64>, _>>()?;
+        wrapper.display_group_list(req_id, groups).await;
         Ok(())
     }
 
@@ -3217,7 +3760,12 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                contract_info @ 0: String
+        );
+        wrapper.display_group_updated(req_id, contract_info).await;
         Ok(())
     }
 
@@ -3226,7 +3774,7 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -3235,13 +3783,35 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn position_multi_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn position_multi_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                account_number @ 0: AccountId,
+                contract_id @ 0: ContractId,
+                position @ 10: Quantity,
+                average_cost @ 0: f64,
+                model_code @ 0: String
+        );
+        let summary = PositionSummary {
+            contract_id,
+            position,
+            average_cost,
+            account_number,
+        };
+        account::publish_fan_out(shared, req_id, Some(&summary.account_number), || {
+            AccountEvent::Position(summary.clone())
+        });
+        wrapper.position_multi(req_id, summary, model_code).await;
         Ok(())
     }
 
@@ -3249,8 +3819,14 @@ where
     pub async fn position_multi_end_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64
+        );
+        account::publish_fan_out_end(shared, req_id);
+        wrapper.position_multi_end(req_id).await;
         Ok(())
     }
 
@@ -3259,7 +3835,21 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64,
+                account_number @ 0: AccountId,
+                model_code @ 0: String,
+                name @ 0: String,
+                value @ 0: String,
+                currency @ 0: String
+        );
+        let Some(attribute) = decode_account_attribute(name, value, currency)? else {
+            return Ok(());
+        };
+        wrapper
+            .account_update_multi(req_id, attribute, account_number, model_code)
+            .await;
         Ok(())
     }
 
@@ -3268,7 +3858,11 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 2: i64
+        );
+        wrapper.account_update_multi_end(req_id).await;
         Ok(())
     }
 
@@ -3276,8 +3870,52 @@ where
     pub async fn security_definition_option_parameter_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                exchange @ 0: String,
+                underlying_contract_id @ 0: ContractId,
+                trading_class @ 0: String,
+                multiplier @ 0: String,
+                expiration_count @ 0: usize
+        );
+        let expirations = (0..expiration_count)
+            .map(|_| {
+                decode_fields!(fields => expiration @ 0: String);
+                NaiveDate::parse_from_str(&expiration, "%Y%m%d")
+                    .with_context(|| "Invalid date string in option chain expiration")
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        decode_fields!(fields => strike_count @ 0: usize);
+        let strikes = (0..strike_count)
+            .map(|_| {
+                decode_fields!(fields => strike @ 0: f64);
+                Ok(strike)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // IBKR reports each trading class once per exchange, which are merged into one chain
+        if let Some(chains) = lock(&shared.option_chain_results)?.get_mut(&req_id) {
+            match chains.iter_mut().find(|chain| {
+                chain.trading_class == trading_class && chain.multiplier == multiplier
+            }) {
+                Some(chain) => chain.merge(exchange, expirations, strikes),
+                None => {
+                    let mut chain = OptionChain {
+                        underlying_contract_id,
+                        trading_class,
+                        multiplier,
+                        exchanges: Vec::new(),
+                        expirations: Vec::new(),
+                        strikes: Vec::new(),
+                    };
+                    chain.merge(exchange, expirations, strikes);
+                    chains.push(chain);
+                }
+            }
+        }
         Ok(())
     }
 
@@ -3285,26 +3923,74 @@ where
     pub async fn security_definition_option_parameter_end_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(fields => req_id @ 1: i64);
+        if let Some(chains) = lock(&shared.option_chain_results)?.remove(&req_id) {
+            shared.option_chains.deliver(&req_id, Ok(chains));
+        }
         Ok(())
     }
 
     #[inline]
     pub async fn soft_dollar_tiers_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn family_codes_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn symbol_samples_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn symbol_samples_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                count @ 0: usize
+        );
+        let samples = (0..count)
+            .map(|_| {
+                decode_fields!(
+                    fields =>
+                        contract_id @ 0: ContractId,
+                        symbol @ 0: String,
+                        security_type @ 0: String,
+                        primary_exchange @ 0: String,
+                        currency @ 0: String,
+                        derivative_count @ 0: usize
+                );
+                let derivative_security_types = (0..derivative_count)
+                    .map(|_| nth(fields, 0))
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| "Expected derivative security type but none found")?;
+                decode_fields!(
+                    fields =>
+                        description @ 0: String,
+                        issuer_id @ 0: String
+                );
+                Ok(SymbolSample {
+                    contract_id,
+                    symbol,
+                    security_type,
+                    primary_exchange: primary_exchange.parse().ok(),
+                    currency: currency.parse().ok(),
+                    derivative_security_types,
+                    description,
+                    issuer_id,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        shared
+            .matching_symbols
+            .deliver(&req_id, Ok(samples.clone()));
+        wrapper.symbol_samples(req_id, samples).await;
         Ok(())
     }
 
@@ -3313,12 +3999,16 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn tick_req_params_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_req_params_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -3326,6 +4016,7 @@ where
                 exchange_id @ 0: ExchangeId,
                 snapshot_permissions @ 0: u32
         );
+        lock(&shared.exchange_ids)?.insert(req_id, exchange_id.clone());
         wrapper
             .tick_params(req_id, min_tick, exchange_id, snapshot_permissions)
             .await;
@@ -3333,32 +4024,146 @@ where
     }
 
     #[inline]
-    pub async fn smart_components_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn smart_components_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                count @ 0: usize
+        );
+        let components = (0..count)
+            .map(|_| {
+                decode_fields!(
+                    fields =>
+                        bit_number @ 0: u32,
+                        exchange @ 0: crate::exchange::Primary,
+                        letter @ 0: char
+                );
+                Ok(SmartComponent {
+                    bit_number,
+                    exchange,
+                    letter,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        lock(&shared.component_exchanges)?.extend(
+            components
+                .iter()
+                .map(|component| (component.letter, component.exchange)),
+        );
+        shared.smart_components.deliver(&req_id, components.clone());
+        wrapper.smart_components(req_id, components).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn news_article_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn news_article_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                article_type @ 0: i64,
+                text @ 0: String
+        );
+        let article_type = match article_type {
+            0 => ArticleType::Text,
+            1 => ArticleType::Binary,
+            _ => return Err(anyhow::Error::msg("Unexpected news article type")),
+        };
+        let article = NewsArticle { article_type, text };
+        shared.news_articles.deliver(&req_id, Ok(article.clone()));
+        wrapper.news_article(req_id, article).await;
         Ok(())
     }
 
     #[inline]
     pub async fn tick_news_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                timestamp @ 0: i64,
+                provider_code @ 0: String,
+                article_id @ 0: String,
+                headline @ 0: String,
+                extra_data @ 0: String
+        );
+        let datetime = chrono::DateTime::from_timestamp_millis(timestamp)
+            .ok_or_else(|| anyhow::Error::msg("Invalid timestamp"))?
+            .naive_utc();
+        wrapper
+            .tick_news(
+                req_id,
+                NewsHeadline {
+                    datetime,
+                    provider_code,
+                    article_id,
+                    headline,
+                    extra_data,
+                },
+            )
+            .await;
         Ok(())
     }
 
     #[inline]
-    pub async fn news_providers_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn news_providers_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                count @ 1: usize
+        );
+        let providers = (0..count)
+            .map(|_| {
+                decode_fields!(
+                    fields =>
+                        code @ 0: String,
+                        name @ 0: String
+                );
+                Ok(NewsProvider { code, name })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        shared.news_providers.deliver(&(), providers.clone());
+        wrapper.news_providers(providers).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn historical_news_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn historical_news_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                datetime @ 0: String,
+                provider_code @ 0: String,
+                article_id @ 0: String,
+                headline @ 0: String
+        );
+        let headline = NewsHeadline {
+            datetime: NaiveDateTime::parse_from_str(datetime.as_str(), "%Y-%m-%d %H:%M:%S%.f")?,
+            provider_code,
+            article_id,
+            headline,
+            extra_data: String::new(),
+        };
+        if shared.historical_news.is_awaited(&req_id) {
+            lock(&shared.historical_news_results)?
+                .entry(req_id)
+                .or_default()
+                .push(headline.clone());
+        }
+        wrapper.historical_news(req_id, headline).await;
         Ok(())
     }
 
@@ -3366,24 +4171,46 @@ where
     pub async fn historical_news_end_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                has_more @ 0: i64
+        );
+        let has_more = has_more != 0;
+        let headlines = lock(&shared.historical_news_results)?
+            .remove(&req_id)
+            .unwrap_or_default();
+        shared.historical_news.deliver(
+            &req_id,
+            Ok(HistoricalNews {
+                headlines,
+                has_more,
+            }),
+        );
+        wrapper.historical_news_end(req_id, has_more).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn head_timestamp_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn head_timestamp_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 timestamp @ 0: String
         );
-        wrapper
-            .head_timestamp(
-                req_id,
-                NaiveDateTime::parse_from_str(timestamp.as_str(), "%Y%m%d-%T")?,
-            )
-            .await;
+        let timestamp = match timestamp.parse() {
+            Ok(timestamp) => NaiveDateTime::from_timestamp_opt(timestamp, 0)
+                .ok_or_else(|| anyhow::Error::msg("Invalid timestamp"))?,
+            Err(_) => NaiveDateTime::parse_from_str(timestamp.as_str(), "%Y%m%d-%T")?,
+        };
+        shared.head_timestamps.deliver(&req_id, timestamp);
+        wrapper.head_timestamp(req_id, timestamp).await;
         Ok(())
     }
 
@@ -3428,7 +4255,7 @@ where
                 volume @ 0: f64
         );
         let core = BarCore {
-            datetime: NaiveDateTime::parse_and_remainder(datetime_str.as_str(), "%Y%m%d %T")?.0,
+            datetime: decode_bar_datetime(datetime_str.as_str())?,
             open,
             high,
             low,
@@ -3453,7 +4280,7 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -3462,18 +4289,22 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn market_rule_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
-    pub async fn pnl_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn pnl_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -3486,12 +4317,17 @@ where
             unrealized: unrealized_pnl,
             realized: realized_pnl,
         };
+        account::publish_fan_out(shared, req_id, None, || AccountEvent::Pnl(pnl));
         wrapper.pnl(req_id, pnl).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn pnl_single_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn pnl_single_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -3506,7 +4342,14 @@ where
             unrealized: unrealized_pnl,
             realized: realized_pnl,
         };
-
+        account::publish_fan_out(shared, req_id, None, || AccountEvent::PositionPnl {
+            pnl,
+            position,
+            market_value,
+        });
+        wrapper
+            .single_position_pnl(req_id, pnl, position, market_value)
+            .await;
         Ok(())
     }
 
@@ -3514,27 +4357,24 @@ where
     pub async fn historical_ticks_midpoint_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 tick_count @ 0: usize
         );
-        let mut ticks = Vec::with_capacity(tick_count);
-        for chunk in fields
-            .take(tick_count * 4)
-            .collect::<Vec<String>>()
-            .chunks_exact(4)
-        {
-            if let [time, _, price, size] = chunk {
-                ticks.push(Tick::Midpoint {
-                    datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
-                        .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
-                    price: price.parse()?,
-                });
-            }
+        let fields = fields.take(tick_count * 4).collect();
+        if offload(shared, tick_count) {
+            lock(&shared.pending_batches)?.push_back(Batch::Ticks(
+                req_id,
+                tokio::task::spawn_blocking(move || decode_midpoint_ticks(fields)),
+            ));
+        } else {
+            let ticks = decode_midpoint_ticks(fields)?;
+            deliver_historical(&shared.historical_ticks, req_id, &ticks);
+            wrapper.historical_ticks(req_id, ticks).await;
         }
-        wrapper.historical_ticks(req_id, ticks).await;
         Ok(())
     }
 
@@ -3542,30 +4382,24 @@ where
     pub async fn historical_ticks_bid_ask_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 tick_count @ 0: usize
         );
-        let mut ticks = Vec::with_capacity(tick_count);
-        for chunk in fields
-            .take(tick_count * 6)
-            .collect::<Vec<String>>()
-            .chunks_exact(6)
-        {
-            if let [time, _, bid_price, ask_price, bid_size, ask_size] = chunk {
-                ticks.push(Tick::BidAsk {
-                    datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
-                        .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
-                    bid_price: bid_price.parse()?,
-                    ask_price: ask_price.parse()?,
-                    bid_size: bid_size.parse()?,
-                    ask_size: ask_size.parse()?,
-                });
-            }
+        let fields = fields.take(tick_count * 6).collect();
+        if offload(shared, tick_count) {
+            lock(&shared.pending_batches)?.push_back(Batch::Ticks(
+                req_id,
+                tokio::task::spawn_blocking(move || decode_bid_ask_ticks(fields)),
+            ));
+        } else {
+            let ticks = decode_bid_ask_ticks(fields)?;
+            deliver_historical(&shared.historical_ticks, req_id, &ticks);
+            wrapper.historical_ticks(req_id, ticks).await;
         }
-        wrapper.historical_ticks(req_id, ticks).await;
         Ok(())
     }
 
@@ -3573,34 +4407,33 @@ where
     pub async fn historical_ticks_last_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
                 tick_count @ 0: usize
         );
-        let mut ticks = Vec::with_capacity(tick_count);
-        for chunk in fields
-            .take(tick_count * 6)
-            .collect::<Vec<String>>()
-            .chunks_exact(6)
-        {
-            if let [time, _, price, size, exchange, _] = chunk {
-                ticks.push(Tick::Last {
-                    datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
-                        .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
-                    price: price.parse()?,
-                    size: size.parse()?,
-                    exchange: exchange.parse()?,
-                });
-            }
+        let fields = fields.take(tick_count * 6).collect();
+        if offload(shared, tick_count) {
+            lock(&shared.pending_batches)?.push_back(Batch::Ticks(
+                req_id,
+                tokio::task::spawn_blocking(move || decode_last_ticks(fields)),
+            ));
+        } else {
+            let ticks = decode_last_ticks(fields)?;
+            deliver_historical(&shared.historical_ticks, req_id, &ticks);
+            wrapper.historical_ticks(req_id, ticks).await;
         }
-        wrapper.historical_ticks(req_id, ticks).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn tick_by_tick_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
+    pub async fn tick_by_tick_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
         decode_fields!(
             fields =>
                 req_id @ 1: i64,
@@ -3610,19 +4443,30 @@ where
         let datetime = NaiveDateTime::from_timestamp_opt(timestamp, 0)
             .ok_or_else(|| anyhow::Error::msg("Invalid timestamp"))?;
         let tick = match tick_type {
-            1 | 2 => Tick::Last {
-                datetime,
-                price: nth(fields, 0)?.parse()?,
-                size: nth(fields, 0)?.parse()?,
-                exchange: nth(fields, 1)?.parse()?,
-            },
+            1 | 2 => {
+                decode_fields!(
+                    fields =>
+                        price @ 0: f64,
+                        size @ 0: f64,
+                        mask @ 0: u8,
+                        exchange @ 0: crate::exchange::Primary
+                );
+                Tick::Last {
+                    datetime,
+                    price,
+                    size,
+                    exchange,
+                    attributes: TickAttributes::from_last_mask(mask),
+                }
+            }
             3 => {
                 decode_fields!(
                     fields =>
                         bid_price @ 0: f64,
                         ask_price @ 0: f64,
                         bid_size @ 0: f64,
-                        ask_size @ 0: f64
+                        ask_size @ 0: f64,
+                        mask @ 0: u8
                 );
                 Tick::BidAsk {
                     datetime,
@@ -3630,6 +4474,7 @@ where
                     ask_price,
                     bid_size,
                     ask_size,
+                    attributes: TickAttributes::from_bid_ask_mask(mask),
                 }
             }
             4 => Tick::Midpoint {
@@ -3638,19 +4483,22 @@ where
             },
             _ => Err(anyhow::Error::msg("Unexpected tick type"))?,
         };
-        wrapper.live_tick(req_id, tick).await;
+        if trade_filter(shared, req_id)?.is_none_or(|f| f.accepts_tick(&tick)) {
+            stream::publish(shared, req_id, || TypedEvent::Tick(tick));
+            wrapper.live_tick(req_id, tick).await;
+        }
         Ok(())
     }
 
     #[inline]
     pub async fn order_bound_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn completed_order_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -3659,25 +4507,25 @@ where
         fields: &mut Fields,
         wrapper: &mut W,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn replace_fa_end_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn wsh_meta_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
     #[inline]
     pub async fn wsh_event_data_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        event!(debug, fields = ?fields, "Received unsupported message");
         Ok(())
     }
 
@@ -3685,14 +4533,61 @@ where
     pub async fn historical_schedule_msg(
         fields: &mut Fields,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                start @ 0: String,
+                end @ 0: String,
+                time_zone @ 0: String,
+                count @ 0: usize
+        );
+        let time_zone = time_zone
+            .parse::<crate::timezone::Tz>()
+            .map_err(|_| anyhow::Error::msg(format!("Unrecognized time zone {time_zone}")))?;
+        let mut sessions = Vec::with_capacity(count);
+        for _ in 0..count {
+            decode_fields!(
+                fields =>
+                    open @ 0: String,
+                    close @ 0: String,
+                    reference_date @ 0: String
+            );
+            sessions.push(ScheduledSession {
+                reference_date: NaiveDate::parse_from_str(&reference_date, "%Y%m%d")?,
+                open: parse_schedule_time(&open, time_zone)?,
+                close: parse_schedule_time(&close, time_zone)?,
+            });
+        }
+        let schedule = HistoricalSchedule {
+            time_zone,
+            start: parse_schedule_time(&start, time_zone)?,
+            end: parse_schedule_time(&end, time_zone)?,
+            sessions,
+        };
+        shared
+            .historical_schedules
+            .deliver(&req_id, Ok(schedule.clone()));
+        wrapper.historical_schedule(req_id, schedule).await;
         Ok(())
     }
 
     #[inline]
-    pub async fn user_info_msg(fields: &mut Fields, wrapper: &mut W) -> anyhow::Result<()> {
-        println!("{:?}", &fields);
+    pub async fn user_info_msg(
+        fields: &mut Fields,
+        wrapper: &mut W,
+        shared: &Shared,
+    ) -> anyhow::Result<()> {
+        decode_fields!(
+            fields =>
+                req_id @ 1: i64,
+                white_branding_id @ 0: String
+        );
+        shared
+            .user_infos
+            .deliver(&req_id, white_branding_id.clone());
+        wrapper.user_info(req_id, white_branding_id).await;
         Ok(())
     }
 
@@ -3702,6 +4597,7 @@ where
         tick_type: u16,
         value: f64,
         wrapper: &mut W,
+        shared: &Shared,
     ) -> anyhow::Result<()> {
         match tick_type {
             0 | 3 | 5 => {
@@ -3711,6 +4607,7 @@ where
                     5 => Size::Last(value),
                     _ => panic!("The impossible occurred"),
                 });
+                stream::publish(shared, req_id, || TypedEvent::Size(size));
                 wrapper.size_data(req_id, size).await;
             }
             8 | 74 => {
@@ -3719,6 +4616,7 @@ where
                     74 => Class::Delayed(value),
                     _ => panic!("The impossible occurred"),
                 };
+                stream::publish(shared, req_id, || TypedEvent::Volume(volume));
                 wrapper.volume(req_id, volume).await;
             }
             21 | 63 | 64 | 65 => {
@@ -3802,6 +4700,7 @@ where
                     71 => Size::Last(value),
                     _ => panic!("The impossible occurred"),
                 });
+                stream::publish(shared, req_id, || TypedEvent::Size(size));
                 wrapper.size_data(req_id, size).await;
             }
             101 | 102 => {
@@ -3813,9 +4712,9 @@ where
                 wrapper.ipo(req_id, ipo).await;
             }
             t => {
-                return Err(anyhow::Error::msg(format!(
-                    "Unexpected generic market data request: {t}"
-                )))
+                wrapper
+                    .other_tick(req_id, TickType::from(u32::from(t)), value.to_string())
+                    .await;
             }
         };
 
@@ -3850,16 +4749,804 @@ impl std::error::Error for MissingInputData {
 }
 
 #[inline]
+fn lock<T>(mutex: &Mutex<T>) -> anyhow::Result<MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| anyhow::Error::msg("Shared client state was poisoned"))
+}
+
+#[inline]
+fn trade_filter(shared: &Shared, req_id: i64) -> anyhow::Result<Option<TradeFilter>> {
+    Ok(lock(&shared.trade_filters)?.get(&req_id).copied())
+}
+
+/// Hold an execution until its contract arrives, asking the resolver for the contract unless an
+/// earlier execution of the same contract already did.
+fn hold_unresolved(
+    shared: &Shared,
+    resolver: &tokio::sync::mpsc::UnboundedSender<ContractId>,
+    req_id: i64,
+    execution: Execution,
+) -> anyhow::Result<()> {
+    let mut unresolved = lock(&shared.unresolved_executions)?;
+    if unresolved
+        .iter()
+        .all(|(_, held)| held.contract_id != execution.contract_id)
+    {
+        // A closed resolver leaves the execution for `Client::resolve_execution_contracts`
+        let _ = resolver.send(execution.contract_id);
+    }
+    unresolved.push((req_id, execution));
+    Ok(())
+}
+
+#[inline]
+fn take_unresolved(
+    shared: &Shared,
+    contract_id: ContractId,
+) -> anyhow::Result<Vec<(i64, Execution)>> {
+    let mut unresolved = lock(&shared.unresolved_executions)?;
+    let (resolved, remaining) = std::mem::take(&mut *unresolved)
+        .into_iter()
+        .partition(|(_, execution)| execution.contract_id == contract_id);
+    *unresolved = remaining;
+    Ok(resolved)
+}
+
+#[inline]
+// IBKR sends an empty field or the maximum double when a price does not apply to an order
+fn decode_order_price(price: &str) -> Option<f64> {
+    price.parse().ok().filter(|p: &f64| *p < f64::MAX)
+}
+
+/// Decode an order's time in force, along with its "Good Till Date" if it is a GTD order. The date
+/// is sent either in UTC ("yyyyMMdd-HH:mm:ss") or in a given time zone ("yyyyMMdd HH:mm:ss Zone"),
+/// and is assumed to be in UTC if the time zone is omitted.
+fn decode_time_in_force(time_in_force: &str, good_till_date: &str) -> anyhow::Result<TimeInForce> {
+    if time_in_force != "GTD" {
+        return Ok(time_in_force.parse()?);
+    }
+    let (datetime, zone) = match good_till_date.split_once('-') {
+        Some(_) => (
+            NaiveDateTime::parse_from_str(good_till_date, "%Y%m%d-%T"),
+            None,
+        ),
+        None => match good_till_date.rsplit_once(' ') {
+            Some((datetime, zone)) if !zone.contains(':') => (
+                NaiveDateTime::parse_from_str(datetime, "%Y%m%d %T"),
+                Some(
+                    zone.parse::<crate::timezone::Tz>()
+                        .map_err(|e| anyhow::anyhow!(e))?,
+                ),
+            ),
+            _ => (
+                NaiveDateTime::parse_from_str(good_till_date, "%Y%m%d %T"),
+                None,
+            ),
+        },
+    };
+    let datetime = datetime.with_context(|| format!("Invalid good till date {good_till_date}"))?;
+    let datetime = match zone {
+        Some(zone) => zone
+            .from_local_datetime(&datetime)
+            .earliest()
+            .with_context(|| format!("Invalid good till date {good_till_date}"))?
+            .with_timezone(&chrono::Utc),
+        None => chrono::Utc.from_utc_datetime(&datetime),
+    };
+    Ok(TimeInForce::Gtd(datetime))
+}
+
+/// Skip the fields of an open order from its Rule 80A through its "Not Held" flag, which lie
+/// between the order's "Good Till Date" and its delta-neutral contract. Their number depends on a
+/// number of variable-length groups.
+fn skip_to_delta_neutral_contract(fields: &mut Fields) -> Option<()> {
+    let count = |value: String| value.parse::<usize>().unwrap_or(0);
+
+    // Rule 80A through the volatility type of a volatility order
+    skip_fields(fields, 25)?;
+    let hedged = !fields.next()?.is_empty();
+    // The delta-neutral aux price (and the delta-neutral order's attributes) through the
+    // description of the combo legs
+    skip_fields(fields, 8 + usize::from(hedged) * 8)?;
+    let combo_legs = count(fields.next()?);
+    skip_fields(fields, combo_legs * 8)?;
+    let order_combo_legs = count(fields.next()?);
+    skip_fields(fields, order_combo_legs)?;
+    let smart_combo_routing_params = count(fields.next()?);
+    skip_fields(fields, smart_combo_routing_params * 2 + 2)?;
+    if decode_order_price(&fields.next()?).is_some_and(|increment| increment > 0.0) {
+        // The attributes of a scale order
+        skip_fields(fields, 7)?;
+    }
+    if !fields.next()?.is_empty() {
+        // The hedge parameter of a hedge order
+        skip_fields(fields, 1)?;
+    }
+    // Opt out of SMART routing through "Not Held"
+    skip_fields(fields, 4)
+}
+
+/// Decode the delta-neutral contract of an open order, which follows a number of variable-length
+/// groups of fields after the order's "Good Till Date".
+///
+/// # Returns
+/// The delta-neutral contract, or [`None`] if the order has none or if the fields do not have the
+/// expected layout.
+fn decode_delta_neutral_contract(fields: &mut Fields) -> Option<DeltaNeutralContract> {
+    skip_to_delta_neutral_contract(fields)?;
+    if !matches!(fields.next()?.as_str(), "1" | "true") {
+        return None;
+    }
+    Some(DeltaNeutralContract {
+        contract_id: fields.next()?.parse().ok()?,
+        delta: fields.next()?.parse().ok()?,
+        price: fields.next()?.parse().ok()?,
+    })
+}
+
+/// Decode the order state that follows the what-if flag in an open order message, walking the
+/// fields after the order's "Good Till Date" in the order in which server version 177 sends them.
+///
+/// # Returns
+/// The order state, or [`None`] if the fields do not have the expected layout.
+fn decode_order_state(fields: &mut Fields) -> Option<OrderState> {
+    skip_to_delta_neutral_contract(fields)?;
+    if matches!(fields.next()?.as_str(), "1" | "true") {
+        // The delta-neutral contract's ID, delta and price
+        skip_fields(fields, 3)?;
+    }
+    if !fields.next()?.is_empty() {
+        // The parameters of an algorithmic order
+        let algo_params = fields.next()?.parse::<usize>().unwrap_or(0);
+        skip_fields(fields, algo_params * 2)?;
+    }
+    // Solicited, what-if and the order status
+    skip_fields(fields, 3)?;
+    let state = fields.take(14).collect::<Vec<_>>();
+    if state.len() < 14 {
+        return None;
+    }
+    let value = |i: usize| decode_order_price(&state[i]);
+    Some(OrderState {
+        initial_margin_before: value(0),
+        maintenance_margin_before: value(1),
+        equity_with_loan_before: value(2),
+        initial_margin_change: value(3),
+        maintenance_margin_change: value(4),
+        equity_with_loan_change: value(5),
+        initial_margin_after: value(6),
+        maintenance_margin_after: value(7),
+        equity_with_loan_after: value(8),
+        commission: value(9),
+        min_commission: value(10),
+        max_commission: value(11),
+        commission_currency: state[12].parse().ok(),
+        warning: Some(state[13].clone()).filter(|w| !w.is_empty()),
+    })
+}
+
+#[inline]
+fn skip_fields(fields: &mut Fields, n: usize) -> Option<()> {
+    if n > 0 {
+        fields.nth(n - 1)?;
+    }
+    Some(())
+}
+
+#[inline]
+fn decode_execution_datetime(datetime: &str) -> anyhow::Result<NaiveDateTime> {
+    Ok(NaiveDateTime::parse_and_remainder(datetime, "%Y%m%d %T")
+        .or_else(|_| NaiveDateTime::parse_and_remainder(datetime, "%Y%m%d-%T"))
+        .with_context(|| format!("Invalid execution time {datetime}"))?
+        .0)
+}
+
+#[derive(Debug)]
+/// A batch of historical data that is being decoded on the blocking thread pool.
+pub(crate) enum Batch {
+    Bars(i64, tokio::task::JoinHandle<anyhow::Result<Vec<Bar>>>),
+    Ticks(i64, tokio::task::JoinHandle<anyhow::Result<Vec<Tick>>>),
+}
+
+impl Batch {
+    #[inline]
+    fn is_finished(&self) -> bool {
+        match self {
+            Self::Bars(_, handle) => handle.is_finished(),
+            Self::Ticks(_, handle) => handle.is_finished(),
+        }
+    }
+}
+
+#[inline]
+fn offload(shared: &Shared, count: usize) -> bool {
+    match shared.decode_offload_threshold.load(Ordering::Relaxed) {
+        0 => false,
+        threshold => count >= threshold,
+    }
+}
+
+#[inline]
+fn next_finished_batch(shared: &Shared) -> anyhow::Result<Option<Batch>> {
+    let mut pending = lock(&shared.pending_batches)?;
+    Ok(match pending.front() {
+        Some(batch) if batch.is_finished() => pending.pop_front(),
+        _ => None,
+    })
+}
+
+#[inline]
+fn decode_contract_details(
+    fields: &mut Fields,
+    sec_type: &str,
+) -> anyhow::Result<crate::contract::ContractDetails> {
+    // Each group of fields was introduced in a later server version, so any of them may be missing
+    let mut next_decimal = || fields.next().and_then(|value| value.parse().ok());
+    let min_size = next_decimal();
+    let size_increment = next_decimal();
+    let suggested_size_increment = next_decimal();
+    let fund = if sec_type == "FUND" && fields.len() >= 17 {
+        let decimal = |value: String| value.parse().ok();
+        let flag = |value: String| value == "1" || value.eq_ignore_ascii_case("true");
+        Some(crate::contract::FundDetails {
+            name: nth(fields, 0)?,
+            family: nth(fields, 0)?,
+            fund_type: nth(fields, 0)?,
+            front_load: decimal(nth(fields, 0)?),
+            back_load: decimal(nth(fields, 0)?),
+            back_load_time_interval: nth(fields, 0)?,
+            management_fee: decimal(nth(fields, 0)?),
+            closed: flag(nth(fields, 0)?),
+            closed_for_new_investors: flag(nth(fields, 0)?),
+            closed_for_new_money: flag(nth(fields, 0)?),
+            notify_amount: decimal(nth(fields, 0)?),
+            minimum_initial_purchase: decimal(nth(fields, 0)?),
+            subsequent_minimum_purchase: decimal(nth(fields, 0)?),
+            blue_sky_states: nth(fields, 0)?,
+            blue_sky_territories: nth(fields, 0)?,
+            distribution_policy: nth(fields, 0)?.parse().ok(),
+            asset_type: nth(fields, 0)?.parse().ok(),
+        })
+    } else {
+        None
+    };
+    Ok(crate::contract::ContractDetails {
+        min_size,
+        size_increment,
+        suggested_size_increment,
+        fund,
+        issuer_id: None,
+    })
+}
+
+#[inline]
+/// Cache the details, time zone, and trading hours that IBKR reported alongside a contract.
+fn cache_contract_details(
+    shared: &Shared,
+    contract_id: ContractId,
+    details: crate::contract::ContractDetails,
+    time_zone_id: &str,
+    trading_hours: &str,
+) -> anyhow::Result<()> {
+    lock(&shared.contract_details)?.insert(contract_id, details);
+    if let Ok(time_zone) = time_zone_id.parse() {
+        if let Ok(hours) = TradingHours::parse(trading_hours, time_zone) {
+            lock(&shared.trading_hours)?.insert(contract_id, hours);
+        }
+        lock(&shared.time_zones)?.insert(contract_id, time_zone);
+    }
+    Ok(())
+}
+
+#[inline]
+/// Decode the details of a bond, which IBKR reports in a message of its own. Bonds cannot yet be
+/// represented as a [`Contract`], so a query that awaits one fails, but the bond's details
+/// (including its issuer ID) are cached for [`crate::client::Client::get_contract_details`].
+pub(crate) fn decode_bond_contract(fields: &mut Fields, shared: &Shared) -> anyhow::Result<()> {
+    decode_fields!(
+        fields =>
+            req_id @ 1: i64,
+            contract_id @ 17: ContractId,
+            time_zone_id @ 8: String,
+            trading_hours @ 0: String,
+            security_id_count @ 3: usize
+    );
+    // Skip the security IDs, the aggregate group, and the market rule IDs
+    fields.nth(2 * security_id_count + 1);
+    let mut details = decode_contract_details(fields, "BOND")?;
+    details.issuer_id = fields.next().filter(|id| !id.is_empty());
+    cache_contract_details(shared, contract_id, details, &time_zone_id, &trading_hours)?;
+    shared.contract_queries.deliver(
+        &req_id,
+        Err(Error::UnexpectedSecurityType(
+            format!(
+                "BOND contract {} is not supported; its details are available from \
+                 Client::get_contract_details",
+                contract_id.0
+            )
+            .into(),
+        )),
+    );
+    Ok(())
+}
+
+#[inline]
+/// Deliver the bars or ticks of a historical data request to [`crate::client::Client`], if it is
+/// awaiting them.
+fn deliver_historical<T: Clone>(
+    responses: &crate::client::Responses<i64, Result<Vec<T>, Error>>,
+    req_id: i64,
+    data: &[T],
+) {
+    if responses.is_awaited(&req_id) {
+        responses.deliver(&req_id, Ok(data.to_vec()));
+    }
+}
+
+#[inline]
+fn decode_bars(fields: Vec<String>) -> anyhow::Result<Vec<Bar>> {
+    let mut bars = Vec::with_capacity(fields.len() / 8);
+    for chunk in fields.chunks(8) {
+        if let [date, open, high, low, close, volume, wap, trade_count] = chunk {
+            let core = BarCore {
+                datetime: decode_bar_datetime(date)?,
+                open: open.parse()?,
+                high: high.parse()?,
+                low: low.parse()?,
+                close: close.parse()?,
+            };
+            let (volume, wap, trade_count) =
+                (volume.parse()?, wap.parse()?, trade_count.parse::<i64>()?);
+            let bar = if volume > 0. && wap > 0. && trade_count > 0 {
+                Bar::Trades {
+                    bar: core,
+                    volume,
+                    wap,
+                    trade_count: trade_count.try_into()?,
+                }
+            } else {
+                Bar::Ordinary(core)
+            };
+            bars.push(bar);
+        }
+    }
+    Ok(bars)
+}
+
+#[inline]
+fn decode_midpoint_ticks(fields: Vec<String>) -> anyhow::Result<Vec<Tick>> {
+    let mut ticks = Vec::with_capacity(fields.len() / 4);
+    for chunk in fields.chunks_exact(4) {
+        if let [time, _, price, size] = chunk {
+            ticks.push(Tick::Midpoint {
+                datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
+                    .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
+                price: price.parse()?,
+            });
+        }
+    }
+    Ok(ticks)
+}
+
+#[inline]
+fn decode_bid_ask_ticks(fields: Vec<String>) -> anyhow::Result<Vec<Tick>> {
+    let mut ticks = Vec::with_capacity(fields.len() / 6);
+    for chunk in fields.chunks_exact(6) {
+        if let [time, mask, bid_price, ask_price, bid_size, ask_size] = chunk {
+            ticks.push(Tick::BidAsk {
+                datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
+                    .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
+                bid_price: bid_price.parse()?,
+                ask_price: ask_price.parse()?,
+                bid_size: bid_size.parse()?,
+                ask_size: ask_size.parse()?,
+                attributes: TickAttributes::from_historical_bid_ask_mask(mask.parse()?),
+            });
+        }
+    }
+    Ok(ticks)
+}
+
+#[inline]
+fn decode_last_ticks(fields: Vec<String>) -> anyhow::Result<Vec<Tick>> {
+    let mut ticks = Vec::with_capacity(fields.len() / 6);
+    for chunk in fields.chunks_exact(6) {
+        if let [time, mask, price, size, exchange, _] = chunk {
+            ticks.push(Tick::Last {
+                datetime: NaiveDateTime::from_timestamp_opt(time.parse()?, 0)
+                    .ok_or_else(|| anyhow::Error::msg("Invalid datetime"))?,
+                price: price.parse()?,
+                size: size.parse()?,
+                exchange: exchange.parse()?,
+                attributes: TickAttributes::from_last_mask(mask.parse()?),
+            });
+        }
+    }
+    Ok(ticks)
+}
+
+#[inline]
+fn decode_bar_datetime(datetime: &str) -> anyhow::Result<NaiveDateTime> {
+    match datetime.parse() {
+        Ok(timestamp) => NaiveDateTime::from_timestamp_opt(timestamp, 0)
+            .ok_or_else(|| anyhow::Error::msg("Invalid timestamp")),
+        Err(_) => Ok(NaiveDate::parse_and_remainder(datetime, "%Y%m%d")
+            .with_context(|| format!("Invalid bar date {datetime}"))?
+            .0
+            .and_time(NaiveTime::MIN)),
+    }
+}
+
+/// Decode a time in a historical schedule (ex: "20240506-09:30:00"), given in the exchange's time
+/// zone.
+fn parse_schedule_time(
+    time: &str,
+    time_zone: crate::timezone::Tz,
+) -> anyhow::Result<chrono::DateTime<chrono::Utc>> {
+    let local = NaiveDateTime::parse_from_str(time, "%Y%m%d-%H:%M:%S")
+        .with_context(|| format!("Invalid schedule time {time}"))?;
+    time_zone
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|time| time.with_timezone(&chrono::Utc))
+        .ok_or_else(|| anyhow::Error::msg(format!("Nonexistent schedule time {time}")))
+}
+
+#[allow(clippy::too_many_lines)]
+/// Decode an account value from [`crate::client::Client::req_account_updates`] or
+/// [`crate::client::Client::req_account_updates_multi`].
+///
+/// # Returns
+/// The attribute, or [`None`] for a segment title, which carries no value.
+fn decode_account_attribute(
+    name: String,
+    value: String,
+    currency: String,
+) -> anyhow::Result<Option<account::Attribute>> {
+    let attribute = match name.as_str() {
+        "AccountCode" => account::Attribute::AccountCode(value),
+        "AccountOrGroup" => match value.as_str() {
+            "All" => account::Attribute::AccountOrGroup(account::Group::All, currency.parse()?),
+            name => account::Attribute::AccountOrGroup(
+                account::Group::Name(name.to_owned()),
+                currency.parse()?,
+            ),
+        },
+        "AccountReady" => account::Attribute::AccountReady(value.parse()?),
+        "AccountType" => account::Attribute::AccountType(value),
+        expand_seg_variants!("AccruedCash") => account::Attribute::AccruedCash(
+            impl_seg_variants!("AccruedCash", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("AccruedDividend") => account::Attribute::AccruedDividend(
+            impl_seg_variants!("AccruedDividend", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("AvailableFunds") => account::Attribute::AvailableFunds(
+            impl_seg_variants!("AvailableFunds", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("Billable") => account::Attribute::Billable(
+            impl_seg_variants!("Billable", name, value),
+            currency.parse()?,
+        ),
+        "BuyingPower" => account::Attribute::BuyingPower(value.parse()?, currency.parse()?),
+        "CashBalance" => account::Attribute::CashBalance(value.parse()?, currency.parse()?),
+        expand_seg_variants!("ColumnPrio") => {
+            account::Attribute::ColumnPrio(impl_seg_variants!("ColumnPrio", name, value))
+        }
+        "CorporateBondValue" => {
+            account::Attribute::CorporateBondValue(value.parse()?, currency.parse()?)
+        }
+        "Cryptocurrency" => account::Attribute::Cryptocurrency(value.parse()?, currency.parse()?),
+        "Currency" => account::Attribute::Currency(value.parse()?),
+        "Cushion" => account::Attribute::Cushion(value.parse()?),
+        "DayTradesRemaining" => account::Attribute::DayTradesRemaining(value.parse()?),
+        "DayTradesRemainingT+1" => account::Attribute::DayTradesRemainingTPlus1(value.parse()?),
+        "DayTradesRemainingT+2" => account::Attribute::DayTradesRemainingTPlus2(value.parse()?),
+        "DayTradesRemainingT+3" => account::Attribute::DayTradesRemainingTPlus3(value.parse()?),
+        "DayTradesRemainingT+4" => account::Attribute::DayTradesRemainingTPlus4(value.parse()?),
+        "DayTradingStatus-S" => account::Attribute::DayTradingStatus(value),
+        expand_seg_variants!("EquityWithLoanValue") => account::Attribute::EquityWithLoanValue(
+            impl_seg_variants!("EquityWithLoanValue", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("ExcessLiquidity") => account::Attribute::ExcessLiquidity(
+            impl_seg_variants!("ExcessLiquidity", name, value),
+            currency.parse()?,
+        ),
+        "ExchangeRate" => account::Attribute::ExchangeRate(value.parse()?, currency.parse()?),
+        expand_seg_variants!("FullAvailableFunds") => account::Attribute::FullAvailableFunds(
+            impl_seg_variants!("FullAvailableFunds", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("FullExcessLiquidity") => account::Attribute::FullExcessLiquidity(
+            impl_seg_variants!("FullExcessLiquidity", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("FullInitMarginReq") => account::Attribute::FullInitMarginReq(
+            impl_seg_variants!("FullInitMarginReq", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("FullMaintMarginReq") => account::Attribute::FullMaintenanceMarginReq(
+            impl_seg_variants!("FullMaintMarginReq", name, value),
+            currency.parse()?,
+        ),
+        "FundValue" => account::Attribute::FundValue(value.parse()?, currency.parse()?),
+        "FutureOptionValue" => {
+            account::Attribute::FutureOptionValue(value.parse()?, currency.parse()?)
+        }
+        "FuturesPNL" => account::Attribute::FuturesPnl(value.parse()?, currency.parse()?),
+        "FxCashBalance" => account::Attribute::FxCashBalance(value.parse()?, currency.parse()?),
+        "GrossPositionValue" => {
+            account::Attribute::GrossPositionValue(value.parse()?, currency.parse()?)
+        }
+        "GrossPositionValue-S" => {
+            account::Attribute::GrossPositionValueSecurity(value.parse()?, currency.parse()?)
+        }
+        expand_seg_variants!("Guarantee") => account::Attribute::Guarantee(
+            impl_seg_variants!("Guarantee", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("IndianStockHaircut") => account::Attribute::IndianStockHaircut(
+            impl_seg_variants!("IndianStockHaircut", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("InitMarginReq") => account::Attribute::InitMarginReq(
+            impl_seg_variants!("InitMarginReq", name, value),
+            currency.parse()?,
+        ),
+        "IssuerOptionValue" => {
+            account::Attribute::IssuerOptionValue(value.parse()?, currency.parse()?)
+        }
+        "Leverage-S" => account::Attribute::LeverageSecurity(value.parse()?),
+        expand_seg_variants!("LookAheadAvailableFunds") => {
+            account::Attribute::LookAheadAvailableFunds(
+                impl_seg_variants!("LookAheadAvailableFunds", name, value),
+                currency.parse()?,
+            )
+        }
+        expand_seg_variants!("LookAheadExcessLiquidity") => {
+            account::Attribute::LookAheadExcessLiquidity(
+                impl_seg_variants!("LookAheadExcessLiquidity", name, value),
+                currency.parse()?,
+            )
+        }
+        expand_seg_variants!("LookAheadInitMarginReq") => {
+            account::Attribute::LookAheadInitMarginReq(
+                impl_seg_variants!("LookAheadInitMarginReq", name, value),
+                currency.parse()?,
+            )
+        }
+        expand_seg_variants!("LookAheadMaintMarginReq") => {
+            account::Attribute::LookAheadMaintenanceMarginReq(
+                impl_seg_variants!("LookAheadMaintMarginReq", name, value),
+                currency.parse()?,
+            )
+        }
+        "LookAheadNextChange" => account::Attribute::LookAheadNextChange(value.parse()?),
+        expand_seg_variants!("MaintMarginReq") => account::Attribute::MaintenanceMarginReq(
+            impl_seg_variants!("MaintMarginReq", name, value),
+            currency.parse()?,
+        ),
+        "MoneyMarketFundValue" => {
+            account::Attribute::MoneyMarketFundValue(value.parse()?, currency.parse()?)
+        }
+        "MutualFundValue" => account::Attribute::MutualFundValue(value.parse()?, currency.parse()?),
+        "NLVAndMarginInReview" => account::Attribute::NlvAndMarginInReview(value.parse()?),
+        "NetDividend" => account::Attribute::NetDividend(value.parse()?, currency.parse()?),
+        expand_seg_variants!("NetLiquidation") => account::Attribute::NetLiquidation(
+            impl_seg_variants!("NetLiquidation", name, value),
+            currency.parse()?,
+        ),
+        "NetLiquidationByCurrency" => {
+            account::Attribute::NetLiquidationByCurrency(value.parse()?, currency.parse()?)
+        }
+        "NetLiquidationUncertainty" => {
+            account::Attribute::NetLiquidationUncertainty(value.parse()?, currency.parse()?)
+        }
+        "OptionMarketValue" => {
+            account::Attribute::OptionMarketValue(value.parse()?, currency.parse()?)
+        }
+        expand_seg_variants!("PASharesValue") => account::Attribute::PaSharesValue(
+            impl_seg_variants!("PASharesValue", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("PhysicalCertificateValue") => {
+            account::Attribute::PhysicalCertificateValue(
+                impl_seg_variants!("PhysicalCertificateValue", name, value),
+                currency.parse()?,
+            )
+        }
+        expand_seg_variants!("PostExpirationExcess") => account::Attribute::PostExpirationExcess(
+            impl_seg_variants!("PostExpirationExcess", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("PostExpirationMargin") => account::Attribute::PostExpirationMargin(
+            impl_seg_variants!("PostExpirationMargin", name, value),
+            currency.parse()?,
+        ),
+        "PreviousDayEquityWithLoanValue" => {
+            account::Attribute::PreviousDayEquityWithLoanValue(value.parse()?, currency.parse()?)
+        }
+        "PreviousDayEquityWithLoanValue-S" => {
+            account::Attribute::PreviousDayEquityWithLoanValueSecurity(
+                value.parse()?,
+                currency.parse()?,
+            )
+        }
+        "RealCurrency" => account::Attribute::RealCurrency(currency.parse()?),
+        "RealizedPnL" => account::Attribute::RealizedPnL(value.parse()?, currency.parse()?),
+        "RegTEquity" => account::Attribute::RegTEquity(value.parse()?, currency.parse()?),
+        "RegTEquity-S" => account::Attribute::RegTEquitySecurity(value.parse()?, currency.parse()?),
+        "RegTMargin" => account::Attribute::RegTMargin(value.parse()?, currency.parse()?),
+        "RegTMargin-S" => account::Attribute::RegTMarginSecurity(value.parse()?, currency.parse()?),
+        "SMA" => account::Attribute::Sma(value.parse()?, currency.parse()?),
+        "SMA-S" => account::Attribute::SmaSecurity(value.parse()?, currency.parse()?),
+        "StockMarketValue" => {
+            account::Attribute::StockMarketValue(value.parse()?, currency.parse()?)
+        }
+        "TBillValue" => account::Attribute::TBillValue(value.parse()?, currency.parse()?),
+        "TBondValue" => account::Attribute::TBondValue(value.parse()?, currency.parse()?),
+        "TotalCashBalance" => {
+            account::Attribute::TotalCashBalance(value.parse()?, currency.parse()?)
+        }
+        expand_seg_variants!("TotalCashValue") => account::Attribute::TotalCashValue(
+            impl_seg_variants!("TotalCashValue", name, value),
+            currency.parse()?,
+        ),
+        expand_seg_variants!("TotalDebitCardPendingCharges") => {
+            account::Attribute::TotalDebitCardPendingCharges(
+                impl_seg_variants!("TotalDebitCardPendingCharges", name, value),
+                currency.parse()?,
+            )
+        }
+        "TradingType-S" => account::Attribute::TradingTypeSecurity(value),
+        "UnrealizedPnL" => account::Attribute::UnrealizedPnL(value.parse()?, currency.parse()?),
+        "WarrantValue" => account::Attribute::WarrantValue(value.parse()?, currency.parse()?),
+        "WhatIfPMEnabled" => account::Attribute::WhatIfPMEnabled(value.parse()?),
+        expand_seg_variants!("SegmentTitle") => {
+            if name.ends_with('C') || name.ends_with('P') || name.ends_with('S') {
+                return Ok(None);
+            }
+            return Err(anyhow::Error::msg("Unexpected segment title encountered.  This may mandate an API update: currently-supported values are C, P, and S as outlined in the account::Segment type."));
+        }
+        _ => {
+            return Err(anyhow::Error::msg(format!(
+                "Invalid account attribute encountered: {name}"
+            )))
+        }
+    };
+    Ok(Some(attribute))
+}
+
+/// Deliver an error from IBKR to every awaited request with the given ID.
+pub(crate) fn fail_requests(shared: &Shared, req_id: i64, error_code: i64, error_string: &str) {
+    // Informational messages, such as notices of a market data farm connection, are not failures
+    if (2100..3000).contains(&error_code) {
+        return;
+    }
+    let api_error = || Error::from_api(error_code, error_string);
+    shared.contract_queries.deliver(&req_id, Err(api_error()));
+    shared.contract_searches.deliver(&req_id, Err(api_error()));
+    shared.option_chains.deliver(&req_id, Err(api_error()));
+    shared.matching_symbols.deliver(&req_id, Err(api_error()));
+    shared.news_articles.deliver(&req_id, Err(api_error()));
+    shared.historical_news.deliver(&req_id, Err(api_error()));
+    shared.historical_bars.deliver(&req_id, Err(api_error()));
+    shared
+        .historical_schedules
+        .deliver(&req_id, Err(api_error()));
+    shared.historical_ticks.deliver(&req_id, Err(api_error()));
+    // Warnings about an order do not prevent it from being previewed
+    if error_code != 399 {
+        shared.what_if_orders.deliver(&req_id, Err(api_error()));
+        shared
+            .order_latency
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .cancel(req_id);
+    }
+}
+
+/// Check whether two consecutive real-time bars fall within the same trading session of the
+/// subscription's contract, so that the gap between them is a loss of data rather than the break
+/// between two sessions.
+///
+/// The contract's trading hours are used if they cover the first bar. Otherwise, the bundled
+/// calendar of the contract's primary exchange is used. A gap that neither can place is taken to
+/// be within a session.
+pub(crate) fn is_within_session(
+    shared: &Shared,
+    req_id: i64,
+    previous: NaiveDateTime,
+    next: NaiveDateTime,
+) -> anyhow::Result<bool> {
+    let Some(contract_id) = lock(&shared.real_time_bar_contracts)?.get(&req_id).copied() else {
+        return Ok(true);
+    };
+    let previous = chrono::Utc.from_utc_datetime(&previous);
+    let next = chrono::Utc.from_utc_datetime(&next);
+    let close = lock(&shared.trading_hours)?
+        .get(&contract_id)
+        .filter(|hours| hours.is_open(previous))
+        .and_then(|hours| hours.next_session_close(previous));
+    let close = match close {
+        Some(close) => Some(close),
+        None => lock(&shared.contracts)?
+            .get(&contract_id)
+            .and_then(Contract::get_primary_exchange)
+            .and_then(crate::calendar::Calendar::from_exchange)
+            .filter(|calendar| calendar.is_open(previous))
+            .and_then(|calendar| calendar.next_session_close(previous)),
+    };
+    Ok(close.map_or(true, |close| next < close))
+}
+
+#[inline]
+/// Deliver the contracts found by a search once IBKR reports that it has sent all of them.
+pub(crate) fn end_contract_search(shared: &Shared, req_id: i64) -> anyhow::Result<()> {
+    if let Some(found) = lock(&shared.contract_search_results)?.remove(&req_id) {
+        shared.contract_searches.deliver(&req_id, Ok(found));
+    }
+    Ok(())
+}
+
+#[inline]
+/// Decode a message that arrives while a local wrapper is being built. The responses that the
+/// wrapper's [`crate::wrapper::Initializer`] may await, namely contracts, the end of contract
+/// searches, and the errors that answer them, are delivered to the requests that await them.
+///
+/// # Returns
+/// The message, if it must also be decoded once the wrapper has been built; otherwise, [`None`].
+pub(crate) async fn decode_msg_init(
+    fields: Vec<String>,
+    shared: &Shared,
+) -> anyhow::Result<Option<Vec<String>>> {
+    match fields.first().and_then(|t| t.parse().ok()) {
+        // Contracts are only decoded for the requests that await them, so they need not reach
+        // the wrapper
+        Some(In::ContractData) => {
+            decode_contract_no_wrapper(&mut fields.into_iter(), shared)
+                .await
+                .with_context(|| "contract data msg")?;
+            Ok(None)
+        }
+        Some(In::ContractDataEnd) => {
+            let mut iter = fields.clone().into_iter();
+            decode_fields!(&mut iter => req_id @ 2: i64);
+            end_contract_search(shared, req_id).with_context(|| "contract data end msg")?;
+            Ok(Some(fields))
+        }
+        Some(In::ErrMsg) => {
+            let mut iter = fields.clone().into_iter();
+            decode_fields!(
+                &mut iter =>
+                    req_id @ 2: i64,
+                    error_code @ 0: i64,
+                    error_string @ 0: String
+            );
+            fail_requests(shared, req_id, error_code, &error_string);
+            Ok(Some(fields))
+        }
+        _ => Ok(Some(fields)),
+    }
+}
+
 pub(crate) fn nth(fields: &mut Fields, n: usize) -> Result<String, MissingInputData> {
     fields.nth(n).ok_or(MissingInputData)
 }
 
 #[inline]
+/// Decode a contract, and deliver it to the query or search that awaits it.
+///
+/// # Returns
+/// The contract, if it was awaited; otherwise, [`None`].
 pub(crate) async fn decode_contract_no_wrapper(
     fields: &mut Fields,
-    tx: &mut Tx,
-    rx: &mut Rx,
-) -> anyhow::Result<()> {
+    shared: &Shared,
+) -> anyhow::Result<Option<Contract>> {
     decode_fields!(
         fields =>
             req_id @ 1: i64,
@@ -3881,7 +5568,9 @@ pub(crate) async fn decode_contract_no_wrapper(
             long_name @ 0: String,
             primary_exchange @ 0: String,
             sector @ 1: String,
-            security_id_count @ 7: usize
+            time_zone_id @ 2: String,
+            trading_hours @ 0: String,
+            security_id_count @ 3: usize
     );
 
     let order_types = order_types
@@ -3912,6 +5601,9 @@ pub(crate) async fn decode_contract_no_wrapper(
                 "RIC" => Ok(SecurityId::Ric(
                     nth(fields, 0).with_context(|| "Expected RIC but none found")?,
                 )),
+                "FIGI" => Ok(SecurityId::Figi(
+                    nth(fields, 0).with_context(|| "Expected FIGI but none found")?,
+                )),
                 _ => Err(anyhow::Error::msg(
                     "Invalid security_id type found in STK contract_data_msg",
                 )),
@@ -3919,13 +5611,11 @@ pub(crate) async fn decode_contract_no_wrapper(
         })
         .collect::<Result<Vec<SecurityId>, _>>()?;
 
-    if let Ok(ToWrapper::ContractQuery((con_id_client, req_id_client))) = rx.try_recv() {
-        if con_id_client != contract_id {
-            return Err(anyhow::Error::msg("Unexpected contract ID"));
-        }
-        if req_id_client != req_id {
-            return Err(anyhow::Error::msg("Unexpected request ID"));
-        }
+    if shared.contract_queries.is_awaited(&req_id) || shared.contract_searches.is_awaited(&req_id) {
+        let stock_type = nth(fields, 5);
+        let details = decode_contract_details(fields, &sec_type)?;
+        // The details are cached even for contracts that cannot yet be represented
+        cache_contract_details(shared, contract_id, details, &time_zone_id, &trading_hours)?;
         let contract = match sec_type.as_str() {
             "STK" => Some(Contract::Stock(Stock {
                 symbol,
@@ -3943,7 +5633,7 @@ pub(crate) async fn decode_contract_no_wrapper(
                 order_types,
                 valid_exchanges,
                 security_ids,
-                stock_type: nth(fields, 5).with_context(|| "Expected stock_type but none found")?,
+                stock_type: stock_type.with_context(|| "Expected stock_type but none found")?,
             })),
             "OPT" => {
                 let inner = SecOptionInner {
@@ -4041,14 +5731,41 @@ pub(crate) async fn decode_contract_no_wrapper(
                 order_types,
                 valid_exchanges,
             })),
-            _ => todo!(),
+            // Funds cannot yet be represented as a contract, but their details remain available
+            "FUND" => {
+                shared.contract_queries.deliver(
+                    &req_id,
+                    Err(Error::UnexpectedSecurityType(
+                        format!(
+                            "FUND contract {} is not supported; its details are available from \
+                             Client::get_contract_details",
+                            contract_id.0
+                        )
+                        .into(),
+                    )),
+                );
+                return Ok(None);
+            }
+            // The query fails rather than the decoding loop, and searches omit the contract
+            _ => {
+                let msg = format!("{sec_type} contracts are not supported");
+                shared.contract_queries.deliver(
+                    &req_id,
+                    Err(Error::UnexpectedSecurityType(msg.clone().into())),
+                );
+                return Err(anyhow::Error::msg(msg));
+            }
         };
 
-        tx.send(ToClient::NewContract(
-            contract.ok_or_else(|| anyhow::Error::msg("No contract was created"))?,
-        ))
-        .await
-        .with_context(|| "Failure when sending contract")?;
+        let contract = contract.ok_or_else(|| anyhow::Error::msg("No contract was created"))?;
+        lock(&shared.contracts)?.insert(contract_id, contract.clone());
+        if let Some(found) = lock(&shared.contract_search_results)?.get_mut(&req_id) {
+            found.push(contract.clone());
+        }
+        shared
+            .contract_queries
+            .deliver(&req_id, Ok(contract.clone()));
+        return Ok(Some(contract));
     }
-    Ok(())
+    Ok(None)
 }
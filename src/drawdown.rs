@@ -0,0 +1,298 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::task::Poll;
+
+use tokio::sync::watch;
+
+use crate::account::{AccountId, ValueKey, ValueUpdate};
+use crate::client::{indicators::Active, Client, Environment, Profile};
+use crate::contract::ContractId;
+use crate::flatten::{FlattenStyle, Holding};
+use crate::payload::Position;
+use crate::quantity::Quantity;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// What a [`DrawdownRule`] monitors.
+pub enum DrawdownScope {
+    /// The account's net liquidation value, measured from its highest value.
+    Account,
+    /// The market price of one of the account's positions, measured from its best price since
+    /// the position was opened (the highest price for a long position, or the lowest price for a
+    /// short position).
+    Position(ContractId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// What to do when a [`DrawdownRule`] is breached, which
+/// [`crate::client::Client::apply_drawdown_action`] carries out (or
+/// [`crate::client::Client::enforce_drawdown`], as the monitor's events arrive).
+pub enum DrawdownAction {
+    /// Only emit the [`DrawdownEvent`].
+    Alert,
+    /// Close part of the positions in the rule's scope.
+    Reduce {
+        /// The fraction of each position to close (ex: 0.5 to halve it), which is rounded down to
+        /// the contract's lot size.
+        fraction: f64,
+        /// How the closing orders are priced.
+        style: FlattenStyle,
+    },
+    /// Close every position in the rule's scope.
+    Flatten(FlattenStyle),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A limit on the drawdown of an account or of one of its positions.
+pub struct DrawdownRule {
+    /// What the rule monitors.
+    pub scope: DrawdownScope,
+    /// The drawdown at which the rule is breached, as a fraction of the high-water mark (ex: 0.1
+    /// for a 10% decline).
+    pub limit: f64,
+    /// What to do when the rule is breached.
+    pub action: DrawdownAction,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// An event emitted by a [`DrawdownMonitor`] when one of its rules is breached.
+pub struct DrawdownEvent {
+    /// The monitored account.
+    pub account_number: AccountId,
+    /// The rule that was breached.
+    pub rule: DrawdownRule,
+    /// The high-water mark from which the drawdown is measured.
+    pub high_water_mark: f64,
+    /// The value (or price) that breached the rule.
+    pub value: f64,
+    /// The drawdown, as a fraction of the high-water mark.
+    pub drawdown: f64,
+}
+
+#[derive(Debug)]
+/// Follows the high-water marks of an account and of its positions, and emits a
+/// [`DrawdownEvent`] whenever the decline from one of them breaches a [`DrawdownRule`]. A rule
+/// that was breached is re-armed once the drawdown falls back below its limit.
+///
+/// The values are only updated while a [`crate::client::Client::req_account_updates`]
+/// subscription for the account is active. The high-water marks are only kept in memory, so they
+/// start over from the current values when a new monitor is created.
+pub struct DrawdownMonitor {
+    account_number: AccountId,
+    net_liquidation: watch::Receiver<Option<ValueUpdate>>,
+    account: Mark,
+    positions: Vec<(ContractId, watch::Receiver<Option<Position>>, Mark)>,
+    pending: VecDeque<DrawdownEvent>,
+}
+
+#[derive(Debug, Default)]
+struct Mark {
+    best: Option<f64>,
+    short: bool,
+    rules: Vec<(DrawdownRule, bool)>,
+}
+
+// === Type implementations ===
+
+impl DrawdownMonitor {
+    #[must_use]
+    /// Create a new [`DrawdownMonitor`] for an account.
+    ///
+    /// # Arguments
+    /// * `client` - The client whose account values and positions to monitor.
+    /// * `account_number` - The account to monitor.
+    /// * `rules` - The limits on the drawdowns of the account and of its positions.
+    pub fn new<P: Profile, E: Environment>(
+        client: &Client<Active<P, E>>,
+        account_number: AccountId,
+        rules: impl IntoIterator<Item = DrawdownRule>,
+    ) -> Self {
+        let mut account = Mark::default();
+        let mut positions = Vec::<(ContractId, _, Mark)>::new();
+        for rule in rules {
+            let mark = match rule.scope {
+                DrawdownScope::Account => &mut account,
+                DrawdownScope::Position(contract_id) => {
+                    match positions.iter().position(|(id, ..)| *id == contract_id) {
+                        Some(i) => &mut positions[i].2,
+                        None => {
+                            let rx = client.watch_position(&account_number, contract_id);
+                            positions.push((contract_id, rx, Mark::default()));
+                            &mut positions.last_mut().expect("A position was just pushed").2
+                        }
+                    }
+                }
+            };
+            mark.rules.push((rule, false));
+        }
+        let mut monitor = Self {
+            net_liquidation: client.watch_account_value(&account_number, ValueKey::NetLiquidation),
+            account_number,
+            account,
+            positions,
+            pending: VecDeque::new(),
+        };
+        monitor.update();
+        monitor
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the account being monitored.
+    pub const fn get_account_number(&self) -> &AccountId {
+        &self.account_number
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get a high-water mark.
+    ///
+    /// # Returns
+    /// The highest net liquidation value of the account, or the best price of a position, or
+    /// [`None`] if no value has been received for the scope (or the position is flat).
+    pub fn get_high_water_mark(&self, scope: DrawdownScope) -> Option<f64> {
+        match scope {
+            DrawdownScope::Account => self.account.best,
+            DrawdownScope::Position(contract_id) => self
+                .positions
+                .iter()
+                .find(|(id, ..)| *id == contract_id)
+                .and_then(|(.., mark)| mark.best),
+        }
+    }
+
+    /// Wait for one of the rules to be breached.
+    ///
+    /// # Returns
+    /// The next [`DrawdownEvent`], or [`None`] if the client that supplies the values was dropped.
+    pub async fn next_event(&mut self) -> Option<DrawdownEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            {
+                let mut changes = self
+                    .positions
+                    .iter_mut()
+                    .map(|(_, rx, _)| Box::pin(rx.changed()))
+                    .collect::<Vec<_>>();
+                let position_changed = std::future::poll_fn(|cx| {
+                    changes
+                        .iter_mut()
+                        .find_map(|change| match change.as_mut().poll(cx) {
+                            Poll::Ready(res) => Some(res),
+                            Poll::Pending => None,
+                        })
+                        .map_or(Poll::Pending, Poll::Ready)
+                });
+                tokio::select! {
+                    res = self.net_liquidation.changed() => res.ok()?,
+                    res = position_changed => res.ok()?,
+                }
+            }
+            self.update();
+        }
+    }
+
+    fn update(&mut self) {
+        let account_number = &self.account_number;
+        let pending = &mut self.pending;
+        if let Some(update) = *self.net_liquidation.borrow_and_update() {
+            self.account
+                .update(update.value.to_f64(), false, account_number, pending);
+        }
+        for (_, rx, mark) in &mut self.positions {
+            let Some(position) = rx.borrow_and_update().clone() else {
+                continue;
+            };
+            if position.position.is_zero() {
+                mark.reset();
+            } else {
+                let short = position.position.is_negative();
+                if mark.short != short {
+                    mark.reset();
+                    mark.short = short;
+                }
+                mark.update(position.market_price, short, account_number, pending);
+            }
+        }
+    }
+}
+
+impl Mark {
+    #[inline]
+    fn reset(&mut self) {
+        self.best = None;
+        for (_, breached) in &mut self.rules {
+            *breached = false;
+        }
+    }
+
+    fn update(
+        &mut self,
+        value: f64,
+        short: bool,
+        account_number: &AccountId,
+        pending: &mut VecDeque<DrawdownEvent>,
+    ) {
+        if !value.is_finite() || value <= 0.0 {
+            return;
+        }
+        // The best price of a short position is its lowest price
+        let best = match (self.best, short) {
+            (Some(best), false) => best.max(value),
+            (Some(best), true) => best.min(value),
+            (None, _) => value,
+        };
+        self.best = Some(best);
+        let drawdown = if short {
+            (value - best) / best
+        } else {
+            (best - value) / best
+        };
+        for (rule, breached) in &mut self.rules {
+            match (*breached, drawdown >= rule.limit) {
+                (false, true) => pending.push_back(DrawdownEvent {
+                    account_number: account_number.clone(),
+                    rule: *rule,
+                    high_water_mark: best,
+                    value,
+                    drawdown,
+                }),
+                (true, false) => (),
+                _ => continue,
+            }
+            *breached = !*breached;
+        }
+    }
+}
+
+// === Helper functions ===
+
+/// Scale down a position to the part of it that a [`DrawdownAction::Reduce`] closes.
+///
+/// # Arguments
+/// * `holding` - The position.
+/// * `fraction` - The fraction of the position to close.
+/// * `increment` - The contract's lot size.
+///
+/// # Returns
+/// The part of the position to close, rounded down to whole lots, or [`None`] if it is smaller
+/// than a single lot.
+pub(crate) fn get_reduction(holding: Holding, fraction: f64, increment: f64) -> Option<Holding> {
+    let increment = if increment > 0.0 { increment } else { 1.0 };
+    let lots = (holding.position.to_f64() * fraction.clamp(0.0, 1.0) / increment).trunc();
+    let decimals = increment
+        .to_string()
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    let position = format!("{:.decimals$}", lots * increment)
+        .parse::<Quantity>()
+        .ok()
+        .filter(|position| !position.is_zero())?;
+    Some(Holding {
+        position,
+        market_price: holding.market_price,
+    })
+}
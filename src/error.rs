@@ -0,0 +1,219 @@
+use crate::account::AccountId;
+use crate::client::ModeMismatch;
+
+// === Type definitions ===
+
+#[derive(Debug)]
+#[non_exhaustive]
+/// The error type returned by the fallible operations of this crate.
+///
+/// New variants may be added in minor releases, so a match on this type must include a wildcard
+/// arm.
+pub enum Error {
+    /// An error was encountered while reading from or writing to the connection.
+    Io(std::io::Error),
+    /// The "config.toml" file could not be read or parsed.
+    Config(String),
+    /// The handshake that initiates a connection with IBKR's trading systems failed.
+    Handshake(String),
+    /// The IBKR platform does not support the minimum server version required by the client.
+    ServerVersion {
+        /// The minimum server version required by the client.
+        required: u32,
+        /// The server version reported by the IBKR platform.
+        actual: u32,
+    },
+    /// A value received from IBKR could not be parsed.
+    Parse(String),
+    /// An account that is not one of the client's managed accounts was provided to a request.
+    InvalidAccount {
+        /// The invalid account.
+        account_number: AccountId,
+        /// The name of the method to which the account was provided.
+        method: &'static str,
+    },
+    /// A client declared as paper trading is connected to a live trading environment.
+    ModeMismatch(ModeMismatch),
+    /// A contract returned by IBKR did not match the expected security type.
+    UnexpectedSecurityType(Box<dyn std::error::Error + Send + Sync>),
+    /// IBKR answered a request with an error message.
+    Api {
+        /// The error code.
+        code: i64,
+        /// The error message.
+        message: String,
+    },
+    /// IBKR rejected a request because it violated IBKR's pacing rules (ex: more than 60
+    /// historical data requests within ten minutes, or more than 50 messages per second). The
+    /// request may succeed if it is retried later.
+    Pacing {
+        /// The error code.
+        code: i64,
+        /// The error message.
+        message: String,
+    },
+    /// A subscription could not be written because the connection was lost.
+    Unsent {
+        /// Whether the subscription is resent when the client reconnects (see
+        /// [`crate::client::Client::set_resend_subscriptions`]).
+        resend_on_reconnect: bool,
+        /// The error encountered while writing the subscription.
+        source: std::io::Error,
+    },
+    /// An order (or a modification or cancellation of it) could not be written because the
+    /// connection was lost. Orders are never resent automatically, since part of the message may
+    /// have reached IBKR, so the order's status should be checked once the client reconnects.
+    OrderNotSent {
+        /// The ID of the order.
+        order_id: i64,
+        /// The error encountered while writing the order.
+        source: std::io::Error,
+    },
+    /// A request was not sent because it would have exceeded the client's
+    /// [`crate::client::RateLimit`].
+    RateLimited,
+    /// IBKR did not answer a request within the allotted time.
+    Timeout,
+    /// A description of a contract did not match exactly one contract. Holds the contracts that
+    /// matched, from which the intended one can be chosen.
+    AmbiguousContract(Vec<crate::contract::Contract>),
+    /// An order could not be priced because no market price is known for its contract.
+    MissingMarketPrice(crate::contract::ContractId),
+    /// A rebalance could not be planned or placed.
+    Rebalance(crate::rebalance::RebalanceError),
+    /// The positions of an account are not known, because neither
+    /// [`crate::client::Client::req_positions`] nor [`crate::client::Client::req_account_updates`]
+    /// has delivered them.
+    PositionsUnknown(AccountId),
+    /// The client loop stopped, so the request could not be completed.
+    Disconnected,
+}
+
+// === Type implementations ===
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Config(msg) => write!(f, "Invalid config file: {msg}"),
+            Self::Handshake(msg) => write!(f, "Handshake with IBKR platform failed: {msg}"),
+            Self::ServerVersion { required, actual } => write!(
+                f,
+                "IBKR platform server version {actual} is below the required version {required}"
+            ),
+            Self::Parse(msg) => write!(f, "Failed to parse value from IBKR: {msg}"),
+            Self::InvalidAccount {
+                account_number,
+                method,
+            } => write!(
+                f,
+                "Invalid account number {account_number} provided to {method}"
+            ),
+            Self::ModeMismatch(mismatch) => write!(f, "{mismatch}"),
+            Self::UnexpectedSecurityType(e) => write!(f, "Unexpected security type: {e}"),
+            Self::Api { code, message } => write!(f, "IBKR returned error {code}: {message}"),
+            Self::Pacing { code, message } => write!(f, "IBKR pacing violation {code}: {message}"),
+            Self::Unsent {
+                resend_on_reconnect,
+                source,
+            } => {
+                write!(f, "Subscription could not be sent: {source}")?;
+                if *resend_on_reconnect {
+                    write!(f, " (it will be resent upon reconnecting)")?;
+                }
+                Ok(())
+            }
+            Self::OrderNotSent { order_id, source } => {
+                write!(f, "Order {order_id} could not be sent: {source}")
+            }
+            Self::RateLimited => write!(f, "The request would exceed the rate limit"),
+            Self::Timeout => write!(f, "IBKR did not answer the request in time"),
+            Self::AmbiguousContract(matches) => write!(
+                f,
+                "Expected exactly one matching contract but found {}",
+                matches.len()
+            ),
+            Self::MissingMarketPrice(contract_id) => {
+                write!(f, "No market price is known for contract {}", contract_id.0)
+            }
+            Self::Rebalance(e) => write!(f, "Rebalance failed: {e}"),
+            Self::PositionsUnknown(account_number) => write!(
+                f,
+                "The positions of account {account_number} have not been received"
+            ),
+            Self::Disconnected => write!(f, "The client loop is no longer running"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) | Self::Unsent { source: e, .. } | Self::OrderNotSent { source: e, .. } => {
+                Some(e)
+            }
+            Self::ModeMismatch(mismatch) => Some(mismatch),
+            Self::Rebalance(e) => Some(e),
+            Self::UnexpectedSecurityType(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Return whether an error message from IBKR reports a pacing violation.
+    pub(crate) fn is_pacing(code: i64, message: &str) -> bool {
+        // 100 is the message rate limit, 420 a real-time pacing violation, and 162 a historical
+        // data error that is a pacing violation only when its message says so
+        matches!(code, 100 | 420)
+            || (code == 162 && message.to_ascii_lowercase().contains("pacing violation"))
+    }
+
+    /// Classify an error message from IBKR as a pacing violation or an ordinary API error.
+    pub(crate) fn from_api(code: i64, message: &str) -> Self {
+        if Self::is_pacing(code, message) {
+            Self::Pacing {
+                code,
+                message: message.to_owned(),
+            }
+        } else {
+            Self::Api {
+                code,
+                message: message.to_owned(),
+            }
+        }
+    }
+
+    #[inline]
+    /// Attribute an error encountered while writing an order to that order.
+    pub(crate) fn for_order(self, order_id: i64) -> Self {
+        match self {
+            Self::Io(source) => Self::OrderNotSent { order_id, source },
+            e => e,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<crate::rebalance::RebalanceError> for Error {
+    fn from(value: crate::rebalance::RebalanceError) -> Self {
+        Self::Rebalance(value)
+    }
+}
+
+impl From<ModeMismatch> for Error {
+    fn from(value: ModeMismatch) -> Self {
+        Self::ModeMismatch(value)
+    }
+}
+
+impl From<tokio::task::JoinError> for Error {
+    fn from(value: tokio::task::JoinError) -> Self {
+        Self::Io(value.into())
+    }
+}
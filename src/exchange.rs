@@ -455,6 +455,220 @@ impl FromStr for Routing {
     }
 }
 
+impl Primary {
+    #[allow(clippy::too_many_lines)]
+    #[must_use]
+    /// Get the exchange's code, as used by IBKR (ex: "NASDAQ").
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::AmsterdamseEffectenbeurs => "AEB",
+            Self::AlphaTradingSystems => "ALPHA",
+            Self::AmericanStockExchange => "AMEX",
+            Self::ApexEuronext => "APEXEN",
+            Self::ApexItaly => "APEXIT",
+            Self::AquisExchangeEuropeGermany => "AQEUDE",
+            Self::AquisExchangeEuropeEuronext => "AQEUEN",
+            Self::AquisExchangeEuropeSpain => "AQEUES",
+            Self::AquisExchangeEuropeItaly => "AQEUIT",
+            Self::Quadriserv => "AQS",
+            Self::Archipelago => "ARCA",
+            Self::Arcaedge => "ARCAEDGE",
+            Self::AustralianStockExchange => "ASX",
+            Self::AsxCentrePoint => "ASXCEN",
+            Self::BarclaysGovernmentBonds => "BARCBONDG",
+            Self::BatsTradingInc => "BATS",
+            Self::BelgianFuturesAmpOptionsExchange => "BELFOX",
+            Self::NasdaqOmxBx => "BEX",
+            Self::Bloomberg => "BLOOMBERG",
+            Self::BolsaDeMadrid => "BM",
+            Self::Bond1GovernmentBonds => "BOND1G",
+            Self::BondDesk => "BONDDESK",
+            Self::BonddeskForUsGovernmentSecurities => "BONDDESKG",
+            Self::BondDeskMunicipalBonds => "BONDDESKM",
+            Self::GovernmentBondsLargeOrders => "BONDLARGE",
+            Self::BostonOptionExchange => "BOX",
+            Self::BudapestStockExchange => "BUX",
+            Self::LisbonStockExchange => "BVL",
+            Self::BorsaValoriDiMilano => "BVME",
+            Self::BorsaItalianaEtf => "BVME.ETF",
+            Self::BatsYExchange => "BYX",
+            Self::CommerzbankAgFrankfurtCurrencyDealing2 => "CBK2FX",
+            Self::CommerzbankAgFrankfurtCurrencyDealing => "CBKFX",
+            Self::ChicagoBoardOptionsExchange => "CBOE",
+            Self::CboeJapanLimited => "CBOE.JPN",
+            Self::ChicagoBoardOptionsExchange2 => "CBOE2",
+            Self::ChicagoBoardOfTrade => "CBOT",
+            Self::CanadianDerivativesExchange => "CDE",
+            Self::CboeFuturesExchange => "CFE",
+            Self::ChicagoFuturesExchangeTradingAtSettlement => "CFETAS",
+            Self::ChinextSharesOnShenzhenStockExchange => "CHINEXT",
+            Self::ChiXCanadaAtsLimited => "CHIX_CA",
+            Self::ChiXAustralia => "CHIXAU",
+            Self::ChicagoStockExchange => "CHX",
+            Self::CitibankCurrencyDealing => "CITIFX",
+            Self::ChicagoMercantileExchange => "CME",
+            Self::CommodityExchange => "COMEX",
+            Self::CopenhagenStockExchange => "CPH",
+            Self::CreditSuisseGovernmentBondsSmallOrders => "CSBONDG",
+            Self::CsfbAlgorithmicEngine => "CSFBALGO",
+            Self::CreditSuisseCurrencyDealing => "CSFX",
+            Self::CitadelZeroCommission => "CTDLZERO",
+            Self::DirectEdgeEcnLlc => "DRCTEDGE",
+            Self::CboeGermany => "DXEDE",
+            Self::CboeEuronext => "DXEEN",
+            Self::CboeSpain => "DXEES",
+            Self::CboeEuropeBVDxeOrderBookItaly => "DXEIT",
+            Self::ElektronischeBoerseSchweiz => "EBS",
+            Self::DirectEdgeEcnEdgea => "EDGEA",
+            Self::BatsTradingEdgx => "EDGX",
+            Self::MiaxEmeraldExchange => "EMERALD",
+            Self::IceEndexFutures => "ENDEX",
+            Self::EuronextBelgium => "ENEXT.BE",
+            Self::InternalFractionalShareVenueForEuStocksAndEtfs => "EUIBFRSH",
+            Self::IbEuropeanSystematicInternaliser => "EUIBSI",
+            Self::EurexBritishMarketsForLchCrestClearing => "EUREXUK",
+            Self::FoxRiver => "FOXRIVER",
+            Self::PartnerFractionalShares => "FRACSHARE",
+            Self::FinancieleTermijnmarktAmsterdam => "FTA",
+            Self::Finra => "FINRA",
+            Self::MutualFundHoldingVenue => "FUNDSERV",
+            Self::FrankfurterWertpapierboerse => "FWB",
+            Self::NonStandardSettlementForFx => "FXSETTLE",
+            Self::IseGemini => "GEMINI",
+            Self::BRseMNchenAg => "GETTEX",
+            Self::BRseMNchenAgForCblSettlement => "GETTEX2",
+            Self::GoldmanSachsCurrencyDealing2 => "GS2FX",
+            Self::GoldmanSachsCurrencyDealing => "GSFX",
+            Self::HeadlandsTechnologies => "HEADLAND",
+            Self::HeadlandsTechnologiesMunis => "HEADLANDM",
+            Self::HelsinkiStockExchange => "HEX",
+            Self::HongKongFuturesExchange => "HKFE",
+            Self::HsbcCurrencyDealing2 => "HSBC2FX",
+            Self::HsbcCurrencyDealing => "HSBCFX",
+            Self::HartfieldTitusAndDonnelly => "HTD",
+            Self::IbCfdDealingAsiaPacific => "IBAPCFD",
+            Self::InteractiveBrokersBond => "IBBOND",
+            Self::InteractiveBrokersCommodity => "IBCMDTY",
+            Self::IbDarkPool => "IBDARK",
+            Self::IbkrOvernightExchange => "IBEOS",
+            Self::IbCurrencyDealing => "IBFX",
+            Self::IbFxCfdDealing => "IBFXCFD",
+            Self::IntegriertesBoersenhandelsUndInformationsSystem => "IBIS",
+            Self::InteractiveBrokersAssetManagement => "IBKRAM",
+            Self::IbkrNote => "IBKRNOTE",
+            Self::InternalizedTradingOfMetals => "IBMETAL",
+            Self::IbCfdDealingUs => "IBUSCFD",
+            Self::IbUsOpt => "IBUSOPT",
+            Self::IceCryptocurrency => "ICECRYPTO",
+            Self::IceFuturesUsInc => "ICEUS",
+            Self::InteractiveBrokersDealingSystem => "IDEAL",
+            Self::IbForexPro => "IDEALPRO",
+            Self::IdealCurrencyDealing => "IDEALFX",
+            Self::ItalianDerivativesMarketMilano => "IDEM",
+            Self::InvestorsExchange => "IEX",
+            Self::InternationalPetroleumExchange => "IPE",
+            Self::InterestRateRecordingExchange => "IR",
+            Self::InternationalSecuritiesExchange => "ISE",
+            Self::Island => "ISLAND",
+            Self::JaneStreetExecutionServices => "JANE",
+            Self::JaneStreetZeroCommission => "JANEZERO",
+            Self::JefferiesAlgorithmicEngine => "JEFFALGO",
+            Self::JpmcCorporateBonds => "JPMCBOND",
+            Self::Japannext => "JPNNEXT",
+            Self::KoreaStockExchange => "KSE",
+            Self::LongTermStockExchange => "LTSE",
+            Self::MarcheATermeDInstrumentsFinanciers => "MATIF",
+            Self::MercadoEspanolDeFuturosFinancierosRentaVariableProxy => "MEFFRV",
+            Self::MembersExchange => "MEMX",
+            Self::IseMercury => "MERCURY",
+            Self::MercadoMexicanoDeDerivados => "MEXDER",
+            Self::MexicoStockExchange => "MEXI",
+            Self::MiamiOptionsExchange => "MIAX",
+            Self::MillenniumAdvisorsCorporateBonds => "MILLADV",
+            Self::MarketaxessCorporates => "MKTAXESS",
+            Self::MarcheDesOptsNegDeLaBourseDeParis => "MONEP",
+            Self::MorganStanleyCurrencyDealing => "MSFX",
+            Self::NasdaqRiga => "N.RIGA",
+            Self::NasdaqTallinn => "N.TALLINN",
+            Self::AbNasdaqVilnius => "N.VILNIUS",
+            Self::NationalAssociationOfSecurityDealers => "NASDAQ",
+            Self::NasdaqOmxBxOptionsExchange => "NASDAQBX",
+            Self::NationalAssociationOfSecurityDealersOptionsMarket => "NASDAQOM",
+            Self::NatixisCurrencyDealing => "NATIXISFX",
+            Self::KnightTradingOtcbbAndPinkSheets => "NITE",
+            Self::IbkrRetailZeroCommission => "NITEZERO",
+            Self::NationalStockExchangeOfIndiaLimited => "NSE",
+            Self::NewYorkBoardOfTrade => "NYBOT",
+            Self::NewYorkMercantileExchange => "NYMEX",
+            Self::NewYorkStockExchange => "NYSE",
+            Self::NyseFloor => "NYSEFLOOR",
+            Self::NyseLiffeUs => "NYSELIFFE",
+            Self::NyseNational => "NYSENAT",
+            Self::OmegaAts => "OMEGA",
+            Self::StockholmOptionsMarket => "OMS",
+            Self::NorwegianSharesOnOmx => "OMXNO",
+            Self::OsloStockExchange => "OSE",
+            Self::OsakaStockExchange => "OSE.JPN",
+            Self::OslCryptoExchange => "OSL",
+            Self::OtcBulletinBoard => "OTCBB",
+            Self::OtcLinkEcn => "OTCLNKECN",
+            Self::OvernightTrading => "OVERNIGHT",
+            Self::PaxosCryptoExchange => "PAXOS",
+            Self::MiaxPearlExchange => "PEARL",
+            Self::PhiladelphiaStockExchange => "PHLX",
+            Self::PinkSheets => "PINK",
+            Self::PraqueStockExchange => "PRA",
+            Self::PacificStockExchange => "PSE",
+            Self::NasdaqOmxPsx => "PSX",
+            Self::PureTrading => "PURE",
+            Self::RoyalBankOfCanadaCurrencyDealing2 => "RBC2FX",
+            Self::RoyalBankOfCanadaCurrencyDealing => "RBCFX",
+            Self::RoyalBankOfScotlandCurrencyDealing => "RBSFX",
+            Self::ExchangeForRussellIndices => "RUSSELL",
+            Self::StockExchangeOfHongKong => "SEHK",
+            Self::StockExchangeHongKongNorthboundTradingLink => "SEHKNTL",
+            Self::HongKongShenzhenStockExchangeNorthboundTradingLink => "SEHKSZSE",
+            Self::StockholmFondbors => "SFB",
+            Self::SingaporeExchange => "SGX",
+            Self::SingaporeExchangeCme => "SGXCME",
+            Self::TheSmallExchange => "SMFE",
+            Self::SydneyFuturesExchange => "SNFE",
+            Self::SumridgePartners => "SUMRIDGE",
+            Self::SumridgePartnersMunicipalBonds => "SUMRIDGEM",
+            Self::StuttgartWertpapierboerse => "SWB",
+            Self::TelAvivStockExchange => "TASE",
+            Self::Tradegate => "TGATE",
+            Self::TurquoiseGlobalHoldingsEuropeBVGermany => "TGHEDE",
+            Self::TurquoiseGlobalHoldingsEuropeBVEuronext => "TGHEEN",
+            Self::TurquoiseGlobalHoldingsEuropeBVSpain => "TGHEES",
+            Self::TurquoiseGlobalHoldingsBVItaly => "TGHEIT",
+            Self::ThFxCfdDealing => "THFXCFD",
+            Self::TPlusOne => "TPLUS1",
+            Self::TradewebCorporate => "TRADEWEB",
+            Self::TradewebGovernment => "TRADEWEBG",
+            Self::TorontoStockExchange => "TSE",
+            Self::TokyoStockExchange => "TSEJ",
+            Self::UbsCurrencyDealing2 => "UBS2FX",
+            Self::UbsCorporateBond => "UBSBOND",
+            Self::UbsCurrencyDealing => "UBSFX",
+            Self::KnightValuebondCorporate => "VALUBOND",
+            Self::KnightValuebondGovernment => "VALUBONDG",
+            Self::MunicipalBondsOnValuebond => "VALUBONDM",
+            Self::TsxVentureExchange => "VENTURE",
+            Self::VirtuFinancialGovernmentBonds => "VIRTBONDG",
+            Self::ViennaStockExchange => "VSE",
+            Self::WellsFargoForex => "WFFX",
+            Self::WarsawStockExchange => "WSE",
+        }
+    }
+}
+
+impl std::fmt::Display for Primary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl FromStr for Primary {
     type Err = ParseExchangeError;
 
@@ -1,21 +1,38 @@
+use crate::account::AccountId;
 use crate::comm::serialize_naive_datetime_yyyymmdd_hhcolon_mm_colon_ss;
+use crate::contract::{Contract, ContractId};
+use crate::currency::Currency;
 use crate::exchange::Primary;
-use chrono::NaiveDateTime;
+use crate::quantity::{Price, Quantity};
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize)]
+/// The conditions with which to determine whether an execution will be returned by
+/// [`crate::client::Client::req_executions`].
 pub struct Filter {
+    /// The ID of the client that placed the orders.
     pub client_id: i64,
-    pub account_number: String,
+    /// The account in which the orders were executed.
+    pub account_number: AccountId,
     #[serde(serialize_with = "serialize_naive_datetime_yyyymmdd_hhcolon_mm_colon_ss")]
+    /// The time after which the executions occurred.
     pub start_time: NaiveDateTime,
+    /// The symbol of the executed contracts.
     pub symbol: String,
+    /// The type of the executed contracts.
     pub contract_type: ContractType,
+    /// The exchange on which the executions occurred.
     pub exchange: Primary,
+    /// The side of the executions.
     pub side: OrderSide,
 }
 
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize)]
+/// The types of contracts by which executions can be filtered.
 pub enum ContractType {
     #[serde(rename(serialize = "CASH"))]
     /// A [`crate::contract::Forex`] contract.
@@ -47,9 +64,443 @@ pub enum ContractType {
 }
 
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, PartialEq, Eq, Hash, Serialize)]
+/// The side of an execution.
 pub enum OrderSide {
     #[serde(rename(serialize = "BUY"))]
+    /// A purchase.
     Buy,
     #[serde(rename(serialize = "SELL"))]
+    /// A sale.
     Sell,
 }
+
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+/// An error type returned when a given side cannot be matched with a valid [`OrderSide`]
+pub struct ParseOrderSideError(String);
+
+impl std::fmt::Display for ParseOrderSideError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid order side {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseOrderSideError {}
+
+impl std::fmt::Display for OrderSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Buy => "BUY",
+            Self::Sell => "SELL",
+        })
+    }
+}
+
+impl FromStr for OrderSide {
+    type Err = ParseOrderSideError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "BOT" | "BUY" => Self::Buy,
+            "SLD" | "SELL" => Self::Sell,
+            s => return Err(ParseOrderSideError(s.to_owned())),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A single (possibly partial) fill of an order.
+pub struct Execution {
+    /// The unique identifier of the execution.
+    pub execution_id: String,
+    /// The ID of the order that was executed.
+    pub order_id: i64,
+    /// The ID of the executed contract.
+    pub contract_id: ContractId,
+    /// The symbol of the executed contract.
+    pub symbol: String,
+    /// The time at which the execution occurred.
+    pub datetime: NaiveDateTime,
+    /// The account in which the execution occurred.
+    pub account_number: AccountId,
+    /// The exchange on which the execution occurred.
+    pub exchange: String,
+    /// The side of the execution.
+    pub side: OrderSide,
+    /// The number of shares / units executed.
+    pub quantity: Quantity,
+    /// The price at which the execution occurred.
+    pub price: Price,
+    /// The order's permanent ID, which remains the same across API sessions.
+    pub perm_id: i64,
+    /// The ID of the client that placed the order.
+    pub client_id: i64,
+    /// Whether the execution was the result of a liquidation by IBKR.
+    pub liquidation: bool,
+    /// The cumulative number of shares / units executed for the order.
+    pub cumulative_quantity: Quantity,
+    /// The average price of all of the order's executions.
+    pub average_price: Price,
+    /// The reference attached to the order.
+    pub order_reference: String,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// An [`Execution`] combined with the full definition of the contract that was executed.
+pub struct ResolvedExecution {
+    /// The execution.
+    pub execution: Execution,
+    /// The contract that was executed.
+    pub contract: Contract,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// The commission and realized P&L associated with a single [`Execution`].
+pub struct CommissionReport {
+    /// The unique identifier of the [`Execution`] to which the report belongs.
+    pub execution_id: String,
+    /// The commission charged for the execution.
+    pub commission: f64,
+    /// The currency in which the commission was charged.
+    pub currency: Currency,
+    /// The P&L realized by the execution, if any.
+    pub realized_pnl: Option<f64>,
+    /// The income return of a bond execution, if any.
+    pub yld: Option<f64>,
+    /// The redemption date associated with the yield of a bond execution, if any.
+    pub yield_redemption_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+/// Aggregated commission information for a group of executions.
+pub struct CommissionTotals {
+    /// The total commission charged.
+    pub commission: f64,
+    /// The total realized P&L.
+    pub realized_pnl: f64,
+    /// The number of executions in the group.
+    pub executions: u64,
+    yield_sum: f64,
+    yield_count: u64,
+}
+
+impl CommissionTotals {
+    #[inline]
+    #[must_use]
+    /// Return the average yield of the bond executions in the group.
+    ///
+    /// # Returns
+    /// The average yield, if any of the executions reported a yield; otherwise, [`None`].
+    pub fn average_yield(&self) -> Option<f64> {
+        #[allow(clippy::cast_precision_loss)]
+        (self.yield_count > 0).then(|| self.yield_sum / self.yield_count as f64)
+    }
+
+    #[inline]
+    fn add(&mut self, report: &CommissionReport) {
+        self.commission += report.commission;
+        self.realized_pnl += report.realized_pnl.unwrap_or(0.0);
+        self.executions += 1;
+        if let Some(yld) = report.yld {
+            self.yield_sum += yld;
+            self.yield_count += 1;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+/// A summary of all the commission reports received by a client, aggregated by trading day and
+/// by contract.
+///
+/// Each [`CommissionReport`] is joined with the [`Execution`] to which it belongs, so a report is
+/// only included in the summary once its execution has been received.
+pub struct CommissionSummary {
+    executions: HashMap<String, (NaiveDate, ContractId)>,
+    reports: HashMap<String, CommissionReport>,
+    applied: HashSet<String>,
+    by_day: BTreeMap<NaiveDate, CommissionTotals>,
+    by_contract: HashMap<ContractId, CommissionTotals>,
+}
+
+impl CommissionSummary {
+    #[inline]
+    #[must_use]
+    /// Return the totals for each trading day.
+    pub const fn by_day(&self) -> &BTreeMap<NaiveDate, CommissionTotals> {
+        &self.by_day
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the totals for each contract.
+    pub const fn by_contract(&self) -> &HashMap<ContractId, CommissionTotals> {
+        &self.by_contract
+    }
+
+    #[must_use]
+    /// Return the totals across all trading days and contracts.
+    pub fn total(&self) -> CommissionTotals {
+        self.by_day
+            .values()
+            .fold(CommissionTotals::default(), |mut acc, day| {
+                acc.commission += day.commission;
+                acc.realized_pnl += day.realized_pnl;
+                acc.executions += day.executions;
+                acc.yield_sum += day.yield_sum;
+                acc.yield_count += day.yield_count;
+                acc
+            })
+    }
+
+    pub(crate) fn add_execution(&mut self, execution: &Execution) {
+        if self.applied.contains(&execution.execution_id) {
+            return;
+        }
+        let key = (execution.datetime.date(), execution.contract_id);
+        match self.reports.remove(&execution.execution_id) {
+            Some(report) => self.apply(key, &report),
+            None => {
+                self.executions.insert(execution.execution_id.clone(), key);
+            }
+        }
+    }
+
+    pub(crate) fn add_report(&mut self, report: CommissionReport) {
+        if self.applied.contains(&report.execution_id) {
+            return;
+        }
+        match self.executions.remove(&report.execution_id) {
+            Some(key) => self.apply(key, &report),
+            None => {
+                self.reports.insert(report.execution_id.clone(), report);
+            }
+        }
+    }
+
+    fn apply(&mut self, (day, contract_id): (NaiveDate, ContractId), report: &CommissionReport) {
+        self.by_day.entry(day).or_default().add(report);
+        self.by_contract.entry(contract_id).or_default().add(report);
+        self.applied.insert(report.execution_id.clone());
+    }
+}
+
+// === Blotter ===
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A single fill recorded by a [`Blotter`].
+pub struct BlotterEntry {
+    /// The unique identifier of the execution.
+    pub execution_id: String,
+    /// The account in which the execution occurred.
+    pub account_number: AccountId,
+    /// The symbol of the executed contract.
+    pub symbol: String,
+    /// The side of the execution.
+    pub side: OrderSide,
+    /// The number of shares / units executed.
+    pub quantity: Quantity,
+    /// The price at which the execution occurred.
+    pub price: Price,
+    /// The time at which the execution occurred.
+    pub datetime: NaiveDateTime,
+    /// The commission charged for the execution, once its [`CommissionReport`] has been received.
+    pub commission: Option<f64>,
+    /// The currency in which the commission was charged, once its [`CommissionReport`] has been
+    /// received.
+    pub commission_currency: Option<Currency>,
+    /// The exchange on which the execution occurred.
+    pub exchange: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+/// A record of all the fills received by a client during a session, in the order in which they
+/// were received.
+///
+/// Each [`Execution`] is joined with its [`CommissionReport`], and the blotter can be exported as
+/// CSV or JSON for end-of-day reporting.
+pub struct Blotter {
+    entries: Vec<BlotterEntry>,
+    index: HashMap<String, usize>,
+    reports: HashMap<String, CommissionReport>,
+}
+
+impl Blotter {
+    const HEADER: [&'static str; 10] = [
+        "execution_id",
+        "account",
+        "symbol",
+        "side",
+        "quantity",
+        "price",
+        "time",
+        "commission",
+        "commission_currency",
+        "exchange",
+    ];
+
+    #[inline]
+    #[must_use]
+    /// Return the recorded fills.
+    pub fn get_entries(&self) -> &[BlotterEntry] {
+        &self.entries
+    }
+
+    #[must_use]
+    /// Create a blotter that contains only the fills that occurred on a given trading day.
+    ///
+    /// # Arguments
+    /// * `day` - The trading day whose fills should be kept.
+    ///
+    /// # Returns
+    /// A new [`Blotter`] for the day.
+    pub fn end_of_day(&self, day: NaiveDate) -> Self {
+        let mut blotter = Self::default();
+        for entry in self.entries.iter().filter(|e| e.datetime.date() == day) {
+            blotter
+                .index
+                .insert(entry.execution_id.clone(), blotter.entries.len());
+            blotter.entries.push(entry.clone());
+        }
+        blotter
+    }
+
+    /// Write the blotter as CSV, with a header row followed by one row per fill.
+    ///
+    /// # Arguments
+    /// * `writer` - The destination of the CSV data.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing to `writer`.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "{}", Self::HEADER.join(","))?;
+        for entry in &self.entries {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&entry.execution_id),
+                csv_field(&entry.account_number.0),
+                csv_field(&entry.symbol),
+                entry.side,
+                entry.quantity,
+                entry.price,
+                entry.datetime.format("%Y-%m-%dT%H:%M:%S"),
+                entry.commission.map(|c| c.to_string()).unwrap_or_default(),
+                entry
+                    .commission_currency
+                    .map(|c| c.to_string())
+                    .unwrap_or_default(),
+                csv_field(&entry.exchange),
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Write the blotter as a JSON array, with one object per fill.
+    ///
+    /// # Arguments
+    /// * `writer` - The destination of the JSON data.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing to `writer`.
+    pub fn write_json<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(b"[")?;
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            let values = [
+                json_string(&entry.execution_id),
+                json_string(&entry.account_number.0),
+                json_string(&entry.symbol),
+                json_string(&entry.side.to_string()),
+                entry.quantity.to_string(),
+                entry.price.to_string(),
+                json_string(&entry.datetime.format("%Y-%m-%dT%H:%M:%S").to_string()),
+                entry
+                    .commission
+                    .map_or_else(|| "null".to_owned(), json_number),
+                entry
+                    .commission_currency
+                    .map_or_else(|| "null".to_owned(), |c| json_string(&c.to_string())),
+                json_string(&entry.exchange),
+            ];
+            writer.write_all(b"{")?;
+            for (j, (key, value)) in Self::HEADER.iter().zip(values).enumerate() {
+                if j > 0 {
+                    writer.write_all(b",")?;
+                }
+                write!(writer, "\"{key}\":{value}")?;
+            }
+            writer.write_all(b"}")?;
+        }
+        writer.write_all(b"]")?;
+        writer.flush()
+    }
+
+    pub(crate) fn add_execution(&mut self, execution: &Execution) {
+        if self.index.contains_key(&execution.execution_id) {
+            return;
+        }
+        let report = self.reports.remove(&execution.execution_id);
+        self.index
+            .insert(execution.execution_id.clone(), self.entries.len());
+        self.entries.push(BlotterEntry {
+            execution_id: execution.execution_id.clone(),
+            account_number: execution.account_number.clone(),
+            symbol: execution.symbol.clone(),
+            side: execution.side,
+            quantity: execution.quantity,
+            price: execution.price,
+            datetime: execution.datetime,
+            commission: report.as_ref().map(|r| r.commission),
+            commission_currency: report.map(|r| r.currency),
+            exchange: execution.exchange.clone(),
+        });
+    }
+
+    pub(crate) fn add_report(&mut self, report: &CommissionReport) {
+        match self.index.get(&report.execution_id) {
+            Some(&i) => {
+                self.entries[i].commission = Some(report.commission);
+                self.entries[i].commission_currency = Some(report.currency);
+            }
+            None => {
+                self.reports
+                    .insert(report.execution_id.clone(), report.clone());
+            }
+        }
+    }
+}
+
+#[inline]
+fn csv_field(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+#[inline]
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[inline]
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::account::AccountId;
+use crate::client::{indicators::Active, Client, Environment, Profile};
+use crate::contract::{Contract, Forex, Security};
+use crate::currency::Currency;
+use crate::error::Error;
+
+// === Type definitions ===
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+/// The exposure of an account to a single currency.
+pub struct CurrencyExposure {
+    /// The cash balance held in the currency.
+    pub cash: f64,
+    /// The market value of the positions denominated in the currency.
+    pub positions: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// An account's exposure to each currency in which it holds cash or positions, as returned by
+/// [`crate::client::Client::currency_exposure`].
+pub struct ExposureReport {
+    account_number: AccountId,
+    exposures: HashMap<Currency, CurrencyExposure>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A set of exchange rates used to convert an [`ExposureReport`] into a single currency.
+///
+/// Each rate is the value of one unit of a currency in the table's base currency, so conversions
+/// between any two currencies in the table are triangulated through the base currency.
+pub struct FxRates {
+    base: Currency,
+    rates: HashMap<Currency, f64>,
+}
+
+// === Type implementations ===
+
+impl CurrencyExposure {
+    #[inline]
+    #[must_use]
+    /// Return the total exposure to the currency (cash and positions).
+    pub fn total(&self) -> f64 {
+        self.cash + self.positions
+    }
+}
+
+impl ExposureReport {
+    #[inline]
+    #[must_use]
+    pub(crate) fn new(
+        account_number: AccountId,
+        exposures: HashMap<Currency, CurrencyExposure>,
+    ) -> Self {
+        Self {
+            account_number,
+            exposures,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the account to which the report belongs.
+    pub const fn get_account_number(&self) -> &AccountId {
+        &self.account_number
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the account's exposure to a single currency.
+    ///
+    /// # Arguments
+    /// * `currency` - The currency whose exposure to return.
+    pub fn get(&self, currency: Currency) -> Option<&CurrencyExposure> {
+        self.exposures.get(&currency)
+    }
+
+    #[inline]
+    /// Iterate over each currency and the account's exposure to it.
+    pub fn iter(&self) -> impl Iterator<Item = (Currency, &CurrencyExposure)> {
+        self.exposures
+            .iter()
+            .map(|(currency, exp)| (*currency, exp))
+    }
+
+    #[must_use]
+    /// Convert the total exposure to each currency into a single currency.
+    ///
+    /// # Arguments
+    /// * `rates` - The exchange rates with which to convert the exposures.
+    /// * `target` - The currency into which the exposures are converted.
+    ///
+    /// # Returns
+    /// The converted exposure to each currency, or [`None`] if `rates` is missing the rate of any
+    /// currency in the report.
+    pub fn convert(&self, rates: &FxRates, target: Currency) -> Option<HashMap<Currency, f64>> {
+        self.exposures
+            .iter()
+            .map(|(currency, exp)| {
+                Some((*currency, rates.convert(exp.total(), *currency, target)?))
+            })
+            .collect()
+    }
+
+    #[must_use]
+    /// Sum the account's exposure to every currency in a single currency.
+    ///
+    /// # Arguments
+    /// * `rates` - The exchange rates with which to convert the exposures.
+    /// * `target` - The currency into which the exposures are converted.
+    ///
+    /// # Returns
+    /// The total exposure, or [`None`] if `rates` is missing the rate of any currency in the
+    /// report.
+    pub fn net(&self, rates: &FxRates, target: Currency) -> Option<f64> {
+        Some(self.convert(rates, target)?.values().sum())
+    }
+}
+
+impl FxRates {
+    #[inline]
+    #[must_use]
+    /// Create a new table of exchange rates that holds only the rate of its base currency.
+    ///
+    /// # Arguments
+    /// * `base` - The currency in which every rate is expressed.
+    pub fn new(base: Currency) -> Self {
+        Self {
+            base,
+            rates: HashMap::from([(base, 1.0)]),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Add an exchange rate to the table.
+    ///
+    /// # Arguments
+    /// * `currency` - The currency whose rate to set.
+    /// * `rate` - The value of one unit of `currency` in the base currency.
+    pub fn with_rate(mut self, currency: Currency, rate: f64) -> Self {
+        self.set_rate(currency, rate);
+        self
+    }
+
+    #[inline]
+    /// Set an exchange rate in the table, replacing any existing rate.
+    ///
+    /// # Arguments
+    /// * `currency` - The currency whose rate to set.
+    /// * `rate` - The value of one unit of `currency` in the base currency.
+    pub fn set_rate(&mut self, currency: Currency, rate: f64) {
+        if currency != self.base {
+            self.rates.insert(currency, rate);
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the table's base currency.
+    pub const fn get_base(&self) -> Currency {
+        self.base
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the value of one unit of a currency in the base currency.
+    ///
+    /// # Arguments
+    /// * `currency` - The currency whose rate to return.
+    pub fn get_rate(&self, currency: Currency) -> Option<f64> {
+        self.rates.get(&currency).copied()
+    }
+
+    #[must_use]
+    /// Convert an amount from one currency into another.
+    ///
+    /// # Arguments
+    /// * `amount` - The amount to convert.
+    /// * `from` - The currency in which `amount` is denominated.
+    /// * `to` - The currency into which `amount` is converted.
+    ///
+    /// # Returns
+    /// The converted amount, or [`None`] if the table is missing the rate of either currency.
+    pub fn convert(&self, amount: f64, from: Currency, to: Currency) -> Option<f64> {
+        if from == to {
+            return Some(amount);
+        }
+        Some(amount * self.get_rate(from)? / self.get_rate(to)?)
+    }
+
+    /// Build a table of exchange rates from snapshot quotes of IDEALPRO currency pairs.
+    ///
+    /// Each pair contributes the midpoint of its quote (see [`crate::payload::Quote::midpoint`]).
+    /// Pairs that do not involve the base currency are triangulated through the rates of the
+    /// other pairs, so (for example) a EUR.GBP pair provides the rate of EUR if the rate of GBP is
+    /// also known.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to request the quotes.
+    /// * `base` - The currency in which every rate is expressed.
+    /// * `pairs` - The currency pairs to quote.
+    /// * `timeout` - The maximum amount of time to wait for the quotes.
+    ///
+    /// # Errors
+    /// Returns any error encountered while requesting the quotes.
+    ///
+    /// # Returns
+    /// The table of exchange rates. Pairs without a quote, or whose currencies cannot be
+    /// triangulated, are omitted.
+    pub async fn from_quotes<P: Profile, E: Environment>(
+        client: &mut Client<Active<P, E>>,
+        base: Currency,
+        pairs: &[Forex],
+        timeout: std::time::Duration,
+    ) -> Result<Self, Error> {
+        let contracts = pairs
+            .iter()
+            .cloned()
+            .map(Contract::Forex)
+            .collect::<Vec<_>>();
+        let quotes = client.req_quotes(&contracts, pairs.len(), timeout).await?;
+
+        let mut pending = pairs
+            .iter()
+            .filter_map(|pair| {
+                let mid = quotes.get(&pair.get_contract_id())?.midpoint()?;
+                Some((
+                    pair.get_symbol().parse::<Currency>().ok()?,
+                    pair.get_currency(),
+                    mid,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        let mut rates = Self::new(base);
+        loop {
+            let before = pending.len();
+            pending.retain(|&(symbol, currency, mid)| {
+                match (rates.get_rate(symbol), rates.get_rate(currency)) {
+                    (None, Some(rate)) => rates.set_rate(symbol, mid * rate),
+                    (Some(rate), None) => rates.set_rate(currency, rate / mid),
+                    (Some(_), Some(_)) => (),
+                    (None, None) => return true,
+                }
+                false
+            });
+            if pending.is_empty() || pending.len() == before {
+                break;
+            }
+        }
+        Ok(rates)
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::account::AccountId;
+use crate::contract::{Contract, ContractId, SecOption};
+use crate::error::Error;
+use crate::execution::OrderSide;
+use crate::payload::OrderStatus;
+use crate::quantity::{Price, Quantity};
+use crate::tracker::{OrderEvent, OrderTracker};
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// How the orders that close a position are priced.
+pub enum FlattenStyle {
+    /// A market order, which closes the position as quickly as possible at any price.
+    Market,
+    /// A limit order priced through the position's most recent market price, so that it fills
+    /// immediately in most markets while bounding the price at which it can fill. The market price
+    /// is reported by [`crate::client::Client::req_account_updates`], which must therefore be
+    /// active for the position's account.
+    AggressiveLimit {
+        /// The distance of the limit price from the market price, as a fraction of the market
+        /// price (ex: 0.005 for a limit 0.5% through the market).
+        slippage: f64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A single event delivered by a [`Flattening`].
+pub enum FlattenEvent {
+    /// An event for the order that closes a position.
+    Order {
+        /// The account that holds the position.
+        account_number: AccountId,
+        /// The ID of the position's contract.
+        contract_id: ContractId,
+        /// The event.
+        event: Box<OrderEvent>,
+    },
+    /// The order that closes a position was filled, so the position is flat.
+    Flat {
+        /// The account that held the position.
+        account_number: AccountId,
+        /// The ID of the position's contract.
+        contract_id: ContractId,
+    },
+    /// The order that closes a position was done without being filled (ex: it was cancelled or
+    /// rejected), so the position is still open.
+    Failed {
+        /// The account that holds the position.
+        account_number: AccountId,
+        /// The ID of the position's contract.
+        contract_id: ContractId,
+        /// The final status of the order.
+        status: OrderStatus,
+    },
+}
+
+#[derive(Debug, Default)]
+/// A handle to the orders placed by [`crate::client::Client::flatten_position`] and
+/// [`crate::client::Client::flatten_all`], which merges their events into a single
+/// [`futures_core::Stream`] of [`FlattenEvent`]s that ends once every order is done.
+///
+/// Dropping the handle stops the delivery of its events, but it does not cancel the orders.
+pub struct Flattening {
+    orders: Vec<Closing>,
+    pending: VecDeque<FlattenEvent>,
+}
+
+#[derive(Debug)]
+struct Closing {
+    account_number: AccountId,
+    contract_id: ContractId,
+    tracker: OrderTracker,
+    done: bool,
+    flat: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A position as last reported by IBKR, which the client tracks so that it can be closed.
+pub(crate) struct Holding {
+    pub(crate) position: Quantity,
+    pub(crate) market_price: Option<f64>,
+}
+
+// === Type implementations ===
+
+impl FlattenStyle {
+    /// Get the limit price of the order that closes a position.
+    ///
+    /// # Arguments
+    /// * `side` - The side of the closing order.
+    /// * `market_price` - The position's most recent market price.
+    /// * `contract` - The position's contract.
+    ///
+    /// # Errors
+    /// Returns [`Error::MissingMarketPrice`] if the market price is not a finite number.
+    ///
+    /// # Returns
+    /// The limit price, or [`None`] for a market order.
+    pub(crate) fn get_limit_price(
+        self,
+        side: OrderSide,
+        market_price: f64,
+        contract: &Contract,
+    ) -> Result<Option<Price>, Error> {
+        match self {
+            Self::Market => Ok(None),
+            Self::AggressiveLimit { slippage } => {
+                get_marketable_price(side, market_price, slippage, get_min_tick(contract))
+                    .map(Some)
+                    .ok_or(Error::MissingMarketPrice(contract.get_contract_id()))
+            }
+        }
+    }
+}
+
+impl Flattening {
+    #[inline]
+    pub(crate) fn push(
+        &mut self,
+        account_number: AccountId,
+        contract_id: ContractId,
+        tracker: OrderTracker,
+    ) {
+        self.orders.push(Closing {
+            account_number,
+            contract_id,
+            tracker,
+            done: false,
+            flat: false,
+        });
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the IDs of the orders that close the positions.
+    pub fn get_order_ids(&self) -> Vec<i64> {
+        self.orders
+            .iter()
+            .map(|closing| closing.tracker.get_order_id())
+            .collect()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Check whether every position is flat, as of the last event received from the handle.
+    pub fn is_flat(&self) -> bool {
+        self.orders.iter().all(|closing| closing.flat)
+    }
+
+    #[inline]
+    /// Wait for the next event.
+    ///
+    /// # Returns
+    /// The next event, or [`None`] once every order is done or the client loop stopped.
+    pub async fn recv(&mut self) -> Option<FlattenEvent> {
+        std::future::poll_fn(|cx| self.poll_event(cx)).await
+    }
+
+    /// Wait until every order is done, discarding any intermediate events.
+    ///
+    /// # Returns
+    /// Returns [`true`] if every position is flat.
+    pub async fn done(&mut self) -> bool {
+        while self.recv().await.is_some() {}
+        self.is_flat()
+    }
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<FlattenEvent>> {
+        if let Some(event) = self.pending.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+        for closing in self.orders.iter_mut().filter(|closing| !closing.done) {
+            match Pin::new(&mut closing.tracker).poll_next(cx) {
+                Poll::Ready(Some(event)) => {
+                    if let OrderEvent::Transition { update, .. } = &event {
+                        if update.status.is_done() {
+                            closing.done = true;
+                            closing.flat = update.status == OrderStatus::Filled;
+                            self.pending.push_back(if closing.flat {
+                                FlattenEvent::Flat {
+                                    account_number: closing.account_number.clone(),
+                                    contract_id: closing.contract_id,
+                                }
+                            } else {
+                                FlattenEvent::Failed {
+                                    account_number: closing.account_number.clone(),
+                                    contract_id: closing.contract_id,
+                                    status: update.status,
+                                }
+                            });
+                        }
+                    }
+                    return Poll::Ready(Some(FlattenEvent::Order {
+                        account_number: closing.account_number.clone(),
+                        contract_id: closing.contract_id,
+                        event: Box::new(event),
+                    }));
+                }
+                // The client loop stopped
+                Poll::Ready(None) => closing.done = true,
+                Poll::Pending => (),
+            }
+        }
+        if self.orders.iter().all(|closing| closing.done) {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Stream for Flattening {
+    type Item = FlattenEvent;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_event(cx)
+    }
+}
+
+// === Helper functions ===
+
+/// Get the price of a limit order that lies through the market by a fraction of the market price,
+/// rounded away from the market to the contract's minimum price increment.
+///
+/// # Arguments
+/// * `side` - The side of the order.
+/// * `market_price` - The market price.
+/// * `slippage` - The distance from the market price, as a fraction of the market price.
+/// * `min_tick` - The contract's minimum price increment, if any.
+///
+/// # Returns
+/// The price, or [`None`] if it is not a finite number.
+pub(crate) fn get_marketable_price(
+    side: OrderSide,
+    market_price: f64,
+    slippage: f64,
+    min_tick: Option<f64>,
+) -> Option<Price> {
+    // A sale is aggressive below the market, and a purchase above it
+    let price = match side {
+        OrderSide::Buy => market_price * (1.0 + slippage),
+        OrderSide::Sell => market_price * (1.0 - slippage),
+    };
+    let Some(tick) = min_tick.filter(|tick| *tick > 0.0) else {
+        return Price::try_from(price).ok();
+    };
+    let ticks = match side {
+        OrderSide::Buy => (price / tick).ceil(),
+        OrderSide::Sell => (price / tick).floor(),
+    };
+    // Cut the floating-point residue of the rounding (ex: 100.01000000000001) at the tick's
+    // decimal places
+    let decimals = tick
+        .to_string()
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    format!("{:.decimals$}", ticks * tick).parse().ok()
+}
+
+#[inline]
+/// Get a contract's minimum price increment.
+pub(crate) fn get_min_tick(contract: &Contract) -> Option<f64> {
+    match contract {
+        Contract::Forex(fx) => Some(fx.min_tick),
+        Contract::Crypto(crypto) => Some(crypto.min_tick),
+        Contract::Stock(stk) => Some(stk.min_tick),
+        Contract::Index(ind) => Some(ind.min_tick),
+        Contract::SecFuture(fut) => Some(fut.min_tick),
+        Contract::SecOption(SecOption::Call(inner) | SecOption::Put(inner)) => Some(inner.min_tick),
+        Contract::Commodity(cmdty) => Some(cmdty.min_tick),
+        Contract::Combo(_) => None,
+    }
+}
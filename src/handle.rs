@@ -0,0 +1,115 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::client::{indicators::Active, Client, Environment, Profile, Trading, Unverified};
+use crate::error::Error;
+
+// === Type definitions ===
+
+type Job<P, E> = Box<
+    dyn for<'c> FnOnce(
+            &'c mut Client<Active<P, E>>,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send + 'c>>
+        + Send,
+>;
+
+/// A cheap, cloneable handle through which many tasks can issue requests over a single
+/// connection.
+///
+/// The client is moved into its own task, which acts as a serialized executor: it runs the
+/// requests submitted by every handle one at a time, in the order in which they are received, and
+/// never starts a request before the previous one has finished. Since writing a request does not
+/// wait for its answer, the tasks that share a handle can issue requests concurrently without
+/// coordinating among themselves.
+///
+/// A request which waits for its answer (such as [`Client::req_current_time_await`], a contract
+/// lookup, or [`Client::recv_historical_bars`]) holds the client until the answer arrives, and
+/// every other handle's requests wait behind it. Such requests are better made sparingly through a
+/// handle, or through a client of their own.
+pub struct ClientHandle<P: Profile = Trading, E: Environment = Unverified> {
+    tx: mpsc::UnboundedSender<Job<P, E>>,
+}
+
+// === Type implementations ===
+
+impl<P: Profile, E: Environment> ClientHandle<P, E> {
+    #[must_use]
+    /// Move a client into its own task and create the first handle to it.
+    ///
+    /// # Arguments
+    /// * `client` - The client through which the handles' requests are made.
+    ///
+    /// # Returns
+    /// The handle, along with the task that owns the client. Once every handle has been dropped,
+    /// the task finishes and returns the client, which can then be disconnected.
+    pub fn new(mut client: Client<Active<P, E>>) -> (Self, JoinHandle<Client<Active<P, E>>>) {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Job<P, E>>();
+        let task = tokio::spawn(async move {
+            while let Some(job) = rx.recv().await {
+                job(&mut client).await;
+            }
+            client
+        });
+        (Self { tx }, task)
+    }
+
+    /// Run a request with the client and return its result.
+    ///
+    /// The request runs once every request submitted before it has finished, and delays every
+    /// request submitted after it until it finishes (see [`ClientHandle`]), so it should not wait
+    /// longer than necessary.
+    ///
+    /// # Arguments
+    /// * `request` - A function that makes the request with the client, such as
+    /// `|client| Box::pin(async move { client.req_current_time().await })`.
+    ///
+    /// # Errors
+    /// Returns [`Error::Disconnected`] if the task that owns the client has stopped.
+    ///
+    /// # Returns
+    /// The value returned by `request`.
+    pub async fn run<T, F>(&self, request: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: for<'c> FnOnce(
+                &'c mut Client<Active<P, E>>,
+            ) -> Pin<Box<dyn Future<Output = T> + Send + 'c>>
+            + Send
+            + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let job: Job<P, E> = Box::new(move |client| {
+            Box::pin(async move {
+                let _ = tx.send(request(client).await);
+            })
+        });
+        self.tx.send(job).map_err(|_| Error::Disconnected)?;
+        rx.await.map_err(|_| Error::Disconnected)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Check whether the task that owns the client is still running.
+    pub fn is_connected(&self) -> bool {
+        !self.tx.is_closed()
+    }
+}
+
+impl<P: Profile, E: Environment> Clone for ClientHandle<P, E> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<P: Profile, E: Environment> std::fmt::Debug for ClientHandle<P, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientHandle")
+            .field("connected", &self.is_connected())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,207 @@
+use chrono::NaiveDateTime;
+
+use crate::calendar::Session;
+use crate::payload::Bar;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A single problem found in a series of bars by [`check_bars`].
+pub enum Anomaly {
+    /// A bar is timestamped earlier than the bar before it.
+    NonMonotonic {
+        /// The index of the bar.
+        index: usize,
+        /// The timestamp of the previous bar.
+        previous: NaiveDateTime,
+        /// The timestamp of the bar.
+        datetime: NaiveDateTime,
+    },
+    /// A bar has the same timestamp as the bar before it.
+    Duplicate {
+        /// The index of the bar.
+        index: usize,
+        /// The timestamp shared by both bars.
+        datetime: NaiveDateTime,
+    },
+    /// A bar of trades has a negative volume.
+    NegativeVolume {
+        /// The index of the bar.
+        index: usize,
+        /// The timestamp of the bar.
+        datetime: NaiveDateTime,
+        /// The bar's volume.
+        volume: f64,
+    },
+    /// A trading session within the span of the bars is not fully covered by bars.
+    MissingBars {
+        /// The session.
+        session: Session,
+        /// The number of bars that cover the session in full.
+        expected: usize,
+        /// The number of bars that fall within the session.
+        found: usize,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq, PartialOrd)]
+/// The outcome of validating a series of bars with [`check_bars`].
+pub struct IntegrityReport {
+    /// The number of bars that were checked.
+    pub bar_count: usize,
+    /// The problems that were found, in the order in which they were found.
+    pub anomalies: Vec<Anomaly>,
+}
+
+// === Type implementations ===
+
+impl IntegrityReport {
+    #[inline]
+    #[must_use]
+    /// Return whether no problems were found.
+    pub fn is_clean(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the problems that were found.
+    pub fn get_anomalies(&self) -> &[Anomaly] {
+        &self.anomalies
+    }
+}
+
+impl std::fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonMonotonic {
+                index,
+                previous,
+                datetime,
+            } => write!(
+                f,
+                "Bar {index} at {datetime} precedes the previous bar at {previous}"
+            ),
+            Self::Duplicate { index, datetime } => {
+                write!(f, "Bar {index} duplicates the previous bar at {datetime}")
+            }
+            Self::NegativeVolume {
+                index,
+                datetime,
+                volume,
+            } => write!(f, "Bar {index} at {datetime} has negative volume {volume}"),
+            Self::MissingBars {
+                session,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Session from {} to {} has {found} of {expected} bars",
+                session.open, session.close
+            ),
+        }
+    }
+}
+
+// === Functions ===
+
+/// Validate a series of bars, such as those received in
+/// [`crate::wrapper::Local::historical_bars`] (or [`crate::wrapper::Remote::historical_bars`]),
+/// before they are stored or used in a backtest.
+///
+/// The bars are checked for timestamps that go backwards, duplicated timestamps and negative
+/// volumes. When sessions are given, every session that lies entirely between the first bar's
+/// start and the last bar's end must also be covered by bars. Since the sessions are only compared
+/// with the bars' start times, coverage is not checked for resolutions of a day or more.
+///
+/// # Arguments
+/// * `bars` - The bars, in the order in which they were received.
+/// * `resolution` - The length of each bar.
+/// * `sessions` - The contract's trading sessions over the span of the bars, against which to
+/// check the session coverage, such as those of a
+/// [`crate::client::Client::req_historical_schedule`] request converted with [`Session::from`].
+///
+/// # Returns
+/// A report of every problem that was found.
+pub fn check_bars(
+    bars: &[Bar],
+    resolution: std::time::Duration,
+    sessions: &[Session],
+) -> IntegrityReport {
+    let mut anomalies = Vec::new();
+    let mut previous: Option<NaiveDateTime> = None;
+    for (index, bar) in bars.iter().enumerate() {
+        let datetime = bar.core().datetime;
+        match previous {
+            Some(previous) if datetime < previous => anomalies.push(Anomaly::NonMonotonic {
+                index,
+                previous,
+                datetime,
+            }),
+            Some(previous) if datetime == previous => {
+                anomalies.push(Anomaly::Duplicate { index, datetime });
+            }
+            _ => (),
+        }
+        if let Bar::Trades { volume, .. } = *bar {
+            if volume < 0.0 {
+                anomalies.push(Anomaly::NegativeVolume {
+                    index,
+                    datetime,
+                    volume,
+                });
+            }
+        }
+        previous = Some(datetime);
+    }
+
+    let resolution = chrono::Duration::from_std(resolution).unwrap_or(chrono::Duration::MAX);
+    if resolution < chrono::Duration::days(1) {
+        anomalies.extend(check_coverage(bars, resolution, sessions));
+    }
+
+    IntegrityReport {
+        bar_count: bars.len(),
+        anomalies,
+    }
+}
+
+// === Helper functions ===
+
+/// Find the sessions between the first and last bars that are not fully covered by bars.
+fn check_coverage<'s>(
+    bars: &[Bar],
+    resolution: chrono::Duration,
+    sessions: &'s [Session],
+) -> impl Iterator<Item = Anomaly> + 's {
+    let mut starts = bars
+        .iter()
+        .map(|bar| bar.core().datetime.and_utc())
+        .collect::<Vec<_>>();
+    starts.sort_unstable();
+    starts.dedup();
+    let span = starts.first().copied().zip(
+        starts
+            .last()
+            .and_then(|&last| last.checked_add_signed(resolution)),
+    );
+
+    sessions.iter().filter_map(move |&session| {
+        let (first, end) = span?;
+        if session.open < first || session.close > end || resolution <= chrono::Duration::zero() {
+            return None;
+        }
+        let length = (session.close - session.open).num_seconds();
+        let step = resolution.num_seconds().max(1);
+        let expected = usize::try_from((length + step - 1) / step).unwrap_or(usize::MAX);
+        let found = starts
+            .iter()
+            .filter(|&&start| session.open <= start && start < session.close)
+            .count();
+        (found < expected).then_some(Anomaly::MissingBars {
+            session,
+            expected,
+            found,
+        })
+    })
+}
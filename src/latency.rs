@@ -0,0 +1,95 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+// === Constants ===
+
+/// The number of most recent round trips from which [`LatencyStats`] are computed.
+const SAMPLE_SIZE: usize = 1000;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Aggregate statistics of the time between placing an order and receiving its first order status,
+/// as returned by [`crate::client::Client::get_order_latency_stats`].
+pub struct LatencyStats {
+    /// The number of round trips from which the statistics were computed.
+    pub count: usize,
+    /// The shortest round trip.
+    pub min: Duration,
+    /// The median round trip.
+    pub p50: Duration,
+    /// The 99th percentile round trip.
+    pub p99: Duration,
+    /// The longest round trip.
+    pub max: Duration,
+}
+
+#[derive(Debug, Default)]
+/// Measures the time between writing each order and receiving its first order status.
+pub(crate) struct LatencyTracker {
+    placed: HashMap<i64, Instant>,
+    measured: HashMap<i64, Duration>,
+    recent: VecDeque<Duration>,
+}
+
+// === Type implementations ===
+
+impl LatencyTracker {
+    #[inline]
+    /// Record that an order is about to be written.
+    pub(crate) fn placed(&mut self, order_id: i64) {
+        self.placed.insert(order_id, Instant::now());
+    }
+
+    #[inline]
+    /// Forget an order that could not be written, or that IBKR answered with an error, so that it
+    /// does not wait for an order status that may never arrive.
+    pub(crate) fn cancel(&mut self, order_id: i64) {
+        self.placed.remove(&order_id);
+    }
+
+    /// Record that an order status was received. Only the first status after an order is placed
+    /// completes its round trip, and the order's round trip is forgotten once the order is done
+    /// (although it still counts towards the [`LatencyStats`]).
+    ///
+    /// # Arguments
+    /// * `order_id` - The ID of the order.
+    /// * `done` - Whether the status is final (see [`crate::payload::OrderStatus::is_done`]).
+    pub(crate) fn status(&mut self, order_id: i64, done: bool) {
+        if let Some(placed) = self.placed.remove(&order_id) {
+            let latency = placed.elapsed();
+            self.measured.insert(order_id, latency);
+            if self.recent.len() == SAMPLE_SIZE {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(latency);
+        }
+        if done {
+            self.measured.remove(&order_id);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, order_id: i64) -> Option<Duration> {
+        self.measured.get(&order_id).copied()
+    }
+
+    pub(crate) fn stats(&self) -> Option<LatencyStats> {
+        if self.recent.is_empty() {
+            return None;
+        }
+        let mut sorted = self.recent.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        // Nearest-rank percentiles, which are always one of the measured round trips
+        let percentile = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+        Some(LatencyStats {
+            count: sorted.len(),
+            min: sorted[0],
+            p50: percentile(50),
+            p99: percentile(99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
@@ -12,14 +12,34 @@
     clippy::single_char_lifetime_names
 )]
 
+#[macro_use]
+mod trace;
+
 /// Contains types related to account information.
 pub mod account;
+/// Contains the [`alert::Alerts`] type, which runs user-defined hooks (or posts to webhooks) when
+/// critical events occur.
+pub mod alert;
+#[cfg(feature = "bridge")]
+/// Contains the [`bridge::Bridge`] type, which shares a client's connection with other processes on
+/// the same machine over a local socket, using the protobuf messages described in
+/// [`bridge::PROTO`].
+pub mod bridge;
+/// Contains the [`calendar::Calendar`] type, which bundles the holidays and session times of
+/// exchanges, and the [`calendar::TradingHours`] that IBKR reports for each contract.
+pub mod calendar;
 /// Contains the all-important [`client::Client`] struct and its methods, which facilitate
 /// communication with the IBKR. Also contains a [`client::Builder`] struct to manage the
 /// creation of new connections.
 pub mod client;
+/// Contains the [`clock::Clock`] trait, which supplies the current time, and the
+/// [`clock::ReplayClock`] that replays a period of time at a controllable speed.
+pub mod clock;
 mod comm;
 mod constants;
+/// Contains the [`context::Contexts`] type, a store that attaches user context to request IDs so
+/// that it can be looked up from any callback that receives a given request's ID.
+pub mod context;
 /// Contains the definitions of all [`contract::Security`] implementors, which represent tradable
 /// contracts.
 ///
@@ -27,6 +47,9 @@ mod constants;
 /// enum. They all implement the [`contract::Security`] trait, which means they are a valid IBKR
 /// contract and that they have at least one valid order type.
 pub mod contract;
+/// Contains the [`corporate::reconcile`] function, which detects splits and dividends in
+/// re-downloaded adjusted bars and brings cached bars up to date.
+pub mod corporate;
 /// Contains the definition of a [`currency::Currency`] enum, which represents the possible trading
 /// currencies available in the API.
 pub mod currency;
@@ -39,9 +62,34 @@ pub mod currency;
     clippy::unused_async
 )]
 mod decode;
+/// Contains the [`drawdown::DrawdownMonitor`] type, which follows the high-water marks of an
+/// account and its positions and emits events when their drawdowns breach a set of rules.
+pub mod drawdown;
+/// Contains the crate's [`error::Error`] type, which is returned by all of its fallible operations.
+pub mod error;
 /// Contains types related to security exchanges and trading venues available in the API.
 pub mod exchange;
-mod execution;
+/// Contains types related to executions and their commissions, as well as the filters used to
+/// request them.
+pub mod execution;
+/// Contains the [`exposure::ExposureReport`] type, which aggregates an account's exposure to each
+/// currency, and the [`exposure::FxRates`] used to convert it into a single currency.
+pub mod exposure;
+/// Contains the [`flatten::Flattening`] type, which follows the orders that close a set of
+/// positions until they are flat.
+pub mod flatten;
+/// Contains the [`handle::ClientHandle`] type, which allows many tasks to issue requests over a
+/// single connection.
+pub mod handle;
+/// Contains the [`integrity::check_bars`] function, which validates downloaded bars and reports
+/// any anomalies in an [`integrity::IntegrityReport`].
+pub mod integrity;
+/// Contains the [`latency::LatencyStats`] type, which summarizes the time IBKR takes to acknowledge
+/// orders.
+pub mod latency;
+/// Contains the [`margin::MarginMonitor`] type, which emits events when an account's margin
+/// utilization crosses a set of thresholds.
+pub mod margin;
 /// Contains modules that each relate to different market data requests. In particular, each module
 /// defines: 1) General types used in a given market data query and 2) Optionally, a private
 /// indicator trait that defines whether a given [`contract::Security`] allows for the data request
@@ -50,10 +98,60 @@ pub mod market_data;
 mod message;
 /// Contains types and traits related to orders.
 pub mod order;
+/// Contains the [`order_id::OrderIdStore`] trait, which persists the highest order ID used by a
+/// client across restarts, and the [`order_id::FileOrderIdStore`] that keeps it in a file.
+pub mod order_id;
+mod pacing;
 /// Contains the types that are parsed from API callbacks. They are used in the [`wrapper::Local`] and
 /// [`wrapper::Remote`] callback functions.
 pub mod payload;
+/// Contains the [`quantity::Quantity`] and [`quantity::Price`] types, which represent fractional
+/// share and crypto quantities and prices exactly.
+pub mod quantity;
 mod reader;
+/// Contains the [`rebalance::Rebalancer`] type, which computes and places the orders that move an
+/// account's positions to a set of targets.
+pub mod rebalance;
+/// Contains the [`resample::TickAggregator`] type, which builds bars of any resolution from
+/// historical trade ticks, and the [`resample::MultiResolution`] type, which builds synchronized
+/// bars of several resolutions from shorter bars.
+pub mod resample;
+/// Contains types related to market scanner subscriptions, which are created in
+/// [`client::Client::req_scanner_subscription`].
+pub mod scanner;
+/// Contains the [`schedule::Schedule`] type, which describes the one-shot and recurring timers that
+/// a client delivers to its wrapper.
+pub mod schedule;
+#[cfg(all(feature = "shm", unix))]
+/// Contains the [`shm::TickPublisher`] type, which publishes price and size ticks to a ring buffer
+/// in shared memory for colocated processes, and the [`shm::TickReader`] that reads them.
+pub mod shm;
+/// Contains the [`simulate::Simulator`] type, which fills orders against historical data for
+/// backtests.
+pub mod simulate;
+#[cfg(feature = "sink")]
+/// Contains the [`sink::Sink`] type, which republishes ticks, bars and fills to Redis pub/sub or
+/// NATS.
+pub mod sink;
+/// Contains the [`slicer::Slicer`] type, which executes a large order as a schedule of smaller
+/// child orders placed by the client.
+pub mod slicer;
+/// Contains the [`stops::StopBook`] type, which holds the client-side stop losses that
+/// [`client::Client::trigger_soft_stops`] checks against live prices.
+pub mod stops;
+/// Contains the [`store::BarStore`] trait, which keeps series of bars between sessions, and the
+/// [`store::HistoricalDownloader`] that fills a store and keeps it up to date.
+pub mod store;
+/// Contains the [`strategy::Strategy`] trait, along with the runners that drive a strategy from a
+/// live client or from a backtest.
+pub mod strategy;
+/// Contains the [`stream::EventStream`] type, which delivers the events of a single subscription as
+/// a [`futures_core::Stream`].
+pub mod stream;
+#[cfg(feature = "ta")]
+/// Contains the [`ta::Indicator`] trait and the common technical indicators that implement it,
+/// which are updated incrementally from live bars or ticks and in batch from historical bars.
+pub mod ta;
 /// Contains modules, types, and functions related to live data subscriptions, namely those
 /// that are created in [`client::Client::req_market_data`].
 ///
@@ -67,6 +165,19 @@ mod reader;
 ///this module, each of our groups gets its own submodule and corresponds one-to-one with a
 /// [`wrapper::Local`] or [`wrapper::Remote`] method.
 pub mod tick;
+/// Contains utilities that convert the timestamps of bars and ticks into the time zone of the
+/// exchange on which a security trades, as reported by [`client::Client::get_time_zone`].
+pub mod timezone;
+#[cfg(feature = "tls")]
+/// Contains the [`tls::TlsConfig`] type, which encrypts the connection to an IBKR platform (such
+/// as the SSL port of IB Gateway) when passed to [`client::Builder::with_tls`].
+pub mod tls;
+/// Contains the [`tracker::OrderTracker`] type, which follows a single order from placement until
+/// it is filled or cancelled.
+pub mod tracker;
+/// Contains the [`watchlist::Watchlist`] type, which manages the market data subscriptions for a
+/// set of securities and merges their data into a single stream.
+pub mod watchlist;
 /// Contains the definition of the [`wrapper::Local`] and [`wrapper::Remote`] traits. Implementing these traits for a
 /// type allows users to customize callback behavior.
 pub mod wrapper;
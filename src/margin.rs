@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+
+use tokio::sync::watch;
+
+use crate::account::{AccountId, ValueKey, ValueUpdate};
+use crate::alert::{Alert, Alerts};
+use crate::client::{indicators::Active, Client, Environment, Profile};
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// An event emitted by a [`MarginMonitor`] when an account's margin utilization crosses one of
+/// its thresholds.
+pub enum MarginEvent {
+    /// The margin utilization rose above the threshold.
+    Exceeded {
+        /// The threshold that was crossed.
+        threshold: f64,
+        /// The margin utilization that crossed the threshold.
+        utilization: f64,
+    },
+    /// The margin utilization fell back to or below the threshold.
+    Recovered {
+        /// The threshold that was crossed.
+        threshold: f64,
+        /// The margin utilization that crossed the threshold.
+        utilization: f64,
+    },
+}
+
+#[derive(Debug)]
+/// Combines the account value updates of a single account into its margin utilization, which is
+/// the ratio of its maintenance margin requirement to its net liquidation value, and emits a
+/// [`MarginEvent`] whenever the utilization crosses one of a set of thresholds.
+///
+/// The account values are only updated while a
+/// [`crate::client::Client::req_account_updates`] subscription for the account is active. Each
+/// time a threshold is exceeded, an [`Alert::MarginThreshold`] is also fired through the client's
+/// [`Alerts`].
+pub struct MarginMonitor {
+    account_number: AccountId,
+    net_liquidation: watch::Receiver<Option<ValueUpdate>>,
+    maintenance_margin: watch::Receiver<Option<ValueUpdate>>,
+    thresholds: Vec<(f64, bool)>,
+    pending: VecDeque<MarginEvent>,
+    alerts: Alerts,
+}
+
+// === Type implementations ===
+
+impl MarginMonitor {
+    #[must_use]
+    /// Create a new [`MarginMonitor`] for an account.
+    ///
+    /// # Arguments
+    /// * `client` - The client whose account values to monitor.
+    /// * `account_number` - The account to monitor.
+    /// * `thresholds` - The margin utilizations at which to emit events (ex: 0.8 for a maintenance
+    /// margin requirement of 80% of the net liquidation value).
+    pub fn new<P: Profile, E: Environment>(
+        client: &Client<Active<P, E>>,
+        account_number: AccountId,
+        thresholds: impl IntoIterator<Item = f64>,
+    ) -> Self {
+        let mut thresholds = thresholds
+            .into_iter()
+            .map(|threshold| (threshold, false))
+            .collect::<Vec<_>>();
+        thresholds.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut monitor = Self {
+            net_liquidation: client.watch_account_value(&account_number, ValueKey::NetLiquidation),
+            maintenance_margin: client
+                .watch_account_value(&account_number, ValueKey::MaintenanceMarginReq),
+            account_number,
+            thresholds,
+            pending: VecDeque::new(),
+            alerts: client.get_alerts(),
+        };
+        monitor.update();
+        monitor
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the account being monitored.
+    pub const fn get_account_number(&self) -> &AccountId {
+        &self.account_number
+    }
+
+    #[must_use]
+    /// Get the account's current margin utilization.
+    ///
+    /// # Returns
+    /// The ratio of the maintenance margin requirement to the net liquidation value, or [`None`]
+    /// if either value has not yet been received, if they are denominated differently, or if the
+    /// net liquidation value is not positive.
+    pub fn utilization(&self) -> Option<f64> {
+        let net_liquidation = (*self.net_liquidation.borrow())?;
+        let maintenance_margin = (*self.maintenance_margin.borrow())?;
+        let net_liquidation_value = net_liquidation.value.to_f64();
+        (net_liquidation.denomination == maintenance_margin.denomination
+            && net_liquidation_value > 0.0)
+            .then(|| maintenance_margin.value.to_f64() / net_liquidation_value)
+    }
+
+    #[must_use]
+    /// Get the account's current margin headroom.
+    ///
+    /// # Returns
+    /// The difference between the net liquidation value and the maintenance margin requirement,
+    /// or [`None`] if either value has not yet been received or if they are denominated
+    /// differently.
+    pub fn headroom(&self) -> Option<ValueUpdate> {
+        let net_liquidation = (*self.net_liquidation.borrow())?;
+        let maintenance_margin = (*self.maintenance_margin.borrow())?;
+        (net_liquidation.denomination == maintenance_margin.denomination).then_some(ValueUpdate {
+            value: net_liquidation.value - maintenance_margin.value,
+            denomination: net_liquidation.denomination,
+        })
+    }
+
+    /// Wait for the account's margin utilization to cross one of the thresholds.
+    ///
+    /// # Returns
+    /// The next [`MarginEvent`], or [`None`] if the client that supplies the account values was
+    /// dropped.
+    pub async fn next_event(&mut self) -> Option<MarginEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            tokio::select! {
+                res = self.net_liquidation.changed() => res.ok()?,
+                res = self.maintenance_margin.changed() => res.ok()?,
+            }
+            self.update();
+        }
+    }
+
+    fn update(&mut self) {
+        let Some(utilization) = self.utilization() else {
+            return;
+        };
+        for (threshold, exceeded) in &mut self.thresholds {
+            match (*exceeded, utilization > *threshold) {
+                (false, true) => {
+                    self.alerts.fire(Alert::MarginThreshold {
+                        account_number: self.account_number.clone(),
+                        threshold: *threshold,
+                        utilization,
+                    });
+                    self.pending.push_back(MarginEvent::Exceeded {
+                        threshold: *threshold,
+                        utilization,
+                    });
+                }
+                (true, false) => self.pending.push_back(MarginEvent::Recovered {
+                    threshold: *threshold,
+                    utilization,
+                }),
+                _ => continue,
+            }
+            *exceeded = !*exceeded;
+        }
+    }
+}
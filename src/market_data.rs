@@ -59,8 +59,10 @@ pub mod historical_bar {
     pub enum EndDateTime {
         /// The present moment.
         Present,
-        /// Some date and time in the past.
+        /// Some date and time in the past, which TWS interprets in its own time zone.
         Past(chrono::NaiveDateTime),
+        /// Some date and time in the past, given in UTC.
+        PastUtc(chrono::NaiveDateTime),
     }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -155,6 +157,7 @@ pub mod historical_bar {
         {
             match *self {
                 Self::Past(dt) => Some(dt.format("%Y%m%d %H%M%S").to_string()),
+                Self::PastUtc(dt) => Some(dt.format("%Y%m%d-%H:%M:%S").to_string()),
                 Self::Present => None,
             }
             .serialize(serializer)
@@ -237,11 +240,22 @@ pub mod historical_bar {
             /// The realized volatility during the bar interval.
             HistoricalVolatility: "HISTORICAL_VOLATILITY",
             /// The options market implied volatility during the bar interval.
-            SecOptionImpliedVolatility: "OPTION_IMPLIED_VOLATILITY"
+            SecOptionImpliedVolatility: "OPTION_IMPLIED_VOLATILITY",
+            /// The traded prices during the bar interval, adjusted for splits and dividends (only up to the present).
+            AdjustedLast: "ADJUSTED_LAST"
         );
 
         impl_data_type!(
-            (Trades, Midpoint, Bid, Ask, BidAsk, HistoricalVolatility, SecOptionImpliedVolatility);
+            (
+                Trades,
+                Midpoint,
+                Bid,
+                Ask,
+                BidAsk,
+                HistoricalVolatility,
+                SecOptionImpliedVolatility,
+                AdjustedLast
+            );
             (Stock)
         );
 
@@ -547,7 +561,7 @@ pub mod live_data {
     /// Contains the potential data types for a [`crate::client::Client::req_market_data`] request.
     pub mod data_types {
         use crate::contract::{
-            Commodity, Crypto, Forex, Index, SecFuture, SecOption, Security, Stock,
+            Combo, Commodity, Crypto, Forex, Index, SecFuture, SecOption, Security, Stock,
         };
 
         make_variants!(
@@ -571,6 +585,8 @@ pub mod live_data {
             AuctionValues: "225",
             /// Last trade's price, size, and time.
             RealTimeVolume: "233",
+            /// Last trade's price, size, and time, excluding unreportable trades.
+            RealTimeTradeVolume: "375",
             /// The level of difficulty associated with short-selling a security.
             Shortable: "236",
             /// Available inventory for short-selling.
@@ -597,6 +613,7 @@ pub mod live_data {
                 MarkPrice,
                 AuctionValues,
                 RealTimeVolume,
+                RealTimeTradeVolume,
                 Shortable,
                 Inventory,
                 FundamentalRatios,
@@ -614,6 +631,7 @@ pub mod live_data {
                 MarkPrice,
                 AuctionValues,
                 RealTimeVolume,
+                RealTimeTradeVolume,
                 Shortable,
                 Inventory,
                 FundamentalRatios,
@@ -623,6 +641,8 @@ pub mod live_data {
             );
             (Forex, SecOption, SecFuture, Crypto, Index, Commodity)
         );
+
+        impl_data_type!((Empty); (Combo));
     }
 }
 
@@ -655,3 +675,67 @@ pub mod live_ticks {
         );
     }
 }
+
+/// Contains types used by [`crate::client::Client::req_fundamental_data`].
+pub mod fundamental {
+
+    // === Type definitions ===
+
+    use serde::{Serialize, Serializer};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// The fundamental data reports available from Refinitiv (formerly Reuters), each of which is
+    /// returned as an XML document.
+    pub enum ReportType {
+        /// A company overview, with its key ratios and business summary ("ReportSnapshot").
+        Snapshot,
+        /// A summary of the company's financials, such as its earnings and dividends per share
+        /// ("ReportsFinSummary").
+        FinancialSummary,
+        /// The company's financial ratios ("ReportRatios").
+        Ratios,
+        /// The company's income statements, balance sheets and cash flow statements
+        /// ("ReportsFinStatements").
+        FinancialStatements,
+        /// The consensus of analyst estimates ("RESC").
+        AnalystEstimates,
+        /// The company's calendar of events, such as earnings announcements ("CalendarReport").
+        Calendar,
+        /// The company's ownership, by holder ("ReportsOwnership").
+        Ownership,
+    }
+
+    // === Type implementations ===
+
+    impl ReportType {
+        #[inline]
+        #[must_use]
+        /// Get the name by which IBKR identifies the report.
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Snapshot => "ReportSnapshot",
+                Self::FinancialSummary => "ReportsFinSummary",
+                Self::Ratios => "ReportRatios",
+                Self::FinancialStatements => "ReportsFinStatements",
+                Self::AnalystEstimates => "RESC",
+                Self::Calendar => "CalendarReport",
+                Self::Ownership => "ReportsOwnership",
+            }
+        }
+    }
+
+    impl std::fmt::Display for ReportType {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.as_str())
+        }
+    }
+
+    impl Serialize for ReportType {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            self.as_str().serialize(serializer)
+        }
+    }
+}
@@ -1,7 +1,5 @@
 use std::str::FromStr;
 
-use crate::contract::{Contract, ContractId};
-
 #[derive(Debug, Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct InvalidInMsg(pub String);
 
@@ -368,14 +366,3 @@ pub enum Out {
     #[serde(rename(serialize = "104"))]
     ReqUserInfo,
 }
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum ToWrapper {
-    ContractQuery((ContractId, i64)),
-}
-
-#[allow(clippy::redundant_pub_crate)]
-#[derive(Debug, Clone, PartialEq)]
-pub(crate) enum ToClient {
-    NewContract(Contract),
-}
@@ -1,4 +1,12 @@
-use crate::contract::{Commodity, Crypto, Forex, Index, SecFuture, SecOption, Security, Stock};
+use crate::account::AccountId;
+use crate::contract::{
+    Combo, ComboLeg, Commodity, ContractId, Crypto, DeltaNeutralField, Forex, Index, SecFuture,
+    SecOption, Security, Stock,
+};
+use crate::exchange::Routing;
+use crate::execution::{ContractType, OrderSide};
+use crate::quantity::{Price, Quantity};
+use chrono::{Datelike, NaiveDate};
 use serde::ser::SerializeTuple;
 use serde::{Serialize, Serializer};
 use std::collections::HashMap;
@@ -7,151 +15,2150 @@ use std::ops::Deref;
 use std::rc::Rc;
 use std::str::FromStr;
 
+/// The UTC format in which IBKR accepts a "Good After Time" or "Good Till Date".
+const UTC_ORDER_TIME_FORMAT: &str = "%Y%m%d-%H:%M:%S";
+
 // ==============================================
 // === Core Order Types (Market, Limit, etc.) ===
 // ==============================================
 
 // === Type definitions ===
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
-/// The time periods for which an order is active and can be executed against.
-pub enum TimeInForce {
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The time periods for which an order is active and can be executed against.
+pub enum TimeInForce {
+    #[default]
+    /// Valid for the day only.
+    Day,
+    /// Good until canceled. The order will continue to work within the system and in the marketplace until it executes or is canceled. GTC orders will be automatically be cancelled under the following conditions:
+    /// If a corporate action on a security results in a stock split (forward or reverse), exchange for shares, or distribution of shares. If you do not log into your IB account for 90 days.
+    /// At the end of the calendar quarter following the current quarter. For example, an order placed during the third quarter of 2011 will be canceled at the end of the first quarter of 2012. If the last day is a non-trading day, the cancellation will occur at the close of the final trading day of that quarter. For example, if the last day of the quarter is Sunday, the orders will be cancelled on the preceding Friday.
+    /// Orders that are modified will be assigned a new “Auto Expire” date consistent with the end of the calendar quarter following the current quarter.
+    /// Orders submitted to IB that remain in force for more than one day will not be reduced for dividends. To allow adjustment to your order price on ex-dividend date, consider using a Good-Til-Date/Time (GTD) or Good-after-Time/Date (GAT) order type, or a combination of the two.
+    Gtc,
+    /// Immediate or Cancel. Any portion that is not filled as soon as it becomes available in the market is canceled.
+    Ioc,
+    /// Good until Date. It will remain working within the system and in the marketplace until it executes or until the specified time.
+    Gtd(chrono::DateTime<chrono::Utc>),
+    /// Use OPG to send a market-on-open (MOO) or limit-on-open (LOO) order.
+    Opg,
+    /// If the entire Fill-or-Kill order does not execute as soon as it becomes available, the entire order is canceled.
+    Fok,
+    /// Day until canceled.
+    Dtc,
+}
+
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+/// A basic error type that represents an invalid [`TimeInForce`]
+pub struct ParseTimeInForceError(String);
+
+impl std::fmt::Display for ParseTimeInForceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid time in force: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseTimeInForceError {}
+
+impl FromStr for TimeInForce {
+    type Err = ParseTimeInForceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "DAY" => Self::Day,
+            "GTC" => Self::Gtc,
+            "IOC" => Self::Ioc,
+            "OPG" => Self::Opg,
+            "FOK" => Self::Fok,
+            "DTC" => Self::Dtc,
+            _ => return Err(ParseTimeInForceError(s.to_owned())),
+        })
+    }
+}
+
+impl TimeInForce {
+    #[inline]
+    const fn get_code(&self) -> &'static str {
+        match self {
+            Self::Day => "DAY",
+            Self::Gtc => "GTC",
+            Self::Ioc => "IOC",
+            Self::Gtd(_) => "GTD",
+            Self::Opg => "OPG",
+            Self::Fok => "FOK",
+            Self::Dtc => "DTC",
+        }
+    }
+}
+
+impl Serialize for TimeInForce {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // The expiry of a GTD order is serialized separately, as its "Good Till Date"
+        serializer.serialize_str(self.get_code())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A generic order to buy or sell a security `S`: `Security` according to the parameters specified by the generic
+/// parameter `E`: `Executable`.
+pub enum Order<S: Security, E: Executable<S>> {
+    /// An order to Buy `S`: `Security`] according to the method described by `E`: `Executable`.
+    Buy {
+        /// The security to buy.
+        security: Rc<S>,
+        /// The execution method to use.
+        execute_method: Rc<E>,
+        /// Additional attributes that apply regardless of the execution method.
+        attributes: Attributes,
+    },
+    /// An order to Sell `S`: `Security` according to the method described by `E`: `Executable`.
+    Sell {
+        /// The security to sell.
+        security: Rc<S>,
+        /// The execution method to use.
+        execute_method: Rc<E>,
+        /// Additional attributes that apply regardless of the execution method.
+        attributes: Attributes,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq, PartialOrd)]
+/// Order attributes that apply to an [`Order`] regardless of its execution method. When an
+/// attribute is set, it takes precedence over the corresponding value from the [`Executable`].
+pub struct Attributes {
+    /// The ID of the parent order to which the order is attached.
+    pub parent_id: Option<i64>,
+    /// Whether the order is transmitted immediately. When `false`, the order is only staged until
+    /// another order in the same group is transmitted.
+    pub transmit: Option<bool>,
+    /// Whether the order can trigger or fill outside of regular trading hours.
+    pub outside_regular_trading_hours: Option<bool>,
+    /// The name of the One-Cancels-All group to which the order belongs.
+    pub one_cancels_all_group: Option<String>,
+    /// How the other orders in the One-Cancels-All group are handled when the order executes.
+    pub one_cancels_all_type: Option<OneCancelsAllType>,
+    /// The conditions that must be met before the order is submitted (or cancelled).
+    pub conditions: Option<Conditions>,
+    /// The IB algorithm with which the order is executed.
+    pub algo: Option<Algo>,
+    /// Whether the order is only previewed for its margin and commission impact, rather than
+    /// placed.
+    pub what_if: Option<bool>,
+    /// The amount of currency to buy or sell, in place of the order's quantity.
+    pub cash_quantity: Option<f64>,
+    /// The time after which the order becomes active.
+    pub good_after_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// The quantity that is displayed on the book, for an iceberg order.
+    pub display_size: Option<Quantity>,
+    /// Whether the order is hidden from the book (NASDAQ-routed orders only).
+    pub hidden: Option<bool>,
+    /// Whether the order is filled at the best available prices across every exchange.
+    pub sweep_to_fill: Option<bool>,
+    /// Whether the order must be filled in its entirety in a single execution.
+    pub all_or_none: Option<bool>,
+    /// Whether the broker may use its discretion over the time and price of the execution.
+    pub not_held: Option<bool>,
+    /// The limit price of each leg of a [`Combo`] order, in the same order as the combo's legs.
+    pub combo_leg_prices: Option<Vec<Option<Price>>>,
+    /// The account to which the order is allocated, in place of the default account.
+    pub account: Option<AccountId>,
+    /// The financial advisor group across whose accounts the order is allocated.
+    pub fa_group: Option<String>,
+    /// The method by which the order is allocated across the accounts of the advisor group.
+    pub fa_method: Option<FaMethod>,
+    /// The percentage by which each account's position changes, for the
+    /// [`FaMethod::PercentChange`] method.
+    pub fa_percentage: Option<f64>,
+    /// The financial advisor allocation profile according to which the order is allocated.
+    ///
+    /// Since server version 177, profiles are merged into groups, so the profile is sent as the
+    /// order's group when no [`Attributes::fa_group`] is set.
+    pub fa_profile: Option<String>,
+}
+
+impl<Sec, E> Serialize for Order<Sec, E>
+where
+    Sec: Security,
+    E: Executable<Sec>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut ser = serializer.serialize_tuple(1 + crate::constants::ORDER_TUPLE_SIZE)?;
+        let (action, security, exec, attributes) = match self {
+            Self::Buy {
+                security,
+                execute_method,
+                attributes,
+            } => ("BUY", security, execute_method, attributes),
+            Self::Sell {
+                security,
+                execute_method,
+                attributes,
+            } => ("SELL", security, execute_method, attributes),
+        };
+        ser.serialize_element(action)?;
+        serialize_executable(Rc::deref(exec), attributes, Rc::deref(security), &mut ser)?;
+        ser.end()
+    }
+}
+
+impl<S: Security, E: Executable<S>> Order<S, E> {
+    #[inline]
+    #[must_use]
+    /// Create a new order to buy a security.
+    ///
+    /// # Arguments
+    /// * `security` - The security to buy.
+    /// * `execute_method` - The execution method to use.
+    pub fn buy(security: S, execute_method: E) -> Self {
+        Self::Buy {
+            security: Rc::new(security),
+            execute_method: Rc::new(execute_method),
+            attributes: Attributes::default(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Create a new order to sell a security.
+    ///
+    /// # Arguments
+    /// * `security` - The security to sell.
+    /// * `execute_method` - The execution method to use.
+    pub fn sell(security: S, execute_method: E) -> Self {
+        Self::Sell {
+            security: Rc::new(security),
+            execute_method: Rc::new(execute_method),
+            attributes: Attributes::default(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Create a new order to buy or sell a security.
+    ///
+    /// # Arguments
+    /// * `side` - Whether to buy or sell the security.
+    /// * `security` - The security to buy or sell.
+    /// * `execute_method` - The execution method to use.
+    pub fn new(side: OrderSide, security: S, execute_method: E) -> Self {
+        match side {
+            OrderSide::Buy => Self::buy(security, execute_method),
+            OrderSide::Sell => Self::sell(security, execute_method),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Attach the order to a parent order, such that it is only active once the parent order is
+    /// filled (ex: a stop loss or profit taker attached to an entry order).
+    ///
+    /// Note that [`crate::client::Client::req_attach_order`] sets this automatically.
+    ///
+    /// # Arguments
+    /// * `parent_id` - The ID of the parent order.
+    pub fn attach_to(mut self, parent_id: i64) -> Self {
+        self.get_attributes_mut().parent_id = Some(parent_id);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether the order is transmitted immediately.
+    ///
+    /// # Arguments
+    /// * `transmit` - When `false`, the order is staged until another order in the same group is
+    /// transmitted.
+    pub fn with_transmit(mut self, transmit: bool) -> Self {
+        self.get_attributes_mut().transmit = Some(transmit);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether the order can trigger or fill outside of regular trading hours.
+    ///
+    /// # Arguments
+    /// * `outside_regular_trading_hours` - When `true`, the order is also active during the
+    /// pre-market, after-hours and overnight sessions.
+    pub fn with_outside_regular_trading_hours(
+        mut self,
+        outside_regular_trading_hours: bool,
+    ) -> Self {
+        self.get_attributes_mut().outside_regular_trading_hours =
+            Some(outside_regular_trading_hours);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Display only part of the order's quantity on the book at a time (an iceberg order).
+    ///
+    /// # Arguments
+    /// * `display_size` - The quantity that is displayed, which is replenished as it fills.
+    pub fn with_display_size(mut self, display_size: impl Into<Quantity>) -> Self {
+        self.get_attributes_mut().display_size = Some(display_size.into());
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether the order is hidden from the book.
+    ///
+    /// # Arguments
+    /// * `hidden` - When `true`, the order is not displayed on the book. Only NASDAQ-routed orders
+    /// can be hidden.
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.get_attributes_mut().hidden = Some(hidden);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether the order is a sweep-to-fill order.
+    ///
+    /// # Arguments
+    /// * `sweep_to_fill` - When `true`, the order is filled as quickly as possible at the best
+    /// prices available across every exchange, rather than at its routing destination alone.
+    pub fn with_sweep_to_fill(mut self, sweep_to_fill: bool) -> Self {
+        self.get_attributes_mut().sweep_to_fill = Some(sweep_to_fill);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether the order is an all-or-none order.
+    ///
+    /// # Arguments
+    /// * `all_or_none` - When `true`, the order is only filled in its entirety, in a single
+    /// execution.
+    pub fn with_all_or_none(mut self, all_or_none: bool) -> Self {
+        self.get_attributes_mut().all_or_none = Some(all_or_none);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether the order is a not-held order.
+    ///
+    /// # Arguments
+    /// * `not_held` - When `true`, the broker may use its discretion over the time and price at
+    /// which the order executes.
+    pub fn with_not_held(mut self, not_held: bool) -> Self {
+        self.get_attributes_mut().not_held = Some(not_held);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Add the order to a One-Cancels-All group, such that the execution of any order in the group
+    /// cancels or reduces the others (ex: the profit taker and stop loss that exit a position).
+    ///
+    /// # Arguments
+    /// * `group` - The name of the group, which must be shared by every order in the group.
+    /// * `one_cancels_all_type` - How the other orders in the group are handled when the order
+    /// executes.
+    pub fn with_one_cancels_all(
+        mut self,
+        group: impl Into<String>,
+        one_cancels_all_type: OneCancelsAllType,
+    ) -> Self {
+        let attributes = self.get_attributes_mut();
+        attributes.one_cancels_all_group = Some(group.into());
+        attributes.one_cancels_all_type = Some(one_cancels_all_type);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Attach conditions to the order, such that it is only submitted (or, alternatively,
+    /// cancelled) once they are met.
+    ///
+    /// # Arguments
+    /// * `conditions` - The conditions to attach.
+    pub fn with_conditions(mut self, conditions: Conditions) -> Self {
+        self.get_attributes_mut().conditions = Some(conditions);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Execute the order with one of IB's algorithms.
+    ///
+    /// # Arguments
+    /// * `algo` - The algorithm, along with its parameters.
+    pub fn with_algo(mut self, algo: Algo) -> Self {
+        self.get_attributes_mut().algo = Some(algo);
+        self
+    }
+
+    #[must_use]
+    /// Denominate the order in the security's currency rather than in shares/units (ex: buy
+    /// €10,000 of a stock), in which case the order's quantity is ignored.
+    ///
+    /// # Arguments
+    /// * `cash_quantity` - The amount of currency to buy or sell.
+    ///
+    /// # Returns
+    /// The order, or [`None`] if the order is neither a [`Market`] nor a [`Limit`] order, which
+    /// are the only order types that IBKR accepts with a cash quantity, or if the amount is not
+    /// positive.
+    pub fn with_cash_quantity(mut self, cash_quantity: f64) -> Option<Self> {
+        if !matches!(self.get_execute_method().get_order_type(), "MKT" | "LMT")
+            || cash_quantity <= 0.0
+            || !cash_quantity.is_finite()
+        {
+            return None;
+        }
+        self.get_attributes_mut().cash_quantity = Some(cash_quantity);
+        Some(self)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Hold the order until a given time, after which it becomes active (a "Good After Time"
+    /// order).
+    ///
+    /// # Arguments
+    /// * `good_after_time` - The time after which the order becomes active.
+    pub fn with_good_after_time(mut self, good_after_time: chrono::DateTime<chrono::Utc>) -> Self {
+        self.get_attributes_mut().good_after_time = Some(good_after_time);
+        self
+    }
+
+    #[must_use]
+    /// Set the limit price of each leg of a [`Combo`] order, in place of a single price for the
+    /// combo as a whole.
+    ///
+    /// # Arguments
+    /// * `prices` - The limit price of each leg, in the same order as the combo's legs, where
+    /// [`None`] leaves a leg's price unset.
+    ///
+    /// # Returns
+    /// The order, or [`None`] if the number of prices differs from the number of the security's
+    /// legs (which is zero for any security other than a [`Combo`]).
+    pub fn with_combo_leg_prices(mut self, prices: Vec<Option<Price>>) -> Option<Self> {
+        if prices.is_empty() || prices.len() != self.get_security().get_combo_legs().len() {
+            return None;
+        }
+        self.get_attributes_mut().combo_leg_prices = Some(prices);
+        Some(self)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Allocate the order to a specific account, such as when the client manages several.
+    ///
+    /// # Arguments
+    /// * `account` - The account to which the order is allocated.
+    pub fn with_account(mut self, account: AccountId) -> Self {
+        self.get_attributes_mut().account = Some(account);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Allocate the order across the accounts of a financial advisor group.
+    ///
+    /// # Arguments
+    /// * `group` - The name of the advisor group.
+    /// * `method` - The method by which the order is allocated across the group's accounts.
+    pub fn with_fa_group(mut self, group: impl Into<String>, method: FaMethod) -> Self {
+        let attributes = self.get_attributes_mut();
+        attributes.fa_group = Some(group.into());
+        attributes.fa_method = Some(method);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the percentage by which each account's position changes, for an order allocated with
+    /// the [`FaMethod::PercentChange`] method.
+    ///
+    /// # Arguments
+    /// * `percentage` - The percentage change (ex: 10.0 for an increase of 10%).
+    pub fn with_fa_percentage(mut self, percentage: f64) -> Self {
+        self.get_attributes_mut().fa_percentage = Some(percentage);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Allocate the order according to a financial advisor allocation profile.
+    ///
+    /// # Arguments
+    /// * `profile` - The name of the allocation profile.
+    pub fn with_fa_profile(mut self, profile: impl Into<String>) -> Self {
+        self.get_attributes_mut().fa_profile = Some(profile.into());
+        self
+    }
+
+    #[must_use]
+    /// Return the order's `attributes`
+    pub const fn get_attributes(&self) -> &Attributes {
+        match self {
+            Self::Buy { attributes, .. } | Self::Sell { attributes, .. } => attributes,
+        }
+    }
+
+    #[inline]
+    fn get_attributes_mut(&mut self) -> &mut Attributes {
+        match self {
+            Self::Buy { attributes, .. } | Self::Sell { attributes, .. } => attributes,
+        }
+    }
+
+    #[must_use]
+    /// Return a copy of the order (sharing the same security and execution method) with a
+    /// different set of attributes.
+    pub(crate) fn with_attributes(&self, attributes: Attributes) -> Self {
+        match self {
+            Self::Buy {
+                security,
+                execute_method,
+                ..
+            } => Self::Buy {
+                security: Rc::clone(security),
+                execute_method: Rc::clone(execute_method),
+                attributes,
+            },
+            Self::Sell {
+                security,
+                execute_method,
+                ..
+            } => Self::Sell {
+                security: Rc::clone(security),
+                execute_method: Rc::clone(execute_method),
+                attributes,
+            },
+        }
+    }
+
+    #[must_use]
+    /// Return a copy of the order (sharing the same security and attributes) with a different
+    /// execution method.
+    fn with_execute_method<F: Executable<S>>(&self, execute_method: F) -> Order<S, F> {
+        match self {
+            Self::Buy {
+                security,
+                attributes,
+                ..
+            } => Order::Buy {
+                security: Rc::clone(security),
+                execute_method: Rc::new(execute_method),
+                attributes: attributes.clone(),
+            },
+            Self::Sell {
+                security,
+                attributes,
+                ..
+            } => Order::Sell {
+                security: Rc::clone(security),
+                execute_method: Rc::new(execute_method),
+                attributes: attributes.clone(),
+            },
+        }
+    }
+
+    #[must_use]
+    /// Return the order's `security`
+    pub fn get_security(&self) -> &S {
+        match self {
+            Self::Buy { security, .. } | Self::Sell { security, .. } => Rc::deref(security),
+        }
+    }
+
+    #[must_use]
+    /// Return the order's `execute_method`
+    pub fn get_execute_method(&self) -> &E {
+        match self {
+            Self::Buy { execute_method, .. } | Self::Sell { execute_method, .. } => {
+                Rc::deref(execute_method)
+            }
+        }
+    }
+}
+
+impl<E: Executable<Stock>> Order<Stock, E> {
+    #[must_use]
+    /// Route the order to IBKR's overnight venue, so that it can execute during the overnight
+    /// session of US equities. The order is also allowed to fill outside of regular trading hours.
+    ///
+    /// Note that IBKR only accepts [`TimeInForce::Day`] limit orders during the overnight session.
+    ///
+    /// # Returns
+    /// The rerouted order, or [`None`] if the stock cannot be traded overnight.
+    pub fn route_overnight(self) -> Option<Self> {
+        let overnight = Rc::new(self.get_security().to_overnight()?);
+        Some(
+            match self {
+                Self::Buy {
+                    execute_method,
+                    attributes,
+                    ..
+                } => Self::Buy {
+                    security: overnight,
+                    execute_method,
+                    attributes,
+                },
+                Self::Sell {
+                    execute_method,
+                    attributes,
+                    ..
+                } => Self::Sell {
+                    security: overnight,
+                    execute_method,
+                    attributes,
+                },
+            }
+            .with_outside_regular_trading_hours(true),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A market order: Buy or sell at the best available price for a given quantity. Sensitive to price fluctuations.
+pub struct Market {
+    /// The number of shares/units to execute.
+    pub quantity: Quantity,
+    /// The time for which the order will remain valid
+    pub time_in_force: TimeInForce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A market order: Buy or sell at a price as good or better than the limit price. May not be filled.
+pub struct Limit {
+    /// The number of shares/units to buy.
+    pub quantity: Quantity,
+    /// The limit price, which sets the upper / lower bound on the price per unit.
+    pub price: Price,
+    /// The time for which the order will remain valid
+    pub time_in_force: TimeInForce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A stop order: Buy or sell at the best available price once the market reaches the stop price.
+pub struct Stop {
+    /// The number of shares/units to execute.
+    pub quantity: Quantity,
+    /// The stop price, which triggers a market order once it is reached.
+    pub price: Price,
+    /// The time for which the order will remain valid
+    pub time_in_force: TimeInForce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A pegged-to-market order: A limit order whose price tracks the national best bid (when buying)
+/// or offer (when selling), less (or plus) an offset.
+pub struct PeggedToMarket {
+    /// The number of shares/units to execute.
+    pub quantity: Quantity,
+    /// The amount by which the price is more aggressive than the market, which may be zero.
+    pub offset: Price,
+    /// The time for which the order will remain valid
+    pub time_in_force: TimeInForce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A pegged-to-midpoint order: A limit order whose price tracks the midpoint of the national best
+/// bid and offer, less (when buying) or plus (when selling) an offset.
+pub struct PeggedToMidpoint {
+    /// The number of shares/units to execute.
+    pub quantity: Quantity,
+    /// The amount by which the price is less aggressive than the midpoint, which may be zero.
+    pub offset: Price,
+    /// The price beyond which the order's price is never pegged, if any.
+    pub price_cap: Option<Price>,
+    /// The time for which the order will remain valid
+    pub time_in_force: TimeInForce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A relative (or pegged-to-primary) order: A limit order whose price tracks the national best
+/// bid (when buying) or offer (when selling), plus (or less) an offset, such that it is more
+/// aggressive than the market.
+pub struct Relative {
+    /// The number of shares/units to execute.
+    pub quantity: Quantity,
+    /// The amount by which the price is more aggressive than the market, which may be zero.
+    pub offset: Price,
+    /// The price beyond which the order's price is never pegged, if any.
+    pub price_cap: Option<Price>,
+    /// The time for which the order will remain valid
+    pub time_in_force: TimeInForce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A pegged-to-benchmark order: A limit order whose price moves by a set amount whenever the
+/// price of a reference contract moves by another set amount.
+pub struct PeggedToBenchmark {
+    /// The number of shares/units to execute.
+    pub quantity: Quantity,
+    /// The initial limit price of the order.
+    pub starting_price: Price,
+    /// The ID of the reference contract whose price is tracked.
+    pub reference_contract_id: ContractId,
+    /// The exchange from which the reference contract's price is taken.
+    pub reference_exchange: Routing,
+    /// The price of the reference contract when the order is placed.
+    pub reference_price: Price,
+    /// The amount by which the reference contract's price must move to adjust the order's price.
+    pub reference_change_amount: Price,
+    /// The amount by which the order's price moves in the same direction as the reference
+    /// contract's price. A negative amount moves the order's price in the opposite direction.
+    pub pegged_change_amount: Price,
+    /// The range of the reference contract's price, outside of which the order is cancelled, if
+    /// any.
+    pub reference_range: Option<(Price, Price)>,
+    /// The time for which the order will remain valid
+    pub time_in_force: TimeInForce,
+}
+
+impl Market {
+    #[inline]
+    #[must_use]
+    /// Begin building a new [`Market`] order. The order can only be built once a quantity has
+    /// been provided.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ibapi::order::{Market, TimeInForce};
+    /// let market = Market::builder().quantity(100).time_in_force(TimeInForce::Gtc).build();
+    /// assert_eq!(market.quantity.to_string(), "100");
+    /// ```
+    pub const fn builder() -> builder::MarketBuilder<builder::Missing> {
+        builder::MarketBuilder::new()
+    }
+}
+
+impl Limit {
+    #[inline]
+    #[must_use]
+    /// Begin building a new [`Limit`] order. The order can only be built once both a price and a
+    /// quantity have been provided.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ibapi::order::{Limit, TimeInForce};
+    /// # use ibapi::quantity::Price;
+    /// let price: Price = "101.5".parse().unwrap();
+    /// let limit = Limit::builder().price(price).quantity(100).build();
+    /// assert_eq!(limit.time_in_force, TimeInForce::Day);
+    /// ```
+    pub const fn builder() -> builder::LimitBuilder<builder::Missing, builder::Missing> {
+        builder::LimitBuilder::new()
+    }
+}
+
+impl Stop {
+    #[inline]
+    #[must_use]
+    /// Begin building a new [`Stop`] order. The order can only be built once both a stop price
+    /// and a quantity have been provided.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ibapi::order::{Stop, TimeInForce};
+    /// # use ibapi::quantity::Price;
+    /// let price: Price = "98.25".parse().unwrap();
+    /// let stop = Stop::builder().quantity(100).price(price).build();
+    /// assert_eq!(stop.price, price);
+    /// ```
+    pub const fn builder() -> builder::StopBuilder<builder::Missing, builder::Missing> {
+        builder::StopBuilder::new()
+    }
+}
+
+impl PeggedToMarket {
+    #[inline]
+    #[must_use]
+    /// Begin building a new [`PeggedToMarket`] order. The order can only be built once a quantity
+    /// has been provided.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ibapi::order::PeggedToMarket;
+    /// # use ibapi::quantity::Price;
+    /// let order = PeggedToMarket::builder().quantity(100).build();
+    /// assert_eq!(order.offset, Price::ZERO);
+    /// ```
+    pub const fn builder() -> builder::PeggedToMarketBuilder<builder::Missing> {
+        builder::PeggedToMarketBuilder::new()
+    }
+}
+
+impl PeggedToMidpoint {
+    #[inline]
+    #[must_use]
+    /// Begin building a new [`PeggedToMidpoint`] order. The order can only be built once a
+    /// quantity has been provided.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ibapi::order::PeggedToMidpoint;
+    /// # use ibapi::quantity::Price;
+    /// let cap: Price = "50.1".parse().unwrap();
+    /// let order = PeggedToMidpoint::builder().quantity(100).price_cap(cap).build();
+    /// assert_eq!(order.price_cap, Some(cap));
+    /// ```
+    pub const fn builder() -> builder::PeggedToMidpointBuilder<builder::Missing> {
+        builder::PeggedToMidpointBuilder::new()
+    }
+}
+
+impl Relative {
+    #[inline]
+    #[must_use]
+    /// Begin building a new [`Relative`] order. The order can only be built once a quantity has
+    /// been provided.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ibapi::order::Relative;
+    /// # use ibapi::quantity::Price;
+    /// let offset: Price = "0.02".parse().unwrap();
+    /// let order = Relative::builder().offset(offset).quantity(100).build();
+    /// assert_eq!(order.price_cap, None);
+    /// ```
+    pub const fn builder() -> builder::RelativeBuilder<builder::Missing> {
+        builder::RelativeBuilder::new()
+    }
+}
+
+impl PeggedToBenchmark {
+    #[inline]
+    #[must_use]
+    /// Begin building a new [`PeggedToBenchmark`] order. The order can only be built once a
+    /// quantity, a starting price, a reference contract, and the amounts by which the prices
+    /// change have been provided.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ibapi::contract::ContractId;
+    /// # use ibapi::exchange::Routing;
+    /// # use ibapi::order::PeggedToBenchmark;
+    /// # use ibapi::quantity::Price;
+    /// let order = PeggedToBenchmark::builder()
+    ///     .quantity(100)
+    ///     .starting_price("25".parse::<Price>().unwrap())
+    ///     .reference(ContractId(756_733), Routing::Smart, "450".parse::<Price>().unwrap())
+    ///     .change("1".parse::<Price>().unwrap(), "0.05".parse::<Price>().unwrap())
+    ///     .build();
+    /// assert_eq!(order.reference_range, None);
+    /// ```
+    pub const fn builder() -> builder::PeggedToBenchmarkBuilder<
+        builder::Missing,
+        builder::Missing,
+        builder::Missing,
+        builder::Missing,
+    > {
+        builder::PeggedToBenchmarkBuilder::new()
+    }
+}
+
+/// Contains typestate builders for the order types. A builder only exposes its `build`
+/// method once every field required by its order type has been provided, so an incomplete order
+/// is a compile-time error rather than a server-side rejection.
+pub mod builder {
+    use super::{
+        ContractId, Limit, Market, PeggedToBenchmark, PeggedToMarket, PeggedToMidpoint, Price,
+        Quantity, Relative, Routing, Stop, TimeInForce,
+    };
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// Indicates that a required field has not yet been provided to a builder.
+    pub struct Missing;
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// A builder for a [`Market`] order.
+    pub struct MarketBuilder<Q> {
+        quantity: Q,
+        time_in_force: TimeInForce,
+    }
+
+    impl MarketBuilder<Missing> {
+        #[inline]
+        pub(super) const fn new() -> Self {
+            Self {
+                quantity: Missing,
+                time_in_force: TimeInForce::Day,
+            }
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the number of shares/units to execute.
+        pub fn quantity(self, quantity: impl Into<Quantity>) -> MarketBuilder<Quantity> {
+            MarketBuilder {
+                quantity: quantity.into(),
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<Q> MarketBuilder<Q> {
+        #[inline]
+        #[must_use]
+        /// Set the time for which the order will remain valid (defaults to [`TimeInForce::Day`]).
+        pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+            self.time_in_force = time_in_force;
+            self
+        }
+    }
+
+    impl MarketBuilder<Quantity> {
+        #[inline]
+        #[must_use]
+        /// Build the [`Market`] order.
+        pub const fn build(self) -> Market {
+            Market {
+                quantity: self.quantity,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// A builder for a [`Limit`] order.
+    pub struct LimitBuilder<P, Q> {
+        price: P,
+        quantity: Q,
+        time_in_force: TimeInForce,
+    }
+
+    impl LimitBuilder<Missing, Missing> {
+        #[inline]
+        pub(super) const fn new() -> Self {
+            Self {
+                price: Missing,
+                quantity: Missing,
+                time_in_force: TimeInForce::Day,
+            }
+        }
+    }
+
+    impl<Q> LimitBuilder<Missing, Q> {
+        #[inline]
+        #[must_use]
+        /// Set the limit price, which sets the upper / lower bound on the price per unit.
+        pub fn price(self, price: impl Into<Price>) -> LimitBuilder<Price, Q> {
+            LimitBuilder {
+                price: price.into(),
+                quantity: self.quantity,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<P> LimitBuilder<P, Missing> {
+        #[inline]
+        #[must_use]
+        /// Set the number of shares/units to buy.
+        pub fn quantity(self, quantity: impl Into<Quantity>) -> LimitBuilder<P, Quantity> {
+            LimitBuilder {
+                price: self.price,
+                quantity: quantity.into(),
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<P, Q> LimitBuilder<P, Q> {
+        #[inline]
+        #[must_use]
+        /// Set the time for which the order will remain valid (defaults to [`TimeInForce::Day`]).
+        pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+            self.time_in_force = time_in_force;
+            self
+        }
+    }
+
+    impl LimitBuilder<Price, Quantity> {
+        #[inline]
+        #[must_use]
+        /// Build the [`Limit`] order.
+        pub const fn build(self) -> Limit {
+            Limit {
+                quantity: self.quantity,
+                price: self.price,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// A builder for a [`Stop`] order.
+    pub struct StopBuilder<P, Q> {
+        price: P,
+        quantity: Q,
+        time_in_force: TimeInForce,
+    }
+
+    impl StopBuilder<Missing, Missing> {
+        #[inline]
+        pub(super) const fn new() -> Self {
+            Self {
+                price: Missing,
+                quantity: Missing,
+                time_in_force: TimeInForce::Day,
+            }
+        }
+    }
+
+    impl<Q> StopBuilder<Missing, Q> {
+        #[inline]
+        #[must_use]
+        /// Set the stop price, which triggers a market order once it is reached.
+        pub fn price(self, price: impl Into<Price>) -> StopBuilder<Price, Q> {
+            StopBuilder {
+                price: price.into(),
+                quantity: self.quantity,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<P> StopBuilder<P, Missing> {
+        #[inline]
+        #[must_use]
+        /// Set the number of shares/units to execute.
+        pub fn quantity(self, quantity: impl Into<Quantity>) -> StopBuilder<P, Quantity> {
+            StopBuilder {
+                price: self.price,
+                quantity: quantity.into(),
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<P, Q> StopBuilder<P, Q> {
+        #[inline]
+        #[must_use]
+        /// Set the time for which the order will remain valid (defaults to [`TimeInForce::Day`]).
+        pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+            self.time_in_force = time_in_force;
+            self
+        }
+    }
+
+    impl StopBuilder<Price, Quantity> {
+        #[inline]
+        #[must_use]
+        /// Build the [`Stop`] order.
+        pub const fn build(self) -> Stop {
+            Stop {
+                quantity: self.quantity,
+                price: self.price,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// A builder for a [`PeggedToMarket`] order.
+    pub struct PeggedToMarketBuilder<Q> {
+        quantity: Q,
+        offset: Price,
+        time_in_force: TimeInForce,
+    }
+
+    impl PeggedToMarketBuilder<Missing> {
+        #[inline]
+        pub(super) const fn new() -> Self {
+            Self {
+                quantity: Missing,
+                offset: Price::ZERO,
+                time_in_force: TimeInForce::Day,
+            }
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the number of shares/units to execute.
+        pub fn quantity(self, quantity: impl Into<Quantity>) -> PeggedToMarketBuilder<Quantity> {
+            PeggedToMarketBuilder {
+                quantity: quantity.into(),
+                offset: self.offset,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<Q> PeggedToMarketBuilder<Q> {
+        #[inline]
+        #[must_use]
+        /// Set the amount by which the price is more aggressive than the market (defaults to
+        /// zero).
+        pub fn offset(mut self, offset: impl Into<Price>) -> Self {
+            self.offset = offset.into();
+            self
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the time for which the order will remain valid (defaults to [`TimeInForce::Day`]).
+        pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+            self.time_in_force = time_in_force;
+            self
+        }
+    }
+
+    impl PeggedToMarketBuilder<Quantity> {
+        #[inline]
+        #[must_use]
+        /// Build the [`PeggedToMarket`] order.
+        pub const fn build(self) -> PeggedToMarket {
+            PeggedToMarket {
+                quantity: self.quantity,
+                offset: self.offset,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// A builder for a [`PeggedToMidpoint`] order.
+    pub struct PeggedToMidpointBuilder<Q> {
+        quantity: Q,
+        offset: Price,
+        price_cap: Option<Price>,
+        time_in_force: TimeInForce,
+    }
+
+    impl PeggedToMidpointBuilder<Missing> {
+        #[inline]
+        pub(super) const fn new() -> Self {
+            Self {
+                quantity: Missing,
+                offset: Price::ZERO,
+                price_cap: None,
+                time_in_force: TimeInForce::Day,
+            }
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the number of shares/units to execute.
+        pub fn quantity(self, quantity: impl Into<Quantity>) -> PeggedToMidpointBuilder<Quantity> {
+            PeggedToMidpointBuilder {
+                quantity: quantity.into(),
+                offset: self.offset,
+                price_cap: self.price_cap,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<Q> PeggedToMidpointBuilder<Q> {
+        #[inline]
+        #[must_use]
+        /// Set the amount by which the price is less aggressive than the midpoint (defaults to
+        /// zero).
+        pub fn offset(mut self, offset: impl Into<Price>) -> Self {
+            self.offset = offset.into();
+            self
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the price beyond which the order's price is never pegged (defaults to no cap).
+        pub fn price_cap(mut self, price_cap: impl Into<Price>) -> Self {
+            self.price_cap = Some(price_cap.into());
+            self
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the time for which the order will remain valid (defaults to [`TimeInForce::Day`]).
+        pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+            self.time_in_force = time_in_force;
+            self
+        }
+    }
+
+    impl PeggedToMidpointBuilder<Quantity> {
+        #[inline]
+        #[must_use]
+        /// Build the [`PeggedToMidpoint`] order.
+        pub const fn build(self) -> PeggedToMidpoint {
+            PeggedToMidpoint {
+                quantity: self.quantity,
+                offset: self.offset,
+                price_cap: self.price_cap,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// A builder for a [`Relative`] order.
+    pub struct RelativeBuilder<Q> {
+        quantity: Q,
+        offset: Price,
+        price_cap: Option<Price>,
+        time_in_force: TimeInForce,
+    }
+
+    impl RelativeBuilder<Missing> {
+        #[inline]
+        pub(super) const fn new() -> Self {
+            Self {
+                quantity: Missing,
+                offset: Price::ZERO,
+                price_cap: None,
+                time_in_force: TimeInForce::Day,
+            }
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the number of shares/units to execute.
+        pub fn quantity(self, quantity: impl Into<Quantity>) -> RelativeBuilder<Quantity> {
+            RelativeBuilder {
+                quantity: quantity.into(),
+                offset: self.offset,
+                price_cap: self.price_cap,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<Q> RelativeBuilder<Q> {
+        #[inline]
+        #[must_use]
+        /// Set the amount by which the price is more aggressive than the market (defaults to
+        /// zero).
+        pub fn offset(mut self, offset: impl Into<Price>) -> Self {
+            self.offset = offset.into();
+            self
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the price beyond which the order's price is never pegged (defaults to no cap).
+        pub fn price_cap(mut self, price_cap: impl Into<Price>) -> Self {
+            self.price_cap = Some(price_cap.into());
+            self
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the time for which the order will remain valid (defaults to [`TimeInForce::Day`]).
+        pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+            self.time_in_force = time_in_force;
+            self
+        }
+    }
+
+    impl RelativeBuilder<Quantity> {
+        #[inline]
+        #[must_use]
+        /// Build the [`Relative`] order.
+        pub const fn build(self) -> Relative {
+            Relative {
+                quantity: self.quantity,
+                offset: self.offset,
+                price_cap: self.price_cap,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// The reference contract of a [`PeggedToBenchmark`] order, as provided to its builder.
+    pub struct Reference {
+        contract_id: ContractId,
+        exchange: Routing,
+        price: Price,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// The amounts by which the prices of a [`PeggedToBenchmark`] order and its reference contract
+    /// change, as provided to its builder.
+    pub struct Change {
+        reference: Price,
+        pegged: Price,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    /// A builder for a [`PeggedToBenchmark`] order.
+    pub struct PeggedToBenchmarkBuilder<Q, S, R, C> {
+        quantity: Q,
+        starting_price: S,
+        reference: R,
+        change: C,
+        reference_range: Option<(Price, Price)>,
+        time_in_force: TimeInForce,
+    }
+
+    impl PeggedToBenchmarkBuilder<Missing, Missing, Missing, Missing> {
+        #[inline]
+        pub(super) const fn new() -> Self {
+            Self {
+                quantity: Missing,
+                starting_price: Missing,
+                reference: Missing,
+                change: Missing,
+                reference_range: None,
+                time_in_force: TimeInForce::Day,
+            }
+        }
+    }
+
+    impl<S, R, C> PeggedToBenchmarkBuilder<Missing, S, R, C> {
+        #[inline]
+        #[must_use]
+        /// Set the number of shares/units to execute.
+        pub fn quantity(
+            self,
+            quantity: impl Into<Quantity>,
+        ) -> PeggedToBenchmarkBuilder<Quantity, S, R, C> {
+            PeggedToBenchmarkBuilder {
+                quantity: quantity.into(),
+                starting_price: self.starting_price,
+                reference: self.reference,
+                change: self.change,
+                reference_range: self.reference_range,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<Q, R, C> PeggedToBenchmarkBuilder<Q, Missing, R, C> {
+        #[inline]
+        #[must_use]
+        /// Set the initial limit price of the order.
+        pub fn starting_price(
+            self,
+            starting_price: impl Into<Price>,
+        ) -> PeggedToBenchmarkBuilder<Q, Price, R, C> {
+            PeggedToBenchmarkBuilder {
+                quantity: self.quantity,
+                starting_price: starting_price.into(),
+                reference: self.reference,
+                change: self.change,
+                reference_range: self.reference_range,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<Q, S, C> PeggedToBenchmarkBuilder<Q, S, Missing, C> {
+        #[inline]
+        #[must_use]
+        /// Set the reference contract whose price is tracked.
+        ///
+        /// # Arguments
+        /// * `contract_id` - The ID of the reference contract.
+        /// * `exchange` - The exchange from which the reference contract's price is taken.
+        /// * `price` - The price of the reference contract when the order is placed.
+        pub fn reference(
+            self,
+            contract_id: ContractId,
+            exchange: Routing,
+            price: impl Into<Price>,
+        ) -> PeggedToBenchmarkBuilder<Q, S, Reference, C> {
+            PeggedToBenchmarkBuilder {
+                quantity: self.quantity,
+                starting_price: self.starting_price,
+                reference: Reference {
+                    contract_id,
+                    exchange,
+                    price: price.into(),
+                },
+                change: self.change,
+                reference_range: self.reference_range,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<Q, S, R> PeggedToBenchmarkBuilder<Q, S, R, Missing> {
+        #[inline]
+        #[must_use]
+        /// Set the amounts by which the prices change.
+        ///
+        /// # Arguments
+        /// * `reference` - The amount by which the reference contract's price must move to adjust
+        /// the order's price.
+        /// * `pegged` - The amount by which the order's price moves in the same direction as the
+        /// reference contract's price. A negative amount moves the order's price in the opposite
+        /// direction.
+        pub fn change(
+            self,
+            reference: impl Into<Price>,
+            pegged: impl Into<Price>,
+        ) -> PeggedToBenchmarkBuilder<Q, S, R, Change> {
+            PeggedToBenchmarkBuilder {
+                quantity: self.quantity,
+                starting_price: self.starting_price,
+                reference: self.reference,
+                change: Change {
+                    reference: reference.into(),
+                    pegged: pegged.into(),
+                },
+                reference_range: self.reference_range,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+
+    impl<Q, S, R, C> PeggedToBenchmarkBuilder<Q, S, R, C> {
+        #[inline]
+        #[must_use]
+        /// Set the range of the reference contract's price, outside of which the order is
+        /// cancelled (defaults to no range).
+        pub fn reference_range(mut self, low: impl Into<Price>, high: impl Into<Price>) -> Self {
+            self.reference_range = Some((low.into(), high.into()));
+            self
+        }
+
+        #[inline]
+        #[must_use]
+        /// Set the time for which the order will remain valid (defaults to [`TimeInForce::Day`]).
+        pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+            self.time_in_force = time_in_force;
+            self
+        }
+    }
+
+    impl PeggedToBenchmarkBuilder<Quantity, Price, Reference, Change> {
+        #[inline]
+        #[must_use]
+        /// Build the [`PeggedToBenchmark`] order.
+        pub const fn build(self) -> PeggedToBenchmark {
+            PeggedToBenchmark {
+                quantity: self.quantity,
+                starting_price: self.starting_price,
+                reference_contract_id: self.reference.contract_id,
+                reference_exchange: self.reference.exchange,
+                reference_price: self.reference.price,
+                reference_change_amount: self.change.reference,
+                pegged_change_amount: self.change.pegged,
+                reference_range: self.reference_range,
+                time_in_force: self.time_in_force,
+            }
+        }
+    }
+}
+
+// ======================
+// === Bracket Orders ===
+// ======================
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// An entry order with two attached exit orders: a take-profit [`Limit`] order and a stop-loss
+/// [`Stop`] order, which are only active once the entry order is filled. When one of the exit
+/// orders is filled, IBKR cancels the other.
+///
+/// The exit orders are [`TimeInForce::Gtc`] regardless of the entry order, so that they keep
+/// protecting the position after the day on which it is opened (see
+/// [`BracketOrder::with_exit_time_in_force`]).
+///
+/// A bracket is placed with [`crate::client::Client::req_place_bracket`], which sets the
+/// parent IDs and transmit flags of its orders so that none of them is transmitted until all three
+/// have been placed.
+pub struct BracketOrder<S, E>
+where
+    S: Security,
+    E: Executable<S>,
+    Limit: Executable<S>,
+    Stop: Executable<S>,
+{
+    parent: Order<S, E>,
+    take_profit: Order<S, Limit>,
+    stop_loss: Order<S, Stop>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The IDs of the orders placed by [`crate::client::Client::req_place_bracket`].
+pub struct BracketOrderIds {
+    /// The ID of the entry order.
+    pub parent: i64,
+    /// The ID of the take-profit order.
+    pub take_profit: i64,
+    /// The ID of the stop-loss order.
+    pub stop_loss: i64,
+}
+
+impl<S, E> BracketOrder<S, E>
+where
+    S: Security,
+    E: Executable<S>,
+    Limit: Executable<S>,
+    Stop: Executable<S>,
+{
+    #[inline]
+    #[must_use]
+    /// Create a bracket that buys a security and then sells it at either the take-profit or the
+    /// stop-loss price.
+    ///
+    /// # Arguments
+    /// * `security` - The security to buy.
+    /// * `execute_method` - The execution method of the entry order.
+    /// * `take_profit_price` - The limit price at which to take a profit.
+    /// * `stop_loss_price` - The stop price at which to cut a loss.
+    pub fn buy(
+        security: S,
+        execute_method: E,
+        take_profit_price: Price,
+        stop_loss_price: Price,
+    ) -> Self {
+        Self::new(
+            Order::buy(security, execute_method),
+            take_profit_price,
+            stop_loss_price,
+        )
+    }
+
+    #[inline]
+    #[must_use]
+    /// Create a bracket that sells a security and then buys it back at either the take-profit or
+    /// the stop-loss price.
+    ///
+    /// # Arguments
+    /// * `security` - The security to sell.
+    /// * `execute_method` - The execution method of the entry order.
+    /// * `take_profit_price` - The limit price at which to take a profit.
+    /// * `stop_loss_price` - The stop price at which to cut a loss.
+    pub fn sell(
+        security: S,
+        execute_method: E,
+        take_profit_price: Price,
+        stop_loss_price: Price,
+    ) -> Self {
+        Self::new(
+            Order::sell(security, execute_method),
+            take_profit_price,
+            stop_loss_price,
+        )
+    }
+
+    fn new(parent: Order<S, E>, take_profit_price: Price, stop_loss_price: Price) -> Self {
+        let quantity = parent.get_execute_method().get_quantity();
+        let time_in_force = TimeInForce::Gtc;
+        let take_profit = Limit {
+            quantity,
+            price: take_profit_price,
+            time_in_force,
+        };
+        let stop_loss = Stop {
+            quantity,
+            price: stop_loss_price,
+            time_in_force,
+        };
+        // The exit orders close the position that the entry order opens
+        let (take_profit, stop_loss) = match &parent {
+            Order::Buy { security, .. } => (
+                Order::Sell {
+                    security: Rc::clone(security),
+                    execute_method: Rc::new(take_profit),
+                    attributes: Attributes::default(),
+                },
+                Order::Sell {
+                    security: Rc::clone(security),
+                    execute_method: Rc::new(stop_loss),
+                    attributes: Attributes::default(),
+                },
+            ),
+            Order::Sell { security, .. } => (
+                Order::Buy {
+                    security: Rc::clone(security),
+                    execute_method: Rc::new(take_profit),
+                    attributes: Attributes::default(),
+                },
+                Order::Buy {
+                    security: Rc::clone(security),
+                    execute_method: Rc::new(stop_loss),
+                    attributes: Attributes::default(),
+                },
+            ),
+        };
+        Self {
+            parent,
+            take_profit,
+            stop_loss,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set whether the bracket's orders can trigger or fill outside of regular trading hours.
+    ///
+    /// # Arguments
+    /// * `outside_regular_trading_hours` - When `true`, the orders are also active during the
+    /// pre-market, after-hours and overnight sessions.
+    pub fn with_outside_regular_trading_hours(self, outside_regular_trading_hours: bool) -> Self {
+        Self {
+            parent: self
+                .parent
+                .with_outside_regular_trading_hours(outside_regular_trading_hours),
+            take_profit: self
+                .take_profit
+                .with_outside_regular_trading_hours(outside_regular_trading_hours),
+            stop_loss: self
+                .stop_loss
+                .with_outside_regular_trading_hours(outside_regular_trading_hours),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the time in force of the take-profit and stop-loss orders, which is
+    /// [`TimeInForce::Gtc`] by default.
+    ///
+    /// # Arguments
+    /// * `time_in_force` - The time in force of the exit orders.
+    pub fn with_exit_time_in_force(self, time_in_force: TimeInForce) -> Self {
+        let take_profit = Limit {
+            time_in_force,
+            ..*self.take_profit.get_execute_method()
+        };
+        let stop_loss = Stop {
+            time_in_force,
+            ..*self.stop_loss.get_execute_method()
+        };
+        Self {
+            take_profit: self.take_profit.with_execute_method(take_profit),
+            stop_loss: self.stop_loss.with_execute_method(stop_loss),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the entry order.
+    pub const fn get_parent(&self) -> &Order<S, E> {
+        &self.parent
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the take-profit order.
+    pub const fn get_take_profit(&self) -> &Order<S, Limit> {
+        &self.take_profit
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the stop-loss order.
+    pub const fn get_stop_loss(&self) -> &Order<S, Stop> {
+        &self.stop_loss
+    }
+}
+
+// ========================
+// === Order Conditions ===
+// ========================
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A condition that must be met before a conditional order is submitted (or cancelled). See
+/// [`Conditions`].
+pub enum OrderCondition {
+    /// A condition on the price of a contract.
+    Price {
+        /// The contract whose price is monitored.
+        contract_id: ContractId,
+        /// The exchange on which the price is monitored.
+        exchange: Routing,
+        /// When [`true`], the condition is met once the price rises above `price`; otherwise,
+        /// once it falls below `price`.
+        is_more: bool,
+        /// The price at which the condition is met.
+        price: f64,
+        /// How the price is determined.
+        trigger_method: TriggerMethod,
+    },
+    /// A condition on the current time.
+    Time {
+        /// When [`true`], the condition is met after `time`; otherwise, before `time`.
+        is_more: bool,
+        /// The time at which the condition is met.
+        time: chrono::DateTime<chrono::Utc>,
+    },
+    /// A condition on the margin cushion of the account.
+    Margin {
+        /// When [`true`], the condition is met once the margin cushion rises above `percent`;
+        /// otherwise, once it falls below `percent`.
+        is_more: bool,
+        /// The margin cushion, in percent, at which the condition is met.
+        percent: u32,
+    },
+    /// A condition that is met once a trade is executed for a given symbol.
+    Execution {
+        /// The type of the traded contract.
+        contract_type: ContractType,
+        /// The exchange on which the trade was executed.
+        exchange: Routing,
+        /// The symbol of the traded contract.
+        symbol: String,
+    },
+    /// A condition on the trading volume of a contract.
+    Volume {
+        /// The contract whose volume is monitored.
+        contract_id: ContractId,
+        /// The exchange on which the volume is monitored.
+        exchange: Routing,
+        /// When [`true`], the condition is met once the volume rises above `volume`; otherwise,
+        /// once it falls below `volume`.
+        is_more: bool,
+        /// The volume at which the condition is met.
+        volume: u64,
+    },
+    /// A condition on the change in the price of a contract since the previous close.
+    PercentChange {
+        /// The contract whose price is monitored.
+        contract_id: ContractId,
+        /// The exchange on which the price is monitored.
+        exchange: Routing,
+        /// When [`true`], the condition is met once the change rises above `percent`; otherwise,
+        /// once it falls below `percent`.
+        is_more: bool,
+        /// The change, in percent, at which the condition is met.
+        percent: f64,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// How the conditions of a conditional order are combined.
+pub enum Conjunction {
+    #[default]
+    /// Every condition must be met.
+    And,
+    /// Any one condition must be met.
+    Or,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, PartialOrd)]
+/// The conditions attached to an order with [`Order::with_conditions`].
+pub struct Conditions {
+    /// The conditions that must be met.
+    pub conditions: Vec<OrderCondition>,
+    /// How the conditions are combined.
+    pub conjunction: Conjunction,
+    /// When [`true`], the order is cancelled once the conditions are met. Otherwise, it is
+    /// submitted.
+    pub cancel_order: bool,
+    /// When [`true`], the conditions are also evaluated outside of regular trading hours.
+    pub ignore_regular_trading_hours: bool,
+}
+
+impl OrderCondition {
+    #[inline]
+    const fn get_type(&self) -> u8 {
+        match self {
+            Self::Price { .. } => 1,
+            Self::Time { .. } => 3,
+            Self::Margin { .. } => 4,
+            Self::Execution { .. } => 5,
+            Self::Volume { .. } => 6,
+            Self::PercentChange { .. } => 7,
+        }
+    }
+}
+
+impl Serialize for Conditions {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut ser = serializer.serialize_tuple(3 + self.conditions.len())?;
+        ser.serialize_element(&self.conditions.len())?;
+        if self.conditions.is_empty() {
+            return ser.end();
+        }
+        let conjunction = match self.conjunction {
+            Conjunction::And => "a",
+            Conjunction::Or => "o",
+        };
+        for condition in &self.conditions {
+            ser.serialize_element(&condition.get_type())?;
+            ser.serialize_element(conjunction)?;
+            match condition {
+                OrderCondition::Price {
+                    contract_id,
+                    exchange,
+                    is_more,
+                    price,
+                    trigger_method,
+                } => {
+                    ser.serialize_element(&(is_more, price, contract_id, exchange, trigger_method))?
+                }
+                OrderCondition::Time { is_more, time } => {
+                    ser.serialize_element(&(is_more, time.format("%Y%m%d-%H:%M:%S").to_string()))?
+                }
+                OrderCondition::Margin { is_more, percent } => {
+                    ser.serialize_element(&(is_more, percent))?;
+                }
+                OrderCondition::Execution {
+                    contract_type,
+                    exchange,
+                    symbol,
+                } => ser.serialize_element(&(contract_type, exchange, symbol))?,
+                OrderCondition::Volume {
+                    contract_id,
+                    exchange,
+                    is_more,
+                    volume,
+                } => ser.serialize_element(&(is_more, volume, contract_id, exchange))?,
+                OrderCondition::PercentChange {
+                    contract_id,
+                    exchange,
+                    is_more,
+                    percent,
+                } => ser.serialize_element(&(is_more, percent, contract_id, exchange))?,
+            }
+        }
+        ser.serialize_element(&self.ignore_regular_trading_hours)?;
+        ser.serialize_element(&self.cancel_order)?;
+        ser.end()
+    }
+}
+
+// ===================
+// === Algo Orders ===
+// ===================
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// How quickly an [`Algo::Adaptive`] order seeks to be filled.
+pub enum AdaptivePriority {
+    /// Fill quickly, at the expense of price.
+    Urgent,
+    #[default]
+    /// Balance the speed of the fill against its price.
+    Normal,
+    /// Seek a better price, at the expense of speed.
+    Patient,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The urgency with which an [`Algo::ArrivalPrice`] order trades.
+pub enum RiskAversion {
+    /// Trade as soon as possible.
+    GetDone,
+    /// Trade aggressively.
+    Aggressive,
+    #[default]
+    /// Trade neutrally.
+    Neutral,
+    /// Trade passively.
+    Passive,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The prices at which an [`Algo::Twap`] order may trade.
+pub enum TwapStrategyType {
     #[default]
-    #[serde(rename(serialize = "DAY"))]
-    /// Valid for the day only.
-    Day,
-    #[serde(rename(serialize = "GTC"))]
-    /// Good until canceled. The order will continue to work within the system and in the marketplace until it executes or is canceled. GTC orders will be automatically be cancelled under the following conditions:
-    /// If a corporate action on a security results in a stock split (forward or reverse), exchange for shares, or distribution of shares. If you do not log into your IB account for 90 days.
-    /// At the end of the calendar quarter following the current quarter. For example, an order placed during the third quarter of 2011 will be canceled at the end of the first quarter of 2012. If the last day is a non-trading day, the cancellation will occur at the close of the final trading day of that quarter. For example, if the last day of the quarter is Sunday, the orders will be cancelled on the preceding Friday.
-    /// Orders that are modified will be assigned a new “Auto Expire” date consistent with the end of the calendar quarter following the current quarter.
-    /// Orders submitted to IB that remain in force for more than one day will not be reduced for dividends. To allow adjustment to your order price on ex-dividend date, consider using a Good-Til-Date/Time (GTD) or Good-after-Time/Date (GAT) order type, or a combination of the two.
-    Gtc,
-    #[serde(rename(serialize = "IOC"))]
-    /// Immediate or Cancel. Any portion that is not filled as soon as it becomes available in the market is canceled.
-    Ioc,
-    // #[serde(rename(serialize="GTD"))]
-    // /// Good until Date. It will remain working within the system and in the marketplace until it executes or until the close of the market on the date specified
-    // Gtd,
-    // #[serde(rename(serialize="OPG"))]
-    // /// Use OPG to send a market-on-open (MOO) or limit-on-open (LOO) order.
-    // Opg,
-    #[serde(rename(serialize = "FOK"))]
-    /// If the entire Fill-or-Kill order does not execute as soon as it becomes available, the entire order is canceled.
-    Fok,
-    #[serde(rename(serialize = "DTC"))]
-    /// Day until canceled.
-    Dtc,
+    /// Cross the spread.
+    Marketable,
+    /// Trade at the midpoint.
+    MatchingMidpoint,
+    /// Trade at the bid (when buying) or the ask (when selling).
+    MatchingSameSide,
+    /// Trade at the last price.
+    MatchingLast,
 }
 
-#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-/// A basic error type that represents an invalid [`TimeInForce`]
-pub struct ParseTimeInForceError(String);
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The IB algorithms with which an order may be executed, along with their parameters.
+///
+/// Start and end times that are [`None`] default to the start and end of the trading day,
+/// respectively.
+///
+/// For more information about IB's API algorithms, refer to IBKR's
+/// [IB algorithm description](https://interactivebrokers.github.io/tws-api/ibalgos.html)
+pub enum Algo {
+    /// Adaptive algorithm, which works a limit or market order between the bid and the ask.
+    Adaptive {
+        /// How quickly the order seeks to be filled.
+        priority: AdaptivePriority,
+    },
+    /// Arrival price algorithm.
+    ArrivalPrice {
+        /// The maximum fraction of the daily volume that the order may make up, between 0.1
+        /// and 0.5.
+        max_percent_volume: f64,
+        /// The urgency with which the order trades.
+        risk_aversion: RiskAversion,
+        /// The time at which the algorithm starts.
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        /// The time at which the algorithm ends.
+        end: Option<chrono::DateTime<chrono::Utc>>,
+        /// Whether to attempt to fill the order by the end time, even if that exceeds the
+        /// maximum percentage of volume.
+        force_completion: bool,
+        /// Whether the order may keep trading after the end time.
+        allow_past_end_time: bool,
+    },
+    /// Dark ice algorithm.
+    DarkIce {
+        /// The quantity displayed to the market at any one time.
+        display_size: Quantity,
+        /// The time at which the algorithm starts.
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        /// The time at which the algorithm ends.
+        end: Option<chrono::DateTime<chrono::Utc>>,
+        /// Whether the order may keep trading after the end time.
+        allow_past_end_time: bool,
+    },
+    /// Percentage of volume algorithm.
+    PercentVolume {
+        /// The target fraction of the daily volume that the order makes up, between 0.1 and
+        /// 0.5.
+        percent_volume: f64,
+        /// The time at which the algorithm starts.
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        /// The time at which the algorithm ends.
+        end: Option<chrono::DateTime<chrono::Utc>>,
+        /// Whether the order is prevented from taking liquidity.
+        no_take_liquidity: bool,
+    },
+    /// TWAP (Time Weighted Average Price) algorithm.
+    Twap {
+        /// The prices at which the order may trade.
+        strategy_type: TwapStrategyType,
+        /// The time at which the algorithm starts.
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        /// The time at which the algorithm ends.
+        end: Option<chrono::DateTime<chrono::Utc>>,
+        /// Whether the order may keep trading after the end time.
+        allow_past_end_time: bool,
+    },
+    /// VWAP (Volume Weighted Average Price) algorithm.
+    Vwap {
+        /// The maximum fraction of the daily volume that the order may make up, between 0.1
+        /// and 0.5.
+        max_percent_volume: f64,
+        /// The time at which the algorithm starts.
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        /// The time at which the algorithm ends.
+        end: Option<chrono::DateTime<chrono::Utc>>,
+        /// Whether the order may keep trading after the end time.
+        allow_past_end_time: bool,
+        /// Whether the order is prevented from taking liquidity.
+        no_take_liquidity: bool,
+    },
+}
 
-impl std::fmt::Display for ParseTimeInForceError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Invalid time in force: {}", self.0)
-    }
+#[deprecated(note = "use `Algo`, which carries the algorithm's parameters")]
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, PartialEq, Hash, Eq)]
+/// The potential execution algorithms for algo orders, whose parameters are given separately by
+/// [`Executable::get_algo_strategy_content`].
+pub enum AlgoStrategy {
+    /// Arrival price algorithm.
+    ArrivalPrice,
+    /// Dark ice algorithm.
+    DarkIce,
+    /// Percentage of volume algorithm.
+    PercentVolume,
+    /// TWAP (Time Weighted Average Price) algorithm.
+    Twap,
+    /// VWAP (Volume Weighted Average Price) algorithm.
+    Vwap,
 }
 
-impl std::error::Error for ParseTimeInForceError {}
+impl Algo {
+    #[inline]
+    const fn get_name(&self) -> &'static str {
+        match self {
+            Self::Adaptive { .. } => "Adaptive",
+            Self::ArrivalPrice { .. } => "ArrivalPx",
+            Self::DarkIce { .. } => "DarkIce",
+            Self::PercentVolume { .. } => "PctVol",
+            Self::Twap { .. } => "Twap",
+            Self::Vwap { .. } => "Vwap",
+        }
+    }
 
-impl FromStr for TimeInForce {
-    type Err = ParseTimeInForceError;
+    /// Return the algorithm's parameters as tag-value pairs.
+    fn get_params(&self) -> Vec<(&'static str, String)> {
+        let flag = |b: bool| if b { "1" } else { "0" }.to_owned();
+        let time = |t: chrono::DateTime<chrono::Utc>| t.format("%Y%m%d-%H:%M:%S").to_string();
+        let window = |start: Option<_>, end: Option<_>| {
+            start
+                .map(|t| ("startTime", time(t)))
+                .into_iter()
+                .chain(end.map(|t| ("endTime", time(t))))
+        };
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "DAY" => Self::Day,
-            "GTC" => Self::Gtc,
-            "IOC" => Self::Ioc,
-            // "GTD" => Self::Gtd,
-            "FOK" => Self::Fok,
-            "DTC" => Self::Dtc,
-            _ => return Err(ParseTimeInForceError(s.to_owned())),
-        })
+        let mut params = Vec::new();
+        match *self {
+            Self::Adaptive { priority } => {
+                let priority = match priority {
+                    AdaptivePriority::Urgent => "Urgent",
+                    AdaptivePriority::Normal => "Normal",
+                    AdaptivePriority::Patient => "Patient",
+                };
+                params.push(("adaptivePriority", priority.to_owned()));
+            }
+            Self::ArrivalPrice {
+                max_percent_volume,
+                risk_aversion,
+                start,
+                end,
+                force_completion,
+                allow_past_end_time,
+            } => {
+                params.push(("maxPctVol", max_percent_volume.to_string()));
+                let risk_aversion = match risk_aversion {
+                    RiskAversion::GetDone => "Get Done",
+                    RiskAversion::Aggressive => "Aggressive",
+                    RiskAversion::Neutral => "Neutral",
+                    RiskAversion::Passive => "Passive",
+                };
+                params.push(("riskAversion", risk_aversion.to_owned()));
+                params.extend(window(start, end));
+                params.push(("allowPastEndTime", flag(allow_past_end_time)));
+                params.push(("forceCompletion", flag(force_completion)));
+            }
+            Self::DarkIce {
+                display_size,
+                start,
+                end,
+                allow_past_end_time,
+            } => {
+                params.push(("displaySize", display_size.to_string()));
+                params.extend(window(start, end));
+                params.push(("allowPastEndTime", flag(allow_past_end_time)));
+            }
+            Self::PercentVolume {
+                percent_volume,
+                start,
+                end,
+                no_take_liquidity,
+            } => {
+                params.push(("pctVol", percent_volume.to_string()));
+                params.extend(window(start, end));
+                params.push(("noTakeLiq", flag(no_take_liquidity)));
+            }
+            Self::Twap {
+                strategy_type,
+                start,
+                end,
+                allow_past_end_time,
+            } => {
+                let strategy_type = match strategy_type {
+                    TwapStrategyType::Marketable => "Marketable",
+                    TwapStrategyType::MatchingMidpoint => "Matching Midpoint",
+                    TwapStrategyType::MatchingSameSide => "Matching Same Side",
+                    TwapStrategyType::MatchingLast => "Matching Last",
+                };
+                params.push(("strategyType", strategy_type.to_owned()));
+                params.extend(window(start, end));
+                params.push(("allowPastEndTime", flag(allow_past_end_time)));
+            }
+            Self::Vwap {
+                max_percent_volume,
+                start,
+                end,
+                allow_past_end_time,
+                no_take_liquidity,
+            } => {
+                params.push(("maxPctVol", max_percent_volume.to_string()));
+                params.extend(window(start, end));
+                params.push(("allowPastEndTime", flag(allow_past_end_time)));
+                params.push(("noTakeLiq", flag(no_take_liquidity)));
+            }
+        }
+        params
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
-/// A generic order to buy or sell a security `S`: `Security` according to the parameters specified by the generic
-/// parameter `E`: `Executable`.
-pub enum Order<S: Security, E: Executable<S>> {
-    /// An order to Buy `S`: `Security`] according to the method described by `E`: `Executable`.
-    Buy {
-        /// The security to buy.
-        security: Rc<S>,
-        /// The execution method to use.
-        execute_method: Rc<E>,
-    },
-    /// An order to Sell `S`: `Security` according to the method described by `E`: `Executable`.
-    Sell {
-        /// The security to sell.
-        security: Rc<S>,
-        /// The execution method to use.
-        execute_method: Rc<E>,
-    },
+#[allow(deprecated)]
+impl Serialize for AlgoStrategy {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(match self {
+            Self::ArrivalPrice => "ArrivalPx",
+            Self::DarkIce => "DarkIce",
+            Self::PercentVolume => "PctVol",
+            Self::Twap => "Twap",
+            Self::Vwap => "Vwap",
+        })
+    }
 }
 
-impl<Sec, E> Serialize for Order<Sec, E>
-where
-    Sec: Security,
-    E: Executable<Sec>,
-{
+impl Serialize for Algo {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut ser = serializer.serialize_tuple(1 + crate::constants::ORDER_TUPLE_SIZE)?;
-        let (action, exec) = match self {
-            Self::Buy { execute_method, .. } => ("BUY", execute_method),
-            Self::Sell { execute_method, .. } => ("SELL", execute_method),
-        };
-        ser.serialize_element(action)?;
-        serialize_executable(Rc::deref(exec), &mut ser)?;
+        let params = self.get_params();
+        let mut ser = serializer.serialize_tuple(2 + params.len())?;
+        ser.serialize_element(self.get_name())?;
+        ser.serialize_element(&params.len())?;
+        for param in &params {
+            ser.serialize_element(param)?;
+        }
         ser.end()
     }
 }
 
-impl<S: Security, E: Executable<S>> Order<S, E> {
+// =====================
+// === Order Expiry ===
+// =====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The date on which a resting order will be automatically cancelled by IBKR if it remains
+/// unfilled.
+pub enum Expiry {
+    /// A [`TimeInForce::Gtc`] order, which is cancelled at the end of the calendar quarter
+    /// following the quarter in which it was placed or last modified.
+    GoodTillCanceled(NaiveDate),
+    /// An order with an explicit "Good Till Date", which is cancelled at the end of that date.
+    GoodTillDate(NaiveDate),
+}
+
+impl Expiry {
+    #[inline]
     #[must_use]
-    /// Return the order's `security`
-    pub fn get_security(&self) -> &S {
+    /// Return the date on which the order expires.
+    pub const fn get_date(&self) -> NaiveDate {
         match self {
-            Self::Buy { security, .. } | Self::Sell { security, .. } => Rc::deref(security),
+            Self::GoodTillCanceled(date) | Self::GoodTillDate(date) => *date,
         }
     }
 
     #[must_use]
-    /// Return the order's `execute_method`
-    pub fn get_execute_method(&self) -> &E {
-        match self {
-            Self::Buy { execute_method, .. } | Self::Sell { execute_method, .. } => {
-                Rc::deref(execute_method)
+    /// Determine when an order expires, if it does at all.
+    ///
+    /// # Arguments
+    /// * `exec` - The order's execution method.
+    /// * `placed` - The date on which the order was placed or last modified.
+    ///
+    /// # Returns
+    /// The order's [`Expiry`] if it has an explicit "Good Till Date", such as a
+    /// [`TimeInForce::Gtd`] order, or is a [`TimeInForce::Gtc`] order; otherwise, [`None`].
+    pub fn new<S: Security, E: Executable<S>>(exec: &E, placed: NaiveDate) -> Option<Self> {
+        if let Some(date) = exec
+            .get_good_until_date()
+            .and_then(|d| NaiveDate::parse_from_str(d.get(..8)?, "%Y%m%d").ok())
+        {
+            return Some(Self::GoodTillDate(date));
+        }
+        match exec.get_time_in_force() {
+            TimeInForce::Gtd(datetime) => Some(Self::GoodTillDate(datetime.date_naive())),
+            TimeInForce::Gtc => {
+                // The last month of the quarter following the current one
+                let months = placed.year() * 12 + i32::try_from(placed.month0() / 3 * 3).ok()? + 5;
+                let first_of_next = NaiveDate::from_ymd_opt(months.div_euclid(12), 1, 1)?
+                    .checked_add_months(chrono::Months::new(
+                        u32::try_from(months.rem_euclid(12)).ok()? + 1,
+                    ))?;
+                Some(Self::GoodTillCanceled(first_of_next.pred_opt()?))
             }
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
-/// A market order: Buy or sell at the best available price for a given quantity. Sensitive to price fluctuations.
-pub struct Market {
-    /// The number of shares/units to execute.
-    pub quantity: f64,
-    /// The time for which the order will remain valid
-    pub time_in_force: TimeInForce,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
-/// A market order: Buy or sell at a price as good or better than the limit price. May not be filled.
-pub struct Limit {
-    /// The number of shares/units to buy.
-    pub quantity: f64,
-    /// The limit price, which sets the upper / lower bound on the price per unit.
-    pub price: f64,
-    /// The time for which the order will remain valid
-    pub time_in_force: TimeInForce,
-}
-
 // ==================================================
 // === Order Trait Definition and Implementations ===
 // ==================================================
@@ -171,21 +2178,21 @@ pub type OrderConditionsContent<'a> = (usize, HashMap<&'a str, &'a str>, bool, b
 /// if a type `O` implements [`Executable<S>`], then `O` is a valid order for `S`.
 pub trait Executable<S: Security>: Send + Sync {
     /// Return the total number of contracts being bought/sold.
-    fn get_quantity(&self) -> f64;
+    fn get_quantity(&self) -> Quantity;
 
     /// Return the order's type
     fn get_order_type(&self) -> &'static str;
 
     #[inline]
     /// Return the order's limit price
-    fn get_limit_price(&self) -> Option<f64> {
+    fn get_limit_price(&self) -> Option<Price> {
         None
     }
 
     #[inline]
     /// Return the order's auxiliary price, which is a generic price used for specifying parameters
     /// like the stop price in a stop-limit order.
-    fn get_auxiliary_price(&self) -> Option<f64> {
+    fn get_auxiliary_price(&self) -> Option<Price> {
         None
     }
 
@@ -249,8 +2256,8 @@ pub trait Executable<S: Security>: Send + Sync {
 
     #[inline]
     /// Return the publicly disclosed order size, used when placing Iceberg orders.
-    fn get_iceberg_order_size(&self) -> u64 {
-        0
+    fn get_iceberg_order_size(&self) -> Quantity {
+        Quantity::ZERO
     }
 
     #[inline]
@@ -361,7 +2368,7 @@ pub trait Executable<S: Security>: Send + Sync {
     ///
     /// For BOX orders only.
 
-    fn get_box_starting_price(&self) -> Option<f64> {
+    fn get_box_starting_price(&self) -> Option<Price> {
         None
     }
 
@@ -369,7 +2376,7 @@ pub trait Executable<S: Security>: Send + Sync {
     /// Return the stock's reference price.
     /// The reference price is used for VOL orders to compute the limit price sent to an exchange
     /// (whether or not Continuous Update is selected), and for price range monitoring.
-    fn get_box_stock_reference_price(&self) -> Option<f64> {
+    fn get_box_stock_reference_price(&self) -> Option<Price> {
         None
     }
 
@@ -383,7 +2390,7 @@ pub trait Executable<S: Security>: Send + Sync {
     /// Return the lower value for the acceptable underlying stock price range.
     ///
     /// For price improvement option orders on BOX and VOL orders with dynamic management.
-    fn get_box_vol_stock_range_lower(&self) -> Option<f64> {
+    fn get_box_vol_stock_range_lower(&self) -> Option<Price> {
         None
     }
 
@@ -391,7 +2398,7 @@ pub trait Executable<S: Security>: Send + Sync {
     /// Return the upper value for the acceptable underlying stock price range.
     ///
     /// For price improvement option orders on BOX and VOL orders with dynamic management.
-    fn get_box_vol_stock_range_upper(&self) -> Option<f64> {
+    fn get_box_vol_stock_range_upper(&self) -> Option<Price> {
         None
     }
 
@@ -438,7 +2445,7 @@ pub trait Executable<S: Security>: Send + Sync {
     ///
     /// Use this field to enter a value if the value in the `deltaNeutralOrderType` field is an order
     /// type that requires an Aux price, such as a REL order. VOL orders only.
-    fn get_delta_neutral_auxiliary_price(&self) -> Option<f64> {
+    fn get_delta_neutral_auxiliary_price(&self) -> Option<Price> {
         None
     }
 
@@ -466,7 +2473,7 @@ pub trait Executable<S: Security>: Send + Sync {
 
     #[inline]
     /// Return the trailing stop price for trail limit orders
-    fn get_trail_stop_price(&self) -> Option<f64> {
+    fn get_trail_stop_price(&self) -> Option<Price> {
         None
     }
 
@@ -589,7 +2596,19 @@ pub trait Executable<S: Security>: Send + Sync {
     }
 
     #[inline]
-    /// Return the algorithm strategy.
+    /// Return the IB algorithm with which the order is executed, along with its parameters.
+    ///
+    /// For more information about IB's API algorithms, refer to IBKR's
+    /// [IB algorithm description](https://interactivebrokers.github.io/tws-api/ibalgos.html)
+    fn get_algo(&self) -> Option<Algo> {
+        None
+    }
+
+    #[inline]
+    #[deprecated(note = "implement `get_algo`, which carries the algorithm's parameters")]
+    #[allow(deprecated)]
+    /// Return the algorithm strategy, which is only used if [`Executable::get_algo`] returns
+    /// [`None`].
     ///
     /// For more information about IB's API algorithms, refer to IBKR's
     /// [IB algorithm description](https://interactivebrokers.github.io/tws-api/ibalgos.html)
@@ -599,7 +2618,7 @@ pub trait Executable<S: Security>: Send + Sync {
 
     #[inline]
     /// Return the algorithm strategy content (ie. The list of parameters for the IB algorithm),
-    /// if it exists.
+    /// if it exists. Only used along with [`Executable::get_algo_strategy`].
     ///
     /// For more information about IB's API algorithms, refer to IBKR's
     /// [IB algorithm description](https://interactivebrokers.github.io/tws-api/ibalgos.html)
@@ -651,7 +2670,13 @@ pub trait Executable<S: Security>: Send + Sync {
 
     #[inline]
     /// Return peg bench order content, if it exists.
-    fn get_peg_bench_order_content(&self) -> ConditionalField<(), (i64, bool, f64, f64, &str)> {
+    ///
+    /// That is, the reference contract's ID, whether the order's price moves in the opposite
+    /// direction of the reference contract's price, the amount by which the order's price moves,
+    /// the amount by which the reference contract's price must move, and the reference exchange.
+    fn get_peg_bench_order_content(
+        &self,
+    ) -> ConditionalField<(), (ContractId, bool, Price, Price, Routing)> {
         ConditionalField::default()
     }
 
@@ -844,44 +2869,114 @@ pub trait Executable<S: Security>: Send + Sync {
 
 #[inline]
 #[allow(clippy::too_many_lines)]
-fn serialize_executable<E, Sec, Ser>(exec: &E, ser: &mut Ser) -> Result<(), Ser::Error>
+fn serialize_executable<E, Sec, Ser>(
+    exec: &E,
+    attributes: &Attributes,
+    security: &Sec,
+    ser: &mut Ser,
+) -> Result<(), Ser::Error>
 where
     E: Executable<Sec>,
     Sec: crate::contract::Security,
     Ser: SerializeTuple,
 {
-    ser.serialize_element(&exec.get_quantity())?;
+    match attributes.cash_quantity {
+        Some(_) => ser.serialize_element(&Quantity::ZERO)?,
+        None => ser.serialize_element(&exec.get_quantity())?,
+    }
     ser.serialize_element(&exec.get_order_type())?;
     ser.serialize_element(&exec.get_limit_price())?;
     ser.serialize_element(&exec.get_auxiliary_price())?;
     ser.serialize_element(&exec.get_time_in_force())?;
-    ser.serialize_element(&exec.get_one_cancels_all_group())?;
-    ser.serialize_element(&exec.get_account())?;
+    ser.serialize_element(
+        &attributes
+            .one_cancels_all_group
+            .as_deref()
+            .or_else(|| exec.get_one_cancels_all_group()),
+    )?;
+    ser.serialize_element(
+        &attributes
+            .account
+            .as_ref()
+            .map(|account| account.0.as_str())
+            .or_else(|| exec.get_account()),
+    )?;
     ser.serialize_element(&None::<()>)?;
     ser.serialize_element(&exec.get_origin())?;
     ser.serialize_element(&exec.get_order_reference())?;
-    ser.serialize_element(&exec.get_will_transmit())?;
-    ser.serialize_element(&exec.get_parent_id())?;
+    ser.serialize_element(
+        &attributes
+            .transmit
+            .unwrap_or_else(|| exec.get_will_transmit()),
+    )?;
+    ser.serialize_element(&attributes.parent_id.unwrap_or_else(|| exec.get_parent_id()))?;
     ser.serialize_element(&exec.get_is_block_order())?;
-    ser.serialize_element(&exec.get_is_sweep_to_fill())?;
-    ser.serialize_element(&exec.get_iceberg_order_size())?;
+    ser.serialize_element(
+        &attributes
+            .sweep_to_fill
+            .unwrap_or_else(|| exec.get_is_sweep_to_fill()),
+    )?;
+    ser.serialize_element(
+        &attributes
+            .display_size
+            .unwrap_or_else(|| exec.get_iceberg_order_size()),
+    )?;
     ser.serialize_element(&exec.get_trigger_method())?;
-    ser.serialize_element(&exec.get_can_fill_outside_regular_trading_hours())?;
-    ser.serialize_element(&exec.get_is_hidden_on_nasdaq_market_depth())?;
-    ser.serialize_element(&exec.get_bag_request_content())?;
+    ser.serialize_element(
+        &attributes
+            .outside_regular_trading_hours
+            .unwrap_or_else(|| exec.get_can_fill_outside_regular_trading_hours()),
+    )?;
+    ser.serialize_element(
+        &attributes
+            .hidden
+            .unwrap_or_else(|| exec.get_is_hidden_on_nasdaq_market_depth()),
+    )?;
+    let legs = security.get_combo_legs();
+    if legs.is_empty() {
+        ser.serialize_element(&exec.get_bag_request_content())?;
+    } else {
+        ser.serialize_element(&OrderLegs {
+            legs,
+            prices: attributes.combo_leg_prices.as_deref().unwrap_or_default(),
+        })?;
+    }
     ser.serialize_element(&None::<()>)?;
     ser.serialize_element(&exec.get_discretionary_amount())?;
-    ser.serialize_element(&exec.get_good_after_time())?;
-    ser.serialize_element(&exec.get_good_until_date())?;
-    ser.serialize_element(&[None::<()>; 3])?;
+    match attributes.good_after_time {
+        Some(time) => ser.serialize_element(&time.format(UTC_ORDER_TIME_FORMAT).to_string())?,
+        None => ser.serialize_element(&exec.get_good_after_time())?,
+    }
+    match exec.get_time_in_force() {
+        TimeInForce::Gtd(time) => {
+            ser.serialize_element(&time.format(UTC_ORDER_TIME_FORMAT).to_string())?;
+        }
+        _ => ser.serialize_element(&exec.get_good_until_date())?,
+    }
+    ser.serialize_element(
+        &attributes
+            .fa_group
+            .as_deref()
+            .or(attributes.fa_profile.as_deref()),
+    )?;
+    ser.serialize_element(&attributes.fa_method)?;
+    ser.serialize_element(&attributes.fa_percentage)?;
     ser.serialize_element(&exec.get_model_code())?;
     ser.serialize_element(&0)?;
     ser.serialize_element(&None::<()>)?;
     ser.serialize_element(&-1)?;
-    ser.serialize_element(&exec.get_one_cancels_all_type())?;
+    ser.serialize_element(
+        &attributes
+            .one_cancels_all_type
+            .unwrap_or_else(|| exec.get_one_cancels_all_type()),
+    )?;
     ser.serialize_element(&exec.get_rule_80a())?;
     ser.serialize_element(&None::<()>)?;
-    ser.serialize_element(&exec.get_is_all_or_none())?;
+    ser.serialize_element(
+        &attributes
+            .all_or_none
+            .unwrap_or_else(|| exec.get_is_all_or_none()),
+    )?;
     ser.serialize_element(&exec.get_minimum_quantity())?;
     ser.serialize_element(&exec.get_percent_offset())?;
     ser.serialize_element(&false)?;
@@ -915,18 +3010,34 @@ where
     ser.serialize_element(&exec.get_opt_out_smart_routing())?;
     ser.serialize_element(&exec.get_clearing_account())?;
     ser.serialize_element(&exec.get_clearing_intent())?;
-    ser.serialize_element(&exec.get_is_not_held())?;
-    ser.serialize_element(&exec.get_delta_neutral_contract_content())?;
-    ser.serialize_element(&exec.get_algo_strategy())?;
-    ser.serialize_element(&exec.get_algo_strategy_content())?;
+    ser.serialize_element(
+        &attributes
+            .not_held
+            .unwrap_or_else(|| exec.get_is_not_held()),
+    )?;
+    match security.get_delta_neutral_contract() {
+        Some(contract) => ser.serialize_element(&DeltaNeutralField(Some(contract)))?,
+        None => ser.serialize_element(&exec.get_delta_neutral_contract_content())?,
+    }
+    match attributes.algo.or_else(|| exec.get_algo()) {
+        Some(algo) => ser.serialize_element(&algo)?,
+        None => {
+            #[allow(deprecated)]
+            ser.serialize_element(&exec.get_algo_strategy())?;
+            ser.serialize_element(&exec.get_algo_strategy_content())?;
+        }
+    }
     ser.serialize_element(&exec.get_algo_id())?;
-    ser.serialize_element(&exec.get_what_if())?;
+    ser.serialize_element(&attributes.what_if.unwrap_or_else(|| exec.get_what_if()))?;
     ser.serialize_element(&None::<()>)?;
     ser.serialize_element(&exec.get_solicited())?;
     ser.serialize_element(&exec.get_will_randomize_size())?;
     ser.serialize_element(&exec.get_will_randomize_price())?;
     ser.serialize_element(&exec.get_peg_bench_order_content())?;
-    ser.serialize_element(&exec.get_order_conditions_content())?;
+    match &attributes.conditions {
+        Some(conditions) => ser.serialize_element(conditions)?,
+        None => ser.serialize_element(&exec.get_order_conditions_content())?,
+    }
     ser.serialize_element(&exec.get_adjusted_order_type())?;
     ser.serialize_element(&exec.get_trigger_price())?;
     ser.serialize_element(&exec.get_limit_price_offset())?;
@@ -936,7 +3047,11 @@ where
     ser.serialize_element(&exec.get_adjusted_trailing_unit())?;
     ser.serialize_element(&exec.get_ext_operator())?;
     ser.serialize_element(&exec.get_soft_dollar_tier())?;
-    ser.serialize_element(&exec.get_cash_quantity())?;
+    ser.serialize_element(
+        &attributes
+            .cash_quantity
+            .unwrap_or_else(|| exec.get_cash_quantity()),
+    )?;
     ser.serialize_element(&exec.get_decision_maker())?;
     ser.serialize_element(&exec.get_decision_algorithm())?;
     ser.serialize_element(&exec.get_execution_trader())?;
@@ -1122,20 +3237,33 @@ pub enum ClearingIntent {
 }
 
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, PartialEq, Hash, Eq, Serialize)]
-/// The potential execution algorithms for algo orders.
-pub enum AlgoStrategy {
-    #[serde(rename(serialize = "ArrivalPx"))]
-    /// Arrival price algorithm.
-    ArrivalPrice,
-    /// Dark ice algorithm.
-    DarkIce,
-    #[serde(rename(serialize = "PctVol"))]
-    /// Percentage of volume algorithm.
-    PercentVolume,
-    /// TWAP (Time Weighted Average Price) algorithm.
-    Twap,
-    /// VWAP (Volume Weighted Average Price) algorithm.
-    Vwap,
+/// Whether an option position is exercised or allowed to lapse, in
+/// [`crate::client::Client::req_exercise_options`].
+pub enum ExerciseAction {
+    #[serde(rename(serialize = "1"))]
+    /// Exercise the options.
+    Exercise,
+    #[serde(rename(serialize = "2"))]
+    /// Let the options lapse (expire worthless).
+    Lapse,
+}
+
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, PartialEq, Hash, Eq, Serialize)]
+/// The method by which a financial advisor allocates an order across the accounts of a group.
+pub enum FaMethod {
+    #[serde(rename(serialize = "EqualQuantity"))]
+    /// Each account receives the same quantity.
+    EqualQuantity,
+    #[serde(rename(serialize = "NetLiq"))]
+    /// Each account receives a quantity in proportion to its net liquidation value.
+    NetLiquidity,
+    #[serde(rename(serialize = "AvailableEquity"))]
+    /// Each account receives a quantity in proportion to its available equity.
+    AvailableEquity,
+    #[serde(rename(serialize = "PctChange"))]
+    /// Each account's position changes by the same percentage, given by
+    /// [`Attributes::fa_percentage`].
+    PercentChange,
 }
 
 #[derive(Debug, Default, Clone, Copy, Ord, PartialOrd, PartialEq, Hash, Eq, Serialize)]
@@ -1168,6 +3296,36 @@ impl<T: Default, U> Default for ConditionalField<T, U> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The legs of a [`Combo`] order, along with their prices, as they are serialized in the
+/// place-order message.
+struct OrderLegs<'l> {
+    legs: &'l [ComboLeg],
+    prices: &'l [Option<Price>],
+}
+
+impl Serialize for OrderLegs<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut ser = serializer.serialize_tuple(self.legs.len() + self.prices.len() + 3)?;
+        ser.serialize_element(&self.legs.len())?;
+        for leg in self.legs {
+            // Each leg is followed by its open/close, short sale slot, designated location and
+            // exempt code, which are left at their defaults
+            ser.serialize_element(&(leg, 0, 0, None::<()>, -1))?;
+        }
+        ser.serialize_element(&self.prices.len())?;
+        for price in self.prices {
+            ser.serialize_element(price)?;
+        }
+        // No smart combo routing parameters
+        ser.serialize_element(&0)?;
+        ser.end()
+    }
+}
+
 macro_rules! impl_executable {
     ($o_name: ident; $($s_name: ident),*; $executable_impl: tt) => {
         $(
@@ -1177,8 +3335,8 @@ macro_rules! impl_executable {
     };
 }
 
-impl_executable!(Market; Forex, Crypto, Stock, Index, SecFuture, SecOption, Commodity; {
-    fn get_quantity(&self) -> f64 {
+impl_executable!(Market; Forex, Crypto, Stock, Index, SecFuture, SecOption, Commodity, Combo; {
+    fn get_quantity(&self) -> Quantity {
         self.quantity
     }
 
@@ -1190,8 +3348,8 @@ impl_executable!(Market; Forex, Crypto, Stock, Index, SecFuture, SecOption, Comm
         self.time_in_force
     }
 });
-impl_executable!(Limit; Forex, Crypto, Stock, Index, SecFuture, SecOption, Commodity; {
-    fn get_quantity(&self) -> f64 {
+impl_executable!(Limit; Forex, Crypto, Stock, Index, SecFuture, SecOption, Commodity, Combo; {
+    fn get_quantity(&self) -> Quantity {
         self.quantity
     }
 
@@ -1203,7 +3361,124 @@ impl_executable!(Limit; Forex, Crypto, Stock, Index, SecFuture, SecOption, Commo
         self.time_in_force
     }
 
-    fn get_limit_price(&self) -> Option<f64> {
+    fn get_limit_price(&self) -> Option<Price> {
+        Some(self.price)
+    }
+});
+impl_executable!(Stop; Forex, Crypto, Stock, Index, SecFuture, SecOption, Commodity; {
+    fn get_quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    fn get_order_type(&self) -> &'static str {
+        "STP"
+    }
+
+    fn get_time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+    }
+
+    fn get_auxiliary_price(&self) -> Option<Price> {
         Some(self.price)
     }
 });
+impl_executable!(PeggedToMarket; Stock; {
+    fn get_quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    fn get_order_type(&self) -> &'static str {
+        "PEG MKT"
+    }
+
+    fn get_time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+    }
+
+    fn get_auxiliary_price(&self) -> Option<Price> {
+        Some(self.offset)
+    }
+});
+impl_executable!(PeggedToMidpoint; Stock; {
+    fn get_quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    fn get_order_type(&self) -> &'static str {
+        "PEG MID"
+    }
+
+    fn get_time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+    }
+
+    fn get_limit_price(&self) -> Option<Price> {
+        self.price_cap
+    }
+
+    fn get_auxiliary_price(&self) -> Option<Price> {
+        Some(self.offset)
+    }
+});
+impl_executable!(Relative; Stock, SecOption, SecFuture; {
+    fn get_quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    fn get_order_type(&self) -> &'static str {
+        "REL"
+    }
+
+    fn get_time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+    }
+
+    fn get_limit_price(&self) -> Option<Price> {
+        self.price_cap
+    }
+
+    fn get_auxiliary_price(&self) -> Option<Price> {
+        Some(self.offset)
+    }
+});
+impl_executable!(PeggedToBenchmark; Stock, SecOption; {
+    fn get_quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    fn get_order_type(&self) -> &'static str {
+        "PEG BENCH"
+    }
+
+    fn get_time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+    }
+
+    fn get_box_starting_price(&self) -> Option<Price> {
+        Some(self.starting_price)
+    }
+
+    fn get_box_stock_reference_price(&self) -> Option<Price> {
+        Some(self.reference_price)
+    }
+
+    fn get_box_vol_stock_range_lower(&self) -> Option<Price> {
+        self.reference_range.map(|(lower, _)| lower)
+    }
+
+    fn get_box_vol_stock_range_upper(&self) -> Option<Price> {
+        self.reference_range.map(|(_, upper)| upper)
+    }
+
+    fn get_peg_bench_order_content(
+        &self,
+    ) -> ConditionalField<(), (ContractId, bool, Price, Price, Routing)> {
+        ConditionalField::Present((
+            self.reference_contract_id,
+            self.pegged_change_amount.is_negative(),
+            self.pegged_change_amount.abs(),
+            self.reference_change_amount,
+            self.reference_exchange,
+        ))
+    }
+});
@@ -0,0 +1,78 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// === Type definitions ===
+
+/// A place in which a client records the highest order ID it has used, so that a restarted client
+/// never reuses an order ID, even when the server's next valid ID has been reset (see
+/// [`crate::client::Client::set_order_id_store`]).
+pub trait OrderIdStore: std::fmt::Debug + Send + Sync {
+    /// Load the recorded high-water mark.
+    ///
+    /// # Errors
+    /// Returns any error encountered while reading the mark.
+    ///
+    /// # Returns
+    /// The lowest order ID that has not been used, or [`None`] if no mark has been recorded.
+    fn load(&self) -> std::io::Result<Option<i64>>;
+
+    /// Record a new high-water mark.
+    ///
+    /// # Arguments
+    /// * `next_id` - The lowest order ID that has not been used.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the mark.
+    fn save(&self, next_id: i64) -> std::io::Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An [`OrderIdStore`] that records the high-water mark in a single text file.
+pub struct FileOrderIdStore {
+    path: PathBuf,
+}
+
+// === Type implementations ===
+
+impl FileOrderIdStore {
+    #[inline]
+    #[must_use]
+    /// Create a new store. The file is created the first time that a mark is saved.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the path of the file.
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl OrderIdStore for FileOrderIdStore {
+    fn load(&self) -> std::io::Result<Option<i64>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents.trim().parse().map(Some).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid order ID in {}: {e}", self.path.display()),
+                )
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self, next_id: i64) -> std::io::Result<()> {
+        // The mark is written to a temporary file first so that a crash never leaves it truncated
+        let temp = self.path.with_extension("tmp");
+        let mut file = std::fs::File::create(&temp)?;
+        writeln!(file, "{next_id}")?;
+        file.sync_all()?;
+        std::fs::rename(temp, &self.path)
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{HashMap, VecDeque};
+
+use std::sync::Arc;
+
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::client::Shared;
+use crate::comm::Writer;
+
+// === Constants ===
+
+/// The number of historical data requests that IBKR allows within [`WINDOW`].
+const REQUESTS_PER_WINDOW: usize = 60;
+/// The period over which IBKR counts historical data requests.
+const WINDOW: Duration = Duration::from_secs(600);
+/// The minimum spacing between two identical historical data requests.
+const IDENTICAL_SPACING: Duration = Duration::from_secs(15);
+
+// === Type definitions ===
+
+#[derive(Debug, Default)]
+/// Schedules historical data requests so that they respect IBKR's pacing rules, namely that no
+/// more than 60 requests are made in any ten-minute period and that an identical request is not
+/// repeated within fifteen seconds.
+///
+/// Requests are scheduled in the order in which they are reserved, so a request is never sent
+/// before one that was reserved earlier.
+pub(crate) struct Pacer {
+    scheduled: VecDeque<Instant>,
+    identical: HashMap<Vec<u8>, Instant>,
+    queue: Option<UnboundedSender<Paced>>,
+}
+
+#[derive(Debug)]
+/// A request that waits in the queue of the pacing writer until the time that was reserved for it.
+pub(crate) struct Paced {
+    at: Instant,
+    req_id: i64,
+    msg: Vec<u8>,
+}
+
+// === Type implementations ===
+
+impl Pacer {
+    /// Reserve the earliest time at which a request may be sent.
+    ///
+    /// # Arguments
+    /// * `key` - The encoded request without its request ID, which identifies identical requests.
+    ///
+    /// # Returns
+    /// The time at which the request may be sent.
+    pub(crate) fn reserve(&mut self, key: Vec<u8>) -> Instant {
+        let now = Instant::now();
+        let mut at = self.scheduled.back().map_or(now, |last| now.max(*last));
+        if self.scheduled.len() >= REQUESTS_PER_WINDOW {
+            at = at.max(self.scheduled[self.scheduled.len() - REQUESTS_PER_WINDOW] + WINDOW);
+        }
+        if let Some(previous) = self.identical.get(&key) {
+            at = at.max(*previous + IDENTICAL_SPACING);
+        }
+
+        self.scheduled.push_back(at);
+        while self.scheduled.len() > REQUESTS_PER_WINDOW {
+            self.scheduled.pop_front();
+        }
+        self.identical
+            .retain(|_, previous| *previous + IDENTICAL_SPACING > now);
+        self.identical.insert(key, at);
+        at
+    }
+
+    /// Reserve the earliest time at which a request may be sent and queue it to be written at
+    /// that time, so that the caller need not wait. If the queue's writer is not running, it is
+    /// started with the writer and cancellation token returned by `start`.
+    ///
+    /// # Arguments
+    /// * `key` - The encoded request without its request ID, which identifies identical requests.
+    /// * `req_id` - The ID of the request, to which any error encountered while writing it is
+    /// delivered.
+    /// * `msg` - The encoded request.
+    /// * `shared` - The state to which errors are delivered.
+    /// * `start` - Returns the writer and cancellation token of a new pacing writer.
+    pub(crate) fn enqueue(
+        &mut self,
+        key: Vec<u8>,
+        req_id: i64,
+        msg: Vec<u8>,
+        shared: &Arc<Shared>,
+        start: impl FnOnce() -> (Writer, CancellationToken),
+    ) {
+        let at = self.reserve(key);
+        let queue = match self.queue.take() {
+            Some(queue) if !queue.is_closed() => queue,
+            _ => {
+                let (writer, cancel) = start();
+                spawn_writer(writer, Arc::clone(shared), cancel)
+            }
+        };
+        if let Err(unsent) = queue.send(Paced { at, req_id, msg }) {
+            let error = std::io::Error::from(std::io::ErrorKind::NotConnected);
+            fail_request(shared, unsent.0.req_id, error.into());
+        }
+        self.queue = Some(queue);
+    }
+}
+
+// === Functions ===
+
+/// Spawn the task that writes paced requests in order, each once its reserved time has arrived.
+fn spawn_writer(
+    mut writer: Writer,
+    shared: Arc<Shared>,
+    cancel: CancellationToken,
+) -> UnboundedSender<Paced> {
+    let (queue, mut paced) = tokio::sync::mpsc::unbounded_channel::<Paced>();
+    tokio::spawn(async move {
+        loop {
+            let request = tokio::select! {
+                () = cancel.cancelled() => break,
+                request = paced.recv() => match request {
+                    Some(request) => request,
+                    None => break,
+                },
+            };
+            let now = Instant::now();
+            if request.at > now {
+                event!(
+                    debug,
+                    wait = ?(request.at - now),
+                    "Delaying historical data request to respect pacing"
+                );
+                tokio::select! {
+                    () = cancel.cancelled() => break,
+                    () = tokio::time::sleep_until(request.at) => (),
+                }
+            }
+            writer.add_raw(&request.msg);
+            if let Err(e) = writer.send().await {
+                fail_request(&shared, request.req_id, e);
+            }
+        }
+    });
+    queue
+}
+
+/// Deliver an error to the historical bar or tick request with the given ID.
+fn fail_request(shared: &Shared, req_id: i64, error: crate::error::Error) {
+    if shared.historical_bars.is_awaited(&req_id) {
+        shared.historical_bars.deliver(&req_id, Err(error));
+    } else {
+        shared.historical_ticks.deliver(&req_id, Err(error));
+    }
+}
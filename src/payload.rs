@@ -1,6 +1,11 @@
 use chrono::NaiveDateTime;
 
-use crate::contract::ContractId;
+use crate::account::AccountId;
+use crate::contract::{ContractId, DeltaNeutralContract};
+use crate::currency::Currency;
+use crate::execution::OrderSide;
+use crate::order::TimeInForce;
+use crate::quantity::{Price, Quantity};
 use serde::Serialize;
 use std::str::FromStr;
 
@@ -63,6 +68,189 @@ impl FromStr for ExchangeId {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+/// One of the exchanges that comprise the SMART aggregate exchange, as returned by a
+/// [`crate::client::Client::req_smart_components`] request.
+pub struct SmartComponent {
+    /// The bit number that identifies the exchange.
+    pub bit_number: u32,
+    /// The component exchange.
+    pub exchange: crate::exchange::Primary,
+    /// The letter that identifies the exchange in a [`crate::tick::QuotingExchanges`] tick.
+    pub letter: char,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// The options on an underlying contract that share a trading class, as returned by a
+/// [`crate::client::Client::req_option_chain`] request.
+pub struct OptionChain {
+    /// The ID of the underlying contract.
+    pub underlying_contract_id: ContractId,
+    /// The trading class of the options (ex: "SPXW" for the weekly options on SPX).
+    pub trading_class: String,
+    /// The options' multiplier, as reported by IBKR (ex: "100"), which need not be a whole
+    /// number.
+    pub multiplier: String,
+    /// The codes of the exchanges on which the options trade, as reported by IBKR (ex: "CBOE"),
+    /// including any exchange that is not a [`crate::exchange::Routing`] supported by the crate.
+    pub exchanges: Vec<String>,
+    /// The options' expiration dates, in ascending order.
+    pub expirations: Vec<chrono::NaiveDate>,
+    /// The options' strike prices, in ascending order.
+    pub strikes: Vec<f64>,
+}
+
+impl OptionChain {
+    /// Merge the options that trade on another exchange into the chain.
+    pub(crate) fn merge(
+        &mut self,
+        exchange: String,
+        expirations: Vec<chrono::NaiveDate>,
+        strikes: Vec<f64>,
+    ) {
+        if !self.exchanges.contains(&exchange) {
+            self.exchanges.push(exchange);
+        }
+        self.expirations.extend(expirations);
+        self.expirations.sort_unstable();
+        self.expirations.dedup();
+        self.strikes.extend(strikes);
+        self.strikes.sort_unstable_by(f64::total_cmp);
+        self.strikes.dedup();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A contract whose symbol or description matches a pattern, as returned by a
+/// [`crate::client::Client::req_matching_symbols`] request.
+pub struct SymbolSample {
+    /// The ID of the contract.
+    pub contract_id: ContractId,
+    /// The contract's symbol.
+    pub symbol: String,
+    /// The type of the contract, as named by IBKR (ex: "STK").
+    pub security_type: String,
+    /// The contract's primary exchange, or [`None`] if it is not one of the supported exchanges.
+    pub primary_exchange: Option<crate::exchange::Primary>,
+    /// The currency in which the contract is denominated, or [`None`] if it is not one of the
+    /// supported currencies.
+    pub currency: Option<Currency>,
+    /// The types of the derivatives that are written on the contract (ex: "OPT", "WAR").
+    pub derivative_security_types: Vec<String>,
+    /// The description of the contract, which is only reported for bonds.
+    pub description: String,
+    /// The ID of the contract's issuer, which is only reported for bonds.
+    pub issuer_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A news provider to which the user is subscribed, as returned by a
+/// [`crate::client::Client::req_news_providers`] request.
+pub struct NewsProvider {
+    /// The provider's code (ex: "BRFG"), which identifies it in news requests.
+    pub code: String,
+    /// The provider's name.
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The headline of a news article, as returned by a
+/// [`crate::client::Client::req_historical_news`] request or streamed by a
+/// [`crate::client::Client::req_market_data`] subscription to news ticks.
+pub struct NewsHeadline {
+    /// The time at which the article was published, in UTC.
+    pub datetime: NaiveDateTime,
+    /// The code of the article's provider.
+    pub provider_code: String,
+    /// The ID of the article, with which its body is requested in
+    /// [`crate::client::Client::req_news_article`].
+    pub article_id: String,
+    /// The headline.
+    pub headline: String,
+    /// Any extra data that the provider attached to the headline (ex: its language or sentiment
+    /// score), which is only reported for streamed headlines.
+    pub extra_data: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The headlines returned by a [`crate::client::Client::req_historical_news`] request.
+pub struct HistoricalNews {
+    /// The headlines, from the most recent to the oldest.
+    pub headlines: Vec<NewsHeadline>,
+    /// Whether more headlines matched the request than the number that was requested.
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The format of a [`NewsArticle`].
+pub enum ArticleType {
+    /// Plain text or HTML.
+    Text,
+    /// A binary document (such as a PDF), encoded in base 64.
+    Binary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The body of a news article, as returned by a [`crate::client::Client::req_news_article`]
+/// request.
+pub struct NewsArticle {
+    /// The format of the article.
+    pub article_type: ArticleType,
+    /// The article's text, or its base 64 encoding for a binary article.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The kind of a [`NewsBulletin`].
+pub enum BulletinKind {
+    /// A regular news bulletin.
+    Regular,
+    /// An exchange is no longer available for trading.
+    ExchangeUnavailable,
+    /// An exchange is available for trading again.
+    ExchangeAvailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An IB news bulletin, as streamed by a [`crate::client::Client::req_news_bulletins`]
+/// subscription.
+pub struct NewsBulletin {
+    /// The ID of the bulletin.
+    pub id: i64,
+    /// The kind of bulletin.
+    pub kind: BulletinKind,
+    /// The bulletin's message.
+    pub message: String,
+    /// The exchange from which the bulletin originated.
+    pub exchange: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A single trading session in a [`HistoricalSchedule`].
+pub struct ScheduledSession {
+    /// The trading date to which the session belongs, which may differ from the calendar date
+    /// of its open (ex: for futures that open on the previous evening).
+    pub reference_date: chrono::NaiveDate,
+    /// The time at which the session opens.
+    pub open: chrono::DateTime<chrono::Utc>,
+    /// The time at which the session closes.
+    pub close: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The trading sessions of a contract over a period, as returned by a
+/// [`crate::client::Client::req_historical_schedule`] request.
+pub struct HistoricalSchedule {
+    /// The time zone of the contract's exchange.
+    pub time_zone: crate::timezone::Tz,
+    /// The beginning of the period.
+    pub start: chrono::DateTime<chrono::Utc>,
+    /// The end of the period.
+    pub end: chrono::DateTime<chrono::Utc>,
+    /// The sessions in the period, in chronological order.
+    pub sessions: Vec<ScheduledSession>,
+}
+
 /// Re-export of [`crate::market_data::live_data::Class`].
 pub type MarketDataClass = crate::market_data::live_data::Class;
 
@@ -81,19 +269,43 @@ pub mod market_depth {
         Delete(CompleteEntry),
     }
 
+    #[derive(Debug, Default, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+    /// An error type that represents an invalid order book operation.
+    pub struct ParseOperationError(pub i64);
+
+    impl std::fmt::Display for ParseOperationError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "Invalid int encountered while parsing operation: {}",
+                self.0
+            )
+        }
+    }
+
+    impl std::error::Error for ParseOperationError {}
+
+    #[derive(Debug, Default, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+    /// An error type that represents an invalid side of an order book.
+    pub struct ParseSideError(pub u32);
+
+    impl std::fmt::Display for ParseSideError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Invalid int encountered while parsing side: {}", self.0)
+        }
+    }
+
+    impl std::error::Error for ParseSideError {}
+
     impl TryFrom<(i64, CompleteEntry)> for Operation {
-        type Error = anyhow::Error;
+        type Error = ParseOperationError;
 
         fn try_from(value: (i64, CompleteEntry)) -> Result<Self, Self::Error> {
             Ok(match value.0 {
                 0 => Self::Insert(value.1),
                 1 => Self::Update(value.1),
                 2 => Self::Delete(value.1),
-                _ => {
-                    return Err(anyhow::Error::msg(
-                        "Invalid int encountered while parsing operation",
-                    ))
-                }
+                operation => return Err(ParseOperationError(operation)),
             })
         }
     }
@@ -122,7 +334,7 @@ pub mod market_depth {
     }
 
     impl TryFrom<(u32, u64, f64, f64)> for Entry {
-        type Error = anyhow::Error;
+        type Error = ParseSideError;
 
         fn try_from(value: (u32, u64, f64, f64)) -> Result<Self, Self::Error> {
             Ok(match value.0 {
@@ -136,9 +348,7 @@ pub mod market_depth {
                     price: value.2,
                     size: value.3,
                 },
-                _ => Err(anyhow::Error::msg(
-                    "Invalid int encountered while parsing side",
-                ))?,
+                side => return Err(ParseSideError(side)),
             })
         }
     }
@@ -211,6 +421,17 @@ pub enum Bar {
     },
 }
 
+impl Bar {
+    #[inline]
+    #[must_use]
+    /// Get the core of the bar, which holds its timestamp and prices.
+    pub const fn core(&self) -> &BarCore {
+        match self {
+            Self::Ordinary(core) | Self::Trades { bar: core, .. } => core,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 /// A historical or live tick.
 pub enum Tick {
@@ -233,6 +454,8 @@ pub enum Tick {
         bid_size: f64,
         /// The ask size.
         ask_size: f64,
+        /// The attributes of the quote.
+        attributes: crate::tick::TickAttributes,
     },
     /// A tick representing the last trade.
     Last {
@@ -244,16 +467,81 @@ pub enum Tick {
         size: f64,
         /// The last traded exchange.
         exchange: crate::exchange::Primary,
+        /// The attributes of the trade.
+        attributes: crate::tick::TickAttributes,
     },
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+/// A snapshot of the prices and sizes of a single security, as returned by
+/// [`crate::client::Client::req_quotes`]. Each value is [`None`] if it was not received before the
+/// snapshot ended.
+pub struct Quote {
+    /// The best bid price.
+    pub bid: Option<f64>,
+    /// The size at the best bid price.
+    pub bid_size: Option<f64>,
+    /// The best ask price.
+    pub ask: Option<f64>,
+    /// The size at the best ask price.
+    pub ask_size: Option<f64>,
+    /// The last traded price.
+    pub last: Option<f64>,
+    /// The last traded size.
+    pub last_size: Option<f64>,
+    /// The day's high price.
+    pub high: Option<f64>,
+    /// The day's low price.
+    pub low: Option<f64>,
+    /// The day's opening price.
+    pub open: Option<f64>,
+    /// The previous day's closing price.
+    pub close: Option<f64>,
+    /// The day's traded volume.
+    pub volume: Option<f64>,
+    /// Whether the snapshot ended before the request timed out.
+    pub complete: bool,
+}
+
+impl Quote {
+    #[inline]
+    #[must_use]
+    /// Return the midpoint of the best bid and ask prices, falling back to the last traded price
+    /// and then to the previous day's closing price when either side of the market is missing.
+    pub fn midpoint(&self) -> Option<f64> {
+        match (self.bid, self.ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => self.last.or(self.close),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn apply(&mut self, tick_type: u16, value: f64) {
+        let field = match tick_type {
+            0 | 69 => &mut self.bid_size,
+            1 | 66 => &mut self.bid,
+            2 | 67 => &mut self.ask,
+            3 | 70 => &mut self.ask_size,
+            4 | 68 => &mut self.last,
+            5 | 71 => &mut self.last_size,
+            6 | 72 => &mut self.high,
+            7 | 73 => &mut self.low,
+            8 | 74 => &mut self.volume,
+            9 | 75 => &mut self.close,
+            14 | 76 => &mut self.open,
+            _ => return,
+        };
+        *field = Some(value);
+    }
+}
+
 #[derive(Debug, Clone, PartialOrd, PartialEq)]
 /// A single position, comprising a single security and details about its current value, P&L, etc.
 pub struct Position {
     /// The ID of the underlying contract.
     pub contract_id: ContractId,
     /// The number of contracts owned.
-    pub position: f64,
+    pub position: Quantity,
     /// The current market price of each contract.
     pub market_price: f64,
     /// The current market value of the entire position.
@@ -265,7 +553,7 @@ pub struct Position {
     /// The realized P&L of the position.
     pub realized_pnl: f64,
     /// The account number holding the position.
-    pub account_number: String,
+    pub account_number: AccountId,
 }
 
 #[derive(Debug, Clone, PartialOrd, PartialEq)]
@@ -274,11 +562,11 @@ pub struct PositionSummary {
     /// The ID of the underlying contract.
     pub contract_id: ContractId,
     /// The number of contracts owned.
-    pub position: f64,
+    pub position: Quantity,
     /// The average cost per contract for the entire position.
     pub average_cost: f64,
     /// The account number holding the position.
-    pub account_number: String,
+    pub account_number: AccountId,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialOrd, PartialEq)]
@@ -292,6 +580,181 @@ pub struct Pnl {
     pub realized: f64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A gap in a [`crate::client::Client::req_real_time_bars`] subscription, which occurs when bars
+/// are dropped (for example, during a brief loss of connection).
+pub struct MissedBars {
+    /// The timestamp of the first missing bar.
+    pub from: NaiveDateTime,
+    /// The timestamp of the last missing bar.
+    pub to: NaiveDateTime,
+}
+
+impl MissedBars {
+    #[inline]
+    #[must_use]
+    /// Return the number of missing bars.
+    pub fn count(&self) -> i64 {
+        (self.to - self.from).num_seconds() / REAL_TIME_BAR_SECONDS + 1
+    }
+}
+
+/// The length of each bar in a [`crate::client::Client::req_real_time_bars`] subscription.
+pub(crate) const REAL_TIME_BAR_SECONDS: i64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
+/// An order that is open, as reported by the [`crate::client::Client::req_open_orders`] family
+/// of requests.
+pub struct OpenOrder {
+    /// The ID of the order.
+    pub order_id: i64,
+    /// The ID of the contract being traded.
+    pub contract_id: ContractId,
+    /// Whether the order buys or sells.
+    pub side: OrderSide,
+    /// The total quantity of the order.
+    pub quantity: Quantity,
+    /// The limit price of the order, if any.
+    pub limit_price: Option<Price>,
+    /// The auxiliary price of the order (e.g. the stop price of a stop order), if any.
+    pub aux_price: Option<Price>,
+    /// The time in force of the order.
+    pub time_in_force: TimeInForce,
+    /// The delta-neutral contract with which the order's combo is hedged, if any.
+    pub delta_neutral_contract: Option<DeltaNeutralContract>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The state of an order, as reported by an [`OrderStatusUpdate`].
+pub enum OrderStatus {
+    /// The order has not yet been acknowledged by TWS / IB Gateway.
+    ApiPending,
+    /// The order has been transmitted, but not yet accepted by IBKR.
+    PendingSubmit,
+    /// A cancellation of the order has been requested, but not yet confirmed.
+    PendingCancel,
+    /// The order has been accepted by IBKR, but is held until it is elected (ex: a simulated stop
+    /// order whose price has not yet been reached).
+    PreSubmitted,
+    /// The order has been accepted and is working.
+    Submitted,
+    /// The order was cancelled by TWS / IB Gateway before it was submitted.
+    ApiCancelled,
+    /// The order was cancelled.
+    Cancelled,
+    /// The order was filled in its entirety.
+    Filled,
+    /// The order was received, but is not working (ex: it was rejected).
+    Inactive,
+}
+
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+/// An error type returned when a given status cannot be matched with a valid [`OrderStatus`].
+pub struct ParseOrderStatusError(String);
+
+impl std::fmt::Display for ParseOrderStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid order status {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseOrderStatusError {}
+
+impl FromStr for OrderStatus {
+    type Err = ParseOrderStatusError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "ApiPending" => Self::ApiPending,
+            "PendingSubmit" => Self::PendingSubmit,
+            "PendingCancel" => Self::PendingCancel,
+            "PreSubmitted" => Self::PreSubmitted,
+            "Submitted" => Self::Submitted,
+            "ApiCancelled" => Self::ApiCancelled,
+            "Cancelled" => Self::Cancelled,
+            "Filled" => Self::Filled,
+            "Inactive" => Self::Inactive,
+            s => return Err(ParseOrderStatusError(s.to_owned())),
+        })
+    }
+}
+
+impl OrderStatus {
+    #[inline]
+    #[must_use]
+    /// Return whether the order is done, such that its status will not change again.
+    pub const fn is_done(self) -> bool {
+        matches!(
+            self,
+            Self::ApiCancelled | Self::Cancelled | Self::Filled | Self::Inactive
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+/// A change in the status of an order, such as when it is submitted, filled, or cancelled.
+pub struct OrderStatusUpdate {
+    /// The ID of the order.
+    pub order_id: i64,
+    /// The order's new status.
+    pub status: OrderStatus,
+    /// The number of shares / units that have been filled.
+    pub filled: Quantity,
+    /// The number of shares / units that remain to be filled.
+    pub remaining: Quantity,
+    /// The average price of the order's fills.
+    pub average_fill_price: f64,
+    /// The order's permanent ID, which remains the same across API sessions.
+    pub perm_id: i64,
+    /// The ID of the order's parent, or 0 if it has none.
+    pub parent_id: i64,
+    /// The price of the order's most recent fill.
+    pub last_fill_price: f64,
+    /// The ID of the client that placed the order.
+    pub client_id: i64,
+    /// The reason the order is held, if it is (ex: "locate" when shares cannot be located for a
+    /// short sale).
+    pub why_held: String,
+    /// The price to which a market order was capped by IBKR, if it was.
+    pub market_cap_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialOrd, PartialEq)]
+/// The margin and commission impact of an order, as previewed by
+/// [`crate::client::Client::req_what_if_order`] without placing the order.
+///
+/// Each value is [`None`] if IBKR did not report it.
+pub struct OrderState {
+    /// The account's initial margin before the order.
+    pub initial_margin_before: Option<f64>,
+    /// The account's maintenance margin before the order.
+    pub maintenance_margin_before: Option<f64>,
+    /// The account's equity with loan value before the order.
+    pub equity_with_loan_before: Option<f64>,
+    /// The change in the account's initial margin due to the order.
+    pub initial_margin_change: Option<f64>,
+    /// The change in the account's maintenance margin due to the order.
+    pub maintenance_margin_change: Option<f64>,
+    /// The change in the account's equity with loan value due to the order.
+    pub equity_with_loan_change: Option<f64>,
+    /// The account's initial margin after the order.
+    pub initial_margin_after: Option<f64>,
+    /// The account's maintenance margin after the order.
+    pub maintenance_margin_after: Option<f64>,
+    /// The account's equity with loan value after the order.
+    pub equity_with_loan_after: Option<f64>,
+    /// The estimated commission of the order.
+    pub commission: Option<f64>,
+    /// The lowest possible commission of the order.
+    pub min_commission: Option<f64>,
+    /// The highest possible commission of the order.
+    pub max_commission: Option<f64>,
+    /// The currency in which the commission is charged.
+    pub commission_currency: Option<Currency>,
+    /// A warning about the order, such as one concerning its margin impact.
+    pub warning: Option<String>,
+}
+
 #[allow(non_snake_case, missing_docs)]
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct OrderDetails {
@@ -0,0 +1,279 @@
+use std::fmt::Formatter;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+
+// === Constants ===
+
+/// The number of decimal places with which a [`Quantity`] or a [`Price`] is stored.
+const DECIMALS: u32 = 16;
+/// The value of one unit, in the fixed-point representation of a [`Quantity`] or a [`Price`].
+const ONE: i128 = 10_i128.pow(DECIMALS);
+
+// === Type definitions ===
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An exact decimal quantity of shares, contracts, or units of a currency, such as a fractional
+/// share or a crypto quantity, with up to 16 decimal places.
+///
+/// Unlike an [`f64`], a [`Quantity`] represents every decimal value that IBKR sends and accepts
+/// exactly, so that quantities can be added and compared without rounding errors.
+///
+/// # Examples
+/// ```
+/// # use ibapi::quantity::Quantity;
+/// let fill: Quantity = "0.1".parse().unwrap();
+/// assert_eq!(fill + fill + fill, "0.3".parse().unwrap());
+/// assert_eq!(Quantity::from(100).to_string(), "100");
+/// ```
+pub struct Quantity(i128);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An exact decimal price, such as a limit price or the price of an execution, with up to 16
+/// decimal places.
+///
+/// A [`Price`] is represented in the same way as a [`Quantity`], so that prices are sent to and
+/// received from IBKR without rounding errors.
+///
+/// # Examples
+/// ```
+/// # use ibapi::quantity::Price;
+/// let price: Price = "101.15".parse().unwrap();
+/// assert_eq!(price, Price::try_from(101.15).unwrap());
+/// assert_eq!((price - Price::from(100)).to_string(), "1.15");
+/// ```
+pub struct Price(i128);
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An error type returned when a value cannot be represented as a [`Quantity`] or a [`Price`],
+/// either because it is not a decimal number or because it has too many decimal places or digits.
+pub struct ParseQuantityError(pub String);
+
+impl std::fmt::Display for ParseQuantityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid decimal number {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseQuantityError {}
+
+// === Type implementations ===
+
+macro_rules! impl_decimal {
+    ($($decimal: ident),*) => {
+        $(
+            impl $decimal {
+                /// A value of zero.
+                pub const ZERO: Self = Self(0);
+
+                #[inline]
+                #[must_use]
+                /// Return whether the value is zero.
+                pub const fn is_zero(self) -> bool {
+                    self.0 == 0
+                }
+
+                #[inline]
+                #[must_use]
+                /// Return whether the value is less than zero, such as the size of a short
+                /// position.
+                pub const fn is_negative(self) -> bool {
+                    self.0 < 0
+                }
+
+                #[inline]
+                #[must_use]
+                /// Return the absolute value.
+                pub const fn abs(self) -> Self {
+                    Self(self.0.abs())
+                }
+
+                #[inline]
+                #[must_use]
+                #[allow(clippy::cast_precision_loss)]
+                /// Return the nearest [`f64`] to the value, such as to compute the value of a
+                /// quantity at a given price.
+                pub fn to_f64(self) -> f64 {
+                    // Formatting and parsing rounds correctly, unlike dividing two rounded floats
+                    self.to_string()
+                        .parse()
+                        .unwrap_or(self.0 as f64 / ONE as f64)
+                }
+            }
+
+            impl std::fmt::Display for $decimal {
+                fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                    write_decimal(f, self.0)
+                }
+            }
+
+            impl FromStr for $decimal {
+                type Err = ParseQuantityError;
+
+                #[inline]
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    parse_decimal(s).map(Self)
+                }
+            }
+
+            impl TryFrom<f64> for $decimal {
+                type Error = ParseQuantityError;
+
+                #[inline]
+                /// Convert the shortest decimal representation of a float, so that a value like
+                /// `0.1` is converted to exactly one tenth. A float with more than 16 decimal
+                /// places is rounded to the 16th.
+                fn try_from(value: f64) -> Result<Self, Self::Error> {
+                    if !value.is_finite() {
+                        return Err(ParseQuantityError(value.to_string()));
+                    }
+                    ryu::Buffer::new()
+                        .format_finite(value)
+                        .parse()
+                        .or_else(|_| format!("{value:.16}").parse())
+                }
+            }
+
+            impl_from_int!($decimal; i32, i64, u32, u64);
+
+            impl Add for $decimal {
+                type Output = Self;
+
+                #[inline]
+                fn add(self, rhs: Self) -> Self::Output {
+                    Self(self.0 + rhs.0)
+                }
+            }
+
+            impl AddAssign for $decimal {
+                #[inline]
+                fn add_assign(&mut self, rhs: Self) {
+                    self.0 += rhs.0;
+                }
+            }
+
+            impl Sub for $decimal {
+                type Output = Self;
+
+                #[inline]
+                fn sub(self, rhs: Self) -> Self::Output {
+                    Self(self.0 - rhs.0)
+                }
+            }
+
+            impl SubAssign for $decimal {
+                #[inline]
+                fn sub_assign(&mut self, rhs: Self) {
+                    self.0 -= rhs.0;
+                }
+            }
+
+            impl Neg for $decimal {
+                type Output = Self;
+
+                #[inline]
+                fn neg(self) -> Self::Output {
+                    Self(-self.0)
+                }
+            }
+
+            impl std::iter::Sum for $decimal {
+                fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                    iter.fold(Self::ZERO, Add::add)
+                }
+            }
+
+            impl Serialize for $decimal {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: Serializer,
+                {
+                    serializer.collect_str(self)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_from_int {
+    ($decimal: ident; $($int: ty),*) => {
+        $(
+            impl From<$int> for $decimal {
+                #[inline]
+                fn from(value: $int) -> Self {
+                    Self(i128::from(value) * ONE)
+                }
+            }
+        )*
+    };
+}
+
+impl_decimal!(Quantity, Price);
+
+// === Helper functions ===
+
+fn write_decimal(f: &mut Formatter<'_>, value: i128) -> std::fmt::Result {
+    let sign = if value < 0 { "-" } else { "" };
+    let (whole, fraction) = (
+        value.unsigned_abs() / ONE.unsigned_abs(),
+        value.unsigned_abs() % ONE.unsigned_abs(),
+    );
+    if fraction == 0 {
+        write!(f, "{sign}{whole}")
+    } else {
+        let fraction = format!("{fraction:0width$}", width = DECIMALS as usize);
+        write!(f, "{sign}{whole}.{}", fraction.trim_end_matches('0'))
+    }
+}
+
+fn parse_decimal(s: &str) -> Result<i128, ParseQuantityError> {
+    let err = || ParseQuantityError(s.to_owned());
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().map_err(|_| err())?),
+        None => (s, 0),
+    };
+    let (negative, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if whole.is_empty() && fraction.is_empty()
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(err());
+    }
+
+    // Scale the digits so that the last one sits at its place in the fixed-point value
+    let digits = whole.chars().chain(fraction.chars()).collect::<String>();
+    let digits = digits.trim_start_matches('0');
+    // The exponent is untrusted, so the scale is computed without overflowing
+    let scale = i32::try_from(DECIMALS)
+        .ok()
+        .and_then(|decimals| decimals.checked_add(exponent))
+        .and_then(|scale| scale.checked_sub(i32::try_from(fraction.len()).ok()?))
+        .ok_or_else(err)?;
+    let value = if digits.is_empty() {
+        0
+    } else if scale >= 0 {
+        digits
+            .parse::<i128>()
+            .ok()
+            .and_then(|v| v.checked_mul(10_i128.checked_pow(scale.unsigned_abs())?))
+            .ok_or_else(err)?
+    } else {
+        // Digits beyond the last decimal place are only accepted if they are all zeros
+        let cut = digits.len().saturating_sub(scale.unsigned_abs() as usize);
+        if digits[cut..].chars().any(|c| c != '0') {
+            return Err(err());
+        }
+        match &digits[..cut] {
+            "" => 0,
+            kept => kept.parse::<i128>().map_err(|_| err())?,
+        }
+    };
+    Ok(if negative { -value } else { value })
+}
@@ -1,44 +1,60 @@
-use bytes::{Buf, BytesMut};
 use crossbeam::queue::SegQueue;
 use std::sync::Arc;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
+
+use crate::client::Shared;
+use crate::comm::ReadHalf;
 
 #[derive(Debug)]
 pub struct Reader {
-    inner: OwnedReadHalf,
+    inner: ReadHalf,
     queue: Arc<SegQueue<Vec<String>>>,
     disconnect: tokio_util::sync::CancellationToken,
+    shared: Arc<Shared>,
 }
 
 impl Reader {
     pub fn new(
-        r_reader: OwnedReadHalf,
+        r_reader: ReadHalf,
         r_queue: Arc<SegQueue<Vec<String>>>,
         r_disconnect: tokio_util::sync::CancellationToken,
+        r_shared: Arc<Shared>,
     ) -> Self {
         Self {
             inner: r_reader,
             queue: r_queue,
             disconnect: r_disconnect,
+            shared: r_shared,
         }
     }
 
     pub async fn run(mut self) -> Self {
         loop {
             tokio::select! {
-                () = self.disconnect.cancelled() => {println!("Reader thread: disconnecting"); break self},
-                () = async {
-                    if let Ok(Ok(len)) = self.inner.read_u32().await.map(usize::try_from) {
-                        let mut buf = BytesMut::with_capacity(len);
-                        if len == self.inner.read_buf(&mut buf).await.unwrap_or(0) {
-                            let msg = buf.chunk()
+                () = self.disconnect.cancelled() => {event!(debug, "Reader thread: disconnecting"); break self},
+                connected = async {
+                    match self.inner.read_u32().await {
+                        Ok(len) => {
+                            let Ok(len) = usize::try_from(len) else { return true };
+                            // A message may span several reads (or TLS records), so the whole body
+                            // is read before it is split into fields
+                            let mut buf = vec![0; len];
+                            if self.inner.read_exact(&mut buf).await.is_err() {
+                                return false;
+                            }
+                            let msg = buf
                                 .split(|b| *b == 0)
                                 .map(|s| core::str::from_utf8(s).unwrap_or("").to_owned())
                                 .collect::<Vec<String>>();
                             self.queue.push(msg);
+                            self.shared.received.notify_one();
+                            true
                         }
+                        // The socket was closed, either by a network failure or by the IBKR
+                        // platform itself (e.g. when TWS restarts)
+                        Err(_) => false,
                     }
-                } => (),
+                } => if !connected { event!(warn, "Reader thread: connection lost"); break self },
             }
         }
     }
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+
+use crate::account::AccountId;
+use crate::contract::{Contract, ContractDetails, ContractId, SecOption};
+use crate::error::Error;
+use crate::execution::OrderSide;
+use crate::flatten::{self, Holding};
+use crate::payload::{OrderState, OrderStatus};
+use crate::quantity::{Price, Quantity};
+use crate::tracker::OrderTracker;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The position that a [`Rebalancer`] aims to hold in a contract.
+pub enum Target {
+    /// A number of shares / units, which is negative for a short position.
+    Quantity(Quantity),
+    /// A fraction of the rebalanced capital (ex: 0.25 for a quarter of it), which is negative for
+    /// a short position.
+    Weight(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// How the orders placed by a rebalance are priced.
+pub enum Aggression {
+    /// A market order, which fills as quickly as possible at any price.
+    Market,
+    /// A limit order priced relative to the contract's reference price (see
+    /// [`Rebalancer::with_price`]).
+    Limit {
+        /// The distance of the limit price through the reference price, as a fraction of the
+        /// reference price (ex: 0.005 for a limit 0.5% through it, or 0 for a limit at it).
+        slippage: f64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Describes the positions that an account should hold, from which
+/// [`crate::client::Client::plan_rebalance`] computes the orders that reach them and
+/// [`crate::client::Client::req_rebalance`] places those orders.
+///
+/// The account's current positions are taken from [`crate::client::Client::req_positions`] or
+/// [`crate::client::Client::req_account_updates`], one of which must be active. Contracts that
+/// are not given a target are left as they are.
+pub struct Rebalancer {
+    account_number: AccountId,
+    targets: Vec<(Contract, Target)>,
+    prices: HashMap<ContractId, f64>,
+    capital: Option<f64>,
+    aggression: Aggression,
+    preview: Option<std::time::Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// An order computed by [`crate::client::Client::plan_rebalance`].
+pub struct RebalanceOrder {
+    /// The contract to trade.
+    pub contract: Contract,
+    /// Whether to buy or sell the contract.
+    pub side: OrderSide,
+    /// The number of shares / units to trade.
+    pub quantity: Quantity,
+    /// The limit price, or [`None`] for a market order.
+    pub limit_price: Option<Price>,
+    /// The account's position in the contract before the order.
+    pub current: Quantity,
+    /// The account's position in the contract once the order is filled.
+    pub target: Quantity,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// An error caused when a rebalance cannot be planned or placed.
+pub enum RebalanceError {
+    /// A [`Target::Weight`] was given, but no capital was provided with
+    /// [`Rebalancer::with_capital`] and the account's net liquidation value is unknown.
+    MissingCapital,
+    /// The target of a contract could not be converted into a quantity (ex: a weight that is not
+    /// finite).
+    InvalidTarget(ContractId),
+    /// The preview of an order showed that the account's initial margin would exceed its equity
+    /// with loan value, so no order was placed.
+    InsufficientMargin {
+        /// The ID of the order's contract.
+        contract_id: ContractId,
+        /// The preview of the order.
+        preview: Box<OrderState>,
+    },
+}
+
+#[derive(Debug)]
+/// The orders placed by [`crate::client::Client::req_rebalance`], along with their previews.
+pub struct Rebalance {
+    orders: Vec<RebalanceOrder>,
+    previews: Vec<OrderState>,
+    trackers: Vec<OrderTracker>,
+}
+
+// === Type implementations ===
+
+impl Rebalancer {
+    #[inline]
+    #[must_use]
+    /// Create a rebalancer for an account, without any targets, that places market orders without
+    /// previewing them.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account to rebalance.
+    pub fn new(account_number: AccountId) -> Self {
+        Self {
+            account_number,
+            targets: Vec::new(),
+            prices: HashMap::new(),
+            capital: None,
+            aggression: Aggression::Market,
+            preview: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the position to hold in a contract, replacing any previous target for it.
+    ///
+    /// # Arguments
+    /// * `contract` - The contract.
+    /// * `target` - The position to hold.
+    pub fn with_target(mut self, contract: Contract, target: Target) -> Self {
+        let contract_id = contract.get_contract_id();
+        self.targets
+            .retain(|(existing, _)| existing.get_contract_id() != contract_id);
+        self.targets.push((contract, target));
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the reference price of a contract, with which weights are converted into quantities and
+    /// limit orders are priced. By default, the contract's most recent market price from
+    /// [`crate::client::Client::req_account_updates`] is used.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract.
+    /// * `price` - The reference price.
+    pub fn with_price(mut self, contract_id: ContractId, price: f64) -> Self {
+        self.prices.insert(contract_id, price);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the capital of which each [`Target::Weight`] is a fraction. By default, the account's
+    /// net liquidation value from [`crate::client::Client::req_account_updates`] is used.
+    pub const fn with_capital(mut self, capital: f64) -> Self {
+        self.capital = Some(capital);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set how the orders are priced.
+    pub const fn with_aggression(mut self, aggression: Aggression) -> Self {
+        self.aggression = aggression;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Preview the margin impact of each order before placing any of them, and place none if any
+    /// preview shows that the account's initial margin would exceed its equity with loan value.
+    /// Each order is previewed on its own, so the combined impact of the orders is not checked.
+    ///
+    /// # Arguments
+    /// * `timeout` - The maximum amount of time to wait for each preview.
+    pub const fn with_preview(mut self, timeout: std::time::Duration) -> Self {
+        self.preview = Some(timeout);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the account to rebalance.
+    pub const fn get_account_number(&self) -> &AccountId {
+        &self.account_number
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn get_preview(&self) -> Option<std::time::Duration> {
+        self.preview
+    }
+
+    /// Compute the orders that move the account's positions to their targets.
+    ///
+    /// # Arguments
+    /// * `get_holding` - Get the account's position in a contract, or [`None`] if it holds none. The
+    ///   account's positions must have been received, so that an unknown position is not taken as
+    ///   flat.
+    /// * `get_details` - Get the details of a contract, from which its lot size is taken.
+    /// * `net_liquidation` - The account's net liquidation value, if known.
+    ///
+    /// # Returns
+    /// The orders, with the sales before the purchases so that they free up capital first.
+    pub(crate) fn plan(
+        &self,
+        get_holding: impl Fn(ContractId) -> Option<Holding>,
+        get_details: impl Fn(ContractId) -> Option<ContractDetails>,
+        net_liquidation: Option<f64>,
+    ) -> Result<Vec<RebalanceOrder>, Error> {
+        let mut orders = Vec::with_capacity(self.targets.len());
+        for (contract, target) in &self.targets {
+            let contract_id = contract.get_contract_id();
+            let holding = get_holding(contract_id);
+            let current = holding.map_or(Quantity::ZERO, |holding| holding.position);
+            let price = self
+                .prices
+                .get(&contract_id)
+                .copied()
+                .or_else(|| holding.and_then(|holding| holding.market_price));
+            let get_price = || price.ok_or(Error::MissingMarketPrice(contract_id));
+
+            let target = match *target {
+                Target::Quantity(quantity) => quantity.to_f64(),
+                Target::Weight(weight) => {
+                    let capital = self
+                        .capital
+                        .or(net_liquidation)
+                        .ok_or(Error::Rebalance(RebalanceError::MissingCapital))?;
+                    capital * weight / (get_price()? * f64::from(get_multiplier(contract)))
+                }
+            };
+            let details = get_details(contract_id).unwrap_or_default();
+            let increment = details
+                .size_increment
+                .filter(|increment| *increment > 0.0)
+                .unwrap_or(1.0);
+            // Trade whole lots, rounding towards the current position so as to never overshoot
+            let lots = ((target - current.to_f64()) / increment).trunc();
+            let decimals = increment
+                .to_string()
+                .split_once('.')
+                .map_or(0, |(_, fraction)| fraction.len());
+            let delta: Quantity = format!("{:.decimals$}", lots * increment)
+                .parse()
+                .map_err(|_| Error::Rebalance(RebalanceError::InvalidTarget(contract_id)))?;
+            let quantity = delta.abs();
+            if quantity.is_zero()
+                || details
+                    .min_size
+                    .is_some_and(|min_size| quantity.to_f64() < min_size)
+            {
+                continue;
+            }
+
+            let side = if delta.is_negative() {
+                OrderSide::Sell
+            } else {
+                OrderSide::Buy
+            };
+            let limit_price = match self.aggression {
+                Aggression::Market => None,
+                Aggression::Limit { slippage } => Some(
+                    flatten::get_marketable_price(
+                        side,
+                        get_price()?,
+                        slippage,
+                        flatten::get_min_tick(contract),
+                    )
+                    .ok_or(Error::MissingMarketPrice(contract_id))?,
+                ),
+            };
+            orders.push(RebalanceOrder {
+                contract: contract.clone(),
+                side,
+                quantity,
+                limit_price,
+                current,
+                target: current + delta,
+            });
+        }
+        orders.sort_by_key(|order| order.side == OrderSide::Buy);
+        Ok(orders)
+    }
+}
+
+impl std::fmt::Display for RebalanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingCapital => write!(
+                f,
+                "A target weight was given, but the capital to rebalance is unknown"
+            ),
+            Self::InvalidTarget(contract_id) => {
+                write!(f, "Invalid target for contract {}", contract_id.0)
+            }
+            Self::InsufficientMargin { contract_id, .. } => write!(
+                f,
+                "The order for contract {} would exceed the account's available margin",
+                contract_id.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RebalanceError {}
+
+impl Rebalance {
+    #[inline]
+    pub(crate) fn new(
+        orders: Vec<RebalanceOrder>,
+        previews: Vec<OrderState>,
+        trackers: Vec<OrderTracker>,
+    ) -> Self {
+        Self {
+            orders,
+            previews,
+            trackers,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the orders that were placed.
+    pub fn get_orders(&self) -> &[RebalanceOrder] {
+        &self.orders
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the preview of each order, in the same order as [`Rebalance::get_orders`], or an empty
+    /// slice if the orders were not previewed.
+    pub fn get_previews(&self) -> &[OrderState] {
+        &self.previews
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the tracker of each order, in the same order as [`Rebalance::get_orders`].
+    pub fn get_trackers_mut(&mut self) -> &mut [OrderTracker] {
+        &mut self.trackers
+    }
+
+    /// Wait until every order is done, discarding any intermediate events.
+    ///
+    /// # Returns
+    /// Returns [`true`] if every order was filled.
+    pub async fn done(&mut self) -> bool {
+        let mut filled = true;
+        for tracker in &mut self.trackers {
+            filled &= tracker.done().await == Some(OrderStatus::Filled);
+        }
+        filled
+    }
+}
+
+// === Helper functions ===
+
+#[inline]
+/// Get the number of units of a contract's underlying that each unit of the contract represents.
+fn get_multiplier(contract: &Contract) -> u32 {
+    match contract {
+        Contract::SecFuture(fut) => fut.multiplier,
+        Contract::SecOption(SecOption::Call(inner) | SecOption::Put(inner)) => inner.multiplier,
+        _ => 1,
+    }
+}
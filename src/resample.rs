@@ -0,0 +1,447 @@
+use chrono::{DateTime, NaiveTime, TimeZone, Utc};
+
+use crate::calendar::Session;
+use crate::payload::{Bar, BarCore, Tick};
+use crate::timezone::Tz;
+
+// === Constants ===
+
+/// The number of seconds in a calendar day, which is the longest resolution a [`TickAggregator`]
+/// supports.
+const SECONDS_PER_DAY: u32 = 86_400;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, PartialEq)]
+/// Aggregates trade ticks, such as those returned by [`crate::client::Client::req_historical_ticks`]
+/// for [`crate::market_data::historical_ticks::data_types::Trades`], into OHLCV bars of any
+/// resolution, including those shorter than IBKR's own bars.
+///
+/// Bars are aligned to the open of each trading session (ex: hourly bars from 9:30 to 10:30 on a
+/// US stock exchange) and never span two sessions, so the last bar of a session is cut short at
+/// its close when the resolution does not evenly divide the session. Each bar is timestamped in UTC
+/// at the start of its interval, as are IBKR's own bars. Intervals without any trades produce no
+/// bar.
+///
+/// Only [`Tick::Last`] ticks that fall within a session are aggregated; the other ticks are
+/// ignored. The ticks must be pushed in chronological order.
+pub struct TickAggregator {
+    resolution: u32,
+    time_zone: Tz,
+    sessions: Vec<Session>,
+    current: Option<PartialBar>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Builds bars of several resolutions at once from shorter bars of the same contract, such as the
+/// five-second bars of [`crate::client::Client::req_real_time_bars`], so that the bars of every
+/// resolution stay synchronized.
+///
+/// Each bar is only returned once it is complete, which is as soon as a pushed bar ends with its
+/// interval or with its session, or otherwise once a pushed bar falls beyond it. The bars are
+/// aligned to the open of each trading session as in a [`TickAggregator`], so a resolution of one
+/// day yields one bar per session, which is returned with the session's last pushed bar.
+pub struct MultiResolution {
+    base: u32,
+    time_zone: Tz,
+    sessions: Vec<Session>,
+    frames: Vec<Frame>,
+    trades: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Frame {
+    resolution: u32,
+    current: Option<PartialBar>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PartialBar {
+    session: Session,
+    interval: u32,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    notional: f64,
+    trade_count: u64,
+}
+
+// === Type implementations ===
+
+impl TickAggregator {
+    #[must_use]
+    /// Create a new aggregator.
+    ///
+    /// # Arguments
+    /// * `resolution` - The length of each bar, in whole seconds.
+    /// * `time_zone` - The exchange's time zone, from [`crate::client::Client::get_time_zone`].
+    /// * `sessions` - The trading sessions to which the ticks belong, such as those of a
+    /// [`crate::client::Client::req_historical_schedule`] request. If there are none, each
+    /// exchange date is treated as a session from midnight to midnight.
+    ///
+    /// # Returns
+    /// The aggregator, or [`None`] if the resolution is not a whole number of seconds between one
+    /// second and one day.
+    pub fn new(
+        resolution: std::time::Duration,
+        time_zone: Tz,
+        sessions: &[Session],
+    ) -> Option<Self> {
+        if resolution.subsec_nanos() != 0 {
+            return None;
+        }
+        let resolution = u32::try_from(resolution.as_secs()).ok()?;
+        (1..=SECONDS_PER_DAY).contains(&resolution).then(|| Self {
+            resolution,
+            time_zone,
+            sessions: sort_sessions(sessions),
+            current: None,
+        })
+    }
+
+    /// Add a tick to the bar that is being built.
+    ///
+    /// # Arguments
+    /// * `tick` - The next tick.
+    ///
+    /// # Returns
+    /// The previous bar, once the tick falls beyond its interval.
+    pub fn push(&mut self, tick: &Tick) -> Option<Bar> {
+        let Tick::Last {
+            datetime,
+            price,
+            size,
+            ..
+        } = *tick
+        else {
+            return None;
+        };
+        let (session, interval) = locate(
+            &self.sessions,
+            self.time_zone,
+            datetime.and_utc(),
+            self.resolution,
+        )?;
+
+        match &mut self.current {
+            Some(bar) if bar.session == session && bar.interval == interval => {
+                bar.high = bar.high.max(price);
+                bar.low = bar.low.min(price);
+                bar.close = price;
+                bar.volume += size;
+                bar.notional += price * size;
+                bar.trade_count += 1;
+                None
+            }
+            current => {
+                let completed = current.take().map(|bar| self.to_bar(&bar));
+                self.current = Some(PartialBar {
+                    session,
+                    interval,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: size,
+                    notional: price * size,
+                    trade_count: 1,
+                });
+                completed
+            }
+        }
+    }
+
+    /// Complete the bar that is being built if its interval has ended, even though no later tick
+    /// has been pushed, such as when trading is quiet.
+    ///
+    /// # Arguments
+    /// * `now` - The current time, such as from [`crate::clock::Clock::now`].
+    ///
+    /// # Returns
+    /// The bar, or [`None`] if no bar is being built or its interval has not yet ended.
+    pub fn flush(&mut self, now: DateTime<Utc>) -> Option<Bar> {
+        let bar = self.current?;
+        (now >= get_end(&bar, self.resolution))
+            .then(|| self.finish())
+            .flatten()
+    }
+
+    #[must_use]
+    /// Complete the bar that is being built, such as once the final tick has been pushed.
+    ///
+    /// # Returns
+    /// The bar, or [`None`] if no tick has been pushed since the last bar was completed.
+    pub fn finish(&mut self) -> Option<Bar> {
+        self.current.take().map(|bar| self.to_bar(&bar))
+    }
+
+    #[inline]
+    fn to_bar(&self, bar: &PartialBar) -> Bar {
+        to_bar(bar, self.resolution)
+    }
+}
+
+impl MultiResolution {
+    #[must_use]
+    /// Create a new set of synchronized resolutions.
+    ///
+    /// # Arguments
+    /// * `base` - The length of each bar that is pushed (ex: five seconds for real-time bars).
+    /// * `resolutions` - The lengths of the bars to build (ex: one minute, five minutes and one
+    /// day).
+    /// * `time_zone` - The exchange's time zone, from [`crate::client::Client::get_time_zone`].
+    /// * `sessions` - The trading sessions to which the bars belong, as in
+    /// [`TickAggregator::new`].
+    ///
+    /// # Returns
+    /// The resolutions, or [`None`] if any resolution is not a whole multiple of the base length,
+    /// or is longer than a day.
+    pub fn new(
+        base: std::time::Duration,
+        resolutions: &[std::time::Duration],
+        time_zone: Tz,
+        sessions: &[Session],
+    ) -> Option<Self> {
+        let seconds = |length: std::time::Duration| {
+            let seconds = u32::try_from(length.as_secs()).ok()?;
+            (length.subsec_nanos() == 0 && (1..=SECONDS_PER_DAY).contains(&seconds))
+                .then_some(seconds)
+        };
+        let base = seconds(base)?;
+        let mut frames = resolutions
+            .iter()
+            .map(|&resolution| {
+                let resolution = seconds(resolution).filter(|resolution| resolution % base == 0)?;
+                Some(Frame {
+                    resolution,
+                    current: None,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        frames.sort_unstable_by_key(|frame| frame.resolution);
+        frames.dedup_by_key(|frame| frame.resolution);
+        Some(Self {
+            base,
+            time_zone,
+            sessions: sort_sessions(sessions),
+            frames,
+            trades: false,
+        })
+    }
+
+    /// Add a bar to the bars that are being built.
+    ///
+    /// # Arguments
+    /// * `bar` - The next bar, whose length must be the base length.
+    ///
+    /// # Returns
+    /// The bars that the new bar completes, with their resolutions, from the shortest resolution
+    /// to the longest. A bar outside every session completes nothing and is otherwise ignored.
+    pub fn push(&mut self, bar: &Bar) -> Vec<(std::time::Duration, Bar)> {
+        let (core, volume, wap, trade_count) = match *bar {
+            Bar::Ordinary(core) => (core, 0.0, core.close, 0),
+            Bar::Trades {
+                bar,
+                volume,
+                wap,
+                trade_count,
+            } => (bar, volume, wap, trade_count),
+        };
+        let time = core.datetime.and_utc();
+        let Some((session, _)) = locate(&self.sessions, self.time_zone, time, self.base) else {
+            return Vec::new();
+        };
+        self.trades = matches!(bar, Bar::Trades { .. });
+        let end = time + chrono::Duration::seconds(i64::from(self.base));
+
+        let mut completed = Vec::new();
+        for frame in &mut self.frames {
+            let interval = get_interval(&session, time, frame.resolution);
+            match &mut frame.current {
+                Some(current) if current.session == session && current.interval == interval => {
+                    current.high = current.high.max(core.high);
+                    current.low = current.low.min(core.low);
+                    current.close = core.close;
+                    current.volume += volume;
+                    current.notional += wap * volume;
+                    current.trade_count += trade_count;
+                }
+                current => {
+                    if let Some(previous) = current.take() {
+                        completed.push((frame.resolution, previous));
+                    }
+                    *current = Some(PartialBar {
+                        session,
+                        interval,
+                        open: core.open,
+                        high: core.high,
+                        low: core.low,
+                        close: core.close,
+                        volume,
+                        notional: wap * volume,
+                        trade_count,
+                    });
+                }
+            }
+            // A bar that ends with its interval or its session completes it without waiting for
+            // the next bar
+            if frame
+                .current
+                .is_some_and(|current| end >= get_end(&current, frame.resolution))
+            {
+                completed.extend(frame.current.take().map(|bar| (frame.resolution, bar)));
+            }
+        }
+        completed
+            .into_iter()
+            .map(|(resolution, bar)| self.to_output(resolution, &bar))
+            .collect()
+    }
+
+    #[must_use]
+    /// Complete every bar that is being built, such as once the final bar has been pushed.
+    ///
+    /// # Returns
+    /// The bars, with their resolutions, from the shortest resolution to the longest.
+    pub fn finish(&mut self) -> Vec<(std::time::Duration, Bar)> {
+        let completed = self
+            .frames
+            .iter_mut()
+            .filter_map(|frame| frame.current.take().map(|bar| (frame.resolution, bar)))
+            .collect::<Vec<_>>();
+        completed
+            .into_iter()
+            .map(|(resolution, bar)| self.to_output(resolution, &bar))
+            .collect()
+    }
+
+    fn to_output(&self, resolution: u32, bar: &PartialBar) -> (std::time::Duration, Bar) {
+        let bar = match to_bar(bar, resolution) {
+            Bar::Trades { bar, .. } if !self.trades => Bar::Ordinary(bar),
+            bar => bar,
+        };
+        (std::time::Duration::from_secs(u64::from(resolution)), bar)
+    }
+}
+
+// === Functions ===
+
+/// Aggregate trade ticks into OHLCV bars with a [`TickAggregator`].
+///
+/// # Arguments
+/// * `ticks` - The ticks, in chronological order.
+/// * `resolution` - The length of each bar, in whole seconds.
+/// * `time_zone` - The exchange's time zone, from [`crate::client::Client::get_time_zone`].
+/// * `sessions` - The trading sessions to which the ticks belong, as in [`TickAggregator::new`].
+///
+/// # Returns
+/// The bars in chronological order, or [`None`] if the resolution is not a whole number of seconds
+/// between one second and one day.
+pub fn ticks_to_bars<'t, I>(
+    ticks: I,
+    resolution: std::time::Duration,
+    time_zone: Tz,
+    sessions: &[Session],
+) -> Option<Vec<Bar>>
+where
+    I: IntoIterator<Item = &'t Tick>,
+{
+    let mut aggregator = TickAggregator::new(resolution, time_zone, sessions)?;
+    let mut bars = ticks
+        .into_iter()
+        .filter_map(|tick| aggregator.push(tick))
+        .collect::<Vec<_>>();
+    bars.extend(aggregator.finish());
+    Some(bars)
+}
+
+// === Helper functions ===
+
+fn sort_sessions(sessions: &[Session]) -> Vec<Session> {
+    let mut sessions = sessions.to_vec();
+    sessions.sort_unstable();
+    sessions.dedup();
+    sessions
+}
+
+/// Find the session in which a time falls, along with the index of its interval within that
+/// session.
+fn locate(
+    sessions: &[Session],
+    time_zone: Tz,
+    time: DateTime<Utc>,
+    resolution: u32,
+) -> Option<(Session, u32)> {
+    let session = if sessions.is_empty() {
+        get_exchange_date(time_zone, time)
+    } else {
+        let index = sessions.partition_point(|session| session.open <= time);
+        sessions
+            .get(index.checked_sub(1)?)
+            .copied()
+            .filter(|session| time < session.close)?
+    };
+    Some((session, get_interval(&session, time, resolution)))
+}
+
+#[inline]
+fn get_interval(session: &Session, time: DateTime<Utc>, resolution: u32) -> u32 {
+    u32::try_from((time - session.open).num_seconds() / i64::from(resolution)).unwrap_or(u32::MAX)
+}
+
+#[inline]
+fn get_start(bar: &PartialBar, resolution: u32) -> DateTime<Utc> {
+    bar.session.open + chrono::Duration::seconds(i64::from(bar.interval) * i64::from(resolution))
+}
+
+/// Get the end of a bar's interval, which is cut short by the close of its session.
+#[inline]
+fn get_end(bar: &PartialBar, resolution: u32) -> DateTime<Utc> {
+    (get_start(bar, resolution) + chrono::Duration::seconds(i64::from(resolution)))
+        .min(bar.session.close)
+}
+
+/// Get the session that spans an exchange date from midnight to midnight, for when no sessions are
+/// given.
+fn get_exchange_date(time_zone: Tz, time: DateTime<Utc>) -> Session {
+    let midnight = |date: chrono::NaiveDate| {
+        let local = date.and_time(NaiveTime::MIN);
+        // A midnight that falls in a daylight saving gap is moved to the end of the gap
+        time_zone
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| {
+                time_zone
+                    .from_local_datetime(&(local + chrono::Duration::hours(1)))
+                    .earliest()
+            })
+            .map_or_else(|| local.and_utc(), |start| start.with_timezone(&Utc))
+    };
+    let date = time.with_timezone(&time_zone).date_naive();
+    Session {
+        open: midnight(date),
+        close: date.succ_opt().map_or(DateTime::<Utc>::MAX_UTC, midnight),
+    }
+}
+
+/// Convert a bar that is being built into a bar timestamped in UTC at the start of its interval.
+fn to_bar(bar: &PartialBar, resolution: u32) -> Bar {
+    Bar::Trades {
+        bar: BarCore {
+            datetime: get_start(bar, resolution).naive_utc(),
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+        },
+        volume: bar.volume,
+        wap: if bar.volume > 0.0 {
+            bar.notional / bar.volume
+        } else {
+            bar.close
+        },
+        trade_count: bar.trade_count,
+    }
+}
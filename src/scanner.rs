@@ -0,0 +1,107 @@
+use serde::{Serialize, Serializer};
+
+use crate::contract::ContractId;
+use crate::currency::Currency;
+use crate::exchange::Routing;
+
+// === Type definitions ===
+
+#[derive(Debug, Default, Clone, PartialEq, PartialOrd, Serialize)]
+/// The parameters of a market scanner subscription made with
+/// [`crate::client::Client::req_scanner_subscription`].
+///
+/// The valid values of the text parameters can be found in the XML document returned by a market
+/// scanner parameters request.
+pub struct Subscription {
+    /// The maximum number of results to return. If [`None`], IBKR's default is used.
+    pub number_of_rows: Option<u32>,
+    /// The type of instrument to scan (ie: `"STK"`).
+    pub instrument: String,
+    /// The location of the instruments to scan (ie: `"STK.US.MAJOR"`).
+    pub location_code: String,
+    /// The scan to run (ie: `"TOP_PERC_GAIN"`).
+    pub scan_code: String,
+    /// Exclude results whose price is below this value.
+    pub above_price: Option<f64>,
+    /// Exclude results whose price is above this value.
+    pub below_price: Option<f64>,
+    /// Exclude results whose volume is below this value.
+    pub above_volume: Option<i64>,
+    /// Exclude results whose market capitalization is below this value.
+    pub market_cap_above: Option<f64>,
+    /// Exclude results whose market capitalization is above this value.
+    pub market_cap_below: Option<f64>,
+    /// Exclude results whose Moody's rating is below this value.
+    pub moody_rating_above: String,
+    /// Exclude results whose Moody's rating is above this value.
+    pub moody_rating_below: String,
+    /// Exclude results whose S&P rating is below this value.
+    pub sp_rating_above: String,
+    /// Exclude results whose S&P rating is above this value.
+    pub sp_rating_below: String,
+    /// Exclude results whose maturity date is before this value.
+    pub maturity_date_above: String,
+    /// Exclude results whose maturity date is after this value.
+    pub maturity_date_below: String,
+    /// Exclude results whose coupon rate is below this value.
+    pub coupon_rate_above: Option<f64>,
+    /// Exclude results whose coupon rate is above this value.
+    pub coupon_rate_below: Option<f64>,
+    /// When [`true`], exclude convertible bonds.
+    pub exclude_convertible: bool,
+    /// Exclude results whose average option volume is below this value.
+    pub average_option_volume_above: Option<i64>,
+    /// Additional scanner settings, formatted as IBKR expects them.
+    pub scanner_setting_pairs: String,
+    /// Limit the results to a given type of stock (ie: `"CORP"`).
+    pub stock_type_filter: String,
+    #[serde(serialize_with = "serialize_filter_options")]
+    /// Generic filters, as `(tag, value)` pairs, to apply to the scan.
+    pub filter_options: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A single result of a market scanner subscription.
+pub struct Row {
+    /// The rank of the result in the scan, starting at 0.
+    pub rank: u32,
+    /// The contract ID of the result.
+    pub contract_id: ContractId,
+    /// The symbol of the result.
+    pub symbol: String,
+    /// The security type of the result.
+    pub security_type: String,
+    /// The exchange of the result.
+    pub exchange: Routing,
+    /// The trading currency of the result.
+    pub currency: Currency,
+    /// The local symbol of the result.
+    pub local_symbol: String,
+    /// The name of the market on which the result trades.
+    pub market_name: String,
+    /// The trading class of the result.
+    pub trading_class: String,
+    /// The distance of the result from the scan's criteria, if any.
+    pub distance: String,
+    /// The benchmark against which the result was ranked, if any.
+    pub benchmark: String,
+    /// The projection of the result, if any.
+    pub projection: String,
+    /// A description of the combination legs of the result, if any.
+    pub legs: String,
+}
+
+// === Type implementations ===
+
+#[inline]
+fn serialize_filter_options<S: Serializer>(
+    options: &[(String, String)],
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    options
+        .iter()
+        .fold(String::new(), |acc, (tag, value)| {
+            acc + tag + "=" + value + ";"
+        })
+        .serialize(ser)
+}
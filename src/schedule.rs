@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Utc, Weekday};
+
+use crate::calendar::Calendar;
+use crate::client::Shared;
+use crate::clock::{Clock, SystemClock};
+use crate::exchange::Primary;
+use crate::timezone::Tz;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The ID of a timer scheduled with [`crate::client::Client::schedule`], which identifies the
+/// timer in [`crate::wrapper::Local::timer`] and [`crate::wrapper::Remote::timer`].
+pub struct TimerId(pub u64);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// When a timer falls due.
+pub enum Schedule {
+    /// Once, at a given time. A time that has already passed falls due immediately.
+    Once(DateTime<Utc>),
+    /// Repeatedly, at a fixed rate. Runs that are missed, such as while a long callback is
+    /// running, are skipped rather than delivered in a burst.
+    Every {
+        /// The time of the first run.
+        start: DateTime<Utc>,
+        /// The amount of time between runs.
+        interval: chrono::Duration,
+    },
+    /// Daily, at a given wall-clock time in a given time zone, like a cron entry (ex: 15:55
+    /// New York time on weekdays, to manage positions before the close). A time that does not
+    /// exist on a given day, because of a daylight saving transition, is skipped on that day.
+    Daily {
+        /// The time of day.
+        time: NaiveTime,
+        /// The time zone in which `time` is given.
+        time_zone: Tz,
+        /// The days of the week on which the timer runs, or every day if empty.
+        weekdays: Vec<Weekday>,
+    },
+    /// At the open of each regular session of an exchange, according to its bundled
+    /// [`Calendar`], so that the timer is skipped on holidays. The timer stops once the calendar
+    /// no longer covers the next session.
+    SessionOpen {
+        /// The exchange.
+        exchange: Primary,
+        /// The amount of time after the open (or before it, if negative) at which to run.
+        offset: chrono::Duration,
+    },
+    /// At the close of each regular session of an exchange, according to its bundled
+    /// [`Calendar`], so that the timer follows the early close of a half day. The timer stops once
+    /// the calendar no longer covers the next session.
+    SessionClose {
+        /// The exchange.
+        exchange: Primary,
+        /// The amount of time after the close (or before it, if negative) at which to run.
+        offset: chrono::Duration,
+    },
+}
+
+#[derive(Debug)]
+/// The timers scheduled with a client, which the client loop delivers to the wrapper as they
+/// fall due according to the client's clock.
+pub(crate) struct Scheduler {
+    next_id: u64,
+    timers: HashMap<TimerId, Entry>,
+    clock: Arc<dyn Clock>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    schedule: Schedule,
+    due: DateTime<Utc>,
+}
+
+// === Type implementations ===
+
+impl std::fmt::Display for TimerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Schedule {
+    #[inline]
+    #[must_use]
+    /// Create a schedule that runs at a fixed rate, starting one interval from now according to
+    /// the system's clock. When the client follows another clock (see
+    /// [`crate::client::Client::set_clock`]), create a [`Schedule::Every`] from its time instead.
+    ///
+    /// # Arguments
+    /// * `interval` - The amount of time between runs.
+    pub fn every(interval: chrono::Duration) -> Self {
+        Self::Every {
+            start: Utc::now() + interval,
+            interval,
+        }
+    }
+
+    /// Get the first time at which the schedule falls due.
+    ///
+    /// # Arguments
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    /// The time, or [`None`] if the schedule never falls due (ex: a non-positive interval, or an
+    /// exchange without a bundled calendar).
+    fn get_first(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Once(time) => Some(*time),
+            Self::Every { start, interval } => {
+                (*interval > chrono::Duration::zero()).then_some(*start)
+            }
+            Self::Daily { .. } | Self::SessionOpen { .. } | Self::SessionClose { .. } => {
+                self.get_next(now, now)
+            }
+        }
+    }
+
+    /// Get the next time at which the schedule falls due after it fell due at `due`.
+    ///
+    /// # Arguments
+    /// * `due` - The time at which the schedule last fell due.
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    /// The time, or [`None`] if the schedule does not fall due again.
+    fn get_next(&self, due: DateTime<Utc>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Once(_) => None,
+            Self::Every { interval, .. } => {
+                let mut next = due + *interval;
+                if next <= now {
+                    let missed = (now - next).num_nanoseconds()? / interval.num_nanoseconds()?;
+                    next += *interval * i32::try_from(missed + 1).ok()?;
+                }
+                Some(next)
+            }
+            Self::Daily {
+                time,
+                time_zone,
+                weekdays,
+            } => {
+                let after = due.max(now);
+                let today = after.with_timezone(time_zone).date_naive();
+                // A week (plus a day for time zones behind UTC) covers every weekday
+                today
+                    .iter_days()
+                    .take(8)
+                    .filter(|date| weekdays.is_empty() || weekdays.contains(&date.weekday()))
+                    .filter_map(|date| {
+                        time_zone
+                            .from_local_datetime(&date.and_time(*time))
+                            .earliest()
+                    })
+                    .map(|datetime| datetime.with_timezone(&Utc))
+                    .find(|&datetime| datetime > after)
+            }
+            Self::SessionOpen { exchange, offset } => {
+                let after = due.max(now);
+                let next = Calendar::from_exchange(*exchange)?
+                    .sessions_from(after - *offset)
+                    .map(|session| session.open + *offset)
+                    .find(|&time| time > after);
+                warn_if_uncovered(next, *exchange)
+            }
+            Self::SessionClose { exchange, offset } => {
+                let after = due.max(now);
+                let next = Calendar::from_exchange(*exchange)?
+                    .sessions_from(after - *offset)
+                    .map(|session| session.close + *offset)
+                    .find(|&time| time > after);
+                warn_if_uncovered(next, *exchange)
+            }
+        }
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            timers: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl Scheduler {
+    #[inline]
+    /// Get the current time according to the scheduler's clock.
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    #[inline]
+    /// Replace the clock according to which timers fall due.
+    pub(crate) fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Add a timer.
+    ///
+    /// # Returns
+    /// The timer's ID, or [`None`] if the schedule never falls due.
+    pub(crate) fn add(&mut self, schedule: Schedule, now: DateTime<Utc>) -> Option<TimerId> {
+        let due = schedule.get_first(now)?;
+        let timer_id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.timers.insert(timer_id, Entry { schedule, due });
+        Some(timer_id)
+    }
+
+    #[inline]
+    /// Remove a timer, returning whether it was scheduled.
+    pub(crate) fn cancel(&mut self, timer_id: TimerId) -> bool {
+        self.timers.remove(&timer_id).is_some()
+    }
+
+    #[inline]
+    /// Get the time at which the earliest timer falls due.
+    fn get_next_due(&self) -> Option<DateTime<Utc>> {
+        self.timers.values().map(|entry| entry.due).min()
+    }
+
+    /// Remove the timers that are due, rescheduling the recurring ones.
+    ///
+    /// # Returns
+    /// The due timers and the times at which they fell due, in chronological order.
+    fn take_due(&mut self, now: DateTime<Utc>) -> Vec<(TimerId, DateTime<Utc>)> {
+        let mut due = Vec::new();
+        self.timers.retain(|&timer_id, entry| {
+            if entry.due > now {
+                return true;
+            }
+            due.push((timer_id, entry.due));
+            match entry.schedule.get_next(entry.due, now) {
+                Some(next) => {
+                    entry.due = next;
+                    true
+                }
+                None => false,
+            }
+        });
+        due.sort_unstable_by_key(|&(timer_id, time)| (time, timer_id));
+        due
+    }
+}
+
+// === Helper functions ===
+
+#[inline]
+pub(crate) fn lock_scheduler(shared: &Shared) -> std::sync::MutexGuard<'_, Scheduler> {
+    shared
+        .scheduler
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[inline]
+/// Warn that a session timer stops because its exchange's calendar does not cover the next
+/// session, rather than letting it stop silently.
+fn warn_if_uncovered(next: Option<DateTime<Utc>>, _exchange: Primary) -> Option<DateTime<Utc>> {
+    if next.is_none() {
+        event!(
+            warn,
+            exchange = ?_exchange,
+            "The bundled calendar does not cover the next session, so the timer stops"
+        );
+    }
+    next
+}
+
+/// Wait until at least one timer is due, which the client loop awaits alongside the incoming
+/// messages so that the timers are delivered on the same task as every other callback.
+///
+/// # Returns
+/// The due timers and the times at which they fell due, in chronological order.
+pub(crate) async fn wait(shared: &Shared) -> Vec<(TimerId, DateTime<Utc>)> {
+    loop {
+        let (next_due, clock) = {
+            let mut scheduler = lock_scheduler(shared);
+            let now = scheduler.now();
+            let due = scheduler.take_due(now);
+            if !due.is_empty() {
+                return due;
+            }
+            (scheduler.get_next_due(), Arc::clone(&scheduler.clock))
+        };
+        match next_due {
+            Some(next_due) => {
+                let wait = clock.get_wait(next_due);
+                tokio::select! {
+                    () = tokio::time::sleep(wait) => (),
+                    () = shared.scheduler_changed.notified() => (),
+                }
+            }
+            None => shared.scheduler_changed.notified().await,
+        }
+    }
+}
@@ -0,0 +1,522 @@
+use std::path::Path;
+use std::ptr::NonNull;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+
+use crate::contract::ContractId;
+use crate::error::Error;
+use crate::tick::{Class, Price, Size};
+
+const MAGIC: u64 = u64::from_le_bytes(*b"IBKRTICK");
+const VERSION: u64 = 2;
+const HEADER_SIZE: usize = 64;
+const SLOT_SIZE: usize = 64;
+
+/// The generation of a file that has been replaced by a newer one.
+const RETIRED: u64 = 0;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The field of a quote that a [`SharedTick`] updates.
+pub enum TickField {
+    /// The bid price.
+    Bid,
+    /// The ask price.
+    Ask,
+    /// The last traded price.
+    Last,
+    /// The high price of the day.
+    High,
+    /// The low price of the day.
+    Low,
+    /// The previous day's closing price.
+    Close,
+    /// The session's opening price.
+    Open,
+    /// The last price traded during regular trading hours.
+    LastRthTrade,
+    /// The size offered at the bid price.
+    BidSize,
+    /// The size offered at the ask price.
+    AskSize,
+    /// The size traded at the last price.
+    LastSize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A price or size tick, as written to the shared memory of a [`TickPublisher`].
+pub struct SharedTick {
+    /// The ID of the contract.
+    pub contract_id: ContractId,
+    /// The time at which the tick was published.
+    pub timestamp: DateTime<Utc>,
+    /// The field that the tick updates.
+    pub field: TickField,
+    /// The new price or size.
+    pub value: f64,
+    /// Whether the tick is delayed rather than live.
+    pub delayed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The outcome of [`TickReader::try_read`].
+pub enum ReadOutcome {
+    /// The next tick.
+    Tick(SharedTick),
+    /// No tick has been published since the last one that was read.
+    Empty,
+    /// The reader fell so far behind that the ticks it had yet to read were overwritten. The
+    /// reader skipped the given number of ticks to catch up.
+    Lagged(u64),
+    /// The publisher replaced the file with a new one, to which no more ticks are written. The
+    /// reader must be opened again with [`TickReader::open`] to follow the new file.
+    Replaced,
+}
+
+#[derive(Debug)]
+/// Publishes the price and size ticks of a client to a ring buffer in shared memory, from which
+/// the other processes on the same machine read them without any socket or system call.
+///
+/// The buffer is a file, which is best placed on a memory-backed file system (ex:
+/// "/dev/shm/ibkr-ticks" on Linux). It consists of native-endian 8-byte words, starting with a
+/// 64-byte header: the magic number (the bytes "IBKRTICK" on a little-endian machine), the layout
+/// version (2) in the low half of a word with the slot size (64) in its high half, the number of
+/// slots (a power of two), the number of ticks published so far, and the file's generation. It is
+/// followed by the 64-byte slots, each of which holds a sequence number, the contract ID, the timestamp in nanoseconds
+/// since the Unix epoch, the bits of the value as an `f64`, and a word with the field code in its
+/// low half (1 to 11, in the order of [`TickField`]) and the delayed flag in its high half.
+///
+/// Each slot is guarded by a seqlock: the `n`-th tick (counting from 0) is written to slot
+/// `n % slots`, whose sequence number is `2n + 1` while it is being written and `2n + 2` once it
+/// is complete. A reader copies the slot and accepts it only if the sequence number was `2n + 2`
+/// both before and after the copy. There must only be one publisher per file.
+///
+/// The generation is a nonzero number that identifies the publisher that created the file. A new
+/// publisher never reuses an existing file: it sets the old file's generation to 0 and unlinks it
+/// before creating a new one, so a reader that still maps the old file sees its generation change
+/// and knows to reopen the path.
+pub struct TickPublisher {
+    mapping: Mapping,
+    capacity: u64,
+    next: u64,
+}
+
+#[derive(Debug)]
+/// Reads the ticks written by a [`TickPublisher`], such as from another process.
+pub struct TickReader {
+    mapping: Mapping,
+    capacity: u64,
+    generation: u64,
+    next: u64,
+}
+
+#[derive(Debug)]
+struct Mapping {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+// === Type implementations ===
+
+impl TickField {
+    #[inline]
+    const fn get_code(self) -> u64 {
+        match self {
+            Self::Bid => 1,
+            Self::Ask => 2,
+            Self::Last => 3,
+            Self::High => 4,
+            Self::Low => 5,
+            Self::Close => 6,
+            Self::Open => 7,
+            Self::LastRthTrade => 8,
+            Self::BidSize => 9,
+            Self::AskSize => 10,
+            Self::LastSize => 11,
+        }
+    }
+
+    #[inline]
+    const fn from_code(code: u64) -> Option<Self> {
+        Some(match code {
+            1 => Self::Bid,
+            2 => Self::Ask,
+            3 => Self::Last,
+            4 => Self::High,
+            5 => Self::Low,
+            6 => Self::Close,
+            7 => Self::Open,
+            8 => Self::LastRthTrade,
+            9 => Self::BidSize,
+            10 => Self::AskSize,
+            11 => Self::LastSize,
+            _ => return None,
+        })
+    }
+}
+
+impl SharedTick {
+    #[must_use]
+    /// Create a tick from a price, stamped with the current time.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract.
+    /// * `price` - The price, as received in [`crate::wrapper::Local::price_data`] (or
+    ///   [`crate::wrapper::Remote::price_data`]).
+    pub fn from_price(contract_id: ContractId, price: Class<Price>) -> Self {
+        let (price, delayed) = match price {
+            Class::Live(price) => (price, false),
+            Class::Delayed(price) => (price, true),
+        };
+        let (field, value) = match price {
+            Price::Bid(value) => (TickField::Bid, value),
+            Price::Ask(value) => (TickField::Ask, value),
+            Price::Last(value) => (TickField::Last, value),
+            Price::High(value) => (TickField::High, value),
+            Price::Low(value) => (TickField::Low, value),
+            Price::Close(value) => (TickField::Close, value),
+            Price::Open(value) => (TickField::Open, value),
+            Price::LastRthTrade(value) => (TickField::LastRthTrade, value),
+        };
+        Self {
+            contract_id,
+            timestamp: Utc::now(),
+            field,
+            value,
+            delayed,
+        }
+    }
+
+    #[must_use]
+    /// Create a tick from a size, stamped with the current time.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract.
+    /// * `size` - The size, as received in [`crate::wrapper::Local::size_data`] (or
+    ///   [`crate::wrapper::Remote::size_data`]).
+    pub fn from_size(contract_id: ContractId, size: Class<Size>) -> Self {
+        let (size, delayed) = match size {
+            Class::Live(size) => (size, false),
+            Class::Delayed(size) => (size, true),
+        };
+        let (field, value) = match size {
+            Size::Bid(value) => (TickField::BidSize, value),
+            Size::Ask(value) => (TickField::AskSize, value),
+            Size::Last(value) => (TickField::LastSize, value),
+        };
+        Self {
+            contract_id,
+            timestamp: Utc::now(),
+            field,
+            value,
+            delayed,
+        }
+    }
+}
+
+impl TickPublisher {
+    /// Create the shared memory file, replacing any file that already exists at the path. The
+    /// readers of a replaced file are told to reopen the path (see [`ReadOutcome::Replaced`]).
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    /// * `capacity` - The number of ticks that the buffer holds, which is rounded up to a power of
+    ///   two. A reader that falls further behind than the capacity misses ticks.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if the file could not be created or mapped into memory.
+    pub fn create(path: impl AsRef<Path>, capacity: usize) -> Result<Self, Error> {
+        let capacity = capacity.max(1).next_power_of_two();
+        let len = capacity
+            .checked_mul(SLOT_SIZE)
+            .and_then(|len| len.checked_add(HEADER_SIZE))
+            .ok_or_else(|| invalid_data("The capacity is too large"))?;
+        let path = path.as_ref();
+        retire(path);
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => (),
+        }
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        file.set_len(len as u64)?;
+        let mapping = Mapping::new(&file, len, true)?;
+        let publisher = Self {
+            mapping,
+            capacity: capacity as u64,
+            next: 0,
+        };
+        // The creation time tells apart the files created at the same path, and is never 0
+        let generation = (Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64).max(1);
+        publisher
+            .mapping
+            .word(32)
+            .store(generation, Ordering::Relaxed);
+        publisher
+            .mapping
+            .word(16)
+            .store(publisher.capacity, Ordering::Relaxed);
+        publisher
+            .mapping
+            .word(8)
+            .store(VERSION | (SLOT_SIZE as u64) << 32, Ordering::Relaxed);
+        publisher.mapping.word(0).store(MAGIC, Ordering::Release);
+        Ok(publisher)
+    }
+
+    /// Write a tick to the buffer, overwriting the oldest tick once the buffer is full.
+    pub fn publish(&mut self, tick: &SharedTick) {
+        let n = self.next;
+        let slot = HEADER_SIZE + (n & (self.capacity - 1)) as usize * SLOT_SIZE;
+        let seq = self.mapping.word(slot);
+        seq.store(2 * n + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        self.mapping
+            .word(slot + 8)
+            .store(tick.contract_id.0 as u64, Ordering::Relaxed);
+        self.mapping.word(slot + 16).store(
+            tick.timestamp.timestamp_nanos_opt().unwrap_or_default() as u64,
+            Ordering::Relaxed,
+        );
+        self.mapping
+            .word(slot + 24)
+            .store(tick.value.to_bits(), Ordering::Relaxed);
+        self.mapping.word(slot + 32).store(
+            tick.field.get_code() | u64::from(tick.delayed) << 32,
+            Ordering::Relaxed,
+        );
+        seq.store(2 * n + 2, Ordering::Release);
+        self.next = n + 1;
+        self.mapping.word(24).store(self.next, Ordering::Release);
+    }
+
+    #[inline]
+    /// Write a price to the buffer, as in [`TickPublisher::publish`].
+    pub fn publish_price(&mut self, contract_id: ContractId, price: Class<Price>) {
+        self.publish(&SharedTick::from_price(contract_id, price));
+    }
+
+    #[inline]
+    /// Write a size to the buffer, as in [`TickPublisher::publish`].
+    pub fn publish_size(&mut self, contract_id: ContractId, size: Class<Size>) {
+        self.publish(&SharedTick::from_size(contract_id, size));
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the number of ticks published so far.
+    pub const fn get_published(&self) -> u64 {
+        self.next
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the number of ticks that the buffer holds.
+    pub const fn get_capacity(&self) -> u64 {
+        self.capacity
+    }
+}
+
+impl TickReader {
+    /// Open the shared memory file of a [`TickPublisher`]. The reader starts with the next tick
+    /// to be published.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if the file could not be opened or mapped into memory, or if it is not
+    /// the file of a [`TickPublisher`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| invalid_data("The file is too large"))?;
+        if len < HEADER_SIZE {
+            return Err(invalid_data("The file is too small"));
+        }
+        let mapping = Mapping::new(&file, len, false)?;
+        if mapping.word(0).load(Ordering::Acquire) != MAGIC {
+            return Err(invalid_data("The file was not created by a tick publisher"));
+        }
+        if mapping.word(8).load(Ordering::Relaxed) != VERSION | (SLOT_SIZE as u64) << 32 {
+            return Err(invalid_data("The file has an unsupported layout"));
+        }
+        let capacity = mapping.word(16).load(Ordering::Relaxed);
+        let expected = usize::try_from(capacity)
+            .ok()
+            .and_then(|capacity| capacity.checked_mul(SLOT_SIZE))
+            .and_then(|slots| slots.checked_add(HEADER_SIZE));
+        if !capacity.is_power_of_two() || expected != Some(len) {
+            return Err(invalid_data("The file has an invalid capacity"));
+        }
+        let generation = mapping.word(32).load(Ordering::Acquire);
+        if generation == RETIRED {
+            return Err(invalid_data("The file was replaced by a newer one"));
+        }
+        let next = mapping.word(24).load(Ordering::Acquire);
+        Ok(Self {
+            mapping,
+            capacity,
+            generation,
+            next,
+        })
+    }
+
+    /// Read the next tick without blocking.
+    pub fn try_read(&mut self) -> ReadOutcome {
+        if self.mapping.word(32).load(Ordering::Acquire) != self.generation {
+            return ReadOutcome::Replaced;
+        }
+        let n = self.next;
+        let slot = HEADER_SIZE + (n & (self.capacity - 1)) as usize * SLOT_SIZE;
+        let seq = self.mapping.word(slot);
+        let before = seq.load(Ordering::Acquire);
+        if before < 2 * n + 2 {
+            return ReadOutcome::Empty;
+        }
+        let contract_id = self.mapping.word(slot + 8).load(Ordering::Relaxed);
+        let timestamp = self.mapping.word(slot + 16).load(Ordering::Relaxed);
+        let value = self.mapping.word(slot + 24).load(Ordering::Relaxed);
+        let field = self.mapping.word(slot + 32).load(Ordering::Relaxed);
+        fence(Ordering::Acquire);
+        let after = seq.load(Ordering::Relaxed);
+        if before != 2 * n + 2 || after != before {
+            // Skip to the most recent half of the buffer, so that the reader is not overtaken
+            // again straight away
+            let head = self.mapping.word(24).load(Ordering::Acquire);
+            self.next = head.saturating_sub(self.capacity / 2).max(n + 1);
+            return ReadOutcome::Lagged(self.next - n);
+        }
+        self.next = n + 1;
+        match TickField::from_code(field & u64::from(u32::MAX)) {
+            Some(tick_field) => ReadOutcome::Tick(SharedTick {
+                contract_id: ContractId(contract_id as i64),
+                timestamp: DateTime::from_timestamp_nanos(timestamp as i64),
+                field: tick_field,
+                value: f64::from_bits(value),
+                delayed: field >> 32 != 0,
+            }),
+            // A tick from a newer publisher with more fields is skipped
+            None => ReadOutcome::Lagged(1),
+        }
+    }
+
+    /// Wait for the next tick by spinning, which burns a CPU core in exchange for the lowest
+    /// latency.
+    ///
+    /// # Returns
+    /// The next tick, the number of ticks that were skipped if the reader fell behind, or
+    /// [`ReadOutcome::Replaced`] if the file was replaced.
+    pub fn read_spin(&mut self) -> ReadOutcome {
+        loop {
+            match self.try_read() {
+                ReadOutcome::Empty => std::hint::spin_loop(),
+                outcome => return outcome,
+            }
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the number of ticks published so far, including those that were not read yet.
+    pub fn get_published(&self) -> u64 {
+        self.mapping.word(24).load(Ordering::Acquire)
+    }
+}
+
+impl Mapping {
+    fn new(file: &std::fs::File, len: usize, writable: bool) -> Result<Self, Error> {
+        use std::os::fd::AsRawFd;
+
+        let protection = if writable {
+            libc::PROT_READ | libc::PROT_WRITE
+        } else {
+            libc::PROT_READ
+        };
+        // SAFETY: The file descriptor is valid for the duration of the call, and a null address
+        // lets the kernel choose where to place the mapping.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                protection,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+        let ptr = NonNull::new(ptr.cast()).ok_or_else(|| invalid_data("Null mapping"))?;
+        Ok(Self { ptr, len })
+    }
+
+    #[inline]
+    /// Get the 8-byte word at an offset, which must be a multiple of 8 within the mapping.
+    fn word(&self, offset: usize) -> &AtomicU64 {
+        assert!(offset.is_multiple_of(8) && offset + 8 <= self.len);
+        // SAFETY: The mapping is page-aligned, so the offset is aligned for an `AtomicU64`, and it
+        // is in bounds. The memory is only ever accessed atomically, including by other processes
+        // that follow the documented layout.
+        unsafe { &*self.ptr.as_ptr().add(offset).cast::<AtomicU64>() }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: The pointer and length are those of a mapping created in `Mapping::new`, and no
+        // reference into it outlives `self`.
+        unsafe {
+            libc::munmap(self.ptr.as_ptr().cast(), self.len);
+        }
+    }
+}
+
+// SAFETY: The mapping is only accessed through atomics, so it may be shared across threads.
+unsafe impl Send for Mapping {}
+
+// SAFETY: See the implementation of `Send`.
+unsafe impl Sync for Mapping {}
+
+// === Helper functions ===
+
+/// Tell the readers of an existing publisher's file that it is being replaced, by setting its
+/// generation to [`RETIRED`]. Files that do not belong to a publisher are left untouched.
+fn retire(path: &Path) {
+    let Ok(file) = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+    else {
+        return;
+    };
+    let Some(len) = file
+        .metadata()
+        .ok()
+        .and_then(|metadata| usize::try_from(metadata.len()).ok())
+        .filter(|&len| len >= HEADER_SIZE)
+    else {
+        return;
+    };
+    if let Ok(mapping) = Mapping::new(&file, len, true) {
+        if mapping.word(0).load(Ordering::Acquire) == MAGIC
+            && mapping.word(8).load(Ordering::Relaxed) == VERSION | (SLOT_SIZE as u64) << 32
+        {
+            mapping.word(32).store(RETIRED, Ordering::Release);
+        }
+    }
+}
+
+#[inline]
+fn invalid_data(message: &str) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message,
+    ))
+}
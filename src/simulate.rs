@@ -0,0 +1,644 @@
+use chrono::NaiveDateTime;
+
+use crate::account::AccountId;
+use crate::contract::{ContractId, Security};
+use crate::currency::Currency;
+use crate::execution::{CommissionReport, Execution, OrderSide};
+use crate::order::{Executable, OneCancelsAllType, Order};
+use crate::payload::{Bar, OrderStatus, OrderStatusUpdate, Tick};
+use crate::quantity::{Price, Quantity};
+use crate::wrapper::{Local, Remote};
+
+// === Type definitions ===
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+/// The amount by which a simulated fill is worse than the price that triggered it.
+pub enum Slippage {
+    #[default]
+    /// Fill at the triggering price.
+    None,
+    /// Fill a fixed amount away from the triggering price (ex: `0.01` for one cent).
+    Absolute(f64),
+    /// Fill a fraction of the triggering price away from it (ex: `0.0005` for five basis points).
+    Relative(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The prices of a single market data event, against which a [`Simulator`]'s resting orders are
+/// matched.
+pub enum MarketEvent {
+    /// A trade, or any other single price (such as a midpoint).
+    Trade {
+        /// The time of the event.
+        datetime: NaiveDateTime,
+        /// The price.
+        price: f64,
+    },
+    /// A change in the best bid and ask.
+    Quote {
+        /// The time of the event.
+        datetime: NaiveDateTime,
+        /// The best bid price, at which sell orders fill.
+        bid: f64,
+        /// The best ask price, at which buy orders fill.
+        ask: f64,
+    },
+    /// A bar, whose open is assumed to have been traded first, followed by the rest of its range.
+    Bar {
+        /// The time of the bar.
+        datetime: NaiveDateTime,
+        /// The bar's open price.
+        open: f64,
+        /// The bar's high price.
+        high: f64,
+        /// The bar's low price.
+        low: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The order types that a [`Simulator`] can fill.
+pub enum SimulatedOrderType {
+    /// A market order, which fills at the next event's price.
+    Market,
+    /// A limit order, which fills once the market touches its price.
+    Limit(f64),
+    /// A stop order, which fills like a market order once the market touches its price.
+    Stop(f64),
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// An order that is resting in a [`Simulator`].
+pub struct SimulatedOrder {
+    /// The ID of the order.
+    pub order_id: i64,
+    /// The ID of the contract being traded.
+    pub contract_id: ContractId,
+    /// The symbol of the contract being traded.
+    pub symbol: String,
+    /// Whether the order buys or sells.
+    pub side: OrderSide,
+    /// The order's type and price.
+    pub order_type: SimulatedOrderType,
+    /// The total quantity of the order.
+    pub quantity: Quantity,
+    /// The ID of the order's parent, or 0 if it has none.
+    pub parent_id: i64,
+    /// Whether the order is held until its parent fills, during which it cannot fill.
+    pub held: bool,
+    /// The name of the One-Cancels-All group to which the order belongs.
+    pub one_cancels_all_group: Option<String>,
+    /// How the other orders in the One-Cancels-All group are handled when the order fills.
+    pub one_cancels_all_type: OneCancelsAllType,
+    /// The reference attached to the order.
+    pub order_reference: String,
+}
+
+/// Determines whether, and at what price, a [`Simulator`]'s resting orders fill.
+///
+/// Implement this trait to model fills differently than [`TouchFillModel`] does, such as to
+/// account for queue position or liquidity.
+pub trait FillModel: std::fmt::Debug {
+    /// Determine whether a resting order fills against a market event.
+    ///
+    /// # Arguments
+    /// * `order` - The resting order.
+    /// * `event` - The market event for the order's contract.
+    ///
+    /// # Returns
+    /// The price at which the order fills in its entirety, or [`None`] if it does not fill.
+    fn get_fill_price(&self, order: &SimulatedOrder, event: &MarketEvent) -> Option<f64>;
+
+    /// Determine the commission charged for a fill.
+    ///
+    /// # Arguments
+    /// * `order` - The order that was filled.
+    /// * `price` - The price at which the order was filled.
+    fn get_commission(&self, order: &SimulatedOrder, price: f64) -> f64;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+/// The default [`FillModel`]: market orders fill at the next event's price, limit orders fill once
+/// the market touches their price, and stop orders fill like market orders once the market
+/// touches their price. Market and stop fills are worse than the triggering price by the given
+/// slippage, while limit fills are never worse than the limit price.
+pub struct TouchFillModel {
+    /// The slippage applied to each fill.
+    pub slippage: Slippage,
+    /// The commission charged for each share / unit filled.
+    pub commission_per_unit: f64,
+    /// The minimum commission charged for each fill.
+    pub minimum_commission: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// An event produced by a [`Simulator`], which corresponds to a wrapper callback.
+pub enum SimulatedEvent {
+    /// An order's status changed, which corresponds to [`Local::order_status`].
+    OrderStatus(OrderStatusUpdate),
+    /// An order was filled, which corresponds to [`Local::execution`].
+    Execution(Execution),
+    /// A fill was charged a commission, which corresponds to [`Local::commission_report`].
+    CommissionReport(CommissionReport),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A simulated execution engine for backtests, which fills orders against historical ticks or
+/// bars and reports them through the same callbacks as a live session, so that the same strategy
+/// code can be run against both.
+///
+/// Orders are placed with [`Simulator::place_order`] and are matched against each subsequent
+/// event passed to [`Simulator::on_tick`], [`Simulator::on_bar`], or [`Simulator::on_event`]. An
+/// order is never filled by the event during which it was placed, and is always filled in its
+/// entirety.
+///
+/// As in a live session, an order attached to a parent (such as the exits of a bracket) is held
+/// until its parent fills, and may fill from the next event onwards. Once one of the orders
+/// attached to a parent fills, the others are cancelled. Likewise, once an order of a
+/// One-Cancels-All group fills, the other orders of the group are cancelled or, for the reducing
+/// [`OneCancelsAllType`]s, reduced by the filled quantity. Cancelling a parent also cancels the
+/// orders attached to it. The resulting [`SimulatedEvent`]s are delivered to a wrapper with
+/// [`SimulatedEvent::deliver_local`] or [`SimulatedEvent::deliver_remote`].
+pub struct Simulator<M: FillModel = TouchFillModel> {
+    model: M,
+    account_number: AccountId,
+    currency: Currency,
+    orders: Vec<SimulatedOrder>,
+    execution_count: u64,
+}
+
+// === Type implementations ===
+
+impl Slippage {
+    #[inline]
+    #[must_use]
+    /// Apply the slippage to a price.
+    ///
+    /// # Arguments
+    /// * `price` - The triggering price.
+    /// * `side` - The side of the fill, which determines the direction of the slippage.
+    ///
+    /// # Returns
+    /// The price, moved against the side of the fill.
+    pub fn apply(self, price: f64, side: OrderSide) -> f64 {
+        let amount = match self {
+            Self::None => 0.0,
+            Self::Absolute(amount) => amount,
+            Self::Relative(fraction) => price.abs() * fraction,
+        };
+        match side {
+            OrderSide::Buy => price + amount,
+            OrderSide::Sell => price - amount,
+        }
+    }
+}
+
+impl MarketEvent {
+    #[inline]
+    #[must_use]
+    /// Return the time of the event.
+    pub const fn get_datetime(&self) -> NaiveDateTime {
+        match self {
+            Self::Trade { datetime, .. }
+            | Self::Quote { datetime, .. }
+            | Self::Bar { datetime, .. } => *datetime,
+        }
+    }
+}
+
+impl From<&Tick> for MarketEvent {
+    fn from(value: &Tick) -> Self {
+        match *value {
+            Tick::Midpoint { datetime, price }
+            | Tick::Last {
+                datetime, price, ..
+            } => Self::Trade { datetime, price },
+            Tick::BidAsk {
+                datetime,
+                bid_price,
+                ask_price,
+                ..
+            } => Self::Quote {
+                datetime,
+                bid: bid_price,
+                ask: ask_price,
+            },
+        }
+    }
+}
+
+impl From<&Bar> for MarketEvent {
+    fn from(value: &Bar) -> Self {
+        let (Bar::Ordinary(bar) | Bar::Trades { bar, .. }) = value;
+        Self::Bar {
+            datetime: bar.datetime,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+        }
+    }
+}
+
+impl FillModel for TouchFillModel {
+    fn get_fill_price(&self, order: &SimulatedOrder, event: &MarketEvent) -> Option<f64> {
+        let buy = order.side == OrderSide::Buy;
+        // The price at which the order first becomes marketable, and the worst price it reaches
+        let (first, extreme) = match *event {
+            MarketEvent::Trade { price, .. } => (price, price),
+            MarketEvent::Quote { bid, ask, .. } => {
+                let price = if buy { ask } else { bid };
+                (price, price)
+            }
+            MarketEvent::Bar {
+                open, high, low, ..
+            } => (open, if buy { low } else { high }),
+        };
+        match order.order_type {
+            SimulatedOrderType::Market => Some(self.slippage.apply(first, order.side)),
+            SimulatedOrderType::Limit(limit) => {
+                let (better, touched) = if buy {
+                    (first <= limit, extreme <= limit)
+                } else {
+                    (first >= limit, extreme >= limit)
+                };
+                let price = if better {
+                    first
+                } else if touched {
+                    limit
+                } else {
+                    return None;
+                };
+                let price = self.slippage.apply(price, order.side);
+                Some(if buy {
+                    price.min(limit)
+                } else {
+                    price.max(limit)
+                })
+            }
+            SimulatedOrderType::Stop(stop) => {
+                // A stop is triggered by a move against the position, unlike a limit
+                let extreme = match *event {
+                    MarketEvent::Bar { high, low, .. } => {
+                        if buy {
+                            high
+                        } else {
+                            low
+                        }
+                    }
+                    _ => extreme,
+                };
+                let (gapped, touched) = if buy {
+                    (first >= stop, extreme >= stop)
+                } else {
+                    (first <= stop, extreme <= stop)
+                };
+                let price = if gapped {
+                    first
+                } else if touched {
+                    stop
+                } else {
+                    return None;
+                };
+                Some(self.slippage.apply(price, order.side))
+            }
+        }
+    }
+
+    #[inline]
+    fn get_commission(&self, order: &SimulatedOrder, _price: f64) -> f64 {
+        (order.quantity.abs().to_f64() * self.commission_per_unit).max(self.minimum_commission)
+    }
+}
+
+impl SimulatedEvent {
+    /// Deliver the event to the corresponding callback of a [`Local`] wrapper.
+    ///
+    /// # Arguments
+    /// * `wrapper` - The wrapper to which the event is delivered.
+    pub async fn deliver_local<'c, W: Local<'c>>(self, wrapper: &mut W) {
+        match self {
+            Self::OrderStatus(status) => {
+                wrapper.order_status(status).await;
+            }
+            Self::Execution(execution) => {
+                wrapper.execution(-1, execution).await;
+            }
+            Self::CommissionReport(report) => {
+                wrapper.commission_report(report).await;
+            }
+        }
+    }
+
+    /// Deliver the event to the corresponding callback of a [`Remote`] wrapper.
+    ///
+    /// # Arguments
+    /// * `wrapper` - The wrapper to which the event is delivered.
+    pub async fn deliver_remote<W: Remote>(self, wrapper: &mut W) {
+        match self {
+            Self::OrderStatus(status) => {
+                wrapper.order_status(status).await;
+            }
+            Self::Execution(execution) => {
+                wrapper.execution(-1, execution).await;
+            }
+            Self::CommissionReport(report) => {
+                wrapper.commission_report(report).await;
+            }
+        }
+    }
+}
+
+impl<M: FillModel> Simulator<M> {
+    #[must_use]
+    /// Create a new simulator.
+    ///
+    /// # Arguments
+    /// * `model` - The model that determines when and at what price orders fill.
+    /// * `account_number` - The account reported in each simulated [`Execution`].
+    /// * `currency` - The currency in which commissions are charged.
+    pub fn new(model: M, account_number: AccountId, currency: Currency) -> Self {
+        Self {
+            model,
+            account_number,
+            currency,
+            orders: Vec::new(),
+            execution_count: 0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the model that determines when and at what price orders fill.
+    pub const fn get_model(&self) -> &M {
+        &self.model
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the orders that are resting in the simulator.
+    pub fn get_open_orders(&self) -> &[SimulatedOrder] {
+        &self.orders
+    }
+
+    /// Place an order, which rests in the simulator until it is filled or cancelled.
+    ///
+    /// # Arguments
+    /// * `order_id` - The ID of the order, such as from [`crate::client::Client::get_next_order_id`].
+    /// * `order` - The order to place.
+    ///
+    /// # Returns
+    /// A [`OrderStatus::Submitted`] update, or a [`OrderStatus::Inactive`] update if the order's
+    /// type cannot be simulated. Only market, limit and stop orders with a quantity can be
+    /// simulated.
+    pub fn place_order<S, E>(&mut self, order_id: i64, order: &Order<S, E>) -> SimulatedEvent
+    where
+        S: Security,
+        E: Executable<S>,
+    {
+        let (side, security, exec, attributes) = match order {
+            Order::Buy {
+                security,
+                execute_method,
+                attributes,
+            } => (OrderSide::Buy, security, execute_method, attributes),
+            Order::Sell {
+                security,
+                execute_method,
+                attributes,
+            } => (OrderSide::Sell, security, execute_method, attributes),
+        };
+        let order_type = match (exec.get_order_type(), attributes.cash_quantity) {
+            ("MKT", None) => Some(SimulatedOrderType::Market),
+            ("LMT", None) => exec
+                .get_limit_price()
+                .map(|price| SimulatedOrderType::Limit(price.to_f64())),
+            ("STP", None) => exec
+                .get_auxiliary_price()
+                .map(|price| SimulatedOrderType::Stop(price.to_f64())),
+            _ => None,
+        };
+        let parent_id = attributes.parent_id.unwrap_or_else(|| exec.get_parent_id());
+        let simulated = SimulatedOrder {
+            order_id,
+            contract_id: security.get_contract_id(),
+            symbol: security.get_symbol().to_owned(),
+            side,
+            order_type: order_type.unwrap_or(SimulatedOrderType::Market),
+            quantity: exec.get_quantity(),
+            parent_id,
+            held: parent_id != 0 && self.orders.iter().any(|o| o.order_id == parent_id),
+            one_cancels_all_group: attributes
+                .one_cancels_all_group
+                .as_deref()
+                .or_else(|| exec.get_one_cancels_all_group())
+                .map(ToOwned::to_owned),
+            one_cancels_all_type: attributes
+                .one_cancels_all_type
+                .unwrap_or_else(|| exec.get_one_cancels_all_type()),
+            order_reference: exec.get_order_reference().unwrap_or_default().to_owned(),
+        };
+        let status = match order_type {
+            None => OrderStatus::Inactive,
+            Some(_) if simulated.held => OrderStatus::PreSubmitted,
+            Some(_) => OrderStatus::Submitted,
+        };
+        let update = self.status(&simulated, status, None);
+        if order_type.is_some() {
+            self.orders.retain(|o| o.order_id != order_id);
+            self.orders.push(simulated);
+        }
+        update
+    }
+
+    /// Cancel a resting order, along with any orders attached to it.
+    ///
+    /// # Arguments
+    /// * `order_id` - The ID of the order to cancel.
+    ///
+    /// # Returns
+    /// A [`OrderStatus::Cancelled`] update for the order and for each order attached to it, which
+    /// is empty if no such order is resting.
+    pub fn cancel_order(&mut self, order_id: i64) -> Vec<SimulatedEvent> {
+        let mut events = Vec::new();
+        let mut orders = std::mem::take(&mut self.orders);
+        orders.retain(|o| {
+            let cancelled = o.order_id == order_id || (o.held && o.parent_id == order_id);
+            if cancelled {
+                events.push(self.status(o, OrderStatus::Cancelled, None));
+            }
+            !cancelled
+        });
+        self.orders = orders;
+        events
+    }
+
+    /// Match the resting orders for a contract against a tick.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The contract to which the tick belongs.
+    /// * `tick` - The tick.
+    ///
+    /// # Returns
+    /// The events produced by any fills, in the order in which they occurred.
+    pub fn on_tick(&mut self, contract_id: ContractId, tick: &Tick) -> Vec<SimulatedEvent> {
+        self.on_event(contract_id, &MarketEvent::from(tick))
+    }
+
+    /// Match the resting orders for a contract against a bar.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The contract to which the bar belongs.
+    /// * `bar` - The bar.
+    ///
+    /// # Returns
+    /// The events produced by any fills, in the order in which they occurred.
+    pub fn on_bar(&mut self, contract_id: ContractId, bar: &Bar) -> Vec<SimulatedEvent> {
+        self.on_event(contract_id, &MarketEvent::from(bar))
+    }
+
+    /// Match the resting orders for a contract against a market event.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The contract to which the event belongs.
+    /// * `event` - The event.
+    ///
+    /// # Returns
+    /// The events produced by any fills, in the order in which they occurred.
+    pub fn on_event(
+        &mut self,
+        contract_id: ContractId,
+        event: &MarketEvent,
+    ) -> Vec<SimulatedEvent> {
+        let mut events = Vec::new();
+        let mut released = Vec::new();
+        let mut pending = std::collections::VecDeque::from(std::mem::take(&mut self.orders));
+        let mut resting = Vec::with_capacity(pending.len());
+        while let Some(order) = pending.pop_front() {
+            let price = (!order.held && order.contract_id == contract_id)
+                .then(|| self.model.get_fill_price(&order, event))
+                .flatten()
+                // A fill price that is not a finite number leaves the order resting
+                .and_then(|price| Price::try_from(price).ok());
+            let Some(price) = price else {
+                resting.push(order);
+                continue;
+            };
+            self.fill(&order, price, event.get_datetime(), &mut events);
+            released.push(order.order_id);
+            // The orders that the fill cancels must not fill later during the same event
+            for orders in [pending.make_contiguous(), resting.as_mut_slice()] {
+                for other in orders.iter_mut() {
+                    self.settle(&order, other, &mut events);
+                }
+            }
+            pending.retain(|o| !o.quantity.is_zero());
+            resting.retain(|o| !o.quantity.is_zero());
+        }
+        // The orders attached to a filled parent may only fill from the next event onwards
+        for order in &mut resting {
+            if released.contains(&order.parent_id) {
+                order.held = false;
+                events.push(self.status(order, OrderStatus::Submitted, None));
+            }
+        }
+        self.orders = resting;
+        events
+    }
+
+    /// Apply the effect of a fill to another resting order, which is cancelled if it is attached
+    /// to the same parent or belongs to the same One-Cancels-All group. A cancelled order's
+    /// quantity is set to zero, so that it can be removed.
+    fn settle(
+        &self,
+        filled: &SimulatedOrder,
+        other: &mut SimulatedOrder,
+        events: &mut Vec<SimulatedEvent>,
+    ) {
+        let sibling = filled.parent_id != 0 && other.parent_id == filled.parent_id;
+        let same_group = filled.one_cancels_all_group.is_some()
+            && other.one_cancels_all_group == filled.one_cancels_all_group;
+        if !sibling && !same_group {
+            return;
+        }
+        let reduce = !sibling
+            && matches!(
+                filled.one_cancels_all_type,
+                OneCancelsAllType::ReduceWithBlock | OneCancelsAllType::ReduceNonBlock
+            );
+        if reduce && other.quantity.abs() > filled.quantity.abs() {
+            other.quantity = other.quantity.abs() - filled.quantity.abs();
+            events.push(self.status(other, OrderStatus::Submitted, None));
+        } else {
+            events.push(self.status(other, OrderStatus::Cancelled, None));
+            other.quantity = Quantity::ZERO;
+        }
+    }
+
+    fn fill(
+        &mut self,
+        order: &SimulatedOrder,
+        price: Price,
+        datetime: NaiveDateTime,
+        events: &mut Vec<SimulatedEvent>,
+    ) {
+        self.execution_count += 1;
+        let execution_id = format!("sim.{:08}", self.execution_count);
+        events.push(SimulatedEvent::Execution(Execution {
+            execution_id: execution_id.clone(),
+            order_id: order.order_id,
+            contract_id: order.contract_id,
+            symbol: order.symbol.clone(),
+            datetime,
+            account_number: self.account_number.clone(),
+            exchange: "SIM".to_owned(),
+            side: order.side,
+            quantity: order.quantity,
+            price,
+            perm_id: order.order_id,
+            client_id: 0,
+            liquidation: false,
+            cumulative_quantity: order.quantity,
+            average_price: price,
+            order_reference: order.order_reference.clone(),
+        }));
+        events.push(SimulatedEvent::CommissionReport(CommissionReport {
+            execution_id,
+            commission: self.model.get_commission(order, price.to_f64()),
+            currency: self.currency,
+            realized_pnl: None,
+            yld: None,
+            yield_redemption_date: None,
+        }));
+        events.push(self.status(order, OrderStatus::Filled, Some(price.to_f64())));
+    }
+
+    fn status(
+        &self,
+        order: &SimulatedOrder,
+        status: OrderStatus,
+        fill_price: Option<f64>,
+    ) -> SimulatedEvent {
+        let filled = if fill_price.is_some() {
+            order.quantity
+        } else {
+            Quantity::ZERO
+        };
+        SimulatedEvent::OrderStatus(OrderStatusUpdate {
+            order_id: order.order_id,
+            status,
+            filled,
+            remaining: if status.is_done() {
+                Quantity::ZERO
+            } else {
+                order.quantity - filled
+            },
+            average_fill_price: fill_price.unwrap_or(0.0),
+            perm_id: order.order_id,
+            parent_id: order.parent_id,
+            last_fill_price: fill_price.unwrap_or(0.0),
+            client_id: 0,
+            why_held: String::new(),
+            market_cap_price: None,
+        })
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use crate::account::AccountId;
+use crate::client::{indicators::Active, Client, Environment, Trading};
+use crate::contract::Security;
+use crate::error::Error;
+use crate::execution::OrderSide;
+use crate::order::{Executable, Limit, Market};
+use crate::payload::OrderStatus;
+use crate::quantity::{Price, Quantity};
+use crate::tracker::{OrderEvent, OrderTracker};
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// How a [`Slicer`] splits its parent quantity into child orders.
+pub enum SliceStyle {
+    /// A time-weighted average price schedule, which places one child order per interval, each
+    /// for an equal share of the quantity that remains. A child order that is still working when
+    /// the next one falls due is cancelled, and its unfilled quantity is added to the following
+    /// child orders.
+    Twap {
+        /// The number of child orders.
+        slices: u32,
+        /// The amount of time between child orders.
+        interval: std::time::Duration,
+    },
+    /// An iceberg, which only shows a small part of the quantity at a time by placing the next
+    /// child order once the previous one is filled.
+    Iceberg {
+        /// The quantity of each child order.
+        display: Quantity,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A single event emitted by a [`Slicer`].
+pub enum SliceEvent {
+    /// A child order was placed.
+    Placed {
+        /// The ID of the child order.
+        order_id: i64,
+        /// The number of shares / units in the child order.
+        quantity: Quantity,
+    },
+    /// An event for a child order.
+    Order {
+        /// The ID of the child order.
+        order_id: i64,
+        /// The event.
+        event: Box<OrderEvent>,
+    },
+    /// The slicer was paused, and its working child order (if any) was cancelled.
+    Paused,
+    /// The slicer was resumed, and its schedule was delayed by the time it spent paused.
+    Resumed,
+    /// The parent quantity was filled.
+    Completed {
+        /// The number of shares / units filled.
+        filled: Quantity,
+    },
+    /// A child order was done without being filled (ex: it was rejected, or cancelled outside of
+    /// the slicer), so the slicer stopped before the parent quantity was filled.
+    Stopped {
+        /// The final status of the child order.
+        status: OrderStatus,
+        /// The number of shares / units filled.
+        filled: Quantity,
+    },
+}
+
+#[derive(Debug, Clone)]
+/// A handle that pauses and resumes a [`Slicer`] from another task, while the slicer is being
+/// driven by [`Slicer::next_event`].
+pub struct SliceControl(Arc<watch::Sender<bool>>);
+
+#[derive(Debug)]
+/// Executes a parent quantity as a series of smaller child orders, placed on the client side
+/// according to a [`SliceStyle`]. Unlike IBKR's algorithmic orders, the child orders are plain
+/// market or limit orders, so they can be routed to venues that do not support those algorithms.
+///
+/// The slicer does nothing on its own: it places its child orders and follows their fills while
+/// [`Slicer::next_event`] is awaited. Dropping the slicer stops the schedule, but it does not
+/// cancel a child order that is still working.
+pub struct Slicer<S: Security> {
+    security: S,
+    account_number: AccountId,
+    side: OrderSide,
+    quantity: Quantity,
+    style: SliceStyle,
+    limit_price: Option<Price>,
+    filled: Quantity,
+    slices_placed: u32,
+    start: Instant,
+    child: Option<OrderTracker>,
+    cancelling: bool,
+    paused_at: Option<Instant>,
+    control: SliceControl,
+    paused: watch::Receiver<bool>,
+    done: bool,
+    pending: VecDeque<SliceEvent>,
+}
+
+// === Type implementations ===
+
+impl SliceControl {
+    #[inline]
+    /// Pause the slicer, which cancels its working child order and places no new ones until it
+    /// is resumed.
+    pub fn pause(&self) {
+        self.0.send_replace(true);
+    }
+
+    #[inline]
+    /// Resume the slicer.
+    pub fn resume(&self) {
+        self.0.send_replace(false);
+    }
+
+    #[inline]
+    #[must_use]
+    /// Check whether the slicer is paused.
+    pub fn is_paused(&self) -> bool {
+        *self.0.borrow()
+    }
+}
+
+impl<S: Security + Clone> Slicer<S> {
+    #[must_use]
+    /// Create a new [`Slicer`], whose first child order is placed as soon as
+    /// [`Slicer::next_event`] is first awaited.
+    ///
+    /// # Arguments
+    /// * `security` - The security to trade.
+    /// * `account_number` - The account in which to place the child orders.
+    /// * `side` - Whether to buy or sell the security.
+    /// * `quantity` - The parent quantity.
+    /// * `style` - How to split the parent quantity into child orders.
+    ///
+    /// # Returns
+    /// The slicer, or [`None`] if the quantity is not positive, if a [`SliceStyle::Twap`] has no
+    /// slices, or if a [`SliceStyle::Iceberg`] does not display a positive quantity.
+    pub fn new(
+        security: S,
+        account_number: AccountId,
+        side: OrderSide,
+        quantity: Quantity,
+        style: SliceStyle,
+    ) -> Option<Self> {
+        let is_valid = match style {
+            SliceStyle::Twap { slices, .. } => slices > 0,
+            SliceStyle::Iceberg { display } => display > Quantity::ZERO,
+        };
+        if !is_valid || quantity <= Quantity::ZERO {
+            return None;
+        }
+        let (tx, paused) = watch::channel(false);
+        Some(Self {
+            security,
+            account_number,
+            side,
+            quantity,
+            style,
+            limit_price: None,
+            filled: Quantity::ZERO,
+            slices_placed: 0,
+            start: Instant::now(),
+            child: None,
+            cancelling: false,
+            paused_at: None,
+            control: SliceControl(Arc::new(tx)),
+            paused,
+            done: false,
+            pending: VecDeque::new(),
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    /// Place the child orders as limit orders at a given price, rather than as market orders.
+    pub const fn with_limit_price(mut self, price: Price) -> Self {
+        self.limit_price = Some(price);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get a handle that pauses and resumes the slicer.
+    pub fn get_control(&self) -> SliceControl {
+        self.control.clone()
+    }
+
+    #[inline]
+    /// Pause the slicer. See [`SliceControl::pause`].
+    pub fn pause(&self) {
+        self.control.pause();
+    }
+
+    #[inline]
+    /// Resume the slicer. See [`SliceControl::resume`].
+    pub fn resume(&self) {
+        self.control.resume();
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the number of shares / units filled by the child orders that are done.
+    pub const fn get_filled(&self) -> Quantity {
+        self.filled
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the number of shares / units that remain to be filled, including those of the working
+    /// child order.
+    pub fn get_remaining(&self) -> Quantity {
+        self.quantity - self.filled
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the ID of the working child order, if there is one.
+    pub fn get_working_order_id(&self) -> Option<i64> {
+        self.child.as_ref().map(OrderTracker::get_order_id)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Check whether the slicer has completed or stopped, such that it emits no further events.
+    pub const fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Drive the slicer until its next event, placing and cancelling child orders as they fall
+    /// due.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to place the child orders.
+    ///
+    /// # Errors
+    /// Returns any error encountered while placing or cancelling a child order, or
+    /// [`Error::Disconnected`] if the client loop stopped while a child order was working.
+    ///
+    /// # Returns
+    /// The next event, or [`None`] once the slicer has completed or stopped.
+    pub async fn next_event<N: Environment>(
+        &mut self,
+        client: &mut Client<Active<Trading, N>>,
+    ) -> Result<Option<SliceEvent>, Error>
+    where
+        Market: Executable<S>,
+        Limit: Executable<S>,
+    {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            if self.done {
+                return Ok(None);
+            }
+
+            let now = Instant::now();
+            let paused = *self.paused.borrow_and_update();
+            if paused && self.paused_at.is_none() {
+                self.paused_at = Some(now);
+                self.cancel_child(client).await?;
+                return Ok(Some(SliceEvent::Paused));
+            }
+            if !paused {
+                if let Some(paused_at) = self.paused_at.take() {
+                    self.start += now - paused_at;
+                    return Ok(Some(SliceEvent::Resumed));
+                }
+            }
+
+            let due = self.get_due();
+            if self.child.is_none() {
+                if self.get_remaining() <= Quantity::ZERO {
+                    self.done = true;
+                    return Ok(Some(SliceEvent::Completed {
+                        filled: self.filled,
+                    }));
+                }
+                if !paused && due.is_none_or(|due| due <= now) {
+                    return self.place_child(client).await.map(Some);
+                }
+            } else if !paused && !self.cancelling && due.is_some_and(|due| due <= now) {
+                // The next slice is due, so the rest of this one rolls into the following ones
+                self.cancel_child(client).await?;
+                continue;
+            }
+
+            let wake = due.filter(|_| !paused && !self.cancelling);
+            let child = &mut self.child;
+            let control = &mut self.paused;
+            tokio::select! {
+                event = async {
+                    match child {
+                        Some(tracker) => tracker.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => return self.observe(event.ok_or(Error::Disconnected)?).map(Some),
+                () = async {
+                    match wake {
+                        Some(wake) => tokio::time::sleep_until(wake).await,
+                        None => std::future::pending().await,
+                    }
+                } => (),
+                // The slicer holds its own sender, so the channel is never closed
+                _ = control.changed() => (),
+            }
+        }
+    }
+
+    /// Get the time at which the next child order falls due.
+    ///
+    /// # Returns
+    /// The time, or [`None`] if the next child order should be placed as soon as the previous one
+    /// is done (as in an iceberg, or once a TWAP schedule has run out of slices).
+    fn get_due(&self) -> Option<Instant> {
+        match self.style {
+            SliceStyle::Twap { slices, interval } if self.slices_placed < slices => {
+                Some(self.start + interval * self.slices_placed)
+            }
+            SliceStyle::Twap { .. } | SliceStyle::Iceberg { .. } => None,
+        }
+    }
+
+    /// Place the next child order.
+    async fn place_child<N: Environment>(
+        &mut self,
+        client: &mut Client<Active<Trading, N>>,
+    ) -> Result<SliceEvent, Error>
+    where
+        Market: Executable<S>,
+        Limit: Executable<S>,
+    {
+        let remaining = self.get_remaining();
+        let quantity = match self.style {
+            SliceStyle::Twap { slices, .. } => {
+                split(remaining, slices.saturating_sub(self.slices_placed))
+            }
+            SliceStyle::Iceberg { display } => display.min(remaining),
+        };
+        let tracker = client
+            .req_place_simple_order(
+                self.security.clone(),
+                &self.account_number,
+                self.side,
+                quantity,
+                self.limit_price,
+            )
+            .await?;
+        let order_id = tracker.get_order_id();
+        self.child = Some(tracker);
+        self.slices_placed += 1;
+        Ok(SliceEvent::Placed { order_id, quantity })
+    }
+
+    #[inline]
+    /// Cancel the working child order, if it is not already being cancelled.
+    async fn cancel_child<N: Environment>(
+        &mut self,
+        client: &mut Client<Active<Trading, N>>,
+    ) -> Result<(), Error> {
+        if let Some(tracker) = &self.child {
+            if !self.cancelling {
+                client.cancel_order(tracker.get_order_id()).await?;
+                self.cancelling = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Update the slicer with an event for its working child order.
+    fn observe(&mut self, event: OrderEvent) -> Result<SliceEvent, Error> {
+        let order_id = self.get_working_order_id().ok_or(Error::Disconnected)?;
+        if let OrderEvent::Transition { update, .. } = &event {
+            if update.status.is_done() {
+                self.filled += update.filled;
+                self.child = None;
+                let cancelled = std::mem::take(&mut self.cancelling);
+                if update.status != OrderStatus::Filled && !cancelled {
+                    self.done = true;
+                    self.pending.push_back(SliceEvent::Stopped {
+                        status: update.status,
+                        filled: self.filled,
+                    });
+                }
+            }
+        }
+        Ok(SliceEvent::Order {
+            order_id,
+            event: Box::new(event),
+        })
+    }
+}
+
+// === Helper functions ===
+
+#[inline]
+/// Split a quantity into a number of equal parts, rounded up to whole shares / units.
+///
+/// # Returns
+/// The size of the first part, which is never more than the quantity itself.
+fn split(quantity: Quantity, parts: u32) -> Quantity {
+    if parts <= 1 {
+        return quantity;
+    }
+    Quantity::try_from((quantity.to_f64() / f64::from(parts)).ceil())
+        .map_or(quantity, |part| part.min(quantity))
+}
@@ -0,0 +1,462 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::Poll;
+
+use futures_core::Stream;
+
+use serde::{Deserialize, Serialize};
+
+use crate::account::AccountId;
+use crate::contract::ContractId;
+use crate::error::Error;
+use crate::execution::OrderSide;
+use crate::flatten::FlattenStyle;
+use crate::quantity::Quantity;
+use crate::stream::{EventStream, TypedEvent};
+use crate::tick::{Class, Price};
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The ID of a [`SoftStop`] in a [`StopBook`], which remains the same when the book is reloaded
+/// from its file.
+pub struct StopId(pub u64);
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A stop loss that is kept on the client side rather than resting at IBKR, so that it is not
+/// visible in the market. Once the price of its contract breaches the stop price,
+/// [`crate::client::Client::trigger_soft_stops`] submits an order that closes the position.
+pub struct SoftStop {
+    account_number: AccountId,
+    contract_id: ContractId,
+    side: OrderSide,
+    stop_price: f64,
+    quantity: Option<Quantity>,
+    style: FlattenStyle,
+}
+
+#[derive(Debug, Default)]
+/// A set of [`SoftStop`]s, which is optionally persisted to a TOML file so that the stops survive
+/// a restart of the application.
+///
+/// [`crate::client::Client::watch_soft_stops`] subscribes to the live prices of the book's
+/// contracts and triggers the stops as they are breached. Alternatively, prices from another
+/// source can be supplied to [`crate::client::Client::trigger_soft_stops`].
+pub struct StopBook {
+    path: Option<PathBuf>,
+    next_id: u64,
+    stops: BTreeMap<StopId, SoftStop>,
+    watched: HashMap<ContractId, EventStream>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct File {
+    next_id: u64,
+    #[serde(default)]
+    stops: Vec<Record>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Record {
+    id: u64,
+    account_number: String,
+    contract_id: i64,
+    side: String,
+    stop_price: f64,
+    quantity: Option<String>,
+    slippage: Option<f64>,
+}
+
+// === Type implementations ===
+
+impl std::fmt::Display for StopId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl SoftStop {
+    #[inline]
+    #[must_use]
+    /// Create a stop that protects a long position by selling it once the price falls to or below
+    /// the stop price. By default, the stop closes the whole position with a market order.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account that holds the position.
+    /// * `contract_id` - The ID of the position's contract.
+    /// * `stop_price` - The price at which the stop is triggered.
+    pub const fn long(account_number: AccountId, contract_id: ContractId, stop_price: f64) -> Self {
+        Self::new(account_number, contract_id, OrderSide::Sell, stop_price)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Create a stop that protects a short position by buying it back once the price rises to or
+    /// above the stop price. By default, the stop closes the whole position with a market order.
+    ///
+    /// # Arguments
+    /// * `account_number` - The account that holds the position.
+    /// * `contract_id` - The ID of the position's contract.
+    /// * `stop_price` - The price at which the stop is triggered.
+    pub const fn short(
+        account_number: AccountId,
+        contract_id: ContractId,
+        stop_price: f64,
+    ) -> Self {
+        Self::new(account_number, contract_id, OrderSide::Buy, stop_price)
+    }
+
+    #[inline]
+    const fn new(
+        account_number: AccountId,
+        contract_id: ContractId,
+        side: OrderSide,
+        stop_price: f64,
+    ) -> Self {
+        Self {
+            account_number,
+            contract_id,
+            side,
+            stop_price,
+            quantity: None,
+            style: FlattenStyle::Market,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Close at most a given number of shares / units of the position, rather than all of it.
+    pub const fn with_quantity(mut self, quantity: Quantity) -> Self {
+        self.quantity = Some(quantity);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set how the closing order is priced. An [`FlattenStyle::AggressiveLimit`] is priced through
+    /// the price that triggered the stop.
+    pub const fn with_style(mut self, style: FlattenStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the account that holds the position.
+    pub const fn get_account_number(&self) -> &AccountId {
+        &self.account_number
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the ID of the position's contract.
+    pub const fn get_contract_id(&self) -> ContractId {
+        self.contract_id
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the side of the closing order, which is [`OrderSide::Sell`] for a long position.
+    pub const fn get_side(&self) -> OrderSide {
+        self.side
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the price at which the stop is triggered.
+    pub const fn get_stop_price(&self) -> f64 {
+        self.stop_price
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the maximum quantity to close, or [`None`] to close the whole position.
+    pub const fn get_quantity(&self) -> Option<Quantity> {
+        self.quantity
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get how the closing order is priced.
+    pub const fn get_style(&self) -> FlattenStyle {
+        self.style
+    }
+
+    #[inline]
+    #[must_use]
+    /// Check whether a price breaches the stop.
+    pub fn is_breached(&self, price: f64) -> bool {
+        match self.side {
+            OrderSide::Sell => price <= self.stop_price,
+            OrderSide::Buy => price >= self.stop_price,
+        }
+    }
+
+    #[inline]
+    fn to_record(&self, stop_id: StopId) -> Record {
+        Record {
+            id: stop_id.0,
+            account_number: self.account_number.0.clone(),
+            contract_id: self.contract_id.0,
+            side: self.side.to_string(),
+            stop_price: self.stop_price,
+            quantity: self.quantity.map(|quantity| quantity.to_string()),
+            slippage: match self.style {
+                FlattenStyle::Market => None,
+                FlattenStyle::AggressiveLimit { slippage } => Some(slippage),
+            },
+        }
+    }
+
+    fn from_record(record: Record) -> Result<(StopId, Self), String> {
+        Ok((
+            StopId(record.id),
+            Self {
+                account_number: AccountId(record.account_number),
+                contract_id: ContractId(record.contract_id),
+                side: record.side.parse().map_err(|_| record.side.clone())?,
+                stop_price: record.stop_price,
+                quantity: record
+                    .quantity
+                    .map(|quantity| quantity.parse().map_err(|_| quantity))
+                    .transpose()?,
+                style: record.slippage.map_or(FlattenStyle::Market, |slippage| {
+                    FlattenStyle::AggressiveLimit { slippage }
+                }),
+            },
+        ))
+    }
+}
+
+impl StopBook {
+    #[inline]
+    #[must_use]
+    /// Create an empty book that is only kept in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a book that is persisted to a TOML file, loading the stops that the file already
+    /// holds. Every subsequent change to the book is written back to the file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file, which is created upon the first change if it is missing.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if the file exists but could not be read or parsed.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let file = match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str::<File>(&contents).map_err(invalid_data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => File::default(),
+            Err(e) => return Err(Error::Io(e)),
+        };
+        let stops = file
+            .stops
+            .into_iter()
+            .map(SoftStop::from_record)
+            .collect::<Result<BTreeMap<_, _>, _>>()
+            .map_err(|value| invalid_data(format!("Invalid soft stop value {value}")))?;
+        Ok(Self {
+            path: Some(path),
+            next_id: file.next_id,
+            stops,
+            watched: HashMap::new(),
+        })
+    }
+
+    /// Add a stop to the book.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if the book could not be written to its file, in which case the stop
+    /// is not added.
+    ///
+    /// # Returns
+    /// The stop's ID.
+    pub fn add(&mut self, stop: SoftStop) -> Result<StopId, Error> {
+        let stop_id = StopId(self.next_id);
+        self.stops.insert(stop_id, stop);
+        self.next_id += 1;
+        if let Err(e) = self.save() {
+            self.stops.remove(&stop_id);
+            self.next_id -= 1;
+            return Err(e);
+        }
+        Ok(stop_id)
+    }
+
+    /// Remove a stop from the book.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if the book could not be written to its file, in which case the stop
+    /// is not removed.
+    ///
+    /// # Returns
+    /// The stop, or [`None`] if the book does not hold it.
+    pub fn remove(&mut self, stop_id: StopId) -> Result<Option<SoftStop>, Error> {
+        let Some(stop) = self.stops.remove(&stop_id) else {
+            return Ok(None);
+        };
+        if let Err(e) = self.save() {
+            self.stops.insert(stop_id, stop);
+            return Err(e);
+        }
+        Ok(Some(stop))
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get a stop.
+    pub fn get(&self, stop_id: StopId) -> Option<&SoftStop> {
+        self.stops.get(&stop_id)
+    }
+
+    #[inline]
+    /// Iterate over the stops, in the order in which they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (StopId, &SoftStop)> {
+        self.stops.iter().map(|(stop_id, stop)| (*stop_id, stop))
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the number of stops in the book.
+    pub fn len(&self) -> usize {
+        self.stops.len()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Check whether the book holds no stops.
+    pub fn is_empty(&self) -> bool {
+        self.stops.is_empty()
+    }
+
+    #[must_use]
+    /// Get the IDs of the contracts whose prices must be monitored, without duplicates.
+    pub fn get_contract_ids(&self) -> Vec<ContractId> {
+        let mut contract_ids = self
+            .stops
+            .values()
+            .map(SoftStop::get_contract_id)
+            .collect::<Vec<_>>();
+        contract_ids.sort_unstable();
+        contract_ids.dedup();
+        contract_ids
+    }
+
+    #[inline]
+    /// Get the stops on a contract that are breached by a price.
+    pub(crate) fn get_breached(
+        &self,
+        contract_id: ContractId,
+        price: f64,
+    ) -> Vec<(StopId, SoftStop)> {
+        self.stops
+            .iter()
+            .filter(|(_, stop)| stop.contract_id == contract_id && stop.is_breached(price))
+            .map(|(stop_id, stop)| (*stop_id, stop.clone()))
+            .collect()
+    }
+
+    #[inline]
+    /// Get the IDs of the contracts that have a stop but whose prices are not yet watched.
+    pub(crate) fn get_unwatched(&self) -> Vec<ContractId> {
+        self.get_contract_ids()
+            .into_iter()
+            .filter(|contract_id| !self.watched.contains_key(contract_id))
+            .collect()
+    }
+
+    #[inline]
+    /// Start watching the prices of a contract.
+    pub(crate) fn watch(&mut self, contract_id: ContractId, prices: EventStream) {
+        self.watched.insert(contract_id, prices);
+    }
+
+    #[inline]
+    /// Stop watching the prices of the contracts that no longer have a stop.
+    ///
+    /// # Returns
+    /// The price streams of those contracts, whose subscriptions must be cancelled.
+    pub(crate) fn take_unneeded(&mut self) -> Vec<EventStream> {
+        let stops = &self.stops;
+        let unneeded = self
+            .watched
+            .keys()
+            .filter(|contract_id| !stops.values().any(|stop| stop.contract_id == **contract_id))
+            .copied()
+            .collect::<Vec<_>>();
+        unneeded
+            .into_iter()
+            .filter_map(|contract_id| self.watched.remove(&contract_id))
+            .collect()
+    }
+
+    /// Wait for the next last traded price of any watched contract.
+    ///
+    /// # Returns
+    /// The contract and its price, or [`None`] if no contract is watched or the client loop
+    /// stopped.
+    pub(crate) async fn next_price(&mut self) -> Option<(ContractId, f64)> {
+        std::future::poll_fn(|cx| {
+            let mut open = false;
+            for (contract_id, prices) in &mut self.watched {
+                loop {
+                    match Pin::new(&mut *prices).poll_next(cx) {
+                        Poll::Ready(Some(TypedEvent::Price { price, .. })) => {
+                            if let Class::Live(Price::Last(last))
+                            | Class::Delayed(Price::Last(last)) = price
+                            {
+                                return Poll::Ready(Some((*contract_id, last)));
+                            }
+                        }
+                        Poll::Ready(Some(_)) => (),
+                        Poll::Ready(None) => break,
+                        Poll::Pending => {
+                            open = true;
+                            break;
+                        }
+                    }
+                }
+            }
+            if open {
+                Poll::Pending
+            } else {
+                Poll::Ready(None)
+            }
+        })
+        .await
+    }
+
+    /// Write the book to its file, if it has one. The book is written to a temporary file that
+    /// then replaces the original, so that a crash never leaves a partially written file.
+    fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = File {
+            next_id: self.next_id,
+            stops: self
+                .stops
+                .iter()
+                .map(|(stop_id, stop)| stop.to_record(*stop_id))
+                .collect(),
+        };
+        let contents = toml::to_string(&file).map_err(invalid_data)?;
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        std::fs::write(&temp, contents).map_err(Error::Io)?;
+        std::fs::rename(&temp, path).map_err(Error::Io)
+    }
+}
+
+// === Helper functions ===
+
+#[inline]
+fn invalid_data(e: impl std::fmt::Display) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        e.to_string(),
+    ))
+}
@@ -0,0 +1,985 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::calendar::Session;
+use crate::client::{indicators::Active, Client, Environment, Profile};
+use crate::contract::{ContractId, Security};
+use crate::corporate::{self, CachePolicy, CorporateActionDetected};
+use crate::error::Error;
+use crate::integrity::{self, IntegrityReport};
+use crate::market_data::historical_bar;
+use crate::payload::{Bar, BarCore};
+use crate::timezone::{Timestamp, Tz};
+
+// === Constants ===
+
+/// The format of each bar's timestamp in a [`FileStore`].
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// The header line of each file in a [`FileStore`].
+const HEADER: &str = "datetime,open,high,low,close,volume,wap,trade_count";
+
+/// The number of bytes at the end of a file in a [`FileStore`] that are read to find its last bar,
+/// which is more than any line takes.
+const TAIL_LENGTH: u64 = 512;
+
+/// The format of each date in the file of empty dates of a [`FileStore`].
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The code of the error with which IBKR answers a historical data request that has no data. IBKR
+/// also uses it for other failures, such as pacing violations, so [`NO_DATA_MESSAGE`] must match
+/// too.
+const NO_DATA_CODE: i64 = 162;
+
+/// The text of the error with which IBKR answers a historical data request that has no data.
+const NO_DATA_MESSAGE: &str = "HMDS query returned no data";
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Identifies a single series of bars in a [`BarStore`].
+pub struct SeriesKey {
+    /// The ID of the contract.
+    pub contract_id: ContractId,
+    /// The size of each bar.
+    pub bar_size: historical_bar::Size,
+    /// The type of data (ex: "TRADES" or "`ADJUSTED_LAST`").
+    pub data_type: String,
+}
+
+/// A place where series of bars are kept between sessions, such as a directory of files or a
+/// database. The bars of each series are kept in chronological order, with one bar per timestamp.
+pub trait BarStore {
+    /// Load every bar of a series.
+    ///
+    /// # Errors
+    /// Returns any error encountered while reading the series.
+    ///
+    /// # Returns
+    /// The bars in chronological order, which are empty if the series has never been saved.
+    fn load(&self, key: &SeriesKey) -> std::io::Result<Vec<Bar>>;
+
+    /// Replace every bar of a series.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the series.
+    fn save(&mut self, key: &SeriesKey, bars: &[Bar]) -> std::io::Result<()>;
+
+    /// Add bars to a series, replacing any existing bars with the same timestamps.
+    ///
+    /// The default implementation loads the whole series and saves it again, which stores that
+    /// support appending in place should override.
+    ///
+    /// # Errors
+    /// Returns any error encountered while reading or writing the series.
+    fn append(&mut self, key: &SeriesKey, bars: &[Bar]) -> std::io::Result<()> {
+        let mut series = self.load(key)?;
+        series.extend_from_slice(bars);
+        self.save(key, &merge(series))
+    }
+
+    /// Load the dates on which a series is known to have no bars, such as holidays, so that they
+    /// are not downloaded again.
+    ///
+    /// The default implementation records no such dates.
+    ///
+    /// # Errors
+    /// Returns any error encountered while reading the dates.
+    ///
+    /// # Returns
+    /// The dates, in no particular order.
+    fn load_empty_dates(&self, _key: &SeriesKey) -> std::io::Result<Vec<NaiveDate>> {
+        Ok(Vec::new())
+    }
+
+    /// Record dates on which a series has no bars, in addition to those already recorded.
+    ///
+    /// The default implementation discards the dates, so they are downloaded again by every query.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the dates.
+    fn add_empty_dates(&mut self, _key: &SeriesKey, _dates: &[NaiveDate]) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A [`BarStore`] that keeps each series in its own CSV file within a directory, named after the
+/// contract ID, data type and bar size (ex: "265598_TRADES_5m.csv"). The dates on which a series
+/// has no bars are kept alongside it, one per line (ex: "265598_TRADES_5m.empty").
+pub struct FileStore {
+    root: PathBuf,
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+/// A [`BarStore`] that keeps every series in a single SQLite database, with one row per bar.
+pub struct SqliteStore {
+    connection: rusqlite::Connection,
+}
+
+#[derive(Debug)]
+/// Downloads series of bars into a [`BarStore`] and keeps them up to date.
+///
+/// A download fetches one day of bars per trading day with
+/// [`Client::req_historical_bar_sessions_paced`] and merges them into the stored series. The bars
+/// of an [`historical_bar::data_types::AdjustedLast`] series are merged with
+/// [`corporate::reconcile`], so that a re-download of adjusted data also brings the older bars in
+/// line with any split or dividend.
+///
+/// To keep a series updated, request it with [`Client::req_updating_historical_bar`], register the
+/// request with [`HistoricalDownloader::keep_updated`] and pass each bar from
+/// [`crate::wrapper::Local::updating_historical_bar`] (or
+/// [`crate::wrapper::Remote::updating_historical_bar`]) to [`HistoricalDownloader::update`]. Since
+/// IBKR repeatedly updates the bar that is in progress, a bar is only appended once the next one
+/// begins.
+pub struct HistoricalDownloader<B: BarStore> {
+    store: B,
+    policy: CachePolicy,
+    updating: HashMap<i64, (SeriesKey, Option<Bar>)>,
+    actions: Option<tokio::sync::mpsc::UnboundedSender<(SeriesKey, CorporateActionDetected)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// The outcome of [`HistoricalDownloader::download`].
+pub struct DownloadReport {
+    /// The corporate action detected while merging the bars, if any.
+    pub corporate_action: Option<CorporateActionDetected>,
+    /// The problems found in the downloaded bars by [`integrity::check_bars`], with the session
+    /// coverage checked against the security's historical schedule.
+    pub integrity: IntegrityReport,
+}
+
+#[derive(Debug, Default)]
+/// The bars downloaded by [`fetch`].
+struct Fetched {
+    bars: Vec<Bar>,
+    empty: Vec<NaiveDate>,
+}
+
+// === Type implementations ===
+
+impl SeriesKey {
+    #[must_use]
+    /// Create a new series key.
+    ///
+    /// # Arguments
+    /// * `contract_id` - The ID of the contract.
+    /// * `bar_size` - The size of each bar.
+    /// * `data_type` - The type of data (ex: [`historical_bar::data_types::Trades`]).
+    pub fn new(
+        contract_id: ContractId,
+        bar_size: historical_bar::Size,
+        data_type: &impl ToString,
+    ) -> Self {
+        Self {
+            contract_id,
+            bar_size,
+            data_type: data_type.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for SeriesKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let size = match self.bar_size {
+            historical_bar::Size::Seconds(s) => format!("{}s", s as u8),
+            historical_bar::Size::Minutes(m) => format!("{}m", m as u8),
+            historical_bar::Size::Hours(h) => format!("{}h", h as u8),
+            historical_bar::Size::Day => "1d".to_owned(),
+            historical_bar::Size::Week => "1w".to_owned(),
+            historical_bar::Size::Month => "1mo".to_owned(),
+        };
+        write!(f, "{}_{}_{size}", self.contract_id.0, self.data_type)
+    }
+}
+
+impl FileStore {
+    /// Open a store in a directory, which is created if it does not exist.
+    ///
+    /// # Errors
+    /// Returns any error encountered while creating the directory.
+    pub fn open(root: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    #[must_use]
+    /// Get the path of the file in which a series is kept.
+    pub fn get_path(&self, key: &SeriesKey) -> PathBuf {
+        self.root.join(format!("{key}.csv"))
+    }
+
+    #[must_use]
+    /// Get the path of the file in which the dates on which a series has no bars are kept.
+    pub fn get_empty_dates_path(&self, key: &SeriesKey) -> PathBuf {
+        self.root.join(format!("{key}.empty"))
+    }
+
+    /// Get the timestamp of the last bar of a series without reading the whole file.
+    fn get_last_datetime(&self, key: &SeriesKey) -> std::io::Result<Option<NaiveDateTime>> {
+        let mut file = match std::fs::File::open(self.get_path(key)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let length = file.metadata()?.len();
+        file.seek(SeekFrom::Start(length.saturating_sub(TAIL_LENGTH)))?;
+        let mut tail = String::new();
+        file.read_to_string(&mut tail)?;
+        match tail.lines().rev().find(|line| !line.is_empty()) {
+            Some(line) if line != HEADER => Ok(Some(parse_line(line)?.core().datetime)),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl BarStore for FileStore {
+    fn load(&self, key: &SeriesKey) -> std::io::Result<Vec<Bar>> {
+        let file = match std::fs::File::open(self.get_path(key)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        std::io::BufReader::new(file)
+            .lines()
+            .skip(1)
+            .filter(|line| line.as_ref().map_or(true, |line| !line.is_empty()))
+            .map(|line| parse_line(&line?))
+            .collect()
+    }
+
+    fn save(&mut self, key: &SeriesKey, bars: &[Bar]) -> std::io::Result<()> {
+        // The series is written to a temporary file first, so that a failed write never leaves a
+        // truncated series behind
+        let path = self.get_path(key);
+        let temporary = path.with_extension("csv.tmp");
+        let mut writer = BufWriter::new(std::fs::File::create(&temporary)?);
+        writeln!(writer, "{HEADER}")?;
+        for bar in bars {
+            write_line(&mut writer, bar)?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        std::fs::rename(temporary, path)
+    }
+
+    fn append(&mut self, key: &SeriesKey, bars: &[Bar]) -> std::io::Result<()> {
+        let path = self.get_path(key);
+        match self.get_last_datetime(key)? {
+            // Bars that only extend the series are appended to its file in place
+            Some(last) if bars.iter().all(|bar| bar.core().datetime > last) => {
+                let mut writer =
+                    BufWriter::new(std::fs::OpenOptions::new().append(true).open(path)?);
+                for bar in merge(bars.to_vec()) {
+                    write_line(&mut writer, &bar)?;
+                }
+                writer.flush()
+            }
+            _ => {
+                let mut series = self.load(key)?;
+                series.extend_from_slice(bars);
+                self.save(key, &merge(series))
+            }
+        }
+    }
+
+    fn load_empty_dates(&self, key: &SeriesKey) -> std::io::Result<Vec<NaiveDate>> {
+        let file = match std::fs::File::open(self.get_empty_dates_path(key)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        std::io::BufReader::new(file)
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |line| !line.is_empty()))
+            .map(|line| {
+                let line = line?;
+                NaiveDate::parse_from_str(&line, DATE_FORMAT).map_err(|_| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Invalid date in store: {line}"),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn add_empty_dates(&mut self, key: &SeriesKey, dates: &[NaiveDate]) -> std::io::Result<()> {
+        if dates.is_empty() {
+            return Ok(());
+        }
+        let mut writer = BufWriter::new(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.get_empty_dates_path(key))?,
+        );
+        for date in dates {
+            writeln!(writer, "{}", date.format(DATE_FORMAT))?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    /// Open a store in a database file, which is created if it does not exist.
+    ///
+    /// # Errors
+    /// Returns any error encountered while opening the database or creating its tables.
+    pub fn open(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let connection = rusqlite::Connection::open(path).map_err(sqlite_error)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS bars (
+                    series TEXT NOT NULL,
+                    datetime TEXT NOT NULL,
+                    open REAL NOT NULL,
+                    high REAL NOT NULL,
+                    low REAL NOT NULL,
+                    close REAL NOT NULL,
+                    volume REAL,
+                    wap REAL,
+                    trade_count INTEGER,
+                    PRIMARY KEY (series, datetime)
+                );
+                CREATE TABLE IF NOT EXISTS empty_dates (
+                    series TEXT NOT NULL,
+                    date TEXT NOT NULL,
+                    PRIMARY KEY (series, date)
+                );",
+            )
+            .map_err(sqlite_error)?;
+        Ok(Self { connection })
+    }
+
+    /// Insert bars into a series within a transaction, replacing any with the same timestamps.
+    fn insert(
+        transaction: &rusqlite::Transaction<'_>,
+        key: &SeriesKey,
+        bars: &[Bar],
+    ) -> rusqlite::Result<()> {
+        let mut statement = transaction.prepare_cached(
+            "INSERT OR REPLACE INTO bars
+                (series, datetime, open, high, low, close, volume, wap, trade_count)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        let series = key.to_string();
+        for bar in bars {
+            let BarCore {
+                datetime,
+                open,
+                high,
+                low,
+                close,
+            } = *bar.core();
+            let (volume, wap, trade_count) = match *bar {
+                Bar::Ordinary(_) => (None, None, None),
+                Bar::Trades {
+                    volume,
+                    wap,
+                    trade_count,
+                    ..
+                } => (
+                    Some(volume),
+                    Some(wap),
+                    Some(i64::try_from(trade_count).unwrap_or(i64::MAX)),
+                ),
+            };
+            statement.execute(rusqlite::params![
+                series,
+                datetime.format(DATETIME_FORMAT).to_string(),
+                open,
+                high,
+                low,
+                close,
+                volume,
+                wap,
+                trade_count,
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl BarStore for SqliteStore {
+    fn load(&self, key: &SeriesKey) -> std::io::Result<Vec<Bar>> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT datetime, open, high, low, close, volume, wap, trade_count
+                    FROM bars WHERE series = ?1 ORDER BY datetime",
+            )
+            .map_err(sqlite_error)?;
+        let rows = statement
+            .query_map([key.to_string()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    BarCore {
+                        datetime: NaiveDateTime::MIN,
+                        open: row.get(1)?,
+                        high: row.get(2)?,
+                        low: row.get(3)?,
+                        close: row.get(4)?,
+                    },
+                    row.get::<_, Option<f64>>(5)?,
+                    row.get::<_, Option<f64>>(6)?,
+                    row.get::<_, Option<i64>>(7)?,
+                ))
+            })
+            .map_err(sqlite_error)?;
+        let bars = rows
+            .map(|row| {
+                let (datetime, mut core, volume, wap, trade_count) = row.map_err(sqlite_error)?;
+                core.datetime =
+                    NaiveDateTime::parse_from_str(&datetime, DATETIME_FORMAT).map_err(|_| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("Invalid datetime in store: {datetime}"),
+                        )
+                    })?;
+                Ok(match (volume, wap, trade_count) {
+                    (Some(volume), Some(wap), Some(trade_count)) => Bar::Trades {
+                        bar: core,
+                        volume,
+                        wap,
+                        trade_count: u64::try_from(trade_count).unwrap_or_default(),
+                    },
+                    _ => Bar::Ordinary(core),
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        Ok(bars)
+    }
+
+    fn save(&mut self, key: &SeriesKey, bars: &[Bar]) -> std::io::Result<()> {
+        let transaction = self.connection.transaction().map_err(sqlite_error)?;
+        transaction
+            .execute("DELETE FROM bars WHERE series = ?1", [key.to_string()])
+            .map_err(sqlite_error)?;
+        Self::insert(&transaction, key, bars).map_err(sqlite_error)?;
+        transaction.commit().map_err(sqlite_error)
+    }
+
+    fn append(&mut self, key: &SeriesKey, bars: &[Bar]) -> std::io::Result<()> {
+        let transaction = self.connection.transaction().map_err(sqlite_error)?;
+        Self::insert(&transaction, key, bars).map_err(sqlite_error)?;
+        transaction.commit().map_err(sqlite_error)
+    }
+
+    fn load_empty_dates(&self, key: &SeriesKey) -> std::io::Result<Vec<NaiveDate>> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT date FROM empty_dates WHERE series = ?1")
+            .map_err(sqlite_error)?;
+        let rows = statement
+            .query_map([key.to_string()], |row| row.get::<_, String>(0))
+            .map_err(sqlite_error)?;
+        let dates = rows
+            .map(|row| {
+                let date = row.map_err(sqlite_error)?;
+                NaiveDate::parse_from_str(&date, DATE_FORMAT).map_err(|_| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Invalid date in store: {date}"),
+                    )
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        Ok(dates)
+    }
+
+    fn add_empty_dates(&mut self, key: &SeriesKey, dates: &[NaiveDate]) -> std::io::Result<()> {
+        let transaction = self.connection.transaction().map_err(sqlite_error)?;
+        {
+            let mut statement = transaction
+                .prepare_cached("INSERT OR IGNORE INTO empty_dates (series, date) VALUES (?1, ?2)")
+                .map_err(sqlite_error)?;
+            let series = key.to_string();
+            for date in dates {
+                statement
+                    .execute(rusqlite::params![
+                        series,
+                        date.format(DATE_FORMAT).to_string()
+                    ])
+                    .map_err(sqlite_error)?;
+            }
+        }
+        transaction.commit().map_err(sqlite_error)
+    }
+}
+
+impl<B: BarStore> HistoricalDownloader<B> {
+    #[inline]
+    #[must_use]
+    /// Create a new downloader.
+    ///
+    /// # Arguments
+    /// * `store` - The store in which the series are kept.
+    /// * `policy` - How a stored series is treated when a download detects a corporate action.
+    pub fn new(store: B, policy: CachePolicy) -> Self {
+        Self {
+            store,
+            policy,
+            updating: HashMap::new(),
+            actions: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the store in which the series are kept.
+    pub const fn get_store(&self) -> &B {
+        &self.store
+    }
+
+    /// Receive a [`CorporateActionDetected`] event, along with the series in which it was
+    /// detected, whenever a download or a load detects a corporate action. Any receiver returned
+    /// by a previous call stops receiving events.
+    ///
+    /// # Returns
+    /// The receiving end of the events.
+    pub fn subscribe_corporate_actions(
+        &mut self,
+    ) -> tokio::sync::mpsc::UnboundedReceiver<(SeriesKey, CorporateActionDetected)> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        self.actions = Some(tx);
+        rx
+    }
+
+    /// Download the bars of every trading day in a range of dates, validate them, and merge them
+    /// into the stored series.
+    ///
+    /// The downloaded bars are checked with [`integrity::check_bars`]. For bars shorter than a
+    /// day, the sessions of the dates are also requested with
+    /// [`Client::req_historical_schedule`], so that the check reports the sessions that are not
+    /// fully covered. The bars are stored whatever the outcome of the check.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to make the requests.
+    /// * `security` - The security for which to download bars.
+    /// * `from` - The first date for which to download bars.
+    /// * `to` - The last date for which to download bars.
+    /// * `bar_size` - The size of each individual bar.
+    /// * `data` - The type of data to download.
+    /// * `regular_trading_hours_only` - When [`true`], only download bars from regular trading
+    /// hours.
+    /// * `timeout` - The maximum amount of time to wait for the bars of each day.
+    ///
+    /// # Errors
+    /// Returns any error encountered while making the requests, [`Error::Api`] (or
+    /// [`Error::Pacing`]) if IBKR rejects one of them, [`Error::Timeout`] if the bars of a day are not received in time, and
+    /// [`Error::Io`] if the series cannot be read or written. The stored series is only updated
+    /// once every day has been received, and the bars of a day that has not yet ended (in the time
+    /// zone of the security's exchange) are not stored, since the day would never be refilled.
+    ///
+    /// # Returns
+    /// The corporate action detected while merging the bars, if any, and the outcome of the
+    /// integrity check.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn download<P, E, S, D>(
+        &mut self,
+        client: &mut Client<Active<P, E>>,
+        security: &S,
+        from: NaiveDate,
+        to: NaiveDate,
+        bar_size: historical_bar::Size,
+        data: D,
+        regular_trading_hours_only: bool,
+        timeout: std::time::Duration,
+    ) -> Result<DownloadReport, Error>
+    where
+        P: Profile,
+        E: Environment,
+        S: Security,
+        D: historical_bar::data_types::DataType<S>,
+    {
+        let key = SeriesKey::new(security.get_contract_id(), bar_size, &data);
+        let time_zone = client
+            .get_time_zone(security.get_contract_id())
+            .unwrap_or(Tz::UTC);
+        let today = get_today(client, time_zone);
+        let mut fresh = fetch(
+            client,
+            security,
+            from,
+            to,
+            bar_size,
+            data,
+            regular_trading_hours_only,
+            timeout,
+            false,
+        )
+        .await?
+        .bars;
+        fresh.retain(|bar| bar.get_exchange_date(time_zone) < today);
+        let integrity = match get_bar_length(bar_size) {
+            Some(resolution) => {
+                let sessions = get_sessions(client, security, from, to).await?;
+                integrity::check_bars(&fresh, resolution, &sessions)
+            }
+            None => integrity::check_bars(&fresh, std::time::Duration::from_secs(86_400), &[]),
+        };
+        let mut series = self.store.load(&key)?;
+        let corporate_action = self.reconcile(&key, &mut series, &fresh);
+        self.store.save(&key, &series)?;
+        Ok(DownloadReport {
+            corporate_action,
+            integrity,
+        })
+    }
+
+    /// Load the bars of a range of dates, downloading the dates that are missing from the stored
+    /// series first, so that stored and remote data are accessed alike.
+    ///
+    /// A date is missing when the series holds no bars on it, in the time zone of the security's
+    /// exchange (see [`Client::get_time_zone`]) or in UTC if it is unknown, and the store has not
+    /// recorded it as empty (see [`BarStore::load_empty_dates`]). Consecutive missing dates are
+    /// downloaded together as in [`HistoricalDownloader::download`]. The dates that are not trading
+    /// days, and those for which IBKR returns no data, are recorded as empty so that they are not
+    /// requested again.
+    ///
+    /// The current date is always downloaded, since it may not have ended. Its bars are returned
+    /// but neither stored nor, if there are none, recorded as empty.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to make any requests.
+    /// * `security` - The security for which to load bars.
+    /// * `dates` - The range of dates for which to load bars.
+    /// * `bar_size` - The size of each individual bar.
+    /// * `data` - The type of data to load.
+    /// * `regular_trading_hours_only` - When [`true`], only download bars from regular trading
+    /// hours.
+    /// * `timeout` - The maximum amount of time to wait for the bars of each downloaded day.
+    ///
+    /// # Errors
+    /// Returns any error encountered while making the requests, [`Error::Api`] (or
+    /// [`Error::Pacing`]) if IBKR rejects one of them, [`Error::Timeout`] if the bars of a day are not received in time, and
+    /// [`Error::Io`] if the series cannot be read or written.
+    ///
+    /// # Returns
+    /// The bars of the dates, in chronological order.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn load_bars<P, E, S, D>(
+        &mut self,
+        client: &mut Client<Active<P, E>>,
+        security: &S,
+        dates: std::ops::RangeInclusive<NaiveDate>,
+        bar_size: historical_bar::Size,
+        data: D,
+        regular_trading_hours_only: bool,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<Bar>, Error>
+    where
+        P: Profile,
+        E: Environment,
+        S: Security,
+        D: historical_bar::data_types::DataType<S>,
+    {
+        let key = SeriesKey::new(security.get_contract_id(), bar_size, &data);
+        let time_zone = client
+            .get_time_zone(security.get_contract_id())
+            .unwrap_or(Tz::UTC);
+        let date_of = |bar: &Bar| bar.get_exchange_date(time_zone);
+        let today = get_today(client, time_zone);
+
+        let mut series = self.store.load(&key)?;
+        let mut known = series.iter().map(date_of).collect::<HashSet<_>>();
+        known.extend(self.store.load_empty_dates(&key)?);
+        known.retain(|date| *date < today);
+        let mut fresh = Fetched::default();
+        let mut missing = dates
+            .start()
+            .iter_days()
+            .take_while(|date| dates.contains(date))
+            .filter(|date| !known.contains(date))
+            .peekable();
+        while let Some(from) = missing.next() {
+            let mut to = from;
+            while let Some(next) = missing.next_if(|&next| to.succ_opt() == Some(next)) {
+                to = next;
+            }
+            let fetched = fetch(
+                client,
+                security,
+                from,
+                to,
+                bar_size,
+                data,
+                regular_trading_hours_only,
+                timeout,
+                true,
+            )
+            .await?;
+            fresh.bars.extend(fetched.bars);
+            fresh.empty.extend(fetched.empty);
+        }
+        fresh.empty.retain(|date| *date < today);
+        self.store.add_empty_dates(&key, &fresh.empty)?;
+        let (complete, unfinished): (Vec<_>, Vec<_>) =
+            fresh.bars.into_iter().partition(|bar| date_of(bar) < today);
+        if !complete.is_empty() {
+            self.reconcile(&key, &mut series, &complete);
+            self.store.save(&key, &series)?;
+        }
+        if !unfinished.is_empty() {
+            series.extend(unfinished);
+            series = merge(series);
+        }
+        series.retain(|bar| dates.contains(&date_of(bar)));
+        Ok(series)
+    }
+
+    /// Merge newly downloaded bars into a stored series. Only
+    /// [`historical_bar::data_types::AdjustedLast`] series are checked for corporate actions, since
+    /// the prices of other data types are never adjusted after the fact. A detected action is also
+    /// sent to the receiver of [`HistoricalDownloader::subscribe_corporate_actions`].
+    fn reconcile(
+        &self,
+        key: &SeriesKey,
+        series: &mut Vec<Bar>,
+        fresh: &[Bar],
+    ) -> Option<CorporateActionDetected> {
+        if key.data_type != historical_bar::data_types::AdjustedLast.to_string() {
+            series.extend_from_slice(fresh);
+            *series = merge(std::mem::take(series));
+            return None;
+        }
+        let detected =
+            corporate::reconcile(series, fresh, self.policy, corporate::DEFAULT_TOLERANCE);
+        if let (Some(action), Some(actions)) = (detected, &self.actions) {
+            // The receiver may have been dropped, in which case nobody awaits the event
+            let _ = actions.send((key.clone(), action));
+        }
+        detected
+    }
+
+    /// Keep a series updated with the bars of an updating historical bar request.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID returned by [`Client::req_updating_historical_bar`].
+    /// * `key` - The series to which the bars are appended.
+    pub fn keep_updated(&mut self, req_id: i64, key: SeriesKey) {
+        self.updating.insert(req_id, (key, None));
+    }
+
+    /// Stop updating the series of a request, and append the bar in progress.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the series.
+    pub fn stop_updating(&mut self, req_id: i64) -> std::io::Result<()> {
+        match self.updating.remove(&req_id) {
+            Some((key, Some(bar))) => self.store.append(&key, &[bar]),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle a bar from an updating historical bar request.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID of the request.
+    /// * `bar` - The bar, from [`crate::wrapper::Local::updating_historical_bar`].
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the series.
+    ///
+    /// # Returns
+    /// Whether a completed bar was appended to the series.
+    pub fn update(&mut self, req_id: i64, bar: Bar) -> std::io::Result<bool> {
+        let Some((key, in_progress)) = self.updating.get_mut(&req_id) else {
+            return Ok(false);
+        };
+        let completed = in_progress
+            .replace(bar)
+            .filter(|previous| previous.core().datetime < bar.core().datetime);
+        match completed {
+            Some(completed) => self.store.append(key, &[completed]).map(|()| true),
+            None => Ok(false),
+        }
+    }
+}
+
+// === Helper functions ===
+
+/// Download one day of bars per trading day in a range of dates.
+///
+/// When `skip_empty` is set, the days for which IBKR returns no data are skipped rather than
+/// failing the whole download. Those days, and the days that were not requested because they are
+/// not trading days, are returned as empty.
+#[allow(clippy::too_many_arguments)]
+async fn fetch<P, E, S, D>(
+    client: &mut Client<Active<P, E>>,
+    security: &S,
+    from: NaiveDate,
+    to: NaiveDate,
+    bar_size: historical_bar::Size,
+    data: D,
+    regular_trading_hours_only: bool,
+    timeout: std::time::Duration,
+    skip_empty: bool,
+) -> Result<Fetched, Error>
+where
+    P: Profile,
+    E: Environment,
+    S: Security,
+    D: historical_bar::data_types::DataType<S>,
+{
+    let req_ids = client
+        .req_historical_bar_sessions_paced(
+            security,
+            from,
+            to,
+            bar_size,
+            data,
+            regular_trading_hours_only,
+        )
+        .await?;
+    let mut fetched = Fetched {
+        bars: Vec::new(),
+        empty: from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .filter(|date| req_ids.iter().all(|(requested, _)| requested != date))
+            .collect(),
+    };
+    for (date, req_id) in req_ids {
+        match client.recv_historical_bars(req_id, timeout).await {
+            Ok(day) if day.is_empty() => fetched.empty.push(date),
+            Ok(day) => fetched.bars.extend(day),
+            Err(Error::Api {
+                code: NO_DATA_CODE,
+                message,
+            }) if skip_empty && message.contains(NO_DATA_MESSAGE) => fetched.empty.push(date),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(fetched)
+}
+
+/// Request the trading sessions of a range of dates from the security's historical schedule.
+async fn get_sessions<P, E, S>(
+    client: &mut Client<Active<P, E>>,
+    security: &S,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Session>, Error>
+where
+    P: Profile,
+    E: Environment,
+    S: Security,
+{
+    let Some(end) = to.succ_opt() else {
+        return Ok(Vec::new());
+    };
+    let days = u32::try_from((end - from).num_days()).unwrap_or(u32::MAX);
+    let schedule = client
+        .req_historical_schedule(
+            security,
+            historical_bar::Duration::Day(days),
+            historical_bar::EndDateTime::Past(end.and_time(chrono::NaiveTime::MIN)),
+        )
+        .await?;
+    Ok(schedule.sessions.into_iter().map(Session::from).collect())
+}
+
+/// Get the length of a bar that is shorter than a day.
+fn get_bar_length(bar_size: historical_bar::Size) -> Option<std::time::Duration> {
+    let seconds = match bar_size {
+        historical_bar::Size::Seconds(s) => s as u64,
+        historical_bar::Size::Minutes(m) => m as u64 * 60,
+        historical_bar::Size::Hours(h) => h as u64 * 3600,
+        historical_bar::Size::Day | historical_bar::Size::Week | historical_bar::Size::Month => {
+            return None
+        }
+    };
+    Some(std::time::Duration::from_secs(seconds))
+}
+
+/// Get the exchange's current date, before which every trading day has ended.
+fn get_today<P: Profile, E: Environment>(
+    client: &Client<Active<P, E>>,
+    time_zone: Tz,
+) -> NaiveDate {
+    client
+        .get_clock()
+        .now()
+        .with_timezone(&time_zone)
+        .date_naive()
+}
+
+#[cfg(feature = "sqlite")]
+#[inline]
+fn sqlite_error(error: rusqlite::Error) -> std::io::Error {
+    std::io::Error::other(error)
+}
+
+/// Sort bars chronologically, keeping only the last bar given for each timestamp.
+fn merge(bars: Vec<Bar>) -> Vec<Bar> {
+    bars.into_iter()
+        .map(|bar| (bar.core().datetime, bar))
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into_values()
+        .collect()
+}
+
+fn write_line(writer: &mut impl Write, bar: &Bar) -> std::io::Result<()> {
+    let BarCore {
+        datetime,
+        open,
+        high,
+        low,
+        close,
+    } = *bar.core();
+    let datetime = datetime.format(DATETIME_FORMAT);
+    match *bar {
+        Bar::Ordinary(_) => writeln!(writer, "{datetime},{open},{high},{low},{close},,,"),
+        Bar::Trades {
+            volume,
+            wap,
+            trade_count,
+            ..
+        } => writeln!(
+            writer,
+            "{datetime},{open},{high},{low},{close},{volume},{wap},{trade_count}"
+        ),
+    }
+}
+
+fn parse_line(line: &str) -> std::io::Result<Bar> {
+    let invalid = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Invalid bar in store: {line}"),
+        )
+    };
+    let fields = line.split(',').collect::<Vec<_>>();
+    let [datetime, open, high, low, close, volume, wap, trade_count] = fields[..] else {
+        return Err(invalid());
+    };
+    let price = |field: &str| field.parse::<f64>().map_err(|_| invalid());
+    let core = BarCore {
+        datetime: NaiveDateTime::parse_from_str(datetime, DATETIME_FORMAT)
+            .map_err(|_| invalid())?,
+        open: price(open)?,
+        high: price(high)?,
+        low: price(low)?,
+        close: price(close)?,
+    };
+    if volume.is_empty() {
+        return Ok(Bar::Ordinary(core));
+    }
+    Ok(Bar::Trades {
+        bar: core,
+        volume: price(volume)?,
+        wap: price(wap)?,
+        trade_count: trade_count.parse().map_err(|_| invalid())?,
+    })
+}
@@ -0,0 +1,494 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::client::{ActiveClient, Client};
+use crate::clock::{Clock, ReplayClock, ReplayMode};
+use crate::contract::{ContractId, Security};
+use crate::error::Error;
+use crate::execution::{Execution, ResolvedExecution};
+use crate::order::{Executable, Order};
+use crate::payload::{Bar, OrderStatusUpdate, Tick};
+use crate::schedule::{Schedule, TimerId};
+use crate::simulate::{FillModel, SimulatedEvent, Simulator, TouchFillModel};
+use crate::wrapper::{CancelToken, Initializer, Local};
+
+// === Type definitions ===
+
+/// The orders and time available to a [`Strategy`], which are provided by the live client or by
+/// a [`Backtest`], so that a strategy runs identically against both.
+pub trait Broker {
+    /// Place an order.
+    ///
+    /// # Arguments
+    /// * `order` - The order to execute.
+    ///
+    /// # Errors
+    /// Returns any error encountered while placing the order.
+    ///
+    /// # Returns
+    /// The ID of the order.
+    fn place_order<S, E>(
+        &mut self,
+        order: &Order<S, E>,
+    ) -> impl Future<Output = Result<i64, Error>>
+    where
+        S: Security,
+        E: Executable<S>;
+
+    /// Cancel an order.
+    ///
+    /// # Arguments
+    /// * `order_id` - The ID of the order to cancel.
+    ///
+    /// # Errors
+    /// Returns any error encountered while cancelling the order.
+    fn cancel_order(&mut self, order_id: i64) -> impl Future<Output = Result<(), Error>>;
+
+    /// Get the current time, which is the time of the latest replayed event during a backtest.
+    fn get_time(&self) -> DateTime<Utc>;
+
+    #[inline]
+    /// Get the live client, to make requests that have no backtest equivalent (such as market
+    /// data subscriptions).
+    ///
+    /// # Returns
+    /// The client, or [`None`] during a backtest.
+    fn get_client(&mut self) -> Option<&mut ActiveClient> {
+        None
+    }
+}
+
+/// A trading strategy, whose callbacks are driven by a [`LiveRunner`] or by a [`BacktestRunner`].
+///
+/// Each callback receives a [`Broker`] with which to place and cancel orders. Bars and ticks are
+/// identified by the request ID of their subscription during a live session, and by the ID given
+/// to the [`BacktestRunner`] during a backtest.
+pub trait Strategy {
+    /// The callback that is run once, before any other callback, such as to subscribe to market
+    /// data.
+    fn on_start<B: Broker>(&mut self, _broker: &mut B) -> impl Future<Output = ()> {
+        async {}
+    }
+    /// The callback that receives each new bar.
+    fn on_bar<B: Broker>(
+        &mut self,
+        _broker: &mut B,
+        _req_id: i64,
+        _bar: &Bar,
+    ) -> impl Future<Output = ()> {
+        async {}
+    }
+    /// The callback that receives each new tick.
+    fn on_tick<B: Broker>(
+        &mut self,
+        _broker: &mut B,
+        _req_id: i64,
+        _tick: &Tick,
+    ) -> impl Future<Output = ()> {
+        async {}
+    }
+    /// The callback that receives each fill of an order.
+    fn on_fill<B: Broker>(
+        &mut self,
+        _broker: &mut B,
+        _execution: &Execution,
+    ) -> impl Future<Output = ()> {
+        async {}
+    }
+    /// The callback that receives each change in the status of an order.
+    fn on_order_status<B: Broker>(
+        &mut self,
+        _broker: &mut B,
+        _status: &OrderStatusUpdate,
+    ) -> impl Future<Output = ()> {
+        async {}
+    }
+    /// The callback that is run at the interval given to the runner.
+    fn on_timer<B: Broker>(
+        &mut self,
+        _broker: &mut B,
+        _now: DateTime<Utc>,
+    ) -> impl Future<Output = ()> {
+        async {}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The timer of a [`BacktestRunner`], which is measured by its replay clock.
+struct Timer {
+    interval: chrono::Duration,
+    next: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+/// An [`Initializer`] that runs a [`Strategy`] against a live client, as in
+/// `builder.connect(..).await?.local(Live::new(strategy, None)).await`.
+pub struct Live<St: Strategy> {
+    strategy: St,
+    timer: Option<chrono::Duration>,
+}
+
+#[derive(Debug)]
+/// The [`Local`] wrapper that forwards a live client's bars, ticks, fills and order statuses to a
+/// [`Strategy`].
+///
+/// The strategy's timer is scheduled with [`Client::schedule`], so it runs on the same task as
+/// the other callbacks, even while the connection is quiet.
+pub struct LiveRunner<'c, St: Strategy> {
+    client: &'c mut ActiveClient,
+    strategy: St,
+    timer: Option<TimerId>,
+    cancel_loop: CancelToken,
+}
+
+#[derive(Debug)]
+/// The [`Broker`] of a backtest, which routes orders to a [`Simulator`] and tells the time from a
+/// [`ReplayClock`].
+pub struct Backtest<M: FillModel = TouchFillModel> {
+    simulator: Simulator<M>,
+    clock: Arc<ReplayClock>,
+    next_order_id: i64,
+    pending: Vec<SimulatedEvent>,
+}
+
+#[derive(Debug)]
+/// Runs a [`Strategy`] against historical bars and ticks, which are filled by a [`Simulator`].
+///
+/// Each event is first matched against the resting orders, whose fills are delivered to the
+/// strategy before the event itself. The events must be passed in chronological order.
+///
+/// The runner advances a [`ReplayClock`] to the time of each event before replaying it, so the
+/// backtest runs as fast as possible by default. A clock in another [`ReplayMode`], given with
+/// [`BacktestRunner::with_clock`], paces the replay instead, such as to watch a strategy trade in
+/// accelerated time.
+pub struct BacktestRunner<St: Strategy, M: FillModel = TouchFillModel> {
+    strategy: St,
+    backtest: Backtest<M>,
+    timer: Option<Timer>,
+    started: bool,
+}
+
+// === Type implementations ===
+
+impl Broker for ActiveClient {
+    #[inline]
+    async fn place_order<S, E>(&mut self, order: &Order<S, E>) -> Result<i64, Error>
+    where
+        S: Security,
+        E: Executable<S>,
+    {
+        self.req_place_order(order).await
+    }
+
+    #[inline]
+    async fn cancel_order(&mut self, order_id: i64) -> Result<(), Error> {
+        Client::cancel_order(self, order_id).await
+    }
+
+    #[inline]
+    fn get_time(&self) -> DateTime<Utc> {
+        self.get_clock().now()
+    }
+
+    #[inline]
+    fn get_client(&mut self) -> Option<&mut ActiveClient> {
+        Some(self)
+    }
+}
+
+impl Timer {
+    #[inline]
+    fn new(interval: chrono::Duration) -> Self {
+        Self {
+            interval,
+            next: None,
+        }
+    }
+
+    /// Return whether the timer is due at a given time, scheduling its next run if it is. The
+    /// first run is one interval after the first check.
+    fn is_due(&mut self, now: DateTime<Utc>) -> bool {
+        match self.next {
+            Some(next) if next <= now => {
+                self.next = Some(now + self.interval);
+                true
+            }
+            Some(_) => false,
+            None => {
+                self.next = Some(now + self.interval);
+                false
+            }
+        }
+    }
+}
+
+impl<St: Strategy> Live<St> {
+    #[inline]
+    #[must_use]
+    /// Create a new initializer.
+    ///
+    /// # Arguments
+    /// * `strategy` - The strategy to run.
+    /// * `timer` - The interval at which [`Strategy::on_timer`] runs, if at all.
+    pub const fn new(strategy: St, timer: Option<chrono::Duration>) -> Self {
+        Self { strategy, timer }
+    }
+}
+
+impl<'c, St: Strategy + 'static> Initializer<'c> for Live<St> {
+    type Wrap = LiveRunner<'c, St>;
+
+    async fn build(self, client: &'c mut ActiveClient, cancel_loop: CancelToken) -> Self::Wrap {
+        let now = client.get_clock().now();
+        let timer = self.timer.and_then(|interval| {
+            client.schedule(Schedule::Every {
+                start: now + interval,
+                interval,
+            })
+        });
+        let mut runner = LiveRunner {
+            client,
+            strategy: self.strategy,
+            timer,
+            cancel_loop,
+        };
+        runner.strategy.on_start(runner.client).await;
+        runner
+    }
+}
+
+impl<St: Strategy> LiveRunner<'_, St> {
+    #[inline]
+    #[must_use]
+    /// Return the strategy.
+    pub const fn get_strategy(&self) -> &St {
+        &self.strategy
+    }
+
+    #[inline]
+    /// Stop the client loop, which disconnects the client.
+    pub fn stop(&self) {
+        self.cancel_loop.cancel();
+    }
+}
+
+impl<'c, St: Strategy> Local<'c> for LiveRunner<'c, St> {
+    fn real_time_bar(&mut self, req_id: i64, bar: Bar) -> impl Future {
+        async move {
+            self.strategy.on_bar(self.client, req_id, &bar).await;
+        }
+    }
+
+    fn updating_historical_bar(&mut self, req_id: i64, bar: Bar) -> impl Future {
+        async move {
+            self.strategy.on_bar(self.client, req_id, &bar).await;
+        }
+    }
+
+    fn live_tick(&mut self, req_id: i64, tick: Tick) -> impl Future {
+        async move {
+            self.strategy.on_tick(self.client, req_id, &tick).await;
+        }
+    }
+
+    fn execution(&mut self, _req_id: i64, execution: Execution) -> impl Future {
+        async move {
+            self.strategy.on_fill(self.client, &execution).await;
+        }
+    }
+
+    fn resolved_execution(&mut self, _req_id: i64, execution: ResolvedExecution) -> impl Future {
+        async move {
+            self.strategy
+                .on_fill(self.client, &execution.execution)
+                .await;
+        }
+    }
+
+    fn order_status(&mut self, status: OrderStatusUpdate) -> impl Future {
+        async move {
+            self.strategy.on_order_status(self.client, &status).await;
+        }
+    }
+
+    fn timer(&mut self, timer_id: TimerId, time: DateTime<Utc>) -> impl Future {
+        async move {
+            if self.timer == Some(timer_id) {
+                self.strategy.on_timer(self.client, time).await;
+            }
+        }
+    }
+}
+
+impl<M: FillModel> Backtest<M> {
+    #[inline]
+    #[must_use]
+    /// Return the simulator to which orders are routed.
+    pub const fn get_simulator(&self) -> &Simulator<M> {
+        &self.simulator
+    }
+}
+
+impl<M: FillModel> Broker for Backtest<M> {
+    async fn place_order<S, E>(&mut self, order: &Order<S, E>) -> Result<i64, Error>
+    where
+        S: Security,
+        E: Executable<S>,
+    {
+        let order_id = self.next_order_id;
+        self.next_order_id += 1;
+        let event = self.simulator.place_order(order_id, order);
+        self.pending.push(event);
+        Ok(order_id)
+    }
+
+    async fn cancel_order(&mut self, order_id: i64) -> Result<(), Error> {
+        self.pending.extend(self.simulator.cancel_order(order_id));
+        Ok(())
+    }
+
+    #[inline]
+    fn get_time(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+}
+
+impl<St: Strategy, M: FillModel> BacktestRunner<St, M> {
+    #[must_use]
+    /// Create a new runner.
+    ///
+    /// # Arguments
+    /// * `strategy` - The strategy to run.
+    /// * `simulator` - The simulator with which orders are filled.
+    /// * `timer` - The interval at which [`Strategy::on_timer`] runs, if at all, which is measured
+    /// by the replay clock as it advances to the time of each event.
+    pub fn new(strategy: St, simulator: Simulator<M>, timer: Option<chrono::Duration>) -> Self {
+        Self {
+            strategy,
+            backtest: Backtest {
+                simulator,
+                clock: Arc::new(ReplayClock::new(
+                    DateTime::<Utc>::MIN_UTC,
+                    ReplayMode::AsFastAsPossible,
+                )),
+                next_order_id: 1,
+                pending: Vec::new(),
+            },
+            timer: timer.map(Timer::new),
+            started: false,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Replace the clock that the runner advances to the time of each event, which is otherwise
+    /// a [`ReplayClock`] in [`ReplayMode::AsFastAsPossible`]. The clock should start no later than
+    /// the first event.
+    ///
+    /// # Arguments
+    /// * `clock` - The clock, which may also be given to the components that the strategy uses
+    /// (such as a client's [`Client::set_clock`]) so that they follow the replay.
+    pub fn with_clock(mut self, clock: Arc<ReplayClock>) -> Self {
+        self.backtest.clock = clock;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the strategy.
+    pub const fn get_strategy(&self) -> &St {
+        &self.strategy
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the backtest's broker, which holds its simulator.
+    pub const fn get_backtest(&self) -> &Backtest<M> {
+        &self.backtest
+    }
+
+    #[inline]
+    #[must_use]
+    /// Consume the runner, returning the strategy.
+    pub fn into_strategy(self) -> St {
+        self.strategy
+    }
+
+    /// Replay a bar.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID with which the bar is passed to [`Strategy::on_bar`].
+    /// * `contract_id` - The contract to which the bar belongs.
+    /// * `bar` - The bar.
+    pub async fn on_bar(&mut self, req_id: i64, contract_id: ContractId, bar: &Bar) {
+        self.advance(Utc.from_utc_datetime(&bar.core().datetime))
+            .await;
+        let events = self.backtest.simulator.on_bar(contract_id, bar);
+        self.deliver(events).await;
+        self.strategy.on_bar(&mut self.backtest, req_id, bar).await;
+        self.flush().await;
+    }
+
+    /// Replay a tick.
+    ///
+    /// # Arguments
+    /// * `req_id` - The ID with which the tick is passed to [`Strategy::on_tick`].
+    /// * `contract_id` - The contract to which the tick belongs.
+    /// * `tick` - The tick.
+    pub async fn on_tick(&mut self, req_id: i64, contract_id: ContractId, tick: &Tick) {
+        let (Tick::Midpoint { datetime, .. }
+        | Tick::BidAsk { datetime, .. }
+        | Tick::Last { datetime, .. }) = tick;
+        self.advance(Utc.from_utc_datetime(datetime)).await;
+        let events = self.backtest.simulator.on_tick(contract_id, tick);
+        self.deliver(events).await;
+        self.strategy
+            .on_tick(&mut self.backtest, req_id, tick)
+            .await;
+        self.flush().await;
+    }
+
+    async fn advance(&mut self, now: DateTime<Utc>) {
+        self.backtest.clock.advance_to(now).await;
+        if !self.started {
+            self.started = true;
+            self.strategy.on_start(&mut self.backtest).await;
+            self.flush().await;
+        }
+        if self
+            .timer
+            .as_mut()
+            .is_some_and(|timer| timer.is_due(self.backtest.clock.now()))
+        {
+            let now = self.backtest.clock.now();
+            self.strategy.on_timer(&mut self.backtest, now).await;
+            self.flush().await;
+        }
+    }
+
+    /// Deliver the status updates caused by the orders that the strategy placed or cancelled.
+    async fn flush(&mut self) {
+        while !self.backtest.pending.is_empty() {
+            let events = std::mem::take(&mut self.backtest.pending);
+            self.deliver(events).await;
+        }
+    }
+
+    async fn deliver(&mut self, events: Vec<SimulatedEvent>) {
+        for event in events {
+            match event {
+                SimulatedEvent::OrderStatus(status) => {
+                    self.strategy
+                        .on_order_status(&mut self.backtest, &status)
+                        .await;
+                }
+                SimulatedEvent::Execution(execution) => {
+                    self.strategy.on_fill(&mut self.backtest, &execution).await;
+                }
+                SimulatedEvent::CommissionReport(_) => (),
+            }
+        }
+    }
+}
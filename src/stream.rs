@@ -0,0 +1,254 @@
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio::sync::mpsc;
+
+use crate::client::{Replay, Shared};
+use crate::comm::Writer;
+use crate::message::Out;
+use crate::payload::{Bar, Tick};
+use crate::tick::{Class, Price, Size, TickAttributes, Volume};
+
+// === Constants ===
+
+/// The number of events that a stream holds before further events are dropped, which bounds the
+/// memory used by a consumer that falls behind.
+pub const CAPACITY: usize = 4096;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A single event delivered by an [`EventStream`].
+pub enum TypedEvent {
+    /// A price tick from [`crate::client::Client::req_market_data_stream`].
+    Price {
+        /// The price.
+        price: Class<Price>,
+        /// The attributes of the price.
+        attributes: TickAttributes,
+    },
+    /// A size tick from [`crate::client::Client::req_market_data_stream`].
+    Size(Class<Size>),
+    /// A volume tick from [`crate::client::Client::req_market_data_stream`].
+    Volume(Volume),
+    /// A bar from [`crate::client::Client::req_real_time_bars_stream`].
+    Bar(Bar),
+    /// A tick from [`crate::client::Client::req_tick_by_tick_data_stream`].
+    Tick(Tick),
+}
+
+#[derive(Debug)]
+/// A [`futures_core::Stream`] of the events of a single subscription, which allows the events to
+/// be processed with ordinary async iteration instead of a [`crate::wrapper::Remote`] callback.
+///
+/// The events are also delivered to the client's wrapper as usual. The stream holds at most
+/// [`CAPACITY`] events; should the consumer fall further behind, the newest events are dropped
+/// and counted (see [`EventStream::get_dropped`]). Dropping the stream cancels its subscription,
+/// unless the subscription was already cancelled with the corresponding `cancel_*` method.
+pub struct EventStream {
+    req_id: i64,
+    subscription: Subscription,
+    rx: mpsc::Receiver<TypedEvent>,
+    dropped: Arc<AtomicU64>,
+    writer: Option<Writer>,
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The kind of subscription to which an [`EventStream`] belongs, which determines how it is
+/// cancelled.
+pub(crate) enum Subscription {
+    MarketData,
+    RealTimeBars,
+    TickByTick,
+}
+
+#[derive(Debug)]
+/// The sending half of an [`EventStream`], as registered with the client.
+pub(crate) struct Sender {
+    tx: mpsc::Sender<TypedEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+// === Type implementations ===
+
+impl EventStream {
+    #[inline]
+    pub(crate) fn register(
+        req_id: i64,
+        subscription: Subscription,
+        writer: Writer,
+        shared: &Arc<Shared>,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        lock_streams(shared).insert(
+            req_id,
+            Sender {
+                tx,
+                dropped: Arc::clone(&dropped),
+            },
+        );
+        Self {
+            req_id,
+            subscription,
+            rx,
+            dropped,
+            writer: Some(writer),
+            shared: Arc::clone(shared),
+        }
+    }
+
+    #[inline]
+    /// Unregister a stream whose request could not be made, so that dropping it does not cancel
+    /// the request.
+    pub(crate) fn disarm(mut self) {
+        self.writer = None;
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the ID of the request to which the stream belongs.
+    pub const fn get_req_id(&self) -> i64 {
+        self.req_id
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the number of events that were dropped because the stream was full.
+    pub fn get_dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    /// Wait for the next event.
+    ///
+    /// # Returns
+    /// The next event, or [`None`] if the client loop stopped or the subscription was cancelled.
+    pub async fn recv(&mut self) -> Option<TypedEvent> {
+        self.rx.recv().await
+    }
+}
+
+impl futures_core::Stream for EventStream {
+    type Item = TypedEvent;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        // A stream whose subscription was already cancelled is no longer registered
+        let registered = lock_streams(&self.shared).remove(&self.req_id).is_some();
+        let Some(writer) = self.writer.take().filter(|_| registered) else {
+            return;
+        };
+        self.subscription.forget(&self.shared, self.req_id);
+        let (subscription, req_id) = (self.subscription, self.req_id);
+        // The subscription cannot be cancelled without a runtime on which to write the message
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(subscription.cancel(writer, req_id));
+        }
+    }
+}
+
+impl Subscription {
+    #[inline]
+    const fn get_request(self) -> Out {
+        match self {
+            Self::MarketData => Out::ReqMktData,
+            Self::RealTimeBars => Out::ReqRealTimeBars,
+            Self::TickByTick => Out::ReqTickByTickData,
+        }
+    }
+
+    /// Discard the state that the client keeps for the subscription, including the request that
+    /// would otherwise be replayed upon reconnecting.
+    fn forget(self, shared: &Shared, req_id: i64) {
+        match self {
+            Self::MarketData => {
+                lock(&shared.exchange_ids).remove(&req_id);
+            }
+            Self::RealTimeBars => {
+                lock(&shared.real_time_bars).remove(&req_id);
+                lock(&shared.real_time_bar_contracts).remove(&req_id);
+            }
+            Self::TickByTick => (),
+        }
+        lock(&shared.dropped_streams).push(Replay::Request(req_id, self.get_request()));
+    }
+
+    /// Write the message that cancels the subscription.
+    async fn cancel(self, mut writer: Writer, req_id: i64) {
+        let added = match self {
+            Self::MarketData => writer.add_body((Out::CancelMktData, 2_u8, req_id)),
+            Self::RealTimeBars => writer.add_body((Out::CancelRealTimeBars, 1_u8, req_id)),
+            Self::TickByTick => writer.add_body((Out::CancelTickByTickData, req_id)),
+        };
+        let result = match added {
+            Ok(()) => writer.send().await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(_e) = result {
+            event!(
+                warn,
+                error = %_e,
+                req_id,
+                "Failed to cancel the subscription of a dropped stream"
+            );
+        }
+    }
+}
+
+// === Helper functions ===
+
+#[inline]
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[inline]
+fn lock_streams(
+    shared: &Shared,
+) -> std::sync::MutexGuard<'_, std::collections::HashMap<i64, Sender>> {
+    lock(&shared.streams)
+}
+
+#[inline]
+/// Unregister the stream of a request whose subscription is being cancelled, so that dropping the
+/// stream does not cancel the subscription again.
+pub(crate) fn unregister(shared: &Shared, req_id: i64) {
+    lock_streams(shared).remove(&req_id);
+}
+
+#[inline]
+/// Close every stream once the client loop stops, so that waiting for its events ends instead of
+/// hanging.
+pub(crate) fn close_all(shared: &Shared) {
+    lock_streams(shared).clear();
+}
+
+#[inline]
+/// Deliver an event to the stream of a request, if it has one. The event is only built when there
+/// is a stream to receive it, and it is dropped (and counted) if the stream is full.
+pub(crate) fn publish(shared: &Shared, req_id: i64, event: impl FnOnce() -> TypedEvent) {
+    let mut streams = lock_streams(shared);
+    if let Some(sender) = streams.get(&req_id) {
+        match sender.tx.try_send(event()) {
+            Ok(()) => (),
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                sender.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                streams.remove(&req_id);
+            }
+        }
+    }
+}
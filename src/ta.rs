@@ -0,0 +1,333 @@
+use std::collections::VecDeque;
+
+use crate::payload::{Bar, BarCore, Tick};
+
+// === Type definitions ===
+
+/// A technical indicator that is updated incrementally, one input at a time, so that the same
+/// indicator serves both live streams and historical series.
+pub trait Indicator {
+    /// The value from which the indicator is computed (ex: a closing price).
+    type Input;
+    /// The indicator's value.
+    type Output;
+
+    /// Update the indicator with the next input.
+    ///
+    /// # Returns
+    /// The indicator's new value, or [`None`] until enough inputs have been received.
+    fn update(&mut self, input: Self::Input) -> Option<Self::Output>;
+
+    /// Update the indicator with a whole series of inputs, such as the bars of a historical
+    /// request.
+    ///
+    /// # Returns
+    /// The indicator's value after each input, in the same order as the inputs.
+    fn batch<I>(&mut self, inputs: I) -> Vec<Option<Self::Output>>
+    where
+        I: IntoIterator<Item = Self::Input>,
+        Self: Sized,
+    {
+        inputs.into_iter().map(|input| self.update(input)).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A simple moving average of the last `period` values.
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// An exponential moving average, which is seeded with the simple average of its first `period`
+/// values.
+pub struct Ema {
+    alpha: f64,
+    seed: Sma,
+    value: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Welles Wilder's relative strength index, between 0 and 100.
+pub struct Rsi {
+    period: usize,
+    previous: Option<f64>,
+    count: usize,
+    average_gain: f64,
+    average_loss: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Welles Wilder's average true range, which is computed from bars rather than single values.
+pub struct Atr {
+    period: usize,
+    previous_close: Option<f64>,
+    count: usize,
+    value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Bollinger bands, which lie a number of standard deviations on either side of a simple moving
+/// average.
+pub struct Bollinger {
+    average: Sma,
+    width: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The value of [`Bollinger`] bands.
+pub struct Bands {
+    /// The lower band.
+    pub lower: f64,
+    /// The moving average.
+    pub middle: f64,
+    /// The upper band.
+    pub upper: f64,
+}
+
+// === Type implementations ===
+
+impl Sma {
+    #[must_use]
+    /// Create a new simple moving average.
+    ///
+    /// # Returns
+    /// The average, or [`None`] if the period is zero.
+    pub fn new(period: usize) -> Option<Self> {
+        (period > 0).then(|| Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the number of values that are averaged.
+    pub const fn get_period(&self) -> usize {
+        self.period
+    }
+
+    fn get_standard_deviation(&self) -> Option<f64> {
+        #[allow(clippy::cast_precision_loss)]
+        let period = self.period as f64;
+        let mean = self.sum / period;
+        (self.window.len() == self.period).then(|| {
+            (self
+                .window
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f64>()
+                / period)
+                .sqrt()
+        })
+    }
+}
+
+impl Indicator for Sma {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, input: f64) -> Option<f64> {
+        self.window.push_back(input);
+        self.sum += input;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or_default();
+        }
+        #[allow(clippy::cast_precision_loss)]
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+impl Ema {
+    #[must_use]
+    /// Create a new exponential moving average, whose smoothing factor is 2 / (`period` + 1).
+    ///
+    /// # Returns
+    /// The average, or [`None`] if the period is zero.
+    pub fn new(period: usize) -> Option<Self> {
+        #[allow(clippy::cast_precision_loss)]
+        let alpha = 2.0 / (period as f64 + 1.0);
+        Some(Self {
+            alpha,
+            seed: Sma::new(period)?,
+            value: None,
+        })
+    }
+}
+
+impl Indicator for Ema {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, input: f64) -> Option<f64> {
+        let value = match self.value {
+            Some(value) => value + self.alpha * (input - value),
+            None => self.seed.update(input)?,
+        };
+        self.value = Some(value);
+        Some(value)
+    }
+}
+
+impl Rsi {
+    #[must_use]
+    /// Create a new relative strength index.
+    ///
+    /// # Returns
+    /// The index, or [`None`] if the period is zero.
+    pub const fn new(period: usize) -> Option<Self> {
+        if period == 0 {
+            return None;
+        }
+        Some(Self {
+            period,
+            previous: None,
+            count: 0,
+            average_gain: 0.0,
+            average_loss: 0.0,
+        })
+    }
+}
+
+impl Indicator for Rsi {
+    type Input = f64;
+    type Output = f64;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn update(&mut self, input: f64) -> Option<f64> {
+        let change = input - self.previous.replace(input)?;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let period = self.period as f64;
+        // The first averages are simple, after which they are smoothed
+        if self.count < self.period {
+            self.count += 1;
+            self.average_gain += gain / period;
+            self.average_loss += loss / period;
+            if self.count < self.period {
+                return None;
+            }
+        } else {
+            self.average_gain = (self.average_gain * (period - 1.0) + gain) / period;
+            self.average_loss = (self.average_loss * (period - 1.0) + loss) / period;
+        }
+        Some(if self.average_loss == 0.0 {
+            100.0
+        } else {
+            100.0 - 100.0 / (1.0 + self.average_gain / self.average_loss)
+        })
+    }
+}
+
+impl Atr {
+    #[must_use]
+    /// Create a new average true range.
+    ///
+    /// # Returns
+    /// The average, or [`None`] if the period is zero.
+    pub const fn new(period: usize) -> Option<Self> {
+        if period == 0 {
+            return None;
+        }
+        Some(Self {
+            period,
+            previous_close: None,
+            count: 0,
+            value: 0.0,
+        })
+    }
+}
+
+impl Indicator for Atr {
+    type Input = BarCore;
+    type Output = f64;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn update(&mut self, input: BarCore) -> Option<f64> {
+        let range = input.high - input.low;
+        let true_range = self
+            .previous_close
+            .replace(input.close)
+            .map_or(range, |close| {
+                range
+                    .max((input.high - close).abs())
+                    .max((input.low - close).abs())
+            });
+        let period = self.period as f64;
+        // The first average is simple, after which it is smoothed
+        if self.count < self.period {
+            self.count += 1;
+            self.value += true_range / period;
+            (self.count == self.period).then_some(self.value)
+        } else {
+            self.value = (self.value * (period - 1.0) + true_range) / period;
+            Some(self.value)
+        }
+    }
+}
+
+impl Bollinger {
+    #[must_use]
+    /// Create new Bollinger bands.
+    ///
+    /// # Arguments
+    /// * `period` - The number of values in the moving average (ex: 20).
+    /// * `width` - The number of standard deviations between the average and each band (ex: 2.0).
+    ///
+    /// # Returns
+    /// The bands, or [`None`] if the period is zero.
+    pub fn new(period: usize, width: f64) -> Option<Self> {
+        Some(Self {
+            average: Sma::new(period)?,
+            width,
+        })
+    }
+}
+
+impl Indicator for Bollinger {
+    type Input = f64;
+    type Output = Bands;
+
+    fn update(&mut self, input: f64) -> Option<Bands> {
+        let middle = self.average.update(input)?;
+        let deviation = self.average.get_standard_deviation()? * self.width;
+        Some(Bands {
+            lower: middle - deviation,
+            middle,
+            upper: middle + deviation,
+        })
+    }
+}
+
+// === Functions ===
+
+#[inline]
+/// Get the closing prices of a series of bars, which are the usual input of the indicators that
+/// take single values.
+pub fn closes<'b, I>(bars: I) -> impl Iterator<Item = f64> + 'b
+where
+    I: IntoIterator<Item = &'b Bar>,
+    I::IntoIter: 'b,
+{
+    bars.into_iter().map(|bar| bar.core().close)
+}
+
+#[inline]
+#[must_use]
+/// Get the price of a tick, which can be the input of the indicators that take single values.
+///
+/// # Returns
+/// The trade price of a [`Tick::Last`], the midpoint of a [`Tick::Midpoint`] or [`Tick::BidAsk`],
+/// or [`None`] for a quote with a missing side.
+pub fn tick_price(tick: &Tick) -> Option<f64> {
+    match *tick {
+        Tick::Last { price, .. } | Tick::Midpoint { price, .. } => Some(price),
+        Tick::BidAsk {
+            bid_price,
+            ask_price,
+            ..
+        } => (bid_price > 0.0 && ask_price > 0.0).then(|| (bid_price + ask_price) / 2.0),
+    }
+}
@@ -303,6 +303,305 @@ pub type News = String;
 /// Trade count for the day.
 pub type TradeCount = f64;
 
+#[derive(Debug, Default, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+/// The attributes that accompany a price tick or a tick-by-tick trade / quote.
+///
+/// IBKR only sets the attributes that are relevant to a given message: price ticks carry
+/// `can_auto_execute`, `past_limit`, and `pre_open`; trades carry `past_limit` and `unreported`;
+/// and quotes carry `bid_past_low` and `ask_past_high`. All other attributes are [`false`].
+pub struct TickAttributes {
+    /// Whether the bid / ask order can be automatically executed.
+    pub can_auto_execute: bool,
+    /// Whether the bid / ask price is outside of the current limits (or the trade occurred at a
+    /// price outside of them).
+    pub past_limit: bool,
+    /// Whether the bid / ask price is a pre-open (indicative) price.
+    pub pre_open: bool,
+    /// Whether the trade was unreported, which includes odd-lot trades.
+    pub unreported: bool,
+    /// Whether the bid price is lower than the day's lowest price.
+    pub bid_past_low: bool,
+    /// Whether the ask price is higher than the day's highest price.
+    pub ask_past_high: bool,
+}
+
+impl TickAttributes {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn from_price_mask(mask: u8) -> Self {
+        Self {
+            can_auto_execute: mask & 1 != 0,
+            past_limit: mask & 2 != 0,
+            pre_open: mask & 4 != 0,
+            unreported: false,
+            bid_past_low: false,
+            ask_past_high: false,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn from_last_mask(mask: u8) -> Self {
+        Self {
+            can_auto_execute: false,
+            past_limit: mask & 1 != 0,
+            pre_open: false,
+            unreported: mask & 2 != 0,
+            bid_past_low: false,
+            ask_past_high: false,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn from_bid_ask_mask(mask: u8) -> Self {
+        Self {
+            can_auto_execute: false,
+            past_limit: false,
+            pre_open: false,
+            unreported: false,
+            bid_past_low: mask & 1 != 0,
+            ask_past_high: mask & 2 != 0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Historical bid / ask ticks order the two bits the other way around from tick-by-tick
+    /// quotes.
+    pub(crate) const fn from_historical_bid_ask_mask(mask: u8) -> Self {
+        Self {
+            can_auto_execute: false,
+            past_limit: false,
+            pre_open: false,
+            unreported: false,
+            bid_past_low: mask & 2 != 0,
+            ask_past_high: mask & 1 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+/// Determines which trades are delivered by a tick-by-tick or real-time volume stream. Set it for
+/// a given request with [`crate::client::Client::set_trade_filter`].
+pub struct TradeFilter {
+    /// When [`false`], drop trades that are unreported, which includes most odd-lot trades.
+    ///
+    /// Individual unreported trades cannot be identified in [`RealTimeVolume::All`] data, so all
+    /// such data is dropped instead. [`RealTimeVolume::Trades`] data, which can be requested with
+    /// [`crate::market_data::live_data::data_types::RealTimeTradeVolume`], never includes
+    /// unreported trades.
+    pub include_unreported: bool,
+    /// If [`Some`], drop trades whose size is smaller than the given round lot size.
+    pub round_lot_size: Option<f64>,
+}
+
+impl Default for TradeFilter {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            include_unreported: true,
+            round_lot_size: None,
+        }
+    }
+}
+
+impl TradeFilter {
+    #[inline]
+    #[must_use]
+    /// Create a filter that only accepts reported trades of at least one round lot.
+    ///
+    /// # Arguments
+    /// * `round_lot_size` - The size of a round lot (ie: 100 shares for most US stocks).
+    pub const fn reported_round_lots(round_lot_size: f64) -> Self {
+        Self {
+            include_unreported: false,
+            round_lot_size: Some(round_lot_size),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return whether a trade of a given size and with given attributes passes the filter.
+    pub fn accepts(&self, size: f64, attributes: TickAttributes) -> bool {
+        (self.include_unreported || !attributes.unreported)
+            && self.round_lot_size.is_none_or(|lot| size >= lot)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return whether a tick passes the filter. Only [`crate::payload::Tick::Last`] ticks are
+    /// filtered.
+    pub fn accepts_tick(&self, tick: &crate::payload::Tick) -> bool {
+        match tick {
+            crate::payload::Tick::Last {
+                size, attributes, ..
+            } => self.accepts(*size, *attributes),
+            _ => true,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return whether real-time volume data passes the filter.
+    pub fn accepts_volume(&self, volume: &RealTimeVolume) -> bool {
+        match volume {
+            RealTimeVolume::All(base) => {
+                self.include_unreported && self.accepts(base.last_size, TickAttributes::default())
+            }
+            RealTimeVolume::Trades(base) => self.accepts(base.last_size, TickAttributes::default()),
+        }
+    }
+}
+
+macro_rules! tick_types {
+    ($($id: literal => $name: ident, $doc: literal;)*) => {
+        #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+        #[non_exhaustive]
+        /// The types of market data ("ticks") that IBKR identifies by number.
+        ///
+        /// Most ticks are decoded into a dedicated type and passed to a dedicated callback. Those that
+        /// are not are passed to [`crate::wrapper::Local::other_tick`] or
+        /// [`crate::wrapper::Remote::other_tick`] along with their [`TickType`].
+        pub enum TickType {
+            $(
+                #[doc = $doc]
+                $name,
+            )*
+            /// A tick type that is not known to this version of the API.
+            RawOther(u32),
+        }
+
+        impl From<u32> for TickType {
+            #[inline]
+            fn from(value: u32) -> Self {
+                match value {
+                    $($id => Self::$name,)*
+                    other => Self::RawOther(other),
+                }
+            }
+        }
+
+        impl From<TickType> for u32 {
+            #[inline]
+            fn from(value: TickType) -> Self {
+                match value {
+                    $(TickType::$name => $id,)*
+                    TickType::RawOther(other) => other,
+                }
+            }
+        }
+    };
+}
+
+tick_types! {
+    0 => BidSize, "The size of the best bid.";
+    1 => Bid, "The best bid price.";
+    2 => Ask, "The best ask price.";
+    3 => AskSize, "The size of the best ask.";
+    4 => Last, "The last traded price.";
+    5 => LastSize, "The size of the last trade.";
+    6 => High, "The day's highest price.";
+    7 => Low, "The day's lowest price.";
+    8 => Volume, "The day's traded volume.";
+    9 => Close, "The previous day's closing price.";
+    10 => BidOption, "The option calculations based on the best bid.";
+    11 => AskOption, "The option calculations based on the best ask.";
+    12 => LastOption, "The option calculations based on the last trade.";
+    13 => ModelOption, "The option calculations based on IBKR's pricing model.";
+    14 => Open, "The day's opening price.";
+    15 => Low13Week, "The lowest price over the past 13 weeks.";
+    16 => High13Week, "The highest price over the past 13 weeks.";
+    17 => Low26Week, "The lowest price over the past 26 weeks.";
+    18 => High26Week, "The highest price over the past 26 weeks.";
+    19 => Low52Week, "The lowest price over the past 52 weeks.";
+    20 => High52Week, "The highest price over the past 52 weeks.";
+    21 => AverageVolume, "The average daily volume over 90 days.";
+    22 => OpenInterest, "The total number of outstanding option contracts.";
+    23 => OptionHistoricalVolatility, "The 30-day historical volatility.";
+    24 => OptionImpliedVolatility, "The 30-day implied volatility.";
+    25 => OptionBidExchange, "Not used.";
+    26 => OptionAskExchange, "Not used.";
+    27 => OptionCallOpenInterest, "The open interest of call options.";
+    28 => OptionPutOpenInterest, "The open interest of put options.";
+    29 => OptionCallVolume, "The day's traded volume of call options.";
+    30 => OptionPutVolume, "The day's traded volume of put options.";
+    31 => IndexFuturePremium, "The premium of an index future over its underlying index.";
+    32 => BidExchange, "The exchanges quoting the best bid.";
+    33 => AskExchange, "The exchanges quoting the best ask.";
+    34 => AuctionVolume, "The number of shares that would trade in an auction.";
+    35 => AuctionPrice, "The price at which an auction would occur.";
+    36 => AuctionImbalance, "The number of unmatched shares in an auction.";
+    37 => MarkPrice, "The mark price used in margin calculations.";
+    38 => BidEfpComputation, "The EFP computations based on the best bid.";
+    39 => AskEfpComputation, "The EFP computations based on the best ask.";
+    40 => LastEfpComputation, "The EFP computations based on the last trade.";
+    41 => OpenEfpComputation, "The EFP computations based on the day's open.";
+    42 => HighEfpComputation, "The EFP computations based on the day's high.";
+    43 => LowEfpComputation, "The EFP computations based on the day's low.";
+    44 => CloseEfpComputation, "The EFP computations based on the previous close.";
+    45 => LastTimestamp, "The time of the last trade.";
+    46 => Shortable, "The ease with which the security can be shorted.";
+    47 => FundamentalRatios, "Fundamental ratios of the security.";
+    48 => RealTimeVolume, "The last trade details, including non-reportable trades.";
+    49 => Halted, "Whether trading of the security is halted.";
+    50 => BidYield, "The yield implied by the best bid.";
+    51 => AskYield, "The yield implied by the best ask.";
+    52 => LastYield, "The yield implied by the last trade.";
+    53 => CustomOptionComputation, "The option calculations based on custom parameters.";
+    54 => TradeCount, "The day's number of trades.";
+    55 => TradeRate, "The number of trades per minute.";
+    56 => VolumeRate, "The traded volume per minute.";
+    57 => LastRegularTradingHoursTrade, "The last trade during regular trading hours.";
+    58 => RealTimeHistoricalVolatility, "The 30-day real time historical volatility.";
+    59 => Dividends, "The security's dividends.";
+    60 => BondFactorMultiplier, "The bond factor multiplier.";
+    61 => RegulatoryImbalance, "The imbalance used in regulatory auctions.";
+    62 => News, "The security's news feed.";
+    63 => ShortTermVolume3Minute, "The traded volume over the past 3 minutes.";
+    64 => ShortTermVolume5Minute, "The traded volume over the past 5 minutes.";
+    65 => ShortTermVolume10Minute, "The traded volume over the past 10 minutes.";
+    66 => DelayedBid, "The delayed best bid price.";
+    67 => DelayedAsk, "The delayed best ask price.";
+    68 => DelayedLast, "The delayed last traded price.";
+    69 => DelayedBidSize, "The delayed size of the best bid.";
+    70 => DelayedAskSize, "The delayed size of the best ask.";
+    71 => DelayedLastSize, "The delayed size of the last trade.";
+    72 => DelayedHigh, "The delayed day's highest price.";
+    73 => DelayedLow, "The delayed day's lowest price.";
+    74 => DelayedVolume, "The delayed day's traded volume.";
+    75 => DelayedClose, "The delayed previous day's closing price.";
+    76 => DelayedOpen, "The delayed day's opening price.";
+    77 => RealTimeTradeVolume, "The last trade details, excluding non-reportable trades.";
+    78 => CreditmanMarkPrice, "The mark price used in IBKR's credit manager.";
+    79 => CreditmanSlowMarkPrice, "The slower mark price used in IBKR's credit manager.";
+    80 => DelayedBidOption, "The delayed option calculations based on the best bid.";
+    81 => DelayedAskOption, "The delayed option calculations based on the best ask.";
+    82 => DelayedLastOption, "The delayed option calculations based on the last trade.";
+    83 => DelayedModelOption, "The delayed option calculations based on IBKR's pricing model.";
+    84 => LastExchange, "The exchange of the last trade.";
+    85 => LastRegulatoryTime, "The time of the last regulatory trade.";
+    86 => FuturesOpenInterest, "The total number of outstanding futures contracts.";
+    87 => AverageOptionVolume, "The average daily option volume over 90 days.";
+    88 => DelayedLastTimestamp, "The delayed time of the last trade.";
+    89 => ShortableShares, "The number of shares available to short.";
+    90 => DelayedHalted, "Whether trading of the security is halted, delayed.";
+    91 => Reuters2MutualFunds, "Reuters mutual fund data.";
+    92 => EtfNavClose, "The ETF's closing net asset value.";
+    93 => EtfNavPriorClose, "The ETF's previous closing net asset value.";
+    94 => EtfNavBid, "The ETF's net asset value based on the best bid.";
+    95 => EtfNavAsk, "The ETF's net asset value based on the best ask.";
+    96 => EtfNavLast, "The ETF's net asset value based on the last trade.";
+    97 => EtfFrozenNavLast, "The ETF's frozen net asset value based on the last trade.";
+    98 => EtfNavHigh, "The ETF's highest net asset value.";
+    99 => EtfNavLow, "The ETF's lowest net asset value.";
+    100 => SocialMarketAnalytics, "Social market analytics data.";
+    101 => EstimatedIpoMidpoint, "The estimated midpoint price of an IPO.";
+    102 => FinalIpoLast, "The final price of an IPO.";
+    103 => DelayedBidYield, "The delayed yield implied by the best bid.";
+    104 => DelayedAskYield, "The delayed yield implied by the best ask.";
+}
+
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 /// The two classes of data that can be returned for various market data requests.
 pub enum Class<P: indicators::Valid> {
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::payload::{Bar, BarCore, Tick};
+
+pub use chrono_tz::Tz;
+
+// === Type definitions ===
+
+/// Implemented by all types that carry a timestamp received from IBKR.
+///
+/// Intraday bars and ticks are timestamped in UTC. Bars of one day or longer are instead dated by
+/// IBKR with the exchange's own trading date, so their timestamps are midnight of that date and
+/// must not be converted.
+pub trait Timestamp {
+    /// Get the raw timestamp of the value.
+    fn get_datetime(&self) -> NaiveDateTime;
+
+    #[inline]
+    /// Convert the value's UTC timestamp into the time zone of an exchange.
+    ///
+    /// # Arguments
+    /// * `time_zone` - The exchange's time zone, from [`crate::client::Client::get_time_zone`].
+    ///
+    /// # Returns
+    /// The timestamp in the exchange's local time.
+    fn to_exchange_time(&self, time_zone: Tz) -> DateTime<Tz> {
+        to_exchange_time(self.get_datetime(), time_zone)
+    }
+
+    #[inline]
+    /// Get the exchange's calendar date at the value's UTC timestamp, which is the date of the
+    /// trading session to which the value belongs.
+    ///
+    /// # Arguments
+    /// * `time_zone` - The exchange's time zone, from [`crate::client::Client::get_time_zone`].
+    ///
+    /// # Returns
+    /// The exchange's local date.
+    fn get_exchange_date(&self, time_zone: Tz) -> NaiveDate {
+        self.to_exchange_time(time_zone).date_naive()
+    }
+}
+
+// === Type implementations ===
+
+impl Timestamp for NaiveDateTime {
+    #[inline]
+    fn get_datetime(&self) -> NaiveDateTime {
+        *self
+    }
+}
+
+impl Timestamp for BarCore {
+    #[inline]
+    fn get_datetime(&self) -> NaiveDateTime {
+        self.datetime
+    }
+}
+
+impl Timestamp for Bar {
+    #[inline]
+    fn get_datetime(&self) -> NaiveDateTime {
+        self.core().datetime
+    }
+}
+
+impl Timestamp for Tick {
+    #[inline]
+    fn get_datetime(&self) -> NaiveDateTime {
+        match self {
+            Self::Midpoint { datetime, .. }
+            | Self::BidAsk { datetime, .. }
+            | Self::Last { datetime, .. } => *datetime,
+        }
+    }
+}
+
+// === Functions ===
+
+#[inline]
+#[must_use]
+/// Convert a UTC timestamp into the time zone of an exchange.
+///
+/// # Arguments
+/// * `datetime` - The UTC timestamp to convert.
+/// * `time_zone` - The exchange's time zone, from [`crate::client::Client::get_time_zone`].
+///
+/// # Returns
+/// The timestamp in the exchange's local time.
+pub fn to_exchange_time(datetime: NaiveDateTime, time_zone: Tz) -> DateTime<Tz> {
+    time_zone.from_utc_datetime(&datetime)
+}
+
+#[inline]
+/// Group intraday bars or ticks by the exchange's trading date, so that each group covers a single
+/// session rather than a single UTC day.
+///
+/// # Arguments
+/// * `values` - The bars or ticks to group.
+/// * `time_zone` - The exchange's time zone, from [`crate::client::Client::get_time_zone`].
+///
+/// # Returns
+/// The values keyed by exchange date, each group in its original order.
+pub fn group_by_exchange_date<T, I>(values: I, time_zone: Tz) -> BTreeMap<NaiveDate, Vec<T>>
+where
+    T: Timestamp,
+    I: IntoIterator<Item = T>,
+{
+    values
+        .into_iter()
+        .fold(BTreeMap::new(), |mut groups, value| {
+            groups
+                .entry(value.get_exchange_date(time_zone))
+                .or_insert_with(Vec::new)
+                .push(value);
+            groups
+        })
+}
@@ -0,0 +1,172 @@
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::{
+    self,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, WebPkiSupportedAlgorithms},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+
+use crate::error::Error;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Determines how the certificate presented by an IBKR platform is verified.
+pub enum Verification {
+    /// Verify the certificate against Mozilla's set of trusted root certificates.
+    WebPkiRoots,
+    /// Verify the certificate against the root certificates in a PEM file. This is the usual
+    /// choice for IB Gateway, which presents a self-signed certificate that can be exported and
+    /// trusted explicitly.
+    RootCertificates(PathBuf),
+    /// Accept any certificate. The connection is still encrypted, but it is not protected from an
+    /// attacker who can intercept it, so this should only be used on a trusted network.
+    Disabled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The options of an encrypted connection to an IBKR platform, which are passed to
+/// [`crate::client::Builder::with_tls`].
+pub struct TlsConfig {
+    verification: Verification,
+    server_name: Option<String>,
+}
+
+#[derive(Debug)]
+struct NoVerification(WebPkiSupportedAlgorithms);
+
+// === Type implementations ===
+
+impl TlsConfig {
+    #[inline]
+    #[must_use]
+    /// Create a new [`TlsConfig`].
+    ///
+    /// # Arguments
+    /// * `verification` - How to verify the certificate presented by the platform.
+    pub const fn new(verification: Verification) -> Self {
+        Self {
+            verification,
+            server_name: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the name against which the platform's certificate is verified. By default, the
+    /// certificate is verified against the IP address of the connection.
+    ///
+    /// # Arguments
+    /// * `server_name` - The DNS name of the platform's certificate.
+    pub fn with_server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.server_name = Some(server_name.into());
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return how the platform's certificate is verified.
+    pub const fn get_verification(&self) -> &Verification {
+        &self.verification
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the name against which the platform's certificate is verified, if one was set.
+    pub fn get_server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    fn client_config(&self) -> Result<ClientConfig, Error> {
+        let builder = ClientConfig::builder();
+        let builder = match &self.verification {
+            Verification::WebPkiRoots => {
+                let mut roots = RootCertStore::empty();
+                roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+                builder.with_root_certificates(roots)
+            }
+            Verification::RootCertificates(path) => {
+                let file = std::fs::File::open(path).map_err(|e| {
+                    Error::Config(format!(
+                        "Failed to open root certificates at {}: {e}",
+                        path.display()
+                    ))
+                })?;
+                let mut roots = RootCertStore::empty();
+                for cert in rustls_pemfile::certs(&mut BufReader::new(file)) {
+                    roots
+                        .add(cert?)
+                        .map_err(|e| Error::Config(format!("Invalid PEM root certificate: {e}")))?;
+                }
+                builder.with_root_certificates(roots)
+            }
+            Verification::Disabled => {
+                builder
+                    .dangerous()
+                    .with_custom_certificate_verifier(Arc::new(NoVerification(
+                        ring::default_provider().signature_verification_algorithms,
+                    )))
+            }
+        };
+        Ok(builder.with_no_client_auth())
+    }
+
+    /// Perform a TLS handshake over an open TCP connection.
+    pub(crate) async fn connect(
+        &self,
+        stream: TcpStream,
+        address: std::net::Ipv4Addr,
+    ) -> Result<TlsStream<TcpStream>, Error> {
+        let server_name = match &self.server_name {
+            Some(name) => ServerName::try_from(name.clone())
+                .map_err(|e| Error::Config(format!("Invalid TLS server name {name}: {e}")))?,
+            None => ServerName::from(std::net::IpAddr::V4(address)),
+        };
+        TlsConnector::from(Arc::new(self.client_config()?))
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| Error::Handshake(format!("TLS handshake failed: {e}")))
+    }
+}
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_schemes()
+    }
+}
@@ -0,0 +1,8 @@
+/// Emit a [`tracing`](https://docs.rs/tracing) event at the given level when the "tracing"
+/// feature is enabled. Otherwise, the event and its arguments are compiled out entirely.
+macro_rules! event {
+    ($level: ident, $($arg: tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+    };
+}
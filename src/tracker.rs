@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio::sync::mpsc;
+
+use crate::client::Shared;
+use crate::execution::{CommissionReport, Execution};
+use crate::order::Expiry;
+use crate::payload::{OpenOrder, OrderStatus, OrderStatusUpdate};
+use crate::quantity::Quantity;
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// A single event delivered by an [`OrderTracker`].
+pub enum OrderEvent {
+    /// The order's status or filled quantity changed (ex: PendingSubmit → Submitted → Filled).
+    Transition {
+        /// The order's previous status, or [`None`] if this is the first status received.
+        previous: Option<OrderStatus>,
+        /// The update that caused the transition.
+        update: OrderStatusUpdate,
+    },
+    /// The order was reported as open.
+    Open(OpenOrder),
+    /// The order was (partially) filled.
+    Fill(Execution),
+    /// The commission for one of the order's fills.
+    Commission(CommissionReport),
+    /// The order is due to expire soon (see
+    /// [`crate::client::Client::set_expiry_warning`]).
+    Expiring(Expiry),
+}
+
+#[derive(Debug)]
+/// A handle to an order placed by [`crate::client::Client::req_place_order_tracked`], which joins
+/// the order status, open order, execution, and commission messages for that order into a single
+/// [`futures_core::Stream`] of [`OrderEvent`]s.
+///
+/// Repeated status messages that do not change the order's status or filled quantity are not
+/// delivered. The messages are also delivered to the client's wrapper as usual. Dropping the
+/// tracker stops the delivery of its events, but it does not cancel the order.
+pub struct OrderTracker {
+    order_id: i64,
+    rx: mpsc::UnboundedReceiver<OrderEvent>,
+    shared: Arc<Shared>,
+    status: Option<OrderStatus>,
+    filled: Quantity,
+}
+
+#[derive(Debug, Default)]
+/// The trackers that are registered with a client, along with the executions of the tracked
+/// orders, with which commission reports are matched to their order.
+pub(crate) struct Registry {
+    orders: HashMap<i64, Entry>,
+    executions: HashMap<String, i64>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    tx: mpsc::UnboundedSender<OrderEvent>,
+    status: Option<OrderStatus>,
+    filled: Quantity,
+}
+
+// === Type implementations ===
+
+impl OrderTracker {
+    #[inline]
+    pub(crate) fn register(order_id: i64, shared: &Arc<Shared>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        lock_registry(shared).orders.insert(
+            order_id,
+            Entry {
+                tx,
+                status: None,
+                filled: Quantity::ZERO,
+            },
+        );
+        Self {
+            order_id,
+            rx,
+            shared: Arc::clone(shared),
+            status: None,
+            filled: Quantity::ZERO,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the ID of the tracked order.
+    pub const fn get_order_id(&self) -> i64 {
+        self.order_id
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the order's most recent status, as of the last event received from the tracker.
+    pub const fn get_status(&self) -> Option<OrderStatus> {
+        self.status
+    }
+
+    #[inline]
+    #[must_use]
+    /// Get the order's filled quantity, as of the last event received from the tracker.
+    pub const fn get_filled(&self) -> Quantity {
+        self.filled
+    }
+
+    #[inline]
+    /// Wait for the next event.
+    ///
+    /// # Returns
+    /// The next event, or [`None`] if the client loop stopped.
+    pub async fn recv(&mut self) -> Option<OrderEvent> {
+        let event = self.rx.recv().await;
+        self.observe(event.as_ref());
+        event
+    }
+
+    /// Wait until the order is done, such as when it is filled or cancelled, discarding any
+    /// intermediate events.
+    ///
+    /// # Returns
+    /// The order's final status, or [`None`] if the client loop stopped first.
+    pub async fn done(&mut self) -> Option<OrderStatus> {
+        while !self.status.is_some_and(OrderStatus::is_done) {
+            self.recv().await?;
+        }
+        self.status
+    }
+
+    #[inline]
+    fn observe(&mut self, event: Option<&OrderEvent>) {
+        if let Some(OrderEvent::Transition { update, .. }) = event {
+            self.status = Some(update.status);
+            self.filled = update.filled;
+        }
+    }
+}
+
+impl futures_core::Stream for OrderTracker {
+    type Item = OrderEvent;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.rx.poll_recv(cx);
+        if let Poll::Ready(event) = &poll {
+            self.observe(event.as_ref());
+        }
+        poll
+    }
+}
+
+impl Drop for OrderTracker {
+    fn drop(&mut self) {
+        let mut registry = lock_registry(&self.shared);
+        registry.orders.remove(&self.order_id);
+        let order_id = self.order_id;
+        registry.executions.retain(|_, id| *id != order_id);
+    }
+}
+
+impl Registry {
+    #[inline]
+    fn send(&mut self, order_id: i64, event: OrderEvent) {
+        if let Some(entry) = self.orders.get(&order_id) {
+            if entry.tx.send(event).is_err() {
+                self.orders.remove(&order_id);
+            }
+        }
+    }
+}
+
+// === Helper functions ===
+
+#[inline]
+fn lock_registry(shared: &Shared) -> std::sync::MutexGuard<'_, Registry> {
+    shared
+        .order_trackers
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Close every tracker once the client loop stops, so that waiting for its events ends instead of
+/// hanging.
+pub(crate) fn close_all(shared: &Shared) {
+    let mut registry = lock_registry(shared);
+    registry.orders.clear();
+    registry.executions.clear();
+}
+
+/// Deliver an order status update to the tracker of its order, if it has one and the update
+/// changes the order's status or filled quantity.
+pub(crate) fn publish_status(shared: &Shared, update: &OrderStatusUpdate) {
+    let mut registry = lock_registry(shared);
+    let Some(entry) = registry.orders.get_mut(&update.order_id) else {
+        return;
+    };
+    if entry.status == Some(update.status) && entry.filled == update.filled {
+        return;
+    }
+    let previous = entry.status.replace(update.status);
+    entry.filled = update.filled;
+    registry.send(
+        update.order_id,
+        OrderEvent::Transition {
+            previous,
+            update: update.clone(),
+        },
+    );
+}
+
+#[inline]
+/// Deliver an open order to the tracker of its order, if it has one.
+pub(crate) fn publish_open_order(shared: &Shared, order: OpenOrder) {
+    lock_registry(shared).send(order.order_id, OrderEvent::Open(order));
+}
+
+/// Deliver an execution to the tracker of its order, if it has one, and remember the execution so
+/// that its commission report can be matched with the order.
+pub(crate) fn publish_execution(shared: &Shared, execution: &Execution) {
+    let mut registry = lock_registry(shared);
+    if registry.orders.contains_key(&execution.order_id) {
+        registry
+            .executions
+            .insert(execution.execution_id.clone(), execution.order_id);
+        registry.send(execution.order_id, OrderEvent::Fill(execution.clone()));
+    }
+}
+
+#[inline]
+/// Deliver a warning that an order is due to expire to the tracker of that order, if it has one.
+pub(crate) fn publish_expiring(shared: &Shared, order_id: i64, expiry: Expiry) {
+    lock_registry(shared).send(order_id, OrderEvent::Expiring(expiry));
+}
+
+/// Deliver a commission report to the tracker of the order whose execution it belongs to, if it
+/// has one.
+pub(crate) fn publish_commission(shared: &Shared, report: &CommissionReport) {
+    let mut registry = lock_registry(shared);
+    if let Some(order_id) = registry.executions.remove(&report.execution_id) {
+        registry.send(order_id, OrderEvent::Commission(report.clone()));
+    }
+}
@@ -0,0 +1,590 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use tokio::sync::mpsc;
+
+use crate::client::{self, indicators::Active, Client};
+use crate::contract::{Contract, ContractId, Security, UnexpectedSecurityType};
+use crate::error::Error;
+use crate::market_data::{live_bar, live_data};
+use crate::payload::{market_depth::Operation, Bar};
+use crate::scanner::{Row, Subscription};
+use crate::tick::{Class, Price, Size};
+
+// === Type definitions ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The type of data used to construct the real-time bars of a [`Profile`].
+pub enum BarData {
+    /// The actual trades for a given 5-second interval.
+    Trades,
+    /// The posted midpoint prices for a given 5-second interval.
+    Midpoint,
+    /// The posted bid prices for a given 5-second interval.
+    Bid,
+    /// The posted ask prices for a given 5-second interval.
+    Ask,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The data to which a [`Watchlist`] subscribes for each of its securities.
+pub struct Profile {
+    /// When [`true`], subscribe to streaming price and size ticks with
+    /// [`crate::client::Client::req_market_data`].
+    pub ticks: bool,
+    /// If [`Some`], subscribe to real-time bars with
+    /// [`crate::client::Client::req_real_time_bars`].
+    pub bars: Option<BarData>,
+    /// If [`Some`], subscribe to the given number of rows of market depth with
+    /// [`crate::client::Client::req_market_depth`].
+    pub depth: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// A single piece of market data for one of the securities in a [`Watchlist`].
+pub enum Update {
+    /// A price tick.
+    Price(Class<Price>),
+    /// A size tick.
+    Size(Class<Size>),
+    /// A real-time bar.
+    Bar(Bar),
+    /// A change to the order book.
+    Depth(Operation),
+}
+
+#[derive(Debug, Clone)]
+/// A cheap, cloneable handle that forwards market data callbacks to the merged stream of a
+/// [`Watchlist`].
+///
+/// Hold a [`Router`] in a [`crate::wrapper::Local`] or [`crate::wrapper::Remote`] implementation
+/// and call [`Router::forward`] from the relevant callbacks.
+pub struct Router {
+    routes: Arc<RwLock<HashMap<i64, ContractId>>>,
+    tx: mpsc::UnboundedSender<(ContractId, Update)>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Line {
+    ticks: Option<i64>,
+    bars: Option<i64>,
+    depth: Option<i64>,
+}
+
+#[derive(Debug)]
+/// Manages the market data subscriptions for a set of securities and merges the resulting data
+/// into a single stream keyed by [`ContractId`].
+///
+/// Each security consumes one market data line per subscription in the [`Profile`]. Securities are
+/// only added while the total number of lines stays within the watchlist's line budget.
+pub struct Watchlist {
+    profile: Profile,
+    line_budget: usize,
+    contracts: HashMap<ContractId, (Contract, Line)>,
+    router: Router,
+}
+
+#[derive(Debug)]
+/// A [`Watchlist`] that follows the top-ranked results of a market scanner subscription.
+///
+/// Each time the scanner's results are received in [`crate::wrapper::Local::scanner_data`] (or
+/// [`crate::wrapper::Remote::scanner_data`]), pass them to [`ScannerWatchlist::update`], which
+/// subscribes to the securities that entered the top results and unsubscribes from those that
+/// left them.
+pub struct ScannerWatchlist {
+    req_id: i64,
+    top: usize,
+    watchlist: Watchlist,
+}
+
+#[derive(Debug)]
+/// An error returned when a [`Watchlist`] is unable to subscribe to data for a security.
+pub enum WatchlistError {
+    /// Subscribing would exceed the watchlist's line budget.
+    BudgetExceeded {
+        /// The number of lines required by the security.
+        required: usize,
+        /// The number of lines remaining in the budget.
+        remaining: usize,
+    },
+    /// An error was encountered while writing an outgoing message or creating the contract of a
+    /// security.
+    Client(Error),
+}
+
+// === Type implementations ===
+
+impl Profile {
+    #[inline]
+    #[must_use]
+    /// Return the number of market data lines that the profile consumes for each security.
+    pub const fn lines(&self) -> usize {
+        self.ticks as usize + self.bars.is_some() as usize + self.depth.is_some() as usize
+    }
+}
+
+impl Router {
+    #[must_use]
+    /// Forward a piece of market data to the watchlist's merged stream.
+    ///
+    /// # Arguments
+    /// * `req_id` - The request ID with which the data was received.
+    /// * `update` - The data to forward.
+    ///
+    /// # Returns
+    /// [`true`] if `req_id` belongs to the watchlist and the data was forwarded; otherwise,
+    /// [`false`].
+    pub fn forward(&self, req_id: i64, update: Update) -> bool {
+        let contract_id = self
+            .routes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&req_id)
+            .copied();
+        contract_id.is_some_and(|contract_id| self.tx.send((contract_id, update)).is_ok())
+    }
+
+    #[must_use]
+    /// Return the contract associated with a given request ID.
+    ///
+    /// # Arguments
+    /// * `req_id` - The request ID to look up.
+    ///
+    /// # Returns
+    /// The [`ContractId`] of the security to which the request belongs, if it belongs to the
+    /// watchlist.
+    pub fn get_contract_id(&self, req_id: i64) -> Option<ContractId> {
+        self.routes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&req_id)
+            .copied()
+    }
+
+    fn insert(&self, line: &Line, contract_id: ContractId) {
+        let mut routes = self.routes.write().unwrap_or_else(PoisonError::into_inner);
+        for req_id in [line.ticks, line.bars, line.depth].into_iter().flatten() {
+            routes.insert(req_id, contract_id);
+        }
+    }
+
+    fn remove(&self, line: &Line) {
+        let mut routes = self.routes.write().unwrap_or_else(PoisonError::into_inner);
+        for req_id in [line.ticks, line.bars, line.depth].into_iter().flatten() {
+            routes.remove(&req_id);
+        }
+    }
+}
+
+impl Watchlist {
+    #[must_use]
+    /// Create a new, empty watchlist.
+    ///
+    /// # Arguments
+    /// * `profile` - The data to which to subscribe for each security.
+    /// * `line_budget` - The maximum number of market data lines that the watchlist may consume.
+    ///
+    /// # Returns
+    /// The watchlist and the receiving end of its merged stream of market data.
+    pub fn new(
+        profile: Profile,
+        line_budget: usize,
+    ) -> (Self, mpsc::UnboundedReceiver<(ContractId, Update)>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (
+            Self {
+                profile,
+                line_budget,
+                contracts: HashMap::new(),
+                router: Router {
+                    routes: Arc::new(RwLock::new(HashMap::new())),
+                    tx,
+                },
+            },
+            rx,
+        )
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return a [`Router`] that forwards market data callbacks to the watchlist's merged stream.
+    pub fn get_router(&self) -> Router {
+        self.router.clone()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the watchlist's data profile.
+    pub const fn get_profile(&self) -> Profile {
+        self.profile
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the number of market data lines remaining in the watchlist's budget.
+    pub fn get_remaining_lines(&self) -> usize {
+        self.line_budget
+            .saturating_sub(self.contracts.len() * self.profile.lines())
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the contract IDs of the securities in the watchlist.
+    pub fn get_contract_ids(&self) -> Vec<ContractId> {
+        self.contracts.keys().copied().collect()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return whether a given security is in the watchlist.
+    pub fn contains(&self, contract_id: ContractId) -> bool {
+        self.contracts.contains_key(&contract_id)
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the number of securities in the watchlist.
+    pub fn len(&self) -> usize {
+        self.contracts.len()
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return whether the watchlist is empty.
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty()
+    }
+
+    /// Add a security to the watchlist and subscribe to its data. Adding a security that is
+    /// already in the watchlist does nothing.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to make the subscriptions.
+    /// * `contract` - The security to add.
+    ///
+    /// # Errors
+    /// Returns [`WatchlistError::BudgetExceeded`] if the security's subscriptions would exceed the
+    /// line budget. Returns [`WatchlistError::Client`] if any error is encountered while writing the
+    /// outgoing messages.
+    pub async fn add<P: client::Profile, E: client::Environment>(
+        &mut self,
+        client: &mut Client<Active<P, E>>,
+        contract: Contract,
+    ) -> Result<(), WatchlistError> {
+        let contract_id = contract.get_contract_id();
+        if self.contracts.contains_key(&contract_id) {
+            return Ok(());
+        }
+        let (required, remaining) = (self.profile.lines(), self.get_remaining_lines());
+        if required > remaining {
+            return Err(WatchlistError::BudgetExceeded {
+                required,
+                remaining,
+            });
+        }
+        let line = subscribe_contract(client, &contract, self.profile).await?;
+        self.router.insert(&line, contract_id);
+        self.contracts.insert(contract_id, (contract, line));
+        Ok(())
+    }
+
+    /// Remove a security from the watchlist and cancel its subscriptions.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to cancel the subscriptions.
+    /// * `contract_id` - The ID of the security to remove.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    ///
+    /// # Returns
+    /// The removed security, if it was in the watchlist.
+    pub async fn remove<P: client::Profile, E: client::Environment>(
+        &mut self,
+        client: &mut Client<Active<P, E>>,
+        contract_id: ContractId,
+    ) -> Result<Option<Contract>, Error> {
+        let Some((contract, line)) = self.contracts.remove(&contract_id) else {
+            return Ok(None);
+        };
+        self.router.remove(&line);
+        unsubscribe(client, &line).await?;
+        Ok(Some(contract))
+    }
+
+    /// Remove every security from the watchlist and cancel all of its subscriptions.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to cancel the subscriptions.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    pub async fn clear<P: client::Profile, E: client::Environment>(
+        &mut self,
+        client: &mut Client<Active<P, E>>,
+    ) -> Result<(), Error> {
+        for (_, (_, line)) in self.contracts.drain() {
+            self.router.remove(&line);
+            unsubscribe(client, &line).await?;
+        }
+        Ok(())
+    }
+
+    /// Subscribe to the data for every security in the watchlist again. This should be called
+    /// after a client reconnects, as subscriptions do not survive a dropped connection.
+    ///
+    /// # Arguments
+    /// * `client` - The newly-connected client with which to make the subscriptions.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    pub async fn resubscribe<P: client::Profile, E: client::Environment>(
+        &mut self,
+        client: &mut Client<Active<P, E>>,
+    ) -> Result<(), Error> {
+        for (contract_id, (contract, line)) in &mut self.contracts {
+            self.router.remove(line);
+            *line = subscribe_contract(client, contract, self.profile).await?;
+            self.router.insert(line, *contract_id);
+        }
+        Ok(())
+    }
+}
+
+impl ScannerWatchlist {
+    /// Subscribe to a market scanner and create a watchlist that follows its top results.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to make the subscriptions.
+    /// * `subscription` - The parameters of the scan.
+    /// * `top` - The number of top-ranked results to follow.
+    /// * `profile` - The data to which to subscribe for each result.
+    /// * `line_budget` - The maximum number of market data lines that the watchlist may consume.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing message.
+    ///
+    /// # Returns
+    /// The scanner watchlist and the receiving end of its merged stream of market data.
+    pub async fn new<P: client::Profile, E: client::Environment>(
+        client: &mut Client<Active<P, E>>,
+        subscription: &Subscription,
+        top: usize,
+        profile: Profile,
+        line_budget: usize,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<(ContractId, Update)>), Error> {
+        let req_id = client.req_scanner_subscription(subscription).await?;
+        let (watchlist, rx) = Watchlist::new(profile, line_budget);
+        Ok((
+            Self {
+                req_id,
+                top,
+                watchlist,
+            },
+            rx,
+        ))
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the ID of the scanner subscription.
+    pub const fn get_req_id(&self) -> i64 {
+        self.req_id
+    }
+
+    #[inline]
+    #[must_use]
+    /// Return the underlying watchlist.
+    pub const fn get_watchlist(&self) -> &Watchlist {
+        &self.watchlist
+    }
+
+    /// Update the watchlist with the latest results of the scan. Results that belong to a different
+    /// request are ignored.
+    ///
+    /// Securities that are no longer among the top results are removed before new ones are added,
+    /// and new securities are added in order of rank until the watchlist's line budget is
+    /// exhausted.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to make the subscriptions.
+    /// * `req_id` - The request ID with which the results were received.
+    /// * `rows` - The results of the scan.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages or creating the contracts
+    /// of the new securities.
+    pub async fn update<P: client::Profile, E: client::Environment>(
+        &mut self,
+        client: &mut Client<Active<P, E>>,
+        req_id: i64,
+        rows: &[Row],
+    ) -> Result<(), WatchlistError> {
+        if req_id != self.req_id {
+            return Ok(());
+        }
+        let mut ranked = rows.iter().collect::<Vec<_>>();
+        ranked.sort_by_key(|row| row.rank);
+        let top = ranked
+            .into_iter()
+            .map(|row| row.contract_id)
+            .take(self.top)
+            .collect::<Vec<_>>();
+
+        for contract_id in self.watchlist.get_contract_ids() {
+            if !top.contains(&contract_id) {
+                self.watchlist.remove(client, contract_id).await?;
+            }
+        }
+        for contract_id in top {
+            if self.watchlist.contains(contract_id) {
+                continue;
+            }
+            if self.watchlist.get_remaining_lines() < self.watchlist.profile.lines() {
+                break;
+            }
+            let contract = client.resolve_contract(contract_id).await?;
+            self.watchlist.add(client, contract).await?;
+        }
+        Ok(())
+    }
+
+    /// Cancel the scanner subscription and all of the watchlist's subscriptions.
+    ///
+    /// # Arguments
+    /// * `client` - The client with which to cancel the subscriptions.
+    ///
+    /// # Errors
+    /// Returns any error encountered while writing the outgoing messages.
+    pub async fn cancel<P: client::Profile, E: client::Environment>(
+        mut self,
+        client: &mut Client<Active<P, E>>,
+    ) -> Result<(), Error> {
+        client.cancel_scanner_subscription(self.req_id).await?;
+        self.watchlist.clear(client).await
+    }
+}
+
+impl From<Error> for WatchlistError {
+    fn from(value: Error) -> Self {
+        Self::Client(value)
+    }
+}
+
+impl std::fmt::Display for WatchlistError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BudgetExceeded {
+                required,
+                remaining,
+            } => write!(
+                f,
+                "Watchlist line budget exceeded: {required} lines required, {remaining} remaining"
+            ),
+            Self::Client(e) => write!(f, "Watchlist client error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for WatchlistError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::BudgetExceeded { .. } => None,
+            Self::Client(e) => Some(e),
+        }
+    }
+}
+
+// === Subscription helpers ===
+
+async fn subscribe_contract<P: client::Profile, E: client::Environment>(
+    client: &mut Client<Active<P, E>>,
+    contract: &Contract,
+    profile: Profile,
+) -> Result<Line, Error> {
+    match contract {
+        Contract::Forex(fx) => subscribe(client, fx, profile).await,
+        Contract::Crypto(crypto) => subscribe(client, crypto, profile).await,
+        Contract::Stock(stk) => subscribe(client, stk, profile).await,
+        Contract::Index(ind) => subscribe(client, ind, profile).await,
+        Contract::SecFuture(fut) => subscribe(client, fut, profile).await,
+        Contract::SecOption(opt) => subscribe(client, opt, profile).await,
+        Contract::Commodity(cmdty) => subscribe(client, cmdty, profile).await,
+        Contract::Combo(_) => Err(Error::UnexpectedSecurityType(Box::new(
+            UnexpectedSecurityType("Combo contracts cannot be added to a watchlist"),
+        ))),
+    }
+}
+
+async fn subscribe<S, P: client::Profile, E: client::Environment>(
+    client: &mut Client<Active<P, E>>,
+    security: &S,
+    profile: Profile,
+) -> Result<Line, Error>
+where
+    S: Security,
+    live_data::data_types::Empty: live_data::data_types::DataType<S>,
+    live_bar::data_types::Trades: live_bar::data_types::DataType<S>,
+    live_bar::data_types::Midpoint: live_bar::data_types::DataType<S>,
+    live_bar::data_types::Bid: live_bar::data_types::DataType<S>,
+    live_bar::data_types::Ask: live_bar::data_types::DataType<S>,
+{
+    let ticks = if profile.ticks {
+        Some(
+            client
+                .req_market_data(
+                    security,
+                    vec![live_data::data_types::Empty],
+                    live_data::RefreshType::Streaming,
+                    false,
+                )
+                .await?,
+        )
+    } else {
+        None
+    };
+    let bars = match profile.bars {
+        Some(BarData::Trades) => Some(
+            client
+                .req_real_time_bars(security, live_bar::data_types::Trades, false)
+                .await?,
+        ),
+        Some(BarData::Midpoint) => Some(
+            client
+                .req_real_time_bars(security, live_bar::data_types::Midpoint, false)
+                .await?,
+        ),
+        Some(BarData::Bid) => Some(
+            client
+                .req_real_time_bars(security, live_bar::data_types::Bid, false)
+                .await?,
+        ),
+        Some(BarData::Ask) => Some(
+            client
+                .req_real_time_bars(security, live_bar::data_types::Ask, false)
+                .await?,
+        ),
+        None => None,
+    };
+    let depth = match profile.depth {
+        Some(rows) => Some(client.req_market_depth(security, rows).await?),
+        None => None,
+    };
+    Ok(Line { ticks, bars, depth })
+}
+
+async fn unsubscribe<P: client::Profile, E: client::Environment>(
+    client: &mut Client<Active<P, E>>,
+    line: &Line,
+) -> Result<(), Error> {
+    if let Some(req_id) = line.ticks {
+        client.cancel_market_data(req_id).await?;
+    }
+    if let Some(req_id) = line.bars {
+        client.cancel_real_time_bars(req_id).await?;
+    }
+    if let Some(req_id) = line.depth {
+        client.cancel_market_depth(req_id).await?;
+    }
+    Ok(())
+}
@@ -1,11 +1,13 @@
-use crate::account::{Attribute, TagValue};
-use crate::client::ActiveClient;
+use crate::account::{AccountId, Attribute, TagValue};
+use crate::client::{indicators::Active, Client, Profile, Trading};
+use crate::execution::{CommissionReport, Execution, ResolvedExecution};
 use crate::payload::{self, Bar, ExchangeId, HistogramEntry, Pnl, Position, PositionSummary, Tick};
+use crate::scanner;
 use crate::tick::{
     self, Accessibility, AuctionData, Class, Dividends, ExtremeValue, Ipo, MarkPrice, News,
     OpenInterest, Price, PriceFactor, QuotingExchanges, Rate, RealTimeVolume,
-    SecOptionCalculationSource, SecOptionVolume, Size, SummaryVolume, TimeStamp, TradeCount,
-    Volatility, Volume, Yield,
+    SecOptionCalculationSource, SecOptionVolume, Size, SummaryVolume, TickAttributes, TickType,
+    TimeStamp, TradeCount, Volatility, Volume, Yield,
 };
 use chrono::{NaiveDateTime, NaiveTime};
 use ibapi_macros::debug_trait;
@@ -34,7 +36,13 @@ pub trait Local<'c> {
     /// The callback message that corresponds to ETF Net Asset Value (NAV) data.
     fn etf_nav(&mut self, req_id: i64, nav: tick::EtfNav) -> impl std::future::Future {}
     /// The callback message that corresponds to price data from [`crate::client::Client::req_market_data`].
-    fn price_data(&mut self, req_id: i64, price: Class<Price>) -> impl std::future::Future {}
+    fn price_data(
+        &mut self,
+        req_id: i64,
+        price: Class<Price>,
+        attributes: TickAttributes,
+    ) -> impl std::future::Future {
+    }
     /// The callback message that corresponds to size data from [`crate::client::Client::req_market_data`].
     fn size_data(&mut self, req_id: i64, size: Class<Size>) -> impl std::future::Future {}
     /// The callback message that corresponds to the price (in yield terms) data from [`crate::client::Client::req_market_data`].
@@ -151,7 +159,7 @@ pub trait Local<'c> {
     fn account_attribute(
         &mut self,
         attribute: Attribute,
-        account_number: String,
+        account_number: AccountId,
     ) -> impl std::future::Future {
     }
     /// The callback message containing information about a single [`Position`] from [`crate::client::Client::req_positions`].
@@ -172,12 +180,12 @@ pub trait Local<'c> {
     ) -> impl std::future::Future {
     }
     /// The callback message indicating that all the information for a given account has been received.
-    fn account_download_end(&mut self, account_number: String) -> impl std::future::Future {}
+    fn account_download_end(&mut self, account_number: AccountId) -> impl std::future::Future {}
     /// The callback message associated with account summary information from [`crate::client::Client::req_account_summary`].
     fn account_summary(
         &mut self,
         req_id: i64,
-        account_number: String,
+        account_number: AccountId,
         summary: TagValue,
     ) -> impl std::future::Future {
     }
@@ -191,16 +199,164 @@ pub trait Local<'c> {
     fn open_order_end(&mut self) -> impl std::future::Future {}
     /// The callback message that contains live bar data from [`crate::client::Client::req_real_time_bars`].
     fn real_time_bar(&mut self, req_id: i64, bar: Bar) -> impl std::future::Future {}
+    /// The callback message containing information about a single [`PositionSummary`] from [`crate::client::Client::req_positions_multi`] or [`crate::client::Client::req_positions_all_accounts`], along with the model code to which it belongs (which is empty for an account as a whole).
+    fn position_multi(
+        &mut self,
+        req_id: i64,
+        position: PositionSummary,
+        model_code: String,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message indicating that all the position information for a given request from [`crate::client::Client::req_positions_multi`] or [`crate::client::Client::req_positions_all_accounts`] has been received.
+    fn position_multi_end(&mut self, req_id: i64) -> impl std::future::Future {}
+    /// The callback message containing a single [`Execution`] from [`crate::client::Client::req_executions`], or sent when an order is filled.
+    fn execution(&mut self, req_id: i64, execution: Execution) -> impl std::future::Future {}
+    /// The callback message indicating that all the executions for a given request from [`crate::client::Client::req_executions`] have been received.
+    fn execution_end(&mut self, req_id: i64) -> impl std::future::Future {}
+    /// The callback message containing a single [`ResolvedExecution`], which is delivered instead of an [`Execution`] when [`crate::client::Client::set_execution_resolution`] is enabled.
+    fn resolved_execution(
+        &mut self,
+        req_id: i64,
+        execution: ResolvedExecution,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing the [`CommissionReport`] associated with a single [`Execution`].
+    fn commission_report(&mut self, report: CommissionReport) -> impl std::future::Future {}
+    /// The callback message containing the ranked results of a market scanner subscription from [`crate::client::Client::req_scanner_subscription`].
+    fn scanner_data(&mut self, req_id: i64, rows: Vec<scanner::Row>) -> impl std::future::Future {}
+    /// The callback message that corresponds to market data whose [`TickType`] has no dedicated callback, along with its raw value.
+    fn other_tick(
+        &mut self,
+        req_id: i64,
+        tick_type: TickType,
+        value: String,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing a single [`payload::OpenOrder`] from [`crate::client::Client::req_open_orders`], [`crate::client::Client::req_all_open_orders`], or [`crate::client::Client::req_auto_open_orders`].
+    fn open_order(&mut self, order: payload::OpenOrder) -> impl std::future::Future {}
+    /// The callback message indicating that bars were dropped from a [`crate::client::Client::req_real_time_bars`] subscription within a trading session. It is sent just before the first bar after the gap.
+    fn missed_bars(
+        &mut self,
+        req_id: i64,
+        missed: payload::MissedBars,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing an error encountered while decoding a message from IBKR, such as a malformed or unrecognized message. The message is discarded.
+    fn decode_error(&mut self, error: crate::error::Error) -> impl std::future::Future {}
+    /// The callback message containing the white branding ID of the user from [`crate::client::Client::req_user_info`].
+    fn user_info(&mut self, req_id: i64, white_branding_id: String) -> impl std::future::Future {}
+    /// The callback message containing the exchanges that comprise the SMART aggregate exchange from [`crate::client::Client::req_smart_components`].
+    fn smart_components(
+        &mut self,
+        req_id: i64,
+        components: Vec<payload::SmartComponent>,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message indicating that the connection with the IBKR platform was lost unexpectedly, such as when the platform restarts or the task that reads incoming messages fails. No more messages are received until the client reconnects with [`crate::client::Client::reconnect`].
+    fn connection_lost(&mut self) -> impl std::future::Future {}
+    /// The callback message indicating that the client is connected with the IBKR platform, which is sent once the message loop starts and again whenever the client reconnects with [`crate::client::Client::reconnect`].
+    fn connected(&mut self) -> impl std::future::Future {}
+    /// The callback message indicating that the client closed its connection with the IBKR platform with [`crate::client::Client::disconnect`]. This is the last message that the wrapper receives.
+    fn connection_closed(&mut self) -> impl std::future::Future {}
+    /// The callback message indicating that the IBKR platform lost its connection to IBKR's servers and is attempting to reconnect (error 1100). Trading is not possible until [`Self::reconnected`] is received.
+    fn reconnecting(&mut self) -> impl std::future::Future {}
+    /// The callback message indicating that the IBKR platform restored its connection to IBKR's servers (errors 1101 and 1102). When `data_lost` is [`true`], market data was lost and its subscriptions must be made again.
+    fn reconnected(&mut self, data_lost: bool) -> impl std::future::Future {}
+    /// The callback message containing an [`payload::OrderStatusUpdate`] for an order placed by [`crate::client::Client::req_place_order`], sent whenever the order's status or filled quantity changes.
+    fn order_status(&mut self, status: payload::OrderStatusUpdate) -> impl std::future::Future {}
+    /// The callback message containing a timer scheduled with [`crate::client::Client::schedule`] that has fallen due, along with the time at which it fell due. It runs on the same task as every other callback.
+    fn timer(
+        &mut self,
+        timer_id: crate::schedule::TimerId,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing the delta-neutral contract that IBKR validated for a delta-neutral combo order, which may differ from the one with which the order was placed.
+    fn delta_neutral_validation(
+        &mut self,
+        order_id: i64,
+        contract: crate::contract::DeltaNeutralContract,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing the contracts that match a pattern from [`crate::client::Client::req_matching_symbols`].
+    fn symbol_samples(
+        &mut self,
+        req_id: i64,
+        samples: Vec<payload::SymbolSample>,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing the XML document of a report from [`crate::client::Client::req_fundamental_data`].
+    fn fundamental_data(&mut self, req_id: i64, data: String) -> impl std::future::Future {}
+    /// The callback message containing an IB news bulletin from [`crate::client::Client::req_news_bulletins`].
+    fn news_bulletin(&mut self, bulletin: payload::NewsBulletin) -> impl std::future::Future {}
+    /// The callback message containing the body of a news article from [`crate::client::Client::req_news_article`].
+    fn news_article(
+        &mut self,
+        req_id: i64,
+        article: payload::NewsArticle,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing a news headline from a [`crate::client::Client::req_market_data`] subscription to news ticks.
+    fn tick_news(
+        &mut self,
+        req_id: i64,
+        headline: payload::NewsHeadline,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing the news providers from [`crate::client::Client::req_news_providers`].
+    fn news_providers(
+        &mut self,
+        providers: Vec<payload::NewsProvider>,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing a single headline from [`crate::client::Client::req_historical_news`].
+    fn historical_news(
+        &mut self,
+        req_id: i64,
+        headline: payload::NewsHeadline,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message indicating that all headlines from [`crate::client::Client::req_historical_news`] have been delivered, and whether more headlines matched the request.
+    fn historical_news_end(&mut self, req_id: i64, has_more: bool) -> impl std::future::Future {}
+    /// The callback message containing account attributes from [`crate::client::Client::req_account_updates_multi`], along with the model code to which they belong (which is empty for an account as a whole).
+    fn account_update_multi(
+        &mut self,
+        req_id: i64,
+        attribute: Attribute,
+        account_number: AccountId,
+        model_code: String,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message indicating that all the account attributes for a given request from [`crate::client::Client::req_account_updates_multi`] have been received.
+    fn account_update_multi_end(&mut self, req_id: i64) -> impl std::future::Future {}
+    /// The callback message containing the IDs of the display groups in TWS, from [`crate::client::Client::query_display_groups`].
+    fn display_group_list(&mut self, req_id: i64, groups: Vec<i64>) -> impl std::future::Future {}
+    /// The callback message containing the contract now displayed by a TWS display group (ex: "8314@SMART", or "none"), from [`crate::client::Client::subscribe_to_group_events`].
+    fn display_group_updated(
+        &mut self,
+        req_id: i64,
+        contract_info: String,
+    ) -> impl std::future::Future {
+    }
+    /// The callback message containing the trading sessions of a contract from [`crate::client::Client::req_historical_schedule`].
+    fn historical_schedule(
+        &mut self,
+        req_id: i64,
+        schedule: payload::HistoricalSchedule,
+    ) -> impl std::future::Future {
+    }
 }
 
-/// An initializer for a new [`Local`] wrapper.
-pub trait Initializer<'c> {
+/// An initializer for a new [`Local`] wrapper. The profile `P` determines the kind of client
+/// with which the wrapper is built: a [`crate::client::ActiveClient`] by default, or a
+/// [`crate::client::DataClient`] when it is [`crate::client::DataOnly`].
+pub trait Initializer<'c, P: Profile = Trading> {
     /// The Wrapper
     type Wrap: Local<'c>;
     /// The method to build the wrapper
     fn build(
         self,
-        client: &'c mut ActiveClient,
+        client: &'c mut Client<Active<P>>,
         cancel_loop: CancelToken,
     ) -> impl std::future::Future<Output = Self::Wrap>;
 }
@@ -226,7 +382,13 @@ pub trait Remote: Send + Sync {
     /// The callback message that corresponds to ETF Net Asset Value (NAV) data.
     fn etf_nav(&mut self, req_id: i64, nav: tick::EtfNav) -> impl std::future::Future + Send {}
     /// The callback message that corresponds to price data from [`crate::client::Client::req_market_data`].
-    fn price_data(&mut self, req_id: i64, price: Class<Price>) -> impl std::future::Future + Send {}
+    fn price_data(
+        &mut self,
+        req_id: i64,
+        price: Class<Price>,
+        attributes: TickAttributes,
+    ) -> impl std::future::Future + Send {
+    }
     /// The callback message that corresponds to size data from [`crate::client::Client::req_market_data`].
     fn size_data(&mut self, req_id: i64, size: Class<Size>) -> impl std::future::Future + Send {}
     /// The callback message that corresponds to the price (in yield terms) data from [`crate::client::Client::req_market_data`].
@@ -383,7 +545,7 @@ pub trait Remote: Send + Sync {
     fn account_attribute(
         &mut self,
         attribute: Attribute,
-        account_number: String,
+        account_number: AccountId,
     ) -> impl std::future::Future + Send {
     }
     /// The callback message containing information about a single [`Position`] from [`crate::client::Client::req_positions`].
@@ -404,12 +566,16 @@ pub trait Remote: Send + Sync {
     ) -> impl std::future::Future + Send {
     }
     /// The callback message indicating that all the information for a given account has been received.
-    fn account_download_end(&mut self, account_number: String) -> impl std::future::Future + Send {}
+    fn account_download_end(
+        &mut self,
+        account_number: AccountId,
+    ) -> impl std::future::Future + Send {
+    }
     /// The callback message associated with account summary information from [`crate::client::Client::req_account_summary`].
     fn account_summary(
         &mut self,
         req_id: i64,
-        account_number: String,
+        account_number: AccountId,
         summary: TagValue,
     ) -> impl std::future::Future + Send {
     }
@@ -423,6 +589,180 @@ pub trait Remote: Send + Sync {
     fn open_order_end(&mut self) -> impl std::future::Future + Send {}
     /// The callback message that contains live bar data from [`crate::client::Client::req_real_time_bars`].
     fn real_time_bar(&mut self, req_id: i64, bar: Bar) -> impl std::future::Future + Send {}
+    /// The callback message containing information about a single [`PositionSummary`] from [`crate::client::Client::req_positions_multi`] or [`crate::client::Client::req_positions_all_accounts`], along with the model code to which it belongs (which is empty for an account as a whole).
+    fn position_multi(
+        &mut self,
+        req_id: i64,
+        position: PositionSummary,
+        model_code: String,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message indicating that all the position information for a given request from [`crate::client::Client::req_positions_multi`] or [`crate::client::Client::req_positions_all_accounts`] has been received.
+    fn position_multi_end(&mut self, req_id: i64) -> impl std::future::Future + Send {}
+    /// The callback message containing a single [`Execution`] from [`crate::client::Client::req_executions`], or sent when an order is filled.
+    fn execution(&mut self, req_id: i64, execution: Execution) -> impl std::future::Future + Send {}
+    /// The callback message indicating that all the executions for a given request from [`crate::client::Client::req_executions`] have been received.
+    fn execution_end(&mut self, req_id: i64) -> impl std::future::Future + Send {}
+    /// The callback message containing a single [`ResolvedExecution`], which is delivered instead of an [`Execution`] when [`crate::client::Client::set_execution_resolution`] is enabled.
+    fn resolved_execution(
+        &mut self,
+        req_id: i64,
+        execution: ResolvedExecution,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the [`CommissionReport`] associated with a single [`Execution`].
+    fn commission_report(&mut self, report: CommissionReport) -> impl std::future::Future + Send {}
+    /// The callback message containing the ranked results of a market scanner subscription from [`crate::client::Client::req_scanner_subscription`].
+    fn scanner_data(
+        &mut self,
+        req_id: i64,
+        rows: Vec<scanner::Row>,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message that corresponds to market data whose [`TickType`] has no dedicated callback, along with its raw value.
+    fn other_tick(
+        &mut self,
+        req_id: i64,
+        tick_type: TickType,
+        value: String,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing a single [`payload::OpenOrder`] from [`crate::client::Client::req_open_orders`], [`crate::client::Client::req_all_open_orders`], or [`crate::client::Client::req_auto_open_orders`].
+    fn open_order(&mut self, order: payload::OpenOrder) -> impl std::future::Future + Send {}
+    /// The callback message indicating that bars were dropped from a [`crate::client::Client::req_real_time_bars`] subscription within a trading session. It is sent just before the first bar after the gap.
+    fn missed_bars(
+        &mut self,
+        req_id: i64,
+        missed: payload::MissedBars,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing an error encountered while decoding a message from IBKR, such as a malformed or unrecognized message. The message is discarded.
+    fn decode_error(&mut self, error: crate::error::Error) -> impl std::future::Future + Send {}
+    /// The callback message containing the white branding ID of the user from [`crate::client::Client::req_user_info`].
+    fn user_info(
+        &mut self,
+        req_id: i64,
+        white_branding_id: String,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the exchanges that comprise the SMART aggregate exchange from [`crate::client::Client::req_smart_components`].
+    fn smart_components(
+        &mut self,
+        req_id: i64,
+        components: Vec<payload::SmartComponent>,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message indicating that the connection with the IBKR platform was lost unexpectedly, such as when the platform restarts or the task that reads incoming messages fails. No more messages are received until the client reconnects with [`crate::client::Client::reconnect`].
+    fn connection_lost(&mut self) -> impl std::future::Future + Send {}
+    /// The callback message indicating that the client is connected with the IBKR platform, which is sent once the message loop starts and again whenever the client reconnects with [`crate::client::Client::reconnect`].
+    fn connected(&mut self) -> impl std::future::Future + Send {}
+    /// The callback message indicating that the client closed its connection with the IBKR platform with [`crate::client::Client::disconnect`]. This is the last message that the wrapper receives.
+    fn connection_closed(&mut self) -> impl std::future::Future + Send {}
+    /// The callback message indicating that the IBKR platform lost its connection to IBKR's servers and is attempting to reconnect (error 1100). Trading is not possible until [`Self::reconnected`] is received.
+    fn reconnecting(&mut self) -> impl std::future::Future + Send {}
+    /// The callback message indicating that the IBKR platform restored its connection to IBKR's servers (errors 1101 and 1102). When `data_lost` is [`true`], market data was lost and its subscriptions must be made again.
+    fn reconnected(&mut self, data_lost: bool) -> impl std::future::Future + Send {}
+    /// The callback message containing an [`payload::OrderStatusUpdate`] for an order placed by [`crate::client::Client::req_place_order`], sent whenever the order's status or filled quantity changes.
+    fn order_status(
+        &mut self,
+        status: payload::OrderStatusUpdate,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing a timer scheduled with [`crate::client::Client::schedule`] that has fallen due, along with the time at which it fell due. It runs on the same task as every other callback.
+    fn timer(
+        &mut self,
+        timer_id: crate::schedule::TimerId,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the delta-neutral contract that IBKR validated for a delta-neutral combo order, which may differ from the one with which the order was placed.
+    fn delta_neutral_validation(
+        &mut self,
+        order_id: i64,
+        contract: crate::contract::DeltaNeutralContract,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the contracts that match a pattern from [`crate::client::Client::req_matching_symbols`].
+    fn symbol_samples(
+        &mut self,
+        req_id: i64,
+        samples: Vec<payload::SymbolSample>,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the XML document of a report from [`crate::client::Client::req_fundamental_data`].
+    fn fundamental_data(&mut self, req_id: i64, data: String) -> impl std::future::Future + Send {}
+    /// The callback message containing an IB news bulletin from [`crate::client::Client::req_news_bulletins`].
+    fn news_bulletin(
+        &mut self,
+        bulletin: payload::NewsBulletin,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the body of a news article from [`crate::client::Client::req_news_article`].
+    fn news_article(
+        &mut self,
+        req_id: i64,
+        article: payload::NewsArticle,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing a news headline from a [`crate::client::Client::req_market_data`] subscription to news ticks.
+    fn tick_news(
+        &mut self,
+        req_id: i64,
+        headline: payload::NewsHeadline,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the news providers from [`crate::client::Client::req_news_providers`].
+    fn news_providers(
+        &mut self,
+        providers: Vec<payload::NewsProvider>,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing a single headline from [`crate::client::Client::req_historical_news`].
+    fn historical_news(
+        &mut self,
+        req_id: i64,
+        headline: payload::NewsHeadline,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message indicating that all headlines from [`crate::client::Client::req_historical_news`] have been delivered, and whether more headlines matched the request.
+    fn historical_news_end(
+        &mut self,
+        req_id: i64,
+        has_more: bool,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing account attributes from [`crate::client::Client::req_account_updates_multi`], along with the model code to which they belong (which is empty for an account as a whole).
+    fn account_update_multi(
+        &mut self,
+        req_id: i64,
+        attribute: Attribute,
+        account_number: AccountId,
+        model_code: String,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message indicating that all the account attributes for a given request from [`crate::client::Client::req_account_updates_multi`] have been received.
+    fn account_update_multi_end(&mut self, req_id: i64) -> impl std::future::Future + Send {}
+    /// The callback message containing the IDs of the display groups in TWS, from [`crate::client::Client::query_display_groups`].
+    fn display_group_list(
+        &mut self,
+        req_id: i64,
+        groups: Vec<i64>,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the contract now displayed by a TWS display group (ex: "8314@SMART", or "none"), from [`crate::client::Client::subscribe_to_group_events`].
+    fn display_group_updated(
+        &mut self,
+        req_id: i64,
+        contract_info: String,
+    ) -> impl std::future::Future + Send {
+    }
+    /// The callback message containing the trading sessions of a contract from [`crate::client::Client::req_historical_schedule`].
+    fn historical_schedule(
+        &mut self,
+        req_id: i64,
+        schedule: payload::HistoricalSchedule,
+    ) -> impl std::future::Future + Send {
+    }
 }
 
 pub(crate) mod indicators {